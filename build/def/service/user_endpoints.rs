@@ -377,6 +377,10 @@ impl Order {
                 Field::new("close_price_usd", Type::Numeric),
                 Field::new("volume", Type::Numeric),
                 Field::new("closed_profit", Type::Numeric),
+                Field::new("funding_usd", Type::Numeric),
+                Field::new("closed_volume", Type::Numeric),
+                Field::new("fee_usd", Type::Numeric),
+                Field::new("unrealized_pnl_usd", Type::Numeric),
             ],
         )
     }
@@ -442,6 +446,14 @@ fn encrypted_key() -> Type {
     )
 }
 
+// snapshot data, the stream seq it was taken at, and whether resuming hit a gap in the replay buffer
+fn stream_resume_result(data: Type) -> Vec<Field> {
+    vec![
+        Field::new("data", data),
+        Field::new("seq", Type::BigInt),
+        Field::new("gap", Type::Boolean),
+    ]
+}
 // success and reason if failed
 fn success_result() -> Vec<Field> {
     vec![
@@ -477,6 +489,14 @@ impl Filter {
     fn strategy_id() -> Vec<Field> {
         vec![Field::new("strategy_id", Type::Int)]
     }
+    /// resume a stream after the last received stream_seq, optionally only receiving changed rows
+    fn resume(with_delta: bool) -> Vec<Field> {
+        let mut fields = vec![Field::new("resume_from_seq", Type::optional(Type::BigInt))];
+        if with_delta {
+            fields.push(Field::new("snapshot_then_delta", Type::optional(Type::Boolean)));
+        }
+        fields
+    }
 }
 
 fn get_user_debug_log_list() -> Type {
@@ -505,6 +525,119 @@ fn funding_rates() -> Type {
     )
 }
 
+fn liquidations() -> Type {
+    Type::datatable(
+        "UserLiquidation",
+        vec![
+            Field::new("id", Type::BigInt),
+            Field::new("exchange", Type::String),
+            Field::new("symbol", Type::String),
+            Field::new("side", Type::String),
+            Field::new("price", Type::Numeric),
+            Field::new("size", Type::Numeric),
+            Field::new("datetime", Type::TimeStampMs),
+        ],
+    )
+}
+
+fn candles() -> Type {
+    Type::datatable(
+        "UserCandle",
+        vec![
+            Field::new("exchange", Type::String),
+            Field::new("symbol", Type::String),
+            Field::new("interval", Type::String),
+            Field::new("open", Type::Numeric),
+            Field::new("high", Type::Numeric),
+            Field::new("low", Type::Numeric),
+            Field::new("close", Type::Numeric),
+            Field::new("volume", Type::Numeric),
+            Field::new("datetime", Type::TimeStampMs),
+        ],
+    )
+}
+
+fn funding_history() -> Type {
+    Type::datatable(
+        "UserFundingHistory",
+        vec![
+            Field::new("exchange", Type::String),
+            Field::new("symbol", Type::String),
+            Field::new("rate", Type::Numeric),
+            Field::new("datetime", Type::TimeStampMs),
+        ],
+    )
+}
+
+fn funding_carry() -> Type {
+    Type::datatable(
+        "UserFundingCarry",
+        vec![
+            Field::new("asset", Type::String),
+            Field::new("long_exchange", Type::String),
+            Field::new("short_exchange", Type::String),
+            Field::new("long_rate_annual", Type::Numeric),
+            Field::new("short_rate_annual", Type::Numeric),
+            Field::new("yield_annual", Type::Numeric),
+            Field::new("datetime", Type::TimeStampMs),
+        ],
+    )
+}
+
+fn feed_health() -> Type {
+    Type::datatable(
+        "UserFeedHealth",
+        vec![
+            Field::new("exchange", Type::String),
+            Field::new("symbol", Type::String),
+            Field::new("last_update", Type::TimeStampMs),
+            Field::new("age_ms", Type::BigInt),
+            Field::new("messages_per_second", Type::Numeric),
+            Field::new("stale", Type::Boolean),
+        ],
+    )
+}
+
+fn export_job() -> Type {
+    Type::datatable(
+        "UserExportJob",
+        vec![
+            Field::new("job_id", Type::BigInt),
+            Field::new("dataset", Type::String),
+            Field::new("format", Type::String),
+            Field::new("status", Type::String),
+            Field::new("directory", Type::String),
+            Field::new("steps_done", Type::BigInt),
+            Field::new("steps_total", Type::BigInt),
+            Field::new("rows", Type::BigInt),
+            Field::new("files", Type::BigInt),
+            Field::new("error", Type::optional(Type::String)),
+            Field::new("started_at", Type::TimeStampMs),
+            Field::new("updated_at", Type::TimeStampMs),
+        ],
+    )
+}
+
+fn position_pnl() -> Type {
+    Type::datatable(
+        "UserPositionPnl",
+        vec![
+            Field::new("id", Type::BigInt),
+            Field::new("event_id", Type::BigInt),
+            Field::new("datetime", Type::TimeStampMs),
+            Field::new("update_datetime", Type::TimeStampMs),
+            // exchange:symbol of every leg, comma separated
+            Field::new("legs", Type::String),
+            Field::new("realized_pnl_usd", Type::Numeric),
+            Field::new("unrealized_pnl_usd", Type::Numeric),
+            Field::new("fee_usd", Type::Numeric),
+            Field::new("funding_usd", Type::Numeric),
+            Field::new("total_pnl_usd", Type::Numeric),
+            Field::new("closed", Type::Boolean),
+        ],
+    )
+}
+
 fn user_position_list() -> Type {
     Type::datatable(
         "UserPosition",
@@ -572,6 +705,11 @@ pub fn get_user_endpoints() -> Vec<EndpointSchema> {
             vec![
                 Field::new("status", Type::String),
                 Field::new("time", Type::TimeStampMs),
+                Field::new("active_connections", Type::BigInt),
+                Field::new("rejected_connections", Type::BigInt),
+                Field::new("throttled_requests", Type::BigInt),
+                Field::new("oversized_messages", Type::BigInt),
+                Field::new("rejected_subscriptions", Type::BigInt),
             ],
         ),
         EndpointSchema::new("UserSubLogs", 20010, vec![], vec![]).with_stream_response_type(Type::struct_(
@@ -594,8 +732,11 @@ pub fn get_user_endpoints() -> Vec<EndpointSchema> {
         EndpointSchema::new(
             "UserSubPosition",
             20030,
-            vec![Field::new("unsubscribe", Type::optional(Type::Boolean))],
-            vec![Field::new("data", user_position_list())],
+            concat!(
+                Filter::resume(true),
+                vec![Field::new("unsubscribe", Type::optional(Type::Boolean))]
+            ),
+            stream_resume_result(user_position_list()),
         )
         .with_stream_response_type(user_position_list()),
         EndpointSchema::new(
@@ -607,11 +748,14 @@ pub fn get_user_endpoints() -> Vec<EndpointSchema> {
         EndpointSchema::new(
             "UserSubOrders",
             20040,
-            vec![
-                Field::new("strategy_id", Type::optional(Type::Int)),
-                Field::new("unsubscribe", Type::optional(Type::Boolean)),
-            ],
-            vec![Field::new("data", Order::orders())],
+            concat!(
+                Filter::resume(true),
+                vec![
+                    Field::new("strategy_id", Type::optional(Type::Int)),
+                    Field::new("unsubscribe", Type::optional(Type::Boolean)),
+                ]
+            ),
+            stream_resume_result(Order::orders()),
         )
         .with_stream_response_type(Order::orders()),
         EndpointSchema::new(
@@ -753,8 +897,8 @@ pub fn get_user_endpoints() -> Vec<EndpointSchema> {
         EndpointSchema::new(
             "UserSubEvent1",
             20250,
-            Filter::symbol(false),
-            vec![Field::new("data", Event::event_1())],
+            concat!(Filter::resume(false), Filter::symbol(false)),
+            stream_resume_result(Event::event_1()),
         )
         .with_stream_response_type(Event::event_1()),
         EndpointSchema::new(
@@ -821,8 +965,8 @@ pub fn get_user_endpoints() -> Vec<EndpointSchema> {
         EndpointSchema::new(
             "UserSubLedgerStrategyOne",
             20300,
-            Filter::symbol(false),
-            vec![Field::new("data", Order::ledger())],
+            concat!(Filter::resume(false), Filter::symbol(false)),
+            stream_resume_result(Order::ledger()),
         )
         .with_stream_response_type(Order::ledger()),
         EndpointSchema::new(
@@ -1107,5 +1251,103 @@ pub fn get_user_endpoints() -> Vec<EndpointSchema> {
             vec![Field::new("configuration", user_set_s2_configure())],
             success_result(),
         ),
+        EndpointSchema::new(
+            "UserSubLiquidations",
+            20660,
+            vec![
+                Field::new("exchange", Type::optional(Type::String)),
+                Field::new("symbol", Type::optional(Type::String)),
+                Field::new("unsub", Type::optional(Type::Boolean)),
+            ],
+            vec![Field::new("data", liquidations())],
+        )
+        .with_stream_response_type(liquidations()),
+        EndpointSchema::new(
+            "UserGetCandles",
+            20670,
+            vec![
+                Field::new("exchange", Type::String),
+                Field::new("symbol", Type::String),
+                Field::new("interval", Type::String),
+                Field::new("time_start", Type::optional(Type::TimeStampMs)),
+                Field::new("time_end", Type::optional(Type::TimeStampMs)),
+                Field::new("limit", Type::optional(Type::Int)),
+            ],
+            vec![Field::new("data", candles())],
+        ),
+        EndpointSchema::new(
+            "UserSubCandles",
+            20680,
+            vec![
+                Field::new("exchange", Type::String),
+                Field::new("symbol", Type::String),
+                Field::new("interval", Type::String),
+                Field::new("unsub", Type::optional(Type::Boolean)),
+            ],
+            vec![Field::new("data", candles())],
+        )
+        .with_stream_response_type(candles()),
+        EndpointSchema::new(
+            "UserGetFundingHistory",
+            20690,
+            vec![
+                Field::new("exchange", Type::String),
+                Field::new("symbol", Type::String),
+                Field::new("time_start", Type::optional(Type::TimeStampMs)),
+                Field::new("time_end", Type::optional(Type::TimeStampMs)),
+            ],
+            vec![Field::new("data", funding_history())],
+        ),
+        EndpointSchema::new(
+            "UserGetFundingCarry",
+            20700,
+            vec![Field::new("asset", Type::optional(Type::String))],
+            vec![Field::new("data", funding_carry())],
+        ),
+        EndpointSchema::new(
+            "UserSubFeedHealth",
+            20710,
+            vec![
+                Field::new("exchange", Type::optional(Type::String)),
+                Field::new("unsub", Type::optional(Type::Boolean)),
+            ],
+            vec![Field::new("data", feed_health())],
+        )
+        .with_stream_response_type(feed_health()),
+        EndpointSchema::new(
+            "UserBackupDatabase",
+            20720,
+            vec![Field::new("label", Type::optional(Type::String))],
+            vec![Field::new("path", Type::String), Field::new("size", Type::BigInt)],
+        ),
+        EndpointSchema::new(
+            "UserStartExport",
+            20730,
+            vec![
+                Field::new("dataset", Type::String),
+                Field::new("format", Type::optional(Type::String)),
+                Field::new("time_start", Type::TimeStampMs),
+                Field::new("time_end", Type::TimeStampMs),
+                Field::new("exchange", Type::optional(Type::String)),
+                Field::new("label", Type::optional(Type::String)),
+            ],
+            vec![
+                Field::new("job_id", Type::BigInt),
+                Field::new("directory", Type::String),
+            ],
+        ),
+        EndpointSchema::new(
+            "UserSubExportJobs",
+            20740,
+            vec![Field::new("unsub", Type::optional(Type::Boolean))],
+            vec![Field::new("data", export_job())],
+        )
+        .with_stream_response_type(export_job()),
+        EndpointSchema::new(
+            "UserGetPositionPnl",
+            20750,
+            concat![Filter::time(), Filter::strategy_id()],
+            vec![Field::new("data", position_pnl())],
+        ),
     ]
 }
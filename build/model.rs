@@ -381,6 +381,48 @@ pub enum EnumEndpoint {
     ///
     #[postgres(name = "UserSetS2Configure")]
     UserSetS2Configure = 20650,
+    ///
+    #[postgres(name = "UserSubLiquidations")]
+    UserSubLiquidations = 20660,
+    ///
+    #[postgres(name = "UserGetCandles")]
+    UserGetCandles = 20670,
+    ///
+    #[postgres(name = "UserSubCandles")]
+    UserSubCandles = 20680,
+    ///
+    #[postgres(name = "UserGetFundingHistory")]
+    UserGetFundingHistory = 20690,
+    ///
+    #[postgres(name = "UserGetFundingCarry")]
+    UserGetFundingCarry = 20700,
+    ///
+    #[postgres(name = "UserSubFeedHealth")]
+    UserSubFeedHealth = 20710,
+    ///
+    #[postgres(name = "UserBackupDatabase")]
+    UserBackupDatabase = 20720,
+    ///
+    #[postgres(name = "UserStartExport")]
+    UserStartExport = 20730,
+    ///
+    #[postgres(name = "UserSubExportJobs")]
+    UserSubExportJobs = 20740,
+    ///
+    #[postgres(name = "UserGetPositionPnl")]
+    UserGetPositionPnl = 20750,
+    ///
+    #[postgres(name = "UserGetAccountingHalts")]
+    UserGetAccountingHalts = 20760,
+    ///
+    #[postgres(name = "UserAcknowledgeAccountingHalt")]
+    UserAcknowledgeAccountingHalt = 20770,
+    ///
+    #[postgres(name = "UserGetExecutionAccounts")]
+    UserGetExecutionAccounts = 20780,
+    ///
+    #[postgres(name = "UserSetStrategyAccount")]
+    UserSetStrategyAccount = 20790,
 }
 
 impl EnumEndpoint {
@@ -456,6 +498,20 @@ impl EnumEndpoint {
             }
             Self::UserGet5MinSpreadMean => UserGet5MinSpreadMeanRequest::SCHEMA,
             Self::UserSetS2Configure => UserSetS2ConfigureRequest::SCHEMA,
+            Self::UserSubLiquidations => UserSubLiquidationsRequest::SCHEMA,
+            Self::UserGetCandles => UserGetCandlesRequest::SCHEMA,
+            Self::UserSubCandles => UserSubCandlesRequest::SCHEMA,
+            Self::UserGetFundingHistory => UserGetFundingHistoryRequest::SCHEMA,
+            Self::UserGetFundingCarry => UserGetFundingCarryRequest::SCHEMA,
+            Self::UserSubFeedHealth => UserSubFeedHealthRequest::SCHEMA,
+            Self::UserBackupDatabase => UserBackupDatabaseRequest::SCHEMA,
+            Self::UserStartExport => UserStartExportRequest::SCHEMA,
+            Self::UserSubExportJobs => UserSubExportJobsRequest::SCHEMA,
+            Self::UserGetPositionPnl => UserGetPositionPnlRequest::SCHEMA,
+            Self::UserGetAccountingHalts => UserGetAccountingHaltsRequest::SCHEMA,
+            Self::UserAcknowledgeAccountingHalt => UserAcknowledgeAccountingHaltRequest::SCHEMA,
+            Self::UserGetExecutionAccounts => UserGetExecutionAccountsRequest::SCHEMA,
+            Self::UserSetStrategyAccount => UserSetStrategyAccountRequest::SCHEMA,
         };
         serde_json::from_str(schema).unwrap()
     }
@@ -475,6 +531,9 @@ pub struct ErrorNotImplemented {}
 pub struct ErrorNotFound {}
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
+pub struct ErrorThrottled {}
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct ErrorDatabaseError {}
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -657,6 +716,9 @@ pub enum EnumErrorCode {
     /// Custom NotFoundResource
     #[postgres(name = "NotFound")]
     NotFound = 100404,
+    /// Custom Too many requests
+    #[postgres(name = "Throttled")]
+    Throttled = 100429,
     /// Custom Database error
     #[postgres(name = "DatabaseError")]
     DatabaseError = 100601,
@@ -1080,6 +1142,14 @@ pub struct SubS3TerminalBestAskBestBidResponse {
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
+pub struct UserAccountingHalt {
+    pub exchange: String,
+    pub account: i64,
+    pub reason: String,
+    pub datetime: i64,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct UserAccuracyLog {
     pub datetime: i64,
     pub count_pass: i64,
@@ -1088,6 +1158,17 @@ pub struct UserAccuracyLog {
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
+pub struct UserAcknowledgeAccountingHaltRequest {
+    pub exchange: String,
+    pub account: i64,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserAcknowledgeAccountingHaltResponse {
+    pub reason: String,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct UserAddBlacklistRequest {
     pub strategy_id: i32,
     pub list: Vec<RequestSymbolList>,
@@ -1101,6 +1182,18 @@ pub struct UserAddBlacklistResponse {
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
+pub struct UserBackupDatabaseRequest {
+    #[serde(default)]
+    pub label: Option<String>,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserBackupDatabaseResponse {
+    pub path: String,
+    pub size: i64,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct UserBenchmarkResult {
     pub id: i64,
     pub datetime: i64,
@@ -1121,6 +1214,8 @@ pub struct UserCancelOrderRequest {
     pub exchange: String,
     pub symbol: String,
     pub local_id: String,
+    #[serde(default)]
+    pub account: Option<i64>,
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -1131,6 +1226,19 @@ pub struct UserCancelOrderResponse {
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
+pub struct UserCandle {
+    pub exchange: String,
+    pub symbol: String,
+    pub interval: String,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    pub datetime: i64,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct UserCapturedEvent {
     pub id: i64,
     #[serde(default)]
@@ -1215,6 +1323,59 @@ pub struct UserEvent {
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
+pub struct UserExecutionAccount {
+    pub exchange: String,
+    pub account: i64,
+    pub account_id: String,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserExportJob {
+    pub job_id: i64,
+    pub dataset: String,
+    pub format: String,
+    pub status: String,
+    pub directory: String,
+    pub steps_done: i64,
+    pub steps_total: i64,
+    pub rows: i64,
+    pub files: i64,
+    #[serde(default)]
+    pub error: Option<String>,
+    pub started_at: i64,
+    pub updated_at: i64,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserFeedHealth {
+    pub exchange: String,
+    pub symbol: String,
+    pub last_update: i64,
+    pub age_ms: i64,
+    pub messages_per_second: f64,
+    pub stale: bool,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserFundingCarry {
+    pub asset: String,
+    pub long_exchange: String,
+    pub short_exchange: String,
+    pub long_rate_annual: f64,
+    pub short_rate_annual: f64,
+    pub yield_annual: f64,
+    pub datetime: i64,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserFundingHistory {
+    pub exchange: String,
+    pub symbol: String,
+    pub rate: f64,
+    pub datetime: i64,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct UserFundingRates {
     pub exchange: String,
     pub symbol: String,
@@ -1231,6 +1392,14 @@ pub struct UserGet5MinSpreadMeanResponse {
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
+pub struct UserGetAccountingHaltsRequest {}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserGetAccountingHaltsResponse {
+    pub data: Vec<UserAccountingHalt>,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct UserGetAccuracyRequest {
     #[serde(default)]
     pub symbol: Option<String>,
@@ -1288,6 +1457,24 @@ pub struct UserGetBlacklistResponse {
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
+pub struct UserGetCandlesRequest {
+    pub exchange: String,
+    pub symbol: String,
+    pub interval: String,
+    #[serde(default)]
+    pub time_start: Option<i64>,
+    #[serde(default)]
+    pub time_end: Option<i64>,
+    #[serde(default)]
+    pub limit: Option<i32>,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserGetCandlesResponse {
+    pub data: Vec<UserCandle>,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct UserGetDebugLogRequest {
     #[serde(default)]
     pub limit: Option<i32>,
@@ -1326,6 +1513,41 @@ pub struct UserGetEvent1Response {
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
+pub struct UserGetExecutionAccountsRequest {}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserGetExecutionAccountsResponse {
+    pub accounts: Vec<UserExecutionAccount>,
+    pub assignments: Vec<UserStrategyAccount>,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserGetFundingCarryRequest {
+    #[serde(default)]
+    pub asset: Option<String>,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserGetFundingCarryResponse {
+    pub data: Vec<UserFundingCarry>,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserGetFundingHistoryRequest {
+    pub exchange: String,
+    pub symbol: String,
+    #[serde(default)]
+    pub time_start: Option<i64>,
+    #[serde(default)]
+    pub time_end: Option<i64>,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserGetFundingHistoryResponse {
+    pub data: Vec<UserFundingHistory>,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct UserGetHedgedOrdersRequest {
     pub strategy_id: i32,
 }
@@ -1399,6 +1621,20 @@ pub struct UserGetOrdersPerStrategyResponse {
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
+pub struct UserGetPositionPnlRequest {
+    pub strategy_id: i32,
+    #[serde(default)]
+    pub time_start: Option<i64>,
+    #[serde(default)]
+    pub time_end: Option<i64>,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserGetPositionPnlResponse {
+    pub data: Vec<UserPositionPnl>,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct UserGetPrice0Request {
     #[serde(default)]
     pub time_start: Option<i64>,
@@ -1577,6 +1813,21 @@ pub struct UserLedger {
     pub close_price_usd: f64,
     pub volume: f64,
     pub closed_profit: f64,
+    pub funding_usd: f64,
+    pub closed_volume: f64,
+    pub fee_usd: f64,
+    pub unrealized_pnl_usd: f64,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserLiquidation {
+    pub id: i64,
+    pub exchange: String,
+    pub symbol: String,
+    pub side: String,
+    pub price: f64,
+    pub size: f64,
+    pub datetime: i64,
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -1648,6 +1899,8 @@ pub struct UserPlaceOrderLimitRequest {
     pub price: f64,
     pub size: f64,
     pub local_id: String,
+    #[serde(default)]
+    pub account: Option<i64>,
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -1666,6 +1919,8 @@ pub struct UserPlaceOrderMarketRequest {
     pub price: f64,
     pub size: f64,
     pub local_id: String,
+    #[serde(default)]
+    pub account: Option<i64>,
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -1682,6 +1937,7 @@ pub struct UserPosition {
     #[serde(default)]
     pub cloid: Option<String>,
     pub exchange: String,
+    pub account: i64,
     pub symbol: String,
     pub size: f64,
     pub filled_size: f64,
@@ -1689,6 +1945,21 @@ pub struct UserPosition {
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
+pub struct UserPositionPnl {
+    pub id: i64,
+    pub event_id: i64,
+    pub datetime: i64,
+    pub update_datetime: i64,
+    pub legs: String,
+    pub realized_pnl_usd: f64,
+    pub unrealized_pnl_usd: f64,
+    pub fee_usd: f64,
+    pub funding_usd: f64,
+    pub total_pnl_usd: f64,
+    pub closed: bool,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct UserRemoveBlacklistRequest {
     pub strategy_id: i32,
     pub list: Vec<RequestSymbolList>,
@@ -1760,6 +2031,18 @@ pub struct UserSetS2ConfigureResponse {
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
+pub struct UserSetStrategyAccountRequest {
+    pub strategy_id: i32,
+    pub exchange: String,
+    pub account: i64,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserSetStrategyAccountResponse {
+    pub success: bool,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct UserSetStrategyStatusRequest {
     #[serde(default)]
     pub set_status: Option<Vec<UserStrategyStatus>>,
@@ -1784,6 +2067,25 @@ pub struct UserSetSymbolFlag1Response {
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
+pub struct UserStartExportRequest {
+    pub dataset: String,
+    #[serde(default)]
+    pub format: Option<String>,
+    pub time_start: i64,
+    pub time_end: i64,
+    #[serde(default)]
+    pub exchange: Option<String>,
+    #[serde(default)]
+    pub label: Option<String>,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserStartExportResponse {
+    pub job_id: i64,
+    pub directory: String,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct UserStartServiceRequest {
     pub keys: Vec<UserKey>,
 }
@@ -1802,6 +2104,18 @@ pub struct UserStatusRequest {}
 pub struct UserStatusResponse {
     pub status: String,
     pub time: i64,
+    pub active_connections: i64,
+    pub rejected_connections: i64,
+    pub throttled_requests: i64,
+    pub oversized_messages: i64,
+    pub rejected_subscriptions: i64,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserStrategyAccount {
+    pub strategy_id: i32,
+    pub exchange: String,
+    pub account: i64,
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -1842,14 +2156,32 @@ pub struct UserSubBestBidAskAcrossExchangesWithPositionEventResponse {
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
+pub struct UserSubCandlesRequest {
+    pub exchange: String,
+    pub symbol: String,
+    pub interval: String,
+    #[serde(default)]
+    pub unsub: Option<bool>,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserSubCandlesResponse {
+    pub data: Vec<UserCandle>,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct UserSubEvent1Request {
     #[serde(default)]
     pub symbol: Option<String>,
+    #[serde(default)]
+    pub resume_from_seq: Option<i64>,
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct UserSubEvent1Response {
     pub data: Vec<Event1>,
+    pub seq: i64,
+    pub gap: bool,
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -1876,6 +2208,30 @@ pub struct UserSubExchangeLatencyResponse {
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
+pub struct UserSubExportJobsRequest {
+    #[serde(default)]
+    pub unsub: Option<bool>,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserSubExportJobsResponse {
+    pub data: Vec<UserExportJob>,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserSubFeedHealthRequest {
+    #[serde(default)]
+    pub exchange: Option<String>,
+    #[serde(default)]
+    pub unsub: Option<bool>,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserSubFeedHealthResponse {
+    pub data: Vec<UserFeedHealth>,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct UserSubFundingRatesRequest {
     #[serde(default)]
     pub exchange: Option<String>,
@@ -1906,11 +2262,30 @@ pub struct UserSubLedgerResponse {
 pub struct UserSubLedgerStrategyOneRequest {
     #[serde(default)]
     pub symbol: Option<String>,
+    #[serde(default)]
+    pub resume_from_seq: Option<i64>,
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct UserSubLedgerStrategyOneResponse {
     pub data: Vec<UserLedger>,
+    pub seq: i64,
+    pub gap: bool,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserSubLiquidationsRequest {
+    #[serde(default)]
+    pub exchange: Option<String>,
+    #[serde(default)]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub unsub: Option<bool>,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserSubLiquidationsResponse {
+    pub data: Vec<UserLiquidation>,
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -1925,22 +2300,34 @@ pub struct UserSubOrdersRequest {
     pub strategy_id: Option<i32>,
     #[serde(default)]
     pub unsubscribe: Option<bool>,
+    #[serde(default)]
+    pub resume_from_seq: Option<i64>,
+    #[serde(default)]
+    pub snapshot_then_delta: Option<bool>,
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct UserSubOrdersResponse {
     pub data: Vec<UserOrder>,
+    pub seq: i64,
+    pub gap: bool,
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct UserSubPositionRequest {
     #[serde(default)]
     pub unsubscribe: Option<bool>,
+    #[serde(default)]
+    pub resume_from_seq: Option<i64>,
+    #[serde(default)]
+    pub snapshot_then_delta: Option<bool>,
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct UserSubPositionResponse {
     pub data: Vec<UserPosition>,
+    pub seq: i64,
+    pub gap: bool,
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -2264,6 +2651,26 @@ impl WsRequest for UserStatusRequest {
     {
       "name": "time",
       "ty": "TimeStampMs"
+    },
+    {
+      "name": "active_connections",
+      "ty": "BigInt"
+    },
+    {
+      "name": "rejected_connections",
+      "ty": "BigInt"
+    },
+    {
+      "name": "throttled_requests",
+      "ty": "BigInt"
+    },
+    {
+      "name": "oversized_messages",
+      "ty": "BigInt"
+    },
+    {
+      "name": "rejected_subscriptions",
+      "ty": "BigInt"
     }
   ],
   "stream_response": null,
@@ -2362,6 +2769,18 @@ impl WsRequest for UserSubPositionRequest {
       "ty": {
         "Optional": "Boolean"
       }
+    },
+    {
+      "name": "resume_from_seq",
+      "ty": {
+        "Optional": "BigInt"
+      }
+    },
+    {
+      "name": "snapshot_then_delta",
+      "ty": {
+        "Optional": "Boolean"
+      }
     }
   ],
   "returns": [
@@ -2385,6 +2804,10 @@ impl WsRequest for UserSubPositionRequest {
               "name": "exchange",
               "ty": "String"
             },
+            {
+              "name": "account",
+              "ty": "BigInt"
+            },
             {
               "name": "symbol",
               "ty": "String"
@@ -2404,6 +2827,14 @@ impl WsRequest for UserSubPositionRequest {
           ]
         }
       }
+    },
+    {
+      "name": "seq",
+      "ty": "BigInt"
+    },
+    {
+      "name": "gap",
+      "ty": "Boolean"
     }
   ],
   "stream_response": {
@@ -2424,6 +2855,10 @@ impl WsRequest for UserSubPositionRequest {
           "name": "exchange",
           "ty": "String"
         },
+        {
+          "name": "account",
+          "ty": "BigInt"
+        },
         {
           "name": "symbol",
           "ty": "String"
@@ -2491,9 +2926,21 @@ impl WsRequest for UserSubOrdersRequest {
       "ty": {
         "Optional": "Boolean"
       }
-    }
-  ],
-  "returns": [
+    },
+    {
+      "name": "resume_from_seq",
+      "ty": {
+        "Optional": "BigInt"
+      }
+    },
+    {
+      "name": "snapshot_then_delta",
+      "ty": {
+        "Optional": "Boolean"
+      }
+    }
+  ],
+  "returns": [
     {
       "name": "data",
       "ty": {
@@ -2555,6 +3002,14 @@ impl WsRequest for UserSubOrdersRequest {
           ]
         }
       }
+    },
+    {
+      "name": "seq",
+      "ty": "BigInt"
+    },
+    {
+      "name": "gap",
+      "ty": "Boolean"
     }
   ],
   "stream_response": {
@@ -3555,6 +4010,12 @@ impl WsRequest for UserSubEvent1Request {
       "ty": {
         "Optional": "String"
       }
+    },
+    {
+      "name": "resume_from_seq",
+      "ty": {
+        "Optional": "BigInt"
+      }
     }
   ],
   "returns": [
@@ -3603,6 +4064,14 @@ impl WsRequest for UserSubEvent1Request {
           ]
         }
       }
+    },
+    {
+      "name": "seq",
+      "ty": "BigInt"
+    },
+    {
+      "name": "gap",
+      "ty": "Boolean"
     }
   ],
   "stream_response": {
@@ -4087,6 +4556,22 @@ impl WsRequest for UserGetLedgerRequest {
             {
               "name": "closed_profit",
               "ty": "Numeric"
+            },
+            {
+              "name": "funding_usd",
+              "ty": "Numeric"
+            },
+            {
+              "name": "closed_volume",
+              "ty": "Numeric"
+            },
+            {
+              "name": "fee_usd",
+              "ty": "Numeric"
+            },
+            {
+              "name": "unrealized_pnl_usd",
+              "ty": "Numeric"
             }
           ]
         }
@@ -4207,6 +4692,12 @@ impl WsRequest for UserSubLedgerStrategyOneRequest {
       "ty": {
         "Optional": "String"
       }
+    },
+    {
+      "name": "resume_from_seq",
+      "ty": {
+        "Optional": "BigInt"
+      }
     }
   ],
   "returns": [
@@ -4271,10 +4762,34 @@ impl WsRequest for UserSubLedgerStrategyOneRequest {
             {
               "name": "closed_profit",
               "ty": "Numeric"
+            },
+            {
+              "name": "funding_usd",
+              "ty": "Numeric"
+            },
+            {
+              "name": "closed_volume",
+              "ty": "Numeric"
+            },
+            {
+              "name": "fee_usd",
+              "ty": "Numeric"
+            },
+            {
+              "name": "unrealized_pnl_usd",
+              "ty": "Numeric"
             }
           ]
         }
       }
+    },
+    {
+      "name": "seq",
+      "ty": "BigInt"
+    },
+    {
+      "name": "gap",
+      "ty": "Boolean"
     }
   ],
   "stream_response": {
@@ -4336,6 +4851,22 @@ impl WsRequest for UserSubLedgerStrategyOneRequest {
         {
           "name": "closed_profit",
           "ty": "Numeric"
+        },
+        {
+          "name": "funding_usd",
+          "ty": "Numeric"
+        },
+        {
+          "name": "closed_volume",
+          "ty": "Numeric"
+        },
+        {
+          "name": "fee_usd",
+          "ty": "Numeric"
+        },
+        {
+          "name": "unrealized_pnl_usd",
+          "ty": "Numeric"
         }
       ]
     }
@@ -4428,6 +4959,22 @@ impl WsRequest for UserSubLedgerRequest {
             {
               "name": "closed_profit",
               "ty": "Numeric"
+            },
+            {
+              "name": "funding_usd",
+              "ty": "Numeric"
+            },
+            {
+              "name": "closed_volume",
+              "ty": "Numeric"
+            },
+            {
+              "name": "fee_usd",
+              "ty": "Numeric"
+            },
+            {
+              "name": "unrealized_pnl_usd",
+              "ty": "Numeric"
             }
           ]
         }
@@ -4493,6 +5040,22 @@ impl WsRequest for UserSubLedgerRequest {
         {
           "name": "closed_profit",
           "ty": "Numeric"
+        },
+        {
+          "name": "funding_usd",
+          "ty": "Numeric"
+        },
+        {
+          "name": "closed_volume",
+          "ty": "Numeric"
+        },
+        {
+          "name": "fee_usd",
+          "ty": "Numeric"
+        },
+        {
+          "name": "unrealized_pnl_usd",
+          "ty": "Numeric"
         }
       ]
     }
@@ -6215,6 +6778,12 @@ impl WsRequest for UserPlaceOrderMarketRequest {
     {
       "name": "local_id",
       "ty": "String"
+    },
+    {
+      "name": "account",
+      "ty": {
+        "Optional": "BigInt"
+      }
     }
   ],
   "returns": [
@@ -6274,6 +6843,12 @@ impl WsRequest for UserPlaceOrderLimitRequest {
     {
       "name": "local_id",
       "ty": "String"
+    },
+    {
+      "name": "account",
+      "ty": {
+        "Optional": "BigInt"
+      }
     }
   ],
   "returns": [
@@ -6665,6 +7240,12 @@ impl WsRequest for UserCancelOrderRequest {
     {
       "name": "local_id",
       "ty": "String"
+    },
+    {
+      "name": "account",
+      "ty": {
+        "Optional": "BigInt"
+      }
     }
   ],
   "returns": [
@@ -7340,3 +7921,1047 @@ impl WsRequest for UserSetS2ConfigureRequest {
 impl WsResponse for UserSetS2ConfigureResponse {
     type Request = UserSetS2ConfigureRequest;
 }
+
+impl WsRequest for UserSubLiquidationsRequest {
+    type Response = UserSubLiquidationsResponse;
+    const METHOD_ID: u32 = 20660;
+    const SCHEMA: &'static str = r#"{
+  "name": "UserSubLiquidations",
+  "code": 20660,
+  "parameters": [
+    {
+      "name": "exchange",
+      "ty": {
+        "Optional": "String"
+      }
+    },
+    {
+      "name": "symbol",
+      "ty": {
+        "Optional": "String"
+      }
+    },
+    {
+      "name": "unsub",
+      "ty": {
+        "Optional": "Boolean"
+      }
+    }
+  ],
+  "returns": [
+    {
+      "name": "data",
+      "ty": {
+        "DataTable": {
+          "name": "UserLiquidation",
+          "fields": [
+            {
+              "name": "id",
+              "ty": "BigInt"
+            },
+            {
+              "name": "exchange",
+              "ty": "String"
+            },
+            {
+              "name": "symbol",
+              "ty": "String"
+            },
+            {
+              "name": "side",
+              "ty": "String"
+            },
+            {
+              "name": "price",
+              "ty": "Numeric"
+            },
+            {
+              "name": "size",
+              "ty": "Numeric"
+            },
+            {
+              "name": "datetime",
+              "ty": "TimeStampMs"
+            }
+          ]
+        }
+      }
+    }
+  ],
+  "stream_response": {
+    "DataTable": {
+      "name": "UserLiquidation",
+      "fields": [
+        {
+          "name": "id",
+          "ty": "BigInt"
+        },
+        {
+          "name": "exchange",
+          "ty": "String"
+        },
+        {
+          "name": "symbol",
+          "ty": "String"
+        },
+        {
+          "name": "side",
+          "ty": "String"
+        },
+        {
+          "name": "price",
+          "ty": "Numeric"
+        },
+        {
+          "name": "size",
+          "ty": "Numeric"
+        },
+        {
+          "name": "datetime",
+          "ty": "TimeStampMs"
+        }
+      ]
+    }
+  },
+  "description": "",
+  "json_schema": null
+}"#;
+}
+impl WsResponse for UserSubLiquidationsResponse {
+    type Request = UserSubLiquidationsRequest;
+}
+
+impl WsRequest for UserGetCandlesRequest {
+    type Response = UserGetCandlesResponse;
+    const METHOD_ID: u32 = 20670;
+    const SCHEMA: &'static str = r#"{
+  "name": "UserGetCandles",
+  "code": 20670,
+  "parameters": [
+    {
+      "name": "exchange",
+      "ty": "String"
+    },
+    {
+      "name": "symbol",
+      "ty": "String"
+    },
+    {
+      "name": "interval",
+      "ty": "String"
+    },
+    {
+      "name": "time_start",
+      "ty": {
+        "Optional": "TimeStampMs"
+      }
+    },
+    {
+      "name": "time_end",
+      "ty": {
+        "Optional": "TimeStampMs"
+      }
+    },
+    {
+      "name": "limit",
+      "ty": {
+        "Optional": "Int"
+      }
+    }
+  ],
+  "returns": [
+    {
+      "name": "data",
+      "ty": {
+        "DataTable": {
+          "name": "UserCandle",
+          "fields": [
+            {
+              "name": "exchange",
+              "ty": "String"
+            },
+            {
+              "name": "symbol",
+              "ty": "String"
+            },
+            {
+              "name": "interval",
+              "ty": "String"
+            },
+            {
+              "name": "open",
+              "ty": "Numeric"
+            },
+            {
+              "name": "high",
+              "ty": "Numeric"
+            },
+            {
+              "name": "low",
+              "ty": "Numeric"
+            },
+            {
+              "name": "close",
+              "ty": "Numeric"
+            },
+            {
+              "name": "volume",
+              "ty": "Numeric"
+            },
+            {
+              "name": "datetime",
+              "ty": "TimeStampMs"
+            }
+          ]
+        }
+      }
+    }
+  ],
+  "stream_response": null,
+  "description": "",
+  "json_schema": null
+}"#;
+}
+impl WsResponse for UserGetCandlesResponse {
+    type Request = UserGetCandlesRequest;
+}
+
+impl WsRequest for UserSubCandlesRequest {
+    type Response = UserSubCandlesResponse;
+    const METHOD_ID: u32 = 20680;
+    const SCHEMA: &'static str = r#"{
+  "name": "UserSubCandles",
+  "code": 20680,
+  "parameters": [
+    {
+      "name": "exchange",
+      "ty": "String"
+    },
+    {
+      "name": "symbol",
+      "ty": "String"
+    },
+    {
+      "name": "interval",
+      "ty": "String"
+    },
+    {
+      "name": "unsub",
+      "ty": {
+        "Optional": "Boolean"
+      }
+    }
+  ],
+  "returns": [
+    {
+      "name": "data",
+      "ty": {
+        "DataTable": {
+          "name": "UserCandle",
+          "fields": [
+            {
+              "name": "exchange",
+              "ty": "String"
+            },
+            {
+              "name": "symbol",
+              "ty": "String"
+            },
+            {
+              "name": "interval",
+              "ty": "String"
+            },
+            {
+              "name": "open",
+              "ty": "Numeric"
+            },
+            {
+              "name": "high",
+              "ty": "Numeric"
+            },
+            {
+              "name": "low",
+              "ty": "Numeric"
+            },
+            {
+              "name": "close",
+              "ty": "Numeric"
+            },
+            {
+              "name": "volume",
+              "ty": "Numeric"
+            },
+            {
+              "name": "datetime",
+              "ty": "TimeStampMs"
+            }
+          ]
+        }
+      }
+    }
+  ],
+  "stream_response": {
+    "DataTable": {
+      "name": "UserCandle",
+      "fields": [
+        {
+          "name": "exchange",
+          "ty": "String"
+        },
+        {
+          "name": "symbol",
+          "ty": "String"
+        },
+        {
+          "name": "interval",
+          "ty": "String"
+        },
+        {
+          "name": "open",
+          "ty": "Numeric"
+        },
+        {
+          "name": "high",
+          "ty": "Numeric"
+        },
+        {
+          "name": "low",
+          "ty": "Numeric"
+        },
+        {
+          "name": "close",
+          "ty": "Numeric"
+        },
+        {
+          "name": "volume",
+          "ty": "Numeric"
+        },
+        {
+          "name": "datetime",
+          "ty": "TimeStampMs"
+        }
+      ]
+    }
+  },
+  "description": "",
+  "json_schema": null
+}"#;
+}
+impl WsResponse for UserSubCandlesResponse {
+    type Request = UserSubCandlesRequest;
+}
+
+impl WsRequest for UserGetFundingHistoryRequest {
+    type Response = UserGetFundingHistoryResponse;
+    const METHOD_ID: u32 = 20690;
+    const SCHEMA: &'static str = r#"{
+  "name": "UserGetFundingHistory",
+  "code": 20690,
+  "parameters": [
+    {
+      "name": "exchange",
+      "ty": "String"
+    },
+    {
+      "name": "symbol",
+      "ty": "String"
+    },
+    {
+      "name": "time_start",
+      "ty": {
+        "Optional": "TimeStampMs"
+      }
+    },
+    {
+      "name": "time_end",
+      "ty": {
+        "Optional": "TimeStampMs"
+      }
+    }
+  ],
+  "returns": [
+    {
+      "name": "data",
+      "ty": {
+        "DataTable": {
+          "name": "UserFundingHistory",
+          "fields": [
+            {
+              "name": "exchange",
+              "ty": "String"
+            },
+            {
+              "name": "symbol",
+              "ty": "String"
+            },
+            {
+              "name": "rate",
+              "ty": "Numeric"
+            },
+            {
+              "name": "datetime",
+              "ty": "TimeStampMs"
+            }
+          ]
+        }
+      }
+    }
+  ],
+  "stream_response": null,
+  "description": "",
+  "json_schema": null
+}"#;
+}
+impl WsResponse for UserGetFundingHistoryResponse {
+    type Request = UserGetFundingHistoryRequest;
+}
+
+impl WsRequest for UserGetFundingCarryRequest {
+    type Response = UserGetFundingCarryResponse;
+    const METHOD_ID: u32 = 20700;
+    const SCHEMA: &'static str = r#"{
+  "name": "UserGetFundingCarry",
+  "code": 20700,
+  "parameters": [
+    {
+      "name": "asset",
+      "ty": {
+        "Optional": "String"
+      }
+    }
+  ],
+  "returns": [
+    {
+      "name": "data",
+      "ty": {
+        "DataTable": {
+          "name": "UserFundingCarry",
+          "fields": [
+            {
+              "name": "asset",
+              "ty": "String"
+            },
+            {
+              "name": "long_exchange",
+              "ty": "String"
+            },
+            {
+              "name": "short_exchange",
+              "ty": "String"
+            },
+            {
+              "name": "long_rate_annual",
+              "ty": "Numeric"
+            },
+            {
+              "name": "short_rate_annual",
+              "ty": "Numeric"
+            },
+            {
+              "name": "yield_annual",
+              "ty": "Numeric"
+            },
+            {
+              "name": "datetime",
+              "ty": "TimeStampMs"
+            }
+          ]
+        }
+      }
+    }
+  ],
+  "stream_response": null,
+  "description": "",
+  "json_schema": null
+}"#;
+}
+impl WsResponse for UserGetFundingCarryResponse {
+    type Request = UserGetFundingCarryRequest;
+}
+
+impl WsRequest for UserSubFeedHealthRequest {
+    type Response = UserSubFeedHealthResponse;
+    const METHOD_ID: u32 = 20710;
+    const SCHEMA: &'static str = r#"{
+  "name": "UserSubFeedHealth",
+  "code": 20710,
+  "parameters": [
+    {
+      "name": "exchange",
+      "ty": {
+        "Optional": "String"
+      }
+    },
+    {
+      "name": "unsub",
+      "ty": {
+        "Optional": "Boolean"
+      }
+    }
+  ],
+  "returns": [
+    {
+      "name": "data",
+      "ty": {
+        "DataTable": {
+          "name": "UserFeedHealth",
+          "fields": [
+            {
+              "name": "exchange",
+              "ty": "String"
+            },
+            {
+              "name": "symbol",
+              "ty": "String"
+            },
+            {
+              "name": "last_update",
+              "ty": "TimeStampMs"
+            },
+            {
+              "name": "age_ms",
+              "ty": "BigInt"
+            },
+            {
+              "name": "messages_per_second",
+              "ty": "Numeric"
+            },
+            {
+              "name": "stale",
+              "ty": "Boolean"
+            }
+          ]
+        }
+      }
+    }
+  ],
+  "stream_response": {
+    "DataTable": {
+      "name": "UserFeedHealth",
+      "fields": [
+        {
+          "name": "exchange",
+          "ty": "String"
+        },
+        {
+          "name": "symbol",
+          "ty": "String"
+        },
+        {
+          "name": "last_update",
+          "ty": "TimeStampMs"
+        },
+        {
+          "name": "age_ms",
+          "ty": "BigInt"
+        },
+        {
+          "name": "messages_per_second",
+          "ty": "Numeric"
+        },
+        {
+          "name": "stale",
+          "ty": "Boolean"
+        }
+      ]
+    }
+  },
+  "description": "",
+  "json_schema": null
+}"#;
+}
+impl WsResponse for UserSubFeedHealthResponse {
+    type Request = UserSubFeedHealthRequest;
+}
+
+impl WsRequest for UserBackupDatabaseRequest {
+    type Response = UserBackupDatabaseResponse;
+    const METHOD_ID: u32 = 20720;
+    const SCHEMA: &'static str = r#"{
+  "name": "UserBackupDatabase",
+  "code": 20720,
+  "parameters": [
+    {
+      "name": "label",
+      "ty": {
+        "Optional": "String"
+      }
+    }
+  ],
+  "returns": [
+    {
+      "name": "path",
+      "ty": "String"
+    },
+    {
+      "name": "size",
+      "ty": "BigInt"
+    }
+  ],
+  "stream_response": null,
+  "description": "",
+  "json_schema": null
+}"#;
+}
+impl WsResponse for UserBackupDatabaseResponse {
+    type Request = UserBackupDatabaseRequest;
+}
+
+impl WsRequest for UserStartExportRequest {
+    type Response = UserStartExportResponse;
+    const METHOD_ID: u32 = 20730;
+    const SCHEMA: &'static str = r#"{
+  "name": "UserStartExport",
+  "code": 20730,
+  "parameters": [
+    {
+      "name": "dataset",
+      "ty": "String"
+    },
+    {
+      "name": "format",
+      "ty": {
+        "Optional": "String"
+      }
+    },
+    {
+      "name": "time_start",
+      "ty": "TimeStampMs"
+    },
+    {
+      "name": "time_end",
+      "ty": "TimeStampMs"
+    },
+    {
+      "name": "exchange",
+      "ty": {
+        "Optional": "String"
+      }
+    },
+    {
+      "name": "label",
+      "ty": {
+        "Optional": "String"
+      }
+    }
+  ],
+  "returns": [
+    {
+      "name": "job_id",
+      "ty": "BigInt"
+    },
+    {
+      "name": "directory",
+      "ty": "String"
+    }
+  ],
+  "stream_response": null,
+  "description": "",
+  "json_schema": null
+}"#;
+}
+impl WsResponse for UserStartExportResponse {
+    type Request = UserStartExportRequest;
+}
+
+impl WsRequest for UserSubExportJobsRequest {
+    type Response = UserSubExportJobsResponse;
+    const METHOD_ID: u32 = 20740;
+    const SCHEMA: &'static str = r#"{
+  "name": "UserSubExportJobs",
+  "code": 20740,
+  "parameters": [
+    {
+      "name": "unsub",
+      "ty": {
+        "Optional": "Boolean"
+      }
+    }
+  ],
+  "returns": [
+    {
+      "name": "data",
+      "ty": {
+        "DataTable": {
+          "name": "UserExportJob",
+          "fields": [
+            {
+              "name": "job_id",
+              "ty": "BigInt"
+            },
+            {
+              "name": "dataset",
+              "ty": "String"
+            },
+            {
+              "name": "format",
+              "ty": "String"
+            },
+            {
+              "name": "status",
+              "ty": "String"
+            },
+            {
+              "name": "directory",
+              "ty": "String"
+            },
+            {
+              "name": "steps_done",
+              "ty": "BigInt"
+            },
+            {
+              "name": "steps_total",
+              "ty": "BigInt"
+            },
+            {
+              "name": "rows",
+              "ty": "BigInt"
+            },
+            {
+              "name": "files",
+              "ty": "BigInt"
+            },
+            {
+              "name": "error",
+              "ty": {
+                "Optional": "String"
+              }
+            },
+            {
+              "name": "started_at",
+              "ty": "TimeStampMs"
+            },
+            {
+              "name": "updated_at",
+              "ty": "TimeStampMs"
+            }
+          ]
+        }
+      }
+    }
+  ],
+  "stream_response": {
+    "DataTable": {
+      "name": "UserExportJob",
+      "fields": [
+        {
+          "name": "job_id",
+          "ty": "BigInt"
+        },
+        {
+          "name": "dataset",
+          "ty": "String"
+        },
+        {
+          "name": "format",
+          "ty": "String"
+        },
+        {
+          "name": "status",
+          "ty": "String"
+        },
+        {
+          "name": "directory",
+          "ty": "String"
+        },
+        {
+          "name": "steps_done",
+          "ty": "BigInt"
+        },
+        {
+          "name": "steps_total",
+          "ty": "BigInt"
+        },
+        {
+          "name": "rows",
+          "ty": "BigInt"
+        },
+        {
+          "name": "files",
+          "ty": "BigInt"
+        },
+        {
+          "name": "error",
+          "ty": {
+            "Optional": "String"
+          }
+        },
+        {
+          "name": "started_at",
+          "ty": "TimeStampMs"
+        },
+        {
+          "name": "updated_at",
+          "ty": "TimeStampMs"
+        }
+      ]
+    }
+  },
+  "description": "",
+  "json_schema": null
+}"#;
+}
+impl WsResponse for UserSubExportJobsResponse {
+    type Request = UserSubExportJobsRequest;
+}
+
+impl WsRequest for UserGetPositionPnlRequest {
+    type Response = UserGetPositionPnlResponse;
+    const METHOD_ID: u32 = 20750;
+    const SCHEMA: &'static str = r#"{
+  "name": "UserGetPositionPnl",
+  "code": 20750,
+  "parameters": [
+    {
+      "name": "strategy_id",
+      "ty": "Int"
+    },
+    {
+      "name": "time_start",
+      "ty": {
+        "Optional": "TimeStampMs"
+      }
+    },
+    {
+      "name": "time_end",
+      "ty": {
+        "Optional": "TimeStampMs"
+      }
+    }
+  ],
+  "returns": [
+    {
+      "name": "data",
+      "ty": {
+        "DataTable": {
+          "name": "UserPositionPnl",
+          "fields": [
+            {
+              "name": "id",
+              "ty": "BigInt"
+            },
+            {
+              "name": "event_id",
+              "ty": "BigInt"
+            },
+            {
+              "name": "datetime",
+              "ty": "TimeStampMs"
+            },
+            {
+              "name": "update_datetime",
+              "ty": "TimeStampMs"
+            },
+            {
+              "name": "legs",
+              "ty": "String"
+            },
+            {
+              "name": "realized_pnl_usd",
+              "ty": "Numeric"
+            },
+            {
+              "name": "unrealized_pnl_usd",
+              "ty": "Numeric"
+            },
+            {
+              "name": "fee_usd",
+              "ty": "Numeric"
+            },
+            {
+              "name": "funding_usd",
+              "ty": "Numeric"
+            },
+            {
+              "name": "total_pnl_usd",
+              "ty": "Numeric"
+            },
+            {
+              "name": "closed",
+              "ty": "Boolean"
+            }
+          ]
+        }
+      }
+    }
+  ],
+  "stream_response": null,
+  "description": "",
+  "json_schema": null
+}"#;
+}
+impl WsResponse for UserGetPositionPnlResponse {
+    type Request = UserGetPositionPnlRequest;
+}
+
+impl WsRequest for UserGetAccountingHaltsRequest {
+    type Response = UserGetAccountingHaltsResponse;
+    const METHOD_ID: u32 = 20760;
+    const SCHEMA: &'static str = r#"{
+  "name": "UserGetAccountingHalts",
+  "code": 20760,
+  "parameters": [],
+  "returns": [
+    {
+      "name": "data",
+      "ty": {
+        "DataTable": {
+          "name": "UserAccountingHalt",
+          "fields": [
+            {
+              "name": "exchange",
+              "ty": "String"
+            },
+            {
+              "name": "account",
+              "ty": "BigInt"
+            },
+            {
+              "name": "reason",
+              "ty": "String"
+            },
+            {
+              "name": "datetime",
+              "ty": "TimeStampMs"
+            }
+          ]
+        }
+      }
+    }
+  ],
+  "stream_response": null,
+  "description": "",
+  "json_schema": null
+}"#;
+}
+impl WsResponse for UserGetAccountingHaltsResponse {
+    type Request = UserGetAccountingHaltsRequest;
+}
+
+impl WsRequest for UserAcknowledgeAccountingHaltRequest {
+    type Response = UserAcknowledgeAccountingHaltResponse;
+    const METHOD_ID: u32 = 20770;
+    const SCHEMA: &'static str = r#"{
+  "name": "UserAcknowledgeAccountingHalt",
+  "code": 20770,
+  "parameters": [
+    {
+      "name": "exchange",
+      "ty": "String"
+    },
+    {
+      "name": "account",
+      "ty": "BigInt"
+    }
+  ],
+  "returns": [
+    {
+      "name": "reason",
+      "ty": "String"
+    }
+  ],
+  "stream_response": null,
+  "description": "",
+  "json_schema": null
+}"#;
+}
+impl WsResponse for UserAcknowledgeAccountingHaltResponse {
+    type Request = UserAcknowledgeAccountingHaltRequest;
+}
+
+impl WsRequest for UserGetExecutionAccountsRequest {
+    type Response = UserGetExecutionAccountsResponse;
+    const METHOD_ID: u32 = 20780;
+    const SCHEMA: &'static str = r#"{
+  "name": "UserGetExecutionAccounts",
+  "code": 20780,
+  "parameters": [],
+  "returns": [
+    {
+      "name": "accounts",
+      "ty": {
+        "DataTable": {
+          "name": "UserExecutionAccount",
+          "fields": [
+            {
+              "name": "exchange",
+              "ty": "String"
+            },
+            {
+              "name": "account",
+              "ty": "BigInt"
+            },
+            {
+              "name": "account_id",
+              "ty": "String"
+            }
+          ]
+        }
+      }
+    },
+    {
+      "name": "assignments",
+      "ty": {
+        "DataTable": {
+          "name": "UserStrategyAccount",
+          "fields": [
+            {
+              "name": "strategy_id",
+              "ty": "Int"
+            },
+            {
+              "name": "exchange",
+              "ty": "String"
+            },
+            {
+              "name": "account",
+              "ty": "BigInt"
+            }
+          ]
+        }
+      }
+    }
+  ],
+  "stream_response": null,
+  "description": "",
+  "json_schema": null
+}"#;
+}
+impl WsResponse for UserGetExecutionAccountsResponse {
+    type Request = UserGetExecutionAccountsRequest;
+}
+
+impl WsRequest for UserSetStrategyAccountRequest {
+    type Response = UserSetStrategyAccountResponse;
+    const METHOD_ID: u32 = 20790;
+    const SCHEMA: &'static str = r#"{
+  "name": "UserSetStrategyAccount",
+  "code": 20790,
+  "parameters": [
+    {
+      "name": "strategy_id",
+      "ty": "Int"
+    },
+    {
+      "name": "exchange",
+      "ty": "String"
+    },
+    {
+      "name": "account",
+      "ty": "BigInt"
+    }
+  ],
+  "returns": [
+    {
+      "name": "success",
+      "ty": "Boolean"
+    }
+  ],
+  "stream_response": null,
+  "description": "",
+  "json_schema": null
+}"#;
+}
+impl WsResponse for UserSetStrategyAccountResponse {
+    type Request = UserSetStrategyAccountRequest;
+}
@@ -5,12 +5,20 @@ use trading_model::{DeFiTrade, Exchange, InstrumentCode, Quantity};
 
 use crate::model::{AccountId, FundingPayment, OrderLid, OrderTrade, Portfolio};
 
-#[derive(Debug, Clone, Copy, PartialEq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Hash, Serialize, Deserialize)]
 pub struct SourceStatus {
     pub alive: bool,
     pub initial_positions: bool,
+    /// Set by accounting on an inconsistency. Trading on the source stays
+    /// halted until a human has acknowledged it.
+    pub halt_reason: Option<String>,
 }
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+impl SourceStatus {
+    pub fn is_halted(&self) -> bool {
+        self.halt_reason.is_some()
+    }
+}
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct UpdateBook {
     pub account: AccountId,
 
@@ -2,6 +2,8 @@ use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 use trading_model::{Asset, InstrumentCode, Quantity, Time};
 
+use crate::model::AccountId;
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct FundingLid(pub String);
 
@@ -25,6 +27,8 @@ impl From<&FundingLid> for FundingLid {
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FundingPayment {
+    #[serde(default)]
+    pub account: AccountId,
     pub instrument: InstrumentCode,
     pub source_timestamp: Time,
     pub funding_lid: FundingLid,
@@ -7,7 +7,7 @@
 //! that it then processes incrementally. It can then flag errors or missing
 //! data that the exchange integration can fetch to fill in the gaps. It also
 //! has the ability to stop the source from processing any more trades until a
-//! human has intervened (restarted) the source.
+//! human has intervened (acknowledged the halt of) the source.
 
 use std::fmt::Debug;
 
@@ -17,14 +17,20 @@ use crate::model::{
     AccountId, AccountingUpdateOrder, FundingLid, FundingPayment, OrderLid, OrderTrade, SourceStatus, TradeLid,
     UpdateBook, UpdatePositions,
 };
+use float_eq::float_eq;
 use hashbrown::hash_map::{Entry, EntryRef};
 use hashbrown::{HashMap, HashSet};
-use tracing::warn;
+use tracing::{debug, warn};
 use trading_model::{AssetUniversal, Exchange, InstrumentCode, InstrumentType, Quantity, QuantityUnit, Side, Time};
 
 type PositionDelta = (InstrumentCode, f64);
 type PositionDeltas = [Option<PositionDelta>; 3];
 
+/// A position that differs from the venue for this long after the first
+/// mismatching snapshot is an inconsistency. Shorter gaps are messages still in
+/// flight.
+const POSITION_DESYNC_GRACE_SECS: i64 = 10;
+
 #[derive(Debug, Default)]
 struct UpdateDeltas {
     position_deltas: PositionDeltas,
@@ -73,6 +79,14 @@ pub struct SourceAccount {
 
     // Settled.
     pub(crate) settled_orders: HashMap<OrderLid, OrderState>,
+
+    // Consistency.
+    /// The next snapshot replaces the positions instead of being checked
+    /// against them.
+    pub(crate) rebase: bool,
+    pub(crate) position_mismatches: HashMap<InstrumentCode, Time>,
+    pub(crate) halt_reason: Option<String>,
+    pub(crate) status_changed: bool,
 }
 
 impl SourceAccount {
@@ -93,6 +107,11 @@ impl SourceAccount {
             volatile_trades: HashMap::new(),
 
             settled_orders: HashMap::new(),
+
+            rebase: true,
+            position_mismatches: HashMap::new(),
+            halt_reason: None,
+            status_changed: false,
         }
     }
     pub fn empty_no_desync(exchange: Exchange, cleanup_time: Time) -> Self {
@@ -112,6 +131,11 @@ impl SourceAccount {
             volatile_trades: HashMap::new(),
 
             settled_orders: HashMap::new(),
+
+            rebase: true,
+            position_mismatches: HashMap::new(),
+            halt_reason: None,
+            status_changed: false,
         }
     }
     /// The snapshot must enforce the following invariants to ensure a
@@ -136,43 +160,44 @@ impl SourceAccount {
     /// to show up as settled and will be able to detect such gaps. It is of
     /// course preferable that the exchange sequence message such that gaps
     /// can be caught by accounting itself.
-
+    ///
+    /// Only the first snapshot, and the first one after a halt has been
+    /// acknowledged, is loaded as the starting state. Later snapshots are
+    /// checked against the positions built from the incremental feeds.
     pub fn load_snapshot(&mut self, snapshot: &UpdatePositions) -> UpdateBook {
-        // allows for manual intervention to be detected
+        let historical_trades = if self.rebase {
+            self.rebase = false;
 
-        // assert!(
-        //     self.snapshot_time.is_none(),
-        //     "Resetting to snapshot not supported"
-        // );
+            // Write state from snapshot.
+            self.snapshot_time = Some(snapshot.exchange_time);
 
-        // Write state from snapshot.
-        self.snapshot_time = Some(snapshot.exchange_time);
+            snapshot.update_position_values(&mut self.positions);
 
-        snapshot.update_position_values(&mut self.positions);
+            // Extract historical trades.
+            let historical_trades: Vec<_> = self
+                .volatile_orders
+                .values()
+                .flat_map(|order| order.trades.values().cloned())
+                .collect();
 
-        // Extract historical trades.
-        let historical_trades: Vec<_> = self
-            .volatile_orders
-            .values()
-            .flat_map(|order| order.trades.values().cloned())
-            .collect();
+            // Init trades to avoid duplicate dissemination.
+            self.trades.extend(
+                historical_trades
+                    .iter()
+                    .map(|trade| (trade.trade_lid.clone(), trade.clone())),
+            );
 
-        // Init trades to avoid duplicate dissemination.
-        self.trades.extend(
             historical_trades
-                .iter()
-                .map(|trade| (trade.trade_lid.clone(), trade.clone())),
-        );
+        } else {
+            self.check_snapshot(snapshot);
+
+            vec![]
+        };
+        self.status_changed = false;
 
         UpdateBook {
-            account: snapshot.account,
-            source_status: HashMap::from_iter([(
-                snapshot.range.get_exchange().unwrap(),
-                SourceStatus {
-                    alive: true,
-                    initial_positions: true,
-                },
-            )]),
+            account: self.account,
+            source_status: HashMap::from_iter([(self.exchange, self.source_status())]),
 
             positions: self
                 .positions
@@ -191,6 +216,43 @@ impl SourceAccount {
         }
     }
 
+    /// Compares a snapshot with the positions built from the incremental feeds.
+    /// Balances pass through, fees and funding are not booked against them.
+    fn check_snapshot(&mut self, snapshot: &UpdatePositions) {
+        let mut reported = self.positions.clone();
+        snapshot.update_position_values(&mut reported);
+
+        let grace = chrono::Duration::seconds(POSITION_DESYNC_GRACE_SECS)
+            .num_nanoseconds()
+            .unwrap();
+        for (instrument, quantity) in reported {
+            if matches!(instrument, InstrumentCode::Asset(_)) {
+                self.positions.insert(instrument, quantity);
+                continue;
+            }
+
+            let booked = self.positions.get(&instrument).copied().unwrap_or_default();
+            if float_eq!(booked, quantity, abs <= 1e-9, rmax <= 1e-6) {
+                self.position_mismatches.remove(&instrument);
+                continue;
+            }
+
+            let since = *self
+                .position_mismatches
+                .entry(instrument.clone())
+                .or_insert(snapshot.exchange_time);
+            if snapshot.exchange_time > since && (snapshot.exchange_time - since).nanos() >= grace {
+                self.halt(format!(
+                    "Position mismatch; instrument={instrument}; venue={quantity}; accounting={booked}"
+                ));
+
+                // The venue is the reference from here on.
+                self.position_mismatches.remove(&instrument);
+                self.positions.insert(instrument, quantity);
+            }
+        }
+    }
+
     /* /////////////////////////////////////////////////////////////////////////////
                                         SHARED
     ///////////////////////////////////////////////////////////////////////////// */
@@ -199,6 +261,17 @@ impl SourceAccount {
         source_timestamp > self.snapshot_time.unwrap() && source_timestamp > self.cleanup_time
     }
 
+    /// Flags an inconsistency. The first reason is kept until the halt is
+    /// acknowledged.
+    fn halt(&mut self, reason: String) {
+        warn!("Accounting inconsistency; exchange={}; {reason}", self.exchange);
+
+        if self.halt_reason.is_none() {
+            self.halt_reason = Some(reason);
+            self.status_changed = true;
+        }
+    }
+
     fn lazy_init_order(&mut self, update: &AccountingUpdateOrder) -> Option<&mut OrderState> {
         let is_live = self.is_live(update.source_creation_timestamp);
 
@@ -240,8 +313,12 @@ impl SourceAccount {
     fn on_order_update(&mut self, update: &AccountingUpdateOrder) -> UpdateDeltas {
         // If order is settled, check assertions & return.
         if let Some(closed_state) = self.settled_orders.get(&update.order_lid) {
-            if update.closed() {
-                closed_state.check_close_invariants(update);
+            let inconsistency = match update.closed() {
+                true => closed_state.check_close_invariants(update),
+                false => None,
+            };
+            if let Some(reason) = inconsistency {
+                self.halt(reason);
             }
 
             return UpdateDeltas::default();
@@ -267,10 +344,46 @@ impl SourceAccount {
         }
     }
 
+    /// Processes an [`AccountingUpdateOrder`] event of a venue without a trade
+    /// feed. The fills not covered by trades yet are booked as one trade, so
+    /// that the order can settle.
+    fn on_order_fills(&mut self, update: &AccountingUpdateOrder) -> Vec<UpdateDeltas> {
+        let deltas = self.on_order_update(update);
+
+        let Some(order) = self.volatile_orders.get(&update.order_lid) else {
+            return vec![deltas];
+        };
+        let quantity = order.filled_quantity - order.sum_trade_quantity();
+        let cost = order.filled_cost - order.sum_trade_cost();
+        if quantity <= 1e-12 || cost <= 0.0 {
+            return vec![deltas];
+        }
+
+        // NB: The venue reports neither the trade id, the fill time nor the fee.
+        let trade = OrderTrade {
+            account: self.account,
+            trade_lid: TradeLid(format!("{}|{}", update.order_lid, order.filled_quantity)),
+            instrument: order.instrument.clone(),
+            price: cost / quantity,
+            size: quantity,
+            side: order.side,
+            fee: 0.0,
+            fee_asset: "".into(),
+            order_lid: update.order_lid.clone(),
+            exchange_time: Time::now(),
+            received_time: Time::now(),
+        };
+
+        vec![deltas, self.on_trade(trade)]
+    }
+
     /// Processes a [`OrderTrade`] event.
     fn on_trade(&mut self, trade: OrderTrade) -> UpdateDeltas {
-        assert_ne!(trade.size, 0.0, "Cannot process zero quantity trades; trade={trade:?}");
-        assert!(trade.price > 0.0, "Not tested with non-positive prices");
+        if trade.size == 0.0 || trade.price <= 0.0 {
+            warn!("Cannot process zero quantity or non-positive price trades; trade={trade:?}");
+
+            return UpdateDeltas::default();
+        }
         // Enforce configured max_desync.
         if let Some(max_desync) = self.max_desync {
             let snapshot_time = self.snapshot_time.unwrap();
@@ -296,17 +409,30 @@ impl SourceAccount {
         match self.trades.entry_ref(&trade.trade_lid) {
             EntryRef::Vacant(entry) => entry.insert(trade.clone()),
             EntryRef::Occupied(entry) => {
-                let existing = entry.get();
-                assert_eq!(&trade, existing);
+                if entry.get() != &trade {
+                    let reason = format!("Trade changed; trade={trade:?}; existing={:?}", entry.get());
+                    self.halt(reason);
+                }
 
                 return UpdateDeltas::default();
             }
         };
 
         // Get the pre/post quantity/cost for the trade update.
-        assert!(!self.settled_orders.contains_key(&trade.order_lid));
+        if self.settled_orders.contains_key(&trade.order_lid) {
+            self.halt(format!("Trade for a settled order; trade={trade:?}"));
+
+            return UpdateDeltas::default();
+        }
         let (pre, post) = if let Some(order) = self.volatile_orders.get_mut(&trade.order_lid) {
-            order.apply_new_trade(trade.clone())
+            match order.apply_new_trade(trade.clone()) {
+                Ok(deltas) => deltas,
+                Err(reason) => {
+                    self.halt(reason);
+
+                    return UpdateDeltas::default();
+                }
+            }
         } else {
             if trade.exchange_time < self.snapshot_time.unwrap() {
                 return UpdateDeltas::historical_trade(trade.clone());
@@ -355,8 +481,10 @@ impl SourceAccount {
         match self.funding.entry_ref(&funding.funding_lid) {
             EntryRef::Vacant(entry) => entry.insert(funding.clone()),
             EntryRef::Occupied(entry) => {
-                let existing = entry.get();
-                assert_eq!(&funding, existing);
+                if entry.get() != &funding {
+                    let reason = format!("Funding changed; funding={funding:?}; existing={:?}", entry.get());
+                    self.halt(reason);
+                }
 
                 return UpdateDeltas::default();
             }
@@ -403,7 +531,7 @@ impl SourceAccount {
         let qty_delta = f64::from(post_qty - pre_qty);
         match &instrument {
             InstrumentCode::Simple(ins) => match ins.ty {
-                InstrumentType::Spot => {
+                InstrumentType::Spot | InstrumentType::Margin => {
                     let spot = ins;
                     let cost_delta = post_cost - pre_cost;
 
@@ -419,8 +547,7 @@ impl SourceAccount {
 
                     [Some((base, base_delta.into())), Some((quote, quote_delta.into())), fees]
                 }
-                InstrumentType::Margin => unreachable!(),
-                InstrumentType::Perpetual(_) => {
+                InstrumentType::Perpetual(_) | InstrumentType::Delivery(_) => {
                     let position_delta = match side {
                         Side::Buy => qty_delta,
                         Side::Sell => -qty_delta,
@@ -429,8 +556,11 @@ impl SourceAccount {
 
                     [Some((instrument, position_delta.into())), None, fees]
                 }
-                InstrumentType::Delivery(_) => unreachable!(),
-                InstrumentType::Option => unreachable!(),
+                InstrumentType::Option => {
+                    warn!("Could not compute deltas for instrument={instrument}");
+
+                    [None, None, fees]
+                }
             },
 
             InstrumentCode::CFD(_) => {
@@ -442,7 +572,11 @@ impl SourceAccount {
 
                 [Some((instrument, position_delta.into())), None, fees]
             }
-            _ => unreachable!("Could not compute deltas for instrument={instrument}"),
+            _ => {
+                warn!("Could not compute deltas for instrument={instrument}");
+
+                [None, None, fees]
+            }
         }
     }
 
@@ -472,6 +606,18 @@ impl SourceAccount {
                                         API (READ)
     ///////////////////////////////////////////////////////////////////////////// */
 
+    pub fn halt_reason(&self) -> Option<&str> {
+        self.halt_reason.as_deref()
+    }
+
+    pub fn source_status(&self) -> SourceStatus {
+        SourceStatus {
+            alive: true,
+            initial_positions: self.snapshot_time.is_some(),
+            halt_reason: self.halt_reason.clone(),
+        }
+    }
+
     /// Returns the order matching the provided OrderLid.
     ///
     /// This will check both open & closed order stores.
@@ -515,42 +661,67 @@ impl SourceAccount {
                                         API (WRITE)
     ///////////////////////////////////////////////////////////////////////////// */
 
-    pub(crate) fn advance_cleanup_time(&mut self, cleanup_time: Time) {
+    /// Drops the settled orders, trades and funding payments from before
+    /// `cleanup_time`, they can no longer be duplicated by the venue.
+    pub fn advance_cleanup_time(&mut self, cleanup_time: Time) {
+        // A clock stepping back must not roll the session markers backwards.
+        if cleanup_time <= self.cleanup_time {
+            warn!(
+                "Ignored cleanup time before the current one; cleanup_time={cleanup_time:?}, current={:?}",
+                self.cleanup_time
+            );
+
+            return;
+        }
+
         // Roll the session markers.
-        assert!(cleanup_time > self.cleanup_time);
         self.cleanup_time = cleanup_time;
 
-        // Clean-up all closed orders and trades older than cleanup time.
+        // Clean-up all closed orders, trades and funding older than cleanup time.
         self.settled_orders
-            .retain(|_, order| order.accounting_close_timestamp.unwrap() >= self.cleanup_time);
+            .retain(|_, order| order.accounting_close_timestamp.unwrap() >= cleanup_time);
+        self.funding
+            .retain(|_, funding| funding.source_timestamp >= cleanup_time);
+        let volatile_trades = &mut self.volatile_trades;
         self.trades.retain(|_, trade| {
-            if trade.exchange_time >= self.cleanup_time {
+            if trade.exchange_time >= cleanup_time {
                 true
             } else {
-                assert!(
-                    !self.volatile_trades.contains_key(&trade.order_lid),
-                    "Cannot clean-up volatile trade; trade={trade:?}"
-                );
+                if volatile_trades.remove(&trade.order_lid).is_some() {
+                    warn!("Cleaned up trades that never matched an order; trade={trade:?}");
+                }
 
                 false
             }
         });
 
-        // Assert no limbo orders are from the previous session.
-        let stale_limbo_orders: Vec<_> = self
+        // Drop the limbo orders from the previous session, their trades never
+        // showed up.
+        for (_, order) in self
             .volatile_orders
-            .values()
-            .filter(|order| order.accounting_close_timestamp.map(|time| time < self.cleanup_time) == Some(true))
-            .collect();
-        assert!(
-            stale_limbo_orders.is_empty(),
-            "Had stale limbo orders at session roll: {stale_limbo_orders:?}"
-        );
+            .extract_if(|_, order| order.accounting_close_timestamp.is_some_and(|time| time < cleanup_time))
+        {
+            warn!("Had stale limbo order at session roll: {order:?}");
+        }
+    }
+
+    /// Clears the halt once a human has looked into it. The next snapshot is
+    /// loaded as the new starting state.
+    pub fn acknowledge_halt(&mut self) {
+        self.halt_reason = None;
+        self.position_mismatches.clear();
+        self.rebase = true;
+        self.status_changed = true;
     }
 
+    /// Updates before the first snapshot are left to the snapshot.
     #[must_use]
     pub fn process_updates(&mut self, updates: impl IntoIterator<Item = AccountingUpdate>) -> Option<UpdateBook> {
-        assert!(self.snapshot_time.is_some(), "Received update before snapshot");
+        if self.snapshot_time.is_none() {
+            debug!("Received update before snapshot; exchange={}", self.exchange);
+
+            return None;
+        }
 
         // Process all updates.
         let mut updated_positions = HashSet::new();
@@ -559,34 +730,38 @@ impl SourceAccount {
         let mut funding = Vec::default();
         let mut historical_funding = Vec::default();
         for update in updates {
-            let UpdateDeltas {
+            let deltas = match update {
+                AccountingUpdate::Order(update) => vec![self.on_order_update(&update)],
+                AccountingUpdate::OrderFills(update) => self.on_order_fills(&update),
+                AccountingUpdate::Trade(trade) => vec![self.on_trade(trade)],
+                AccountingUpdate::Funding(funding) => vec![self.on_funding(funding)],
+            };
+
+            for UpdateDeltas {
                 position_deltas,
                 trade,
                 historical_trade,
                 funding: funding_pmt,
                 historical_funding: historical_funding_pmt,
-            } = match update {
-                AccountingUpdate::Order(update) => self.on_order_update(&update),
-                AccountingUpdate::Trade(trade) => self.on_trade(trade),
-                AccountingUpdate::Funding(funding) => self.on_funding(funding),
-            };
+            } in deltas
+            {
+                // Apply deltas.
+                for (instrument, delta) in position_deltas.into_iter().flatten() {
+                    if delta == 0.0 {
+                        continue;
+                    }
 
-            // Apply deltas.
-            for (instrument, delta) in position_deltas.into_iter().flatten() {
-                if delta == 0.0 {
-                    continue;
-                }
+                    *self.positions.entry(instrument.clone()).or_default() += delta;
 
-                *self.positions.entry(instrument.clone()).or_default() += delta;
+                    updated_positions.insert(instrument);
+                }
 
-                updated_positions.insert(instrument);
+                // Propagate new events.
+                trades.extend(trade);
+                historical_trades.extend(historical_trade);
+                funding.extend(funding_pmt);
+                historical_funding.extend(historical_funding_pmt);
             }
-
-            // Propagate new events.
-            trades.extend(trade);
-            historical_trades.extend(historical_trade);
-            funding.extend(funding_pmt);
-            historical_funding.extend(historical_funding_pmt);
         }
 
         // Check for order settlement.
@@ -599,13 +774,19 @@ impl SourceAccount {
             && historical_trades.is_empty()
             && funding.is_empty()
             && historical_funding.is_empty()
+            && !self.status_changed
         {
             return None;
         }
 
+        let mut source_status = HashMap::new();
+        if std::mem::take(&mut self.status_changed) {
+            source_status.insert(self.exchange, self.source_status());
+        }
+
         Some(UpdateBook {
             account: self.account,
-            source_status: HashMap::new(),
+            source_status,
 
             positions: updated_positions
                 .into_iter()
@@ -2309,6 +2490,24 @@ TradingBookUpdate {{
         account.advance_cleanup_time(curr_session_start());
     }
 
+    #[test]
+    fn advance_cleanup_time_ignores_earlier_time() {
+        // arrange
+        let mut account = SourceAccount::empty(
+            Exchange::BinanceSpot,
+            chrono::Duration::seconds(0),
+            prev_session_start(),
+        );
+        let _ = account.load_snapshot(&mock_empty_snapshot());
+        account.advance_cleanup_time(curr_session_start());
+
+        // act
+        account.advance_cleanup_time(prev_session_start());
+
+        // assert
+        assert_eq!(account.cleanup_time, curr_session_start());
+    }
+
     #[test]
     fn advance_cleanup_time_cleans_up_orders() {
         // arrange
@@ -0,0 +1,247 @@
+//! Runs the messages of an execution connection through its
+//! [`SourceAccount`], so that every venue gets the same consistency checks.
+
+use trading_model::{Exchange, InstrumentCode, SharedInstrumentManager, Side, Time};
+
+use super::{AccountingUpdate, SourceAccount};
+use crate::model::{
+    AccountId, AccountingUpdateOrder, ExecutionResponse, OrderLid, SourceStatus, UpdateBook, UpdateOrder,
+    UpdatePositions,
+};
+
+/// Settled orders, trades and funding payments are kept this long to catch
+/// duplicates.
+const CLEANUP_RETENTION_MS: i64 = 24 * 3_600_000;
+const CLEANUP_INTERVAL_MS: i64 = 3_600_000;
+
+/// Orders are keyed by their local id, or by their server id when the venue
+/// does not echo it.
+fn local_order_lid(update: &UpdateOrder) -> OrderLid {
+    if !update.local_id.is_empty() {
+        return update.local_id.clone();
+    }
+    format!("sid|{}", update.server_id).into()
+}
+
+/// Appends the [`UpdateBook`] of every change to the responses of an execution
+/// connection. Position snapshots are checked against the positions built from
+/// the orders, trades and funding, an inconsistency halts the source.
+pub struct AccountingFeed {
+    account: SourceAccount,
+    manager: SharedInstrumentManager,
+    enabled: bool,
+    /// The venue reports every trade, otherwise the fills of the orders are
+    /// booked as trades.
+    trade_feed: bool,
+    order_lid: fn(&UpdateOrder) -> OrderLid,
+    last_cleanup: Time,
+}
+
+impl AccountingFeed {
+    pub fn new(exchange: Exchange, account: AccountId, manager: SharedInstrumentManager, enabled: bool) -> Self {
+        let now = Time::now();
+        let mut source =
+            SourceAccount::empty_no_desync(exchange, Time::from_millis(now.millis() - CLEANUP_RETENTION_MS));
+        source.account = account;
+
+        Self {
+            account: source,
+            manager,
+            enabled,
+            trade_feed: false,
+            order_lid: local_order_lid,
+            last_cleanup: now,
+        }
+    }
+
+    /// For venues that report their trades, `order_lid` must key the orders the
+    /// way the trades refer to them.
+    pub fn with_trade_feed(mut self, order_lid: fn(&UpdateOrder) -> OrderLid) -> Self {
+        self.trade_feed = true;
+        self.order_lid = order_lid;
+        self
+    }
+
+    pub fn account(&self) -> AccountId {
+        self.account.account
+    }
+
+    pub fn source_status(&self) -> SourceStatus {
+        self.account.source_status()
+    }
+
+    pub fn acknowledge_halt(&mut self) {
+        self.account.acknowledge_halt();
+    }
+
+    fn instrument(&self, instrument: &InstrumentCode) -> InstrumentCode {
+        match self.manager.get_by_code(instrument) {
+            Some(details) => details.code_simple.clone(),
+            None => instrument.clone(),
+        }
+    }
+
+    pub fn load_snapshot(&mut self, snapshot: &UpdatePositions) -> UpdateBook {
+        let now = Time::now();
+        if now.millis() - self.last_cleanup.millis() >= CLEANUP_INTERVAL_MS {
+            self.account
+                .advance_cleanup_time(Time::from_millis(now.millis() - CLEANUP_RETENTION_MS));
+            self.last_cleanup = now;
+        }
+
+        let mut snapshot = snapshot.clone();
+        for position in snapshot.positions.iter_mut() {
+            position.instrument = self.instrument(&position.instrument);
+        }
+        self.account.load_snapshot(&snapshot)
+    }
+
+    fn order_update(&self, update: &UpdateOrder) -> Option<AccountingUpdate> {
+        if !matches!(update.side, Side::Buy | Side::Sell) {
+            return None;
+        }
+        let created = [update.open_est, update.create_lt, update.update_est]
+            .into_iter()
+            .find(|time| *time != Time::NULL)
+            .unwrap_or_else(Time::now);
+        let updated = match update.update_est {
+            Time::NULL => Time::now(),
+            time => time,
+        };
+        // NB: Not every venue reports the average price of the fills.
+        let price = [update.average_filled_price, update.last_filled_price, update.price]
+            .into_iter()
+            .find(|price| *price > 0.0)
+            .unwrap_or_default();
+
+        let order = AccountingUpdateOrder {
+            order_lid: (self.order_lid)(update),
+            instrument: self.instrument(&update.instrument),
+            side: update.side,
+            source_creation_timestamp: created,
+            accounting_close_timestamp: update.status.is_dead().then_some(updated),
+            total_quantity: update.size,
+            filled_quantity: update.filled_size,
+            filled_cost_min: update.filled_size * price,
+        };
+        Some(match self.trade_feed {
+            true => AccountingUpdate::Order(order),
+            false => AccountingUpdate::OrderFills(order),
+        })
+    }
+
+    pub fn on_response(&mut self, response: ExecutionResponse) -> ExecutionResponse {
+        if !self.enabled {
+            return response;
+        }
+
+        let update = match response {
+            ExecutionResponse::Group(group) => {
+                return ExecutionResponse::Group(group.into_iter().map(|x| self.on_response(x)).collect());
+            }
+            ExecutionResponse::UpdatePositions(ref snapshot) => {
+                let book = self.load_snapshot(snapshot);
+                // NB: The book goes first, so that the positions of the venue are the last ones applied.
+                return ExecutionResponse::Group(vec![ExecutionResponse::UpdateBook(book), response]);
+            }
+            ExecutionResponse::UpdateOrder(ref update) => self.order_update(update),
+            ExecutionResponse::TradeOrder(ref trade) => {
+                let mut trade = trade.clone();
+                trade.instrument = self.instrument(&trade.instrument);
+                Some(trade.into())
+            }
+            ExecutionResponse::UpdateFunding(ref funding) => {
+                let mut funding = funding.clone();
+                funding.instrument = self.instrument(&funding.instrument);
+                Some(funding.into())
+            }
+            _ => None,
+        };
+
+        match update.and_then(|update| self.account.process_updates([update])) {
+            Some(book) => ExecutionResponse::Group(vec![response, ExecutionResponse::UpdateBook(book)]),
+            None => response,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use trading_model::{InstrumentManager, InstrumentSimple, QuantityUnit};
+
+    use super::*;
+    use crate::model::{OrderStatus, Position};
+
+    fn books(response: &ExecutionResponse) -> Vec<UpdateBook> {
+        match response {
+            ExecutionResponse::Group(group) => group.iter().flat_map(books).collect(),
+            ExecutionResponse::UpdateBook(book) => vec![book.clone()],
+            _ => vec![],
+        }
+    }
+
+    fn snapshot(instrument: &InstrumentCode, total: f64, exchange_time: Time) -> ExecutionResponse {
+        let mut update = UpdatePositions::sync_position(0, Exchange::BinanceFutures);
+        update.exchange_time = exchange_time;
+        update.add_position(&Position {
+            instrument: instrument.clone(),
+            total,
+            available: total,
+            unit: QuantityUnit::Base,
+            ..Position::empty()
+        });
+        ExecutionResponse::UpdatePositions(update)
+    }
+
+    #[test]
+    fn test_fills_settle_and_position_desync_halts() {
+        let manager = Arc::new(InstrumentManager::new());
+        let mut feed = AccountingFeed::new(Exchange::BinanceFutures, 0, manager, true);
+        let instrument = InstrumentCode::from_simple(InstrumentSimple::new_perpetual_linear(
+            Exchange::BinanceFutures,
+            "ETH".into(),
+            "USDT".into(),
+        ));
+        let start = Time::now();
+
+        feed.on_response(snapshot(&instrument, 0.0, start));
+
+        // fills without a trade feed are booked as trades and settle the order
+        let update = UpdateOrder {
+            instrument: instrument.clone(),
+            local_id: "1".into(),
+            side: Side::Buy,
+            size: 2.0,
+            filled_size: 2.0,
+            average_filled_price: 100.0,
+            status: OrderStatus::Filled,
+            create_lt: start + std::time::Duration::from_secs(1),
+            update_est: start + std::time::Duration::from_secs(1),
+            ..UpdateOrder::empty()
+        };
+        let book = books(&feed.on_response(update.into())).pop().unwrap();
+        assert_eq!(book.positions.get(&instrument), Some(&2.0));
+        assert_eq!(book.trades.len(), 1);
+        assert_eq!(book.settled_orders.len(), 1);
+
+        // the venue agrees
+        let book = books(&feed.on_response(snapshot(&instrument, 2.0, start + std::time::Duration::from_secs(2))));
+        assert!(!book[0].source_status[&Exchange::BinanceFutures].is_halted());
+
+        // a position that changed without any order is tolerated for the grace period only
+        feed.on_response(snapshot(&instrument, 3.0, start + std::time::Duration::from_secs(3)));
+        assert!(!feed.source_status().is_halted());
+        let book = books(&feed.on_response(snapshot(&instrument, 3.0, start + std::time::Duration::from_secs(20))));
+        assert!(book[0].source_status[&Exchange::BinanceFutures].is_halted());
+        assert_eq!(book[0].positions.get(&instrument), Some(&3.0));
+
+        // the halt holds until acknowledged, then the next snapshot is the new baseline
+        feed.on_response(snapshot(&instrument, 3.0, start + std::time::Duration::from_secs(21)));
+        assert!(feed.source_status().is_halted());
+        feed.acknowledge_halt();
+        feed.on_response(snapshot(&instrument, 5.0, start + std::time::Duration::from_secs(22)));
+        assert!(!feed.source_status().is_halted());
+    }
+}
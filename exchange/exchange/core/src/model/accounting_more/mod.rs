@@ -1,9 +1,11 @@
 #![allow(dead_code)]
 
 mod account;
+mod feed;
 mod order_state;
 mod update2;
 
 pub use account::*;
+pub use feed::*;
 pub use order_state::*;
 pub(crate) use update2::*;
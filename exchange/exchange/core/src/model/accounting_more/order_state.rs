@@ -24,12 +24,13 @@ impl OrderState {
         self.accounting_close_timestamp.is_some()
     }
 
+    /// The creation timestamp is left out, not every venue reports it on each
+    /// update.
     pub(crate) fn immutable_cmp(&self, other: &AccountingUpdateOrder) -> bool {
         self.order_lid == other.order_lid
             && self.instrument == other.instrument
             && self.side == other.side
             && float_eq!(self.total_quantity, other.total_quantity, r1st <= 1e-5)
-            && self.source_creation_timestamp == other.source_creation_timestamp
     }
 
     pub(crate) fn check_invariants(&self) {
@@ -39,10 +40,17 @@ impl OrderState {
         }
     }
 
-    pub(crate) fn check_close_invariants(&self, update: &AccountingUpdateOrder) {
+    /// Returns an inconsistency when the update reports fills that accounting
+    /// has not seen.
+    pub(crate) fn check_close_invariants(&self, update: &AccountingUpdateOrder) -> Option<String> {
         assert!(update.closed(), "Cannot check close invariants");
 
         if float_ne!(self.filled_quantity, update.filled_quantity, r1st <= 1e-5) {
+            if update.filled_quantity > self.filled_quantity {
+                return Some(format!(
+                    "Filled quantity after close exceeds accounting; self={self:?}; update={update:?}"
+                ));
+            }
             warn!(
                 "Filled quantity mismatch; self={self:?}; update={update:?}",
                 update = update,
@@ -55,6 +63,8 @@ impl OrderState {
                 update.filled_cost_min,
             )
         }
+
+        None
     }
 
     pub(crate) fn sum_trade_quantity(&self) -> f64 {
@@ -89,27 +99,38 @@ impl OrderState {
             .or(update.accounting_close_timestamp);
 
         // Enforce invariants.
-        if self.immutable_cmp(update) {
+        if !self.immutable_cmp(update) {
             warn!("Immutables changed; self={self:?}; other={update:?}",)
         }
 
         self.check_invariants();
         if update.closed() {
-            self.check_close_invariants(update);
+            // NB: Cannot fail as the filled quantity was just raised to the update.
+            let _ = self.check_close_invariants(update);
         }
 
         ((pre_qty, pre_cost), (post_qty, post_cost))
     }
 
-    pub(crate) fn apply_new_trade(&mut self, trade: OrderTrade) -> ((f64, f64), (f64, f64)) {
+    /// Returns an inconsistency instead of applying a trade that does not
+    /// belong to the order.
+    pub(crate) fn apply_new_trade(
+        &mut self,
+        trade: OrderTrade,
+    ) -> Result<((f64, f64), (f64, f64)), String> {
         // Enforce invariants.
-        assert_eq!(
-            trade.side, self.side,
-            "Trade side mismatch; trade={trade:?}; order={self:?}"
-        );
+        if trade.side != self.side {
+            return Err(format!("Trade side mismatch; trade={trade:?}; order={self:?}"));
+        }
+        if self.trades.contains_key(&trade.trade_lid) {
+            return Err(format!("Trade already applied; trade={trade:?}; order={self:?}"));
+        }
+        if self.closed() && self.sum_trade_quantity() + trade.size > self.filled_quantity * (1.0 + 1e-5) {
+            return Err(format!("Fill after close; trade={trade:?}; order={self:?}"));
+        }
 
         // Insert the order so it forms a part of our qty/cost sums.
-        assert!(self.trades.insert(trade.trade_lid.clone(), trade).is_none());
+        self.trades.insert(trade.trade_lid.clone(), trade);
 
         // Track before & after states.
         let pre_qty = self.filled_quantity;
@@ -121,11 +142,6 @@ impl OrderState {
         self.filled_quantity = post_qty;
         self.filled_cost = post_cost;
 
-        // Enforce close invariants.
-        if self.closed() {
-            assert_eq!(pre_qty, post_qty, "Fill after close");
-        }
-
         self.check_invariants();
 
         // Opportunistically close order if fully filled.
@@ -133,7 +149,7 @@ impl OrderState {
             self.accounting_close_timestamp = Some(Time::now());
         }
 
-        ((pre_qty, pre_cost), (post_qty, post_cost))
+        Ok(((pre_qty, pre_cost), (post_qty, post_cost)))
     }
 }
 
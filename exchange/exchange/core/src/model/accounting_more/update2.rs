@@ -3,6 +3,8 @@ use crate::model::{AccountingUpdateOrder, FundingPayment, OrderTrade};
 #[derive(Debug, Clone, PartialEq)]
 pub enum AccountingUpdate {
     Order(AccountingUpdateOrder),
+    /// An order of a venue without a trade feed, its fills are booked as trades.
+    OrderFills(AccountingUpdateOrder),
     Trade(OrderTrade),
     Funding(FundingPayment),
 }
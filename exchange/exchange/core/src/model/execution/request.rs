@@ -3,11 +3,17 @@ use serde::{Deserialize, Serialize};
 
 use trading_model::{Exchange, InstrumentSelector};
 
+use crate::model::AccountId;
+
+mod acknowledge_halt;
 mod cancel_order;
 mod new_order;
+mod query_order;
 mod set_leverage;
+pub use acknowledge_halt::*;
 pub use cancel_order::*;
 pub use new_order::*;
+pub use query_order::*;
 pub use set_leverage::*;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromOne)]
@@ -15,10 +21,14 @@ pub enum ExecutionRequest {
     PlaceOrder(RequestPlaceOrder),
     GetPositions(Exchange),
     CancelOrder(RequestCancelOrder),
+    /// the state of one order, for the orders not reported open
+    QueryOrder(RequestQueryOrder),
     CancelAllOrders(Option<Exchange>),
     SyncOrders(InstrumentSelector),
     QueryAssets(Option<Exchange>),
     UpdateLeverage(RequestUpdateLeverage),
+    /// A human has looked into the accounting halt of the account
+    AcknowledgeHalt(RequestAcknowledgeHalt),
 }
 
 impl ExecutionRequest {
@@ -27,10 +37,31 @@ impl ExecutionRequest {
             Self::PlaceOrder(req) => req.instrument.get_exchange(),
             Self::GetPositions(exchange) => Some(exchange.clone()),
             Self::CancelOrder(req) => req.instrument.get_exchange(),
+            Self::QueryOrder(req) => req.instrument.get_exchange(),
             Self::CancelAllOrders(exchange) => exchange.clone(),
             Self::SyncOrders(range) => range.get_exchange(),
             Self::QueryAssets(exchange) => exchange.clone(),
             Self::UpdateLeverage(req) => Some(req.exchange.clone()),
+            Self::AcknowledgeHalt(req) => Some(req.exchange),
+        }
+    }
+    pub fn get_account(&self) -> Option<AccountId> {
+        match self {
+            Self::PlaceOrder(req) => Some(req.account),
+            Self::CancelOrder(req) => Some(req.account),
+            Self::QueryOrder(req) => Some(req.account),
+            Self::AcknowledgeHalt(req) => Some(req.account),
+            _ => None,
+        }
+    }
+    /// Requests without an account, or with account 0, go to any connection of the exchange.
+    pub fn is_for(&self, exchange: Exchange, account: AccountId) -> bool {
+        if self.get_exchange() != Some(exchange) {
+            return false;
+        }
+        match self.get_account() {
+            Some(0) | None => true,
+            Some(x) => x == account,
         }
     }
 }
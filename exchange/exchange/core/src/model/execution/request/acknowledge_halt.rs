@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+use trading_model::Exchange;
+
+use crate::model::AccountId;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestAcknowledgeHalt {
+    pub exchange: Exchange,
+    pub account: AccountId,
+}
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+use trading_model::{InstrumentCode, Time};
+
+use crate::model::{AccountId, OrderCid, OrderLid, OrderSid, OrderStatus, UpdateOrder};
+
+/// asks the venue for the state of one order, answered by an `UpdateOrder`.
+/// an order the venue does not know is answered `Absent`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestQueryOrder {
+    pub instrument: InstrumentCode,
+    pub order_lid: OrderLid,
+    pub order_cid: OrderCid,
+    pub order_sid: OrderSid,
+    pub account: AccountId,
+    pub strategy_id: u64,
+}
+
+impl RequestQueryOrder {
+    pub fn from_update(update: &UpdateOrder) -> Self {
+        Self {
+            instrument: update.instrument.clone(),
+            order_lid: update.local_id.clone(),
+            order_cid: update.client_id.clone(),
+            order_sid: update.server_id.clone(),
+            account: update.account,
+            strategy_id: update.strategy_id,
+        }
+    }
+    /// the order as the venue did not report it
+    pub fn to_update(&self) -> UpdateOrder {
+        UpdateOrder {
+            instrument: self.instrument.clone(),
+            local_id: self.order_lid.clone(),
+            client_id: self.order_cid.clone(),
+            server_id: self.order_sid.clone(),
+            status: OrderStatus::Absent,
+            account: self.account,
+            update_lt: Time::now(),
+            strategy_id: self.strategy_id,
+            ..UpdateOrder::empty()
+        }
+    }
+}
@@ -2,7 +2,7 @@ use derive_from_one::FromOne;
 use eyre::Result;
 use serde::{Deserialize, Serialize};
 
-use trading_model::Exchange;
+use trading_model::{Exchange, LiquidationEvent};
 
 use crate::model::{
     AccountId, FundingPayment, Order, OrderCache, OrderTrade, Portfolio, PortfolioMulti, SyncOrders, UpdateBook,
@@ -22,6 +22,8 @@ pub enum ExecutionResponse {
     UpdateBook(UpdateBook),
     TradeOrder(OrderTrade),
     CompleteOrder(Order),
+    /// liquidation involving the account
+    Liquidation(LiquidationEvent),
     Group(Vec<ExecutionResponse>),
 }
 
@@ -47,6 +49,7 @@ impl ExecutionResponse {
             Self::UpdatePosition(update_position) => update_position.instrument.get_exchange(),
             Self::TradeOrder(trade) => trade.instrument.get_exchange(),
             Self::CompleteOrder(order) => order.instrument.get_exchange(),
+            Self::Liquidation(liquidation) => liquidation.instrument.get_exchange(),
             Self::Group(g) => g.get(0)?.get_exchange(),
             _ => None,
         }
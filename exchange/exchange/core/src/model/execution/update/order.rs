@@ -136,6 +136,7 @@ impl UpdateOrder {
             opening_cloid: self.opening_cloid.clone(),
             effect: self.effect,
             event_id: self.event_id,
+            strategy_id: self.strategy_id,
             ..Order::empty()
         }
     }
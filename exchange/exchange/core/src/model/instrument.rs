@@ -11,6 +11,10 @@ use trading_model::model::InstrumentManager;
 pub trait InstrumentLoader: Send + Sync {
     fn accept(&self, config: &InstrumentsConfig) -> bool;
     async fn load(&self, config: &InstrumentsConfig) -> Result<Arc<InstrumentManager>>;
+    /// loads the instruments from the exchange again, bypassing any cache
+    async fn reload(&self, config: &InstrumentsConfig) -> Result<Arc<InstrumentManager>> {
+        self.load(config).await
+    }
 }
 #[async_trait]
 impl<T: InstrumentLoader> InstrumentLoader for &T {
@@ -20,6 +24,9 @@ impl<T: InstrumentLoader> InstrumentLoader for &T {
     async fn load(&self, config: &InstrumentsConfig) -> Result<Arc<InstrumentManager>> {
         T::load(*self, config).await
     }
+    async fn reload(&self, config: &InstrumentsConfig) -> Result<Arc<InstrumentManager>> {
+        T::reload(*self, config).await
+    }
 }
 pub struct InstrumentLoaderCached<Loader: InstrumentLoader> {
     loader: Loader,
@@ -55,6 +62,16 @@ impl<Loader: InstrumentLoader> InstrumentLoaderCached<Loader> {
         write.insert(config.clone(), manager.clone());
         Ok(manager)
     }
+    /// fetches the instruments and replaces the cached ones
+    pub async fn reload(&self, config: &InstrumentsConfig) -> Result<Arc<InstrumentManager>> {
+        let instruments = self.loader.load(config).await?;
+        let mut manager = InstrumentManager::new();
+        manager.extend_from(&instruments);
+        let manager = manager.into_shared();
+        let mut write = self.cache.get_or_init(|| RwLock::new(HashMap::new())).write().await;
+        write.insert(config.clone(), manager.clone());
+        Ok(manager)
+    }
 }
 
 #[async_trait]
@@ -65,6 +82,9 @@ impl<Loader: InstrumentLoader> InstrumentLoader for InstrumentLoaderCached<Loade
     async fn load(&self, config: &InstrumentsConfig) -> Result<Arc<InstrumentManager>> {
         InstrumentLoaderCached::load(self, config).await
     }
+    async fn reload(&self, config: &InstrumentsConfig) -> Result<Arc<InstrumentManager>> {
+        InstrumentLoaderCached::reload(self, config).await
+    }
 }
 pub struct InstrumentLoaderManager {
     loaders: Vec<Box<dyn InstrumentLoader>>,
@@ -106,4 +126,18 @@ impl InstrumentLoaderManager {
         manager.retain_network(config.network.clone());
         Ok(manager.into_shared())
     }
+    /// same as `load_instruments_multi`, but always fetches from the exchanges
+    pub async fn reload_instruments_multi(&self, config: &InstrumentsMultiConfig) -> Result<Arc<InstrumentManager>> {
+        info!("Reloading instrument manager: {:?}", config);
+        let mut manager = InstrumentManager::new();
+        for cfg in config.iter() {
+            let loader = self
+                .get_loader(&cfg)
+                .with_context(|| format!("No loader found for {:?}", cfg))?;
+            let symbols = loader.reload(&cfg).await?;
+            manager.extend_from(&symbols);
+        }
+        manager.retain_network(config.network.clone());
+        Ok(manager.into_shared())
+    }
 }
@@ -8,6 +8,7 @@ mod execution;
 mod execution_service;
 mod instrument;
 mod market;
+mod orderbook;
 mod resource;
 mod select;
 mod service;
@@ -23,6 +24,7 @@ pub use execution::*;
 pub use execution_service::*;
 pub use instrument::*;
 pub use market::*;
+pub use orderbook::*;
 pub use resource::*;
 pub use select::*;
 pub use service::*;
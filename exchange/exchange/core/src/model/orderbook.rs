@@ -0,0 +1,96 @@
+use eyre::Result;
+use futures::future::BoxFuture;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tracing::{info, warn};
+use trading_model::model::{BookDelta, BookSequenceRule, InstrumentCode, OrderBookSync, Quotes, Symbol};
+
+pub type BookSnapshotFetcher = Arc<dyn Fn(Symbol) -> BoxFuture<'static, Result<BookDelta>> + Send + Sync>;
+
+/// full-depth books of venues whose snapshots come from a REST endpoint while updates stream over websocket.
+/// a snapshot is fetched in the background for every new symbol and again after every gap
+pub struct RestSnapshotBooks {
+    rule: BookSequenceRule,
+    books: HashMap<Symbol, OrderBookSync>,
+    fetcher: BookSnapshotFetcher,
+    tx: UnboundedSender<(Symbol, Result<BookDelta>)>,
+    rx: UnboundedReceiver<(Symbol, Result<BookDelta>)>,
+}
+
+impl RestSnapshotBooks {
+    pub fn new(rule: BookSequenceRule, fetcher: BookSnapshotFetcher) -> Self {
+        let (tx, rx) = unbounded_channel();
+        Self {
+            rule,
+            books: HashMap::new(),
+            fetcher,
+            tx,
+            rx,
+        }
+    }
+    pub fn get(&self, symbol: &Symbol) -> Option<&OrderBookSync> {
+        self.books.get(symbol)
+    }
+    fn request_snapshot(&mut self, symbol: Symbol) {
+        if let Some(book) = self.books.get_mut(&symbol) {
+            book.begin_resync();
+        }
+        info!("fetching order book snapshot for {}", symbol);
+        let fetch = (self.fetcher)(symbol.clone());
+        let tx = self.tx.clone();
+        tokio::spawn(async move {
+            let _ = tx.send((symbol, fetch.await));
+        });
+    }
+    /// applies fetched snapshots, returns the books that were replaced
+    fn drain_snapshots(&mut self) -> Vec<Quotes> {
+        let mut replaced = vec![];
+        while let Ok((symbol, snapshot)) = self.rx.try_recv() {
+            let Some(book) = self.books.get_mut(&symbol) else {
+                continue;
+            };
+            match snapshot.and_then(|x| Ok(book.on_snapshot(x)?)) {
+                Ok(quotes) => replaced.push(quotes),
+                Err(err) => {
+                    warn!("order book snapshot for {} rejected: {}", symbol, err);
+                    self.request_snapshot(symbol);
+                }
+            }
+        }
+        replaced
+    }
+    /// feeds one websocket update. the returned quotes are safe to apply as they are:
+    /// a resynced book is sent whole, starting with a clear of both sides
+    pub fn on_update(&mut self, symbol: Symbol, instrument: InstrumentCode, delta: BookDelta) -> Option<Quotes> {
+        if !self.books.contains_key(&symbol) {
+            self.books
+                .insert(symbol.clone(), OrderBookSync::new(instrument, self.rule));
+            self.request_snapshot(symbol.clone());
+        }
+        let mut quotes = self.drain_snapshots();
+        let book = self.books.get_mut(&symbol).unwrap();
+        match book.on_update(delta) {
+            Ok(Some(update)) => quotes.push(update),
+            Ok(None) => {}
+            Err(err) => {
+                warn!("order book {} out of sync: {}", symbol, err);
+                self.request_snapshot(symbol);
+            }
+        }
+        merge_quotes(quotes)
+    }
+}
+
+/// concatenates quotes of one instrument, keeping the sequence and times of the last
+pub fn merge_quotes(quotes: Vec<Quotes>) -> Option<Quotes> {
+    let mut iter = quotes.into_iter();
+    let mut merged = iter.next()?;
+    for next in iter {
+        merged.quotes.extend(next.quotes);
+        merged.last_seq = next.last_seq;
+        merged.exchange_time = next.exchange_time;
+        merged.received_time = next.received_time;
+    }
+    Some(merged)
+}
@@ -15,8 +15,9 @@ use tokio_tungstenite::tungstenite::client::IntoClientRequest;
 use tokio_tungstenite::tungstenite::Message;
 use tracing::*;
 use trading_exchange_core::model::{
-    AccountId, ExecutionConfig, ExecutionRequest, ExecutionResource, ExecutionResponse, ExecutionService,
-    ExecutionServiceBuilder, InstrumentsConfig, RequestCancelOrder, RequestPlaceOrder, SigningApiKeySecret,
+    AccountId, AccountingFeed, ExecutionConfig, ExecutionRequest, ExecutionResource, ExecutionResponse,
+    ExecutionService, ExecutionServiceBuilder, InstrumentsConfig, RequestCancelOrder, RequestPlaceOrder,
+    RequestQueryOrder, SigningApiKeySecret,
 };
 use trading_exchange_core::utils::future::interval_conditionally;
 use trading_exchange_core::{
@@ -94,6 +95,7 @@ pub struct BinanceExecutionConnection {
     accounting: bool,
     execution: bool,
     account: AccountId,
+    source: AccountingFeed,
 }
 
 impl Debug for BinanceExecutionConnection {
@@ -128,6 +130,7 @@ impl BinanceExecutionConnection {
             ws: WsSession::new(),
             sync_orders_interval: interval_conditionally(5000, execution),
             sync_balances_interval: interval_conditionally(1000, accounting),
+            source: AccountingFeed::new(exchange, account, manager.clone(), accounting),
             manager,
             accounting,
             execution,
@@ -208,19 +211,29 @@ impl BinanceExecutionConnection {
 
         Ok(())
     }
+
+    fn start_query_order(&mut self, query: &RequestQueryOrder) -> Result<()> {
+        let symbol = self.manager.get_by_code_result(&query.instrument)?;
+        self.session.send_query_order(query, symbol);
+        Ok(())
+    }
 }
 
 #[async_trait(?Send)]
 impl ExecutionService for BinanceExecutionConnection {
     fn accept(&self, request: &ExecutionRequest) -> bool {
-        request.get_exchange() == Some(self.exchange)
+        request.is_for(self.exchange, self.source.account())
     }
 
     async fn request(&mut self, request: &ExecutionRequest) -> Result<()> {
         match request {
             ExecutionRequest::PlaceOrder(req) => self.start_new_order(req),
             ExecutionRequest::CancelOrder(req) => self.start_cancel_order(req),
-
+            ExecutionRequest::QueryOrder(req) => self.start_query_order(req),
+            ExecutionRequest::AcknowledgeHalt(_) => {
+                self.source.acknowledge_halt();
+                Ok(())
+            }
             _ => unimplemented!("unsupported request: {:?}", request),
         }
     }
@@ -234,11 +247,11 @@ impl ExecutionService for BinanceExecutionConnection {
                     };
                     // debug!(?msg, "received message");
                     if let Some(msg) = self.decode_ws_message(msg)? {
-                        return Ok(msg);
+                        return Ok(self.source.on_response(msg));
                     }
                 }
                 msg = self.session.next() => {
-                    return Ok(msg);
+                    return Ok(self.source.on_response(msg));
                 }
 
                 _ = self.sync_orders_interval.tick() => {
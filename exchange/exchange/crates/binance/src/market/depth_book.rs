@@ -0,0 +1,85 @@
+use crate::market::depth_futures::BinanceFuturesDepthUpdate;
+use futures::FutureExt;
+use parking_lot::Mutex;
+use serde::*;
+use serde_with::serde_as;
+use serde_with::DisplayFromStr;
+use std::collections::HashSet;
+use std::sync::Arc;
+use trading_exchange_core::model::RestSnapshotBooks;
+use trading_model::core::{Time, TimeStampMs};
+use trading_model::model::{
+    BookDelta, BookSequence, BookSequenceRule, Exchange, InstrumentManagerExt, Quotes, SharedInstrumentManager, Symbol,
+};
+
+/// response of `/api/v3/depth` and `/fapi/v1/depth`, `T` is only set by futures
+#[serde_as]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BinanceDepthSnapshot {
+    last_update_id: u64,
+    #[serde(rename = "T", default)]
+    transaction_time: TimeStampMs,
+    #[serde_as(as = "Vec<(DisplayFromStr, DisplayFromStr)>")]
+    bids: Vec<(f64, f64)>,
+    #[serde_as(as = "Vec<(DisplayFromStr, DisplayFromStr)>")]
+    asks: Vec<(f64, f64)>,
+}
+impl BinanceDepthSnapshot {
+    pub fn into_delta(self) -> BookDelta {
+        let mut delta = BookDelta::new(BookSequence::single(self.last_update_id)).with_levels(self.bids, self.asks);
+        delta.exchange_time = Time::from_millis(self.transaction_time);
+        delta
+    }
+}
+
+/// full-depth books maintained from `<symbol>@depth@100ms` diffs on top of the REST snapshot
+pub struct BinanceDepthBookChannel {
+    exchange: Exchange,
+    manager: Option<SharedInstrumentManager>,
+    symbols: HashSet<Symbol>,
+    books: Mutex<RestSnapshotBooks>,
+}
+
+impl BinanceDepthBookChannel {
+    pub fn new(exchange: Exchange, depth_url: String, manager: Option<SharedInstrumentManager>) -> Self {
+        // futures ids are not contiguous, each diff refers to the previous one with `pu`
+        let rule = match exchange {
+            Exchange::BinanceFutures => BookSequenceRule::Linked,
+            _ => BookSequenceRule::Overlapping,
+        };
+        let fetcher = Arc::new(move |symbol: Symbol| {
+            let url = format!("{}?symbol={}&limit=1000", depth_url, symbol);
+            async move {
+                let text = reqwest::get(&url).await?.text().await?;
+                let snapshot: BinanceDepthSnapshot = serde_json::from_str(&text)?;
+                Ok(snapshot.into_delta())
+            }
+            .boxed()
+        });
+        Self {
+            exchange,
+            manager,
+            symbols: HashSet::new(),
+            books: Mutex::new(RestSnapshotBooks::new(rule, fetcher)),
+        }
+    }
+    pub fn add_symbol(&mut self, symbol: &Symbol) {
+        self.symbols.insert(symbol.clone());
+    }
+    pub fn contains(&self, symbol: &str) -> bool {
+        self.symbols.contains(&Symbol::from(symbol))
+    }
+    pub fn get_sub_param(&self, symbol: &str) -> String {
+        format!("{}@depth@100ms", symbol.to_ascii_lowercase())
+    }
+    pub fn parse_depth_update(&self, update: BinanceFuturesDepthUpdate, received_time: Time) -> Option<Quotes> {
+        let symbol = Symbol::from(update.s.as_str());
+        let instrument = self.manager.maybe_lookup_instrument(self.exchange, symbol.clone());
+        let mut delta =
+            BookDelta::new(BookSequence::linked(update.pu, update.U, update.u)).with_levels(update.b, update.a);
+        delta.exchange_time = Time::from_millis(update.E);
+        delta.received_time = received_time;
+        self.books.lock().on_update(symbol, instrument, delta)
+    }
+}
@@ -19,6 +19,9 @@ pub struct BinanceFuturesDepthUpdate {
     // First update ID in event
     pub u: u64,
     // Final update ID in event
+    #[serde(default)]
+    pub pu: u64,
+    // Final update ID of the previous event, futures only
     #[serde_as(as = "Vec<(DisplayFromStr, DisplayFromStr)>")]
     pub b: Vec<(
         // Bids to be updated
@@ -0,0 +1,61 @@
+use serde::Deserialize;
+use serde_with::serde_as;
+use serde_with::DisplayFromStr;
+use trading_model::core::{Time, TimeStampMs};
+use trading_model::model::{Exchange, InstrumentManagerExt, LiquidationEvent, SharedInstrumentManager, Side, Symbol};
+
+#[serde_as]
+#[derive(Deserialize, Debug)]
+#[allow(non_snake_case, dead_code)]
+pub struct BinanceForceOrderData {
+    s: Symbol,
+    // Symbol
+    S: Side,
+    // Side
+    #[serde_as(as = "DisplayFromStr")]
+    ap: f64,
+    // Average Price
+    #[serde_as(as = "DisplayFromStr")]
+    z: f64,
+    // Order Filled Accumulated Quantity
+    X: String,
+    // Order Status
+    T: TimeStampMs,
+    // Order Trade Time
+}
+
+/// `<symbol>@forceOrder`, futures only. only the latest liquidation of a symbol within 1000ms is pushed
+#[derive(Deserialize, Debug)]
+#[allow(non_snake_case, dead_code)]
+pub struct BinanceForceOrder {
+    E: TimeStampMs,
+    // Event Time
+    o: BinanceForceOrderData,
+}
+
+pub struct BinanceLiquidationChannel {
+    exchange: Exchange,
+    manager: Option<SharedInstrumentManager>,
+}
+
+impl BinanceLiquidationChannel {
+    pub fn new(exchange: Exchange, manager: Option<SharedInstrumentManager>) -> Self {
+        Self { exchange, manager }
+    }
+
+    pub fn parse_binance_force_order(&self, msg: BinanceForceOrder, received_time: Time) -> LiquidationEvent {
+        let order = msg.o;
+        let instrument = self.manager.maybe_lookup_instrument(self.exchange, order.s);
+        LiquidationEvent {
+            instrument,
+            side: order.S,
+            price: order.ap,
+            size: order.z,
+            exchange_time: Time::from_millis(order.T),
+            received_time,
+        }
+    }
+    pub fn get_sub_param(&self, symbol: &str) -> String {
+        format!("{}@forceOrder", symbol.to_ascii_lowercase())
+    }
+}
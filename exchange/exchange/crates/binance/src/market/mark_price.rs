@@ -0,0 +1,63 @@
+use serde::Deserialize;
+use serde_with::serde_as;
+use serde_with::DisplayFromStr;
+use trading_model::core::{Time, TimeStampMs};
+use trading_model::model::{DerivativesContextEvent, Exchange, InstrumentManagerExt, SharedInstrumentManager, Symbol};
+
+/// `<symbol>@markPrice@1s`, futures only
+#[serde_as]
+#[derive(Deserialize, Debug)]
+#[allow(non_snake_case, dead_code)]
+pub struct BinanceMarkPriceUpdate {
+    E: TimeStampMs,
+    // Event Time
+    s: Symbol,
+    // Symbol
+    #[serde_as(as = "DisplayFromStr")]
+    p: f64,
+    // Mark Price
+    #[serde_as(as = "DisplayFromStr")]
+    i: f64,
+    // Index Price
+    #[serde_as(as = "DisplayFromStr")]
+    P: f64,
+    // Estimated Settle Price, only useful in the last hour before the settlement starts
+    #[serde_as(as = "DisplayFromStr")]
+    r: f64,
+    // Funding Rate
+    T: TimeStampMs,
+    // Next Funding Time
+}
+
+pub struct BinanceMarkPriceChannel {
+    exchange: Exchange,
+    manager: Option<SharedInstrumentManager>,
+}
+
+impl BinanceMarkPriceChannel {
+    pub fn new(exchange: Exchange, manager: Option<SharedInstrumentManager>) -> Self {
+        Self { exchange, manager }
+    }
+
+    pub fn parse_binance_mark_price(
+        &self,
+        msg: BinanceMarkPriceUpdate,
+        received_time: Time,
+    ) -> DerivativesContextEvent {
+        let instrument = self.manager.maybe_lookup_instrument(self.exchange, msg.s);
+        DerivativesContextEvent {
+            instrument,
+            mark_price: Some(msg.p),
+            index_price: Some(msg.i),
+            // not part of the stream, only available from the rest endpoint
+            open_interest: None,
+            funding_rate: Some(msg.r),
+            next_funding_time: Some(Time::from_millis(msg.T)),
+            exchange_time: Time::from_millis(msg.E),
+            received_time,
+        }
+    }
+    pub fn get_sub_param(&self, symbol: &str) -> String {
+        format!("{}@markPrice@1s", symbol.to_ascii_lowercase())
+    }
+}
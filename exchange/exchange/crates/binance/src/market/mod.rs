@@ -1,13 +1,17 @@
 //! Binance exchange
+pub mod depth_book;
 pub mod depth_full_futures;
 pub mod depth_full_spot;
 pub mod depth_futures;
 pub mod depth_spot;
+pub mod liquidation;
+pub mod mark_price;
 pub mod msg;
 pub mod parser;
 pub mod ticker;
 pub mod trade;
 
+use crate::market::depth_book::BinanceDepthBookChannel;
 use crate::market::depth_spot::{BinanceSpotDepthChannel, BinanceSpotDepthConnection, BinanceSpotDepthManager};
 use crate::market::parser::BinanceMarketParser;
 use crate::symbol::BINANCE_INSTRUMENT_LOADER;
@@ -168,9 +172,26 @@ impl BinanceMarketFeedConnection {
                     {
                         self.create_spot_channel(&symbol.symbol);
                     }
+                    MarketFeedSelector::Depth(d) if d.match_depth(MarketFeedDepthKind::UPDATE_FULL) => {
+                        let depth_book = self.converter.depth_book.get_or_insert_with(|| {
+                            BinanceDepthBookChannel::new(
+                                self.urls.exchange,
+                                self.urls.depth_url.clone(),
+                                Some(self.manager.clone()),
+                            )
+                        });
+                        depth_book.add_symbol(&symbol.symbol);
+                        params.push(depth_book.get_sub_param(&symbol.symbol));
+                    }
                     MarketFeedSelector::BookTicker => {
                         params.push(self.converter.book_ticker.get_sub_param(&symbol.symbol));
                     }
+                    MarketFeedSelector::Liquidation if self.urls.exchange == Exchange::BinanceFutures => {
+                        params.push(self.converter.liquidation.get_sub_param(&symbol.symbol));
+                    }
+                    MarketFeedSelector::DerivativesContext if self.urls.exchange == Exchange::BinanceFutures => {
+                        params.push(self.converter.mark_price.get_sub_param(&symbol.symbol));
+                    }
                     _ => {
                         bail!("Unsupported resource: {:?}", res);
                     }
@@ -1,4 +1,6 @@
 use crate::market::depth_futures::BinanceFuturesDepthUpdate;
+use crate::market::liquidation::BinanceForceOrder;
+use crate::market::mark_price::BinanceMarkPriceUpdate;
 use crate::market::ticker::BinanceBookTicker;
 use crate::market::trade::BinanceTrade;
 use serde::Deserialize;
@@ -10,6 +12,8 @@ pub enum BinanceMarketFeedMessage {
     DepthUpdateFutures(BinanceFuturesDepthUpdate),
     Trade(BinanceTrade),
     BookTicker(BinanceBookTicker),
+    ForceOrder(BinanceForceOrder),
+    MarkPriceUpdate(BinanceMarkPriceUpdate),
 }
 
 #[derive(Deserialize)]
@@ -3,8 +3,11 @@ use eyre::Result;
 use trading_model::model::{Exchange, MarketEvent, SharedInstrumentManager, Symbol};
 use trading_model::wire::PacketStr;
 
+use crate::market::depth_book::BinanceDepthBookChannel;
 use crate::market::depth_futures::BinanceFuturesDepthChannel;
 use crate::market::depth_spot::{BinanceSpotDepthChannel, BinanceSpotDepthMessage};
+use crate::market::liquidation::BinanceLiquidationChannel;
+use crate::market::mark_price::BinanceMarkPriceChannel;
 use crate::market::msg::BinanceMarketFeedMessage;
 use crate::market::ticker::{BinanceBookTicker, BinanceBookTickerChannel};
 use crate::market::trade::BinanceTradeChannel;
@@ -13,8 +16,12 @@ pub struct BinanceMarketParser {
     pub(crate) symbol: Option<Symbol>,
     pub(crate) depth_spot: BinanceSpotDepthChannel,
     pub(crate) depth_futures: BinanceFuturesDepthChannel,
+    /// only created when a full-depth book is subscribed
+    pub(crate) depth_book: Option<BinanceDepthBookChannel>,
     pub(crate) trade: BinanceTradeChannel,
     pub(crate) book_ticker: BinanceBookTickerChannel,
+    pub(crate) liquidation: BinanceLiquidationChannel,
+    pub(crate) mark_price: BinanceMarkPriceChannel,
 }
 impl BinanceMarketParser {
     pub fn new(exchange: Exchange, manager: Option<SharedInstrumentManager>) -> Self {
@@ -22,8 +29,11 @@ impl BinanceMarketParser {
             symbol: None,
             depth_spot: BinanceSpotDepthChannel::new(exchange, manager.clone()),
             depth_futures: BinanceFuturesDepthChannel::new(exchange, manager.clone()),
+            depth_book: None,
             trade: BinanceTradeChannel::new(exchange, manager.clone()),
             book_ticker: BinanceBookTickerChannel::new(exchange, manager.clone()),
+            liquidation: BinanceLiquidationChannel::new(exchange, manager.clone()),
+            mark_price: BinanceMarkPriceChannel::new(exchange, manager.clone()),
         }
     }
     pub fn set_symbol(&mut self, symbol: Symbol) {
@@ -51,6 +61,16 @@ impl BinanceMarketParser {
         }
         let msg: BinanceMarketFeedMessage = serde_json::from_str(&pkt)?;
         match msg {
+            BinanceMarketFeedMessage::DepthUpdateFutures(update)
+                if self.depth_book.as_ref().is_some_and(|x| x.contains(&update.s)) =>
+            {
+                let quotes = self
+                    .depth_book
+                    .as_ref()
+                    .unwrap()
+                    .parse_depth_update(update, pkt.received_time);
+                Ok(quotes.map(MarketEvent::Quotes))
+            }
             BinanceMarketFeedMessage::DepthUpdateFutures(update) => {
                 let quotes = self.depth_futures.parse_binance_futures_depth_update(update)?;
 
@@ -64,6 +84,14 @@ impl BinanceMarketParser {
                 let tob = self.book_ticker.parse_binance_book_ticker(ticker, pkt.received_time)?;
                 Ok(Some(MarketEvent::BookTicker(tob)))
             }
+            BinanceMarketFeedMessage::ForceOrder(order) => {
+                let liquidation = self.liquidation.parse_binance_force_order(order, pkt.received_time);
+                Ok(Some(MarketEvent::Liquidation(liquidation)))
+            }
+            BinanceMarketFeedMessage::MarkPriceUpdate(update) => {
+                let context = self.mark_price.parse_binance_mark_price(update, pkt.received_time);
+                Ok(Some(MarketEvent::DerivativesContext(context)))
+            }
         }
     }
 }
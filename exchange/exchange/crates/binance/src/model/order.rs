@@ -7,7 +7,7 @@ use serde::Serialize;
 use serde_with::serde_as;
 use serde_with::DisplayFromStr;
 
-use trading_exchange_core::model::{Order, OrderCid, OrderStatus, OrderType, SyncOrders};
+use trading_exchange_core::model::{AccountId, Order, OrderCid, OrderStatus, OrderType, SyncOrders};
 use trading_model::core::{Time, TimeStampMs};
 use trading_model::model::{Exchange, InstrumentManagerExt, SharedInstrumentManager, Side, Symbol};
 use trading_model::{Price, Quantity};
@@ -182,11 +182,12 @@ pub struct HttpLiveOrder {
 }
 
 pub fn decode_http_open_orders(
+    account: AccountId,
     data: &[u8],
     exchange: Exchange,
     manager: Option<SharedInstrumentManager>,
 ) -> eyre::Result<SyncOrders> {
-    let mut sync_orders = SyncOrders::new(exchange, None);
+    let mut sync_orders = SyncOrders::new(exchange, None).with_account(account);
     let orders: Vec<HttpLiveOrder> = serde_json::from_slice(data)?;
 
     for order in orders {
@@ -205,6 +206,7 @@ pub fn decode_http_open_orders(
             // update_est: TimeStamp::from_millis(order.update_time),
             update_lt: Time::now(),
             filled_size: order.executed_qty,
+            account,
             ..Order::empty()
         });
     }
@@ -6,7 +6,7 @@ use reqwest::Url;
 
 use trading_exchange_core::model::{
     AccountId, ExecutionRequest, ExecutionResponse, OrderSid, OrderStatus, OrderType, RequestCancelOrder,
-    RequestPlaceOrder, SigningApiKeySecret,
+    RequestPlaceOrder, RequestQueryOrder, SigningApiKeySecret,
 };
 use trading_exchange_core::utils::http_session::HttpSession;
 use trading_exchange_core::utils::sign::sign_hmac_sha256_hex;
@@ -15,7 +15,7 @@ use trading_model::model::{
 };
 use trading_model::Time;
 
-use crate::model::order::{decode_http_open_orders, NewOrderResponse};
+use crate::model::order::{decode_http_open_orders, HttpLiveOrder, NewOrderResponse};
 use crate::rest::margin::parse_query_user_assets_margin;
 use crate::rest::spot::parse_query_user_assets_spot;
 use crate::rest::usdm_futures::parse_query_user_assets_usdm_futures;
@@ -252,18 +252,63 @@ impl BinanceRestClient {
             },
         )
     }
+    /// GET the order by server id, or by client id when the placement was never acknowledged
+    pub fn query_order(&self, session: &mut HttpSession, query: &RequestQueryOrder, symbol: &InstrumentDetails) {
+        let mut param = ParamVec::new();
+
+        self.append_symbol(&mut param, &symbol.symbol);
+        if !query.order_sid.is_empty() {
+            append_argument_pair(&mut param, "orderId", &query.order_sid);
+        } else if !query.order_cid.is_empty() {
+            append_argument_pair(&mut param, "origClientOrderId", &query.order_cid);
+        } else {
+            return;
+        };
+        self.append_time(&mut param);
+        let req = self.build_request_signed(Method::GET, self.urls.order.clone(), param);
+        let mut update = query.to_update();
+        session.send_and_handle(
+            ExecutionRequest::QueryOrder(query.clone()),
+            req,
+            move |_request, response| {
+                let resp = match response {
+                    Ok(resp) => resp,
+                    // -2013: the order does not exist on the venue
+                    Err(err) if err.to_string().contains(r#""code":-2013"#) => {
+                        update.reason = "unknown to the exchange".to_string();
+                        return update.into();
+                    }
+                    Err(err) => return ExecutionResponse::Error(err.to_string()),
+                };
+                match serde_json::from_str::<HttpLiveOrder>(&resp) {
+                    Ok(order) => {
+                        update.status = order.status.into();
+                        update.server_id = OrderSid::from_u64(order.order_id as _);
+                        update.price = order.price;
+                        update.size = order.orig_qty;
+                        update.filled_size = order.executed_qty;
+                        update.average_filled_price = order.avg_price;
+                        update.update_tst = Time::from_millis(order.update_time);
+                        update.into()
+                    }
+                    Err(err) => ExecutionResponse::Error(format!("failed to decode order {resp}: {err}")),
+                }
+            },
+        )
+    }
     pub fn sync_orders(&self, session: &mut HttpSession, manager: Option<SharedInstrumentManager>) {
         let mut param = ParamVec::new();
         self.append_time(&mut param);
         let req = self.build_request_signed(Method::GET, self.urls.open_orders.clone(), param);
         let exchange = self.urls.exchange;
+        let account = self.account;
         session.send_and_handle(
             ExecutionRequest::SyncOrders(InstrumentSelector::Exchange(self.urls.exchange)),
             req,
             move |_request, response| match response {
                 Ok(resp) => {
                     let data = resp.as_bytes();
-                    let sync_orders = decode_http_open_orders(data, exchange, manager.clone())
+                    let sync_orders = decode_http_open_orders(account, data, exchange, manager.clone())
                         .expect("decode_http_usdm_futures_open_orders");
                     ExecutionResponse::SyncOrders(sync_orders)
                 }
@@ -385,6 +430,9 @@ impl BinanceRestSession {
     pub fn send_cancel_order(&mut self, order: &RequestCancelOrder, symbol: &InstrumentDetails) {
         self.client.cancel_order(&mut self.session, order, symbol);
     }
+    pub fn send_query_order(&mut self, query: &RequestQueryOrder, symbol: &InstrumentDetails) {
+        self.client.query_order(&mut self.session, query, symbol);
+    }
     pub fn send_sync_orders(&mut self, manager: Option<SharedInstrumentManager>) {
         self.client.sync_orders(&mut self.session, manager);
     }
@@ -5,8 +5,9 @@ use eyre::{Context, Result};
 use itertools::Itertools;
 
 use trading_exchange_core::model::{
-    AccountId, ExecutionConfig, ExecutionRequest, ExecutionResource, ExecutionResponse, ExecutionService,
-    ExecutionServiceBuilder, InstrumentsConfig, RequestCancelOrder, RequestPlaceOrder, SigningApiKeySecret,
+    AccountId, AccountingFeed, ExecutionConfig, ExecutionRequest, ExecutionResource, ExecutionResponse,
+    ExecutionService, ExecutionServiceBuilder, InstrumentsConfig, RequestCancelOrder, RequestPlaceOrder,
+    SigningApiKeySecret,
 };
 use trading_exchange_core::utils::future::interval_conditionally;
 use trading_exchange_core::{
@@ -85,6 +86,7 @@ pub struct BitGetExecutionConnection {
     sync_orders_interval: tokio::time::Interval,
     sync_balances_interval: tokio::time::Interval,
     manager: SharedInstrumentManager,
+    source: AccountingFeed,
 }
 
 impl Debug for BitGetExecutionConnection {
@@ -112,6 +114,7 @@ impl BitGetExecutionConnection {
             ws: BitGetPrivateWs::new(account, urls, signing),
             sync_orders_interval: interval_conditionally(5000, execution),
             sync_balances_interval: interval_conditionally(1000, accounting),
+            source: AccountingFeed::new(Exchange::Bitget, account, manager.clone(), accounting),
             manager,
         })
     }
@@ -133,12 +136,16 @@ impl BitGetExecutionConnection {
 #[async_trait(?Send)]
 impl ExecutionService for BitGetExecutionConnection {
     fn accept(&self, request: &ExecutionRequest) -> bool {
-        matches!(request.get_exchange(), Some(Exchange::Bitget))
+        request.is_for(Exchange::Bitget, self.source.account())
     }
     async fn request(&mut self, request: &ExecutionRequest) -> Result<()> {
         match request {
             ExecutionRequest::PlaceOrder(req) => self.start_new_order(req),
             ExecutionRequest::CancelOrder(req) => self.start_cancel_order(req),
+            ExecutionRequest::AcknowledgeHalt(_) => {
+                self.source.acknowledge_halt();
+                Ok(())
+            }
             _ => unimplemented!("unsupported request: {:?}", request),
         }
     }
@@ -147,10 +154,10 @@ impl ExecutionService for BitGetExecutionConnection {
         loop {
             tokio::select! {
                 msg = self.ws.next() => {
-                    return Ok(msg);
+                    return Ok(self.source.on_response(msg));
                 }
                 msg = self.session.next() => {
-                    return Ok(msg);
+                    return Ok(self.source.on_response(msg));
                 }
 
                 _ = self.sync_orders_interval.tick() => {
@@ -1,4 +1,4 @@
-use eyre::Result;
+use std::collections::HashMap;
 
 use crate::market::{encode_subscribe, lookup_instrument};
 use serde::*;
@@ -8,18 +8,47 @@ use tracing::warn;
 
 use trading_exchange_core::model::WebsocketMarketFeedChannel;
 use trading_model::model::{SharedInstrumentManager, Symbol};
-use trading_model::{InstrumentDetails, Intent, Quote, Quotes, Time, TimeStampMs};
+use trading_model::{
+    BookDelta, BookLevel, BookSequence, BookSequenceRule, BookSyncError, InstrumentDetails, Intent, OrderBookSync,
+    Quote, Quotes, Time, TimeStampMs,
+};
 
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BitGetOrderbookData {
-    #[serde_as(as = "Vec<(DisplayFromStr, DisplayFromStr)>")]
-    pub bids: Vec<(f64, f64)>,
-    // List of bid prices and quantities
-    #[serde_as(as = "Vec<(DisplayFromStr, DisplayFromStr)>")]
-    pub asks: Vec<(f64, f64)>,
+    // prices and quantities are kept as sent, the checksum of `books` is computed over the raw strings
+    pub bids: Vec<(String, String)>,
+    pub asks: Vec<(String, String)>,
     #[serde_as(as = "DisplayFromStr")]
     pub ts: TimeStampMs,
+    /// signed crc32 of the top 25 levels, only sent on `books`
+    #[serde(default)]
+    pub checksum: Option<i64>,
+    #[serde(default)]
+    pub seq: Option<u64>,
+    #[serde(default)]
+    pub pseq: Option<u64>,
+}
+impl BitGetOrderbookData {
+    fn levels(levels: &[(String, String)]) -> Vec<BookLevel> {
+        levels
+            .iter()
+            .filter_map(|(price, size)| {
+                BookLevel::from_raw(price, size)
+                    .map_err(|err| warn!("invalid bitget level {}:{}: {}", price, size, err))
+                    .ok()
+            })
+            .collect()
+    }
+    pub fn into_delta(self) -> BookDelta {
+        let seq = self.seq.unwrap_or_default();
+        let mut delta = BookDelta::new(BookSequence::linked(self.pseq.unwrap_or_default(), seq, seq));
+        delta.bids = Self::levels(&self.bids);
+        delta.asks = Self::levels(&self.asks);
+        delta.checksum = self.checksum.map(|x| x as i32 as u32);
+        delta.exchange_time = Time::from_millis(self.ts);
+        delta
+    }
 }
 
 #[serde_as]
@@ -52,10 +81,23 @@ pub struct BitGetOrderbookArg {
 
 pub struct BitGetOrderbookChannel {
     manager: SharedInstrumentManager,
+    /// subscribe to `books` (full depth, snapshot then updates) instead of `books5`
+    full_depth: bool,
+    books: HashMap<Symbol, OrderBookSync>,
 }
 impl BitGetOrderbookChannel {
+    pub const FULL_DEPTH_CHANNEL: &'static str = "books";
+    pub const CHECKSUM_DEPTH: usize = 25;
+
     pub fn new(manager: SharedInstrumentManager) -> Self {
-        Self { manager }
+        Self {
+            manager,
+            full_depth: false,
+            books: HashMap::new(),
+        }
+    }
+    pub fn set_full_depth(&mut self, full_depth: bool) {
+        self.full_depth |= full_depth;
     }
 
     pub fn process_data(&self, data: BitGetOrderbookEvent, is_snapshot: bool) -> Option<Quotes> {
@@ -63,37 +105,73 @@ impl BitGetOrderbookChannel {
         let mut quotes = Quotes::new(instrument.clone());
         quotes.exchange_time = Time::from_millis(data.ts);
         quotes.received_time = Time::now();
+        let data = data.data.into_iter().next()?;
+        let bids = BitGetOrderbookData::levels(&data.bids);
+        let asks = BitGetOrderbookData::levels(&data.asks);
         if is_snapshot {
             quotes.insert_clear();
-            for (i, (price, quantity)) in data.data[0].bids.iter().copied().enumerate() {
-                quotes.insert_quote(Quote::update_by_level(Intent::Bid, (i + 1) as _, price, quantity));
+            for (i, level) in bids.into_iter().enumerate() {
+                quotes.insert_quote(Quote::update_by_level(
+                    Intent::Bid,
+                    (i + 1) as _,
+                    level.price,
+                    level.size,
+                ));
             }
-            for (i, (price, quantity)) in data.data[0].asks.iter().copied().enumerate() {
-                quotes.insert_quote(Quote::update_by_level(Intent::Ask, (i + 1) as _, price, quantity));
+            for (i, level) in asks.into_iter().enumerate() {
+                quotes.insert_quote(Quote::update_by_level(
+                    Intent::Ask,
+                    (i + 1) as _,
+                    level.price,
+                    level.size,
+                ));
             }
         } else {
-            for (price, quantity) in data.data[0].bids.iter().copied() {
-                quotes.insert_quote(Quote::update_by_price(Intent::Bid, price, quantity));
+            for level in bids {
+                quotes.insert_quote(Quote::update_by_price(Intent::Bid, level.price, level.size));
             }
-            for (price, quantity) in data.data[0].bids.iter().copied() {
-                quotes.insert_quote(Quote::update_by_price(Intent::Ask, price, quantity));
+            for level in asks {
+                quotes.insert_quote(Quote::update_by_price(Intent::Ask, level.price, level.size));
             }
         }
         Some(quotes)
     }
-    pub fn parse_message(&self, message: BitGetOrderbookEvent) -> Result<Option<Quotes>> {
+    /// `books` messages go through the per-symbol `OrderBookSync`, an error means the book must be resubscribed
+    fn process_full_depth(
+        &mut self,
+        message: BitGetOrderbookEvent,
+        is_snapshot: bool,
+    ) -> Result<Option<Quotes>, BookSyncError> {
+        let Some(instrument) = lookup_instrument(&self.manager, &message.arg.instType, &message.arg.instId) else {
+            return Ok(None);
+        };
+        let Some(data) = message.data.into_iter().next() else {
+            return Ok(None);
+        };
+        let book = self.books.entry(message.arg.instId).or_insert_with(|| {
+            OrderBookSync::new(instrument, BookSequenceRule::Linked).with_checksum_depth(Self::CHECKSUM_DEPTH)
+        });
+        let delta = data.into_delta();
+        if is_snapshot {
+            book.on_snapshot(delta).map(Some)
+        } else {
+            book.on_update(delta)
+        }
+    }
+    pub fn parse_message(&mut self, message: BitGetOrderbookEvent) -> Result<Option<Quotes>, BookSyncError> {
         let is_snapshot;
         if message.action == "snapshot" {
             is_snapshot = true;
-        } else if message.action == "delta" {
+        } else if message.action == "update" || message.action == "delta" {
             is_snapshot = false;
         } else {
             warn!("Unknown message type: {}", message.action);
             return Ok(None);
         }
-        let quotes = self.process_data(message, is_snapshot);
-
-        Ok(quotes)
+        if message.arg.channel == Self::FULL_DEPTH_CHANNEL {
+            return self.process_full_depth(message, is_snapshot);
+        }
+        Ok(self.process_data(message, is_snapshot))
     }
 }
 
@@ -103,6 +181,9 @@ impl WebsocketMarketFeedChannel for BitGetOrderbookChannel {
     }
 
     fn encode_subscribe_instrument(&self, instrument: &InstrumentDetails) -> Value {
+        if self.full_depth {
+            return encode_subscribe(instrument.ty, Self::FULL_DEPTH_CHANNEL, &instrument.symbol);
+        }
         let depth = 5;
         let channel = format!("books{}", depth);
         encode_subscribe(instrument.ty, &channel, &instrument.symbol)
@@ -0,0 +1,75 @@
+use crate::market::{encode_subscribe, lookup_instrument};
+use serde::*;
+use serde_json::Value;
+use serde_with::{serde_as, DisplayFromStr};
+
+use trading_exchange_core::model::WebsocketMarketFeedChannel;
+use trading_model::model::{SharedInstrumentManager, Symbol};
+use trading_model::{InstrumentDetails, LiquidationEvent, Side, Time, TimeStampMs};
+
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BitGetLiquidationData {
+    /// side of the forced order
+    pub side: Side,
+    #[serde_as(as = "DisplayFromStr")]
+    pub price: f64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub size: f64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub ts: TimeStampMs,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct BitGetLiquidationArg {
+    pub instType: String,
+    pub channel: String,
+    pub instId: Symbol,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BitGetLiquidationEvent {
+    pub action: String,
+    pub arg: BitGetLiquidationArg,
+    pub data: Vec<BitGetLiquidationData>,
+}
+
+pub struct BitGetLiquidationChannel {
+    manager: SharedInstrumentManager,
+}
+impl BitGetLiquidationChannel {
+    pub const CHANNEL: &'static str = "liquidation-order";
+    pub fn new(manager: SharedInstrumentManager) -> Self {
+        Self { manager }
+    }
+
+    pub fn parse_message(&self, message: BitGetLiquidationEvent) -> Vec<LiquidationEvent> {
+        let Some(instrument) = lookup_instrument(&self.manager, &message.arg.instType, &message.arg.instId) else {
+            return vec![];
+        };
+        let received_time = Time::now();
+        message
+            .data
+            .into_iter()
+            .map(|data| LiquidationEvent {
+                instrument: instrument.clone(),
+                side: data.side,
+                price: data.price,
+                size: data.size,
+                exchange_time: Time::from_millis(data.ts),
+                received_time,
+            })
+            .collect()
+    }
+}
+
+impl WebsocketMarketFeedChannel for BitGetLiquidationChannel {
+    fn name(&self) -> String {
+        "liquidation".to_string()
+    }
+
+    fn encode_subscribe_instrument(&self, instrument: &InstrumentDetails) -> Value {
+        encode_subscribe(instrument.ty, Self::CHANNEL, &instrument.symbol)
+    }
+}
@@ -11,6 +11,7 @@ use trading_model::{Exchange, InstrumentCode, InstrumentManager, InstrumentType,
 use crate::market::ws::BitGetMarketFeedWsConnection;
 use crate::symbol::{category_to_inst_type, inst_type_to_category, BITGET_INSTRUMENT_LOADER};
 pub mod depth;
+pub mod liquidation;
 pub mod ticker;
 mod ws;
 
@@ -1,20 +1,25 @@
 use crate::market::depth::{BitGetOrderbookChannel, BitGetOrderbookEvent};
+use crate::market::liquidation::{BitGetLiquidationChannel, BitGetLiquidationEvent};
 use crate::urls::BitGetUrls;
 use common::await_or_insert_with;
 use common::ws::WsSession;
 use eyre::{bail, Result};
 use futures::future::LocalBoxFuture;
 use futures::FutureExt;
+use std::collections::VecDeque;
 use tokio_tungstenite::tungstenite::Message;
 use tracing::{error, info, warn};
 use trading_exchange_core::model::SubscriptionManager;
 use trading_exchange_core::model::WebsocketMarketFeedChannel;
 use trading_model::model::{MarketEvent, Network, SharedInstrumentManager};
-use trading_model::{InstrumentDetails, MarketFeedSelector};
+use trading_model::{InstrumentDetails, MarketFeedDepthKind, MarketFeedSelector};
 
 pub struct BitGetMarketFeedWsConnection {
     pub ws: WsSession,
     pub orderbook_channel: BitGetOrderbookChannel,
+    pub liquidation_channel: BitGetLiquidationChannel,
+    /// events parsed from a message that carried more than one
+    pub pending: VecDeque<MarketEvent>,
     pub subs: SubscriptionManager,
     pub urls: BitGetUrls,
     pub reconnect_task: Option<LocalBoxFuture<'static, Result<WsSession>>>,
@@ -30,12 +35,15 @@ impl BitGetMarketFeedWsConnection {
             network
         );
         let ws = WsSession::new();
-        let orderbook_channel = BitGetOrderbookChannel::new(manager);
+        let orderbook_channel = BitGetOrderbookChannel::new(manager.clone());
+        let liquidation_channel = BitGetLiquidationChannel::new(manager);
         let subs = SubscriptionManager::new();
         let urls = BitGetUrls::new();
         Self {
             ws,
             orderbook_channel,
+            liquidation_channel,
+            pending: VecDeque::new(),
             subs,
             dump_raw,
             reconnect_task: None,
@@ -72,12 +80,21 @@ impl BitGetMarketFeedWsConnection {
         Ok(())
     }
     pub fn subscribe(&mut self, instrument: &InstrumentDetails, resources: &[MarketFeedSelector]) -> Result<()> {
+        for res in resources {
+            if let MarketFeedSelector::Depth(depth) = res {
+                self.orderbook_channel
+                    .set_full_depth(depth.match_depth(MarketFeedDepthKind::UPDATE_FULL));
+            }
+        }
         let mut channels: Vec<&dyn WebsocketMarketFeedChannel> = vec![];
         for res in resources {
             match res {
                 MarketFeedSelector::Depth(_) => {
                     channels.push(&self.orderbook_channel as &dyn WebsocketMarketFeedChannel)
                 }
+                MarketFeedSelector::Liquidation => {
+                    channels.push(&self.liquidation_channel as &dyn WebsocketMarketFeedChannel)
+                }
                 _ => bail!("Unsupported resource: {:?}", res),
             }
         }
@@ -102,12 +119,33 @@ impl BitGetMarketFeedWsConnection {
                     .or_else(|| msg.get("action"))
                     .and_then(|e| e.as_str())
                     .unwrap_or_default();
+                let channel = msg
+                    .get("arg")
+                    .and_then(|arg| arg.get("channel"))
+                    .and_then(|c| c.as_str())
+                    .unwrap_or_default();
                 match event {
-                    "snapshot" => {
+                    "snapshot" | "update" if channel == BitGetLiquidationChannel::CHANNEL => {
+                        let liquidation: BitGetLiquidationEvent = serde_json::from_value(msg)?;
+                        self.pending.extend(
+                            self.liquidation_channel
+                                .parse_message(liquidation)
+                                .into_iter()
+                                .map(MarketEvent::Liquidation),
+                        );
+                        return Ok(self.pending.pop_front());
+                    }
+                    "snapshot" | "update" if channel.starts_with("books") => {
                         // TODO: use single struct for all kinds of messages
                         let orderbook: BitGetOrderbookEvent = serde_json::from_value(msg)?;
-                        if let Some(quotes) = self.orderbook_channel.parse_message(orderbook)? {
-                            return Ok(Some(MarketEvent::Quotes(quotes)));
+                        match self.orderbook_channel.parse_message(orderbook) {
+                            Ok(Some(quotes)) => return Ok(Some(MarketEvent::Quotes(quotes))),
+                            Ok(None) => {}
+                            Err(err) => {
+                                // resubscribing on a new connection sends fresh snapshots
+                                warn!("bitget order book out of sync, reconnecting: {}", err);
+                                self.ws.close_immediately();
+                            }
                         }
                     }
                     "subscribe" => {
@@ -128,6 +166,9 @@ impl BitGetMarketFeedWsConnection {
     }
     pub async fn next(&mut self) -> Result<MarketEvent> {
         loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(event);
+            }
             tokio::select! {
                 message = self.ws.next() => {
                     let Some(message) = message else {
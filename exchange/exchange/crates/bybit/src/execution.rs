@@ -5,8 +5,9 @@ use eyre::{Context, Result};
 use itertools::Itertools;
 
 use trading_exchange_core::model::{
-    AccountId, ExecutionConfig, ExecutionRequest, ExecutionResource, ExecutionResponse, ExecutionService,
-    ExecutionServiceBuilder, InstrumentsConfig, RequestCancelOrder, RequestPlaceOrder, SigningApiKeySecret,
+    AccountId, AccountingFeed, ExecutionConfig, ExecutionRequest, ExecutionResource, ExecutionResponse,
+    ExecutionService, ExecutionServiceBuilder, InstrumentsConfig, RequestCancelOrder, RequestPlaceOrder,
+    SigningApiKeySecret,
 };
 use trading_exchange_core::utils::future::interval_conditionally;
 use trading_exchange_core::{
@@ -82,6 +83,7 @@ pub struct BybitExecutionConnection {
     sync_orders_interval: tokio::time::Interval,
     sync_balances_interval: tokio::time::Interval,
     manager: SharedInstrumentManager,
+    source: AccountingFeed,
 }
 
 impl Debug for BybitExecutionConnection {
@@ -108,6 +110,7 @@ impl BybitExecutionConnection {
             ws: BybitPrivateWs::new(account, urls, signing),
             sync_orders_interval: interval_conditionally(5000, execution),
             sync_balances_interval: interval_conditionally(1000, accounting),
+            source: AccountingFeed::new(Exchange::Bybit, account, manager.clone(), accounting),
             manager,
         })
     }
@@ -130,12 +133,16 @@ impl BybitExecutionConnection {
 #[async_trait(?Send)]
 impl ExecutionService for BybitExecutionConnection {
     fn accept(&self, request: &ExecutionRequest) -> bool {
-        matches!(request.get_exchange(), Some(Exchange::Bybit))
+        request.is_for(Exchange::Bybit, self.source.account())
     }
     async fn request(&mut self, request: &ExecutionRequest) -> Result<()> {
         match request {
             ExecutionRequest::PlaceOrder(req) => self.start_new_order(req),
             ExecutionRequest::CancelOrder(req) => self.start_cancel_order(req),
+            ExecutionRequest::AcknowledgeHalt(_) => {
+                self.source.acknowledge_halt();
+                Ok(())
+            }
             _ => unimplemented!("unsupported request: {:?}", request),
         }
     }
@@ -143,10 +150,10 @@ impl ExecutionService for BybitExecutionConnection {
         loop {
             tokio::select! {
                 msg = self.ws.next() => {
-                    return Ok(msg);
+                    return Ok(self.source.on_response(msg));
                 }
                 msg = self.session.next() => {
-                    return Ok(msg);
+                    return Ok(self.source.on_response(msg));
                 }
 
                 _ = self.sync_orders_interval.tick() => {
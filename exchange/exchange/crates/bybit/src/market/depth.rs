@@ -3,17 +3,20 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use serde_with::serde_as;
 use serde_with::DisplayFromStr;
+use std::collections::HashMap;
 use tracing::warn;
 use trading_exchange_core::model::WebsocketMarketFeedChannel;
 use trading_model::core::{Time, TimeStampMs};
 use trading_model::model::{
-    Exchange, InstrumentCategory, InstrumentManagerExt, Quote, Quotes, SharedInstrumentManager, Symbol,
+    BookDelta, BookSequence, BookSequenceRule, BookSyncError, Exchange, InstrumentCategory, InstrumentManagerExt,
+    OrderBookSync, Quotes, SharedInstrumentManager, Symbol,
 };
-use trading_model::Intent;
 
 pub struct BybitOrderbookChannel {
     category: InstrumentCategory,
     manager: Option<SharedInstrumentManager>,
+    depth: u32,
+    books: HashMap<Symbol, OrderBookSync>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -44,47 +47,43 @@ pub struct BybitOrderbookData {
 
 impl BybitOrderbookChannel {
     pub fn new(category: InstrumentCategory, manager: Option<SharedInstrumentManager>) -> Self {
-        Self { category, manager }
+        Self {
+            category,
+            manager,
+            depth: 50,
+            books: HashMap::new(),
+        }
     }
-
-    pub fn process_data(&self, data: BybitOrderbookEvent, is_snapshot: bool) -> Option<Quotes> {
+    /// 200 levels for full-depth books, 50 otherwise
+    pub fn set_depth(&mut self, depth: u32) {
+        self.depth = self.depth.max(depth);
+    }
+    /// a snapshot resets the book, deltas must carry consecutive update ids.
+    /// on a gap the book is dropped until the next snapshot, which the caller requests by resubscribing
+    pub fn parse_message(&mut self, message: BybitOrderbookEvent) -> Result<Option<Quotes>, BookSyncError> {
+        let is_snapshot = match message.ty.as_str() {
+            "snapshot" => true,
+            "delta" => false,
+            _ => {
+                warn!("Unknown message type: {}", message.ty);
+                return Ok(None);
+            }
+        };
+        let data = message.data;
         let instrument =
             self.manager
-                .maybe_lookup_instrument_with_category(Exchange::Bybit, data.data.s, self.category);
-        let mut quotes = Quotes::new(instrument);
-        quotes.exchange_time = Time::from_millis(data.ts);
-        quotes.received_time = Time::now();
-        if is_snapshot {
-            quotes.insert_clear();
-            for (i, (price, quantity)) in data.data.b.iter().copied().enumerate() {
-                quotes.insert_quote(Quote::update_by_level(Intent::Bid, (i + 1) as _, price, quantity));
-            }
-            for (i, (price, quantity)) in data.data.a.iter().copied().enumerate() {
-                quotes.insert_quote(Quote::update_by_level(Intent::Ask, (i + 1) as _, price, quantity));
-            }
-        } else {
-            for (price, quantity) in data.data.b.iter().copied() {
-                quotes.insert_quote(Quote::update_by_price(Intent::Bid, price, quantity));
-            }
-            for (price, quantity) in data.data.a.iter().copied() {
-                quotes.insert_quote(Quote::update_by_price(Intent::Ask, price, quantity));
-            }
+                .maybe_lookup_instrument_with_category(Exchange::Bybit, data.s.clone(), self.category);
+        let book = self
+            .books
+            .entry(data.s)
+            .or_insert_with(|| OrderBookSync::new(instrument, BookSequenceRule::Contiguous));
+        let mut delta = BookDelta::new(BookSequence::single(data.u as u64)).with_levels(data.b, data.a);
+        delta.exchange_time = Time::from_millis(message.ts);
+        // u == 1 means the service restarted and the delta is a full book
+        if is_snapshot || data.u == 1 {
+            return book.on_snapshot(delta).map(Some);
         }
-        Some(quotes)
-    }
-    pub fn parse_message(&self, message: BybitOrderbookEvent) -> eyre::Result<Option<Quotes>> {
-        let is_snapshot;
-        if message.ty == "snapshot" {
-            is_snapshot = true;
-        } else if message.ty == "delta" {
-            is_snapshot = false;
-        } else {
-            warn!("Unknown message type: {}", message.ty);
-            return Ok(None);
-        }
-        let quotes = self.process_data(message, is_snapshot);
-
-        Ok(quotes)
+        book.on_update(delta)
     }
 }
 
@@ -94,8 +93,7 @@ impl WebsocketMarketFeedChannel for BybitOrderbookChannel {
     }
 
     fn encode_subscribe_symbol(&self, symbol: &str) -> Value {
-        let depth = 50;
-        let payload = format!("orderbook.{}.{}", depth, symbol);
+        let payload = format!("orderbook.{}.{}", self.depth, symbol);
         let id = next_request_id().to_string();
         encode_subscribe(&id, &payload)
     }
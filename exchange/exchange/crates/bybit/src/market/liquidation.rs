@@ -0,0 +1,72 @@
+use crate::{encode_subscribe, next_request_id};
+use serde::Deserialize;
+use serde_json::Value;
+use serde_with::serde_as;
+use serde_with::DisplayFromStr;
+use trading_exchange_core::model::WebsocketMarketFeedChannel;
+use trading_model::core::Time;
+use trading_model::model::{
+    Exchange, InstrumentCategory, InstrumentManagerExt, LiquidationEvent, SharedInstrumentManager, Symbol,
+};
+use trading_model::Side;
+
+#[derive(Deserialize, Debug)]
+#[allow(non_snake_case, dead_code)]
+pub struct BybitLiquidation {
+    topic: String,
+    ts: i64,
+    data: BybitLiquidationData,
+}
+
+#[serde_as]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BybitLiquidationData {
+    updated_time: i64,
+    symbol: Symbol,
+    /// side of the liquidated position
+    side: Side,
+    #[serde_as(as = "DisplayFromStr")]
+    size: f64,
+    #[serde_as(as = "DisplayFromStr")]
+    price: f64,
+}
+
+pub struct BybitLiquidationChannel {
+    category: InstrumentCategory,
+    manager: Option<SharedInstrumentManager>,
+}
+
+impl BybitLiquidationChannel {
+    pub fn new(category: InstrumentCategory, manager: Option<SharedInstrumentManager>) -> Self {
+        Self { category, manager }
+    }
+
+    pub fn parse_bybit_liquidation(&self, msg: BybitLiquidation) -> LiquidationEvent {
+        let data = msg.data;
+        let instrument =
+            self.manager
+                .maybe_lookup_instrument_with_category(Exchange::Bybit, data.symbol, self.category);
+        LiquidationEvent {
+            instrument,
+            // a liquidated long is closed by a forced sell
+            side: data.side.opposite(),
+            price: data.price,
+            size: data.size,
+            exchange_time: Time::from_millis(data.updated_time),
+            received_time: Time::now(),
+        }
+    }
+}
+
+impl WebsocketMarketFeedChannel for BybitLiquidationChannel {
+    fn name(&self) -> String {
+        "liquidation".to_string()
+    }
+
+    fn encode_subscribe_symbol(&self, symbol: &str) -> Value {
+        let payload = format!("liquidation.{}", symbol);
+        let id = next_request_id().to_string();
+        encode_subscribe(&id, &payload)
+    }
+}
@@ -13,6 +13,8 @@ use crate::market::ws::BybitMarketWsConnection;
 use crate::symbol::BYBIT_INSTRUMENT_LOADER;
 
 mod depth;
+mod liquidation;
+mod ticker;
 mod trade;
 
 mod ws;
@@ -0,0 +1,101 @@
+use crate::{encode_subscribe, next_request_id};
+use serde::Deserialize;
+use serde_json::Value;
+use serde_with::serde_as;
+use serde_with::DisplayFromStr;
+use std::collections::HashMap;
+use trading_exchange_core::model::WebsocketMarketFeedChannel;
+use trading_model::core::Time;
+use trading_model::model::{
+    DerivativesContextEvent, Exchange, InstrumentCategory, InstrumentManagerExt, SharedInstrumentManager, Symbol,
+};
+
+#[derive(Deserialize, Debug)]
+#[allow(non_snake_case, dead_code)]
+pub struct BybitTicker {
+    topic: String,
+    #[serde(rename = "type")]
+    ty: String,
+    ts: i64,
+    data: BybitTickerData,
+}
+
+/// deltas only carry the fields that changed
+#[serde_as]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BybitTickerData {
+    symbol: Symbol,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    mark_price: Option<f64>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    index_price: Option<f64>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    open_interest: Option<f64>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    funding_rate: Option<f64>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    next_funding_time: Option<i64>,
+}
+
+pub struct BybitTickerChannel {
+    category: InstrumentCategory,
+    manager: Option<SharedInstrumentManager>,
+    contexts: HashMap<Symbol, DerivativesContextEvent>,
+}
+
+impl BybitTickerChannel {
+    pub fn new(category: InstrumentCategory, manager: Option<SharedInstrumentManager>) -> Self {
+        Self {
+            category,
+            manager,
+            contexts: HashMap::new(),
+        }
+    }
+
+    /// merges the update into the last known context of the symbol and returns the full context
+    pub fn parse_bybit_ticker(&mut self, msg: BybitTicker) -> DerivativesContextEvent {
+        let data = msg.data;
+        let update = DerivativesContextEvent {
+            instrument: self.manager.maybe_lookup_instrument_with_category(
+                Exchange::Bybit,
+                data.symbol.clone(),
+                self.category,
+            ),
+            mark_price: data.mark_price,
+            index_price: data.index_price,
+            open_interest: data.open_interest,
+            funding_rate: data.funding_rate,
+            next_funding_time: data.next_funding_time.map(Time::from_millis),
+            exchange_time: Time::from_millis(msg.ts),
+            received_time: Time::now(),
+        };
+        if msg.ty == "snapshot" {
+            self.contexts.insert(data.symbol, update.clone());
+            return update;
+        }
+        let context = self
+            .contexts
+            .entry(data.symbol)
+            .or_insert_with(|| DerivativesContextEvent::empty(update.instrument.clone()));
+        context.merge(&update);
+        context.clone()
+    }
+}
+
+impl WebsocketMarketFeedChannel for BybitTickerChannel {
+    fn name(&self) -> String {
+        "tickers".to_string()
+    }
+
+    fn encode_subscribe_symbol(&self, symbol: &str) -> Value {
+        let payload = format!("tickers.{}", symbol);
+        let id = next_request_id().to_string();
+        encode_subscribe(&id, &payload)
+    }
+}
@@ -1,4 +1,6 @@
 use crate::market::depth::{BybitOrderbookChannel, BybitOrderbookEvent};
+use crate::market::liquidation::{BybitLiquidation, BybitLiquidationChannel};
+use crate::market::ticker::{BybitTicker, BybitTickerChannel};
 use crate::market::trade::{BybitTrade, BybitTradeChannel};
 use crate::market::WebsocketMarketFeedChannel;
 use crate::urls::BybitUrls;
@@ -8,15 +10,17 @@ use eyre::{bail, Result};
 use futures::future::LocalBoxFuture;
 use futures::FutureExt;
 use tokio_tungstenite::tungstenite::Message;
-use tracing::error;
+use tracing::{error, warn};
 use trading_exchange_core::model::SubscriptionManager;
 use trading_model::model::{InstrumentCategory, MarketEvent, Network, SharedInstrumentManager};
-use trading_model::MarketFeedSelector;
+use trading_model::{MarketFeedDepthKind, MarketFeedSelector};
 
 pub struct BybitMarketWsConnection {
     pub ws: WsSession,
     pub orderbook_channel: BybitOrderbookChannel,
     pub trade_channel: BybitTradeChannel,
+    pub liquidation_channel: BybitLiquidationChannel,
+    pub ticker_channel: BybitTickerChannel,
     pub subs: SubscriptionManager,
     pub category: InstrumentCategory,
     pub urls: BybitUrls,
@@ -34,12 +38,16 @@ impl BybitMarketWsConnection {
         let ws = WsSession::new();
         let orderbook_channel = BybitOrderbookChannel::new(category, manager.clone());
         let trade_channel = BybitTradeChannel::new(category, manager.clone());
+        let liquidation_channel = BybitLiquidationChannel::new(category, manager.clone());
+        let ticker_channel = BybitTickerChannel::new(category, manager.clone());
         let subs = SubscriptionManager::new();
         let urls = BybitUrls::new(network);
         Self {
             ws,
             orderbook_channel,
             trade_channel,
+            liquidation_channel,
+            ticker_channel,
             subs,
             category,
             dump_raw,
@@ -76,6 +84,11 @@ impl BybitMarketWsConnection {
         Ok(())
     }
     pub fn subscribe(&mut self, symbol: &str, resources: &[MarketFeedSelector]) -> Result<()> {
+        for res in resources {
+            if matches!(res, MarketFeedSelector::Depth(d) if d.match_depth(MarketFeedDepthKind::UPDATE_FULL)) {
+                self.orderbook_channel.set_depth(200);
+            }
+        }
         let mut channels: Vec<&dyn WebsocketMarketFeedChannel> = vec![];
         for res in resources {
             match res {
@@ -83,6 +96,14 @@ impl BybitMarketWsConnection {
                 MarketFeedSelector::Depth(_) => {
                     channels.push(&self.orderbook_channel as &dyn WebsocketMarketFeedChannel)
                 }
+                // liquidations are only published for derivatives
+                MarketFeedSelector::Liquidation if self.category == InstrumentCategory::LinearDerivative => {
+                    channels.push(&self.liquidation_channel as &dyn WebsocketMarketFeedChannel)
+                }
+                // spot tickers carry no mark, index or open interest
+                MarketFeedSelector::DerivativesContext if self.category == InstrumentCategory::LinearDerivative => {
+                    channels.push(&self.ticker_channel as &dyn WebsocketMarketFeedChannel)
+                }
                 _ => bail!("Unsupported resource: {:?}", res),
             }
         }
@@ -100,10 +121,24 @@ impl BybitMarketWsConnection {
                     for trade in self.trade_channel.parse_bybit_trade_update(public_trade)? {
                         return Ok(Some(MarketEvent::Trade(trade)));
                     }
+                } else if message.contains("\"topic\":\"liquidation.") {
+                    let liquidation: BybitLiquidation = serde_json::from_str(&message)?;
+                    let liquidation = self.liquidation_channel.parse_bybit_liquidation(liquidation);
+                    return Ok(Some(MarketEvent::Liquidation(liquidation)));
+                } else if message.contains("\"topic\":\"tickers.") {
+                    let ticker: BybitTicker = serde_json::from_str(&message)?;
+                    let context = self.ticker_channel.parse_bybit_ticker(ticker);
+                    return Ok(Some(MarketEvent::DerivativesContext(context)));
                 } else if message.contains("orderbook") {
                     let orderbook: BybitOrderbookEvent = serde_json::from_str(&message)?;
-                    if let Some(quotes) = self.orderbook_channel.parse_message(orderbook)? {
-                        return Ok(Some(MarketEvent::Quotes(quotes)));
+                    match self.orderbook_channel.parse_message(orderbook) {
+                        Ok(Some(quotes)) => return Ok(Some(MarketEvent::Quotes(quotes))),
+                        Ok(None) => {}
+                        Err(err) => {
+                            // resubscribing on a new connection sends fresh snapshots
+                            warn!("bybit order book out of sync, reconnecting: {}", err);
+                            self.ws.close_immediately();
+                        }
                     }
                 }
             }
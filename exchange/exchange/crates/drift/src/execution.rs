@@ -10,9 +10,9 @@ use std::fmt::Debug;
 use std::sync::Arc;
 use tracing::{info, warn};
 use trading_exchange_core::model::{
-    AccountId, ExecutionConfig, ExecutionRequest, ExecutionResource, ExecutionResponse, ExecutionService,
-    ExecutionServiceBuilder, InstrumentsConfig, Order, OrderLid, OrderStatus, Position, RequestCancelOrder,
-    RequestPlaceOrder, SigningAddressPrivateKey, SyncOrders, TimeInForce, UpdatePositions,
+    AccountId, AccountingFeed, ExecutionConfig, ExecutionRequest, ExecutionResource, ExecutionResponse,
+    ExecutionService, ExecutionServiceBuilder, InstrumentsConfig, Order, OrderLid, OrderStatus, Position,
+    RequestCancelOrder, RequestPlaceOrder, SigningAddressPrivateKey, SyncOrders, TimeInForce, UpdatePositions,
 };
 use trading_exchange_core::utils::future::interval_conditionally;
 use trading_exchange_core::{
@@ -56,6 +56,7 @@ impl DriftExecutionServiceBuilder {
             accounting,
             execution,
             js_sdk,
+            source: AccountingFeed::new(Exchange::Drift, config.account, manager.clone(), accounting),
             manager,
             account: config.account,
             response_rx: post_lookup_rx,
@@ -87,6 +88,7 @@ impl_service_builder_for_execution_service_builder!(DriftExecutionServiceBuilder
 pub struct DriftExecutionConnection {
     js_sdk: DriftJsClient,
     manager: SharedInstrumentManager,
+    source: AccountingFeed,
     // handles delayed order rejection
     response_rx: tokio::sync::mpsc::Receiver<ExecutionResponse>,
     response_tx: tokio::sync::mpsc::Sender<ExecutionResponse>,
@@ -309,13 +311,17 @@ impl DriftExecutionConnection {
 #[async_trait(?Send)]
 impl ExecutionService for DriftExecutionConnection {
     fn accept(&self, request: &ExecutionRequest) -> bool {
-        request.get_exchange() == Some(Exchange::Drift)
+        request.is_for(Exchange::Drift, self.source.account())
     }
 
     async fn request(&mut self, request: &ExecutionRequest) -> Result<()> {
         match request {
             ExecutionRequest::PlaceOrder(order) => self.new_order(order.clone()).await,
             ExecutionRequest::CancelOrder(order) => self.cancel_order(order.clone()).await,
+            ExecutionRequest::AcknowledgeHalt(_) => {
+                self.source.acknowledge_halt();
+                Ok(())
+            }
             _ => unimplemented!("unsupported request: {:?}", request),
         }
     }
@@ -324,10 +330,10 @@ impl ExecutionService for DriftExecutionConnection {
         loop {
             tokio::select! {
                 msg = self.requests.next(), if !self.requests.is_empty() => {
-                    return msg.expect("Never be empty");
+                    return Ok(self.source.on_response(msg.expect("Never be empty")?));
                 }
                 post = self.response_rx.recv() => {
-                    return Ok(post.expect("Never be empty"));
+                    return Ok(self.source.on_response(post.expect("Never be empty")));
                 }
                 _ = self.get_positions_interval.tick() => {
                     self.get_positions()?;
@@ -12,7 +12,8 @@ use tokio::time::Instant;
 use tokio_tungstenite::tungstenite::Message;
 use tracing::{debug, error, info, warn};
 use trading_exchange_core::model::{MarketFeedService, SubscriptionManager};
-use trading_model::model::{InstrumentDetails, Quote, Quotes};
+use trading_model::core::Time;
+use trading_model::model::{DerivativesContextEvent, InstrumentDetails, Quote, Quotes};
 use trading_model::model::{Intent, MarketEvent};
 
 pub struct DriftMarketFeedDepthManager {
@@ -44,6 +45,9 @@ pub struct DriftMarketFeedDepthConnection {
     subs: SubscriptionManager,
     last_heartbeat: Instant,
     instrument: Arc<InstrumentDetails>,
+    emit_quotes: bool,
+    emit_context: bool,
+    pending: Option<MarketEvent>,
 }
 
 const HEARTBEAT_INTERVAL: u64 = 10;
@@ -61,12 +65,20 @@ impl DriftMarketFeedDepthConnection {
             subs: SubscriptionManager::new(),
             last_heartbeat: Instant::now(),
             instrument,
+            emit_quotes: true,
+            emit_context: false,
+            pending: None,
         };
 
         this.subscribe()?;
 
         Ok(this)
     }
+    /// the orderbook channel also carries the oracle, so both resources share one connection
+    pub fn set_resources(&mut self, quotes: bool, context: bool) {
+        self.emit_quotes = quotes;
+        self.emit_context = context;
+    }
     pub fn subscribe(&mut self) -> Result<()> {
         let symbol = &self.instrument.instrument_symbol;
         let depth_msg = subscribe_drift_depth_json(symbol.symbol.as_str(), Some(5));
@@ -110,6 +122,13 @@ impl DriftMarketFeedDepthConnection {
                         _ if channel.contains("orderbook") => {
                             let orderbook_data = value.get("data").and_then(Value::as_str).unwrap();
                             let orderbook = serde_json::from_str::<L2Orderbook>(orderbook_data).unwrap();
+                            let context = self
+                                .emit_context
+                                .then(|| parse_derivatives_context(&self.instrument, &orderbook));
+                            if !self.emit_quotes {
+                                return Ok(context.map(MarketEvent::DerivativesContext));
+                            }
+                            self.pending = context.map(MarketEvent::DerivativesContext);
                             return Ok(Some(parse_l2_orderbook(&self.instrument, orderbook)?.into()));
                         }
                         _ => {
@@ -131,6 +150,9 @@ impl DriftMarketFeedDepthConnection {
 #[async_trait(? Send)]
 impl MarketFeedService for DriftMarketFeedDepthConnection {
     async fn next(&mut self) -> Result<MarketEvent> {
+        if let Some(event) = self.pending.take() {
+            return Ok(event);
+        }
         loop {
             tokio::select! {
                 msg = self.ws.next() => {
@@ -196,3 +218,13 @@ pub fn parse_l2_orderbook(instrument: &InstrumentDetails, orderbook: L2Orderbook
     }
     Ok(quotes.into())
 }
+
+/// drift publishes neither an index nor open interest over the dlob, the oracle stands in for the index
+pub fn parse_derivatives_context(instrument: &InstrumentDetails, orderbook: &L2Orderbook) -> DerivativesContextEvent {
+    let mut context = DerivativesContextEvent::empty(instrument.to_simple_code());
+    context.mark_price = orderbook.mark_price.map(|x| instrument.quote.from_wire(x as f64));
+    context.index_price = orderbook.oracle.map(|x| instrument.quote.from_wire(x as f64));
+    context.exchange_time = Time::now();
+    context.received_time = Time::now();
+    context
+}
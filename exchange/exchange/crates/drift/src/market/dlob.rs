@@ -141,6 +141,11 @@ pub struct L2Orderbook {
     /// sorted asks, lowest first
     pub asks: Vec<L2Level>,
     pub slot: u64,
+    /// oracle price, only pushed over the websocket
+    #[serde(default, deserialize_with = "parse_opt_int_str")]
+    pub oracle: Option<i64>,
+    #[serde(default, rename = "markPrice", deserialize_with = "parse_opt_int_str")]
+    pub mark_price: Option<i64>,
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -178,3 +183,17 @@ where
     let s: &str = de::Deserialize::deserialize(deserializer)?;
     s.parse().map_err(de::Error::custom)
 }
+
+fn parse_opt_int_str<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    // the dlob server sends these either as numbers or as strings
+    let value: Option<serde_json::Value> = de::Deserialize::deserialize(deserializer)?;
+    match value {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(serde_json::Value::Number(n)) => Ok(n.as_i64()),
+        Some(serde_json::Value::String(s)) => s.parse().map(Some).map_err(de::Error::custom),
+        Some(other) => Err(de::Error::custom(format!("invalid integer: {}", other))),
+    }
+}
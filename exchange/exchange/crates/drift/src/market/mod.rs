@@ -69,15 +69,20 @@ impl DriftMarketFeedConnection {
     }
     pub fn subscribe(&mut self, symbol: &InstrumentSymbol, resources: &[MarketFeedSelector]) -> Result<()> {
         let instrument = self.manager.get_by_instrument_symbol(symbol).unwrap();
+        let mut depth = false;
+        let mut context = false;
         for res in resources {
             match res {
-                MarketFeedSelector::Depth(..) => {
-                    self.depth
-                        .add_channel(DriftMarketFeedDepthConnection::new(instrument.clone())?);
-                }
+                MarketFeedSelector::Depth(..) => depth = true,
+                MarketFeedSelector::DerivativesContext if symbol.symbol.ends_with("PERP") => context = true,
                 _ => bail!("unsupported resource: {}", res),
             }
         }
+        if depth || context {
+            let mut channel = DriftMarketFeedDepthConnection::new(instrument.clone())?;
+            channel.set_resources(depth, context);
+            self.depth.add_channel(channel);
+        }
 
         Ok(())
     }
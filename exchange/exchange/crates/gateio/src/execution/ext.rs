@@ -18,6 +18,10 @@ impl Sink<ExecutionRequest> for GateioExecutionConnection {
         match item {
             ExecutionRequest::PlaceOrder(req) => self.start_new_order(&req),
             ExecutionRequest::CancelOrder(req) => self.start_cancel_order(&req),
+            ExecutionRequest::AcknowledgeHalt(_) => {
+                self.source.acknowledge_halt();
+                Ok(())
+            }
             _ => unimplemented!("unsupported request: {:?}", item),
         }
     }
@@ -48,7 +52,7 @@ impl Stream for GateioExecutionConnection {
             self.session.send_query_user_assets(manager);
         }
         if let Poll::Ready(msg) = self.session.poll_next(cx) {
-            return Poll::Ready(Some(Ok(msg)));
+            return Poll::Ready(Some(Ok(self.source.on_response(msg))));
         }
 
         Poll::Pending
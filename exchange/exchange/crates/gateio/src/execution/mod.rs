@@ -13,8 +13,9 @@ use crate::symbol::GATEIO_INSTRUMENT_LOADER;
 use crate::urls::GateioUrls;
 use crate::ExchangeIsGateioExt;
 use trading_exchange_core::model::{
-    AccountId, ExecutionConfig, ExecutionRequest, ExecutionResource, ExecutionResponse, ExecutionService,
-    ExecutionServiceBuilder, InstrumentsConfig, RequestCancelOrder, RequestPlaceOrder, SigningApiKeySecret,
+    AccountId, AccountingFeed, ExecutionConfig, ExecutionRequest, ExecutionResource, ExecutionResponse,
+    ExecutionService, ExecutionServiceBuilder, InstrumentsConfig, RequestCancelOrder, RequestPlaceOrder,
+    SigningApiKeySecret,
 };
 use trading_exchange_core::utils::future::interval_conditionally;
 use trading_exchange_core::{
@@ -91,6 +92,7 @@ pub struct GateioExecutionConnection {
     sync_orders_interval: tokio::time::Interval,
     sync_balances_interval: tokio::time::Interval,
     manager: SharedInstrumentManager,
+    source: AccountingFeed,
     accounting: bool,
     execution: bool,
 }
@@ -132,6 +134,7 @@ impl GateioExecutionConnection {
             },
             sync_orders_interval: interval_conditionally(1000, accounting || execution),
             sync_balances_interval: interval_conditionally(1000, accounting),
+            source: AccountingFeed::new(exchange, account, manager.clone(), accounting),
             manager,
             accounting,
             execution,
@@ -157,8 +160,7 @@ impl GateioExecutionConnection {
 #[async_trait(?Send)]
 impl ExecutionService for GateioExecutionConnection {
     fn accept(&self, request: &ExecutionRequest) -> bool {
-        let exchange = self.exchange;
-        request.get_exchange() == Some(exchange)
+        request.is_for(self.exchange, self.source.account())
     }
     async fn request(&mut self, request: &ExecutionRequest) -> Result<()> {
         SinkExt::send(self, request.clone()).await
@@ -0,0 +1,172 @@
+use futures::FutureExt;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use serde_with::serde_as;
+use serde_with::DisplayFromStr;
+use std::sync::Arc;
+
+use trading_exchange_core::model::RestSnapshotBooks;
+use trading_model::core::{Time, TimeStampMs};
+use trading_model::model::{
+    BookDelta, BookSequence, BookSequenceRule, Exchange, InstrumentManagerExt, Quotes, SharedInstrumentManager, Symbol,
+};
+
+/// `spot.order_book_update` result, ids of consecutive updates overlap on `U..=u`
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GateioSpotDepthUpdate {
+    pub t: TimeStampMs,
+    pub s: Symbol,
+    #[serde(rename = "U")]
+    pub first_update_id: u64,
+    #[serde(rename = "u")]
+    pub last_update_id: u64,
+    #[serde_as(as = "Vec<(DisplayFromStr, DisplayFromStr)>")]
+    pub b: Vec<(f64, f64)>,
+    #[serde_as(as = "Vec<(DisplayFromStr, DisplayFromStr)>")]
+    pub a: Vec<(f64, f64)>,
+}
+
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GateioContractLevel {
+    #[serde_as(as = "DisplayFromStr")]
+    pub p: f64,
+    /// number of contracts
+    pub s: i64,
+}
+
+/// `futures.order_book_update` result, sizes are in contracts
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GateioPerpetualDepthUpdate {
+    pub t: TimeStampMs,
+    pub s: Symbol,
+    #[serde(rename = "U")]
+    pub first_update_id: u64,
+    #[serde(rename = "u")]
+    pub last_update_id: u64,
+    pub b: Vec<GateioContractLevel>,
+    pub a: Vec<GateioContractLevel>,
+}
+
+/// response of `/spot/order_book?with_id=true`
+#[serde_as]
+#[derive(Deserialize, Debug)]
+struct GateioSpotDepthSnapshot {
+    id: u64,
+    current: TimeStampMs,
+    #[serde_as(as = "Vec<(DisplayFromStr, DisplayFromStr)>")]
+    bids: Vec<(f64, f64)>,
+    #[serde_as(as = "Vec<(DisplayFromStr, DisplayFromStr)>")]
+    asks: Vec<(f64, f64)>,
+}
+
+/// response of `/futures/usdt/order_book?with_id=true`, `current` is in seconds
+#[derive(Deserialize, Debug)]
+struct GateioPerpetualDepthSnapshot {
+    id: u64,
+    current: f64,
+    bids: Vec<GateioContractLevel>,
+    asks: Vec<GateioContractLevel>,
+}
+
+/// full-depth books maintained from `order_book_update` diffs on top of the REST snapshot
+pub struct GateioDepthBookChannel {
+    exchange: Exchange,
+    manager: SharedInstrumentManager,
+    books: RestSnapshotBooks,
+}
+
+impl GateioDepthBookChannel {
+    pub fn new(exchange: Exchange, manager: SharedInstrumentManager) -> Self {
+        let fetch_manager = manager.clone();
+        let fetcher = Arc::new(move |symbol: Symbol| {
+            let manager = fetch_manager.clone();
+            async move {
+                let delta = match exchange {
+                    Exchange::GateioPerpetual => {
+                        let url = format!(
+                            "https://api.gateio.ws/api/v4/futures/usdt/order_book?contract={}&limit=100&with_id=true",
+                            symbol
+                        );
+                        let text = reqwest::get(&url).await?.text().await?;
+                        let snapshot: GateioPerpetualDepthSnapshot = serde_json::from_str(&text)?;
+                        let instrument = manager.get_result(&(exchange, symbol.clone()))?;
+                        let contracts = |levels: Vec<GateioContractLevel>| {
+                            levels
+                                .into_iter()
+                                .map(|x| (x.p, instrument.size.multiply(x.s as f64)))
+                                .collect::<Vec<_>>()
+                        };
+                        let mut delta = BookDelta::new(BookSequence::single(snapshot.id))
+                            .with_levels(contracts(snapshot.bids), contracts(snapshot.asks));
+                        delta.exchange_time = Time::from_secs_f(snapshot.current);
+                        delta
+                    }
+                    _ => {
+                        let url = format!(
+                            "https://api.gateio.ws/api/v4/spot/order_book?currency_pair={}&limit=100&with_id=true",
+                            symbol
+                        );
+                        let text = reqwest::get(&url).await?.text().await?;
+                        let snapshot: GateioSpotDepthSnapshot = serde_json::from_str(&text)?;
+                        let mut delta =
+                            BookDelta::new(BookSequence::single(snapshot.id)).with_levels(snapshot.bids, snapshot.asks);
+                        delta.exchange_time = Time::from_millis(snapshot.current);
+                        delta
+                    }
+                };
+                Ok(delta)
+            }
+            .boxed()
+        });
+        Self {
+            exchange,
+            manager,
+            books: RestSnapshotBooks::new(BookSequenceRule::Overlapping, fetcher),
+        }
+    }
+    pub fn encode_subscribe(&self, symbol: &str) -> String {
+        let time = Time::now().secs() as u64;
+        let (channel, payload) = match self.exchange {
+            Exchange::GateioSpot | Exchange::GateioMargin => ("spot.order_book_update", json!([symbol, "100ms"])),
+            Exchange::GateioPerpetual => ("futures.order_book_update", json!([symbol, "100ms", "100"])),
+            _ => unreachable!(),
+        };
+        json!(
+            {
+                "time": time,
+                "channel": channel,
+                "event": "subscribe",
+                "payload": payload
+            }
+        )
+        .to_string()
+    }
+    pub fn parse_spot_depth_update(&mut self, update: GateioSpotDepthUpdate, received_time: Time) -> Option<Quotes> {
+        let instrument = self.manager.maybe_lookup_instrument(self.exchange, update.s.clone());
+        let mut delta = BookDelta::new(BookSequence::range(update.first_update_id, update.last_update_id))
+            .with_levels(update.b, update.a);
+        delta.exchange_time = Time::from_millis(update.t);
+        delta.received_time = received_time;
+        self.books.on_update(update.s, instrument, delta)
+    }
+    pub fn parse_perpetual_depth_update(
+        &mut self,
+        update: GateioPerpetualDepthUpdate,
+        received_time: Time,
+    ) -> eyre::Result<Option<Quotes>> {
+        let instrument = self.manager.get_result(&(self.exchange, update.s.clone()))?;
+        let contracts = |levels: Vec<GateioContractLevel>| {
+            levels
+                .into_iter()
+                .map(|x| (x.p, instrument.size.multiply(x.s as f64)))
+                .collect::<Vec<_>>()
+        };
+        let mut delta = BookDelta::new(BookSequence::range(update.first_update_id, update.last_update_id))
+            .with_levels(contracts(update.b), contracts(update.a));
+        delta.exchange_time = Time::from_millis(update.t);
+        delta.received_time = received_time;
+        Ok(self.books.on_update(update.s, instrument.code_simple.clone(), delta))
+    }
+}
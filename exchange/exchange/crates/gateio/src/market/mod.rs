@@ -1,6 +1,7 @@
 //! Gateio exchange
 
 pub mod depth;
+pub mod depth_book;
 pub mod msg;
 pub mod parser;
 pub mod ticker;
@@ -137,6 +138,10 @@ impl GateioMarketFeedConnection {
                         let value = self.converter.depth_spot.encode_subscribe(&symbol.symbol);
                         self.subs.register_subscription_symbol(symbol.symbol.clone(), value);
                     }
+                    MarketFeedSelector::Depth(d) if d.match_depth(MarketFeedDepthKind::UPDATE_FULL) => {
+                        let value = self.converter.depth_book.encode_subscribe(&symbol.symbol);
+                        self.subs.register_subscription_symbol(symbol.symbol.clone(), value);
+                    }
                     _ => {
                         bail!("Unsupported resource: {:?}", res);
                     }
@@ -1,6 +1,7 @@
 use serde::Deserialize;
 
 use crate::market::depth::{GateioPerpetualDepthMessage, GateioSpotDepthMessage};
+use crate::market::depth_book::{GateioPerpetualDepthUpdate, GateioSpotDepthUpdate};
 use crate::market::ticker::GateioBookTicker;
 use crate::market::trade::{GateioPerpetualTrade, GateioSpotTrade};
 
@@ -11,6 +12,10 @@ pub enum GateioMarketFeedMessage {
     SpotOrderBook(GateioSpotDepthMessage),
     #[serde(rename = "futures.order_book")]
     PerpetualOrderBook(GateioPerpetualDepthMessage),
+    #[serde(rename = "spot.order_book_update")]
+    SpotOrderBookUpdate(GateioSpotDepthUpdate),
+    #[serde(rename = "futures.order_book_update")]
+    PerpetualOrderBookUpdate(GateioPerpetualDepthUpdate),
     #[serde(rename = "spot.trades")]
     SpotTrade(GateioSpotTrade),
     #[serde(rename = "perpetual.trades")]
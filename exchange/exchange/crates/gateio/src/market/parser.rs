@@ -4,6 +4,7 @@ use trading_model::model::{Exchange, MarketEvent, SharedInstrumentManager, Symbo
 use trading_model::wire::PacketStr;
 
 use crate::market::depth::GateioDepthChannel;
+use crate::market::depth_book::GateioDepthBookChannel;
 use crate::market::msg::{GateioMarketFeedMessage, GateioMarketFeedMessageOuter};
 use crate::market::ticker::GateioBookTickerChannel;
 use crate::market::trade::GateioTradeChannel;
@@ -11,6 +12,7 @@ use crate::market::trade::GateioTradeChannel;
 pub struct GateioMarketParser {
     pub(crate) symbol: Option<Symbol>,
     pub(crate) depth_spot: GateioDepthChannel,
+    pub(crate) depth_book: GateioDepthBookChannel,
 
     pub(crate) trade: GateioTradeChannel,
     pub(crate) book_ticker: GateioBookTickerChannel,
@@ -20,6 +22,7 @@ impl GateioMarketParser {
         Self {
             symbol: None,
             depth_spot: GateioDepthChannel::new(exchange, manager.clone()),
+            depth_book: GateioDepthBookChannel::new(exchange, manager.clone()),
             trade: GateioTradeChannel::new(exchange, manager.clone()),
             book_ticker: GateioBookTickerChannel::new(exchange, Some(manager.clone())),
         }
@@ -27,7 +30,7 @@ impl GateioMarketParser {
     pub fn set_symbol(&mut self, symbol: Symbol) {
         self.symbol = Some(symbol);
     }
-    pub fn parse_message(&self, pkt: PacketStr) -> Result<Option<MarketEvent>> {
+    pub fn parse_message(&mut self, pkt: PacketStr) -> Result<Option<MarketEvent>> {
         let msg: GateioMarketFeedMessageOuter = serde_json::from_str(&pkt)?;
         match msg.result {
             GateioMarketFeedMessage::SpotOrderBook(update) => {
@@ -42,6 +45,16 @@ impl GateioMarketParser {
 
                 Ok(Some(MarketEvent::Quotes(quotes)))
             }
+            GateioMarketFeedMessage::SpotOrderBookUpdate(update) => {
+                let quotes = self.depth_book.parse_spot_depth_update(update, pkt.received_time);
+                Ok(quotes.map(MarketEvent::Quotes))
+            }
+            GateioMarketFeedMessage::PerpetualOrderBookUpdate(update) => {
+                let quotes = self
+                    .depth_book
+                    .parse_perpetual_depth_update(update, pkt.received_time)?;
+                Ok(quotes.map(MarketEvent::Quotes))
+            }
             GateioMarketFeedMessage::SpotTrade(trade) => {
                 let trade = self.trade.parse_spot_trade(trade, pkt.received_time)?;
                 Ok(Some(MarketEvent::Trade(trade)))
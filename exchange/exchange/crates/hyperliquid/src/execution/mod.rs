@@ -12,15 +12,14 @@ use tracing::*;
 use crate::execution::ws::HyperliquidExecutionWs;
 use crate::utils::create_order_lid_str;
 use trading_exchange_core::model::{
-    AccountingUpdateOrder, ExecutionConfig, ExecutionRequest, ExecutionResource, ExecutionResponse, ExecutionService,
-    ExecutionServiceBuilder, InstrumentsConfig, RequestCancelOrder, RequestPlaceOrder, SigningAddressPrivateKey,
-    SourceAccount, UpdateBook, UpdateOrder,
+    AccountId, AccountingFeed, ExecutionConfig, ExecutionRequest, ExecutionResource, ExecutionResponse,
+    ExecutionService, ExecutionServiceBuilder, InstrumentsConfig, RequestCancelOrder, RequestPlaceOrder,
+    SigningAddressPrivateKey, UpdateBook,
 };
 use trading_exchange_core::utils::future::{interval, interval_conditionally};
 use trading_exchange_core::{
     impl_service_async_for_execution_service, impl_service_builder_for_execution_service_builder,
 };
-use trading_model::core::Time;
 use trading_model::{DurationMs, Exchange, SharedInstrumentManager, Symbol};
 
 #[derive(Debug, Clone)]
@@ -62,7 +61,8 @@ impl HyperliquidExecutionServiceBuilder {
             })
             .await?;
         let ws = HyperliquidExecutionWs::new(shared.account, manager.clone(), shared.network, signing.address.clone());
-        let conn = HyperliquidExecutionConnection::with_ws(manager, rest, ws, accounting, interval_ms).await?;
+        let conn =
+            HyperliquidExecutionConnection::with_ws(shared.account, manager, rest, ws, accounting, interval_ms).await?;
         Ok(conn)
     }
 }
@@ -85,7 +85,8 @@ pub struct HyperliquidExecutionConnection {
     ws: HyperliquidExecutionWs,
     rest: HyperliquidRest,
     manager: SharedInstrumentManager,
-    account: SourceAccount,
+    account: AccountingFeed,
+    /// the book of the initial snapshot, sent as the first response
     update_positions: Option<UpdateBook>,
     open_orders_interval: tokio::time::Interval,
     query_balances_interval: tokio::time::Interval,
@@ -100,6 +101,7 @@ impl Debug for HyperliquidExecutionConnection {
 
 impl HyperliquidExecutionConnection {
     async fn with_ws(
+        account: AccountId,
         manager: SharedInstrumentManager,
         session: HyperliquidRest,
         ws: HyperliquidExecutionWs,
@@ -110,7 +112,9 @@ impl HyperliquidExecutionConnection {
             rest: session,
             ws,
             manager: manager.clone(),
-            account: SourceAccount::empty_no_desync(Exchange::Hyperliquid, Time::now()),
+            // the fills refer to their order by server id
+            account: AccountingFeed::new(Exchange::Hyperliquid, account, manager.clone(), accounting)
+                .with_trade_feed(|update| create_order_lid_str(update.server_id.as_str())),
             update_positions: None,
             open_orders_interval: interval(interval_ms),
             query_balances_interval: interval_conditionally(interval_ms, accounting),
@@ -134,70 +138,6 @@ impl HyperliquidExecutionConnection {
             .set_client(other.rest.client.session.client().clone());
         Ok(())
     }
-    fn convert_order_update(&self, update: UpdateOrder) -> AccountingUpdateOrder {
-        let cost = update.filled_cost();
-        AccountingUpdateOrder {
-            instrument: update.instrument,
-            order_lid: create_order_lid_str(update.server_id.as_str()),
-            side: update.side,
-            source_creation_timestamp: update.update_est,
-            accounting_close_timestamp: if !update.status.is_dead() {
-                None
-            } else {
-                Some(update.update_est)
-            },
-            total_quantity: update.size,
-            filled_quantity: update.filled_size,
-            // NB: No cost / average price information exists.
-            filled_cost_min: cost,
-        }
-        .into()
-    }
-
-    fn on_execution_response(&mut self, response: ExecutionResponse) -> Result<ExecutionResponse> {
-        if false && self.accounting {
-            match response {
-                ExecutionResponse::UpdateOrder(ref update) => {
-                    let order = self.convert_order_update(update.clone());
-                    if let Some(delta) = self.account.process_updates([order.into()]) {
-                        let group = ExecutionResponse::Group(vec![response, ExecutionResponse::UpdateBook(delta)]);
-                        return Ok(group);
-                    }
-                }
-                ExecutionResponse::UpdatePositions(ref update) => {
-                    let book = self.account.load_snapshot(update);
-                    // FIXME: we use the reversed order to make sure response is processed in the end(which is less buggy)
-                    let group = ExecutionResponse::Group(vec![ExecutionResponse::UpdateBook(book), response]);
-                    return Ok(group);
-                }
-                ExecutionResponse::UpdateFunding(ref funding) => {
-                    let delta = self.account.process_updates([funding.clone().into()]);
-                    if let Some(delta) = delta {
-                        let group = ExecutionResponse::Group(vec![response, ExecutionResponse::UpdateBook(delta)]);
-                        return Ok(group);
-                    }
-                }
-                ExecutionResponse::TradeOrder(ref trade) => {
-                    let delta = self.account.process_updates([trade.clone().into()]);
-                    if let Some(delta) = delta {
-                        let group = ExecutionResponse::Group(vec![response, ExecutionResponse::UpdateBook(delta)]);
-                        return Ok(group);
-                    }
-                }
-                ExecutionResponse::Group(group) => {
-                    let mut new_group = vec![];
-                    for update in group {
-                        let update = self.on_execution_response(update.clone())?;
-                        new_group.push(update);
-                    }
-                    return Ok(ExecutionResponse::Group(new_group));
-                }
-                _ => {}
-            }
-        }
-
-        Ok(response)
-    }
     fn start_new_order(&mut self, order: &RequestPlaceOrder) -> Result<()> {
         let symbol = self.manager.get_by_code_result(&order.instrument)?;
         self.rest.new_order(order, symbol)?;
@@ -237,33 +177,41 @@ impl HyperliquidExecutionConnection {
 #[async_trait(?Send)]
 impl ExecutionService for HyperliquidExecutionConnection {
     fn accept(&self, request: &ExecutionRequest) -> bool {
-        matches!(request.get_exchange(), Some(Exchange::Hyperliquid))
+        request.is_for(Exchange::Hyperliquid, self.account.account())
     }
 
     async fn request(&mut self, request: &ExecutionRequest) -> Result<()> {
         match request {
             ExecutionRequest::PlaceOrder(req) => self.start_new_order(req),
             ExecutionRequest::CancelOrder(req) => self.start_cancel_order(req),
+            ExecutionRequest::QueryOrder(req) => self.rest.query_order(req),
             ExecutionRequest::UpdateLeverage(update) => {
                 self.start_set_leverage(update.symbol.as_ref().map(|x| x.symbol.clone()), update.leverage)
                     .await
             }
+            ExecutionRequest::AcknowledgeHalt(_) => {
+                self.account.acknowledge_halt();
+                Ok(())
+            }
             _ => unimplemented!("unsupported request: {:?}", request),
         }
     }
 
     async fn next(&mut self) -> Result<ExecutionResponse> {
+        if let Some(book) = self.update_positions.take() {
+            return Ok(ExecutionResponse::UpdateBook(book));
+        }
         loop {
             tokio::select! {
                 msg = self.rest.next() => {
                     debug!("Received message: {:?}", msg);
                     let msg = msg?;
-                    return Ok(msg);
+                    return Ok(self.account.on_response(msg));
                 }
                 msg = self.ws.next() => {
                     let msg = msg?;
                     debug!("Received update: {:?}", msg);
-                    return self.on_execution_response(msg);
+                    return Ok(self.account.on_response(msg));
                 }
                 _ = self.open_orders_interval.tick() => {
                     self.rest.get_open_orders(Some(self.manager.clone()))?;
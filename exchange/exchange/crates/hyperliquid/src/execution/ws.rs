@@ -13,7 +13,7 @@ use trading_exchange_core::model::{
 };
 use trading_exchange_core::utils::future::interval;
 use trading_model::core::Time;
-use trading_model::{Exchange, InstrumentManagerExt, Network, SharedInstrumentManager};
+use trading_model::{Exchange, InstrumentManagerExt, LiquidationEvent, Network, SharedInstrumentManager};
 
 pub struct HyperliquidExecutionWs {
     pub ws: WsSession,
@@ -103,6 +103,16 @@ impl HyperliquidExecutionWs {
 
                         let trade_lid = create_trade_lid(&fill.coin, &fill.hash, &fill.start_position);
                         let instrument = self.manager.maybe_lookup_instrument(Exchange::Hyperliquid, fill.coin);
+                        if fill.liquidation.is_some() {
+                            trades.push(ExecutionResponse::Liquidation(LiquidationEvent {
+                                instrument: instrument.clone(),
+                                side,
+                                price: fill.px,
+                                size: fill.sz,
+                                exchange_time: Time::from_millis(timestamp),
+                                received_time: Time::now(),
+                            }));
+                        }
 
                         trades.push(ExecutionResponse::TradeOrder(OrderTrade {
                             account: self.account,
@@ -128,6 +138,7 @@ impl HyperliquidExecutionWs {
                         .unwrap();
 
                     return Ok(Some(ExecutionResponse::UpdateFunding(FundingPayment {
+                        account: self.account,
                         instrument: instrument.code_symbol.clone(),
                         source_timestamp: Time::from_millis(fd.time),
                         funding_lid: create_funding_lid(fd.coin.as_str(), fd.time),
@@ -135,7 +146,17 @@ impl HyperliquidExecutionWs {
                         quantity: fd.usdc,
                     })));
                 }
-                WsUserEvent::Liquidation(_) => {}
+                WsUserEvent::Liquidation(liquidation) => {
+                    // carries no coin, the liquidated positions arrive as fills with the liquidation field set
+                    warn!(
+                        "Account liquidated: liq={} liquidator={} user={} ntl_pos={} account_value={}",
+                        liquidation.liq,
+                        liquidation.liquidator,
+                        liquidation.liquidated_user,
+                        liquidation.liquidated_ntl_pos,
+                        liquidation.liquidated_account_value
+                    );
+                }
                 WsUserEvent::NonUserCancel(_) => {}
             },
             WsResponse::Error(error) => {
@@ -1,11 +1,13 @@
 use crate::model::info::response::CandleSnapshot;
 use crate::model::websocket::request::{HyperliquidCandleInterval, HyperliquidMethod, HyperliquidSubscription};
-use crate::model::websocket::response::{WsBook, WsResponse, WsTrade};
+use crate::model::websocket::response::{WsActiveAssetCtx, WsBook, WsResponse, WsTrade};
 use crate::{model, HyperliquidUrls, HYPERLIQUID_INSTRUMENT_LOADER};
 use async_trait::async_trait;
 use common::ws::WsSession;
 use eyre::{bail, Result};
+use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
+use std::str::FromStr;
 use tokio_tungstenite::tungstenite::Message;
 use tracing::*;
 use trading_exchange_core::model::{
@@ -15,10 +17,10 @@ use trading_exchange_core::utils::future::interval;
 use trading_exchange_core::{
     impl_service_async_for_market_feed_service, impl_service_builder_for_market_feed_service_builder,
 };
-use trading_model::core::Time;
+use trading_model::core::{Time, MILLISECONDS_PER_SECOND, MINUTES_PER_HOUR, SECONDS_PER_MINUTE};
 use trading_model::model::{
-    Exchange, InstrumentManagerExt, MarketEvent, MarketFeedSelector, MarketTrade, Quote, Quotes,
-    SharedInstrumentManager,
+    BookDelta, BookSequence, BookSequenceRule, DerivativesContextEvent, Exchange, InstrumentManagerExt, MarketEvent,
+    MarketFeedSelector, MarketTrade, OrderBookSync, Quote, Quotes, SharedInstrumentManager, Symbol,
 };
 use trading_model::wire::Packet;
 use trading_model::{Intent, OHLCVT};
@@ -50,6 +52,8 @@ pub struct HyperliquidMarketFeedConnection {
     pub config: MarketFeedConfig,
     pub subs: SubscriptionManager,
     pub manager: SharedInstrumentManager,
+    /// every `l2Book` push is a snapshot of the top levels, checked here before it is forwarded
+    books: HashMap<Symbol, OrderBookSync>,
     interval: tokio::time::Interval,
 }
 
@@ -74,6 +78,7 @@ impl HyperliquidMarketFeedConnection {
                 .await?,
             config,
             subs: SubscriptionManager::new(),
+            books: HashMap::new(),
             interval: interval(30_000),
         };
         this.subs.register_subscription_global(
@@ -107,6 +112,9 @@ impl HyperliquidMarketFeedConnection {
                 MarketFeedSelector::Depth(_) => {
                     subs.push(HyperliquidSubscription::L2Book { coin: coin.to_string() });
                 }
+                MarketFeedSelector::DerivativesContext => {
+                    subs.push(HyperliquidSubscription::ActiveAssetCtx { coin: coin.to_string() });
+                }
                 _ => bail!("Unsupported market feed kind: {:?}", kind),
             }
         }
@@ -133,13 +141,34 @@ impl HyperliquidMarketFeedConnection {
         }
         Ok(())
     }
-    fn parse_l2_book(&self, book: WsBook, received_time: Time) -> Result<MarketEvent> {
+    fn parse_l2_book(&mut self, book: WsBook, received_time: Time) -> Result<Option<MarketEvent>> {
         // usually 40 levels in total
 
-        let instrument = self.manager.maybe_lookup_instrument(Exchange::Hyperliquid, book.coin);
+        let instrument = self
+            .manager
+            .maybe_lookup_instrument(Exchange::Hyperliquid, book.coin.clone());
+        let time = book.time as u64;
+        let sync = self
+            .books
+            .entry(book.coin)
+            .or_insert_with(|| OrderBookSync::new(instrument.clone(), BookSequenceRule::Snapshot));
+        if time < sync.last_seq() {
+            // pushed out of order
+            return Ok(None);
+        }
+        let (bids, asks) = book.levels;
+        let mut snapshot = BookDelta::new(BookSequence::single(time))
+            .with_levels(bids.iter().map(|x| (x.px, x.sz)), asks.iter().map(|x| (x.px, x.sz)));
+        snapshot.exchange_time = Time::from_millis(time as _);
+        snapshot.received_time = received_time;
+        if let Err(err) = sync.on_snapshot(snapshot) {
+            warn!("hyperliquid l2Book rejected: {}", err);
+            return Ok(None);
+        }
+
         let mut quotes = Quotes::new(instrument);
+        quotes.exchange_time = Time::from_millis(time as _);
         quotes.received_time = received_time;
-        let (bids, asks) = book.levels;
 
         for (i, bid) in bids.iter().enumerate() {
             quotes.insert_quote(Quote::update_by_level(Intent::Bid, (i + 1) as _, bid.px, bid.sz).with_number(bid.n));
@@ -148,7 +177,7 @@ impl HyperliquidMarketFeedConnection {
             quotes.insert_quote(Quote::update_by_level(Intent::Ask, (i + 1) as _, ask.px, ask.sz).with_number(ask.n));
         }
 
-        return Ok(MarketEvent::Quotes(quotes));
+        return Ok(Some(MarketEvent::Quotes(quotes)));
     }
     fn parse_trades(&self, trades0: Vec<WsTrade>, received_time: Time) -> Result<MarketEvent> {
         let mut trades = vec![];
@@ -181,10 +210,29 @@ impl HyperliquidMarketFeedConnection {
             volume: candle.volume,
             exchange_time: Time::from_millis(candle.time_end),
             received_time,
-            interval_ms: 1000,
+            interval_ms: 60_000,
         };
         return Ok(MarketEvent::OHLCVT(candle));
     }
+    fn parse_active_asset_ctx(&self, msg: WsActiveAssetCtx, received_time: Time) -> Result<MarketEvent> {
+        let instrument = self.manager.maybe_lookup_instrument(Exchange::Hyperliquid, msg.coin);
+        let ctx = msg.ctx;
+        // funding is paid every hour on the hour
+        let hour_ms = MINUTES_PER_HOUR * SECONDS_PER_MINUTE * MILLISECONDS_PER_SECOND;
+        let next_funding_ms = (received_time.millis() / hour_ms + 1) * hour_ms;
+        let context = DerivativesContextEvent {
+            instrument,
+            mark_price: Some(f64::from_str(&ctx.mark_px)?),
+            // hyperliquid has no index, the oracle price plays its role
+            index_price: Some(f64::from_str(&ctx.oracle_px)?),
+            open_interest: Some(f64::from_str(&ctx.open_interest)?),
+            funding_rate: Some(f64::from_str(&ctx.funding)?),
+            next_funding_time: Some(Time::from_millis(next_funding_ms)),
+            exchange_time: received_time,
+            received_time,
+        };
+        Ok(MarketEvent::DerivativesContext(context))
+    }
     pub fn handle_market_message(&mut self, pkt: Packet<Message>) -> Result<Option<MarketEvent>> {
         if let Message::Text(text) = pkt.data {
             if !text.starts_with('{') {
@@ -206,12 +254,15 @@ impl HyperliquidMarketFeedConnection {
                 }
                 WsResponse::L2Book(book) => {
                     // debug!("Parsed book: {:?}", book);
-                    return self.parse_l2_book(book, pkt.received_time).map(Some);
+                    return self.parse_l2_book(book, pkt.received_time);
                 }
                 WsResponse::Trades(trades0) => {
                     // debug!("Parsed trades: {:?}", trades);
                     return self.parse_trades(trades0, pkt.received_time).map(Some);
                 }
+                WsResponse::ActiveAssetCtx(ctx) => {
+                    return self.parse_active_asset_ctx(ctx, pkt.received_time).map(Some);
+                }
 
                 _ => {}
             }
@@ -46,6 +46,14 @@ pub enum Request {
     },
     OrderStatus {
         user: Address,
-        oid: u64,
+        oid: OrderStatusId,
     },
 }
+
+/// `orderStatus` takes either the venue's order id or the client order id
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+pub enum OrderStatusId {
+    Oid(u64),
+    Cloid(String),
+}
@@ -12,6 +12,8 @@ pub struct Asset {
     pub sz_decimals: u32,
     pub max_leverage: u32,
     pub only_isolated: bool,
+    #[serde(default)]
+    pub is_delisted: bool,
 }
 
 #[derive(Deserialize, Debug)]
@@ -122,6 +124,35 @@ impl OpenOrder {
         }
     }
 }
+/// answer of `orderStatus`
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", tag = "status")]
+pub enum OrderStatusResponse {
+    Order { order: OrderStatusEntry },
+    UnknownOid,
+}
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderStatusEntry {
+    pub order: OrderStatusOrder,
+    /// open, filled, canceled, triggered, rejected, or one of the *Canceled reasons
+    pub status: String,
+    pub status_timestamp: TimeStampMs,
+}
+#[serde_as]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderStatusOrder {
+    pub coin: Symbol,
+    #[serde_as(as = "DisplayFromStr")]
+    pub limit_px: f64,
+    /// remaining size
+    #[serde_as(as = "DisplayFromStr")]
+    pub sz: f64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub orig_sz: f64,
+    pub oid: u64,
+}
 #[serde_as]
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -141,6 +172,18 @@ pub struct UserFill {
     pub crossed: bool,
     #[serde_as(as = "DisplayFromStr")]
     pub fee: f64,
+    /// set when the fill is part of a liquidation
+    #[serde(default)]
+    pub liquidation: Option<FillLiquidation>,
+}
+#[serde_as]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FillLiquidation {
+    pub liquidated_user: Option<String>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub mark_px: f64,
+    pub method: String,
 }
 impl UserFill {
     pub fn side(&self) -> Side {
@@ -369,4 +412,61 @@ mod tests {
         let ctxs: Vec<AssetContext> = serde_json::from_str(data).unwrap();
         drop(ctxs)
     }
+
+    #[test]
+    fn test_parse_liquidation_fill() {
+        let data = r#"{
+  "coin": "ETH",
+  "px": "3100.5",
+  "sz": "0.25",
+  "side": "A",
+  "time": 1714300000000,
+  "startPosition": "0.25",
+  "dir": "Close Long",
+  "closedPnl": "-12.5",
+  "hash": "0x0",
+  "oid": 123,
+  "crossed": true,
+  "fee": "0.0",
+  "liquidation": {
+    "liquidatedUser": "0x0000000000000000000000000000000000000001",
+    "markPx": "3099.9",
+    "method": "market"
+  }
+}"#;
+        let fill: UserFill = serde_json::from_str(data).unwrap();
+        let liquidation = fill.liquidation.unwrap();
+        assert_eq!(liquidation.mark_px, 3099.9);
+        assert_eq!(liquidation.method, "market");
+    }
+
+    #[test]
+    fn test_parse_order_status() {
+        let data = r#"{
+  "status": "order",
+  "order": {
+    "order": {
+      "coin": "ETH",
+      "side": "B",
+      "limitPx": "3000.0",
+      "sz": "0.0",
+      "oid": 456,
+      "timestamp": 1714300000000,
+      "origSz": "0.5",
+      "cloid": "0x1e60610f0b3d420597c88c1fed2ad5ee"
+    },
+    "status": "filled",
+    "statusTimestamp": 1714300000100
+  }
+}"#;
+        let OrderStatusResponse::Order { order } = serde_json::from_str(data).unwrap() else {
+            panic!("expected an order");
+        };
+        assert_eq!(order.status, "filled");
+        assert_eq!(order.order.oid, 456);
+        assert_eq!(order.order.orig_sz - order.order.sz, 0.5);
+
+        let unknown: OrderStatusResponse = serde_json::from_str(r#"{"status":"unknownOid"}"#).unwrap();
+        assert!(matches!(unknown, OrderStatusResponse::UnknownOid));
+    }
 }
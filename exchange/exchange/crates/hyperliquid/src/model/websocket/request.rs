@@ -51,6 +51,9 @@ pub enum HyperliquidSubscription {
     Trades {
         coin: String,
     },
+    ActiveAssetCtx {
+        coin: String,
+    },
     Candle {
         coin: String,
         interval: HyperliquidCandleInterval,
@@ -45,6 +45,12 @@ pub struct WebData {
     pub is_vault: bool,
     pub user: Address,
 }
+#[derive(Deserialize, Debug)]
+pub struct WsActiveAssetCtx {
+    pub coin: Symbol,
+    pub ctx: Ctx,
+}
+
 #[serde_as]
 #[derive(Deserialize, Debug)]
 pub struct WsTrade {
@@ -170,6 +176,7 @@ pub enum WsResponse {
     Candle(CandleSnapshot),
     L2Book(WsBook),
     Trades(Vec<WsTrade>),
+    ActiveAssetCtx(WsActiveAssetCtx),
     OrderUpdates(Vec<WsOrderUpdate>),
     User(WsUserEvent),
     SubscriptionResponse(Channel),
@@ -9,11 +9,12 @@ use crate::model::exchange::request::TransferRequest;
 use crate::model::exchange::request::{Action, HyperliquidRequestUserPoints};
 use crate::model::exchange::response::Response;
 use crate::model::exchange::response::Status;
-use crate::model::info::response::{OpenOrder, UserPoints, UserState};
+use crate::model::info::request::OrderStatusId;
+use crate::model::info::response::{OpenOrder, OrderStatusResponse, UserPoints, UserState};
 use crate::model::{info, usd_transfer, API};
 use crate::rest::HyperliquidRestClient;
 use crate::sign::{sign_l1_action, sign_l1_action_inner};
-use crate::utils::convert_status;
+use crate::utils::{convert_order_status_name, convert_status};
 use crate::HyperliquidUrls;
 use ethers::abi::AbiEncode;
 use ethers::prelude::{LocalWallet, Signer, H256};
@@ -25,7 +26,7 @@ use futures::FutureExt;
 use std::sync::Arc;
 use trading_exchange_core::model::{
     AccountId, ExecutionRequest, ExecutionResponse, Order, OrderStatus, RequestCancelOrder, RequestPlaceOrder,
-    SyncOrders, UpdateOrder, UpdatePosition, UpdatePositionSetValues, UpdatePositions,
+    RequestQueryOrder, SyncOrders, UpdateOrder, UpdatePosition, UpdatePositionSetValues, UpdatePositions,
 };
 use trading_exchange_core::utils::http_session::HttpSession;
 use trading_model::core::{Time, NANOSECONDS_PER_MILLISECOND};
@@ -227,11 +228,12 @@ impl HyperliquidExchangeSession {
     pub fn get_open_orders(&mut self, user: Address, manager: Option<SharedInstrumentManager>) -> eyre::Result<()> {
         let request = info::request::Request::OpenOrders { user };
         let request = self.client.build_request(API::Info, &request);
+        let account = self.account;
         let decoder = move |_, response: eyre::Result<String>| match response {
             Ok(data) => {
                 let orders: Vec<OpenOrder> = serde_json::from_str(&data).expect("Failed to parse response");
 
-                let mut sync_orders = SyncOrders::new(Exchange::Hyperliquid, None);
+                let mut sync_orders = SyncOrders::new(Exchange::Hyperliquid, None).with_account(account);
                 for order in orders {
                     let side = order.side();
                     let instrument = manager.maybe_lookup_instrument(Exchange::Hyperliquid, order.coin);
@@ -244,6 +246,7 @@ impl HyperliquidExchangeSession {
                         open_lt: Time::from_millis(order.timestamp),
                         price: order.limit_px.parse().expect("Failed to parse limit_px"),
                         status: OrderStatus::Open,
+                        account,
                         ..Order::empty()
                     });
                 }
@@ -258,6 +261,43 @@ impl HyperliquidExchangeSession {
         );
         Ok(())
     }
+    /// state of one order by its oid, or by its cloid when the oid is not known
+    pub fn query_order(&mut self, user: Address, query: &RequestQueryOrder) -> eyre::Result<()> {
+        let oid = match query.order_sid.parse() {
+            Ok(oid) => OrderStatusId::Oid(oid),
+            Err(_) if !query.order_cid.is_empty() => OrderStatusId::Cloid(query.order_cid.to_string()),
+            Err(_) => eyre::bail!("either server_id or client_id must be specified: {:?}", query),
+        };
+        let request = info::request::Request::OrderStatus { user, oid };
+        let request = self.client.build_request(API::Info, &request);
+        let decoder = |query: RequestQueryOrder, response: eyre::Result<String>| match response {
+            Ok(data) => match serde_json::from_str::<OrderStatusResponse>(&data) {
+                Ok(status) => ExecutionResponse::UpdateOrder(Self::parse_order_status(&query, status)),
+                Err(err) => ExecutionResponse::Error(format!("failed to decode order status {data}: {err}")),
+            },
+            Err(err) => ExecutionResponse::Error(err.to_string()),
+        };
+        self.session.send_and_handle(query.clone(), request, decoder);
+        Ok(())
+    }
+    fn parse_order_status(query: &RequestQueryOrder, response: OrderStatusResponse) -> UpdateOrder {
+        let mut update = query.to_update();
+        match response {
+            OrderStatusResponse::UnknownOid => {
+                update.reason = "unknown to the exchange".to_string();
+            }
+            OrderStatusResponse::Order { order } => {
+                let filled_size = order.order.orig_sz - order.order.sz;
+                update.status = convert_order_status_name(&order.status, filled_size);
+                update.server_id = order.order.oid.into();
+                update.price = order.order.limit_px;
+                update.size = order.order.orig_sz;
+                update.filled_size = filled_size;
+                update.update_tst = Time::from_millis(order.status_timestamp);
+            }
+        }
+        update
+    }
     fn parse_user_state(
         account: AccountId,
         response: String,
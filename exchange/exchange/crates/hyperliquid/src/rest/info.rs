@@ -9,7 +9,7 @@ use crate::{
     model::{
         exchange::request::HyperliquidChain,
         info::{
-            request::{CandleSnapshotRequest, Request},
+            request::{CandleSnapshotRequest, OrderStatusId, Request},
             response::{
                 AssetContext, CandleSnapshot, FundingHistory, L2Book, OpenOrder, Universe,
                 UserFill, UserFunding, UserState,
@@ -149,7 +149,13 @@ impl HyperliquidInfoClient {
     pub async fn order_status(&self, user: Address, oid: u64) -> Result<()> {
         // TODO: This should return an OrderStatus
         self.client
-            .post(API::Info, &Request::OrderStatus { user, oid })
+            .post(
+                API::Info,
+                &Request::OrderStatus {
+                    user,
+                    oid: OrderStatusId::Oid(oid),
+                },
+            )
             .await
     }
 }
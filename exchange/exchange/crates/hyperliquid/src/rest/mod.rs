@@ -20,7 +20,7 @@ use std::str::FromStr;
 use std::sync::Arc;
 use tracing::{debug, warn};
 use trading_exchange_core::model::{
-    AccountId, ExecutionResponse, OrderType, RequestCancelOrder, RequestPlaceOrder, UpdatePositions,
+    AccountId, ExecutionResponse, OrderType, RequestCancelOrder, RequestPlaceOrder, RequestQueryOrder, UpdatePositions,
 };
 use trading_model::math::size::{Size, SizeMode};
 use trading_model::model::{
@@ -108,7 +108,11 @@ impl HyperliquidClient {
                     // in hyperliquid, price precision is 5 significant digits
                     // NOT 5 decimal places after the decimal point
                     price: Size::from_decimals(5).with_mode(SizeMode::Relative),
-                    status: InstrumentStatus::Open,
+                    status: if asset.is_delisted {
+                        InstrumentStatus::Close
+                    } else {
+                        InstrumentStatus::Open
+                    },
                     ty: InstrumentType::Perpetual(SettlementType::Linear.into()),
                     ..InstrumentDetailsBuilder::empty()
                 }
@@ -245,6 +249,9 @@ impl HyperliquidRest {
         Ok(())
     }
 
+    pub fn query_order(&mut self, query: &RequestQueryOrder) -> eyre::Result<()> {
+        self.client.query_order(self.address, query)
+    }
     pub fn get_open_orders(&mut self, manager: Option<SharedInstrumentManager>) -> eyre::Result<()> {
         self.client.get_open_orders(self.address, manager)
     }
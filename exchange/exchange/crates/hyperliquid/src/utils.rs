@@ -54,6 +54,18 @@ pub fn convert_status(status: Status) -> OrderStatus {
     }
 }
 
+/// status names of `orderStatus`; every cancel reason ends with `Canceled`
+pub fn convert_order_status_name(status: &str, filled_size: f64) -> OrderStatus {
+    match status {
+        "open" | "triggered" if filled_size > 0.0 => OrderStatus::PartiallyFilled,
+        "open" | "triggered" => OrderStatus::Open,
+        "filled" => OrderStatus::Filled,
+        "rejected" => OrderStatus::Rejected,
+        s if s.ends_with("Canceled") || s == "canceled" || s == "scheduledCancel" => OrderStatus::Cancelled,
+        _ => OrderStatus::Unknown,
+    }
+}
+
 pub fn create_trade_lid(coin: &str, hash: &str, start_position: &str) -> TradeLid {
     TradeLid(format!("{HYPERLIQUID}|{coin}|{hash}|{start_position}").into())
 }
@@ -10,13 +10,16 @@ use serde::*;
 use serde_json::json;
 use serde_with::serde_as;
 use serde_with::DisplayFromStr;
-use tokio_tungstenite::tungstenite::client::IntoClientRequest;
 use tokio_tungstenite::tungstenite::Message;
+use std::sync::Arc;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
 use tracing::{error, info};
+use trading_exchange_core::model::RestSnapshotBooks;
 use trading_model::TimeStampMs;
 use trading_model::core::Time;
 use trading_model::model::{
-    Exchange, InstrumentCode, InstrumentManagerExt, MarketEvent, Quote, Quotes, SharedInstrumentManager, Symbol,
+    BookDelta, BookSequence, BookSequenceRule, Exchange, InstrumentCode, InstrumentManagerExt, MarketEvent, Quote,
+    Quotes, SharedInstrumentManager, Symbol,
 };
 use trading_model::wire::Packet;
 use trading_model::Intent;
@@ -93,7 +96,7 @@ impl KucoinSpotDepthConnection {
     fn handle_message(&mut self, pkt: Packet<Message>) -> Result<Option<MarketEvent>> {
         match pkt.data {
             Message::Text(message) => {
-                if message.contains("level2") {
+                if !message.contains("\"type\":\"message\"") && !message.starts_with("{\"error") {
                     info!("Status from {}: {}", self.urls.public_websocket, message);
                     return Ok(None);
                 }
@@ -110,10 +113,15 @@ impl KucoinSpotDepthConnection {
                 if self.dump_raw {
                     return Ok(Some(MarketEvent::String(message)));
                 }
-                let message = serde_json::from_str(&message)?;
+                if self.channel.is_full_depth() {
+                    let message: KucoinMessage<KucoinSpotLevel2Update> = serde_json::from_str(&message)?;
+                    let quotes = self.channel.parse_kucoin_spot_level2_update(message.data, pkt.received_time);
+                    return Ok(quotes.map(MarketEvent::Quotes));
+                }
+                let message: KucoinMessage<KucoinSpotDepthMessage> = serde_json::from_str(&message)?;
                 let event = self
                     .channel
-                    .parse_kucoin_spot_depth_update(&self.symbol, message, pkt.received_time)?;
+                    .parse_kucoin_spot_depth_update(&self.symbol, message.data, pkt.received_time)?;
                 return Ok(Some(MarketEvent::Quotes(event)));
             }
             Message::Ping(code) => {
@@ -141,6 +149,13 @@ impl KucoinSpotDepthConnection {
         }
     }
 }
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KucoinMessage<T> {
+    pub topic: String,
+    pub subject: String,
+    pub data: T,
+}
+
  #[serde_as]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -166,22 +181,112 @@ impl KucoinSpotDepthMessage {
     }
 }
 
+/// `[price, size, sequence]`, size 0 removes the level
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KucoinLevel2Change(
+    #[serde_as(as = "DisplayFromStr")] pub f64,
+    #[serde_as(as = "DisplayFromStr")] pub f64,
+    #[serde_as(as = "DisplayFromStr")] pub u64,
+);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KucoinLevel2Changes {
+    pub asks: Vec<KucoinLevel2Change>,
+    pub bids: Vec<KucoinLevel2Change>,
+}
+
+/// `/market/level2` update, consecutive updates cover `sequenceStart..=sequenceEnd`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KucoinSpotLevel2Update {
+    pub changes: KucoinLevel2Changes,
+    pub sequence_start: u64,
+    pub sequence_end: u64,
+    pub symbol: Symbol,
+    pub time: TimeStampMs,
+}
+
+/// response of `/api/v1/market/orderbook/level2_100`
+#[serde_as]
+#[derive(Deserialize, Debug)]
+struct KucoinLevel2Snapshot {
+    #[serde_as(as = "DisplayFromStr")]
+    sequence: u64,
+    time: TimeStampMs,
+    #[serde_as(as = "Vec<(DisplayFromStr, DisplayFromStr)>")]
+    bids: Vec<(f64, f64)>,
+    #[serde_as(as = "Vec<(DisplayFromStr, DisplayFromStr)>")]
+    asks: Vec<(f64, f64)>,
+}
+
+#[derive(Deserialize, Debug)]
+struct KucoinRestResponse<T> {
+    data: T,
+}
+
 pub struct KucoinSpotDepthChannel {
     exchange: Exchange,
     manager: Option<SharedInstrumentManager>,
+    /// full-depth books from `/market/level2` diffs on top of the REST snapshot, None for `level2Depth5`
+    books: Option<RestSnapshotBooks>,
 }
 
 impl KucoinSpotDepthChannel {
     pub fn new(exchange: Exchange, manager: Option<SharedInstrumentManager>) -> Self {
-        Self { exchange, manager }
+        Self {
+            exchange,
+            manager,
+            books: None,
+        }
+    }
+    pub fn with_full_depth(mut self) -> Self {
+        let fetcher = Arc::new(|symbol: Symbol| {
+            let url = format!(
+                "https://api.kucoin.com/api/v1/market/orderbook/level2_100?symbol={}",
+                symbol
+            );
+            async move {
+                let text = reqwest::get(&url).await?.text().await?;
+                let snapshot: KucoinRestResponse<KucoinLevel2Snapshot> = serde_json::from_str(&text)?;
+                let snapshot = snapshot.data;
+                let mut delta =
+                    BookDelta::new(BookSequence::single(snapshot.sequence)).with_levels(snapshot.bids, snapshot.asks);
+                delta.exchange_time = Time::from_millis(snapshot.time);
+                Ok(delta)
+            }
+            .boxed()
+        });
+        self.books = Some(RestSnapshotBooks::new(BookSequenceRule::Overlapping, fetcher));
+        self
+    }
+    pub fn is_full_depth(&self) -> bool {
+        self.books.is_some()
     }
 
    pub fn get_sub_param(&self, symbol: &str) -> String {
+        if self.is_full_depth() {
+            return format!("/market/level2:{}", symbol);
+        }
         let level = "level2";
         let depth = "Depth5";
         format!("/spotMarket/{}{}:{}", level, depth, symbol)
     }
 
+    pub fn parse_kucoin_spot_level2_update(
+        &mut self,
+        update: KucoinSpotLevel2Update,
+        received_time: Time,
+    ) -> Option<Quotes> {
+        let instrument = self.manager.maybe_lookup_instrument(self.exchange, update.symbol.clone());
+        let levels = |changes: Vec<KucoinLevel2Change>| changes.into_iter().map(|x| (x.0, x.1)).collect::<Vec<_>>();
+        let mut delta = BookDelta::new(BookSequence::range(update.sequence_start, update.sequence_end))
+            .with_levels(levels(update.changes.bids), levels(update.changes.asks));
+        delta.exchange_time = Time::from_millis(update.time);
+        delta.received_time = received_time;
+        self.books.as_mut()?.on_update(update.symbol, instrument, delta)
+    }
+
     pub fn parse_kucoin_spot_depth_update(
         &self,
         symbol: &Symbol,
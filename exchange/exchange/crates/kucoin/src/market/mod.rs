@@ -133,11 +133,15 @@ impl KucoinMarketFeedConnection {
         }
         Ok(None)
     }
-    fn create_spot_channel(&mut self, symbol: &Symbol) {
+    fn create_spot_channel(&mut self, symbol: &Symbol, full_depth: bool) {
+        let mut channel = KucoinSpotDepthChannel::new(self.urls.exchange, Some(self.manager.clone()));
+        if full_depth {
+            channel = channel.with_full_depth();
+        }
         self.spot_depth_channels.add_channel(KucoinSpotDepthConnection {
             symbol: symbol.clone(),
             ws: WsSession::new(),
-            channel: KucoinSpotDepthChannel::new(self.urls.exchange, Some(self.manager.clone())),
+            channel,
             urls: self.urls.clone(),
             reconnecting: None,
             dump_raw: self.dump_raw,
@@ -162,7 +166,14 @@ impl KucoinMarketFeedConnection {
                             || self.urls.exchange == Exchange::BinanceMargin)
                             && d.match_depth(MarketFeedDepthKind::SNAPSHOT_LEVEL5) =>
                     {
-                        self.create_spot_channel(&symbol.symbol);
+                        self.create_spot_channel(&symbol.symbol, false);
+                    }
+                    MarketFeedSelector::Depth(d)
+                        if (self.urls.exchange == Exchange::BinanceSpot
+                            || self.urls.exchange == Exchange::BinanceMargin)
+                            && d.match_depth(MarketFeedDepthKind::UPDATE_FULL) =>
+                    {
+                        self.create_spot_channel(&symbol.symbol, true);
                     }
                     MarketFeedSelector::BookTicker => {
                         params.push(self.converter.book_ticker.get_sub_param(&symbol.symbol));
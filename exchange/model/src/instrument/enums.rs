@@ -3,7 +3,7 @@ use crate::math::size::Size;
 use serde::{Deserialize, Serialize};
 use strum_macros::EnumString;
 
-#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum InstrumentStatus {
     Open,
     Pause,
@@ -0,0 +1,311 @@
+//! Order book of arbitrary depth, maintained from snapshots and incremental updates
+
+use crate::{InstrumentCode, Intent, L2OrderBook, Level, LevelOperation, Price, Quantity, Quote, Quotes, Side, Time};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// price key ordered by `f64::total_cmp`
+#[derive(Copy, Clone, Debug)]
+pub struct BookPrice(pub Price);
+impl PartialEq for BookPrice {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+impl Eq for BookPrice {}
+impl PartialOrd for BookPrice {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for BookPrice {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BookLevel {
+    pub price: Price,
+    pub size: Quantity,
+    /// price and size as sent by the venue, only kept where the venue checksums the book
+    pub raw: Option<(String, String)>,
+}
+impl BookLevel {
+    pub fn new(price: Price, size: Quantity) -> Self {
+        Self { price, size, raw: None }
+    }
+    pub fn from_raw(price: &str, size: &str) -> eyre::Result<Self> {
+        Ok(Self {
+            price: price.parse()?,
+            size: size.parse()?,
+            raw: Some((price.to_string(), size.to_string())),
+        })
+    }
+}
+
+/// result of sweeping one side of the book
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BookSweep {
+    pub size: Quantity,
+    pub notional: Quantity,
+    pub average_price: Price,
+    /// price of the last level touched
+    pub worst_price: Price,
+    pub levels: usize,
+    /// false when the side ran out before the target was reached
+    pub filled: bool,
+}
+
+/// walks `(price, size)` levels from the best price outwards.
+/// `remaining` returns the size still wanted given the size and notional taken so far and the level price
+fn sweep_levels(
+    levels: impl Iterator<Item = (Price, Quantity)>,
+    remaining: impl Fn(Quantity, Quantity, Price) -> Quantity,
+) -> Option<BookSweep> {
+    let mut sweep = BookSweep {
+        size: 0.0,
+        notional: 0.0,
+        average_price: 0.0,
+        worst_price: 0.0,
+        levels: 0,
+        filled: false,
+    };
+    for (price, size) in levels {
+        let wanted = remaining(sweep.size, sweep.notional, price);
+        if wanted <= 0.0 {
+            sweep.filled = true;
+            break;
+        }
+        sweep.levels += 1;
+        sweep.worst_price = price;
+        let taken = wanted.min(size);
+        sweep.size += taken;
+        sweep.notional += taken * price;
+        if taken == wanted {
+            sweep.filled = true;
+            break;
+        }
+    }
+    if sweep.size == 0.0 {
+        return None;
+    }
+    sweep.average_price = sweep.notional / sweep.size;
+    Some(sweep)
+}
+/// sweeps levels until `notional` of quote asset is consumed
+pub(crate) fn sweep_notional(levels: impl Iterator<Item = (Price, Quantity)>, notional: Quantity) -> Option<BookSweep> {
+    sweep_levels(levels, |_, taken, price| (notional - taken) / price)
+}
+/// sweeps levels until `size` of base asset is consumed
+pub(crate) fn sweep_size(levels: impl Iterator<Item = (Price, Quantity)>, size: Quantity) -> Option<BookSweep> {
+    sweep_levels(levels, |taken, _, _| size - taken)
+}
+
+#[derive(Clone, Debug)]
+pub struct OrderBook {
+    pub instrument: InstrumentCode,
+    bids: BTreeMap<BookPrice, BookLevel>,
+    asks: BTreeMap<BookPrice, BookLevel>,
+    pub exchange_time: Time,
+    pub received_time: Time,
+}
+
+impl OrderBook {
+    pub fn new(instrument: InstrumentCode) -> Self {
+        Self {
+            instrument,
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            exchange_time: Time::NULL,
+            received_time: Time::NULL,
+        }
+    }
+    pub fn clear(&mut self) {
+        self.bids.clear();
+        self.asks.clear();
+    }
+    fn side_mut(&mut self, intent: Intent) -> &mut BTreeMap<BookPrice, BookLevel> {
+        match intent {
+            Intent::Bid => &mut self.bids,
+            Intent::Ask => &mut self.asks,
+        }
+    }
+    /// levels from the best price outwards
+    pub fn levels(&self, intent: Intent) -> Box<dyn Iterator<Item = &BookLevel> + '_> {
+        match intent {
+            Intent::Bid => Box::new(self.bids.values().rev()),
+            Intent::Ask => Box::new(self.asks.values()),
+        }
+    }
+    pub fn depth(&self, intent: Intent) -> usize {
+        match intent {
+            Intent::Bid => self.bids.len(),
+            Intent::Ask => self.asks.len(),
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.bids.is_empty() && self.asks.is_empty()
+    }
+    /// zero size removes the level
+    pub fn update_level(&mut self, intent: Intent, level: BookLevel) {
+        let side = self.side_mut(intent);
+        if level.size == 0.0 {
+            side.remove(&BookPrice(level.price));
+        } else {
+            side.insert(BookPrice(level.price), level);
+        }
+    }
+    fn remove_best_n(&mut self, intent: Intent, n: usize) {
+        let keys: Vec<BookPrice> = self.levels(intent).take(n).map(|x| BookPrice(x.price)).collect();
+        let side = self.side_mut(intent);
+        for key in keys {
+            side.remove(&key);
+        }
+    }
+    fn remove_worst_n(&mut self, intent: Intent, n: usize) {
+        let depth = self.depth(intent);
+        let keys: Vec<BookPrice> = self
+            .levels(intent)
+            .skip(depth.saturating_sub(n))
+            .map(|x| BookPrice(x.price))
+            .collect();
+        let side = self.side_mut(intent);
+        for key in keys {
+            side.remove(&key);
+        }
+    }
+    /// applies normalized quotes. a run of by-level quotes replaces the top levels of its side
+    pub fn apply_quotes(&mut self, quotes: &Quotes) {
+        let max_level = |intent: Intent| {
+            quotes
+                .quotes
+                .iter()
+                .filter(|x| x.intent == intent && x.operation == LevelOperation::UpdateByLevel)
+                .map(|x| x.level as usize)
+                .max()
+        };
+        let mut pending_bids = max_level(Intent::Bid);
+        let mut pending_asks = max_level(Intent::Ask);
+        for quote in &quotes.quotes {
+            match quote.operation {
+                LevelOperation::UpdateByPrice => {
+                    self.update_level(quote.intent, BookLevel::new(quote.price, quote.size));
+                }
+                LevelOperation::UpdateByLevel => {
+                    let pending = match quote.intent {
+                        Intent::Bid => &mut pending_bids,
+                        Intent::Ask => &mut pending_asks,
+                    };
+                    if let Some(n) = pending.take() {
+                        self.remove_best_n(quote.intent, n);
+                    }
+                    self.update_level(quote.intent, BookLevel::new(quote.price, quote.size));
+                }
+                LevelOperation::DeleteFirstN => self.remove_best_n(quote.intent, quote.level as usize),
+                LevelOperation::DeleteLastN => self.remove_worst_n(quote.intent, quote.level as usize),
+                LevelOperation::DeleteSide => self.side_mut(quote.intent).clear(),
+            }
+        }
+        self.exchange_time = quotes.exchange_time;
+        self.received_time = quotes.received_time;
+    }
+    pub fn best_bid(&self) -> Option<&BookLevel> {
+        self.bids.values().next_back()
+    }
+    pub fn best_ask(&self) -> Option<&BookLevel> {
+        self.asks.values().next()
+    }
+    pub fn mid_price(&self) -> Option<Price> {
+        Some((self.best_bid()?.price + self.best_ask()?.price) / 2.0)
+    }
+    pub fn spread(&self) -> Option<Price> {
+        Some(self.best_ask()?.price - self.best_bid()?.price)
+    }
+    pub fn is_crossed(&self) -> bool {
+        match (self.best_bid(), self.best_ask()) {
+            (Some(bid), Some(ask)) => bid.price >= ask.price,
+            _ => false,
+        }
+    }
+    pub fn top_levels(&self, intent: Intent, n: usize) -> Vec<Level> {
+        self.levels(intent)
+            .take(n)
+            .map(|x| Level {
+                intent,
+                price: x.price,
+                size: x.size,
+            })
+            .collect()
+    }
+    /// fixed-size view for code written against `L2OrderBook`
+    pub fn to_l2<const N: usize>(&self) -> L2OrderBook<N> {
+        let mut book = L2OrderBook::new();
+        book.bids.levels = self.top_levels(Intent::Bid, N);
+        book.asks.levels = self.top_levels(Intent::Ask, N);
+        book
+    }
+    /// the whole book as quotes, starting with a clear of both sides
+    pub fn to_quotes(&self) -> Quotes {
+        let mut quotes = Quotes::new(self.instrument.clone());
+        quotes.exchange_time = self.exchange_time;
+        quotes.received_time = self.received_time;
+        quotes.insert_clear();
+        for intent in [Intent::Bid, Intent::Ask] {
+            for level in self.levels(intent) {
+                quotes.insert_quote(Quote::update_by_price(intent, level.price, level.size));
+            }
+        }
+        quotes
+    }
+    /// sweeps `intent` levels until `notional` of quote asset is consumed.
+    /// a buy consumes `Intent::Ask`, a sell consumes `Intent::Bid`
+    pub fn depth_at_notional(&self, intent: Intent, notional: Quantity) -> Option<BookSweep> {
+        sweep_notional(self.levels(intent).map(|x| (x.price, x.size)), notional)
+    }
+    /// sweeps `intent` levels until `size` of base asset is consumed
+    pub fn depth_at_size(&self, intent: Intent, size: Quantity) -> Option<BookSweep> {
+        sweep_size(self.levels(intent).map(|x| (x.price, x.size)), size)
+    }
+    /// average price a taker order of `size` fills at, None when the book cannot fill it
+    pub fn executable_price(&self, side: Side, size: Quantity) -> Option<Price> {
+        let sweep = self.depth_at_size(Intent::from(side).opposite(), size)?;
+        sweep.filled.then_some(sweep.average_price)
+    }
+    /// base size resting within `bps` of the best price
+    pub fn size_within_bps(&self, intent: Intent, bps: f64) -> Quantity {
+        let Some(best) = self.levels(intent).next().map(|x| x.price) else {
+            return 0.0;
+        };
+        let limit = match intent {
+            Intent::Bid => best * (1.0 - bps / 10_000.0),
+            Intent::Ask => best * (1.0 + bps / 10_000.0),
+        };
+        self.levels(intent)
+            .take_while(|x| match intent {
+                Intent::Bid => x.price >= limit,
+                Intent::Ask => x.price <= limit,
+            })
+            .map(|x| x.size)
+            .sum()
+    }
+    /// crc32 over `bid:bidSize:ask:askSize:...` of the top `depth` levels, using the raw strings
+    pub fn checksum_crc32(&self, depth: usize) -> u32 {
+        let format = |level: &BookLevel| match &level.raw {
+            Some((price, size)) => format!("{}:{}", price, size),
+            None => format!("{}:{}", level.price, level.size),
+        };
+        let bids: Vec<&BookLevel> = self.levels(Intent::Bid).take(depth).collect();
+        let asks: Vec<&BookLevel> = self.levels(Intent::Ask).take(depth).collect();
+        let mut parts = Vec::with_capacity(bids.len() + asks.len());
+        for i in 0..depth {
+            if let Some(bid) = bids.get(i) {
+                parts.push(format(bid));
+            }
+            if let Some(ask) = asks.get(i) {
+                parts.push(format(ask));
+            }
+        }
+        crc32fast::hash(parts.join(":").as_bytes())
+    }
+}
@@ -0,0 +1,345 @@
+//! Sequencing of snapshot and incremental depth messages into an `OrderBook`
+
+use crate::{BookLevel, InstrumentCode, Intent, OrderBook, Quote, Quotes, Time};
+use thiserror::Error;
+
+/// how consecutive update ids of a venue relate to each other
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BookSequenceRule {
+    /// snapshots only, every message replaces the book
+    Snapshot,
+    /// the first id of an update follows the last applied id
+    Contiguous,
+    /// the id range of an update covers the id after the last applied one
+    Overlapping,
+    /// an update carries the last id of the previous update (binance futures `pu`)
+    Linked,
+    /// ids only increase, gaps are caught by the checksum
+    Increasing,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct BookSequence {
+    pub first: u64,
+    pub last: u64,
+    /// last id of the previous update, only used by `BookSequenceRule::Linked`
+    pub prev: u64,
+}
+impl BookSequence {
+    pub fn single(seq: u64) -> Self {
+        Self {
+            first: seq,
+            last: seq,
+            prev: 0,
+        }
+    }
+    pub fn range(first: u64, last: u64) -> Self {
+        Self { first, last, prev: 0 }
+    }
+    pub fn linked(prev: u64, first: u64, last: u64) -> Self {
+        Self { first, last, prev }
+    }
+}
+
+/// one depth message of a venue, either a snapshot or an update
+#[derive(Clone, Debug)]
+pub struct BookDelta {
+    pub sequence: BookSequence,
+    pub bids: Vec<BookLevel>,
+    pub asks: Vec<BookLevel>,
+    pub checksum: Option<u32>,
+    pub exchange_time: Time,
+    pub received_time: Time,
+}
+impl BookDelta {
+    pub fn new(sequence: BookSequence) -> Self {
+        Self {
+            sequence,
+            bids: vec![],
+            asks: vec![],
+            checksum: None,
+            exchange_time: Time::NULL,
+            received_time: Time::now(),
+        }
+    }
+    pub fn with_levels(
+        mut self,
+        bids: impl IntoIterator<Item = (f64, f64)>,
+        asks: impl IntoIterator<Item = (f64, f64)>,
+    ) -> Self {
+        self.bids = bids.into_iter().map(|(p, s)| BookLevel::new(p, s)).collect();
+        self.asks = asks.into_iter().map(|(p, s)| BookLevel::new(p, s)).collect();
+        self
+    }
+    pub fn to_quotes(&self, instrument: InstrumentCode) -> Quotes {
+        let mut quotes = Quotes::new(instrument);
+        quotes.first_seq = self.sequence.first;
+        quotes.last_seq = self.sequence.last;
+        quotes.exchange_time = self.exchange_time;
+        quotes.received_time = self.received_time;
+        for level in &self.bids {
+            quotes.insert_quote(Quote::update_by_price(Intent::Bid, level.price, level.size));
+        }
+        for level in &self.asks {
+            quotes.insert_quote(Quote::update_by_price(Intent::Ask, level.price, level.size));
+        }
+        quotes
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum BookSyncError {
+    #[error("sequence gap: expected {expected}, got {first}..={last}")]
+    Gap { expected: u64, first: u64, last: u64 },
+    #[error("checksum mismatch: expected {expected}, computed {computed}")]
+    Checksum { expected: u32, computed: u32 },
+    #[error("crossed book: bid {bid} >= ask {ask}")]
+    Crossed { bid: f64, ask: f64 },
+    #[error("more than {0} updates buffered while awaiting the snapshot")]
+    BufferOverflow(usize),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BookSyncStatus {
+    /// updates are buffered until the snapshot arrives
+    AwaitingSnapshot,
+    Synced,
+    /// a gap or checksum mismatch was detected, updates are dropped until resynced
+    OutOfSync,
+}
+
+/// maintains an `OrderBook` from snapshots and updates, detecting gaps and checksum mismatches.
+/// the caller fetches a snapshot (or resubscribes) whenever `needs_snapshot` returns true
+pub struct OrderBookSync {
+    book: OrderBook,
+    rule: BookSequenceRule,
+    status: BookSyncStatus,
+    last_seq: u64,
+    /// the first update after a snapshot is matched against the snapshot id
+    after_snapshot: bool,
+    buffer: Vec<BookDelta>,
+    max_buffer: usize,
+    checksum_depth: usize,
+}
+
+impl OrderBookSync {
+    pub fn new(instrument: InstrumentCode, rule: BookSequenceRule) -> Self {
+        Self {
+            book: OrderBook::new(instrument),
+            rule,
+            status: BookSyncStatus::AwaitingSnapshot,
+            last_seq: 0,
+            after_snapshot: false,
+            buffer: vec![],
+            max_buffer: 1000,
+            checksum_depth: 25,
+        }
+    }
+    pub fn with_checksum_depth(mut self, depth: usize) -> Self {
+        self.checksum_depth = depth;
+        self
+    }
+    pub fn with_max_buffer(mut self, max_buffer: usize) -> Self {
+        self.max_buffer = max_buffer;
+        self
+    }
+    pub fn book(&self) -> &OrderBook {
+        &self.book
+    }
+    pub fn status(&self) -> BookSyncStatus {
+        self.status
+    }
+    pub fn last_seq(&self) -> u64 {
+        self.last_seq
+    }
+    pub fn is_synced(&self) -> bool {
+        self.status == BookSyncStatus::Synced
+    }
+    pub fn needs_snapshot(&self) -> bool {
+        self.status == BookSyncStatus::OutOfSync
+    }
+    /// call before requesting a new snapshot, updates received meanwhile are buffered
+    pub fn begin_resync(&mut self) {
+        self.status = BookSyncStatus::AwaitingSnapshot;
+        self.buffer.clear();
+    }
+    fn fail(&mut self, err: BookSyncError) -> BookSyncError {
+        self.status = BookSyncStatus::OutOfSync;
+        self.buffer.clear();
+        err
+    }
+    /// returns whether the update should be applied, stale updates are skipped
+    fn check_sequence(&self, seq: &BookSequence) -> Result<bool, BookSyncError> {
+        let expected = self.last_seq + 1;
+        let gap = || BookSyncError::Gap {
+            expected,
+            first: seq.first,
+            last: seq.last,
+        };
+        match self.rule {
+            BookSequenceRule::Snapshot => Ok(true),
+            _ if seq.last < expected => Ok(false),
+            BookSequenceRule::Contiguous if seq.first != expected => Err(gap()),
+            BookSequenceRule::Overlapping if seq.first > expected => Err(gap()),
+            BookSequenceRule::Linked
+                if seq.prev != self.last_seq && !(self.after_snapshot && seq.first <= expected) =>
+            {
+                Err(gap())
+            }
+            _ => Ok(true),
+        }
+    }
+    fn apply(&mut self, delta: &BookDelta) {
+        for level in &delta.bids {
+            self.book.update_level(Intent::Bid, level.clone());
+        }
+        for level in &delta.asks {
+            self.book.update_level(Intent::Ask, level.clone());
+        }
+        self.book.exchange_time = delta.exchange_time;
+        self.book.received_time = delta.received_time;
+        self.last_seq = delta.sequence.last;
+        self.after_snapshot = false;
+    }
+    fn verify(&mut self, checksum: Option<u32>) -> Result<(), BookSyncError> {
+        if let Some(expected) = checksum {
+            let computed = self.book.checksum_crc32(self.checksum_depth);
+            if computed != expected {
+                return Err(self.fail(BookSyncError::Checksum { expected, computed }));
+            }
+        }
+        if self.book.is_crossed() {
+            let bid = self.book.best_bid().unwrap().price;
+            let ask = self.book.best_ask().unwrap().price;
+            return Err(self.fail(BookSyncError::Crossed { bid, ask }));
+        }
+        Ok(())
+    }
+    /// replaces the book and replays buffered updates. returns the whole book as quotes
+    pub fn on_snapshot(&mut self, snapshot: BookDelta) -> Result<Quotes, BookSyncError> {
+        self.book.clear();
+        self.apply(&snapshot);
+        self.after_snapshot = true;
+        self.status = BookSyncStatus::Synced;
+        self.verify(snapshot.checksum)?;
+        for delta in std::mem::take(&mut self.buffer) {
+            match self.check_sequence(&delta.sequence) {
+                Ok(true) => {
+                    self.apply(&delta);
+                    self.verify(delta.checksum)?;
+                }
+                Ok(false) => {}
+                Err(err) => return Err(self.fail(err)),
+            }
+        }
+        let mut quotes = self.book.to_quotes();
+        quotes.last_seq = self.last_seq;
+        Ok(quotes)
+    }
+    /// applies an update. returns the quotes to forward, None while buffering, out of sync or stale
+    pub fn on_update(&mut self, delta: BookDelta) -> Result<Option<Quotes>, BookSyncError> {
+        if self.rule == BookSequenceRule::Snapshot {
+            return self.on_snapshot(delta).map(Some);
+        }
+        match self.status {
+            BookSyncStatus::AwaitingSnapshot => {
+                if self.buffer.len() >= self.max_buffer {
+                    return Err(self.fail(BookSyncError::BufferOverflow(self.max_buffer)));
+                }
+                self.buffer.push(delta);
+                Ok(None)
+            }
+            BookSyncStatus::OutOfSync => Ok(None),
+            BookSyncStatus::Synced => match self.check_sequence(&delta.sequence) {
+                Ok(true) => {
+                    self.apply(&delta);
+                    self.verify(delta.checksum)?;
+                    Ok(Some(delta.to_quotes(self.book.instrument.clone())))
+                }
+                Ok(false) => Ok(None),
+                Err(err) => Err(self.fail(err)),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delta(seq: BookSequence, bids: &[(f64, f64)], asks: &[(f64, f64)]) -> BookDelta {
+        BookDelta::new(seq).with_levels(bids.iter().copied(), asks.iter().copied())
+    }
+
+    #[test]
+    fn test_contiguous_buffer_replay_and_gap() {
+        let mut sync = OrderBookSync::new(InstrumentCode::None, BookSequenceRule::Contiguous);
+        // buffered before the snapshot, 10 is older than the snapshot and skipped
+        sync.on_update(delta(BookSequence::single(10), &[(99.0, 5.0)], &[]))
+            .unwrap();
+        sync.on_update(delta(BookSequence::single(11), &[(100.0, 0.0)], &[]))
+            .unwrap();
+        sync.on_snapshot(delta(
+            BookSequence::single(10),
+            &[(100.0, 1.0), (99.0, 2.0)],
+            &[(101.0, 1.0)],
+        ))
+        .unwrap();
+        assert!(sync.is_synced());
+        assert_eq!(sync.book().best_bid().unwrap().price, 99.0);
+        assert_eq!(sync.book().best_bid().unwrap().size, 2.0);
+
+        let quotes = sync.on_update(delta(BookSequence::single(12), &[], &[(102.0, 3.0)]));
+        assert!(quotes.unwrap().is_some());
+        let err = sync.on_update(delta(BookSequence::single(14), &[], &[])).unwrap_err();
+        assert_eq!(
+            err,
+            BookSyncError::Gap {
+                expected: 13,
+                first: 14,
+                last: 14
+            }
+        );
+        assert!(sync.needs_snapshot());
+        assert_eq!(sync.on_update(delta(BookSequence::single(15), &[], &[])).unwrap(), None);
+    }
+
+    #[test]
+    fn test_linked_sequence() {
+        let mut sync = OrderBookSync::new(InstrumentCode::None, BookSequenceRule::Linked);
+        sync.begin_resync();
+        sync.on_snapshot(delta(BookSequence::single(100), &[(10.0, 1.0)], &[(11.0, 1.0)]))
+            .unwrap();
+        // first update straddles the snapshot id
+        sync.on_update(delta(BookSequence::linked(95, 98, 105), &[(10.5, 1.0)], &[]))
+            .unwrap();
+        sync.on_update(delta(BookSequence::linked(105, 110, 120), &[], &[]))
+            .unwrap();
+        assert!(sync
+            .on_update(delta(BookSequence::linked(121, 130, 140), &[], &[]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_checksum_and_sweep() {
+        let mut sync = OrderBookSync::new(InstrumentCode::None, BookSequenceRule::Increasing);
+        let mut snapshot = BookDelta::new(BookSequence::single(1));
+        snapshot.bids = vec![BookLevel::from_raw("3366.1", "7.0").unwrap()];
+        snapshot.asks = vec![
+            BookLevel::from_raw("3366.8", "9.0").unwrap(),
+            BookLevel::from_raw("3368", "8.0").unwrap(),
+        ];
+        snapshot.checksum = Some(crc32fast::hash(b"3366.1:7.0:3366.8:9.0:3368:8.0"));
+        sync.on_snapshot(snapshot).unwrap();
+
+        let mut update = BookDelta::new(BookSequence::single(2));
+        update.checksum = Some(0);
+        assert!(matches!(sync.on_update(update), Err(BookSyncError::Checksum { .. })));
+
+        let sweep = sync.book().depth_at_size(Intent::Ask, 10.0).unwrap();
+        assert!(sweep.filled);
+        assert_eq!(sweep.levels, 2);
+        assert_eq!(sweep.worst_price, 3368.0);
+        assert!(!sync.book().depth_at_notional(Intent::Bid, 1e9).unwrap().filled);
+    }
+}
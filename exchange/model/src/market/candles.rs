@@ -0,0 +1,203 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::{BucketSeries, InstrumentCode, MarketTrade, SeriesRow, Time, OHLCVT};
+
+/// intervals trades are aggregated into
+pub const CANDLE_INTERVALS_MS: [i32; 4] = [1_000, 60_000, 300_000, 3_600_000];
+
+impl SeriesRow for OHLCVT {
+    fn get_timestamp(&self) -> Time {
+        self.exchange_time
+    }
+}
+
+impl OHLCVT {
+    /// opening candle of the bucket the trade falls in. `exchange_time` is the bucket open time
+    pub fn from_trade(trade: &MarketTrade, interval_ms: i32) -> Self {
+        Self {
+            instrument: trade.instrument.clone(),
+            open: trade.price,
+            high: trade.price,
+            low: trade.price,
+            close: trade.price,
+            volume: trade.size,
+            exchange_time: candle_open_time(trade.exchange_time, interval_ms),
+            received_time: trade.received_time,
+            interval_ms,
+        }
+    }
+    pub fn merge_trade(&mut self, trade: &MarketTrade) {
+        self.high = self.high.max(trade.price);
+        self.low = self.low.min(trade.price);
+        self.close = trade.price;
+        self.volume += trade.size;
+        self.received_time = trade.received_time;
+    }
+    pub fn close_time(&self) -> Time {
+        Time::from_millis(self.exchange_time.millis() + self.interval_ms as i64)
+    }
+}
+
+/// start of the bucket of `interval_ms` that contains `time`
+pub fn candle_open_time(time: Time, interval_ms: i32) -> Time {
+    let millis = time.millis();
+    Time::from_millis(millis - millis.rem_euclid(interval_ms as i64))
+}
+
+/// "1s", "1m", "5m", "1h" into milliseconds
+pub fn parse_candle_interval(interval: &str) -> eyre::Result<i32> {
+    let Some((unit_index, unit)) = interval.char_indices().last() else {
+        eyre::bail!("empty candle interval");
+    };
+    let value: i32 = interval[..unit_index]
+        .parse()
+        .map_err(|err| eyre::eyre!("invalid candle interval {interval}: {err}"))?;
+    let unit_ms = match unit {
+        's' => 1_000,
+        'm' => 60_000,
+        'h' => 3_600_000,
+        'd' => 86_400_000,
+        _ => eyre::bail!("invalid candle interval unit in {interval}"),
+    };
+    match value.checked_mul(unit_ms) {
+        Some(interval_ms) if interval_ms > 0 => Ok(interval_ms),
+        _ => eyre::bail!("candle interval {interval} out of range"),
+    }
+}
+
+pub fn format_candle_interval(interval_ms: i32) -> String {
+    match interval_ms {
+        x if x % 86_400_000 == 0 => format!("{}d", x / 86_400_000),
+        x if x % 3_600_000 == 0 => format!("{}h", x / 3_600_000),
+        x if x % 60_000 == 0 => format!("{}m", x / 60_000),
+        x => format!("{}s", x / 1_000),
+    }
+}
+
+/// candles of one instrument for every interval in `CANDLE_INTERVALS_MS`, built from trades
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandleAggregator {
+    pub instrument: InstrumentCode,
+    series: Vec<(i32, BucketSeries<OHLCVT>)>,
+}
+
+impl CandleAggregator {
+    pub fn new(instrument: InstrumentCode, capacity: usize) -> Self {
+        let series = CANDLE_INTERVALS_MS
+            .iter()
+            .map(|&interval_ms| {
+                let interval = Duration::from_millis(interval_ms as u64);
+                (interval_ms, BucketSeries::new_bucket(capacity, interval))
+            })
+            .collect();
+        Self { instrument, series }
+    }
+    fn series(&self, interval_ms: i32) -> Option<&BucketSeries<OHLCVT>> {
+        self.series.iter().find(|(x, _)| *x == interval_ms).map(|(_, s)| s)
+    }
+    /// merges the trade into the current candle of every interval.
+    /// returns the candles closed by this trade. trades older than the current candle are dropped
+    pub fn on_trade(&mut self, trade: &MarketTrade) -> Vec<OHLCVT> {
+        let mut closed = vec![];
+        for (interval_ms, series) in self.series.iter_mut() {
+            let open_time = candle_open_time(trade.exchange_time, *interval_ms);
+            let candle = match series.get(0) {
+                Some(last) if last.exchange_time == open_time => {
+                    let mut candle = last.clone();
+                    candle.merge_trade(trade);
+                    candle
+                }
+                Some(last) if last.exchange_time > open_time => continue,
+                last => {
+                    closed.extend(last.cloned());
+                    OHLCVT::from_trade(trade, *interval_ms)
+                }
+            };
+            series.push(candle);
+        }
+        closed
+    }
+    /// inserts a historical candle, e.g. from a REST backfill. candles older than the last one are ignored
+    pub fn on_candle(&mut self, candle: OHLCVT) {
+        let Some((_, series)) = self.series.iter_mut().find(|(x, _)| *x == candle.interval_ms) else {
+            return;
+        };
+        if series
+            .get(0)
+            .map_or(false, |last| last.exchange_time > candle.exchange_time)
+        {
+            return;
+        }
+        series.push(candle);
+    }
+    /// the candle currently being built
+    pub fn last(&self, interval_ms: i32) -> Option<&OHLCVT> {
+        self.series(interval_ms)?.get(0)
+    }
+    /// up to `n` latest candles, oldest first
+    pub fn last_n(&self, interval_ms: i32, n: usize) -> Vec<OHLCVT> {
+        let Some(series) = self.series(interval_ms) else {
+            return vec![];
+        };
+        let mut candles: Vec<_> = series.last_n(n).cloned().collect();
+        candles.reverse();
+        candles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Exchange, Side};
+
+    fn trade(millis: i64, price: f64, size: f64) -> MarketTrade {
+        MarketTrade {
+            instrument: InstrumentCode::from_symbol(Exchange::BinanceFutures, "BTCUSDT".into()),
+            price,
+            size,
+            side: Side::Buy,
+            exchange_time: Time::from_millis(millis),
+            received_time: Time::from_millis(millis),
+            ..MarketTrade::empty()
+        }
+    }
+
+    #[test]
+    fn test_candle_aggregator() {
+        let mut aggregator = CandleAggregator::new(InstrumentCode::None, 10);
+        assert!(aggregator.on_trade(&trade(60_100, 10.0, 1.0)).is_empty());
+        assert!(aggregator.on_trade(&trade(60_500, 12.0, 2.0)).is_empty());
+        let closed = aggregator.on_trade(&trade(61_200, 9.0, 1.0));
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].interval_ms, 1_000);
+        assert_eq!(
+            (closed[0].open, closed[0].high, closed[0].low, closed[0].close),
+            (10.0, 12.0, 10.0, 12.0)
+        );
+        let minute = aggregator.last(60_000).unwrap();
+        assert_eq!(minute.exchange_time, Time::from_millis(60_000));
+        assert_eq!(
+            (minute.high, minute.low, minute.close, minute.volume),
+            (12.0, 9.0, 9.0, 4.0)
+        );
+        // late trade of a closed second is dropped
+        aggregator.on_trade(&trade(60_900, 100.0, 1.0));
+        assert_eq!(aggregator.last(1_000).unwrap().high, 9.0);
+        assert_eq!(aggregator.last_n(1_000, 5).len(), 2);
+    }
+
+    #[test]
+    fn test_candle_interval() {
+        assert_eq!(parse_candle_interval("5m").unwrap(), 300_000);
+        assert_eq!(parse_candle_interval("1h").unwrap(), 3_600_000);
+        assert!(parse_candle_interval("x").is_err());
+        assert!(parse_candle_interval("").is_err());
+        assert!(parse_candle_interval("5µ").is_err());
+        assert!(parse_candle_interval("0m").is_err());
+        assert!(parse_candle_interval("-1m").is_err());
+        assert!(parse_candle_interval("9999999d").is_err());
+        assert_eq!(format_candle_interval(1_000), "1s");
+        assert_eq!(format_candle_interval(300_000), "5m");
+    }
+}
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{InstrumentCode, Price, Quantity, SeriesRow, Time};
+
+/// per-instrument context of a perpetual, fields are None when the venue doesn't publish them
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DerivativesContextEvent {
+    pub instrument: InstrumentCode,
+    pub mark_price: Option<Price>,
+    /// index price, oracle price on venues without an index
+    pub index_price: Option<Price>,
+    /// open interest in base asset
+    pub open_interest: Option<Quantity>,
+    /// predicted funding rate of the current interval
+    pub funding_rate: Option<f64>,
+    pub next_funding_time: Option<Time>,
+    pub exchange_time: Time,
+    pub received_time: Time,
+}
+
+impl DerivativesContextEvent {
+    pub fn empty(instrument: InstrumentCode) -> Self {
+        Self {
+            instrument,
+            mark_price: None,
+            index_price: None,
+            open_interest: None,
+            funding_rate: None,
+            next_funding_time: None,
+            exchange_time: Time::NULL,
+            received_time: Time::NULL,
+        }
+    }
+    /// mark minus index
+    pub fn basis(&self) -> Option<Price> {
+        Some(self.mark_price? - self.index_price?)
+    }
+    /// basis relative to the index
+    pub fn premium(&self) -> Option<f64> {
+        let index = self.index_price?;
+        if index == 0.0 {
+            return None;
+        }
+        Some(self.basis()? / index)
+    }
+    /// open interest valued at the mark price
+    pub fn open_interest_notional(&self) -> Option<Quantity> {
+        Some(self.open_interest? * self.mark_price?)
+    }
+    /// applies the fields present in a partial update
+    pub fn merge(&mut self, update: &Self) {
+        self.mark_price = update.mark_price.or(self.mark_price);
+        self.index_price = update.index_price.or(self.index_price);
+        self.open_interest = update.open_interest.or(self.open_interest);
+        self.funding_rate = update.funding_rate.or(self.funding_rate);
+        self.next_funding_time = update.next_funding_time.or(self.next_funding_time);
+        self.exchange_time = update.exchange_time;
+        self.received_time = update.received_time;
+    }
+}
+
+impl SeriesRow for DerivativesContextEvent {
+    fn get_timestamp(&self) -> Time {
+        self.exchange_time
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
-    BookTicker, FundingRateEvent, InstrumentCode, Market, MarketTrade, MarketUniversal, PriceEvent, Quotes, Time,
-    OHLCVT,
+    BookTicker, DerivativesContextEvent, FundingRateEvent, InstrumentCode, LiquidationEvent, Market, MarketTrade,
+    MarketUniversal, PriceEvent, Quotes, Time, OHLCVT,
 };
 use derive_from_one::FromOne;
 use tracing::warn;
@@ -16,6 +16,8 @@ pub enum MarketEvent {
     Price(PriceEvent),
     FundingRate(FundingRateEvent),
     FundingRates(Vec<FundingRateEvent>),
+    Liquidation(LiquidationEvent),
+    DerivativesContext(DerivativesContextEvent),
 }
 
 impl MarketEvent {
@@ -32,6 +34,8 @@ impl MarketEvent {
             Self::FundingRates(funding_rates) => funding_rates
                 .first()
                 .map(|funding_rate| funding_rate.instrument.clone()),
+            Self::Liquidation(liquidation) => Some(liquidation.instrument.clone()),
+            Self::DerivativesContext(context) => Some(context.instrument.clone()),
         }
     }
 
@@ -49,6 +53,8 @@ impl MarketEvent {
                 .first()
                 .map(|funding_rate| funding_rate.exchange_time)
                 .unwrap_or(Time::NULL),
+            Self::Liquidation(liquidation) => liquidation.exchange_time,
+            Self::DerivativesContext(context) => context.exchange_time,
         }
     }
 
@@ -141,6 +141,7 @@ pub enum MarketFeedSelector {
     Liquidation,
     Price,
     FundingRate,
+    DerivativesContext,
     #[display("{0}")]
     Depth(MarketFeedDepthSelector),
 }
@@ -153,6 +154,7 @@ impl MarketFeedSelector {
             (Self::Liquidation, MarketFeedKind::Liquidation) => true,
             (Self::Price, MarketFeedKind::Price) => true,
             (Self::FundingRate, MarketFeedKind::FundingRate) => true,
+            (Self::DerivativesContext, MarketFeedKind::DerivativesContext) => true,
             (Self::Depth(s), MarketFeedKind::Depth(o)) => s.match_depth(o),
             _ => false,
         }
@@ -191,6 +193,7 @@ pub enum MarketFeedKind {
     Liquidation,
     Price,
     FundingRate,
+    DerivativesContext,
     #[display("{0}")]
     Depth(MarketFeedDepthKind),
 }
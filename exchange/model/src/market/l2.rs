@@ -1,4 +1,5 @@
-use crate::{BookTicker, Intent, LevelOperation, Price, Quantity, Quote};
+use super::book::{sweep_notional, sweep_size};
+use crate::{BookSweep, BookTicker, Intent, LevelOperation, Price, Quantity, Quote, Side};
 use serde::{Deserialize, Serialize};
 use tracing::info;
 
@@ -231,9 +232,58 @@ impl<const N: usize> L2OrderBook<N> {
 
         (buy_liquidity, sell_liquidity)
     }
+    pub fn side(&self, intent: Intent) -> &HalfOrderBook<N> {
+        match intent {
+            Intent::Bid => &self.bids,
+            Intent::Ask => &self.asks,
+        }
+    }
+    /// total size resting on one side
+    pub fn available_size(&self, intent: Intent) -> Quantity {
+        self.side(intent).levels.iter().map(|x| x.size).sum()
+    }
+    /// sweeps `intent` levels until `notional` of quote asset is consumed.
+    /// a buy consumes `Intent::Ask`, a sell consumes `Intent::Bid`
+    pub fn depth_at_notional(&self, intent: Intent, notional: Quantity) -> Option<BookSweep> {
+        sweep_notional(self.side(intent).levels.iter().map(|x| (x.price, x.size)), notional)
+    }
+    /// sweeps `intent` levels until `size` of base asset is consumed
+    pub fn depth_at_size(&self, intent: Intent, size: Quantity) -> Option<BookSweep> {
+        sweep_size(self.side(intent).levels.iter().map(|x| (x.price, x.size)), size)
+    }
+    /// average price a taker order of `size` fills at, None when the book cannot fill it
+    pub fn executable_price(&self, side: Side, size: Quantity) -> Option<Price> {
+        let sweep = self.depth_at_size(Intent::from(side).opposite(), size)?;
+        sweep.filled.then_some(sweep.average_price)
+    }
 }
 
 pub struct L2OrderBookRepr<'a> {
     pub buys: &'a [Level],
     pub sells: &'a [Level],
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_executable_price() {
+        let mut book = L2OrderBook::<10>::new();
+        book.update_quotes(&[
+            Quote::update_by_level(Intent::Ask, 1, 100.0, 1.0),
+            Quote::update_by_level(Intent::Ask, 2, 101.0, 2.0),
+            Quote::update_by_level(Intent::Bid, 1, 99.0, 1.0),
+        ]);
+        assert_eq!(book.executable_price(Side::Buy, 1.0), Some(100.0));
+        assert_eq!(book.executable_price(Side::Buy, 2.0), Some(100.5));
+        assert_eq!(book.executable_price(Side::Buy, 4.0), None);
+        assert_eq!(book.executable_price(Side::Sell, 1.0), Some(99.0));
+
+        let sweep = book.depth_at_notional(Intent::Ask, 302.0).unwrap();
+        assert!(sweep.filled);
+        assert_eq!(sweep.size, 3.0);
+        assert_eq!(sweep.worst_price, 101.0);
+        assert_eq!(book.available_size(Intent::Ask), 3.0);
+    }
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{InstrumentCode, Price, Quantity, SeriesRow, Side, Time};
+
+/// forced order of a liquidated position
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LiquidationEvent {
+    pub instrument: InstrumentCode,
+    /// side of the forced order, Sell means a long position was liquidated
+    pub side: Side,
+    /// price per quantity
+    pub price: Price,
+    /// size of base asset
+    pub size: Quantity,
+    pub exchange_time: Time,
+    pub received_time: Time,
+}
+
+impl LiquidationEvent {
+    pub fn notional(&self) -> Quantity {
+        self.price * self.size
+    }
+}
+
+impl SeriesRow for LiquidationEvent {
+    fn get_timestamp(&self) -> Time {
+        self.exchange_time
+    }
+}
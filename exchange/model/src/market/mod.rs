@@ -1,7 +1,12 @@
+mod book;
+mod book_sync;
+mod candles;
+mod derivatives;
 mod event;
 mod feed;
 mod funding_rate;
 mod l2;
+mod liquidation;
 mod market;
 mod ohlcvt;
 mod price;
@@ -12,10 +17,15 @@ mod tob;
 mod trade;
 mod trades;
 
+pub use book::*;
+pub use book_sync::*;
+pub use candles::*;
+pub use derivatives::*;
 pub use event::*;
 pub use feed::*;
 pub use funding_rate::*;
 pub use l2::*;
+pub use liquidation::*;
 pub use market::*;
 pub use ohlcvt::*;
 pub use price::*;
@@ -28,6 +28,8 @@ pub enum PriceType {
     Bid,
     Oracle,
     Mark,
+    /// composite of several venues
+    Fair,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
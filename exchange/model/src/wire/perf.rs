@@ -58,6 +58,14 @@ impl PerfRecord {
                 self.event_exchange_time = ohlcv.exchange_time;
                 self.event_received_time = ohlcv.received_time;
             }
+            MarketEvent::Liquidation(liquidation) => {
+                self.event_exchange_time = liquidation.exchange_time;
+                self.event_received_time = liquidation.received_time;
+            }
+            MarketEvent::DerivativesContext(context) => {
+                self.event_exchange_time = context.exchange_time;
+                self.event_received_time = context.received_time;
+            }
             _ => {}
         }
     }
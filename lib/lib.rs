@@ -6,6 +6,7 @@ pub mod handler;
 mod listener;
 pub mod log;
 pub mod log_reader;
+pub mod metrics;
 pub mod signal;
 pub mod toolbox;
 pub mod types;
@@ -0,0 +1,261 @@
+use eyre::{ContextCompat, Result};
+use hyper::header::CONTENT_TYPE;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use lazy_static::lazy_static;
+use parking_lot::RwLock;
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing::*;
+
+lazy_static! {
+    pub static ref METRICS: MetricsRegistry = MetricsRegistry::default();
+}
+
+/// buckets in milliseconds for feed lag and order round trips
+pub const LATENCY_BUCKETS_MS: &[f64] = &[
+    1.0, 2.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0,
+];
+
+#[derive(Default)]
+pub struct Counter(AtomicU64);
+impl Counter {
+    pub fn inc(&self) {
+        self.inc_by(1);
+    }
+    pub fn inc_by(&self, value: u64) {
+        self.0.fetch_add(value, Ordering::Relaxed);
+    }
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// f64 stored as bits, so it can be updated without a lock
+#[derive(Default)]
+pub struct Gauge(AtomicU64);
+impl Gauge {
+    pub fn set(&self, value: f64) {
+        self.0.store(value.to_bits(), Ordering::Relaxed);
+    }
+    pub fn add(&self, value: f64) {
+        let _ = self.0.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |x| {
+            Some((f64::from_bits(x) + value).to_bits())
+        });
+    }
+    pub fn get(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+pub struct Histogram {
+    buckets: Vec<f64>,
+    counts: Vec<AtomicU64>,
+    count: AtomicU64,
+    sum: Gauge,
+}
+impl Histogram {
+    fn new(buckets: &[f64]) -> Self {
+        Self {
+            buckets: buckets.to_vec(),
+            counts: buckets.iter().map(|_| AtomicU64::new(0)).collect(),
+            count: AtomicU64::new(0),
+            sum: Gauge::default(),
+        }
+    }
+    pub fn observe(&self, value: f64) {
+        if let Some(i) = self.buckets.iter().position(|x| value <= *x) {
+            self.counts[i].fetch_add(1, Ordering::Relaxed);
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum.add(value);
+    }
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Clone)]
+enum Metric {
+    Counter(Arc<Counter>),
+    Gauge(Arc<Gauge>),
+    Histogram(Arc<Histogram>),
+}
+impl Metric {
+    fn kind(&self) -> &'static str {
+        match self {
+            Metric::Counter(_) => "counter",
+            Metric::Gauge(_) => "gauge",
+            Metric::Histogram(_) => "histogram",
+        }
+    }
+}
+
+struct Family {
+    help: &'static str,
+    /// keyed by the rendered label set, e.g. `exchange="Hyperliquid"`
+    series: BTreeMap<String, Metric>,
+}
+
+/// Process wide registry of metrics rendered in the Prometheus text format.
+/// Metrics are created on first use, callers on hot paths should keep the returned handle.
+#[derive(Default)]
+pub struct MetricsRegistry {
+    families: RwLock<BTreeMap<&'static str, Family>>,
+}
+
+impl MetricsRegistry {
+    pub fn counter(&self, name: &'static str, help: &'static str, labels: &[(&str, &str)]) -> Arc<Counter> {
+        match self.get_or_insert(name, help, labels, || Metric::Counter(Default::default())) {
+            Metric::Counter(x) => x,
+            metric => panic!("metric {} is a {}", name, metric.kind()),
+        }
+    }
+    pub fn gauge(&self, name: &'static str, help: &'static str, labels: &[(&str, &str)]) -> Arc<Gauge> {
+        match self.get_or_insert(name, help, labels, || Metric::Gauge(Default::default())) {
+            Metric::Gauge(x) => x,
+            metric => panic!("metric {} is a {}", name, metric.kind()),
+        }
+    }
+    pub fn histogram(
+        &self,
+        name: &'static str,
+        help: &'static str,
+        buckets: &[f64],
+        labels: &[(&str, &str)],
+    ) -> Arc<Histogram> {
+        match self.get_or_insert(name, help, labels, || {
+            Metric::Histogram(Arc::new(Histogram::new(buckets)))
+        }) {
+            Metric::Histogram(x) => x,
+            metric => panic!("metric {} is a {}", name, metric.kind()),
+        }
+    }
+    fn get_or_insert(
+        &self,
+        name: &'static str,
+        help: &'static str,
+        labels: &[(&str, &str)],
+        new: impl FnOnce() -> Metric,
+    ) -> Metric {
+        let key = render_labels(labels);
+        if let Some(metric) = self.families.read().get(name).and_then(|x| x.series.get(&key)) {
+            return metric.clone();
+        }
+        let mut families = self.families.write();
+        let family = families.entry(name).or_insert_with(|| Family {
+            help,
+            series: Default::default(),
+        });
+        family.series.entry(key).or_insert_with(new).clone()
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for (name, family) in self.families.read().iter() {
+            let Some(first) = family.series.values().next() else {
+                continue;
+            };
+            let _ = writeln!(out, "# HELP {} {}", name, family.help);
+            let _ = writeln!(out, "# TYPE {} {}", name, first.kind());
+            for (labels, metric) in family.series.iter() {
+                match metric {
+                    Metric::Counter(x) => {
+                        let _ = writeln!(out, "{}{} {}", name, braced(labels), x.get());
+                    }
+                    Metric::Gauge(x) => {
+                        let _ = writeln!(out, "{}{} {}", name, braced(labels), x.get());
+                    }
+                    Metric::Histogram(x) => {
+                        let mut cumulative = 0;
+                        for (bucket, count) in x.buckets.iter().zip(x.counts.iter()) {
+                            cumulative += count.load(Ordering::Relaxed);
+                            let le = join_labels(labels, &format!("le=\"{}\"", bucket));
+                            let _ = writeln!(out, "{}_bucket{{{}}} {}", name, le, cumulative);
+                        }
+                        let le = join_labels(labels, "le=\"+Inf\"");
+                        let _ = writeln!(out, "{}_bucket{{{}}} {}", name, le, x.count());
+                        let _ = writeln!(out, "{}_sum{} {}", name, braced(labels), x.sum.get());
+                        let _ = writeln!(out, "{}_count{} {}", name, braced(labels), x.count());
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+fn render_labels(labels: &[(&str, &str)]) -> String {
+    labels
+        .iter()
+        .map(|(key, value)| {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+            format!("{}=\"{}\"", key, value)
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+fn braced(labels: &str) -> String {
+    if labels.is_empty() {
+        "".to_string()
+    } else {
+        format!("{{{}}}", labels)
+    }
+}
+fn join_labels(labels: &str, extra: &str) -> String {
+    if labels.is_empty() {
+        extra.to_string()
+    } else {
+        format!("{},{}", labels, extra)
+    }
+}
+
+async fn handle_metrics_request(req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let resp = match (req.method(), req.uri().path()) {
+        (&Method::GET, "/metrics") => Response::builder()
+            .header(CONTENT_TYPE, "text/plain; version=0.0.4")
+            .body(Body::from(METRICS.render())),
+        _ => Response::builder().status(StatusCode::NOT_FOUND).body(Body::empty()),
+    };
+    Ok(resp.unwrap())
+}
+
+/// serves `GET /metrics` on its own port, separate from the websocket and http gateway listeners
+pub async fn serve_metrics(address: String) -> Result<()> {
+    let addr = tokio::net::lookup_host(&address)
+        .await?
+        .next()
+        .with_context(|| format!("Failed to lookup host to bind: {}", address))?;
+    info!("Metrics listening on {}", addr);
+    let make_service = make_service_fn(|_conn| async { Ok::<_, Infallible>(service_fn(handle_metrics_request)) });
+    Server::try_bind(&addr)?.serve(make_service).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let registry = MetricsRegistry::default();
+        registry
+            .counter("orders_total", "orders", &[("exchange", "Hyperliquid")])
+            .inc_by(2);
+        registry.gauge("ws_connections", "connections", &[]).set(3.0);
+        let histogram = registry.histogram("lag_ms", "lag", &[1.0, 10.0], &[("exchange", "a\"b")]);
+        histogram.observe(0.5);
+        histogram.observe(5.0);
+        histogram.observe(50.0);
+        let text = registry.render();
+        assert!(text.contains("# TYPE orders_total counter\norders_total{exchange=\"Hyperliquid\"} 2\n"));
+        assert!(text.contains("ws_connections 3\n"));
+        assert!(text.contains("lag_ms_bucket{exchange=\"a\\\"b\",le=\"1\"} 1\n"));
+        assert!(text.contains("lag_ms_bucket{exchange=\"a\\\"b\",le=\"10\"} 2\n"));
+        assert!(text.contains("lag_ms_bucket{exchange=\"a\\\"b\",le=\"+Inf\"} 3\n"));
+        assert!(text.contains("lag_ms_count{exchange=\"a\\\"b\"} 3\n"));
+    }
+}
@@ -112,14 +112,25 @@ impl Toolbox {
             } else {
                 return false;
             };
-            Self::send_ws_msg(&state.message_queue, msg, oneshot);
+            Self::send_ws_msg(&state.message_queue, state.conn.wire_format, msg, oneshot);
             true
         });
     }
 
-    pub fn send_ws_msg(sender: &tokio::sync::mpsc::Sender<Message>, resp: WsResponseValue, oneshot: bool) {
-        let resp = serde_json::to_string(&resp).unwrap();
-        if let Err(err) = sender.try_send(resp.into()) {
+    pub fn send_ws_msg(
+        sender: &tokio::sync::mpsc::Sender<Message>,
+        wire_format: WsWireFormat,
+        resp: WsResponseValue,
+        oneshot: bool,
+    ) {
+        let resp = match wire_format.encode(&resp) {
+            Ok(resp) => resp,
+            Err(err) => {
+                error!("Failed to encode websocket message as {:?}: {:?}", wire_format, err);
+                return;
+            }
+        };
+        if let Err(err) = sender.try_send(resp) {
             warn!("Failed to send websocket message: {:?}", err)
         }
         if oneshot {
@@ -2,6 +2,7 @@ use crate::error_code::ErrorCode;
 use crate::handler::RequestHandlerErased;
 use crate::log::LogLevel;
 use crate::toolbox::RequestContext;
+use crate::ws::WsWireFormat;
 use endpoint_gen::model::EndpointSchema;
 use serde::*;
 use serde_json::Value;
@@ -35,6 +36,7 @@ pub struct WsConnection {
     pub role: AtomicU32,
     pub address: SocketAddr,
     pub log_id: u64,
+    pub wire_format: WsWireFormat,
 }
 impl WsConnection {
     pub fn get_user_id(&self) -> i64 {
@@ -65,6 +67,11 @@ pub struct WsStreamResponseGeneric<Params> {
     pub stream_code: u32,
     pub data: Params,
 }
+/// stream message telling subscribers that rows are gone, `removed` holds the row keys of the stream
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WsStreamRemoval {
+    pub removed: Vec<String>,
+}
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct WsLogResponse {
     pub seq: u32,
@@ -1,5 +1,5 @@
 use crate::log::LogLevel;
-use crate::ws::{WsLogResponse, WsRequestGeneric, WsResponseGeneric, WsResponseValue};
+use crate::ws::{WsLogResponse, WsRequestGeneric, WsResponseGeneric, WsResponseValue, WsWireFormat};
 use eyre::{bail, eyre, Context, Result};
 use futures::SinkExt;
 use futures::StreamExt;
@@ -25,10 +25,23 @@ pub trait WsResponse: Serialize + DeserializeOwned + Send + Sync + Clone {
 pub struct WsClient {
     stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
     seq: u32,
+    wire_format: WsWireFormat,
 }
 impl WsClient {
     pub async fn new(connect_addr: &str, header: &str) -> Result<Self> {
-        let mut req = <&str as IntoClientRequest>::into_client_request(connect_addr)?;
+        Self::new_with_format(connect_addr, header, WsWireFormat::JSON).await
+    }
+    /// connects and negotiates `wire_format` for requests and responses
+    pub async fn new_with_format(connect_addr: &str, header: &str, wire_format: WsWireFormat) -> Result<Self> {
+        let query = wire_format.to_query();
+        let connect_addr = if query.is_empty() {
+            connect_addr.to_string()
+        } else if connect_addr.contains('?') {
+            format!("{}&{}", connect_addr, query)
+        } else {
+            format!("{}?{}", connect_addr, query)
+        };
+        let mut req = connect_addr.into_client_request()?;
         req.headers_mut()
             .insert("Sec-WebSocket-Protocol", HeaderValue::from_str(header)?);
 
@@ -36,68 +49,73 @@ impl WsClient {
         Ok(Self {
             stream: ws_stream,
             seq: 0,
+            wire_format,
         })
     }
     pub async fn send_req(&mut self, method: u32, params: impl Serialize) -> Result<()> {
         self.seq += 1;
-        let req = serde_json::to_string(&WsRequestGeneric {
+        let req = self.wire_format.encode(&WsRequestGeneric {
             method,
             seq: self.seq,
             params,
         })?;
         debug!("send req: {}", req);
-        self.stream.send(Message::Text(req)).await?;
+        self.stream.send(req).await?;
         Ok(())
     }
     pub async fn recv_raw(&mut self) -> Result<WsResponseValue> {
         let msg = self.stream.next().await.ok_or(eyre!("Connection closed"))??;
-        let resp: WsResponseValue = serde_json::from_str(&msg.to_string())?;
+        let resp: WsResponseValue = match msg {
+            Message::Binary(data) => self.wire_format.decode_binary(&data)?,
+            msg => serde_json::from_str(&msg.to_string())?,
+        };
         Ok(resp)
     }
     pub async fn recv_resp<T: DeserializeOwned>(&mut self) -> Result<T> {
         loop {
             let msg = self.stream.next().await.ok_or(eyre!("Connection closed"))??;
-            match msg {
+            let resp: WsResponseGeneric<T> = match msg {
                 Message::Text(text) => {
                     debug!("recv resp: {}", text);
-                    let resp: WsResponseGeneric<T> = serde_json::from_str(&text)?;
-                    match resp {
-                        WsResponseGeneric::Immediate(resp) if resp.seq == self.seq => {
-                            return Ok(resp.params);
-                        }
-                        WsResponseGeneric::Immediate(resp) => {
-                            bail!("Seq mismatch this: {} got: {}", self.seq, resp.seq)
-                        }
-                        WsResponseGeneric::Stream(_) => {
-                            debug!("expect immediate response, got stream")
-                        }
-                        WsResponseGeneric::Forwarded(_) => {
-                            debug!("expect immediate response, got forwarded")
-                        }
-                        WsResponseGeneric::Close => {
-                            bail!("unreachable")
-                        }
-                        WsResponseGeneric::Log(WsLogResponse {
-                            log_id, level, message, ..
-                        }) => match level {
-                            LogLevel::Error => error!(?log_id, "{}", message),
-                            LogLevel::Warn => warn!(?log_id, "{}", message),
-                            LogLevel::Info => info!(?log_id, "{}", message),
-                            LogLevel::Debug => debug!(?log_id, "{}", message),
-                            LogLevel::Trace => trace!(?log_id, "{}", message),
-                            LogLevel::Detail => trace!(?log_id, "{}", message),
-                            LogLevel::Off => {}
-                        },
-                        WsResponseGeneric::Error(err) => {
-                            bail!("Error: {} {:?}", err.code, err.params)
-                        }
-                    }
+                    serde_json::from_str(&text)?
                 }
+                Message::Binary(data) => self.wire_format.decode_binary(&data)?,
                 Message::Close(_) => {
                     self.stream.close(None).await?;
                     bail!("Connection closed")
                 }
-                _ => {}
+                _ => continue,
+            };
+            match resp {
+                WsResponseGeneric::Immediate(resp) if resp.seq == self.seq => {
+                    return Ok(resp.params);
+                }
+                WsResponseGeneric::Immediate(resp) => {
+                    bail!("Seq mismatch this: {} got: {}", self.seq, resp.seq)
+                }
+                WsResponseGeneric::Stream(_) => {
+                    debug!("expect immediate response, got stream")
+                }
+                WsResponseGeneric::Forwarded(_) => {
+                    debug!("expect immediate response, got forwarded")
+                }
+                WsResponseGeneric::Close => {
+                    bail!("unreachable")
+                }
+                WsResponseGeneric::Log(WsLogResponse {
+                    log_id, level, message, ..
+                }) => match level {
+                    LogLevel::Error => error!(?log_id, "{}", message),
+                    LogLevel::Warn => warn!(?log_id, "{}", message),
+                    LogLevel::Info => info!(?log_id, "{}", message),
+                    LogLevel::Debug => debug!(?log_id, "{}", message),
+                    LogLevel::Trace => trace!(?log_id, "{}", message),
+                    LogLevel::Detail => trace!(?log_id, "{}", message),
+                    LogLevel::Off => {}
+                },
+                WsResponseGeneric::Error(err) => {
+                    bail!("Error: {} {:?}", err.code, err.params)
+                }
             }
         }
     }
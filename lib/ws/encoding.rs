@@ -0,0 +1,212 @@
+use eyre::{bail, Result};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::io::{Read, Write};
+use std::str::FromStr;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Serialization used for binary frames, the serde derives of the generated models are shared by all of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WsEncoding {
+    #[default]
+    Json,
+    #[serde(rename = "msgpack")]
+    MessagePack,
+    Cbor,
+}
+impl WsEncoding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WsEncoding::Json => "json",
+            WsEncoding::MessagePack => "msgpack",
+            WsEncoding::Cbor => "cbor",
+        }
+    }
+}
+impl Display for WsEncoding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+impl FromStr for WsEncoding {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(WsEncoding::Json),
+            "msgpack" | "messagepack" => Ok(WsEncoding::MessagePack),
+            "cbor" => Ok(WsEncoding::Cbor),
+            _ => bail!("Unsupported encoding: {}", s),
+        }
+    }
+}
+
+/// Application-level encoding of each message payload, applied after the serialization, like an HTTP
+/// `Content-Encoding`. It is not a WebSocket extension: nothing is negotiated in `Sec-WebSocket-Extensions`,
+/// where an offered RFC 7692 permessage-deflate is declined since tungstenite does not implement it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WsPayloadEncoding {
+    #[default]
+    Identity,
+    /// raw deflate stream of each payload on its own, without the context takeover of permessage-deflate
+    Deflate,
+}
+impl WsPayloadEncoding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WsPayloadEncoding::Identity => "identity",
+            WsPayloadEncoding::Deflate => "deflate",
+        }
+    }
+}
+impl Display for WsPayloadEncoding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+impl FromStr for WsPayloadEncoding {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "" | "identity" => Ok(WsPayloadEncoding::Identity),
+            "deflate" => Ok(WsPayloadEncoding::Deflate),
+            _ => bail!("Unsupported payload encoding: {}", s),
+        }
+    }
+}
+
+/// Wire format of a connection, negotiated at handshake with the `encoding` and `payload_encoding` query parameters,
+/// e.g. `wss://host/?encoding=msgpack&payload_encoding=deflate`.
+///
+/// A deflated payload is always sent as a binary frame and clients inflate it themselves.
+/// Text frames are always JSON, so a client can keep sending JSON requests on a binary connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WsWireFormat {
+    pub encoding: WsEncoding,
+    pub payload_encoding: WsPayloadEncoding,
+}
+impl WsWireFormat {
+    pub const JSON: Self = Self {
+        encoding: WsEncoding::Json,
+        payload_encoding: WsPayloadEncoding::Identity,
+    };
+    pub fn new(encoding: WsEncoding, payload_encoding: WsPayloadEncoding) -> Self {
+        Self {
+            encoding,
+            payload_encoding,
+        }
+    }
+    pub fn from_query(query: Option<&str>) -> Result<Self> {
+        let mut this = Self::JSON;
+        for pair in query.unwrap_or_default().split('&') {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            match key {
+                "encoding" => this.encoding = value.parse()?,
+                "payload_encoding" => this.payload_encoding = value.parse()?,
+                // reads like the permessage-deflate extension, which is not what is implemented here
+                "compression" => bail!("Unsupported parameter compression, use payload_encoding"),
+                _ => {}
+            }
+        }
+        Ok(this)
+    }
+    /// query string that negotiates this format, empty for plain JSON
+    pub fn to_query(&self) -> String {
+        let mut params = vec![];
+        if self.encoding != WsEncoding::Json {
+            params.push(format!("encoding={}", self.encoding));
+        }
+        if self.payload_encoding != WsPayloadEncoding::Identity {
+            params.push(format!("payload_encoding={}", self.payload_encoding));
+        }
+        params.join("&")
+    }
+    pub fn is_binary(&self) -> bool {
+        self.encoding != WsEncoding::Json || self.payload_encoding != WsPayloadEncoding::Identity
+    }
+
+    pub fn encode(&self, value: &impl Serialize) -> Result<Message> {
+        let payload = match self.encoding {
+            WsEncoding::Json => {
+                let text = serde_json::to_string(value)?;
+                if self.payload_encoding == WsPayloadEncoding::Identity {
+                    return Ok(Message::Text(text));
+                }
+                text.into_bytes()
+            }
+            WsEncoding::MessagePack => rmp_serde::to_vec_named(value)?,
+            WsEncoding::Cbor => {
+                let mut buf = vec![];
+                ciborium::ser::into_writer(value, &mut buf)?;
+                buf
+            }
+        };
+        match self.payload_encoding {
+            WsPayloadEncoding::Identity => Ok(Message::Binary(payload)),
+            WsPayloadEncoding::Deflate => {
+                let mut encoder = DeflateEncoder::new(Vec::with_capacity(payload.len() / 2), Compression::fast());
+                encoder.write_all(&payload)?;
+                Ok(Message::Binary(encoder.finish()?))
+            }
+        }
+    }
+    pub fn decode_binary<T: DeserializeOwned>(&self, data: &[u8]) -> Result<T> {
+        let inflated;
+        let data = match self.payload_encoding {
+            WsPayloadEncoding::Identity => data,
+            WsPayloadEncoding::Deflate => {
+                let mut buf = vec![];
+                DeflateDecoder::new(data).read_to_end(&mut buf)?;
+                inflated = buf;
+                &inflated[..]
+            }
+        };
+        Ok(match self.encoding {
+            WsEncoding::Json => serde_json::from_slice(data)?,
+            WsEncoding::MessagePack => rmp_serde::from_slice(data)?,
+            WsEncoding::Cbor => ciborium::de::from_reader(data)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ws::{WsResponseValue, WsStreamResponse};
+    use serde_json::json;
+
+    #[test]
+    fn test_wire_format_roundtrip() {
+        let resp = WsResponseValue::Stream(WsStreamResponse {
+            original_seq: 1,
+            method: 20000,
+            stream_seq: 2,
+            stream_code: 3,
+            data: json!({"data": [{"symbol": "BTC", "price": 1.5, "size": -2}]}),
+        });
+        for encoding in [WsEncoding::Json, WsEncoding::MessagePack, WsEncoding::Cbor] {
+            for payload_encoding in [WsPayloadEncoding::Identity, WsPayloadEncoding::Deflate] {
+                let format = WsWireFormat::new(encoding, payload_encoding);
+                assert_eq!(WsWireFormat::from_query(Some(&format.to_query())).unwrap(), format);
+                let decoded: WsResponseValue = match format.encode(&resp).unwrap() {
+                    Message::Text(text) => serde_json::from_str(&text).unwrap(),
+                    Message::Binary(data) => format.decode_binary(&data).unwrap(),
+                    msg => panic!("unexpected message {:?}", msg),
+                };
+                assert_eq!(
+                    serde_json::to_value(&decoded).unwrap(),
+                    serde_json::to_value(&resp).unwrap()
+                );
+            }
+        }
+        assert!(WsWireFormat::from_query(Some("encoding=xml")).is_err());
+        assert!(WsWireFormat::from_query(Some("compression=deflate")).is_err());
+        assert!(WsWireFormat::from_query(Some("payload_encoding=gzip")).is_err());
+    }
+}
@@ -1,5 +1,5 @@
 use crate::toolbox::{ArcToolbox, RequestContext, Toolbox};
-use crate::ws::WsConnection;
+use crate::ws::{WsConnection, WsWireFormat};
 use chrono::Utc;
 use convert_case::Case;
 use convert_case::Casing;
@@ -16,7 +16,8 @@ use tracing::*;
 
 pub struct VerifyProtocol<'a> {
     pub addr: SocketAddr,
-    pub tx: tokio::sync::mpsc::Sender<String>,
+    /// receives the protocol header and the wire format requested in the query string
+    pub tx: tokio::sync::mpsc::Sender<(String, WsWireFormat)>,
     pub allow_cors_domains: &'a Option<Vec<String>>,
 }
 
@@ -38,7 +39,10 @@ impl<'a> Callback for VerifyProtocol<'a> {
             None => "".to_string(),
         };
 
-        self.tx.try_send(protocol_str.clone()).unwrap();
+        let wire_format =
+            WsWireFormat::from_query(request.uri().query()).map_err(|err| ErrorResponse::new(Some(err.to_string())))?;
+
+        self.tx.try_send((protocol_str.clone(), wire_format)).unwrap();
 
         response
             .headers_mut()
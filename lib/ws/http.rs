@@ -0,0 +1,379 @@
+use crate::error_code::ErrorCode;
+use crate::listener::{ConnectionListener, TcpListener, TlsListener};
+use crate::toolbox::{RequestContext, TOOLBOX};
+use crate::utils::{get_conn_id, get_log_id};
+use crate::ws::*;
+use eyre::{bail, ContextCompat, Result};
+use hyper::header::{
+    HeaderValue, ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_ORIGIN, AUTHORIZATION, CONTENT_TYPE, ORIGIN,
+};
+use hyper::server::conn::Http;
+use hyper::service::service_fn;
+use hyper::{Body, Method, Request, Response, StatusCode};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::atomic::AtomicU32;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::*;
+
+/// how long a http request waits for the handler to respond
+const HTTP_RESPONSE_TIMEOUT: Duration = Duration::from_secs(30);
+/// optional scheme in front of the auth string in the `Authorization` header
+const HTTP_AUTH_SCHEME: &str = "Bearer ";
+
+#[derive(Clone, Copy)]
+struct LocalExec;
+
+impl<F: Future + 'static> hyper::rt::Executor<F> for LocalExec {
+    fn execute(&self, fut: F) {
+        tokio::task::spawn_local(fut);
+    }
+}
+
+/// Exposes every non-streaming endpoint of a `WebsocketServer` as `POST /api/<EndpointName>`.
+/// Requests go through the same auth controller, handler and error codes as the websocket session,
+/// the handler reply is captured from the connection message queue and returned as the http body.
+pub struct HttpGateway {
+    server: Arc<WebsocketServer>,
+    states: Arc<WebsocketStates>,
+    endpoints: HashMap<String, u32>,
+    openapi: String,
+}
+
+impl HttpGateway {
+    pub fn new(server: Arc<WebsocketServer>, states: Arc<WebsocketStates>) -> Self {
+        let endpoints = server
+            .handlers
+            .values()
+            .filter(|x| x.schema.stream_response.is_none())
+            .map(|x| (x.schema.name.clone(), x.schema.code))
+            .collect();
+        let schemas = server.handlers.values().map(|x| &x.schema);
+        let openapi = serde_json::to_string_pretty(&generate_openapi(&server.config.name, schemas))
+            .expect("Failed to serialize openapi");
+        Self {
+            server,
+            states,
+            endpoints,
+            openapi,
+        }
+    }
+
+    pub async fn listen(self: Arc<Self>, address: String) -> Result<()> {
+        info!("Http gateway listening on {}", address);
+        let addr = tokio::net::lookup_host(&address)
+            .await?
+            .next()
+            .with_context(|| format!("Failed to lookup host to bind: {}", address))?;
+
+        let listener = TcpListener::bind(addr).await?;
+        let config = &self.server.config;
+        if config.insecure {
+            self.listen_impl(Arc::new(listener)).await
+        } else if config.pub_certs.is_some() && config.priv_key.is_some() {
+            let listener = TlsListener::bind(
+                listener,
+                config.pub_certs.clone().unwrap(),
+                config.priv_key.clone().unwrap(),
+            )
+            .await?;
+            self.listen_impl(Arc::new(listener)).await
+        } else {
+            bail!("pub_certs and priv_key should be set")
+        }
+    }
+
+    async fn listen_impl<T: ConnectionListener + 'static>(self: Arc<Self>, listener: Arc<T>) -> Result<()> {
+        loop {
+            let (stream, addr) = match listener.accept().await {
+                Ok(x) => x,
+                Err(err) => {
+                    error!("Error while accepting http stream: {:?}", err);
+                    continue;
+                }
+            };
+            let listener = Arc::clone(&listener);
+            let this = Arc::clone(&self);
+            tokio::task::spawn_local(async move {
+                let stream = match listener.handshake(stream).await {
+                    Ok(stream) => stream,
+                    Err(err) => {
+                        error!("Error while handshaking http stream: {:?}", err);
+                        return;
+                    }
+                };
+                let service = service_fn(move |req| {
+                    let this = Arc::clone(&this);
+                    async move { Ok::<_, Infallible>(this.handle_request(addr, req).await) }
+                });
+                if let Err(err) = Http::new()
+                    .with_executor(LocalExec)
+                    .serve_connection(stream, service)
+                    .await
+                {
+                    debug!(?addr, "Error while serving http connection: {:?}", err);
+                }
+            });
+        }
+    }
+
+    async fn handle_request(&self, addr: SocketAddr, req: Request<Body>) -> Response<Body> {
+        let origin = req.headers().get(ORIGIN).cloned();
+        let path = req.uri().path().trim_end_matches('/').to_string();
+        let mut resp = match (req.method(), path.as_str()) {
+            (&Method::GET, "/api/openapi.json") => json_response(StatusCode::OK, self.openapi.clone()),
+            (&Method::POST, path) if path.starts_with("/api/") => {
+                let name = &path["/api/".len()..];
+                match self.endpoints.get(name) {
+                    Some(&code) => self.handle_endpoint(addr, code, req).await,
+                    None => error_response(&RequestContext::empty(), ErrorCode::new(100404), Value::Null),
+                }
+            }
+            _ => error_response(&RequestContext::empty(), ErrorCode::new(100404), Value::Null),
+        };
+        self.apply_cors(origin, &mut resp);
+        resp
+    }
+
+    async fn handle_endpoint(&self, addr: SocketAddr, code: u32, req: Request<Body>) -> Response<Body> {
+        let Some(_permit) = RATE_LIMITER.acquire_connection(addr.ip()) else {
+            let ctx = RequestContext {
+                method: code,
+                ..RequestContext::empty()
+            };
+            return error_response(
+                &ctx,
+                ErrorCode::new(THROTTLED_ERROR_CODE),
+                "Too many connections from this address",
+            );
+        };
+        let conn = Arc::new(WsConnection {
+            connection_id: get_conn_id(),
+            user_id: Default::default(),
+            role: AtomicU32::new(0),
+            address: addr,
+            log_id: get_log_id(),
+            wire_format: WsWireFormat::JSON,
+        });
+        let (tx, mut rx) = mpsc::channel(100);
+        self.states.insert(conn.connection_id, tx, conn.clone());
+        let resp = self.handle_endpoint_impl(&conn, code, req, &mut rx).await;
+        self.states.remove(conn.connection_id);
+        resp
+    }
+
+    async fn handle_endpoint_impl(
+        &self,
+        conn: &Arc<WsConnection>,
+        code: u32,
+        req: Request<Body>,
+        rx: &mut mpsc::Receiver<Message>,
+    ) -> Response<Body> {
+        let ctx = RequestContext {
+            method: code,
+            ..RequestContext::from_conn(conn)
+        };
+        let toolbox = self.server.toolbox.clone();
+
+        // auth goes through the same controller as the websocket handshake,
+        // the `Authorization` header carries the same string as `Sec-WebSocket-Protocol` there
+        let header = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|x| x.to_str().ok())
+            .map(|x| x.strip_prefix(HTTP_AUTH_SCHEME).unwrap_or(x))
+            .unwrap_or_default()
+            .to_string();
+        let auth_result = TOOLBOX
+            .scope(
+                toolbox.clone(),
+                Arc::clone(&self.server.auth_controller).auth(&toolbox, header, Arc::clone(conn)),
+            )
+            .await;
+        if let Err(err) = auth_result {
+            return error_response(&ctx, ErrorCode::new(100400), err.to_string());
+        }
+        // the auth controller replies on the connection queue, an error there means the auth failed
+        while let Ok(msg) = rx.try_recv() {
+            if let Some(WsResponseValue::Error(err)) = decode_message(msg) {
+                return error_response(&ctx, ErrorCode::new(err.code), err.params);
+            }
+        }
+
+        let body = match hyper::body::to_bytes(req.into_body()).await {
+            Ok(body) => body,
+            Err(err) => return error_response(&ctx, ErrorCode::new(100400), err.to_string()),
+        };
+        if !RATE_LIMITER.check_message_size(body.len()) {
+            return error_response(&ctx, ErrorCode::new(100400), "Message exceeds max size");
+        }
+        let params: Value = if body.is_empty() {
+            Value::Object(Default::default())
+        } else {
+            match serde_json::from_slice(&body) {
+                Ok(params) => params,
+                Err(err) => return error_response(&ctx, ErrorCode::new(100400), err.to_string()),
+            }
+        };
+
+        // user id and role are only known once the auth controller has run
+        let ctx = RequestContext {
+            method: code,
+            ..RequestContext::from_conn(conn)
+        };
+        let Some(endpoint) = self.server.handlers.get(&code) else {
+            return error_response(&ctx, ErrorCode::new(100501), Value::Null);
+        };
+        if !RATE_LIMITER.check_request(&ctx) {
+            return error_response(&ctx, ErrorCode::new(THROTTLED_ERROR_CODE), Value::Null);
+        }
+        let handler = endpoint.handler.clone();
+        TOOLBOX
+            .scope(toolbox.clone(), handler.handle(&toolbox, ctx, params))
+            .await;
+
+        let wait = async {
+            while let Some(msg) = rx.recv().await {
+                match decode_message(msg) {
+                    Some(WsResponseValue::Immediate(resp)) => {
+                        return json_response(StatusCode::OK, serde_json::to_string(&resp.params).unwrap());
+                    }
+                    Some(WsResponseValue::Error(err)) => {
+                        return json_response(error_status(err.code), serde_json::to_string(&err).unwrap());
+                    }
+                    _ => continue,
+                }
+            }
+            error_response(&ctx, ErrorCode::new(100500), Value::Null)
+        };
+        match tokio::time::timeout(HTTP_RESPONSE_TIMEOUT, wait).await {
+            Ok(resp) => resp,
+            Err(_) => error_response(&ctx, ErrorCode::new(100500), "Timed out waiting for response"),
+        }
+    }
+
+    fn apply_cors(&self, origin: Option<HeaderValue>, resp: &mut Response<Body>) {
+        let Some(origin) = origin else {
+            return;
+        };
+        if let Some(allow_cors_domains) = self.server.config.allow_cors_urls.as_ref() {
+            if !allow_cors_domains
+                .iter()
+                .any(|x| origin.to_str().map(|o| o == x).unwrap_or_default())
+            {
+                return;
+            }
+        }
+        resp.headers_mut().insert(ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+        resp.headers_mut()
+            .insert(ACCESS_CONTROL_ALLOW_CREDENTIALS, HeaderValue::from_static("true"));
+    }
+}
+
+fn decode_message(msg: Message) -> Option<WsResponseValue> {
+    match msg {
+        Message::Text(text) => serde_json::from_str(&text).ok(),
+        Message::Binary(bin) => serde_json::from_slice(&bin).ok(),
+        _ => None,
+    }
+}
+
+/// maps error codes like 100404 or 101403 onto the matching http status, others become 400
+fn error_status(code: u32) -> StatusCode {
+    let status = code % 1000;
+    if (100_000..200_000).contains(&code) && (400..600).contains(&status) {
+        StatusCode::from_u16(status as u16).unwrap_or(StatusCode::BAD_REQUEST)
+    } else {
+        StatusCode::BAD_REQUEST
+    }
+}
+
+fn error_response(ctx: &RequestContext, code: ErrorCode, params: impl Into<Value>) -> Response<Body> {
+    let WsResponseValue::Error(err) = request_error_to_resp(ctx, code, params) else {
+        unreachable!()
+    };
+    json_response(error_status(err.code), serde_json::to_string(&err).unwrap())
+}
+
+fn json_response(status: StatusCode, body: String) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handler::RequestHandlerErased;
+    use crate::toolbox::ArcToolbox;
+    use async_trait::async_trait;
+    use endpoint_gen::model::{EndpointSchema, Field, Type};
+    use serde_json::json;
+
+    struct EchoHandler;
+
+    #[async_trait(?Send)]
+    impl RequestHandlerErased for EchoHandler {
+        async fn handle(&self, toolbox: &ArcToolbox, ctx: RequestContext, req: Value) {
+            toolbox.send_response(&ctx, json!({ "echo": req }));
+        }
+    }
+
+    fn gateway() -> HttpGateway {
+        let mut server = WebsocketServer::new(WsServerConfig {
+            name: "test".to_string(),
+            ..Default::default()
+        });
+        let schema = EndpointSchema::new(
+            "Echo",
+            10010,
+            vec![Field::new("value", Type::Int)],
+            vec![Field::new("echo", Type::Object)],
+        );
+        server.add_handler_erased(schema, Arc::new(EchoHandler));
+        let states = Arc::new(WebsocketStates::new());
+        server.toolbox.set_ws_states(states.clone_states(), false);
+        HttpGateway::new(Arc::new(server), states)
+    }
+
+    async fn body_json(resp: Response<Body>) -> Value {
+        let body = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_gateway_routing_round_trip() {
+        let gateway = gateway();
+        let addr: SocketAddr = "127.0.0.1:12345".parse().unwrap();
+
+        let req = Request::post("/api/Echo")
+            .header(AUTHORIZATION, "Bearer 0authorize,1user,2token")
+            .body(Body::from(r#"{"value":7}"#))
+            .unwrap();
+        let resp = gateway.handle_request(addr, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(body_json(resp).await, json!({"echo": {"value": 7}}));
+
+        let req = Request::get("/api/openapi.json").body(Body::empty()).unwrap();
+        let resp = gateway.handle_request(addr, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(body_json(resp).await["paths"]["/api/Echo"]["post"].is_object());
+
+        let req = Request::post("/api/Missing").body(Body::empty()).unwrap();
+        let resp = gateway.handle_request(addr, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body_json(resp).await["code"], json!(100404));
+
+        let req = Request::get("/api/Echo").body(Body::empty()).unwrap();
+        let resp = gateway.handle_request(addr, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -1,7 +1,12 @@
 mod basics;
 mod client;
 mod conn;
+mod encoding;
 mod headers;
+mod http;
+mod openapi;
+mod rate_limit;
+mod replay;
 mod server;
 mod session;
 mod subs;
@@ -9,7 +14,12 @@ mod subs;
 pub use basics::*;
 pub use client::*;
 pub use conn::*;
+pub use encoding::*;
 pub use headers::*;
+pub use http::*;
+pub use openapi::*;
+pub use rate_limit::*;
+pub use replay::*;
 pub use server::*;
 pub use session::*;
 pub use subs::*;
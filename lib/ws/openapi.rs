@@ -0,0 +1,180 @@
+use convert_case::{Case, Casing};
+use endpoint_gen::model::{EndpointSchema, Field, Type};
+use itertools::Itertools;
+use serde_json::{json, Map, Value};
+
+fn type_to_json_schema(ty: &Type) -> Value {
+    match ty {
+        Type::Int => json!({"type": "integer", "format": "int32"}),
+        Type::BigInt => json!({"type": "integer", "format": "int64"}),
+        Type::TimeStampMs => json!({"type": "integer", "format": "int64", "description": "timestamp in milliseconds"}),
+        Type::Numeric => json!({"type": "number", "format": "double"}),
+        Type::Boolean => json!({"type": "boolean"}),
+        Type::String => json!({"type": "string"}),
+        Type::UUID => json!({"type": "string", "format": "uuid"}),
+        Type::Object => json!({"type": "object"}),
+        Type::Struct { name, fields } => fields_to_json_schema(Some(name), fields),
+        Type::StructRef(name) => json!({"type": "object", "title": name}),
+        Type::DataTable { name, fields } => json!({
+            "type": "array",
+            "items": fields_to_json_schema(Some(name), fields),
+        }),
+        Type::Vec(ty) => json!({"type": "array", "items": type_to_json_schema(ty)}),
+        Type::Optional(ty) => {
+            let mut schema = type_to_json_schema(ty);
+            if let Value::Object(obj) = &mut schema {
+                obj.insert("nullable".to_string(), Value::Bool(true));
+            }
+            schema
+        }
+        Type::Enum { name, variants } => json!({
+            "type": "string",
+            "title": name,
+            "enum": variants.iter().map(|x| x.name.clone()).collect_vec(),
+        }),
+        Type::EnumRef(name) => json!({"type": "string", "title": name}),
+        _ => json!({}),
+    }
+}
+
+fn fields_to_json_schema(title: Option<&str>, fields: &[Field]) -> Value {
+    let mut properties = Map::new();
+    let mut required = vec![];
+    for field in fields {
+        let name = field.name.to_case(Case::Camel);
+        if !matches!(field.ty, Type::Optional(_)) {
+            required.push(Value::String(name.clone()));
+        }
+        properties.insert(name, type_to_json_schema(&field.ty));
+    }
+    let mut schema = json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    });
+    if let Some(title) = title {
+        schema["title"] = Value::String(title.to_string());
+    }
+    schema
+}
+
+/// Generates an OpenAPI 3 document describing `POST /api/<EndpointName>` for each non-streaming endpoint
+pub fn generate_openapi<'a>(title: &str, schemas: impl IntoIterator<Item = &'a EndpointSchema>) -> Value {
+    let mut paths = Map::new();
+    for schema in schemas
+        .into_iter()
+        .filter(|x| x.stream_response.is_none())
+        .sorted_by_key(|x| x.code)
+    {
+        paths.insert(
+            format!("/api/{}", schema.name),
+            json!({
+                "post": {
+                    "operationId": schema.name,
+                    "summary": schema.description,
+                    "x-method-code": schema.code,
+                    "requestBody": {
+                        "required": false,
+                        "content": {
+                            "application/json": {
+                                "schema": fields_to_json_schema(Some(&format!("{}Request", schema.name)), &schema.parameters),
+                            }
+                        }
+                    },
+                    "responses": {
+                        "200": {
+                            "description": "success",
+                            "content": {
+                                "application/json": {
+                                    "schema": fields_to_json_schema(Some(&format!("{}Response", schema.name)), &schema.returns),
+                                }
+                            }
+                        },
+                        "default": {
+                            "description": "error, `code` is the same error code as the websocket protocol",
+                            "content": {
+                                "application/json": {
+                                    "schema": {"$ref": "#/components/schemas/ErrorResponse"}
+                                }
+                            }
+                        }
+                    }
+                }
+            }),
+        );
+    }
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": title,
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": paths,
+        "components": {
+            "schemas": {
+                "ErrorResponse": {
+                    "type": "object",
+                    "properties": {
+                        "method": {"type": "integer"},
+                        "code": {"type": "integer"},
+                        "seq": {"type": "integer"},
+                        "log_id": {"type": "string"},
+                        "params": {},
+                    }
+                }
+            },
+            "securitySchemes": {
+                "authorizationHeader": {
+                    "type": "apiKey",
+                    "in": "header",
+                    "name": "Authorization",
+                    "description": "same auth string as the websocket Sec-WebSocket-Protocol handshake, optionally prefixed with `Bearer `, e.g. 0authorize,1username,2token,3service,4device_id,5device_os"
+                }
+            }
+        },
+        "security": [{"authorizationHeader": []}],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openapi_round_trip() {
+        let mut stream = EndpointSchema::new("SubStream", 10020, vec![], vec![]);
+        stream.stream_response = Some(Type::Int);
+        let schemas = vec![
+            EndpointSchema::new(
+                "GetThing",
+                10010,
+                vec![
+                    Field::new("thing_id", Type::BigInt),
+                    Field::new("label", Type::optional(Type::String)),
+                ],
+                vec![Field::new("value", Type::Numeric)],
+            ),
+            stream,
+        ];
+        let doc = generate_openapi("test", &schemas);
+        let text = serde_json::to_string(&doc).unwrap();
+        let parsed: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(parsed, doc);
+
+        let paths = parsed["paths"].as_object().unwrap();
+        assert_eq!(paths.keys().collect_vec(), vec!["/api/GetThing"]);
+        let post = &parsed["paths"]["/api/GetThing"]["post"];
+        assert_eq!(post["x-method-code"], json!(10010));
+        let request = &post["requestBody"]["content"]["application/json"]["schema"];
+        assert_eq!(request["title"], json!("GetThingRequest"));
+        assert_eq!(request["properties"]["thingId"]["format"], json!("int64"));
+        assert_eq!(request["properties"]["label"]["nullable"], json!(true));
+        assert_eq!(request["required"], json!(["thingId"]));
+        let response = &post["responses"]["200"]["content"]["application/json"]["schema"];
+        assert_eq!(response["properties"]["value"]["type"], json!("number"));
+        assert_eq!(
+            parsed["components"]["securitySchemes"]["authorizationHeader"]["name"],
+            json!("Authorization")
+        );
+    }
+}
@@ -0,0 +1,308 @@
+use crate::error_code::ErrorCode;
+use crate::metrics::METRICS;
+use crate::toolbox::{CustomError, RequestContext};
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+/// error code returned when a connection, request or subscription is throttled
+pub const THROTTLED_ERROR_CODE: u32 = 100429;
+
+lazy_static! {
+    pub static ref RATE_LIMITER: RateLimiter = RateLimiter::default();
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointRateLimit {
+    /// endpoint code, e.g. 20291 for UserGetLedger
+    pub code: u32,
+    pub requests_per_second: f64,
+    #[serde(default)]
+    pub burst: Option<f64>,
+}
+
+/// all limits are disabled when not set
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RateLimitConfig {
+    #[serde(default)]
+    pub max_connections_per_ip: Option<u32>,
+    #[serde(default)]
+    pub max_message_size: Option<usize>,
+    /// default token bucket refill rate per user and endpoint
+    #[serde(default)]
+    pub requests_per_second: Option<f64>,
+    /// default token bucket size, falls back to `requests_per_second`
+    #[serde(default)]
+    pub burst: Option<f64>,
+    /// per endpoint overrides of `requests_per_second` and `burst`
+    #[serde(default)]
+    pub endpoints: Vec<EndpointRateLimit>,
+    /// max number of connections subscribed to one `SubscriptionManager`
+    #[serde(default)]
+    pub max_subscribers_per_stream: Option<usize>,
+    /// max number of keys (e.g. symbols) a connection can subscribe to in one `SubscriptionManager`
+    #[serde(default)]
+    pub max_keys_per_subscriber: Option<usize>,
+}
+
+impl RateLimitConfig {
+    fn bucket_for(&self, code: u32) -> Option<(f64, f64)> {
+        if let Some(endpoint) = self.endpoints.iter().find(|x| x.code == code) {
+            let burst = endpoint.burst.unwrap_or(endpoint.requests_per_second.max(1.0));
+            return Some((endpoint.requests_per_second, burst));
+        }
+        let rate = self.requests_per_second?;
+        Some((rate, self.burst.unwrap_or(rate.max(1.0))))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum RateLimitSubject {
+    User(i64),
+    Ip(IpAddr),
+}
+impl RateLimitSubject {
+    fn from_ctx(ctx: &RequestContext) -> Self {
+        // unauthenticated requests are limited by address
+        if ctx.user_id != 0 {
+            Self::User(ctx.user_id)
+        } else {
+            Self::Ip(ctx.ip_addr)
+        }
+    }
+}
+
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+impl TokenBucket {
+    fn refilled(&self, now: Instant, rate: f64, burst: f64) -> f64 {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        (self.tokens + elapsed * rate).min(burst)
+    }
+    fn try_take(&mut self, rate: f64, burst: f64) -> bool {
+        let now = Instant::now();
+        self.tokens = self.refilled(now, rate, burst);
+        self.updated_at = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+pub struct RateLimitCounters {
+    pub active_connections: u64,
+    pub rejected_connections: u64,
+    pub throttled_requests: u64,
+    pub oversized_messages: u64,
+    pub rejected_subscriptions: u64,
+}
+
+#[derive(Default)]
+pub struct RateLimiter {
+    config: RwLock<RateLimitConfig>,
+    connections: DashMap<IpAddr, u32>,
+    buckets: DashMap<(RateLimitSubject, u32), TokenBucket>,
+    active_connections: AtomicU64,
+    active_websockets: AtomicU64,
+    rejected_connections: AtomicU64,
+    throttled_requests: AtomicU64,
+    oversized_messages: AtomicU64,
+    rejected_subscriptions: AtomicU64,
+}
+
+impl RateLimiter {
+    pub fn set_config(&self, config: RateLimitConfig) {
+        *self.config.write() = config;
+    }
+    pub fn config(&self) -> RateLimitConfig {
+        self.config.read().clone()
+    }
+
+    /// returns a permit that releases the connection slot on drop, or None if the address is over its cap
+    pub fn acquire_connection(&'static self, ip: IpAddr) -> Option<ConnectionPermit> {
+        let max = self.config.read().max_connections_per_ip;
+        let mut count = self.connections.entry(ip).or_default();
+        if let Some(max) = max {
+            if *count >= max {
+                self.rejected_connections.fetch_add(1, Ordering::Relaxed);
+                return None;
+            }
+        }
+        *count += 1;
+        self.active_connections.fetch_add(1, Ordering::Relaxed);
+        Some(ConnectionPermit {
+            limiter: self,
+            ip,
+            websocket: false,
+        })
+    }
+    fn release_connection(&self, ip: IpAddr, websocket: bool) {
+        self.active_connections.fetch_sub(1, Ordering::Relaxed);
+        if websocket {
+            let active = self.active_websockets.fetch_sub(1, Ordering::Relaxed) - 1;
+            set_websockets_gauge(active);
+        }
+        self.connections.remove_if_mut(&ip, |_, count| {
+            *count = count.saturating_sub(1);
+            *count == 0
+        });
+        self.evict_full_buckets();
+    }
+    /// drops buckets that have refilled to their burst, they behave the same as a fresh bucket.
+    /// runs on every disconnect so buckets of users and addresses that went away don't pile up
+    fn evict_full_buckets(&self) {
+        let config = self.config.read();
+        let now = Instant::now();
+        self.buckets.retain(|(_, code), bucket| match config.bucket_for(*code) {
+            Some((rate, burst)) => bucket.refilled(now, rate, burst) < burst,
+            None => false,
+        });
+    }
+
+    /// takes a token from the bucket of the user (or address) and endpoint code in `ctx`
+    pub fn check_request(&self, ctx: &RequestContext) -> bool {
+        let Some((rate, burst)) = self.config.read().bucket_for(ctx.method) else {
+            return true;
+        };
+        let mut bucket = self
+            .buckets
+            .entry((RateLimitSubject::from_ctx(ctx), ctx.method))
+            .or_insert_with(|| TokenBucket {
+                tokens: burst,
+                updated_at: Instant::now(),
+            });
+        let allowed = bucket.try_take(rate, burst);
+        if !allowed {
+            self.throttled_requests.fetch_add(1, Ordering::Relaxed);
+        }
+        allowed
+    }
+
+    pub fn check_message_size(&self, size: usize) -> bool {
+        match self.config.read().max_message_size {
+            Some(max) if size > max => {
+                self.oversized_messages.fetch_add(1, Ordering::Relaxed);
+                false
+            }
+            _ => true,
+        }
+    }
+
+    /// checks the subscription limits given the current subscriber count and the keys count of the connection
+    pub fn check_subscription(&self, subscribers: usize, keys: usize) -> eyre::Result<()> {
+        let config = self.config.read();
+        let over_subscribers = config.max_subscribers_per_stream.map_or(false, |max| subscribers > max);
+        let over_keys = config.max_keys_per_subscriber.map_or(false, |max| keys > max);
+        if over_subscribers || over_keys {
+            self.rejected_subscriptions.fetch_add(1, Ordering::Relaxed);
+            return Err(CustomError::new(ErrorCode::new(THROTTLED_ERROR_CODE), "Subscription limit reached").into());
+        }
+        Ok(())
+    }
+
+    pub fn counters(&self) -> RateLimitCounters {
+        RateLimitCounters {
+            active_connections: self.active_connections.load(Ordering::Relaxed),
+            rejected_connections: self.rejected_connections.load(Ordering::Relaxed),
+            throttled_requests: self.throttled_requests.load(Ordering::Relaxed),
+            oversized_messages: self.oversized_messages.load(Ordering::Relaxed),
+            rejected_subscriptions: self.rejected_subscriptions.load(Ordering::Relaxed),
+        }
+    }
+}
+
+pub struct ConnectionPermit {
+    limiter: &'static RateLimiter,
+    ip: IpAddr,
+    websocket: bool,
+}
+impl ConnectionPermit {
+    /// marks the connection as an upgraded websocket, only those are counted in the `ws_connections` gauge
+    pub fn upgraded(mut self) -> Self {
+        if !self.websocket {
+            self.websocket = true;
+            let active = self.limiter.active_websockets.fetch_add(1, Ordering::Relaxed) + 1;
+            set_websockets_gauge(active);
+        }
+        self
+    }
+}
+fn set_websockets_gauge(active: u64) {
+    METRICS
+        .gauge("ws_connections", "Open upgraded websocket connections", &[])
+        .set(active as _);
+}
+
+impl Drop for ConnectionPermit {
+    fn drop(&mut self) {
+        self.limiter.release_connection(self.ip, self.websocket);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let limiter = RateLimiter::default();
+        limiter.set_config(RateLimitConfig {
+            requests_per_second: Some(0.001),
+            burst: Some(2.0),
+            ..Default::default()
+        });
+        let ctx = RequestContext {
+            user_id: 1,
+            method: 20000,
+            ..RequestContext::empty()
+        };
+        assert!(limiter.check_request(&ctx));
+        assert!(limiter.check_request(&ctx));
+        assert!(!limiter.check_request(&ctx));
+        // other users and endpoints have their own bucket
+        assert!(limiter.check_request(&RequestContext { user_id: 2, ..ctx }));
+        assert!(limiter.check_request(&RequestContext { method: 20010, ..ctx }));
+        assert_eq!(limiter.counters().throttled_requests, 1);
+    }
+
+    #[test]
+    fn test_evict_full_buckets() {
+        let limiter = RateLimiter::default();
+        limiter.set_config(RateLimitConfig {
+            requests_per_second: Some(0.001),
+            burst: Some(2.0),
+            endpoints: vec![EndpointRateLimit {
+                code: 20010,
+                requests_per_second: 1_000_000.0,
+                burst: Some(2.0),
+            }],
+            ..Default::default()
+        });
+        let ctx = RequestContext {
+            user_id: 1,
+            method: 20000,
+            ..RequestContext::empty()
+        };
+        assert!(limiter.check_request(&ctx));
+        assert!(limiter.check_request(&RequestContext { method: 20010, ..ctx }));
+        std::thread::sleep(std::time::Duration::from_millis(1));
+        limiter.evict_full_buckets();
+        // the slow bucket is still draining, the fast one has refilled and is dropped
+        assert_eq!(limiter.buckets.len(), 1);
+        assert!(limiter.buckets.contains_key(&(RateLimitSubject::User(1), 20000)));
+
+        limiter.set_config(RateLimitConfig::default());
+        limiter.evict_full_buckets();
+        assert!(limiter.buckets.is_empty());
+    }
+}
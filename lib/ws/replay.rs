@@ -0,0 +1,71 @@
+use serde_json::Value;
+use std::collections::VecDeque;
+
+pub struct ReplayEntry<Key> {
+    pub seq: u32,
+    /// stream code the message was sent with, e.g. removals have their own code
+    pub stream_code: u32,
+    /// keys the message was published to, empty when it was published to all subscribers
+    pub keys: Vec<Key>,
+    /// None when the message was built for one subscriber only and can't be replayed to others
+    pub data: Option<Value>,
+}
+
+/// Bounded ring buffer of the messages published on a stream, used to resume a subscription
+/// after a reconnect. Sequence numbers start at 1, so a `resume_from_seq` of 0 means nothing was received.
+pub struct ReplayBuffer<Key> {
+    capacity: usize,
+    next_seq: u32,
+    entries: VecDeque<ReplayEntry<Key>>,
+}
+
+impl<Key> ReplayBuffer<Key> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            next_seq: 1,
+            entries: VecDeque::with_capacity(capacity),
+        }
+    }
+    /// sequence number of the last published message, 0 if none
+    pub fn last_seq(&self) -> u32 {
+        self.next_seq - 1
+    }
+    pub fn push(&mut self, stream_code: u32, keys: Vec<Key>, data: Option<Value>) -> u32 {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        if self.capacity == 0 {
+            return seq;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(ReplayEntry {
+            seq,
+            stream_code,
+            keys,
+            data,
+        });
+        seq
+    }
+    /// returns the entries published after `resume_from_seq`,
+    /// or None when the buffer no longer holds all of them and the client needs a snapshot
+    pub fn replay_from(&self, resume_from_seq: u32) -> Option<impl Iterator<Item = &ReplayEntry<Key>>> {
+        if resume_from_seq > self.last_seq() {
+            // the stream was restarted since
+            return None;
+        }
+        if resume_from_seq < self.last_seq() {
+            let oldest = self.entries.front()?.seq;
+            if oldest > resume_from_seq + 1 {
+                return None;
+            }
+        }
+        let entries = self.entries.iter().filter(move |x| x.seq > resume_from_seq);
+        // a per subscriber message in the range can't be rebuilt for the resuming connection
+        if entries.clone().any(|x| x.data.is_none()) {
+            return None;
+        }
+        Some(entries)
+    }
+}
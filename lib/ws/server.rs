@@ -6,6 +6,7 @@ use crate::utils::{get_conn_id, get_log_id};
 use crate::ws::*;
 use endpoint_gen::model::EndpointSchema;
 use eyre::{bail, eyre, ContextCompat, Result};
+use futures::SinkExt;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -33,6 +34,7 @@ pub struct WebsocketServer {
 
 impl WebsocketServer {
     pub fn new(config: WsServerConfig) -> Self {
+        RATE_LIMITER.set_config(config.rate_limit.clone());
         Self {
             auth_controller: Arc::new(SimpleAuthController),
             handlers: Default::default(),
@@ -78,16 +80,29 @@ impl WebsocketServer {
         // TODO remove below after tracing log issue
         tracing::warn!("handle new WS connection");
 
-        let stream = wrap_ws_error(hs)?;
+        let mut stream = wrap_ws_error(hs)?;
+        let (headers, wire_format) = rx.recv().await.ok_or_else(|| eyre!("Failed to receive ws headers"))?;
+        let Some(permit) = RATE_LIMITER.acquire_connection(addr.ip()) else {
+            let ctx = RequestContext::empty();
+            let resp = request_error_to_resp(
+                &ctx,
+                ErrorCode::new(THROTTLED_ERROR_CODE),
+                "Too many connections from this address",
+            );
+            let _ = stream.send(wire_format.encode(&resp)?).await;
+            let _ = stream.close(None).await;
+            bail!("Too many connections from {}", addr.ip());
+        };
+        let _permit = permit.upgraded();
         let conn = Arc::new(WsConnection {
             connection_id: get_conn_id(),
             user_id: Default::default(),
             role: AtomicU32::new(0),
             address: addr,
             log_id: get_log_id(),
+            wire_format,
         });
         debug!(?addr, "New connection handshaken {:?}", conn);
-        let headers = rx.recv().await.ok_or_else(|| eyre!("Failed to receive ws headers"))?;
 
         let (tx, rx) = mpsc::channel(100);
         let conn = Arc::clone(&conn);
@@ -159,6 +174,14 @@ impl WebsocketServer {
             .set_ws_states(states.clone_states(), self.config.header_only);
         let this = Arc::new(self);
         let local_set = LocalSet::new();
+        if let Some(http_address) = this.config.http_address.clone() {
+            let gateway = Arc::new(HttpGateway::new(Arc::clone(&this), Arc::clone(&states)));
+            local_set.spawn_local(async move {
+                if let Err(err) = gateway.listen(http_address).await {
+                    error!("Http gateway terminated: {:?}", err);
+                }
+            });
+        }
         let (mut sigterm, mut sigint) = crate::signal::init_signals()?;
         local_set
             .run_until(async {
@@ -247,6 +270,11 @@ pub struct WsServerConfig {
     pub insecure: bool,
     #[serde(default)]
     pub debug: bool,
+    /// serves non-streaming endpoints as `POST /api/<EndpointName>` when set
+    #[serde(default)]
+    pub http_address: Option<String>,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
     #[serde(skip)]
     pub header_only: bool,
     #[serde(skip)]
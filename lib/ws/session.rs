@@ -1,6 +1,8 @@
 use crate::error_code::ErrorCode;
 use crate::toolbox::{RequestContext, TOOLBOX};
-use crate::ws::{request_error_to_resp, WebsocketServer, WsConnection, WsRequestValue};
+use crate::ws::{
+    request_error_to_resp, WebsocketServer, WsConnection, WsRequestValue, RATE_LIMITER, THROTTLED_ERROR_CODE,
+};
 use eyre::Result;
 use futures::StreamExt;
 use futures::{Sink, SinkExt, Stream};
@@ -59,15 +61,29 @@ impl<
         let addr = &self.conn_info.address;
         let mut context = RequestContext::from_conn(&self.conn_info);
 
-        let obj: Result<WsRequestValue, _> = match msg {
+        if let Message::Text(_) | Message::Binary(_) = &msg {
+            if !RATE_LIMITER.check_message_size(msg.len()) {
+                self.server.toolbox.send(
+                    context.connection_id,
+                    request_error_to_resp(
+                        &context,
+                        ErrorCode::new(100400), // BadRequest
+                        "Message exceeds max size",
+                    ),
+                );
+                return Ok(true);
+            }
+        }
+        let obj: Result<WsRequestValue> = match msg {
             Message::Text(t) => {
                 debug!(?addr, "Handling request {}", t);
 
-                serde_json::from_str(&t)
+                serde_json::from_str(&t).map_err(eyre::Error::from)
             }
             Message::Binary(b) => {
                 debug!(?addr, "Handling request <BIN>");
-                serde_json::from_slice(&b)
+                // binary frames use the wire format negotiated at handshake, JSON by default
+                self.conn_info.wire_format.decode_binary(&b)
             }
             Message::Ping(_) => {
                 return Ok(true);
@@ -117,6 +133,13 @@ impl<
                 return Ok(true);
             }
         };
+        if !RATE_LIMITER.check_request(&context) {
+            self.server.toolbox.send(
+                context.connection_id,
+                request_error_to_resp(&context, ErrorCode::new(THROTTLED_ERROR_CODE), Value::Null),
+            );
+            return Ok(true);
+        }
         let handler = handler.handler.clone();
         let toolbox = self.server.toolbox.clone();
         tokio::task::spawn_local(async move {
@@ -1,11 +1,14 @@
 use serde::Serialize;
+use serde_json::Value;
 use std::borrow::Borrow;
 use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
 use std::sync::atomic::{AtomicU32, Ordering};
 
 use crate::toolbox::{ArcToolbox, RequestContext};
-use crate::ws::{ConnectionId, WsResponseGeneric, WsStreamResponseGeneric};
+use crate::ws::{
+    ConnectionId, ReplayBuffer, WsResponseGeneric, WsStreamRemoval, WsStreamResponseGeneric, RATE_LIMITER,
+};
 
 pub struct SubscribeContext<S> {
     pub ctx: RequestContext,
@@ -17,6 +20,7 @@ pub struct SubscriptionManager<S, Key = ()> {
     pub stream_code: u32,
     pub subscribes: HashMap<ConnectionId, SubscribeContext<S>>,
     pub mappings: HashMap<Key, HashSet<ConnectionId>>,
+    pub replay: Option<ReplayBuffer<Key>>,
 }
 
 impl<S, Key: Eq + Hash> SubscriptionManager<S, Key> {
@@ -25,10 +29,21 @@ impl<S, Key: Eq + Hash> SubscriptionManager<S, Key> {
             stream_code,
             subscribes: Default::default(),
             mappings: Default::default(),
+            replay: None,
         }
     }
+    /// keeps the last `capacity` messages published to keys or to all, so subscribers can resume
+    pub fn with_replay(mut self, capacity: usize) -> Self {
+        self.replay = Some(ReplayBuffer::new(capacity));
+        self
+    }
 
-    pub fn subscribe(&mut self, ctx: RequestContext, setting: S, modify: impl FnOnce(&mut SubscribeContext<S>)) {
+    pub fn subscribe(
+        &mut self,
+        ctx: RequestContext,
+        setting: S,
+        modify: impl FnOnce(&mut SubscribeContext<S>),
+    ) -> eyre::Result<()> {
         self.subscribe_with(ctx, vec![], || setting, modify)
     }
     pub fn subscribe_with_keys(
@@ -37,7 +52,7 @@ impl<S, Key: Eq + Hash> SubscriptionManager<S, Key> {
         keys: Vec<Key>,
         setting: S,
         modify: impl FnOnce(&mut SubscribeContext<S>),
-    ) {
+    ) -> eyre::Result<()> {
         self.subscribe_with(ctx, keys, || setting, modify)
     }
     pub fn subscribe_with(
@@ -46,7 +61,20 @@ impl<S, Key: Eq + Hash> SubscriptionManager<S, Key> {
         keys: Vec<Key>,
         new: impl FnOnce() -> S,
         modify: impl FnOnce(&mut SubscribeContext<S>),
-    ) {
+    ) -> eyre::Result<()> {
+        // enforce the subscription limits before touching any state
+        let subscribers = self.subscribes.len() + usize::from(!self.subscribes.contains_key(&ctx.connection_id));
+        let mut subscribed_keys = self
+            .mappings
+            .iter()
+            .filter(|(_, conn_ids)| conn_ids.contains(&ctx.connection_id))
+            .count();
+        subscribed_keys += keys
+            .iter()
+            .filter(|key| !self.is_subscribed_to(key, ctx.connection_id))
+            .count();
+        RATE_LIMITER.check_subscription(subscribers, subscribed_keys)?;
+
         self.subscribes
             .entry(ctx.connection_id)
             .and_modify(modify)
@@ -59,6 +87,13 @@ impl<S, Key: Eq + Hash> SubscriptionManager<S, Key> {
         for key in keys {
             self.mappings.entry(key).or_default().insert(ctx.connection_id);
         }
+        Ok(())
+    }
+    fn is_subscribed_to(&self, key: &Key, connection_id: ConnectionId) -> bool {
+        self.mappings
+            .get(key)
+            .map(|conn_ids| conn_ids.contains(&connection_id))
+            .unwrap_or_default()
     }
 
     pub fn unsubscribe(&mut self, connection_id: ConnectionId) {
@@ -94,48 +129,79 @@ impl<S, Key: Eq + Hash> SubscriptionManager<S, Key> {
     }
 
     pub fn publish_to(&mut self, toolbox: &ArcToolbox, connection_id: ConnectionId, msg: &impl Serialize) {
-        let mut dead_connection = None;
-
+        let data = serde_json::to_value(msg).unwrap();
+        self.send_to(toolbox, connection_id, self.stream_code, None, data);
+    }
+    /// sends to one subscriber. `seq` is the replay buffer seq of a message published to several subscribers,
+    /// a message for this subscriber only takes the next seq of the replay buffer when the stream has one,
+    /// so every frame of a replay enabled stream is numbered in the same sequence
+    fn send_to(
+        &mut self,
+        toolbox: &ArcToolbox,
+        connection_id: ConnectionId,
+        stream_code: u32,
+        seq: Option<u32>,
+        data: Value,
+    ) {
         let Some(sub) = self.subscribes.get(&connection_id) else {
             return;
         };
-
-        let data = serde_json::to_value(msg).unwrap();
+        let stream_seq = match (seq, self.replay.as_mut()) {
+            (Some(seq), _) => seq,
+            (None, Some(replay)) => replay.push(stream_code, vec![], None),
+            (None, None) => sub.stream_seq.fetch_add(1, Ordering::SeqCst),
+        };
 
         let msg = WsResponseGeneric::Stream(WsStreamResponseGeneric {
             original_seq: sub.ctx.seq,
             method: sub.ctx.method,
-            stream_seq: sub.stream_seq.fetch_add(1, Ordering::SeqCst),
-            stream_code: self.stream_code,
-            data: data.clone(),
+            stream_seq,
+            stream_code,
+            data,
         });
 
         if !toolbox.send(sub.ctx.connection_id, msg) {
-            dead_connection = Some(sub.ctx.connection_id);
-        }
-
-        if let Some(conn_id) = dead_connection {
-            self.unsubscribe(conn_id)
+            self.unsubscribe(connection_id)
         }
     }
     pub fn publish_to_key<Q>(&mut self, toolbox: &ArcToolbox, key: &Q, msg: &impl Serialize)
     where
         Key: Borrow<Q>,
-        Q: Eq + Hash + ?Sized,
+        Q: Eq + Hash + ToOwned<Owned = Key> + ?Sized,
     {
-        let Some(conn_ids) = self.mappings.get(key).cloned() else {
-            return;
-        };
-
-        for conn_id in conn_ids {
-            self.publish_to(toolbox, conn_id, msg);
-        }
+        self.publish_to_keys(toolbox, &[key], msg)
     }
     pub fn publish_to_keys<Q>(&mut self, toolbox: &ArcToolbox, keys: &[&Q], msg: &impl Serialize)
     where
         Key: Borrow<Q>,
-        Q: Eq + Hash + ?Sized,
+        Q: Eq + Hash + ToOwned<Owned = Key> + ?Sized,
     {
+        let data = serde_json::to_value(msg).unwrap();
+        self.publish_to_keys_with_code(toolbox, self.stream_code, keys, data)
+    }
+    /// tells the subscribers of `keys` that the rows identified by `removed` are gone, sent with `stream_code`
+    pub fn publish_removal_to_keys<Q>(
+        &mut self,
+        toolbox: &ArcToolbox,
+        stream_code: u32,
+        keys: &[&Q],
+        removed: Vec<String>,
+    ) where
+        Key: Borrow<Q>,
+        Q: Eq + Hash + ToOwned<Owned = Key> + ?Sized,
+    {
+        let data = serde_json::to_value(WsStreamRemoval { removed }).unwrap();
+        self.publish_to_keys_with_code(toolbox, stream_code, keys, data)
+    }
+    fn publish_to_keys_with_code<Q>(&mut self, toolbox: &ArcToolbox, stream_code: u32, keys: &[&Q], data: Value)
+    where
+        Key: Borrow<Q>,
+        Q: Eq + Hash + ToOwned<Owned = Key> + ?Sized,
+    {
+        let seq = self.replay.as_mut().map(|replay| {
+            let keys = keys.iter().map(|key| (*key).to_owned()).collect();
+            replay.push(stream_code, keys, Some(data.clone()))
+        });
         let mut published = HashSet::new();
         for key in keys {
             let conn_ids = self.mappings.get(key).cloned();
@@ -143,42 +209,94 @@ impl<S, Key: Eq + Hash> SubscriptionManager<S, Key> {
                 for conn_id in conn_ids.iter() {
                     // if newly inserted
                     if published.insert(*conn_id) {
-                        self.publish_to(toolbox, *conn_id, msg);
+                        self.send_to(toolbox, *conn_id, stream_code, seq, data.clone());
                     }
                 }
             }
         }
     }
+    /// messages published with a filter differ per subscriber, on a replay enabled stream they share one seq
+    /// of the buffer but can't be replayed, resuming across them needs a fresh snapshot
     pub fn publish_with_filter<M: Serialize>(
         &mut self,
         toolbox: &ArcToolbox,
         filter: impl Fn(&SubscribeContext<S>) -> Option<M>,
     ) {
-        let mut dead_connections = vec![];
-
-        for sub in self.subscribes.values() {
-            let Some(data) = filter(sub) else {
-                continue;
-            };
-            let data = serde_json::to_value(&data).unwrap();
-            let msg = WsResponseGeneric::Stream(WsStreamResponseGeneric {
-                original_seq: sub.ctx.seq,
-                method: sub.ctx.method,
-                stream_seq: sub.stream_seq.fetch_add(1, Ordering::SeqCst),
-                stream_code: self.stream_code,
-                data,
-            });
-
-            if !toolbox.send(sub.ctx.connection_id, msg) {
-                dead_connections.push(sub.ctx.connection_id);
-            }
+        let messages: Vec<(ConnectionId, Value)> = self
+            .subscribes
+            .iter()
+            .filter_map(|(conn_id, sub)| Some((*conn_id, serde_json::to_value(filter(sub)?).unwrap())))
+            .collect();
+        if messages.is_empty() {
+            return;
         }
-        for conn_id in dead_connections {
-            self.unsubscribe(conn_id);
+        let stream_code = self.stream_code;
+        let seq = self
+            .replay
+            .as_mut()
+            .map(|replay| replay.push(stream_code, vec![], None));
+        for (conn_id, data) in messages {
+            self.send_to(toolbox, conn_id, stream_code, seq, data);
         }
     }
     pub fn publish_to_all(&mut self, toolbox: &ArcToolbox, msg: &impl Serialize) {
-        self.publish_with_filter(toolbox, |_| Some(msg))
+        let data = serde_json::to_value(msg).unwrap();
+        self.publish_to_all_with_code(toolbox, self.stream_code, data)
+    }
+    /// tells every subscriber that the rows identified by `removed` are gone, sent with `stream_code`
+    pub fn publish_removal_to_all(&mut self, toolbox: &ArcToolbox, stream_code: u32, removed: Vec<String>) {
+        let data = serde_json::to_value(WsStreamRemoval { removed }).unwrap();
+        self.publish_to_all_with_code(toolbox, stream_code, data)
+    }
+    fn publish_to_all_with_code(&mut self, toolbox: &ArcToolbox, stream_code: u32, data: Value) {
+        let seq = self
+            .replay
+            .as_mut()
+            .map(|replay| replay.push(stream_code, vec![], Some(data.clone())));
+        let conn_ids = self.subscribes.keys().copied().collect::<Vec<_>>();
+        for conn_id in conn_ids {
+            self.send_to(toolbox, conn_id, stream_code, seq, data.clone());
+        }
+    }
+
+    /// sequence number of the last message kept in the replay buffer, 0 if replay is disabled
+    pub fn last_seq(&self) -> u32 {
+        self.replay.as_ref().map(|x| x.last_seq()).unwrap_or_default()
+    }
+    /// answers the subscribe request of the connection with `response`, then sends the buffered messages published
+    /// after `resume_from_seq` that match its keys, so the client reads them after the response.
+    /// returns false and sends nothing when there is a gap, then the subscriber needs a fresh snapshot
+    pub fn resume(
+        &mut self,
+        toolbox: &ArcToolbox,
+        connection_id: ConnectionId,
+        resume_from_seq: u32,
+        response: &impl Serialize,
+    ) -> bool {
+        let Some(sub) = self.subscribes.get(&connection_id) else {
+            return false;
+        };
+        let Some(replay) = self.replay.as_ref() else {
+            return false;
+        };
+        let Some(entries) = replay.replay_from(resume_from_seq) else {
+            return false;
+        };
+        let subscribed_keys: Vec<&Key> = self
+            .mappings
+            .iter()
+            .filter(|(_, conn_ids)| conn_ids.contains(&connection_id))
+            .map(|(key, _)| key)
+            .collect();
+        let messages: Vec<(u32, u32, Value)> = entries
+            .filter(|entry| entry.keys.is_empty() || entry.keys.iter().any(|key| subscribed_keys.contains(&key)))
+            .filter_map(|entry| Some((entry.seq, entry.stream_code, entry.data.clone()?)))
+            .collect();
+        toolbox.send_response(&sub.ctx, response);
+        for (seq, stream_code, data) in messages {
+            self.send_to(toolbox, connection_id, stream_code, Some(seq), data);
+        }
+        true
     }
 }
 
@@ -198,7 +316,7 @@ mod tests {
             connection_id: 1,
             ..RequestContext::empty()
         };
-        manager.subscribe(ctx, (), |_| {});
+        manager.subscribe(ctx, (), |_| {}).unwrap();
         assert_eq!(manager.subscribes.len(), 1);
         assert_eq!(manager.mappings.len(), 0);
         let toolbox = Arc::new(Toolbox::new());
@@ -209,4 +327,88 @@ mod tests {
         assert_eq!(manager.subscribes.len(), 0);
         assert_eq!(manager.mappings.len(), 0);
     }
+
+    #[test]
+    fn test_resume() {
+        let mut manager: SubscriptionManager<(), String> = SubscriptionManager::new(0).with_replay(2);
+        let sent = Arc::new(parking_lot::Mutex::new(vec![]));
+        let toolbox = Toolbox::new();
+        let sent_clone = sent.clone();
+        *toolbox.send_msg.write() = Arc::new(move |_conn_id, msg| {
+            match msg {
+                WsResponseGeneric::Stream(msg) => sent_clone.lock().push(msg.stream_seq),
+                // the subscribe response
+                WsResponseGeneric::Immediate(_) => sent_clone.lock().push(0),
+                _ => {}
+            }
+            true
+        });
+        let ctx = RequestContext {
+            connection_id: 1,
+            ..RequestContext::empty()
+        };
+        manager
+            .subscribe_with_keys(ctx, vec!["BTC".to_string()], (), |_| {})
+            .unwrap();
+        manager.publish_to_key(&toolbox, "BTC", &1);
+        manager.publish_to_key(&toolbox, "ETH", &2);
+        manager.publish_to_all(&toolbox, &3);
+        assert_eq!(manager.last_seq(), 3);
+        assert_eq!(*sent.lock(), vec![1, 3]);
+
+        sent.lock().clear();
+        assert!(manager.resume(&toolbox, ctx.connection_id, 1, &()));
+        // the response goes out ahead of the replay
+        assert_eq!(*sent.lock(), vec![0, 3]);
+        sent.lock().clear();
+        // seq 2 has been evicted from the buffer
+        assert!(!manager.resume(&toolbox, ctx.connection_id, 0, &()));
+        // the stream restarted
+        assert!(!manager.resume(&toolbox, ctx.connection_id, 10, &()));
+        assert!(sent.lock().is_empty());
+    }
+
+    #[test]
+    fn test_replay_single_seq_space() {
+        let mut manager: SubscriptionManager<(), String> = SubscriptionManager::new(0).with_replay(8);
+        let sent = Arc::new(parking_lot::Mutex::new(vec![]));
+        let toolbox = Toolbox::new();
+        let sent_clone = sent.clone();
+        *toolbox.send_msg.write() = Arc::new(move |conn_id, msg| {
+            if let WsResponseGeneric::Stream(msg) = msg {
+                sent_clone.lock().push((conn_id, msg.stream_seq, msg.stream_code));
+            }
+            true
+        });
+        let ctx = RequestContext {
+            connection_id: 1,
+            ..RequestContext::empty()
+        };
+        let other = RequestContext {
+            connection_id: 2,
+            ..RequestContext::empty()
+        };
+        manager
+            .subscribe_with_keys(ctx, vec!["BTC".to_string()], (), |_| {})
+            .unwrap();
+        manager.publish_to_key(&toolbox, "BTC", &1);
+        manager
+            .subscribe_with_keys(other, vec!["ETH".to_string()], (), |_| {})
+            .unwrap();
+        // one seq for the whole filtered publish, not one per subscriber
+        manager.publish_with_filter(&toolbox, |_| Some(2));
+        manager.publish_to(&toolbox, ctx.connection_id, &3);
+        manager.publish_removal_to_keys(&toolbox, 7, &["BTC"], vec!["BTC".to_string()]);
+        let mut sent_seqs = sent.lock().clone();
+        sent_seqs.sort();
+        assert_eq!(sent_seqs, vec![(1, 1, 0), (1, 2, 0), (1, 3, 0), (1, 4, 7), (2, 2, 0)]);
+        assert_eq!(manager.last_seq(), 4);
+
+        sent.lock().clear();
+        // the removal is replayed with its own stream code
+        assert!(manager.resume(&toolbox, ctx.connection_id, 3, &()));
+        assert_eq!(*sent.lock(), vec![(1, 4, 7)]);
+        // per subscriber messages can't be replayed
+        assert!(!manager.resume(&toolbox, ctx.connection_id, 1, &()));
+    }
 }
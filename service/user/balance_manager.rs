@@ -1,38 +1,49 @@
+use crate::db::gluesql::schema::common::StrategyId;
 use crate::db::worktable::balance::WorktableBalance;
+use crate::execution::ExecutionAccounts;
 use eyre::Result;
+use futures::FutureExt;
 use kanal::AsyncReceiver;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::warn;
-use trading_exchange::model::{ExecutionResponse, OrderStatus, PositionEffect, UpdateOrder};
+use trading_exchange::model::{AccountId, ExecutionResponse, OrderStatus, PositionEffect, UpdateOrder};
 use trading_model::Exchange;
 
 #[derive(Clone, Debug)]
 pub struct Balance {
     pub exchange: Exchange,
+    pub account: AccountId,
     pub amount_usd: f64,
 }
 
+/// funds of every account, strategies see the fund of the account they trade on
 #[derive(Clone)]
 pub struct BalanceManager {
     balance_table: Arc<RwLock<WorktableBalance>>,
+    accounts: Arc<ExecutionAccounts>,
 }
 impl BalanceManager {
-    pub fn new(balance_table: Arc<RwLock<WorktableBalance>>) -> Self {
-        BalanceManager { balance_table }
+    pub fn new(balance_table: Arc<RwLock<WorktableBalance>>, accounts: Arc<ExecutionAccounts>) -> Self {
+        BalanceManager {
+            balance_table,
+            accounts,
+        }
     }
-    pub async fn update_balance(&self, exchange: Exchange, amount_usd: f64) {
+    pub async fn update_balance(&self, exchange: Exchange, account: AccountId, amount_usd: f64) {
         let mut balance = self.balance_table.write().await;
-        balance.insert(exchange, amount_usd);
+        balance.insert(exchange, account, amount_usd);
     }
+    /// funds of the order are returned to the account it was placed on
     pub async fn add_balance(&self, exchange: Exchange, update: UpdateOrder) -> Result<()> {
+        let account = update.account;
         let mut balance = self.balance_table.write().await;
         match update.status {
             OrderStatus::PartiallyFilled => {
                 // only add when it is a close and it is not one of those "size correcting partial fill" message
                 if update.filled_size / update.size < 0.9 && update.effect == PositionEffect::Close {
                     let amount_usd = update.price * update.filled_size;
-                    if let Err(err) = balance.add_fund(exchange, amount_usd) {
+                    if let Err(err) = balance.add_fund(exchange, account, amount_usd) {
                         return Err(err);
                     } else {
                         tracing::debug!("added {amount_usd:?}");
@@ -43,7 +54,7 @@ impl BalanceManager {
                 // only add when it is a close
                 if update.effect == PositionEffect::Close {
                     let amount_usd = update.price * update.size;
-                    if let Err(err) = balance.add_fund(exchange, amount_usd) {
+                    if let Err(err) = balance.add_fund(exchange, account, amount_usd) {
                         warn!("failed to add balance: {:?}", err);
                     } else {
                         tracing::debug!("added {amount_usd:?}");
@@ -52,7 +63,7 @@ impl BalanceManager {
             }
             OrderStatus::Cancelled | OrderStatus::Rejected => {
                 let amount_usd = update.price * update.size;
-                if let Err(err) = balance.add_fund(exchange, amount_usd) {
+                if let Err(err) = balance.add_fund(exchange, account, amount_usd) {
                     warn!("failed to add balance: {:?}", err);
                 } else {
                     tracing::debug!("added {amount_usd:?}");
@@ -62,19 +73,25 @@ impl BalanceManager {
         };
         Ok(())
     }
-    pub async fn deduct_balance(&self, exchange: Exchange, amount_usd: f64) -> Result<bool> {
+    pub async fn deduct_balance(&self, exchange: Exchange, account: AccountId, amount_usd: f64) -> Result<bool> {
         let mut balance = self.balance_table.write().await;
-        let has_deducted = balance.deduct_fund(exchange, amount_usd).is_ok();
+        let has_deducted = balance.deduct_fund(exchange, account, amount_usd).is_ok();
         Ok(has_deducted)
     }
-    pub async fn get_balance(&self, exchange: Exchange) -> Result<Balance> {
+    pub async fn get_balance(&self, exchange: Exchange, account: AccountId) -> Result<Balance> {
         let mut balance = self.balance_table.write().await;
-        let amount = balance.get_fund(exchange).unwrap_or_default();
+        let amount = balance.get_fund(exchange, account).unwrap_or_default();
         Ok(Balance {
             exchange,
+            account,
             amount_usd: amount,
         })
     }
+    /// fund of the account the strategy trades on at the venue
+    pub async fn get_strategy_balance(&self, strategy_id: StrategyId, exchange: Exchange) -> Result<Balance> {
+        let account = self.accounts.resolve(strategy_id, exchange);
+        self.get_balance(exchange, account).await
+    }
 
     async fn handle_execution_response(&self, response: ExecutionResponse) -> Result<()> {
         match response {
@@ -95,11 +112,17 @@ impl BalanceManager {
                     let Some(set_values) = balance_usd.set_values.clone() else {
                         return Ok(());
                     };
-                    if balance.find_by_exchange(exchange).is_none() {
-                        balance.insert(exchange, set_values.available);
+                    if balance.find_by_account(exchange, updates.account).is_none() {
+                        balance.insert(exchange, updates.account, set_values.available);
                     }
                 }
             }
+            // accounting sends the snapshots along with their book
+            ExecutionResponse::Group(group) => {
+                for response in group {
+                    self.handle_execution_response(response).boxed_local().await?;
+                }
+            }
             _ => {}
         }
         Ok(())
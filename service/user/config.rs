@@ -3,17 +3,461 @@ use std::str::FromStr;
 
 use lib::log::LogLevel;
 use lib::ws::WsServerConfig;
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
+use trading_exchange::model::AccountId;
+use trading_model::{Exchange, Network};
+
+use crate::db::gluesql::schema::common::StrategyId;
 
 #[derive(Debug, Clone, Deserialize, Default)]
 pub struct DatabaseConfig {
     pub directory: PathBuf,
+    /// where dumps are written, `backup` next to the database directory when not set
+    #[serde(default)]
+    pub backup_directory: Option<PathBuf>,
+    /// apply pending migrations to an in-memory copy, report and stop without touching the database
+    #[serde(default)]
+    pub migration_dry_run: bool,
+    /// take a snapshot into the backup directory at this interval, disabled when not set
+    #[serde(default)]
+    pub backup_interval_ms: Option<i64>,
+    /// scheduled snapshots kept in the backup directory, older ones are removed
+    #[serde(default = "default_backup_retention")]
+    pub backup_retention: usize,
+    /// where Parquet/CSV exports are written, `export` next to the database directory when not set
+    #[serde(default)]
+    pub export_directory: Option<PathBuf>,
+}
+fn default_backup_retention() -> usize {
+    7
+}
+impl DatabaseConfig {
+    pub fn backup_directory(&self) -> PathBuf {
+        match &self.backup_directory {
+            Some(directory) => directory.clone(),
+            None => self.directory.with_file_name("backup"),
+        }
+    }
+    pub fn export_directory(&self) -> PathBuf {
+        match &self.export_directory {
+            Some(directory) => directory.clone(),
+            None => self.directory.with_file_name("export"),
+        }
+    }
 }
 #[derive(Debug, Clone, Deserialize)]
 pub struct LogConfig {
     pub level: LogLevel,
     pub file: Option<PathBuf>,
 }
+/// market feed settings of the config file
+#[derive(Debug, Clone, Deserialize)]
+pub struct FeedConfig {
+    /// instruments without an update for longer are stale and blocked from signals
+    #[serde(default = "default_stale_ms")]
+    pub stale_ms: i64,
+    /// a connection without any message for longer is reconnected and resubscribed
+    #[serde(default = "default_reconnect_ms")]
+    pub reconnect_ms: i64,
+    /// connections per symbol chunk, events are deduplicated by exchange time when above 1
+    #[serde(default = "default_redundancy")]
+    pub redundancy: usize,
+}
+fn default_stale_ms() -> i64 {
+    10_000
+}
+fn default_reconnect_ms() -> i64 {
+    30_000
+}
+fn default_redundancy() -> usize {
+    1
+}
+impl Default for FeedConfig {
+    fn default() -> Self {
+        Self {
+            stale_ms: default_stale_ms(),
+            reconnect_ms: default_reconnect_ms(),
+            redundancy: default_redundancy(),
+        }
+    }
+}
+
+/// candle backfill settings of the config file
+#[derive(Debug, Clone, Deserialize)]
+pub struct CandleConfig {
+    /// REST endpoint the Binance futures klines are fetched from
+    #[serde(default = "default_binance_futures_url")]
+    pub binance_futures_url: String,
+    /// network the Hyperliquid candle snapshots are fetched from
+    #[serde(default)]
+    pub hyperliquid_network: Network,
+}
+fn default_binance_futures_url() -> String {
+    "https://fapi.binance.com".to_string()
+}
+impl Default for CandleConfig {
+    fn default() -> Self {
+        Self {
+            binance_futures_url: default_binance_futures_url(),
+            hyperliquid_network: Network::default(),
+        }
+    }
+}
+
+/// funding history settings of the config file
+#[derive(Debug, Clone, Deserialize)]
+pub struct FundingConfig {
+    /// REST endpoint the Binance futures funding rates and intervals are fetched from
+    #[serde(default = "default_binance_futures_url")]
+    pub binance_futures_url: String,
+    /// network the Hyperliquid funding history is fetched from
+    #[serde(default)]
+    pub hyperliquid_network: Network,
+}
+impl Default for FundingConfig {
+    fn default() -> Self {
+        Self {
+            binance_futures_url: default_binance_futures_url(),
+            hyperliquid_network: Network::default(),
+        }
+    }
+}
+
+/// venue contributing to the fair price
+#[derive(Debug, Clone, Deserialize)]
+pub struct FairPriceSourceConfig {
+    pub exchange: Exchange,
+    pub weight: f64,
+}
+/// composite fair price settings of the config file
+#[derive(Debug, Clone, Deserialize)]
+pub struct FairPriceConfig {
+    #[serde(default = "default_fair_price_sources")]
+    pub sources: Vec<FairPriceSourceConfig>,
+    /// venues compared against the fair price
+    #[serde(default = "default_fair_price_tradable")]
+    pub tradable: Vec<Exchange>,
+    /// weight of a source halves every half life since its last update
+    #[serde(default = "default_fair_price_half_life_ms")]
+    pub half_life_ms: i64,
+    /// sources without an update for longer are left out
+    #[serde(default = "default_fair_price_max_age_ms")]
+    pub max_age_ms: i64,
+    /// sources further from the weighted median are rejected as outliers
+    #[serde(default = "default_fair_price_outlier_bp")]
+    pub outlier_bp: f64,
+}
+fn default_fair_price_sources() -> Vec<FairPriceSourceConfig> {
+    vec![
+        FairPriceSourceConfig {
+            exchange: Exchange::BinanceFutures,
+            weight: 2.0,
+        },
+        FairPriceSourceConfig {
+            exchange: Exchange::Hyperliquid,
+            weight: 1.0,
+        },
+        FairPriceSourceConfig {
+            exchange: Exchange::Bybit,
+            weight: 1.0,
+        },
+        FairPriceSourceConfig {
+            exchange: Exchange::Coinbase,
+            weight: 1.0,
+        },
+        FairPriceSourceConfig {
+            exchange: Exchange::Bitget,
+            weight: 0.5,
+        },
+        FairPriceSourceConfig {
+            exchange: Exchange::GateioPerpetual,
+            weight: 0.5,
+        },
+    ]
+}
+fn default_fair_price_tradable() -> Vec<Exchange> {
+    vec![Exchange::Hyperliquid]
+}
+fn default_fair_price_half_life_ms() -> i64 {
+    2_000
+}
+fn default_fair_price_max_age_ms() -> i64 {
+    10_000
+}
+fn default_fair_price_outlier_bp() -> f64 {
+    50.0
+}
+impl Default for FairPriceConfig {
+    fn default() -> Self {
+        Self {
+            sources: default_fair_price_sources(),
+            tradable: default_fair_price_tradable(),
+            half_life_ms: default_fair_price_half_life_ms(),
+            max_age_ms: default_fair_price_max_age_ms(),
+            outlier_bp: default_fair_price_outlier_bp(),
+        }
+    }
+}
+
+/// export of the trading data to Postgres/TimescaleDB
+#[derive(Debug, Clone, Deserialize)]
+pub struct PostgresExportConfig {
+    /// connection string, e.g. `host=localhost user=postgres dbname=trading`. the export is disabled when not set
+    #[serde(default)]
+    pub url: Option<String>,
+    /// create the tables as TimescaleDB hypertables partitioned by `datetime`
+    #[serde(default)]
+    pub timescale: bool,
+    /// rows per upsert
+    #[serde(default = "default_export_batch_size")]
+    pub batch_size: usize,
+    #[serde(default = "default_export_interval_ms")]
+    pub interval_ms: i64,
+    /// orders and ledgers are updated in place, the ones younger than this are exported again when changed.
+    /// updates of older rows are not exported
+    #[serde(default = "default_export_mutable_window_ms")]
+    pub mutable_window_ms: i64,
+    /// batches not yet accepted by Postgres, `export_queue` next to the database directory when not set
+    #[serde(default)]
+    pub queue_directory: Option<PathBuf>,
+}
+fn default_export_batch_size() -> usize {
+    1_000
+}
+fn default_export_interval_ms() -> i64 {
+    1_000
+}
+fn default_export_mutable_window_ms() -> i64 {
+    86_400_000
+}
+impl PostgresExportConfig {
+    pub fn queue_directory(&self, database: &DatabaseConfig) -> PathBuf {
+        match &self.queue_directory {
+            Some(directory) => directory.clone(),
+            None => database.directory.with_file_name("export_queue"),
+        }
+    }
+}
+impl Default for PostgresExportConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            timescale: false,
+            batch_size: default_export_batch_size(),
+            interval_ms: default_export_interval_ms(),
+            mutable_window_ms: default_export_mutable_window_ms(),
+            queue_directory: None,
+        }
+    }
+}
+
+/// write-ahead journal of the order requests and updates, replayed on startup
+#[derive(Debug, Clone, Deserialize)]
+pub struct JournalConfig {
+    #[serde(default = "default_journal_enabled")]
+    pub enabled: bool,
+    /// `order_journal.jsonl` next to the database directory when not set
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+    /// orders on a venue are rejected until its open orders are resolved. once this elapses the accounts
+    /// of the unresolved orders are halted until acknowledged
+    #[serde(default = "default_journal_recovery_timeout_ms")]
+    pub recovery_timeout_ms: i64,
+    /// cancel the recovered orders that are still open, instead of adopting them
+    #[serde(default)]
+    pub cancel_recovered: bool,
+}
+fn default_journal_enabled() -> bool {
+    true
+}
+fn default_journal_recovery_timeout_ms() -> i64 {
+    60_000
+}
+impl JournalConfig {
+    pub fn path(&self, database: &DatabaseConfig) -> PathBuf {
+        match &self.path {
+            Some(path) => path.clone(),
+            None => database.directory.with_file_name("order_journal.jsonl"),
+        }
+    }
+}
+impl Default for JournalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_journal_enabled(),
+            path: None,
+            recovery_timeout_ms: default_journal_recovery_timeout_ms(),
+            cancel_recovered: false,
+        }
+    }
+}
+
+/// how a closing fill is matched against the open lots of a position
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LotMethod {
+    /// the oldest lots are closed first, each at its own open price
+    #[default]
+    Fifo,
+    /// every open lot is closed pro rata at the average open price of the position
+    AverageCost,
+}
+
+/// fee rates of a venue, charged on the notional of the fills that come without a fee
+#[derive(Debug, Clone, Deserialize)]
+pub struct FeeRateConfig {
+    pub exchange: Exchange,
+    pub maker: f64,
+    pub taker: f64,
+}
+
+/// fee rates of every venue, one list shared by the executable prices and the ledger
+#[derive(Debug, Clone, Deserialize)]
+#[serde(transparent)]
+pub struct FeeSchedule(pub Vec<FeeRateConfig>);
+impl FeeSchedule {
+    /// 0 for venues that are not listed
+    pub fn fee_rate(&self, exchange: Exchange, taker: bool) -> f64 {
+        self.0
+            .iter()
+            .find(|x| x.exchange == exchange)
+            .map_or(0.0, |x| if taker { x.taker } else { x.maker })
+    }
+}
+impl Default for FeeSchedule {
+    fn default() -> Self {
+        Self(vec![
+            FeeRateConfig {
+                exchange: Exchange::BinanceFutures,
+                maker: 0.0002,
+                taker: 0.0005,
+            },
+            FeeRateConfig {
+                exchange: Exchange::Bybit,
+                maker: 0.0002,
+                taker: 0.00055,
+            },
+            FeeRateConfig {
+                exchange: Exchange::Bitget,
+                maker: 0.0002,
+                taker: 0.0006,
+            },
+            FeeRateConfig {
+                exchange: Exchange::GateioPerpetual,
+                maker: 0.0002,
+                taker: 0.0005,
+            },
+            FeeRateConfig {
+                exchange: Exchange::Hyperliquid,
+                maker: 0.0001,
+                taker: 0.00035,
+            },
+        ])
+    }
+}
+
+/// position ledger settings of the config file
+#[derive(Debug, Clone, Deserialize)]
+pub struct LedgerConfig {
+    #[serde(default)]
+    pub lot_method: LotMethod,
+    /// venues whose fills are taken from their trade stream, the fills of the others are derived
+    /// from the filled size of their order updates
+    #[serde(default = "default_ledger_trade_fills")]
+    pub trade_fills: Vec<Exchange>,
+    /// the unrealized PnL of the open lots is marked to the last prices at this interval
+    #[serde(
+        default = "default_ledger_mark_interval_ms",
+        deserialize_with = "deserialize_positive_ms"
+    )]
+    pub mark_interval_ms: i64,
+}
+fn default_ledger_trade_fills() -> Vec<Exchange> {
+    vec![Exchange::Hyperliquid]
+}
+fn default_ledger_mark_interval_ms() -> i64 {
+    5_000
+}
+/// rejects the intervals a timer can not tick at
+fn deserialize_positive_ms<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+    let ms = i64::deserialize(deserializer)?;
+    if ms <= 0 {
+        return Err(serde::de::Error::custom(format!("interval of {ms}ms must be positive")));
+    }
+    Ok(ms)
+}
+impl Default for LedgerConfig {
+    fn default() -> Self {
+        Self {
+            lot_method: LotMethod::default(),
+            trade_fills: default_ledger_trade_fills(),
+            mark_interval_ms: default_ledger_mark_interval_ms(),
+        }
+    }
+}
+
+/// the account a strategy trades on at a venue, by the id of the stored key.
+/// strategies without one trade on the first account connected on the venue
+#[derive(Debug, Clone, Deserialize)]
+pub struct StrategyAccountConfig {
+    pub strategy_id: StrategyId,
+    pub exchange: Exchange,
+    pub account: AccountId,
+}
+
+/// rows older than `after_ms` are averaged into buckets of `bucket_ms` in the `into` table, then removed
+#[derive(Debug, Clone, Deserialize)]
+pub struct DownsampleConfig {
+    pub after_ms: i64,
+    pub bucket_ms: i64,
+    /// created with the columns of the source table when missing, give it its own policy to keep it bounded
+    pub into: String,
+    /// columns a bucket is split by, e.g. `["asset", "exchange_1", "exchange_2"]`
+    #[serde(default)]
+    pub group_by: Vec<String>,
+}
+
+/// retention of a single table, rows are aged by their `time_column` in milliseconds
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RetentionPolicyConfig {
+    pub table: String,
+    /// SQL condition restricting the policy to some rows, e.g. `interval_ms = 60000`.
+    /// a table has one policy per filter
+    #[serde(default)]
+    pub filter: Option<String>,
+    /// `datetime` when missing
+    #[serde(default)]
+    pub time_column: Option<String>,
+    #[serde(default)]
+    pub max_age_ms: Option<i64>,
+    /// the oldest rows above this count are removed
+    #[serde(default)]
+    pub max_rows: Option<usize>,
+    #[serde(default)]
+    pub downsample: Option<DownsampleConfig>,
+}
+
+/// retention of the volatile and persistent tables, enforced by a single scheduler
+#[derive(Debug, Clone, Deserialize)]
+pub struct RetentionConfig {
+    #[serde(default = "default_retention_interval_ms")]
+    pub interval_ms: i64,
+    /// replaces the built-in policy of the same table and filter, see `db::retention::default_policies`
+    #[serde(default)]
+    pub tables: Vec<RetentionPolicyConfig>,
+}
+fn default_retention_interval_ms() -> i64 {
+    10_000
+}
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            interval_ms: default_retention_interval_ms(),
+            tables: vec![],
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
     pub database: DatabaseConfig,
@@ -21,6 +465,31 @@ pub struct Config {
     pub log: LogConfig,
     #[serde(default)]
     pub skip_key: bool,
+    /// address of the prometheus `/metrics` listener, disabled when not set
+    #[serde(default)]
+    pub metrics_address: Option<String>,
+    #[serde(default)]
+    pub feed: FeedConfig,
+    #[serde(default)]
+    pub fair_price: FairPriceConfig,
+    #[serde(default)]
+    pub candles: CandleConfig,
+    #[serde(default)]
+    pub funding: FundingConfig,
+    /// taker fees netted out of the executable prices of the signals, and charged by the ledger
+    /// on the fills reported without a fee
+    #[serde(default)]
+    pub fees: FeeSchedule,
+    #[serde(default)]
+    pub postgres_export: PostgresExportConfig,
+    #[serde(default)]
+    pub retention: RetentionConfig,
+    #[serde(default)]
+    pub journal: JournalConfig,
+    #[serde(default)]
+    pub ledger: LedgerConfig,
+    #[serde(default)]
+    pub strategy_accounts: Vec<StrategyAccountConfig>,
 }
 
 impl FromStr for Config {
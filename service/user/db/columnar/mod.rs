@@ -0,0 +1,436 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use eyre::{bail, Context, Result};
+use gluesql::core::store::{GStore, GStoreMut, Store};
+use gluesql::prelude::{Glue, SharedMemoryStorage, Value};
+use gluesql_shared_sled_storage::SharedSledStorage;
+use lib::utils::get_time_milliseconds;
+use parking_lot::RwLock;
+use strum_macros::{Display, EnumString};
+use tracing::{info, warn};
+use trading_model::{Exchange, TimeStampMs};
+
+use crate::db::columnar::writer::{write_csv, write_parquet, ColumnSchema};
+use crate::db::gluesql::backup::validate_backup_label;
+use crate::db::gluesql::schema::common::TableName;
+use crate::db::postgres::exporter::select_all_sql;
+
+/// typed Parquet and CSV files
+pub mod writer;
+
+const DAY_MS: TimeStampMs = 86_400_000;
+
+/// UTC days touched by the range, one date partition each
+fn day_count(request: &ExportRequest) -> usize {
+    let first = request.time_start.div_euclid(DAY_MS) * DAY_MS;
+    ((request.time_end - first + DAY_MS - 1) / DAY_MS) as usize
+}
+
+/// group of tables exported together
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+pub enum ExportDataset {
+    #[strum(serialize = "candles")]
+    Candles,
+    #[strum(serialize = "liquidations")]
+    Liquidations,
+    /// funding history and accruals
+    #[strum(serialize = "funding")]
+    Funding,
+    /// best bid/ask and volume, volatile
+    #[strum(serialize = "prices")]
+    Prices,
+    /// all signal tables, volatile
+    #[strum(serialize = "signals")]
+    Signals,
+    /// events of every strategy, volatile
+    #[strum(serialize = "events")]
+    Events,
+    #[strum(serialize = "orders")]
+    Orders,
+    #[strum(serialize = "ledger")]
+    Ledger,
+}
+impl ExportDataset {
+    /// tables of the dataset and whether they are persistent
+    fn tables(&self, table_name: &TableName) -> Vec<(String, bool)> {
+        let sorted = |map: &HashMap<_, String>| {
+            let mut tables: Vec<String> = map.values().cloned().collect();
+            tables.sort();
+            tables
+        };
+        match self {
+            Self::Candles => vec![(table_name.candle.clone(), true)],
+            Self::Liquidations => vec![(table_name.liquidation.clone(), true)],
+            Self::Funding => vec![
+                (table_name.funding_history.clone(), true),
+                (table_name.funding_accrual.clone(), true),
+            ],
+            Self::Prices => vec![(table_name.price_volume.clone(), false)],
+            Self::Signals => {
+                let mut tables = sorted(&table_name.signal_difference);
+                tables.push(table_name.signal_change.clone());
+                tables.push(table_name.signal_diff_generic.clone());
+                tables.push(table_name.signal_change_immediate.clone());
+                tables.push(table_name.signal_trade_flow.clone());
+                tables.push(table_name.signal_fair_price_diff.clone());
+                tables.into_iter().map(|x| (x, false)).collect()
+            }
+            Self::Events => sorted(&table_name.event_price_change_and_diff)
+                .into_iter()
+                .map(|x| (x, false))
+                .collect(),
+            Self::Orders => vec![(table_name.order.clone(), true)],
+            Self::Ledger => vec![
+                (table_name.ledger.clone(), true),
+                (table_name.position_pnl.clone(), true),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+pub enum ExportFormat {
+    #[strum(serialize = "parquet")]
+    Parquet,
+    #[strum(serialize = "csv")]
+    Csv,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportRequest {
+    pub dataset: ExportDataset,
+    pub format: ExportFormat,
+    /// inclusive, ms
+    pub time_start: TimeStampMs,
+    /// exclusive, ms
+    pub time_end: TimeStampMs,
+    /// only the rows of this exchange, for the tables that record one
+    pub exchange: Option<Exchange>,
+    /// root of the partitioned files
+    pub directory: PathBuf,
+}
+
+/// where the datasets are read from
+#[derive(Clone)]
+pub struct ExportStorage {
+    pub persistent: SharedSledStorage,
+    /// the volatile tables only exist inside the running service
+    pub volatile: Option<SharedMemoryStorage>,
+    pub table_name: TableName,
+    /// asset and symbol names by intern hash, for the partition paths. unknown hashes are written as numbers
+    pub names: HashMap<u64, String>,
+}
+
+/// progress of a running export
+#[derive(Debug, Clone, Default)]
+pub struct ExportProgress {
+    /// table days done out of `steps_total`
+    pub steps_done: usize,
+    pub steps_total: usize,
+    pub rows: u64,
+    pub files: u64,
+}
+
+/// partition directory of a row, `exchange=<name>/asset=<name>`
+fn partition_of(row: &[Value], exchange: Option<usize>, asset: Option<usize>, names: &HashMap<u64, String>) -> String {
+    let exchange = match exchange.map(|x| &row[x]) {
+        Some(Value::U8(id)) => match Exchange::from_repr(*id) {
+            Some(exchange) => exchange.to_string(),
+            None => id.to_string(),
+        },
+        _ => "unknown".to_string(),
+    };
+    let asset = match asset.map(|x| &row[x]) {
+        Some(Value::U64(id)) => match names.get(id) {
+            // symbols like BTC/USDT must not add a directory level
+            Some(name) => name.replace(['/', '\\', '='], "_"),
+            None => id.to_string(),
+        },
+        _ => "unknown".to_string(),
+    };
+    format!("exchange={exchange}/asset={asset}")
+}
+
+/// exports one table day by day into `<directory>/<table>/date=<day>/exchange=<name>/asset=<name>/`
+async fn export_table<G: GStore + GStoreMut>(
+    glue: &mut Glue<G>,
+    table: &str,
+    request: &ExportRequest,
+    names: &HashMap<u64, String>,
+    progress: &mut ExportProgress,
+    report: &mut dyn FnMut(&ExportProgress),
+) -> Result<()> {
+    let Some(schema) = glue.storage.fetch_schema(table).await? else {
+        // tables of strategies that never ran
+        progress.steps_done += day_count(request);
+        report(progress);
+        return Ok(());
+    };
+    let Some(column_defs) = schema.column_defs else {
+        bail!("table {table} has no columns");
+    };
+    let columns: Vec<ColumnSchema> = column_defs.iter().map(ColumnSchema::from).collect();
+    let position = |names: &[&str]| columns.iter().position(|x| names.contains(&x.name.as_str()));
+    let Some(time) = position(&["datetime", "created_at"]) else {
+        bail!("table {table} has no time column");
+    };
+    let time = columns[time].name.clone();
+    let exchange = position(&["exchange_id", "exchange"]);
+    let asset = position(&["asset_id", "symbol_id"]);
+
+    let directory = request.directory.join(table);
+    std::fs::create_dir_all(&directory).with_context(|| format!("failed to create {}", directory.display()))?;
+    std::fs::write(directory.join("schema.json"), serde_json::to_vec_pretty(&columns)?)?;
+
+    let mut day = request.time_start.div_euclid(DAY_MS) * DAY_MS;
+    while day < request.time_end {
+        let start = day.max(request.time_start);
+        let end = (day + DAY_MS).min(request.time_end);
+        let mut sql = format!("SELECT * FROM {table} WHERE {time} >= {start} AND {time} < {end}");
+        if let (Some(index), Some(exchange)) = (exchange, request.exchange) {
+            sql += &format!(" AND {} = {}", columns[index].name, exchange as u8);
+        }
+        sql += &format!(" ORDER BY {time}");
+        let (_, rows) = select_all_sql(glue, &sql).await?;
+        let mut partitions: BTreeMap<String, Vec<Vec<Value>>> = BTreeMap::new();
+        for row in rows {
+            partitions
+                .entry(partition_of(&row, exchange, asset, names))
+                .or_default()
+                .push(row);
+        }
+        let date = chrono::DateTime::from_timestamp_millis(day)
+            .map(|x| x.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| day.to_string());
+        for (partition, rows) in partitions {
+            let directory = directory.join(format!("date={date}")).join(partition);
+            std::fs::create_dir_all(&directory).with_context(|| format!("failed to create {}", directory.display()))?;
+            match request.format {
+                ExportFormat::Parquet => write_parquet(&directory.join("part-0.parquet"), &columns, &rows)?,
+                ExportFormat::Csv => write_csv(&directory.join("part-0.csv"), &columns, &rows)?,
+            }
+            progress.rows += rows.len() as u64;
+            progress.files += 1;
+        }
+        progress.steps_done += 1;
+        report(progress);
+        day += DAY_MS;
+    }
+    Ok(())
+}
+
+/// writes the time range of a dataset as partitioned files, `report` is called after every table day
+pub async fn run_export(
+    storage: &ExportStorage,
+    request: &ExportRequest,
+    report: &mut dyn FnMut(&ExportProgress),
+) -> Result<ExportProgress> {
+    if request.time_start >= request.time_end {
+        bail!("time_start must be before time_end");
+    }
+    let tables = request.dataset.tables(&storage.table_name);
+    let mut progress = ExportProgress {
+        steps_total: tables.len() * day_count(request),
+        ..Default::default()
+    };
+    report(&progress);
+    for (table, persistent) in tables {
+        if persistent {
+            let mut glue = Glue::new(storage.persistent.clone());
+            export_table(&mut glue, &table, request, &storage.names, &mut progress, report).await?;
+        } else {
+            let Some(volatile) = &storage.volatile else {
+                bail!("{} is only recorded in memory by the running service", request.dataset);
+            };
+            let mut glue = Glue::new(volatile.clone());
+            export_table(&mut glue, &table, request, &storage.names, &mut progress, report).await?;
+        }
+    }
+    info!(
+        "exported {} rows of {} into {} files under {}",
+        progress.rows,
+        request.dataset,
+        progress.files,
+        request.directory.display()
+    );
+    Ok(progress)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum ExportJobStatus {
+    #[strum(serialize = "running")]
+    Running,
+    #[strum(serialize = "finished")]
+    Finished,
+    #[strum(serialize = "failed")]
+    Failed,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportJob {
+    pub id: u64,
+    pub request: ExportRequest,
+    pub status: ExportJobStatus,
+    pub progress: ExportProgress,
+    pub error: Option<String>,
+    pub started_at: TimeStampMs,
+    pub updated_at: TimeStampMs,
+}
+
+/// export jobs started through the endpoint, kept until the service restarts
+pub struct ExportJobMap {
+    storage: ExportStorage,
+    directory: PathBuf,
+    next_id: AtomicU64,
+    jobs: RwLock<BTreeMap<u64, ExportJob>>,
+}
+impl ExportJobMap {
+    pub fn new(storage: ExportStorage, directory: PathBuf) -> Self {
+        Self {
+            storage,
+            directory,
+            next_id: AtomicU64::new(1),
+            jobs: RwLock::new(BTreeMap::new()),
+        }
+    }
+    pub fn jobs(&self) -> Vec<ExportJob> {
+        self.jobs.read().values().cloned().collect()
+    }
+    fn update(&self, id: u64, update: impl FnOnce(&mut ExportJob)) {
+        if let Some(job) = self.jobs.write().get_mut(&id) {
+            update(job);
+            job.updated_at = get_time_milliseconds();
+        }
+    }
+    /// starts an export into `<export directory>/<label>-<dataset>-<time>` on a blocking thread,
+    /// the table scans and the file writes would otherwise stall the local task set
+    pub fn start(
+        self: &Arc<Self>,
+        label: &str,
+        dataset: ExportDataset,
+        format: ExportFormat,
+        time_start: TimeStampMs,
+        time_end: TimeStampMs,
+        exchange: Option<Exchange>,
+    ) -> Result<ExportJob> {
+        validate_backup_label(label)?;
+        if time_start >= time_end {
+            bail!("time_start must be before time_end");
+        }
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let directory = job_directory(&self.directory, label, dataset);
+        let now = get_time_milliseconds();
+        let job = ExportJob {
+            id,
+            request: ExportRequest {
+                dataset,
+                format,
+                time_start,
+                time_end,
+                exchange,
+                directory,
+            },
+            status: ExportJobStatus::Running,
+            progress: ExportProgress::default(),
+            error: None,
+            started_at: now,
+            updated_at: now,
+        };
+        self.jobs.write().insert(id, job.clone());
+        let this = self.clone();
+        let request = job.request.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut report = |progress: &ExportProgress| this.update(id, |job| job.progress = progress.clone());
+            let result = futures::executor::block_on(run_export(&this.storage, &request, &mut report));
+            this.update(id, |job| match result {
+                Ok(progress) => {
+                    job.progress = progress;
+                    job.status = ExportJobStatus::Finished;
+                }
+                Err(err) => {
+                    warn!("export job {id} failed: {err:?}");
+                    job.status = ExportJobStatus::Failed;
+                    job.error = Some(err.to_string());
+                }
+            });
+        });
+        Ok(job)
+    }
+}
+
+/// output directory of one export, `<label>-<dataset>-<time>` under the export directory
+pub fn job_directory(root: &Path, label: &str, dataset: ExportDataset) -> PathBuf {
+    root.join(format!(
+        "{label}-{dataset}-{}",
+        chrono::Utc::now().format("%Y%m%dT%H%M%S%.3f")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::gluesql::schema::candle::DbRowCandle;
+    use crate::main_core::STRATEGY_IDS;
+    use gluesql_shared_sled_storage::Config as SledConfig;
+    use lib::gluesql::{Table, TableCreate};
+
+    #[tokio::test]
+    async fn test_export_candles() -> Result<()> {
+        let database = tempfile::tempdir()?;
+        let output = tempfile::tempdir()?;
+        let persistent = SharedSledStorage::new(SledConfig::default().path(database.path()), true)?;
+        let table_name = TableName::new(&STRATEGY_IDS);
+        let mut table: Table<SharedSledStorage, DbRowCandle> = Table::new(&table_name.candle, persistent.clone());
+        table.create_table().await?;
+        let symbol = trading_model::Symbol::from("BTC");
+        for (i, datetime) in [DAY_MS - 60_000, DAY_MS, DAY_MS + 60_000].into_iter().enumerate() {
+            table
+                .insert(DbRowCandle {
+                    exchange_id: Exchange::BinanceFutures as u8,
+                    symbol_id: symbol._hash(),
+                    interval_ms: 60_000,
+                    datetime,
+                    open: i as f64,
+                    high: i as f64,
+                    low: i as f64,
+                    close: i as f64,
+                    volume: 1.0,
+                })
+                .await?;
+        }
+        let storage = ExportStorage {
+            persistent,
+            volatile: None,
+            table_name: table_name.clone(),
+            names: HashMap::from([(symbol._hash(), "BTC".to_string())]),
+        };
+        let request = ExportRequest {
+            dataset: ExportDataset::Candles,
+            format: ExportFormat::Csv,
+            time_start: 0,
+            time_end: 2 * DAY_MS,
+            exchange: None,
+            directory: output.path().to_path_buf(),
+        };
+        let progress = run_export(&storage, &request, &mut |_| {}).await?;
+        assert_eq!(progress.rows, 3);
+        assert_eq!(progress.files, 2);
+        assert_eq!(progress.steps_done, progress.steps_total);
+        let partition = output
+            .path()
+            .join(&table_name.candle)
+            .join("date=1970-01-02/exchange=BinanceFutures/asset=BTC/part-0.csv");
+        assert_eq!(std::fs::read_to_string(partition)?.lines().count(), 3);
+
+        // volatile datasets need the running service
+        let request = ExportRequest {
+            dataset: ExportDataset::Prices,
+            ..request
+        };
+        assert!(run_export(&storage, &request, &mut |_| {}).await.is_err());
+        Ok(())
+    }
+}
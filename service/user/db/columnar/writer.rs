@@ -0,0 +1,216 @@
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+use eyre::{bail, Context, Result};
+use gluesql::core::ast::{ColumnDef, DataType};
+use gluesql::prelude::Value;
+use parquet::basic::Compression;
+use parquet::data_type::{BoolType, ByteArray, ByteArrayType, DoubleType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use serde::Serialize;
+
+/// physical type of an exported column
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum ColumnKind {
+    Bool,
+    Int64,
+    /// u64 ids and hashes, stored as INT64 annotated unsigned
+    UInt64,
+    Double,
+    String,
+}
+impl ColumnKind {
+    fn from_data_type(data_type: &DataType) -> Self {
+        match data_type {
+            DataType::Boolean => Self::Bool,
+            DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int
+            | DataType::Uint8
+            | DataType::Uint16
+            | DataType::Uint32 => Self::Int64,
+            DataType::Uint64 => Self::UInt64,
+            DataType::Float32 | DataType::Float => Self::Double,
+            _ => Self::String,
+        }
+    }
+    fn parquet_type(&self) -> &'static str {
+        match self {
+            Self::Bool => "BOOLEAN",
+            Self::Int64 => "INT64 (INTEGER(64,true))",
+            Self::UInt64 => "INT64 (INTEGER(64,false))",
+            Self::Double => "DOUBLE",
+            Self::String => "BYTE_ARRAY (STRING)",
+        }
+    }
+}
+
+/// explicit schema of an exported column, written next to the CSV files and embedded in the Parquet files
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnSchema {
+    pub name: String,
+    pub kind: ColumnKind,
+    pub nullable: bool,
+}
+impl From<&ColumnDef> for ColumnSchema {
+    fn from(column: &ColumnDef) -> Self {
+        Self {
+            name: column.name.clone(),
+            kind: ColumnKind::from_data_type(&column.data_type),
+            nullable: column.nullable,
+        }
+    }
+}
+
+fn as_i64(value: &Value) -> Option<i64> {
+    Some(match value {
+        Value::I8(x) => *x as _,
+        Value::I16(x) => *x as _,
+        Value::I32(x) => *x as _,
+        Value::I64(x) => *x,
+        Value::U8(x) => *x as _,
+        Value::U16(x) => *x as _,
+        Value::U32(x) => *x as _,
+        // reinterpreted, the column is annotated unsigned
+        Value::U64(x) => *x as _,
+        _ => return None,
+    })
+}
+fn as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::F32(x) => Some(*x as _),
+        Value::F64(x) => Some(*x),
+        value => as_i64(value).map(|x| x as _),
+    }
+}
+/// text of a value as written to CSV, empty for NULL
+pub fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Str(x) => x.clone(),
+        Value::Bytea(x) => hex::encode(x),
+        Value::Bool(x) => x.to_string(),
+        Value::F32(x) => x.to_string(),
+        Value::F64(x) => x.to_string(),
+        Value::Decimal(x) => x.to_string(),
+        Value::I128(x) => x.to_string(),
+        Value::U64(x) => x.to_string(),
+        Value::U128(x) => x.to_string(),
+        value => match as_i64(value) {
+            Some(x) => x.to_string(),
+            None => format!("{value:?}"),
+        },
+    }
+}
+
+/// one Parquet file with a single row group, snappy compressed
+pub fn write_parquet(path: &Path, schema: &[ColumnSchema], rows: &[Vec<Value>]) -> Result<()> {
+    let mut message = "message row {\n".to_string();
+    for column in schema {
+        let repetition = if column.nullable { "OPTIONAL" } else { "REQUIRED" };
+        message += &format!("  {} {} {};\n", repetition, column.kind.parquet_type(), column.name);
+    }
+    message += "}";
+    let parquet_schema = Arc::new(parse_message_type(&message)?);
+    let properties = Arc::new(WriterProperties::builder().set_compression(Compression::SNAPPY).build());
+    let file = File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    let mut writer = SerializedFileWriter::new(file, parquet_schema, properties)?;
+    let mut row_group = writer.next_row_group()?;
+    for (index, column) in schema.iter().enumerate() {
+        let Some(mut column_writer) = row_group.next_column()? else {
+            bail!("parquet schema has no column {}", column.name);
+        };
+        let values = rows.iter().map(|row| &row[index]);
+        // definition level 1 when present, only the present values are written
+        let levels: Vec<i16> = values.clone().map(|x| !matches!(x, Value::Null) as i16).collect();
+        let levels = column.nullable.then_some(levels.as_slice());
+        if !column.nullable && values.clone().any(|x| matches!(x, Value::Null)) {
+            bail!("column {} is not nullable", column.name);
+        }
+        let present = values.filter(|x| !matches!(x, Value::Null));
+        match column.kind {
+            ColumnKind::Bool => {
+                let data: Vec<bool> = present.map(|x| matches!(x, Value::Bool(true))).collect();
+                column_writer.typed::<BoolType>().write_batch(&data, levels, None)?;
+            }
+            ColumnKind::Int64 | ColumnKind::UInt64 => {
+                let data: Vec<i64> = present.map(|x| as_i64(x).unwrap_or_default()).collect();
+                column_writer.typed::<Int64Type>().write_batch(&data, levels, None)?;
+            }
+            ColumnKind::Double => {
+                let data: Vec<f64> = present.map(|x| as_f64(x).unwrap_or(f64::NAN)).collect();
+                column_writer.typed::<DoubleType>().write_batch(&data, levels, None)?;
+            }
+            ColumnKind::String => {
+                let data: Vec<ByteArray> = present.map(|x| ByteArray::from(value_to_string(x).as_str())).collect();
+                column_writer
+                    .typed::<ByteArrayType>()
+                    .write_batch(&data, levels, None)?;
+            }
+        }
+        column_writer.close()?;
+    }
+    row_group.close()?;
+    writer.close()?;
+    Ok(())
+}
+
+/// one CSV file with a header, NULL is an empty field
+pub fn write_csv(path: &Path, schema: &[ColumnSchema], rows: &[Vec<Value>]) -> Result<()> {
+    let mut writer = csv::Writer::from_path(path).with_context(|| format!("failed to create {}", path.display()))?;
+    writer.write_record(schema.iter().map(|x| x.name.as_str()))?;
+    for row in rows {
+        writer.write_record(row.iter().map(value_to_string))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::reader::FileReader;
+    use parquet::file::serialized_reader::SerializedFileReader;
+
+    #[test]
+    fn test_write_csv_parquet() -> Result<()> {
+        let directory = tempfile::tempdir()?;
+        let schema = vec![
+            ColumnSchema {
+                name: "id".to_string(),
+                kind: ColumnKind::UInt64,
+                nullable: false,
+            },
+            ColumnSchema {
+                name: "price".to_string(),
+                kind: ColumnKind::Double,
+                nullable: false,
+            },
+            ColumnSchema {
+                name: "note".to_string(),
+                kind: ColumnKind::String,
+                nullable: true,
+            },
+        ];
+        let rows = vec![
+            vec![Value::U64(u64::MAX), Value::F64(1.5), Value::Str("a,b".to_string())],
+            vec![Value::U64(2), Value::F64(2.0), Value::Null],
+        ];
+        let csv_path = directory.path().join("part.csv");
+        write_csv(&csv_path, &schema, &rows)?;
+        assert_eq!(
+            std::fs::read_to_string(&csv_path)?,
+            "id,price,note\n18446744073709551615,1.5,\"a,b\"\n2,2,\n"
+        );
+
+        let parquet_path = directory.path().join("part.parquet");
+        write_parquet(&parquet_path, &schema, &rows)?;
+        let reader = SerializedFileReader::new(File::open(&parquet_path)?)?;
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,443 @@
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use eyre::{bail, ensure, Context, Result};
+use gluesql::core::data::Schema;
+use gluesql::core::store::{GStore, GStoreMut, Store};
+use gluesql::prelude::{Glue, Payload, Value};
+use gluesql_shared_sled_storage::{Config as SledConfig, SharedSledStorage};
+use lib::gluesql::Table;
+use lib::signal::get_terminate_flag;
+use tracing::{info, warn};
+use trading_exchange::utils::future::interval;
+use trading_model::DurationMs;
+
+use crate::config::DatabaseConfig;
+use crate::db::gluesql::migration::{migrations, pending_migrations, Migration};
+use crate::db::gluesql::schema::common::TableName;
+use crate::db::gluesql::schema::settings::{DbRowApplicationSetting, TableVersioning, APP_SETTINGS};
+use crate::APP_VERSION;
+
+/// rows per INSERT statement of a dump
+const DUMP_INSERT_BATCH: usize = 500;
+
+/// renders a value as an SQL literal that GlueSQL casts back to the column type on insert.
+/// NaN and infinities have no literal, they are cast back from their text
+pub fn sql_literal(value: &Value) -> Result<String> {
+    Ok(match value {
+        Value::Null => "NULL".to_string(),
+        Value::Bool(x) => x.to_string().to_uppercase(),
+        Value::I8(x) => x.to_string(),
+        Value::I16(x) => x.to_string(),
+        Value::I32(x) => x.to_string(),
+        Value::I64(x) => x.to_string(),
+        Value::I128(x) => x.to_string(),
+        Value::U8(x) => x.to_string(),
+        Value::U16(x) => x.to_string(),
+        Value::U32(x) => x.to_string(),
+        Value::U64(x) => x.to_string(),
+        Value::U128(x) => x.to_string(),
+        Value::F32(x) if x.is_finite() => format!("{x:?}"),
+        Value::F64(x) if x.is_finite() => format!("{x:?}"),
+        Value::F32(x) => format!("CAST('{x}' AS FLOAT32)"),
+        Value::F64(x) => format!("CAST('{x}' AS FLOAT)"),
+        Value::Decimal(x) => x.to_string(),
+        Value::Str(x) => format!("'{}'", x.replace('\'', "''")),
+        Value::Bytea(x) => {
+            let mut hex = String::with_capacity(x.len() * 2 + 3);
+            hex.push_str("X'");
+            for byte in x {
+                write!(hex, "{byte:02X}")?;
+            }
+            hex.push('\'');
+            hex
+        }
+        value => bail!("value {value:?} can not be rendered as a literal"),
+    })
+}
+
+/// INSERT statements of the rows, batched
+pub fn insert_statements(table_name: &str, labels: &[String], rows: &[Vec<Value>]) -> Result<Vec<String>> {
+    let mut statements = vec![];
+    for chunk in rows.chunks(DUMP_INSERT_BATCH) {
+        let mut sql = format!("INSERT INTO {} ({}) VALUES ", table_name, labels.join(", "));
+        for (i, row) in chunk.iter().enumerate() {
+            if i > 0 {
+                sql.push_str(", ");
+            }
+            let values = row.iter().map(sql_literal).collect::<Result<Vec<_>>>()?;
+            write!(sql, "({})", values.join(", "))?;
+        }
+        statements.push(sql);
+    }
+    Ok(statements)
+}
+
+/// every row of the table with its column labels
+pub async fn select_all<G: GStore + GStoreMut>(
+    glue: &mut Glue<G>,
+    table_name: &str,
+) -> Result<(Vec<String>, Vec<Vec<Value>>)> {
+    let payload = glue
+        .execute(format!("SELECT * FROM {table_name}"))
+        .await
+        .with_context(|| format!("failed to select {table_name}"))?;
+    match payload.into_iter().next() {
+        Some(Payload::Select { labels, rows }) => Ok((labels, rows)),
+        p => bail!("unexpected payload {p:?}"),
+    }
+}
+
+/// one table of a dump, rendered apart from the read
+struct DumpTable {
+    table_name: String,
+    ddl: String,
+    labels: Vec<String>,
+    rows: Vec<Vec<Value>>,
+}
+impl DumpTable {
+    fn new(schema: &Schema, labels: Vec<String>, rows: Vec<Vec<Value>>) -> Self {
+        Self {
+            table_name: schema.table_name.clone(),
+            ddl: schema.to_ddl(),
+            labels,
+            rows,
+        }
+    }
+    /// the DDL followed by the INSERT statements of the rows
+    fn write(&self, out: &mut impl Write) -> Result<()> {
+        writeln!(out, "{};", self.ddl.trim_end().trim_end_matches(';'))?;
+        for statement in insert_statements(&self.table_name, &self.labels, &self.rows)? {
+            writeln!(out, "{statement};")?;
+        }
+        Ok(())
+    }
+}
+
+/// logical dump of every table seen by the glue: one DDL per table followed by its rows.
+/// written table by table, only one table is held in memory
+async fn dump_glue<G: GStore + GStoreMut>(glue: &mut Glue<G>, out: &mut impl Write) -> Result<()> {
+    let schemas = glue.storage.fetch_all_schemas().await?;
+    for schema in schemas {
+        let (labels, rows) = select_all(glue, &schema.table_name).await?;
+        DumpTable::new(&schema, labels, rows).write(out)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// logical dump of every table in the storage, each table is read at its own point in time
+pub async fn dump_storage<G: GStore + GStoreMut + Clone>(storage: &G) -> Result<String> {
+    let mut glue = Glue::new(storage.clone());
+    let mut dump = vec![];
+    dump_glue(&mut glue, &mut dump).await?;
+    Ok(String::from_utf8(dump)?)
+}
+
+/// reads every table inside one transaction and hands them to the writer
+async fn read_snapshot(glue: &mut Glue<SharedSledStorage>, tx: &std::sync::mpsc::Sender<DumpTable>) -> Result<()> {
+    let schemas = glue.storage.fetch_all_schemas().await?;
+    glue.execute("BEGIN").await.context("failed to begin snapshot")?;
+    let mut read = Ok(());
+    for schema in schemas {
+        match select_all(glue, &schema.table_name).await {
+            // a closed channel means the writer failed, its error is reported
+            Ok((labels, rows)) => {
+                if tx.send(DumpTable::new(&schema, labels, rows)).is_err() {
+                    break;
+                }
+            }
+            Err(err) => {
+                read = Err(err);
+                break;
+            }
+        }
+    }
+    // read only, nothing to commit
+    glue.execute("ROLLBACK").await.context("failed to end snapshot")?;
+    read
+}
+
+/// logical dump of the persistent storage at one point in time while the service keeps running.
+/// the tables are read inside one transaction, the writers wait for the reads only: the statements
+/// are rendered and written on the blocking pool while the next tables are read, the tables not written
+/// yet are held in memory
+pub async fn snapshot_storage<W: Write + Send + 'static>(storage: &SharedSledStorage, mut out: W) -> Result<W> {
+    let (tx, rx) = std::sync::mpsc::channel::<DumpTable>();
+    let writer = tokio::task::spawn_blocking(move || -> Result<W> {
+        for table in rx {
+            table.write(&mut out)?;
+        }
+        out.flush()?;
+        Ok(out)
+    });
+    let mut glue = Glue::new(storage.clone());
+    let read = read_snapshot(&mut glue, &tx).await;
+    drop(tx);
+    let written = writer.await?;
+    read?;
+    written
+}
+
+/// executes a dump on an empty storage, returns the number of statements executed
+pub async fn restore_dump<G: GStore + GStoreMut>(storage: G, dump: &str) -> Result<usize> {
+    if dump.trim().is_empty() {
+        return Ok(0);
+    }
+    let mut glue = Glue::new(storage);
+    // parsed as a whole, string literals may span lines
+    let payloads = glue.execute(dump).await.context("failed to restore dump")?;
+    Ok(payloads.len())
+}
+
+/// labels end up in file names
+pub fn validate_backup_label(label: &str) -> Result<()> {
+    ensure!(
+        !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+        "backup label {label:?} must be alphanumeric, '-' or '_'"
+    );
+    Ok(())
+}
+
+/// snapshots the storage into `<directory>/<label>-<timestamp>.sql`. the dump is streamed into a
+/// `.partial` file renamed once complete, a failed snapshot leaves no backup behind.
+/// the files are written on the blocking pool
+pub async fn write_backup(storage: &SharedSledStorage, directory: &Path, label: &str) -> Result<PathBuf> {
+    validate_backup_label(label)?;
+    let path = directory.join(format!(
+        "{}-{}.sql",
+        label,
+        chrono::Utc::now().format("%Y%m%dT%H%M%S%.3f")
+    ));
+    let partial = path.with_extension("sql.partial");
+    let out = {
+        let directory = directory.to_path_buf();
+        let partial = partial.clone();
+        tokio::task::spawn_blocking(move || -> Result<BufWriter<File>> {
+            std::fs::create_dir_all(&directory)
+                .with_context(|| format!("failed to create backup directory {}", directory.display()))?;
+            let file =
+                File::create(&partial).with_context(|| format!("failed to create backup {}", partial.display()))?;
+            Ok(BufWriter::new(file))
+        })
+        .await??
+    };
+    let written = snapshot_storage(storage, out).await;
+    let backup = path.clone();
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        let written = written.and_then(|out| {
+            out.into_inner()
+                .map_err(|err| err.into_error())
+                .and_then(|file| file.sync_all())
+                .map_err(eyre::Report::from)
+        });
+        if let Err(err) = written {
+            std::fs::remove_file(&partial).ok();
+            return Err(err.wrap_err(format!("failed to write backup {}", backup.display())));
+        }
+        std::fs::rename(&partial, &backup).with_context(|| format!("failed to write backup {}", backup.display()))
+    })
+    .await??;
+    info!("database backup written to {}", path.display());
+    Ok(path)
+}
+
+/// removes the oldest backups of the label beyond `keep`, returns the removed files
+pub fn prune_backups(directory: &Path, label: &str, keep: usize) -> Result<Vec<PathBuf>> {
+    if !directory.exists() {
+        return Ok(vec![]);
+    }
+    let prefix = format!("{label}-");
+    let mut backups = vec![];
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+        let Some(name) = path.file_name().and_then(|x| x.to_str()) else {
+            continue;
+        };
+        // the timestamp starts with a digit, `snapshot-` does not match `snapshot-manual-`
+        let is_backup = name
+            .strip_prefix(&prefix)
+            .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()) && rest.ends_with(".sql"));
+        if is_backup {
+            backups.push(path);
+        }
+    }
+    // timestamps sort chronologically
+    backups.sort();
+    let excess = backups.len().saturating_sub(keep);
+    let removed: Vec<PathBuf> = backups.drain(..excess).collect();
+    for path in removed.iter() {
+        std::fs::remove_file(path).with_context(|| format!("failed to remove backup {}", path.display()))?;
+        info!("removed old backup {}", path.display());
+    }
+    Ok(removed)
+}
+
+/// the version recorded in a backup can be brought to `app_version` by the startup migrations
+pub fn ensure_restorable(version: Option<u64>, migrations: &[Migration], app_version: u64) -> Result<u64> {
+    let Some(version) = version else {
+        bail!("backup has no {APP_SETTINGS} version, it is not a database of this service");
+    };
+    ensure!(
+        version <= app_version,
+        "backup version {version} is newer than app version {app_version}"
+    );
+    pending_migrations(migrations, version, app_version)?;
+    Ok(version)
+}
+
+/// restores a backup into a staging directory next to the database, checks its version and swaps it in.
+/// the replaced database is kept as `<directory>.replaced-<timestamp>`. the service must be stopped
+pub async fn restore_backup(
+    file: &Path,
+    database: &DatabaseConfig,
+    table_name: &TableName,
+    verify_only: bool,
+) -> Result<u64> {
+    let dump = std::fs::read_to_string(file).with_context(|| format!("failed to read backup {}", file.display()))?;
+    let directory = &database.directory;
+    let staging = PathBuf::from(format!("{}.restore", directory.display()));
+    if staging.exists() {
+        std::fs::remove_dir_all(&staging)
+            .with_context(|| format!("failed to clear staging directory {}", staging.display()))?;
+    }
+    let checked = async {
+        let storage = SharedSledStorage::new(SledConfig::default().path(&staging), true)?;
+        let statements = restore_dump(storage.clone(), &dump).await?;
+        let mut version: Table<SharedSledStorage, DbRowApplicationSetting> = Table::new(APP_SETTINGS, storage);
+        let version = version.query_table_versioning().await?.map(|x| x.app_version);
+        let version = ensure_restorable(version, &migrations(table_name), APP_VERSION)?;
+        info!(
+            "backup {} verified: {} statements, version {}",
+            file.display(),
+            statements,
+            version
+        );
+        // the storage is dropped here, flushing it before the swap
+        Ok::<_, eyre::Report>(version)
+    }
+    .await;
+    let version = match checked {
+        Ok(version) if !verify_only => version,
+        result => {
+            std::fs::remove_dir_all(&staging).ok();
+            return result;
+        }
+    };
+    if directory.exists() {
+        // sled locks the directory, opening it fails while the service is running
+        if let Err(err) = SharedSledStorage::new(SledConfig::default().path(directory), true) {
+            std::fs::remove_dir_all(&staging).ok();
+            bail!(
+                "database {} is in use, stop the service before restoring: {err}",
+                directory.display()
+            );
+        }
+        let replaced = PathBuf::from(format!(
+            "{}.replaced-{}",
+            directory.display(),
+            chrono::Utc::now().format("%Y%m%dT%H%M%S")
+        ));
+        std::fs::rename(directory, &replaced)
+            .with_context(|| format!("failed to move {} aside", directory.display()))?;
+        info!("previous database moved to {}", replaced.display());
+    }
+    std::fs::rename(&staging, directory)
+        .with_context(|| format!("failed to move restored database into {}", directory.display()))?;
+    info!("database {} restored from {}", directory.display(), file.display());
+    Ok(version)
+}
+
+/// takes a snapshot at the configured interval and keeps the latest `retention` of them
+pub struct BackupScheduler {
+    pub storage: SharedSledStorage,
+    pub directory: PathBuf,
+    pub interval_ms: DurationMs,
+    pub retention: usize,
+}
+impl BackupScheduler {
+    pub const LABEL: &'static str = "scheduled";
+
+    pub async fn run(self) -> Result<()> {
+        let mut interval = interval(self.interval_ms);
+        // the first tick completes immediately, no snapshot right at startup
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if get_terminate_flag() {
+                return Ok(());
+            }
+            if let Err(err) = write_backup(&self.storage, &self.directory, Self::LABEL).await {
+                warn!("scheduled backup failed: {err:?}");
+                continue;
+            }
+            if let Err(err) = prune_backups(&self.directory, Self::LABEL, self.retention) {
+                warn!("failed to prune backups: {err:?}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gluesql::shared_memory_storage::SharedMemoryStorage;
+
+    #[tokio::test]
+    async fn test_dump_restore() -> Result<()> {
+        let storage = SharedMemoryStorage::new();
+        let mut glue = Glue::new(storage.clone());
+        glue.execute("CREATE TABLE note (id UINT64, text TEXT NULL, price FLOAT, flag BOOLEAN)")
+            .await?;
+        glue.execute("INSERT INTO note VALUES (1, 'it''s\nmultiline', 0.1, TRUE), (2, NULL, 1e-9, FALSE)")
+            .await?;
+        // no literal, restored from their text
+        glue.execute(
+            "INSERT INTO note VALUES (3, NULL, CAST('NaN' AS FLOAT), TRUE), (4, NULL, CAST('inf' AS FLOAT), TRUE)",
+        )
+        .await?;
+        let dump = dump_storage(&storage).await?;
+
+        let copy = SharedMemoryStorage::new();
+        restore_dump(copy.clone(), &dump).await?;
+        let mut glue_copy = Glue::new(copy);
+        // NULL is not equal to itself, compared as text
+        assert_eq!(
+            format!("{:?}", select_all(&mut glue_copy, "note").await?),
+            format!("{:?}", select_all(&mut glue, "note").await?)
+        );
+
+        assert_eq!(sql_literal(&Value::F64(f64::NAN))?, "CAST('NaN' AS FLOAT)");
+        assert_eq!(sql_literal(&Value::F64(f64::NEG_INFINITY))?, "CAST('-inf' AS FLOAT)");
+
+        assert!(ensure_restorable(None, &[], 3).is_err());
+        assert!(ensure_restorable(Some(4), &[], 3).is_err());
+        assert!(ensure_restorable(Some(2), &[], 3).is_err());
+        assert_eq!(ensure_restorable(Some(3), &[], 3)?, 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_storage() -> Result<()> {
+        let database = tempfile::tempdir()?;
+        let storage = SharedSledStorage::new(SledConfig::default().path(database.path()), true)?;
+        let mut glue = Glue::new(storage.clone());
+        for table in ["note", "other"] {
+            glue.execute(format!("CREATE TABLE {table} (id UINT64)")).await?;
+            glue.execute(format!("INSERT INTO {table} VALUES (1), (2)")).await?;
+        }
+        let dump = snapshot_storage(&storage, vec![]).await?;
+        // no transaction is left open, the writers go on
+        glue.execute("INSERT INTO note VALUES (3)").await?;
+
+        let copy = SharedMemoryStorage::new();
+        restore_dump(copy.clone(), &String::from_utf8(dump)?).await?;
+        let mut glue_copy = Glue::new(copy);
+        for table in ["note", "other"] {
+            assert_eq!(select_all(&mut glue_copy, table).await?.1.len(), 2);
+        }
+        Ok(())
+    }
+}
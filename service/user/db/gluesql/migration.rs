@@ -0,0 +1,533 @@
+use eyre::{bail, ensure, Context, Result};
+use gluesql::core::store::{GStore, GStoreMut, Store};
+use gluesql::prelude::{Glue, Value};
+use gluesql::shared_memory_storage::SharedMemoryStorage;
+use gluesql_shared_sled_storage::SharedSledStorage;
+use lib::gluesql::{Table, TableCreate};
+use tracing::{debug, info, warn};
+
+use crate::config::DatabaseConfig;
+use crate::db::gluesql::backup::{insert_statements, restore_dump, select_all, snapshot_storage, write_backup};
+use crate::db::gluesql::schema::common::{
+    StrategyId, TableName, LEGACY_LEDGER_TABLE_PREFIX, LEGACY_ORDER_TABLE_PREFIX,
+};
+use crate::db::gluesql::schema::settings::{
+    DbRowApplicationSetting, DbRowAppliedMigration, TableVersioning, APP_MIGRATIONS, APP_SETTINGS,
+};
+use crate::APP_VERSION;
+
+/// rewrites one row in place, the values are in the order of the labels
+pub type RowTransform = fn(&[String], &mut Vec<Value>) -> Result<()>;
+
+/// single change of a migration. steps on a table that does not exist are skipped,
+/// so a migration can list every strategy table whether or not it was created yet
+#[derive(Clone)]
+pub enum MigrationStep {
+    /// `column` is the definition, e.g. `strategy_id INTEGER NOT NULL DEFAULT 0`
+    AddColumn {
+        table: String,
+        column: String,
+    },
+    DropColumn {
+        table: String,
+        column: String,
+    },
+    RenameColumn {
+        table: String,
+        from: String,
+        to: String,
+    },
+    RenameTable {
+        table: String,
+        to: String,
+    },
+    /// rewrites every row of the table
+    TransformRows {
+        table: String,
+        transform: RowTransform,
+    },
+    /// moves the rows matching `filter` into `to`, created with the schema of `table` when missing
+    SplitTable {
+        table: String,
+        to: String,
+        filter: String,
+    },
+    /// moves the rows of every table named `<prefix><strategy_id>` into `to`, tagged with `strategy_id`,
+    /// and drops them. the tables are looked up in the storage, `to` does not need to exist
+    MergeStrategyTables {
+        prefix: String,
+        to: String,
+    },
+    /// executed as is, for anything the steps above do not cover
+    Sql {
+        table: String,
+        sql: String,
+    },
+}
+impl MigrationStep {
+    pub fn table(&self) -> &str {
+        match self {
+            Self::AddColumn { table, .. }
+            | Self::DropColumn { table, .. }
+            | Self::RenameColumn { table, .. }
+            | Self::RenameTable { table, .. }
+            | Self::TransformRows { table, .. }
+            | Self::SplitTable { table, .. }
+            | Self::Sql { table, .. } => table,
+            Self::MergeStrategyTables { to, .. } => to,
+        }
+    }
+}
+
+/// changes bringing the database from the previous version to `version`
+#[derive(Clone)]
+pub struct Migration {
+    pub version: u64,
+    pub name: &'static str,
+    pub steps: Vec<MigrationStep>,
+}
+
+/// returned by the startup migrations in dry-run mode once the migrations succeeded on a copy
+#[derive(Debug)]
+pub struct MigrationDryRunFinished {
+    pub migrations: Vec<String>,
+}
+impl std::fmt::Display for MigrationDryRunFinished {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "migration dry run succeeded for {:?}, the database was not changed",
+            self.migrations
+        )
+    }
+}
+impl std::error::Error for MigrationDryRunFinished {}
+
+/// every migration, ordered by version. the last one is `APP_VERSION`.
+/// to change a persistent `DbRow`, bump `APP_VERSION` and append the migration here
+pub fn migrations(table_name: &TableName) -> Vec<Migration> {
+    vec![
+        Migration {
+            version: 2,
+            name: "unified order and ledger tables",
+            // strategies removed from the config still have their tables
+            steps: vec![
+                MigrationStep::MergeStrategyTables {
+                    prefix: LEGACY_ORDER_TABLE_PREFIX.to_string(),
+                    to: table_name.order.clone(),
+                },
+                MigrationStep::MergeStrategyTables {
+                    prefix: LEGACY_LEDGER_TABLE_PREFIX.to_string(),
+                    to: table_name.ledger.clone(),
+                },
+            ],
+        },
+        Migration {
+            version: 3,
+            name: "ledger lots with fees and funding",
+            steps: ledger_lot_columns(&table_name.ledger),
+        },
+        Migration {
+            version: 4,
+            name: "ledger lots per account",
+            // the account of the lots written before is unknown, they share the funding of every account
+            steps: vec![MigrationStep::AddColumn {
+                table: table_name.ledger.clone(),
+                column: "account INTEGER NOT NULL DEFAULT 0".to_string(),
+            }],
+        },
+    ]
+}
+
+/// the ledger was kept in memory only, so the rows written before can not be matched against fills anymore.
+/// they are settled as they are, the positions they were part of are not reloaded as open lots
+fn ledger_lot_columns(ledger: &str) -> Vec<MigrationStep> {
+    let mut steps: Vec<MigrationStep> = [
+        "closed_volume FLOAT NOT NULL DEFAULT 0.0",
+        "fee_usd FLOAT NOT NULL DEFAULT 0.0",
+        "funding_usd FLOAT NOT NULL DEFAULT 0.0",
+        "unrealized_pnl_usd FLOAT NOT NULL DEFAULT 0.0",
+        "event_id UINT64 NOT NULL DEFAULT 0",
+    ]
+    .into_iter()
+    .map(|column| MigrationStep::AddColumn {
+        table: ledger.to_string(),
+        column: column.to_string(),
+    })
+    .collect();
+    steps.push(MigrationStep::Sql {
+        table: ledger.to_string(),
+        sql: format!("UPDATE {ledger} SET closed_volume = volume"),
+    });
+    steps
+}
+
+/// moves the rows of the per-strategy tables into `to`, tagged with `strategy_id`, and drops them.
+/// the column is appended last, as in the row, so the rows are copied as they are
+async fn merge_strategy_tables<G: GStore + GStoreMut>(glue: &mut Glue<G>, prefix: &str, to: &str) -> Result<()> {
+    let mut tables: Vec<(StrategyId, String)> = glue
+        .storage
+        .fetch_all_schemas()
+        .await?
+        .into_iter()
+        .filter_map(|schema| {
+            let strategy_id = schema.table_name.strip_prefix(prefix)?.parse().ok()?;
+            Some((strategy_id, schema.table_name))
+        })
+        .collect();
+    tables.sort();
+    for (strategy_id, table) in tables {
+        info!("merging table {table} into {to} as strategy {strategy_id}");
+        glue.execute(format!(
+            "ALTER TABLE {table} ADD COLUMN strategy_id INTEGER NOT NULL DEFAULT {strategy_id}"
+        ))
+        .await?;
+        split_table(glue, &table, to, "1 = 1").await?;
+        glue.execute(format!("DROP TABLE {table}")).await?;
+    }
+    Ok(())
+}
+
+async fn split_table<G: GStore + GStoreMut>(glue: &mut Glue<G>, table: &str, to: &str, filter: &str) -> Result<()> {
+    if !table_exists(glue, to).await? {
+        let Some(mut schema) = glue.storage.fetch_schema(table).await? else {
+            bail!("table {table} disappeared");
+        };
+        schema.table_name = to.to_string();
+        glue.execute(schema.to_ddl()).await?;
+    }
+    glue.execute(format!("INSERT INTO {to} SELECT * FROM {table} WHERE {filter}"))
+        .await?;
+    glue.execute(format!("DELETE FROM {table} WHERE {filter}")).await?;
+    Ok(())
+}
+
+/// versions are increasing and none is beyond the app
+pub fn validate_migrations(migrations: &[Migration], app_version: u64) -> Result<()> {
+    for pair in migrations.windows(2) {
+        ensure!(
+            pair[0].version < pair[1].version,
+            "migration {} is not ordered after {}",
+            pair[1].version,
+            pair[0].version
+        );
+    }
+    if let Some(last) = migrations.last() {
+        ensure!(
+            last.version <= app_version,
+            "migration {} is beyond app version {}",
+            last.version,
+            app_version
+        );
+    }
+    Ok(())
+}
+
+/// migrations to apply to a database at `current`, bail when the database can not reach `app_version`
+pub fn pending_migrations(migrations: &[Migration], current: u64, app_version: u64) -> Result<Vec<Migration>> {
+    let pending: Vec<Migration> = migrations
+        .iter()
+        .filter(|x| x.version > current && x.version <= app_version)
+        .cloned()
+        .collect();
+    if current < app_version && pending.last().map(|x| x.version) != Some(app_version) {
+        bail!("no migration registered from database version {current} to app version {app_version}");
+    }
+    Ok(pending)
+}
+
+async fn table_exists<G: GStore + GStoreMut>(glue: &Glue<G>, table: &str) -> Result<bool> {
+    Ok(glue.storage.fetch_schema(table).await?.is_some())
+}
+
+async fn apply_step<G: GStore + GStoreMut>(glue: &mut Glue<G>, step: &MigrationStep) -> Result<()> {
+    if let MigrationStep::MergeStrategyTables { prefix, to } = step {
+        return merge_strategy_tables(glue, prefix, to).await;
+    }
+    let table = step.table();
+    if !table_exists(glue, table).await? {
+        debug!("table {table} does not exist, step skipped");
+        return Ok(());
+    }
+    match step {
+        MigrationStep::AddColumn { column, .. } => {
+            glue.execute(format!("ALTER TABLE {table} ADD COLUMN {column}")).await?;
+        }
+        MigrationStep::DropColumn { column, .. } => {
+            glue.execute(format!("ALTER TABLE {table} DROP COLUMN {column}"))
+                .await?;
+        }
+        MigrationStep::RenameColumn { from, to, .. } => {
+            glue.execute(format!("ALTER TABLE {table} RENAME COLUMN {from} TO {to}"))
+                .await?;
+        }
+        MigrationStep::RenameTable { to, .. } => {
+            glue.execute(format!("ALTER TABLE {table} RENAME TO {to}")).await?;
+        }
+        MigrationStep::TransformRows { transform, .. } => {
+            let (labels, mut rows) = select_all(glue, table).await?;
+            for row in rows.iter_mut() {
+                transform(&labels, row)?;
+            }
+            glue.execute(format!("DELETE FROM {table}")).await?;
+            for statement in insert_statements(table, &labels, &rows)? {
+                glue.execute(statement).await?;
+            }
+        }
+        MigrationStep::SplitTable { to, filter, .. } => {
+            split_table(glue, table, to, filter).await?;
+        }
+        MigrationStep::MergeStrategyTables { .. } => unreachable!("merged above"),
+        MigrationStep::Sql { sql, .. } => {
+            glue.execute(sql).await?;
+        }
+    }
+    Ok(())
+}
+
+/// applies the migrations in order, recording each one and moving the database version along
+pub async fn apply_migrations<G: GStore + GStoreMut + Clone>(
+    storage: G,
+    migrations: &[Migration],
+    backup: &str,
+) -> Result<()> {
+    let mut glue = Glue::new(storage.clone());
+    let mut version: Table<G, DbRowApplicationSetting> = Table::new(APP_SETTINGS, storage.clone());
+    version.create_table().await?;
+    let mut applied: Table<G, DbRowAppliedMigration> = Table::new(APP_MIGRATIONS, storage);
+    applied.create_table().await?;
+    for migration in migrations {
+        info!("applying migration {} {}", migration.version, migration.name);
+        for (i, step) in migration.steps.iter().enumerate() {
+            apply_step(&mut glue, step)
+                .await
+                .with_context(|| format!("migration {} step {} on {}", migration.version, i, step.table()))?;
+        }
+        applied
+            .insert(DbRowAppliedMigration {
+                version: migration.version,
+                name: migration.name.to_string(),
+                applied_at: chrono::Utc::now().timestamp_millis(),
+                backup: backup.to_string(),
+            })
+            .await?;
+        version
+            .upsert_table_versioning(DbRowApplicationSetting {
+                app_version: migration.version,
+            })
+            .await?;
+    }
+    Ok(())
+}
+
+/// brings the persistent database to `APP_VERSION`. a dump is written before anything is changed.
+/// in dry-run mode the migrations are applied to an in-memory copy and startup stops afterwards
+pub async fn run_startup_migrations(
+    storage: SharedSledStorage,
+    table_name: &TableName,
+    config: &DatabaseConfig,
+) -> Result<()> {
+    let migrations = migrations(table_name);
+    validate_migrations(&migrations, APP_VERSION)?;
+    let mut version: Table<SharedSledStorage, DbRowApplicationSetting> = Table::new(APP_SETTINGS, storage.clone());
+    // a dry run never goes on to start the service, even with nothing to migrate
+    let finished = |migrations: Vec<String>| -> Result<()> {
+        match config.migration_dry_run {
+            true => Err(MigrationDryRunFinished { migrations }.into()),
+            false => Ok(()),
+        }
+    };
+    let Some(current) = version.query_table_versioning().await? else {
+        // fresh database, tables are created at the current version
+        return finished(vec![]);
+    };
+    let pending = pending_migrations(&migrations, current.app_version, APP_VERSION)?;
+    if pending.is_empty() {
+        return finished(vec![]);
+    }
+    let names: Vec<String> = pending.iter().map(|x| format!("{} {}", x.version, x.name)).collect();
+    info!(
+        "database version {} is behind {}, pending migrations: {:?}",
+        current.app_version, APP_VERSION, names
+    );
+    if config.migration_dry_run {
+        let dump = snapshot_storage(&storage, vec![]).await?;
+        let copy = SharedMemoryStorage::new();
+        restore_dump(copy.clone(), &String::from_utf8(dump)?).await?;
+        apply_migrations(copy, &pending, "dry run").await?;
+        return Err(MigrationDryRunFinished { migrations: names }.into());
+    }
+    let label = format!("pre-migration-v{}-v{}", current.app_version, APP_VERSION);
+    let backup = write_backup(&storage, &config.backup_directory(), &label).await?;
+    if let Err(err) = apply_migrations(storage, &pending, &backup.display().to_string()).await {
+        warn!(
+            "migration failed, restore the backup {} into an empty database",
+            backup.display()
+        );
+        return Err(err);
+    }
+    info!("database migrated to version {}", APP_VERSION);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::gluesql::backup::dump_storage;
+    use crate::db::gluesql::schema::common::legacy_order_table;
+    use crate::db::gluesql::schema::order::OrderQuery;
+    use crate::db::gluesql::schema::DbRowOrder;
+    use lib::gluesql::TableSelectItem;
+
+    fn double_size(labels: &[String], row: &mut Vec<Value>) -> Result<()> {
+        let index = labels.iter().position(|x| x == "size").unwrap();
+        if let Value::F64(size) = row[index] {
+            row[index] = Value::F64(size * 2.0);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_apply_migrations() -> Result<()> {
+        let storage = SharedMemoryStorage::new();
+        let mut glue = Glue::new(storage.clone());
+        glue.execute("CREATE TABLE orders (id UINT64, qty FLOAT, strategy INTEGER)")
+            .await?;
+        glue.execute("INSERT INTO orders VALUES (1, 1.5, 1), (2, 2.0, 2), (3, 0.5, 2)")
+            .await?;
+        let migrations = vec![
+            Migration {
+                version: 2,
+                name: "rename qty",
+                steps: vec![
+                    MigrationStep::RenameColumn {
+                        table: "orders".to_string(),
+                        from: "qty".to_string(),
+                        to: "size".to_string(),
+                    },
+                    MigrationStep::AddColumn {
+                        table: "orders".to_string(),
+                        column: "note TEXT NULL".to_string(),
+                    },
+                    // skipped
+                    MigrationStep::DropColumn {
+                        table: "missing".to_string(),
+                        column: "note".to_string(),
+                    },
+                ],
+            },
+            Migration {
+                version: 3,
+                name: "split strategy 2",
+                steps: vec![
+                    MigrationStep::TransformRows {
+                        table: "orders".to_string(),
+                        transform: double_size,
+                    },
+                    MigrationStep::SplitTable {
+                        table: "orders".to_string(),
+                        to: "orders_2".to_string(),
+                        filter: "strategy = 2".to_string(),
+                    },
+                ],
+            },
+        ];
+        validate_migrations(&migrations, 3)?;
+        assert!(pending_migrations(&migrations, 1, 4).is_err());
+        let pending = pending_migrations(&migrations, 1, 3)?;
+        assert_eq!(pending.len(), 2);
+
+        // dry run on a copy leaves the source untouched
+        let copy = SharedMemoryStorage::new();
+        restore_dump(copy.clone(), &dump_storage(&storage).await?).await?;
+        apply_migrations(copy, &pending, "dry run").await?;
+        let (labels, _) = select_all(&mut glue, "orders").await?;
+        assert!(labels.contains(&"qty".to_string()));
+
+        apply_migrations(storage.clone(), &pending, "backup.sql").await?;
+        let (labels, rows) = select_all(&mut glue, "orders").await?;
+        assert_eq!(labels, vec!["id", "size", "strategy", "note"]);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][..3], [Value::U64(1), Value::F64(3.0), Value::I64(1)]);
+        // NULL is not equal to itself
+        assert!(matches!(rows[0][3], Value::Null));
+        let (_, rows) = select_all(&mut glue, "orders_2").await?;
+        assert_eq!(rows.len(), 2);
+
+        let mut version: Table<SharedMemoryStorage, DbRowApplicationSetting> = Table::new(APP_SETTINGS, storage);
+        assert_eq!(version.query_table_versioning().await?.unwrap().app_version, 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unify_strategy_tables() -> Result<()> {
+        let storage = SharedMemoryStorage::new();
+        let mut glue = Glue::new(storage.clone());
+        // per-strategy tables as they were before the strategy_id column
+        for strategy_id in [1, 2] {
+            let legacy = legacy_order_table(strategy_id);
+            let mut table: Table<SharedMemoryStorage, DbRowOrder> = Table::new(&legacy, storage.clone());
+            table.create_table().await?;
+            for id in 1..=2 {
+                table
+                    .insert(DbRowOrder {
+                        id,
+                        client_id: format!("{strategy_id}-{id}"),
+                        ..Default::default()
+                    })
+                    .await?;
+            }
+            glue.execute(format!("ALTER TABLE {legacy} DROP COLUMN strategy_id"))
+                .await?;
+        }
+        // strategy 2 is no longer configured, its table is found in the storage
+        let table_name = TableName::new(&[0, 1]);
+        apply_migrations(storage.clone(), &migrations(&table_name), "backup.sql").await?;
+
+        assert!(!table_exists(&glue, &legacy_order_table(1)).await?);
+        let mut orders: Table<SharedMemoryStorage, DbRowOrder> = Table::new(&table_name.order, storage);
+        let rows = orders.select(None, "client_id").await?;
+        let rows: Vec<(i64, String)> = rows.into_iter().map(|x| (x.strategy_id, x.client_id)).collect();
+        assert_eq!(
+            rows,
+            vec![
+                (1, "1-1".to_string()),
+                (1, "1-2".to_string()),
+                (2, "2-1".to_string()),
+                (2, "2-2".to_string())
+            ]
+        );
+        let query = OrderQuery {
+            strategy_id: Some(2),
+            client_id: Some("2-1".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(orders.select(Some(query.filter()), "id").await?.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_never_starts() -> Result<()> {
+        let database = tempfile::tempdir()?;
+        let storage = SharedSledStorage::new(
+            gluesql_shared_sled_storage::Config::default().path(database.path()),
+            true,
+        )?;
+        let table_name = TableName::new(&crate::main_core::STRATEGY_IDS);
+        let mut config = DatabaseConfig {
+            directory: database.path().to_path_buf(),
+            migration_dry_run: true,
+            ..DatabaseConfig::default()
+        };
+        // fresh database, nothing to migrate
+        let err = run_startup_migrations(storage.clone(), &table_name, &config)
+            .await
+            .unwrap_err();
+        let finished = err.downcast_ref::<MigrationDryRunFinished>().unwrap();
+        assert!(finished.migrations.is_empty());
+
+        config.migration_dry_run = false;
+        run_startup_migrations(storage, &table_name, &config).await?;
+        Ok(())
+    }
+}
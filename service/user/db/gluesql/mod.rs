@@ -3,10 +3,17 @@ use self::common::{StrategyId, TableName};
 use self::ledger::DbRowLedger;
 use self::schema::*;
 use super::worktable::orders::OrdersWorkTable;
+use crate::config::DatabaseConfig;
+use crate::db::gluesql::migration::run_startup_migrations;
 use crate::db::gluesql::row_num_checker::RowNumChecker;
 use crate::db::gluesql::schema::bench::DbRowBench;
 use crate::db::gluesql::schema::canclestack::DbRowCandlestick;
+use crate::db::gluesql::schema::candle::DbRowCandle;
+use crate::db::gluesql::schema::funding_accrual::DbRowFundingAccrual;
+use crate::db::gluesql::schema::funding_history::DbRowFundingHistory;
 use crate::db::gluesql::schema::funding_rate::DbRowFundingRate;
+use crate::db::gluesql::schema::liquidation::DbRowLiquidation;
+use crate::db::gluesql::schema::position_pnl::DbRowPositionPnl;
 use crate::db::gluesql::schema::settings::{DbRowApplicationSetting, APP_SETTINGS};
 use crate::db::gluesql::schema::spread::DbRowSpread;
 use crate::db::gluesql::schema::symbol_flag::DbRowSymbolFlagExt;
@@ -15,24 +22,33 @@ use crate::db::worktable::balance::WorktableBalance;
 use crate::db::worktable::order_manager::OrderManager;
 use crate::db::worktable::position_manager::PositionManager;
 use crate::events::price_change_and_diff::DbRowEventPriceChangeAndDiff;
+use crate::signals::candles::CandleMap;
+use crate::signals::fair_price::{DbRowSignalFairPriceDifference, FairPriceMap};
+use crate::signals::funding::FundingCarryMap;
 use crate::signals::price::WorktableSignalPrice;
 use crate::signals::price_change::{DbRowSignalPriceChange, DbRowSignalPriceChangeImmediate};
 use crate::signals::price_difference::{DbRowSignalPriceDifference, DbRowSignalPriceDifferenceGeneric};
 use crate::signals::price_spread::{SpreadMeanTable, WorktableSignalBestBidAskAcrossExchanges};
-use crate::strategy::data_factory::LastPriceMap;
+use crate::signals::trade_flow::{DbRowSignalTradeFlow, TradeFlowMap};
+use crate::strategy::data_factory::{LastDerivativesContextMap, LastPriceMap, OrderBookMap};
+use crate::strategy::instrument_refresh::DynamicInstrumentManager;
 use crate::strategy::strategy_two_and_three::event::DbRowBestBidAskAcrossExchangesAndPosition;
 use crate::strategy::StrategyStatusMap;
 use gluesql::core::store::{GStore, GStoreMut};
 use gluesql::shared_memory_storage::SharedMemoryStorage;
 use gluesql_shared_sled_storage::SharedSledStorage;
-use lib::gluesql::{DbRow, QueryFilter, Table, TableCreate, TableSelectItem};
+use lib::gluesql::{DbRow, QueryFilter, Table, TableCreate, TableGetIndex, TableSelectItem};
 use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::sync::Arc;
 use tracing::info;
 use trading_exchange::model::PortfolioMulti;
-use trading_model::{Asset, InstrumentManager, SharedInstrumentManager};
+use trading_model::{Asset, SharedInstrumentManager};
 
+/// logical dumps of the storage
+pub mod backup;
+/// versioned schema migrations applied on startup
+pub mod migration;
 mod row_num_checker;
 /// table schema
 pub mod schema;
@@ -82,6 +98,8 @@ pub struct VolatileTableMap {
     // strategy 2 (TODO strategy 0 and 1 can also use below for consistency)
     pub signal_price_difference_generic: Table<SharedMemoryStorage, DbRowSignalPriceDifferenceGeneric>,
     pub signal_price_change_immediate: Table<SharedMemoryStorage, DbRowSignalPriceChangeImmediate>,
+    pub signal_trade_flow: Table<SharedMemoryStorage, DbRowSignalTradeFlow>,
+    pub signal_fair_price_diff: Table<SharedMemoryStorage, DbRowSignalFairPriceDifference>,
     pub accuracy: StrategyTable<SharedMemoryStorage, DbRowStrategyAccuracy>,
     pub price_volume: Table<SharedMemoryStorage, DbRowPriceVolume>,
     pub index_price_volume: AssetIndexTable<SharedMemoryStorage, DbRowPriceVolume>,
@@ -98,8 +116,15 @@ pub struct VolatileTableMap {
     pub order_manager: Arc<tokio::sync::RwLock<OrderManager>>,
     pub position_manager: Arc<tokio::sync::RwLock<PositionManager>>,
     pub candlestick: Table<SharedMemoryStorage, DbRowCandlestick>,
-    pub instruments: Arc<InstrumentManager>,
+    /// reloaded periodically, see `InstrumentRefresher`
+    pub instruments: Arc<DynamicInstrumentManager>,
     pub price_map: Arc<LastPriceMap>,
+    pub derivatives_map: Arc<LastDerivativesContextMap>,
+    pub orderbook_map: Arc<OrderBookMap>,
+    pub candle_map: Arc<CandleMap>,
+    pub funding_carry_map: Arc<FundingCarryMap>,
+    pub fair_price_map: Arc<FairPriceMap>,
+    pub trade_flow_map: Arc<TradeFlowMap>,
     pub spread_table: Table<SharedMemoryStorage, DbRowSpread>,
     pub spread_mean: SpreadMeanTable,
 }
@@ -186,6 +211,16 @@ impl VolatileTableMap {
         if let Err(e) = signal_price_change_immediate.create_table().await {
             tracing::warn!("error creating table {e}");
         }
+        let mut signal_trade_flow: Table<SharedMemoryStorage, DbRowSignalTradeFlow> =
+            Table::new(&table_name.signal_trade_flow, volatile.clone());
+        if let Err(e) = signal_trade_flow.create_table().await {
+            tracing::warn!("error creating table {e}");
+        }
+        let mut signal_fair_price_diff: Table<SharedMemoryStorage, DbRowSignalFairPriceDifference> =
+            Table::new(&table_name.signal_fair_price_diff, volatile.clone());
+        if let Err(e) = signal_fair_price_diff.create_table().await {
+            tracing::warn!("error creating table {e}");
+        }
         let mut signal_price_difference_generic: Table<SharedMemoryStorage, DbRowSignalPriceDifferenceGeneric> =
             Table::new(&table_name.signal_diff_generic, volatile.clone());
         if let Err(e) = signal_price_difference_generic.create_table().await {
@@ -200,6 +235,7 @@ impl VolatileTableMap {
         let mut spread: Table<SharedMemoryStorage, DbRowSpread> = Table::new(&table_name.spread, volatile.clone());
         spread.create_table().await.unwrap();
         let mean_spread = SpreadMeanTable::new();
+        let instruments = Arc::new(DynamicInstrumentManager::new(instruments));
         VolatileTableMap {
             price_worktable: Arc::new(tokio::sync::RwLock::new(WorktableSignalPrice::new())),
             signal_price_spread_worktable: Arc::new(tokio::sync::RwLock::new(
@@ -209,6 +245,8 @@ impl VolatileTableMap {
             signal_price_change,
             signal_price_difference_generic,
             signal_price_change_immediate,
+            signal_trade_flow,
+            signal_fair_price_diff,
             accuracy,
             price_volume,
             index_price_volume,
@@ -225,8 +263,14 @@ impl VolatileTableMap {
             order_manager: Arc::new(tokio::sync::RwLock::new(OrderManager::new())),
             position_manager: Arc::new(tokio::sync::RwLock::new(PositionManager::new())),
             candlestick,
+            trade_flow_map: Arc::new(TradeFlowMap::new(instruments.clone())),
             instruments,
             price_map: Arc::new(LastPriceMap::new()),
+            derivatives_map: Arc::new(LastDerivativesContextMap::new()),
+            orderbook_map: Arc::new(OrderBookMap::new()),
+            candle_map: Arc::new(CandleMap::new()),
+            funding_carry_map: Arc::new(FundingCarryMap::new()),
+            fair_price_map: Arc::new(FairPriceMap::new()),
             spread_table: spread,
             spread_mean: mean_spread,
         }
@@ -238,17 +282,29 @@ pub struct PersistentTableMap {
     // pub user: Table<SharedSledStorage, DbRowUser>,
     pub symbol_flag: StrategyTable<SharedSledStorage, DbRowSymbolFlag>,
     pub key: Table<SharedSledStorage, DbRowKey>,
-    // TODO: flatten it
-    pub order: StrategyTable<SharedSledStorage, DbRowOrder>,
-    pub ledger: StrategyTable<SharedSledStorage, DbRowLedger>,
+    // orders and ledgers of every strategy, filtered by strategy_id
+    pub order: Table<SharedSledStorage, DbRowOrder>,
+    pub ledger: Table<SharedSledStorage, DbRowLedger>,
+    pub position_pnl: Table<SharedSledStorage, DbRowPositionPnl>,
     pub trade_status: StrategyTable<SharedSledStorage, DbRowTradeStatus>,
+    pub liquidation: Table<SharedSledStorage, DbRowLiquidation>,
+    pub candle: Table<SharedSledStorage, DbRowCandle>,
+    pub funding_history: Table<SharedSledStorage, DbRowFundingHistory>,
+    pub funding_accrual: Table<SharedSledStorage, DbRowFundingAccrual>,
 }
 impl PersistentTableMap {
     /// initialise table structure and create the table
-    pub async fn new(persistent: SharedSledStorage, table_name: &TableName, asset_ids: Vec<Asset>) -> Self {
+    pub async fn new(
+        persistent: SharedSledStorage,
+        table_name: &TableName,
+        asset_ids: Vec<Asset>,
+        database: &DatabaseConfig,
+    ) -> eyre::Result<Self> {
         let mut version: Table<SharedSledStorage, DbRowApplicationSetting> =
             Table::new(APP_SETTINGS, persistent.clone());
         version.create_table().await.unwrap();
+        // before the tables are created with the current schema
+        run_startup_migrations(persistent.clone(), table_name, database).await?;
 
         // symbol flag
         let mut symbol_flag = HashMap::new();
@@ -288,39 +344,76 @@ impl PersistentTableMap {
 
             trade_status.insert(strategy_id, table);
         }
-        // order
-        let mut order = HashMap::new();
-        for (&strategy_id, table_name) in table_name.order.iter() {
-            let mut table: Table<SharedSledStorage, DbRowOrder> = Table::new(table_name, persistent.clone());
-            if let Err(e) = table.create_table().await {
-                tracing::warn!("error creating table {e}");
-            }
-
-            order.insert(strategy_id, table);
+        let mut order: Table<SharedSledStorage, DbRowOrder> = Table::new(&table_name.order, persistent.clone());
+        if let Err(e) = order.create_table().await {
+            tracing::warn!("error creating table {e}");
         }
-        // fill info
-        let mut ledger = HashMap::new();
-        for (&strategy_id, table_name) in table_name.fill_info.iter() {
-            let mut table: Table<SharedSledStorage, DbRowLedger> = Table::new(table_name, persistent.clone());
-            if let Err(e) = table.create_table().await {
-                tracing::warn!("error creating table {e}");
-            }
-
-            ledger.insert(strategy_id, table);
+        let mut ledger: Table<SharedSledStorage, DbRowLedger> = Table::new(&table_name.ledger, persistent.clone());
+        if let Err(e) = ledger.create_table().await {
+            tracing::warn!("error creating table {e}");
+        }
+        match ledger.get_last_index().await {
+            Ok(index) => ledger.set_index(index.unwrap_or_default()),
+            Err(e) => tracing::warn!("error getting last index of ledger {e}"),
+        }
+        let mut position_pnl: Table<SharedSledStorage, DbRowPositionPnl> =
+            Table::new(&table_name.position_pnl, persistent.clone());
+        if let Err(e) = position_pnl.create_table().await {
+            tracing::warn!("error creating table {e}");
+        }
+        match position_pnl.get_last_index().await {
+            Ok(index) => position_pnl.set_index(index.unwrap_or_default()),
+            Err(e) => tracing::warn!("error getting last index of position pnl {e}"),
+        }
+        let mut liquidation: Table<SharedSledStorage, DbRowLiquidation> =
+            Table::new(&table_name.liquidation, persistent.clone());
+        if let Err(e) = liquidation.create_table().await {
+            tracing::warn!("error creating table {e}");
+        }
+        match liquidation.get_last_index().await {
+            Ok(index) => liquidation.set_index(index.unwrap_or_default()),
+            Err(e) => tracing::warn!("error getting last index of liquidation {e}"),
+        }
+        let mut candle: Table<SharedSledStorage, DbRowCandle> = Table::new(&table_name.candle, persistent.clone());
+        if let Err(e) = candle.create_table().await {
+            tracing::warn!("error creating table {e}");
+        }
+        let mut funding_history: Table<SharedSledStorage, DbRowFundingHistory> =
+            Table::new(&table_name.funding_history, persistent.clone());
+        if let Err(e) = funding_history.create_table().await {
+            tracing::warn!("error creating table {e}");
+        }
+        match funding_history.get_last_index().await {
+            Ok(index) => funding_history.set_index(index.unwrap_or_default()),
+            Err(e) => tracing::warn!("error getting last index of funding history {e}"),
+        }
+        let mut funding_accrual: Table<SharedSledStorage, DbRowFundingAccrual> =
+            Table::new(&table_name.funding_accrual, persistent.clone());
+        if let Err(e) = funding_accrual.create_table().await {
+            tracing::warn!("error creating table {e}");
+        }
+        match funding_accrual.get_last_index().await {
+            Ok(index) => funding_accrual.set_index(index.unwrap_or_default()),
+            Err(e) => tracing::warn!("error getting last index of funding accrual {e}"),
         }
         // let mut user: Table<SharedSledStorage, DbRowUser> = Table::new("user", persistent.clone());
         // let ddl = DbRowUser::get_ddl("user");
         // user.execute(ddl).await.unwrap();
 
-        PersistentTableMap {
+        Ok(PersistentTableMap {
             // user,
             version,
             symbol_flag,
             key,
             order,
             ledger,
+            position_pnl,
             trade_status,
-        }
+            liquidation,
+            candle,
+            funding_history,
+            funding_accrual,
+        })
     }
 }
 
@@ -331,10 +424,11 @@ impl TableMap {
         table_name: &TableName,
         assets: Vec<Asset>,
         instruments: SharedInstrumentManager,
-    ) -> Self {
+        database: &DatabaseConfig,
+    ) -> eyre::Result<Self> {
         let mut map = TableMap {
             volatile: VolatileTableMap::new(volatile, table_name, assets.clone(), instruments).await,
-            persistent: PersistentTableMap::new(persistent, table_name, assets).await,
+            persistent: PersistentTableMap::new(persistent, table_name, assets, database).await?,
         };
         map.volatile
             .order_manager
@@ -348,17 +442,18 @@ impl TableMap {
         for (_, t) in map.persistent.symbol_flag.iter_mut() {
             counter.count_table(t).await;
         }
-        for (_, t) in map.persistent.order.iter_mut() {
-            counter.count_table(t).await;
-        }
-        for (_, t) in map.persistent.ledger.iter_mut() {
-            counter.count_table(t).await;
-        }
+        counter.count_table(&mut map.persistent.order).await;
+        counter.count_table(&mut map.persistent.ledger).await;
+        counter.count_table(&mut map.persistent.position_pnl).await;
         for (_, t) in map.persistent.trade_status.iter_mut() {
             counter.count_table(t).await;
         }
+        counter.count_table(&mut map.persistent.liquidation).await;
+        counter.count_table(&mut map.persistent.candle).await;
+        counter.count_table(&mut map.persistent.funding_history).await;
+        counter.count_table(&mut map.persistent.funding_accrual).await;
         counter.print_sorted();
 
-        map
+        Ok(map)
     }
 }
@@ -0,0 +1,137 @@
+use async_trait::async_trait;
+use eyre::{bail, ContextCompat};
+use gluesql::core::ast_builder;
+use gluesql::core::ast_builder::{col, Build, ExprNode};
+use gluesql::core::store::{GStore, GStoreMut};
+use gluesql::prelude::Payload;
+use gluesql_derive::{FromGlueSqlRow, ReflectGlueSqlRow, ToGlueSql, ToGlueSqlRow};
+use interning::{InternedString, InternedStringHash};
+use serde::{Deserialize, Serialize};
+
+use build::model::UserCandle;
+use lib::gluesql::{Table, TableCreate, TableInfo, TableUpdateItem};
+use trading_model::{format_candle_interval, Exchange, InstrumentCode, Symbol, Time, OHLCVT};
+
+/// candles of every aggregated interval, kept in sled across restarts
+#[derive(Debug, Clone, Serialize, Deserialize, FromGlueSqlRow, ToGlueSqlRow, ReflectGlueSqlRow)]
+pub struct DbRowCandle {
+    /// exchange enum ID
+    pub exchange_id: u8,
+    /// symbol intern hash
+    pub symbol_id: u64,
+    /// candle interval in ms
+    pub interval_ms: i64,
+    /// open time in ms
+    pub datetime: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
+impl DbRowCandle {
+    pub fn exchange(&self) -> Exchange {
+        self.exchange_id.try_into().unwrap()
+    }
+    pub fn symbol(&self) -> InternedString {
+        unsafe { InternedString::from_hash(InternedStringHash::new(self.symbol_id)) }
+    }
+    /// candles of one instrument and interval
+    pub fn by_series(exchange: Exchange, symbol_id: u64, interval_ms: i64) -> ExprNode<'static> {
+        col("exchange_id")
+            .eq((exchange as u8).to_gluesql())
+            .and(col("symbol_id").eq(symbol_id.to_gluesql()))
+            .and(col("interval_ms").eq(interval_ms.to_gluesql()))
+    }
+    /// the row with the same series and open time
+    pub fn filter(&self) -> ExprNode<'static> {
+        Self::by_series(self.exchange(), self.symbol_id, self.interval_ms)
+            .and(col("datetime").eq(self.datetime.to_gluesql()))
+    }
+}
+
+/// fails on candles of an instrument without a symbol or exchange
+impl TryFrom<OHLCVT> for DbRowCandle {
+    type Error = eyre::Error;
+
+    fn try_from(o: OHLCVT) -> eyre::Result<Self> {
+        let exchange = o
+            .instrument
+            .get_exchange()
+            .with_context(|| format!("candle without exchange: {:?}", o.instrument))?;
+        let symbol = o
+            .instrument
+            .get_symbol()
+            .with_context(|| format!("candle without symbol: {:?}", o.instrument))?;
+        Ok(Self {
+            exchange_id: exchange as _,
+            symbol_id: symbol._hash(),
+            interval_ms: o.interval_ms as _,
+            datetime: o.exchange_time.millis(),
+            open: o.open,
+            high: o.high,
+            low: o.low,
+            close: o.close,
+            volume: o.volume,
+        })
+    }
+}
+impl From<DbRowCandle> for OHLCVT {
+    fn from(row: DbRowCandle) -> Self {
+        let symbol = Symbol::from(row.symbol().as_str());
+        Self {
+            instrument: InstrumentCode::from_symbol(row.exchange(), symbol),
+            open: row.open,
+            high: row.high,
+            low: row.low,
+            close: row.close,
+            volume: row.volume,
+            exchange_time: Time::from_millis(row.datetime),
+            received_time: Time::NULL,
+            interval_ms: row.interval_ms as _,
+        }
+    }
+}
+impl From<DbRowCandle> for UserCandle {
+    fn from(row: DbRowCandle) -> Self {
+        UserCandle {
+            exchange: row.exchange().to_string(),
+            symbol: row.symbol().to_string(),
+            interval: format_candle_interval(row.interval_ms as _),
+            open: row.open,
+            high: row.high,
+            low: row.low,
+            close: row.close,
+            volume: row.volume,
+            datetime: row.datetime,
+        }
+    }
+}
+#[async_trait(?Send)]
+impl<T: GStore + GStoreMut> TableCreate<DbRowCandle> for Table<T, DbRowCandle> {
+    async fn create_table(&mut self) -> eyre::Result<()> {
+        let sql = DbRowCandle::get_ddl(self.table_name());
+        self.execute(&sql).await?;
+        Ok(())
+    }
+}
+#[async_trait(?Send)]
+impl<T: GStore + GStoreMut> TableUpdateItem<DbRowCandle, T> for Table<T, DbRowCandle> {
+    async fn update(&mut self, row: DbRowCandle, _filter: Option<ExprNode<'static>>) -> eyre::Result<usize> {
+        let stmt = ast_builder::table(self.table_name())
+            .update()
+            .filter(row.filter())
+            .set("open", row.open.to_gluesql())
+            .set("high", row.high.to_gluesql())
+            .set("low", row.low.to_gluesql())
+            .set("close", row.close.to_gluesql())
+            .set("volume", row.volume.to_gluesql())
+            .build()?;
+        let payload = self.execute_stmt(&stmt).await?;
+        match payload {
+            Payload::Update(n) => Ok(n),
+            _ => bail!("unexpected payload: {:?}", payload),
+        }
+    }
+}
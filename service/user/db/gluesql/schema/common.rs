@@ -1,5 +1,9 @@
 use std::collections::HashMap;
 
+use gluesql::core::store::{GStore, GStoreMut, Store};
+use gluesql::prelude::Glue;
+use tracing::warn;
+
 ////////////////////////////// TABLE NAME
 
 pub type StrategyId = i32;
@@ -12,6 +16,9 @@ pub struct TableName {
     pub symbol_flag: HashMap<StrategyId, String>,
     pub price: String,
     pub funding_rate: String,
+    pub funding_history: String,
+    pub funding_accrual: String,
+    pub liquidation: String,
     pub signal_price_pair: String,
     pub livetest_fill: String,
     // strategy 0 and 1
@@ -20,17 +27,24 @@ pub struct TableName {
     pub signal_change: String,
     // strategy 2 (and onwards)
     pub signal_diff_generic: String,
+    // venues against the fair price
+    pub signal_fair_price_diff: String,
     // strategy 2
     pub signal_change_immediate: String,
+    pub signal_trade_flow: String,
     pub event_price_change_and_diff: HashMap<StrategyId, String>,
     pub event_price_spread_and_position: String,
     pub accuracy: HashMap<StrategyId, String>,
     pub price_volume: String,
-    pub order: HashMap<StrategyId, String>,
-    pub fill_info: HashMap<StrategyId, String>,
+    /// orders of every strategy, keyed by `strategy_id`
+    pub order: String,
+    /// ledgers of every strategy, keyed by `strategy_id`
+    pub ledger: String,
+    pub position_pnl: String,
     pub bench: String,
     pub position: String,
     pub candlestick: String,
+    pub candle: String,
     pub spread: String,
 }
 
@@ -41,8 +55,6 @@ impl TableName {
         let mut event = HashMap::new();
         let mut diff = HashMap::new();
         let mut accuracy = HashMap::new();
-        let mut order = HashMap::new();
-        let mut fill_info = HashMap::new();
 
         for &strategy_id in strategy_ids {
             // no diff for strategy 2
@@ -51,8 +63,6 @@ impl TableName {
             event.insert(strategy_id, format!("event_{strategy_id}"));
             // no accuracy for strategy 0
             accuracy.insert(strategy_id, format!("accuracy_{strategy_id}"));
-            order.insert(strategy_id, format!("order_{strategy_id}"));
-            fill_info.insert(strategy_id, format!("fill_info_{strategy_id}"));
         }
         TableName {
             symbol: "symbol".to_string(),
@@ -60,22 +70,61 @@ impl TableName {
             symbol_flag,
             price: "price".to_string(),
             funding_rate: "funding_rate".to_string(),
+            funding_history: "funding_history".to_string(),
+            funding_accrual: "funding_accrual".to_string(),
+            liquidation: "liquidation".to_string(),
             livetest_fill: "livetest_fill".to_string(),
             signal_price_pair: "price_pair".to_string(),
             signal_difference: diff,
             signal_change: "signal_price_change".to_string(),
             signal_diff_generic: "signal_diff_generic".into(),
+            signal_fair_price_diff: "signal_fair_price_diff".into(),
             signal_change_immediate: "signal_change_immediate".into(),
+            signal_trade_flow: "signal_trade_flow".into(),
             event_price_change_and_diff: event,
             event_price_spread_and_position: "event_price_spread_and_position".to_string(),
             accuracy,
             price_volume: "price_volume".to_string(),
-            order,
-            fill_info,
+            // ORDER is a keyword
+            order: "orders".to_string(),
+            ledger: "ledger".to_string(),
+            position_pnl: "position_pnl".to_string(),
             bench: "bench".to_string(),
             position: "position".to_string(),
             candlestick: "candlestick".to_string(),
+            candle: "candle".to_string(),
             spread: "spread".to_string(),
         }
     }
 }
+
+/// per-strategy order tables before the orders were unified are `<prefix><strategy_id>`, kept for the migration
+pub const LEGACY_ORDER_TABLE_PREFIX: &str = "order_";
+/// per-strategy ledger tables before the ledgers were unified are `<prefix><strategy_id>`, kept for the migration
+pub const LEGACY_LEDGER_TABLE_PREFIX: &str = "fill_info_";
+pub fn legacy_order_table(strategy_id: StrategyId) -> String {
+    format!("{LEGACY_ORDER_TABLE_PREFIX}{strategy_id}")
+}
+
+/// creates the single column indexes `<table>_<column>` that are missing. indexes only speed up the
+/// lookups, a storage without index support keeps working without them
+pub async fn ensure_indexes<G: GStore + GStoreMut>(
+    glue: &mut Glue<G>,
+    table: &str,
+    columns: &[&str],
+) -> eyre::Result<()> {
+    let Some(schema) = glue.storage.fetch_schema(table).await? else {
+        eyre::bail!("table {table} does not exist");
+    };
+    for column in columns {
+        let name = format!("{table}_{column}");
+        if schema.indexes.iter().any(|x| x.name == name) {
+            continue;
+        }
+        if let Err(err) = glue.execute(format!("CREATE INDEX {name} ON {table} ({column})")).await {
+            warn!("index {name} not created: {err}");
+            return Ok(());
+        }
+    }
+    Ok(())
+}
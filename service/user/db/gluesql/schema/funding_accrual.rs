@@ -0,0 +1,49 @@
+use async_trait::async_trait;
+use gluesql::core::ast_builder::{col, ExprNode};
+use gluesql::core::store::{GStore, GStoreMut};
+use gluesql_derive::{FromGlueSqlRow, ReflectGlueSqlRow, ToGlueSql, ToGlueSqlRow};
+use serde::{Deserialize, Serialize};
+
+use lib::gluesql::{Table, TableCreate, TableInfo};
+
+use crate::db::gluesql::schema::common::StrategyId;
+
+/// share of a funding payment attributed to an open ledger entry
+#[derive(Debug, Clone, Serialize, Deserialize, FromGlueSqlRow, ToGlueSqlRow, ReflectGlueSqlRow)]
+pub struct DbRowFundingAccrual {
+    /// primary key
+    pub id: u64,
+    pub strategy_id: i64,
+    /// ledger foreign key
+    pub ledger_id: u64,
+    /// funding payment ID of the exchange
+    pub funding_lid: String,
+    /// exchange enum ID
+    pub exchange_id: u8,
+    /// symbol intern hash
+    pub symbol_id: u64,
+    /// received when positive, paid when negative
+    pub amount_usd: f64,
+    /// funding time in ms
+    pub datetime: i64,
+}
+
+impl DbRowFundingAccrual {
+    pub fn by_ledger(strategy_id: StrategyId, ledger_id: u64) -> ExprNode<'static> {
+        col("strategy_id")
+            .eq((strategy_id as i64).to_gluesql())
+            .and(col("ledger_id").eq(ledger_id.to_gluesql()))
+    }
+    pub fn by_strategy(strategy_id: StrategyId) -> ExprNode<'static> {
+        col("strategy_id").eq((strategy_id as i64).to_gluesql())
+    }
+}
+
+#[async_trait(?Send)]
+impl<T: GStore + GStoreMut> TableCreate<DbRowFundingAccrual> for Table<T, DbRowFundingAccrual> {
+    async fn create_table(&mut self) -> eyre::Result<()> {
+        let sql = DbRowFundingAccrual::get_ddl(self.table_name());
+        self.execute(&sql).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,58 @@
+use async_trait::async_trait;
+use gluesql::core::ast_builder::{col, ExprNode};
+use gluesql::core::store::{GStore, GStoreMut};
+use gluesql_derive::{FromGlueSqlRow, ReflectGlueSqlRow, ToGlueSql, ToGlueSqlRow};
+use interning::{InternedString, InternedStringHash};
+use serde::{Deserialize, Serialize};
+
+use build::model::UserFundingHistory;
+use lib::gluesql::{Table, TableCreate, TableInfo};
+use trading_model::Exchange;
+
+/// settled funding rates of the perpetuals, one row per funding interval
+#[derive(Debug, Clone, Serialize, Deserialize, FromGlueSqlRow, ToGlueSqlRow, ReflectGlueSqlRow)]
+pub struct DbRowFundingHistory {
+    /// primary key
+    pub id: u64,
+    /// exchange enum ID
+    pub exchange_id: u8,
+    /// symbol intern hash
+    pub symbol_id: u64,
+    /// rate paid for the interval, positive when longs pay shorts
+    pub funding_rate: f64,
+    /// funding time in ms
+    pub datetime: i64,
+}
+
+impl DbRowFundingHistory {
+    pub fn exchange(&self) -> Exchange {
+        self.exchange_id.try_into().unwrap()
+    }
+    pub fn symbol(&self) -> InternedString {
+        unsafe { InternedString::from_hash(InternedStringHash::new(self.symbol_id)) }
+    }
+    pub fn by_instrument(exchange: Exchange, symbol_id: u64) -> ExprNode<'static> {
+        col("exchange_id")
+            .eq((exchange as u8).to_gluesql())
+            .and(col("symbol_id").eq(symbol_id.to_gluesql()))
+    }
+}
+
+impl From<DbRowFundingHistory> for UserFundingHistory {
+    fn from(value: DbRowFundingHistory) -> Self {
+        UserFundingHistory {
+            exchange: value.exchange().to_string(),
+            symbol: value.symbol().to_string(),
+            rate: value.funding_rate,
+            datetime: value.datetime,
+        }
+    }
+}
+#[async_trait(?Send)]
+impl<T: GStore + GStoreMut> TableCreate<DbRowFundingHistory> for Table<T, DbRowFundingHistory> {
+    async fn create_table(&mut self) -> eyre::Result<()> {
+        let sql = DbRowFundingHistory::get_ddl(self.table_name());
+        self.execute(&sql).await?;
+        Ok(())
+    }
+}
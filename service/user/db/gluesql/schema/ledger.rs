@@ -1,14 +1,14 @@
-use crate::db::worktable::orders::OrderRowView;
 use async_trait::async_trait;
 use gluesql::core::ast_builder;
 use gluesql::core::ast_builder::{Build, ExprNode};
 use gluesql::core::store::{GStore, GStoreMut};
 use gluesql_derive::{FromGlueSqlRow, ReflectGlueSqlRow, ToGlueSql, ToGlueSqlRow};
-use lib::gluesql::{Table, TableCreate};
+use lib::gluesql::{QueryFilter, Table, TableCreate};
 use lib::gluesql::{TableInfo, TableUpdateItem};
 use serde::{Deserialize, Serialize};
-use tracing::info;
-use trading_model::{Side, TimeStampMs, NANOSECONDS_PER_MILLISECOND};
+use trading_model::{Exchange, Side, Symbol, TimeStampMs};
+
+use crate::db::gluesql::schema::common::{ensure_indexes, StrategyId};
 
 #[derive(Debug, Clone, ReflectGlueSqlRow, FromGlueSqlRow, ToGlueSqlRow, Default, PartialEq, Serialize, Deserialize)]
 pub struct DbRowLedger {
@@ -33,90 +33,122 @@ pub struct DbRowLedger {
     pub symbol_id: u64,
     // position enum ID
     pub open_order_position_type_id: u8,
-    /// volume
+    /// opened volume, grows with the partial fills of the open order
     pub volume: f64,
     // OrderType enum ID
     pub order_type_id: u8,
     // Side enum ID
     pub open_order_side_id: u8,
-    /// price
+    /// average price of the opening fills
     pub open_price_usd: f64,
-    /// price
+    /// average price of the closing fills
     pub close_price_usd: f64,
-    // closed profit
+    /// realized profit of the closed volume, net of the fees and funding of the lot
     pub closed_profit_usd: f64,
+    /// strategy of the open order. the columns added by migrations follow in the order they were added.
+    /// ids were assigned per strategy before the ledgers were unified, rows are keyed by both
+    pub strategy_id: i64,
+    /// part of `volume` closed so far, the lot is open until it reaches `volume`
+    pub closed_volume: f64,
+    /// fees of the opening and closing fills
+    pub fee_usd: f64,
+    /// funding received when positive, paid when negative
+    pub funding_usd: f64,
+    /// of the open volume, marked to the last price
+    pub unrealized_pnl_usd: f64,
+    /// event captured by the open order, shared by the legs of a hedged pair
+    pub event_id: u64,
+    /// account of the open order, 0 for the lots written before the account was recorded
+    pub account: i64,
 }
 
 impl DbRowLedger {
-    /// assume it is order
-    pub fn from_open_close_order_pair(open_order: OrderRowView, close_order: OrderRowView) -> Self {
-        Self::from_open_order(open_order).with_close_order(close_order)
+    pub fn open_volume(&self) -> f64 {
+        self.volume - self.closed_volume
     }
-    pub fn from_open_order(open_order: OrderRowView) -> Self {
-        Self {
-            id: 0,
-            exchange_id: open_order.exchange() as u8,
-            symbol_id: open_order.symbol()._hash(),
-            volume: open_order.size(),
-            datetime: open_order.update_lt() / NANOSECONDS_PER_MILLISECOND,
-            order_type_id: open_order.ty() as u8,
-            open_order_side_id: open_order.side().map_or(0, |x| x as u8),
-            open_order_position_type_id: open_order.position_effect() as u8,
-            closed_profit_usd: 0.0,
-            open_order_id: open_order.local_id().to_string(),
-            close_order_id: "".to_string(),
-            open_order_cloid: open_order.client_id().to_string(),
-            close_order_cloid: "".to_string(),
-            open_price_usd: open_order.price(),
-            close_price_usd: 0.0,
-        }
+    pub fn side(&self) -> Side {
+        Side::from_repr(self.open_order_side_id).unwrap_or(Side::Buy)
+    }
+    pub fn filter_by_key(&self) -> ExprNode<'static> {
+        ast_builder::col("strategy_id")
+            .eq(self.strategy_id.to_gluesql())
+            .and(ast_builder::col("id").eq(self.id.to_gluesql()))
     }
-    pub fn with_close_order(mut self, close_order: OrderRowView) -> Self {
-        self.close_order_id = close_order.local_id().to_string();
-        self.close_order_cloid = close_order.client_id().to_string();
-        self.close_price_usd = close_order.price();
-        self.closed_profit_usd = {
-            let close_order_value_usd = close_order.size() * close_order.price();
-            let open_order_value_usd = self.volume * self.open_price_usd;
-            let profit = close_order_value_usd - open_order_value_usd;
-            if self.open_order_side_id == Side::Buy as u8 {
-                profit
-            } else {
-                -profit
-            }
-        };
-        info!(
-            "Calculate profit: {:?} close order size {} price {}",
-            self,
-            close_order.size(),
-            close_order.price()
-        );
-        self
+}
+
+/// filter on the ledger table, every field is optional and they are combined with AND
+#[derive(Debug, Clone, Default)]
+pub struct LedgerQuery {
+    pub strategy_id: Option<StrategyId>,
+    /// matches the open or the close order
+    pub client_id: Option<String>,
+    pub exchange: Option<Exchange>,
+    pub symbol: Option<Symbol>,
+    /// inclusive, ms
+    pub time_start: Option<TimeStampMs>,
+    /// inclusive, ms
+    pub time_end: Option<TimeStampMs>,
+}
+impl LedgerQuery {
+    pub fn filter(&self) -> ExprNode<'static> {
+        let mut filter = QueryFilter::range(self.time_start, self.time_end);
+        if let Some(strategy_id) = self.strategy_id {
+            filter = filter.and(ast_builder::col("strategy_id").eq((strategy_id as i64).to_gluesql()));
+        }
+        if let Some(client_id) = &self.client_id {
+            filter = filter.and(
+                QueryFilter::eq_string("open_order_cloid", client_id)
+                    .or(QueryFilter::eq_string("close_order_cloid", client_id)),
+            );
+        }
+        if let Some(exchange) = self.exchange {
+            filter = filter.and(ast_builder::col("exchange_id").eq((exchange as u8).to_gluesql()));
+        }
+        if let Some(symbol) = &self.symbol {
+            filter = filter.and(QueryFilter::symbol_id(symbol._hash()));
+        }
+        filter
     }
 }
 
+/// columns looked up by the endpoints and the ledger manager
+const LEDGER_INDEXES: [&str; 5] = [
+    "strategy_id",
+    "open_order_cloid",
+    "close_order_cloid",
+    "symbol_id",
+    "datetime",
+];
+
 #[async_trait(?Send)]
 impl<T: GStore + GStoreMut> TableCreate<DbRowLedger> for Table<T, DbRowLedger> {
     async fn create_table(&mut self) -> eyre::Result<()> {
         let sql = DbRowLedger::get_ddl(self.table_name());
-        match self.glue().execute(sql.as_str()).await {
-            Err(e) => Err(e.into()),
-            _ => Ok(()),
+        if let Err(e) = self.glue().execute(sql.as_str()).await {
+            return Err(e.into());
         }
+        let table_name = self.table_name().clone();
+        ensure_indexes(self.glue(), &table_name, &LEDGER_INDEXES).await
     }
 }
 
 #[async_trait(?Send)]
 impl<T: GStore + GStoreMut> TableUpdateItem<DbRowLedger, T> for Table<T, DbRowLedger> {
     async fn update(&mut self, row: DbRowLedger, filter: Option<ExprNode<'static>>) -> eyre::Result<usize> {
-        let filter = filter.unwrap_or_else(|| ast_builder::col("id").eq(row.id.to_gluesql()));
+        let filter = filter.unwrap_or_else(|| row.filter_by_key());
         let sql = ast_builder::table(self.table_name())
             .update()
             .filter(filter)
+            .set("volume", row.volume.to_gluesql())
+            .set("open_price_usd", row.open_price_usd.to_gluesql())
             .set("close_order_id", row.close_order_id.to_gluesql())
             .set("close_order_cloid", row.close_order_cloid.to_gluesql())
             .set("close_price_usd", row.close_price_usd.to_gluesql())
             .set("closed_profit_usd", row.closed_profit_usd.to_gluesql())
+            .set("closed_volume", row.closed_volume.to_gluesql())
+            .set("fee_usd", row.fee_usd.to_gluesql())
+            .set("funding_usd", row.funding_usd.to_gluesql())
+            .set("unrealized_pnl_usd", row.unrealized_pnl_usd.to_gluesql())
             .build()?;
         match self.glue().execute_stmt(&sql).await {
             Ok(_) => Ok(1),
@@ -0,0 +1,91 @@
+use async_trait::async_trait;
+use eyre::ContextCompat;
+use gluesql::core::ast_builder::{col, ExprNode};
+use gluesql::core::store::{GStore, GStoreMut};
+use gluesql_derive::{FromGlueSqlRow, ReflectGlueSqlRow, ToGlueSql, ToGlueSqlRow};
+use interning::{InternedString, InternedStringHash};
+use serde::{Deserialize, Serialize};
+
+use build::model::UserLiquidation;
+use lib::gluesql::{Table, TableCreate, TableInfo};
+use trading_model::{Exchange, LiquidationEvent, Side};
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromGlueSqlRow, ToGlueSqlRow, ReflectGlueSqlRow)]
+pub struct DbRowLiquidation {
+    /// primary key
+    pub id: u64,
+    /// exchange enum ID
+    pub exchange_id: u8,
+    /// symbol intern hash
+    pub symbol_id: u64,
+    /// Side enum ID of the forced order
+    pub side_id: u8,
+    pub price: f64,
+    pub size: f64,
+    /// exchange time in ms
+    pub datetime: i64,
+    /// received time in ms
+    pub received_time: i64,
+}
+
+impl DbRowLiquidation {
+    pub fn exchange(&self) -> Exchange {
+        self.exchange_id.try_into().unwrap()
+    }
+    pub fn symbol(&self) -> InternedString {
+        unsafe { InternedString::from_hash(InternedStringHash::new(self.symbol_id)) }
+    }
+    pub fn side(&self) -> Side {
+        Side::from_repr(self.side_id).unwrap_or(Side::Unknown)
+    }
+    pub fn after_id(id: u64) -> ExprNode<'static> {
+        col("id").gt(id.to_gluesql())
+    }
+}
+
+/// fails on events of an instrument without a symbol or exchange, they can't be queried back
+impl TryFrom<LiquidationEvent> for DbRowLiquidation {
+    type Error = eyre::Error;
+
+    fn try_from(event: LiquidationEvent) -> eyre::Result<Self> {
+        let symbol = event
+            .instrument
+            .get_symbol()
+            .with_context(|| format!("liquidation without symbol: {:?}", event.instrument))?;
+        let exchange = event
+            .instrument
+            .get_exchange()
+            .with_context(|| format!("liquidation without exchange: {:?}", event.instrument))?;
+        Ok(Self {
+            id: 0,
+            exchange_id: exchange as _,
+            symbol_id: symbol._hash(),
+            side_id: event.side as _,
+            price: event.price,
+            size: event.size,
+            datetime: event.exchange_time.millis(),
+            received_time: event.received_time.millis(),
+        })
+    }
+}
+impl From<DbRowLiquidation> for UserLiquidation {
+    fn from(value: DbRowLiquidation) -> Self {
+        UserLiquidation {
+            id: value.id as _,
+            exchange: value.exchange().to_string(),
+            symbol: value.symbol().to_string(),
+            side: value.side().to_string(),
+            price: value.price,
+            size: value.size,
+            datetime: value.datetime,
+        }
+    }
+}
+#[async_trait(?Send)]
+impl<T: GStore + GStoreMut> TableCreate<DbRowLiquidation> for Table<T, DbRowLiquidation> {
+    async fn create_table(&mut self) -> eyre::Result<()> {
+        let sql = DbRowLiquidation::get_ddl(self.table_name());
+        self.execute(&sql).await?;
+        Ok(())
+    }
+}
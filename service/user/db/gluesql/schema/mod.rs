@@ -8,16 +8,26 @@ pub use symbol_flag::DbRowSymbolFlag;
 
 /// strategy accuracy
 pub mod accuracy;
+/// candles aggregated from trades and backfilled from the exchanges
+pub mod candle;
 /// common SQL for all table targets
 pub mod common;
+/// funding attributed to open ledger entries
+pub mod funding_accrual;
+/// settled funding rates
+pub mod funding_history;
 /// funding rate
 pub mod funding_rate;
 /// exchange key
 pub mod key;
 /// ledger
 pub mod ledger;
+/// liquidations published by the exchanges
+pub mod liquidation;
 /// order generated by strategy
 pub mod order;
+/// PnL of the ledger lots grouped by event, one row per hedged pair
+pub mod position_pnl;
 
 pub mod canclestack;
 /// best bid order price volume
@@ -1,6 +1,6 @@
 use crate::db::worktable::orders::OrderRowView;
 use async_trait::async_trait;
-use gluesql::core::ast_builder::ExprNode;
+use gluesql::core::ast_builder::{col, ExprNode};
 use gluesql::core::{
     ast_builder::Build,
     error::Error,
@@ -13,7 +13,9 @@ use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 use tracing::warn;
 use trading_exchange::model::{Order, OrderStatus, PositionEffect, RequestPlaceOrder};
-use trading_model::{Side, TimeStampMs, NANOSECONDS_PER_MILLISECOND};
+use trading_model::{Exchange, Side, Symbol, TimeStampMs, NANOSECONDS_PER_MILLISECOND};
+
+use crate::db::gluesql::schema::common::{ensure_indexes, StrategyId};
 
 #[derive(Debug, Clone, FromGlueSqlRow, ReflectGlueSqlRow, ToGlueSqlRow, Default, PartialEq, Serialize, Deserialize)]
 pub struct DbRowOrder {
@@ -44,6 +46,8 @@ pub struct DbRowOrder {
     pub open_order_id: Option<u8>,
     // status ID
     pub status_id: u8,
+    /// strategy that placed the order, last so the migrated rows keep their column order
+    pub strategy_id: i64,
 }
 impl DbRowOrder {
     pub fn effect(&self) -> PositionEffect {
@@ -53,14 +57,58 @@ impl DbRowOrder {
         QueryFilter::eq_string("client_id", self.client_id.clone())
     }
 }
+
+/// filter on the order table, every field is optional and they are combined with AND
+#[derive(Debug, Clone, Default)]
+pub struct OrderQuery {
+    pub strategy_id: Option<StrategyId>,
+    pub client_id: Option<String>,
+    pub event_id: Option<u64>,
+    pub exchange: Option<Exchange>,
+    pub symbol: Option<Symbol>,
+    pub status: Option<OrderStatus>,
+    /// inclusive, ms
+    pub time_start: Option<TimeStampMs>,
+    /// inclusive, ms
+    pub time_end: Option<TimeStampMs>,
+}
+impl OrderQuery {
+    pub fn filter(&self) -> ExprNode<'static> {
+        let mut filter = QueryFilter::range(self.time_start, self.time_end);
+        if let Some(strategy_id) = self.strategy_id {
+            filter = filter.and(col("strategy_id").eq((strategy_id as i64).to_gluesql()));
+        }
+        if let Some(client_id) = &self.client_id {
+            filter = filter.and(QueryFilter::eq_string("client_id", client_id));
+        }
+        if let Some(event_id) = self.event_id {
+            filter = filter.and(QueryFilter::u64("event_id", event_id));
+        }
+        if let Some(exchange) = self.exchange {
+            filter = filter.and(col("exchange_id").eq((exchange as u8).to_gluesql()));
+        }
+        if let Some(symbol) = &self.symbol {
+            filter = filter.and(QueryFilter::symbol_id(symbol._hash()));
+        }
+        if let Some(status) = self.status {
+            filter = filter.and(col("status_id").eq((status as u8).to_gluesql()));
+        }
+        filter
+    }
+}
+
+/// columns looked up by the endpoints and the order manager
+const ORDER_INDEXES: [&str; 4] = ["strategy_id", "client_id", "symbol_id", "datetime"];
+
 #[async_trait(?Send)]
 impl<T: GStore + GStoreMut> TableCreate<DbRowOrder> for Table<T, DbRowOrder> {
     async fn create_table(&mut self) -> eyre::Result<()> {
         let sql = DbRowOrder::get_ddl(self.table_name());
-        match self.glue().execute(sql.as_str()).await {
-            Err(e) => Err(e.into()),
-            _ => Ok(()),
+        if let Err(e) = self.glue().execute(sql.as_str()).await {
+            return Err(e.into());
         }
+        let table_name = self.table_name().clone();
+        ensure_indexes(self.glue(), &table_name, &ORDER_INDEXES).await
     }
 }
 // update
@@ -82,6 +130,7 @@ impl<T: GStore + GStoreMut> TableUpdateItem<DbRowOrder, T> for Table<T, DbRowOrd
             .set("event_id", row.event_id.to_gluesql())
             // .set("open_order_id", row.open_order_id.to_gluesql())
             .set("status_id", row.status_id.to_gluesql())
+            .set("strategy_id", row.strategy_id.to_gluesql())
             .filter(filter)
             .build()?;
 
@@ -141,6 +190,7 @@ impl From<RequestPlaceOrder> for DbRowOrder {
             event_id: order.event_id,
             open_order_id: None,
             status_id: OrderStatus::Pending as u8,
+            strategy_id: order.strategy_id as _,
         }
     }
 }
@@ -164,6 +214,7 @@ impl From<Order> for DbRowOrder {
             event_id: order.event_id,
             open_order_id: None,
             status_id: order.status as u8,
+            strategy_id: order.strategy_id as _,
         }
     }
 }
@@ -186,6 +237,7 @@ impl<'a> From<OrderRowView<'a>> for DbRowOrder {
             event_id: order.event_id() as _,
             open_order_id: None,
             status_id: order.status() as _,
+            strategy_id: order.strategy_id() as _,
         }
     }
 }
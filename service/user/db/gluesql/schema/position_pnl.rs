@@ -0,0 +1,56 @@
+use async_trait::async_trait;
+use gluesql::core::ast_builder::{col, ExprNode};
+use gluesql::core::store::{GStore, GStoreMut};
+use gluesql_derive::{FromGlueSqlRow, ReflectGlueSqlRow, ToGlueSql, ToGlueSqlRow};
+use serde::{Deserialize, Serialize};
+
+use lib::gluesql::{Table, TableCreate, TableInfo};
+
+use crate::db::gluesql::schema::common::{ensure_indexes, StrategyId};
+
+/// PnL of the ledger lots opened for one event, the two legs of a hedged pair in one row
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, FromGlueSqlRow, ToGlueSqlRow, ReflectGlueSqlRow)]
+pub struct DbRowPositionPnl {
+    /// primary key
+    pub id: u64,
+    pub strategy_id: i64,
+    /// event captured by the open orders
+    pub event_id: u64,
+    /// utc time of the first opening fill
+    pub datetime: i64,
+    /// utc time of the last change
+    pub update_datetime: i64,
+    /// `exchange:symbol` of every leg, comma separated
+    pub legs: String,
+    /// realized by the closed volume, net of fees and funding
+    pub realized_pnl_usd: f64,
+    pub unrealized_pnl_usd: f64,
+    pub fee_usd: f64,
+    pub funding_usd: f64,
+    /// every lot of the event is closed
+    pub closed: bool,
+}
+
+impl DbRowPositionPnl {
+    pub fn total_pnl_usd(&self) -> f64 {
+        self.realized_pnl_usd + self.unrealized_pnl_usd
+    }
+    pub fn by_strategy(strategy_id: StrategyId) -> ExprNode<'static> {
+        col("strategy_id").eq((strategy_id as i64).to_gluesql())
+    }
+    pub fn open() -> ExprNode<'static> {
+        col("closed").eq(false.to_gluesql())
+    }
+}
+
+const POSITION_PNL_INDEXES: [&str; 3] = ["strategy_id", "event_id", "datetime"];
+
+#[async_trait(?Send)]
+impl<T: GStore + GStoreMut> TableCreate<DbRowPositionPnl> for Table<T, DbRowPositionPnl> {
+    async fn create_table(&mut self) -> eyre::Result<()> {
+        let sql = DbRowPositionPnl::get_ddl(self.table_name());
+        self.execute(&sql).await?;
+        let table_name = self.table_name().clone();
+        ensure_indexes(self.glue(), &table_name, &POSITION_PNL_INDEXES).await
+    }
+}
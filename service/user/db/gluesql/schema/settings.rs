@@ -26,6 +26,26 @@ impl<G: GStore + GStoreMut> TableCreate<DbRowApplicationSetting> for Table<G, Db
         }
     }
 }
+pub const APP_MIGRATIONS: &str = "app_settings_migration";
+/// schema migration applied to the database, see `db::gluesql::migration`
+#[derive(Debug, Clone, FromGlueSqlRow, ReflectGlueSqlRow, ToGlueSqlRow, PartialEq)]
+pub struct DbRowAppliedMigration {
+    pub version: u64,
+    pub name: String,
+    pub applied_at: i64,
+    /// dump taken before the migration
+    pub backup: String,
+}
+
+#[async_trait(?Send)]
+impl<G: GStore + GStoreMut> TableCreate<DbRowAppliedMigration> for Table<G, DbRowAppliedMigration> {
+    async fn create_table(&mut self) -> eyre::Result<()> {
+        let sql = DbRowAppliedMigration::get_ddl(self.table_name());
+        self.execute(sql.as_str()).await?;
+        Ok(())
+    }
+}
+
 #[async_trait(?Send)]
 pub trait TableVersioning<G: GStore + GStoreMut> {
     async fn query_table_versioning(&mut self) -> eyre::Result<Option<DbRowApplicationSetting>>;
@@ -86,13 +106,19 @@ impl<G: GStore + GStoreMut> CheckAppVersion for Table<G, DbRowApplicationSetting
         let versioning = self.query_table_versioning().await?;
         match versioning {
             Some(versioning) => {
-                if versioning.app_version != new_version {
+                if versioning.app_version > new_version {
                     bail!(
-                        "App Version mismatch, old version = {}, new version = {}",
+                        "App Version mismatch, database version = {} is newer than app version = {}",
+                        versioning.app_version,
+                        new_version
+                    );
+                } else if versioning.app_version < new_version {
+                    // migrated on startup, see `run_startup_migrations`
+                    tracing::info!(
+                        "database version {} is behind app version {}, pending migrations",
                         versioning.app_version,
                         new_version
                     );
-                    // TODO: handle migration
                 } else {
                     // it's needed here to restore the table index
                     self.create_table().await?;
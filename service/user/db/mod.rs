@@ -1,4 +1,10 @@
+/// Parquet/CSV export of the recorded history
+pub mod columnar;
 /// database with SQL, both volatile/persistent
 pub mod gluesql;
+/// export to Postgres/TimescaleDB for analytics
+pub mod postgres;
+/// retention and downsampling policies of the tables
+pub mod retention;
 /// in-memory table
 pub mod worktable;
@@ -0,0 +1,431 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+
+use eyre::{bail, Context, Result};
+use gluesql::core::store::{GStore, GStoreMut, Store};
+use gluesql::prelude::{Glue, SharedMemoryStorage, Value};
+use gluesql_shared_sled_storage::SharedSledStorage;
+use lib::gluesql::{DbRow, Table, TableInfo};
+use lib::signal::get_terminate_flag;
+use lib::utils::get_time_milliseconds;
+use tokio_postgres::{Client, NoTls};
+use tracing::{info, warn};
+use trading_exchange::utils::future::interval;
+use trading_model::TimeStampMs;
+
+use crate::config::PostgresExportConfig;
+use crate::db::gluesql::backup::sql_literal;
+use crate::db::postgres::queue::ExportQueue;
+use crate::db::postgres::{create_table_sql, ExportBatch};
+
+/// batches read from one table per tick, the rest waits for the next tick
+const MAX_BATCHES_PER_TICK: usize = 10;
+
+/// how new rows of a table are found
+#[derive(Debug, Clone, Copy)]
+pub enum ExportMode {
+    /// rows are only inserted, tailed by the increasing `cursor` column
+    Append { cursor: &'static str },
+    /// rows are updated in place (orders, ledgers), the ones inside the window are exported again when changed.
+    /// a row updated after its `datetime` left the window keeps its last exported state in Postgres
+    Mutable,
+}
+
+/// one GlueSQL table exported into the Postgres table of the same name
+pub struct ExportSource<G: GStore + GStoreMut> {
+    glue: Glue<G>,
+    table: String,
+    key: Vec<&'static str>,
+    mode: ExportMode,
+    /// cursor of an appended table, restored after a restart for the persistent ones
+    cursor: Option<Value>,
+    /// digest by key of the rows of a mutable table inside the window
+    digests: HashMap<String, u64>,
+}
+impl<G: GStore + GStoreMut + Clone> ExportSource<G> {
+    pub fn new<D: DbRow>(mut table: Table<G, D>, key: &[&'static str], mode: ExportMode) -> Self {
+        Self {
+            glue: Glue::new(table.storage.clone()),
+            table: table.table_name().clone(),
+            key: key.to_vec(),
+            mode,
+            cursor: None,
+            digests: HashMap::new(),
+        }
+    }
+    /// tailed by id, for the signal and event tables. the volatile tables number their rows from 1 again
+    /// after a restart, the datetime keeps the rows of the runs apart
+    pub fn append<D: DbRow>(table: Table<G, D>) -> Self {
+        Self::new(table, &["id", "datetime"], ExportMode::Append { cursor: "id" })
+    }
+    /// ids of the orders and ledgers were assigned per strategy before the tables were unified
+    pub fn mutable<D: DbRow>(table: Table<G, D>) -> Self {
+        Self::new(table, &["strategy_id", "id"], ExportMode::Mutable)
+    }
+    async fn create_target(&mut self, client: &Client, timescale: bool) -> Result<bool> {
+        let Some(schema) = self.glue.storage.fetch_schema(&self.table).await? else {
+            return Ok(false);
+        };
+        let Some(column_defs) = schema.column_defs else {
+            bail!("table {} has no columns", self.table);
+        };
+        let key: Vec<String> = self.key.iter().map(|x| x.to_string()).collect();
+        client
+            .batch_execute(&create_table_sql(&self.table, &key, &column_defs, timescale))
+            .await
+            .with_context(|| format!("failed to create {} in postgres", self.table))?;
+        Ok(true)
+    }
+    /// rows changed since the last poll
+    async fn poll(&mut self, now: TimeStampMs, config: &PostgresExportConfig) -> Result<Vec<ExportBatch>> {
+        match self.mode {
+            ExportMode::Append { cursor } => self.poll_append(cursor, config.batch_size).await,
+            ExportMode::Mutable => {
+                self.poll_mutable(now - config.mutable_window_ms, config.batch_size)
+                    .await
+            }
+        }
+    }
+    async fn poll_append(&mut self, cursor: &str, batch_size: usize) -> Result<Vec<ExportBatch>> {
+        let mut batches = vec![];
+        while batches.len() < MAX_BATCHES_PER_TICK {
+            let filter = match &self.cursor {
+                // rows sharing the last cursor value may have been inserted since, the upsert absorbs the repeats
+                Some(Value::U64(x)) if cursor == "id" => format!("WHERE {cursor} > {x}"),
+                Some(x) => format!("WHERE {cursor} >= {}", sql_literal(x)?),
+                None => String::new(),
+            };
+            let sql = format!(
+                "SELECT * FROM {} {filter} ORDER BY {cursor} LIMIT {batch_size}",
+                self.table
+            );
+            let (labels, rows) = select_all_sql(&mut self.glue, &sql).await?;
+            let Some(index) = labels.iter().position(|x| x == cursor) else {
+                bail!("table {} has no column {cursor}", self.table);
+            };
+            let Some(last) = rows.last() else {
+                break;
+            };
+            let last = last[index].clone();
+            let full = rows.len() >= batch_size;
+            // a repeated cursor value means the whole batch shares it, moving on avoids polling it forever
+            let stuck = self.cursor.as_ref() == Some(&last);
+            self.cursor = Some(last);
+            batches.push(ExportBatch::from_rows(&self.table, &self.key, labels, &rows)?);
+            if !full || stuck {
+                break;
+            }
+        }
+        Ok(batches)
+    }
+    async fn poll_mutable(&mut self, since: TimeStampMs, batch_size: usize) -> Result<Vec<ExportBatch>> {
+        let sql = format!("SELECT * FROM {} WHERE datetime >= {since}", self.table);
+        let (labels, rows) = select_all_sql(&mut self.glue, &sql).await?;
+        let mut key = vec![];
+        for column in self.key.iter() {
+            let Some(index) = labels.iter().position(|x| x == column) else {
+                bail!("table {} has no column {column}", self.table);
+            };
+            key.push(index);
+        }
+        let mut digests = HashMap::with_capacity(rows.len());
+        let mut changed = vec![];
+        for row in rows {
+            let id: Vec<&Value> = key.iter().map(|&x| &row[x]).collect();
+            let id = format!("{id:?}");
+            let mut hasher = DefaultHasher::new();
+            format!("{row:?}").hash(&mut hasher);
+            let digest = hasher.finish();
+            if self.digests.get(&id) != Some(&digest) {
+                changed.push(row);
+            }
+            digests.insert(id, digest);
+        }
+        // rows leaving the window are forgotten
+        self.digests = digests;
+        changed
+            .chunks(batch_size)
+            .map(|rows| ExportBatch::from_rows(&self.table, &self.key, labels.clone(), rows))
+            .collect()
+    }
+}
+
+/// rows of a SELECT with their column labels
+pub(crate) async fn select_all_sql<G: GStore + GStoreMut>(
+    glue: &mut Glue<G>,
+    sql: &str,
+) -> Result<(Vec<String>, Vec<Vec<Value>>)> {
+    let payload = glue
+        .execute(sql)
+        .await
+        .with_context(|| format!("failed to execute {sql}"))?;
+    match payload.into_iter().next() {
+        Some(gluesql::prelude::Payload::Select { labels, rows }) => Ok((labels, rows)),
+        p => bail!("unexpected payload {p:?}"),
+    }
+}
+
+/// Postgres connection that is dropped on the first connection error and reopened on the next tick
+struct PostgresSink {
+    url: String,
+    timescale: bool,
+    client: Option<Client>,
+}
+impl PostgresSink {
+    async fn connect(&mut self) -> Option<&Client> {
+        if self.client.as_ref().is_some_and(|x| x.is_closed()) {
+            self.client = None;
+        }
+        if self.client.is_none() {
+            match tokio_postgres::connect(&self.url, NoTls).await {
+                Ok((client, connection)) => {
+                    tokio::spawn(async move {
+                        if let Err(err) = connection.await {
+                            warn!("postgres connection closed: {err}");
+                        }
+                    });
+                    info!("connected to postgres for export");
+                    self.client = Some(client);
+                }
+                Err(err) => warn!("failed to connect to postgres, exports are queued: {err}"),
+            }
+        }
+        self.client.as_ref()
+    }
+    /// Ok(false) when the connection is lost and the batch has to wait
+    async fn upsert(&mut self, batch: &ExportBatch) -> Result<bool> {
+        let Some(client) = &self.client else {
+            return Ok(false);
+        };
+        match client.batch_execute(&batch.upsert_sql(self.timescale)?).await {
+            Ok(()) => Ok(true),
+            Err(err) if err.is_closed() || client.is_closed() => {
+                warn!("postgres connection lost, exports are queued: {err}");
+                self.client = None;
+                Ok(false)
+            }
+            Err(err) => Err(err).with_context(|| format!("postgres rejected a batch of {}", batch.table)),
+        }
+    }
+}
+
+/// streams the trading tables into Postgres: new signal and event rows by id, orders and ledgers when
+/// they change. batches that can not be delivered wait in an on-disk queue and are replayed in order
+pub struct PostgresExporter {
+    config: PostgresExportConfig,
+    sink: PostgresSink,
+    queue: ExportQueue,
+    /// cursors of the persistent tables, the volatile ones start empty after a restart anyway
+    cursor_file: PathBuf,
+    persistent: Vec<ExportSource<SharedSledStorage>>,
+    volatile: Vec<ExportSource<SharedMemoryStorage>>,
+    created: HashSet<String>,
+}
+impl PostgresExporter {
+    pub fn new(
+        config: PostgresExportConfig,
+        queue_directory: PathBuf,
+        mut persistent: Vec<ExportSource<SharedSledStorage>>,
+        volatile: Vec<ExportSource<SharedMemoryStorage>>,
+    ) -> Result<Self> {
+        let Some(url) = config.url.clone() else {
+            bail!("postgres export has no url");
+        };
+        let cursor_file = queue_directory.join("cursors.json");
+        if cursor_file.exists() {
+            let content = std::fs::read(&cursor_file)?;
+            let cursors: HashMap<String, u64> = serde_json::from_slice(&content)
+                .with_context(|| format!("failed to parse {}", cursor_file.display()))?;
+            for source in persistent.iter_mut() {
+                if let Some(&cursor) = cursors.get(&source.table) {
+                    source.cursor = Some(Value::U64(cursor));
+                }
+            }
+        }
+        Ok(Self {
+            sink: PostgresSink {
+                url,
+                timescale: config.timescale,
+                client: None,
+            },
+            queue: ExportQueue::new(queue_directory)?,
+            config,
+            cursor_file,
+            persistent,
+            volatile,
+            created: HashSet::new(),
+        })
+    }
+    fn save_cursors(&self) -> Result<()> {
+        let cursors: HashMap<&str, u64> = self
+            .persistent
+            .iter()
+            .filter_map(|x| match x.cursor {
+                Some(Value::U64(cursor)) => Some((x.table.as_str(), cursor)),
+                _ => None,
+            })
+            .collect();
+        let temporary = self.cursor_file.with_extension("tmp");
+        std::fs::write(&temporary, serde_json::to_vec(&cursors)?)?;
+        std::fs::rename(&temporary, &self.cursor_file)?;
+        Ok(())
+    }
+    async fn create_targets(&mut self) -> Result<()> {
+        let Some(client) = self.sink.connect().await else {
+            return Ok(());
+        };
+        for source in self.persistent.iter_mut() {
+            if !self.created.contains(&source.table) && source.create_target(client, self.config.timescale).await? {
+                self.created.insert(source.table.clone());
+            }
+        }
+        for source in self.volatile.iter_mut() {
+            if !self.created.contains(&source.table) && source.create_target(client, self.config.timescale).await? {
+                self.created.insert(source.table.clone());
+            }
+        }
+        Ok(())
+    }
+    /// replays the queue oldest first, Ok(false) when Postgres went away meanwhile
+    async fn replay(&mut self) -> Result<bool> {
+        for path in self.queue.pending()? {
+            let Some(batch) = self.queue.read(&path)? else {
+                continue;
+            };
+            match self.sink.upsert(&batch).await {
+                Ok(true) => self.queue.remove(&path)?,
+                Ok(false) => return Ok(false),
+                Err(err) => {
+                    warn!("{err:?}");
+                    self.queue.reject(&path)?;
+                }
+            }
+        }
+        Ok(true)
+    }
+    /// upserts the batch, or queues it while Postgres is unavailable or older batches are waiting.
+    /// returns whether the following batches can still be sent directly
+    async fn deliver(&mut self, batch: ExportBatch, in_order: bool) -> Result<bool> {
+        if in_order {
+            match self.sink.upsert(&batch).await {
+                Ok(true) => return Ok(true),
+                Ok(false) => {}
+                Err(err) => {
+                    warn!("{err:?}");
+                    let path = self.queue.push(&batch)?;
+                    self.queue.reject(&path)?;
+                    return Ok(true);
+                }
+            }
+        }
+        self.queue.push(&batch)?;
+        Ok(false)
+    }
+    async fn export<G: GStore + GStoreMut + Clone>(
+        &mut self,
+        sources: &mut [ExportSource<G>],
+        now: TimeStampMs,
+        mut in_order: bool,
+    ) -> Result<bool> {
+        for source in sources.iter_mut() {
+            let batches = match source.poll(now, &self.config).await {
+                Ok(batches) => batches,
+                Err(err) => {
+                    warn!("failed to read {} for export: {err:?}", source.table);
+                    continue;
+                }
+            };
+            for batch in batches {
+                in_order = self.deliver(batch, in_order).await?;
+            }
+        }
+        Ok(in_order)
+    }
+    async fn tick(&mut self) -> Result<()> {
+        if let Err(err) = self.create_targets().await {
+            warn!("{err:?}");
+        }
+        let in_order = self.replay().await?;
+        let now = get_time_milliseconds();
+        let mut persistent = std::mem::take(&mut self.persistent);
+        let in_order = self.export(&mut persistent, now, in_order).await;
+        self.persistent = persistent;
+        let mut volatile = std::mem::take(&mut self.volatile);
+        let in_order = match in_order {
+            Ok(in_order) => self.export(&mut volatile, now, in_order).await,
+            Err(err) => Err(err),
+        };
+        self.volatile = volatile;
+        in_order?;
+        self.save_cursors()
+    }
+    pub async fn run(mut self) -> Result<()> {
+        let mut interval = interval(self.config.interval_ms);
+        loop {
+            interval.tick().await;
+            if get_terminate_flag() {
+                return Ok(());
+            }
+            if let Err(err) = self.tick().await {
+                warn!("postgres export failed: {err:?}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::gluesql::schema::DbRowOrder;
+    use lib::gluesql::TableCreate;
+
+    /// runs against the Postgres of `POSTGRES_TEST_URL`, e.g. `host=localhost user=postgres password=postgres`
+    #[tokio::test]
+    async fn test_postgres_export() -> Result<()> {
+        let Ok(url) = std::env::var("POSTGRES_TEST_URL") else {
+            return Ok(());
+        };
+        let storage = SharedMemoryStorage::new();
+        let mut table: Table<SharedMemoryStorage, DbRowOrder> = Table::new("export_test_order", storage);
+        table.create_table().await?;
+        let now = get_time_milliseconds();
+        for id in 1..=3 {
+            table
+                .insert(DbRowOrder {
+                    id,
+                    datetime: now,
+                    client_id: format!("cloid-{id}"),
+                    ..Default::default()
+                })
+                .await?;
+        }
+        let (client, connection) = tokio_postgres::connect(&url, NoTls).await?;
+        tokio::spawn(connection);
+        client.batch_execute("DROP TABLE IF EXISTS export_test_order").await?;
+
+        let directory = tempfile::tempdir()?;
+        let config = PostgresExportConfig {
+            url: Some(url),
+            ..Default::default()
+        };
+        let source = ExportSource::mutable(table.clone());
+        let mut exporter = PostgresExporter::new(config, directory.path().to_path_buf(), vec![], vec![source])?;
+        exporter.tick().await?;
+        // unchanged rows are not sent again, an update is
+        table
+            .execute("UPDATE export_test_order SET status_id = 3 WHERE id = 2")
+            .await?;
+        exporter.tick().await?;
+        assert!(exporter.queue.is_empty()?);
+
+        let rows = client
+            .query("SELECT id::TEXT, status_id FROM export_test_order ORDER BY id", &[])
+            .await?;
+        let rows: Vec<(String, i16)> = rows.iter().map(|x| (x.get(0), x.get(1))).collect();
+        assert_eq!(rows, vec![("1".into(), 0), ("2".into(), 3), ("3".into(), 0)]);
+        client.batch_execute("DROP TABLE export_test_order").await?;
+        Ok(())
+    }
+}
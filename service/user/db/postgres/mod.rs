@@ -0,0 +1,189 @@
+use std::fmt::Write as _;
+
+use eyre::{bail, Result};
+use gluesql::core::ast::{ColumnDef, DataType};
+use gluesql::prelude::Value;
+use serde::{Deserialize, Serialize};
+
+/// tails the GlueSQL tables and upserts them into Postgres
+pub mod exporter;
+/// batches waiting for Postgres on disk
+pub mod queue;
+
+/// column value on its way to Postgres, serializable for the queue
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ExportValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    /// integers beyond i64, e.g. u64 ids and hashes
+    Numeric(String),
+    Float(f64),
+    Text(String),
+    Bytes(Vec<u8>),
+}
+impl TryFrom<&Value> for ExportValue {
+    type Error = eyre::Error;
+
+    fn try_from(value: &Value) -> Result<Self> {
+        Ok(match value {
+            Value::Null => Self::Null,
+            Value::Bool(x) => Self::Bool(*x),
+            Value::I8(x) => Self::Int(*x as _),
+            Value::I16(x) => Self::Int(*x as _),
+            Value::I32(x) => Self::Int(*x as _),
+            Value::I64(x) => Self::Int(*x),
+            Value::I128(x) => Self::Numeric(x.to_string()),
+            Value::U8(x) => Self::Int(*x as _),
+            Value::U16(x) => Self::Int(*x as _),
+            Value::U32(x) => Self::Int(*x as _),
+            Value::U64(x) => Self::Numeric(x.to_string()),
+            Value::U128(x) => Self::Numeric(x.to_string()),
+            // NaN and infinity do not survive the JSON queue
+            Value::F32(x) if x.is_finite() => Self::Float(*x as _),
+            Value::F64(x) if x.is_finite() => Self::Float(*x),
+            Value::F32(_) | Value::F64(_) => Self::Null,
+            Value::Decimal(x) => Self::Numeric(x.to_string()),
+            Value::Str(x) => Self::Text(x.clone()),
+            Value::Bytea(x) => Self::Bytes(x.clone()),
+            value => bail!("value {value:?} is not exported"),
+        })
+    }
+}
+impl ExportValue {
+    /// Postgres literal, standard_conforming_strings is assumed
+    pub fn to_sql_literal(&self) -> String {
+        match self {
+            Self::Null => "NULL".to_string(),
+            Self::Bool(x) => x.to_string().to_uppercase(),
+            Self::Int(x) => x.to_string(),
+            Self::Numeric(x) => x.clone(),
+            Self::Float(x) => format!("{x:?}"),
+            Self::Text(x) => format!("'{}'", x.replace('\'', "''")),
+            Self::Bytes(x) => format!("'\\x{}'::bytea", hex::encode(x)),
+        }
+    }
+}
+
+/// rows of one table exported together
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportBatch {
+    pub table: String,
+    /// columns making a row unique
+    pub key: Vec<String>,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<ExportValue>>,
+}
+impl ExportBatch {
+    pub fn from_rows(table: &str, key: &[&str], columns: Vec<String>, rows: &[Vec<Value>]) -> Result<Self> {
+        let rows = rows
+            .iter()
+            .map(|row| row.iter().map(ExportValue::try_from).collect::<Result<Vec<_>>>())
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            table: table.to_string(),
+            key: key.iter().map(|x| x.to_string()).collect(),
+            columns,
+            rows,
+        })
+    }
+    /// one INSERT updating the rows that were exported before, so replaying a batch is harmless
+    pub fn upsert_sql(&self, timescale: bool) -> Result<String> {
+        let key = unique_key(&self.key, &self.columns, timescale);
+        let mut sql = format!("INSERT INTO {} ({}) VALUES ", self.table, self.columns.join(", "));
+        for (i, row) in self.rows.iter().enumerate() {
+            if i > 0 {
+                sql.push_str(", ");
+            }
+            let values: Vec<String> = row.iter().map(|x| x.to_sql_literal()).collect();
+            write!(sql, "({})", values.join(", "))?;
+        }
+        let updates: Vec<String> = self
+            .columns
+            .iter()
+            .filter(|x| !key.contains(&x.as_str()))
+            .map(|x| format!("{x} = EXCLUDED.{x}"))
+            .collect();
+        if updates.is_empty() {
+            write!(sql, " ON CONFLICT ({}) DO NOTHING", key.join(", "))?;
+        } else {
+            write!(
+                sql,
+                " ON CONFLICT ({}) DO UPDATE SET {}",
+                key.join(", "),
+                updates.join(", ")
+            )?;
+        }
+        Ok(sql)
+    }
+}
+
+/// hypertables need the partition column in every unique index
+fn unique_key<'a>(key: &'a [String], columns: &[String], timescale: bool) -> Vec<&'a str> {
+    let mut key: Vec<&str> = key.iter().map(|x| x.as_str()).collect();
+    if timescale && !key.contains(&"datetime") && columns.iter().any(|x| x == "datetime") {
+        key.push("datetime");
+    }
+    key
+}
+
+fn postgres_type(data_type: &DataType) -> &'static str {
+    match data_type {
+        DataType::Boolean => "BOOLEAN",
+        DataType::Int8 | DataType::Int16 | DataType::Uint8 => "SMALLINT",
+        DataType::Int32 | DataType::Uint16 => "INTEGER",
+        DataType::Int | DataType::Uint32 => "BIGINT",
+        DataType::Int128 | DataType::Uint64 | DataType::Uint128 | DataType::Decimal => "NUMERIC",
+        DataType::Float32 => "REAL",
+        DataType::Float => "DOUBLE PRECISION",
+        DataType::Bytea => "BYTEA",
+        _ => "TEXT",
+    }
+}
+
+/// Postgres DDL mirroring the GlueSQL columns of a table, with the hypertable when `timescale` is set
+pub fn create_table_sql(table: &str, key: &[String], column_defs: &[ColumnDef], timescale: bool) -> String {
+    let columns: Vec<String> = column_defs.iter().map(|x| x.name.clone()).collect();
+    let key = unique_key(key, &columns, timescale).join(", ");
+    let mut sql = format!("CREATE TABLE IF NOT EXISTS {table} (");
+    for column in column_defs {
+        let null = if column.nullable { "NULL" } else { "NOT NULL" };
+        sql += &format!("{} {} {}, ", column.name, postgres_type(&column.data_type), null);
+    }
+    sql += &format!("PRIMARY KEY ({key}));");
+    if timescale && columns.iter().any(|x| x == "datetime") {
+        // datetime is in ms
+        sql += &format!(
+            "SELECT create_hypertable('{table}', 'datetime', chunk_time_interval => 86400000, \
+             if_not_exists => TRUE, migrate_data => TRUE);"
+        );
+    }
+    sql
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upsert_sql() -> Result<()> {
+        let batch = ExportBatch::from_rows(
+            "ledger_1",
+            &["id"],
+            vec!["id".to_string(), "datetime".to_string(), "cloid".to_string()],
+            &[
+                vec![Value::U64(u64::MAX), Value::I64(1), Value::Str("a'b".to_string())],
+                vec![Value::U64(2), Value::I64(2), Value::Null],
+            ],
+        )?;
+        assert_eq!(
+            batch.upsert_sql(false)?,
+            "INSERT INTO ledger_1 (id, datetime, cloid) VALUES (18446744073709551615, 1, 'a''b'), (2, 2, NULL) \
+             ON CONFLICT (id) DO UPDATE SET datetime = EXCLUDED.datetime, cloid = EXCLUDED.cloid"
+        );
+        assert!(batch
+            .upsert_sql(true)?
+            .ends_with("ON CONFLICT (id, datetime) DO UPDATE SET cloid = EXCLUDED.cloid"));
+        Ok(())
+    }
+}
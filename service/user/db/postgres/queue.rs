@@ -0,0 +1,126 @@
+use std::path::{Path, PathBuf};
+
+use eyre::{ensure, Context, Result};
+use tracing::warn;
+
+use crate::db::postgres::ExportBatch;
+
+/// the table of a batch ends up in its file name, `-` separates it from the sequence
+fn validate_table_name(table: &str) -> Result<()> {
+    ensure!(
+        table.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && table.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'),
+        "export table {table:?} must be an identifier of alphanumerics and '_'"
+    );
+    Ok(())
+}
+
+/// batches Postgres did not accept yet, one JSON file per batch, replayed oldest first.
+/// survives restarts, the upserts make a replayed batch harmless
+pub struct ExportQueue {
+    directory: PathBuf,
+    sequence: u64,
+}
+impl ExportQueue {
+    pub fn new(directory: PathBuf) -> Result<Self> {
+        std::fs::create_dir_all(&directory)
+            .with_context(|| format!("failed to create export queue {}", directory.display()))?;
+        let mut queue = Self { directory, sequence: 0 };
+        // continue after the batches left by the previous run
+        if let Some(last) = queue.pending()?.last() {
+            queue.sequence = Self::sequence_of(last).unwrap_or_default();
+        }
+        Ok(queue)
+    }
+    fn sequence_of(path: &Path) -> Option<u64> {
+        path.file_name()?.to_str()?.split('-').next()?.parse().ok()
+    }
+    /// queued files, oldest first
+    pub fn pending(&self) -> Result<Vec<PathBuf>> {
+        let mut files = vec![];
+        for entry in std::fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|x| x == "json") && Self::sequence_of(&path).is_some() {
+                files.push(path);
+            }
+        }
+        // zero padded sequence sorts in order
+        files.sort();
+        Ok(files)
+    }
+    pub fn len(&self) -> Result<usize> {
+        Ok(self.pending()?.len())
+    }
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+    pub fn push(&mut self, batch: &ExportBatch) -> Result<PathBuf> {
+        validate_table_name(&batch.table)?;
+        self.sequence += 1;
+        let path = self
+            .directory
+            .join(format!("{:020}-{}.json", self.sequence, batch.table));
+        // written aside and renamed, a crash never leaves half a batch in the queue
+        let temporary = path.with_extension("tmp");
+        std::fs::write(&temporary, serde_json::to_vec(batch)?)
+            .with_context(|| format!("failed to write {}", temporary.display()))?;
+        std::fs::rename(&temporary, &path).with_context(|| format!("failed to queue {}", path.display()))?;
+        Ok(path)
+    }
+    /// a batch Postgres refuses would block the queue forever, it is set aside for inspection instead
+    pub fn reject(&self, path: &Path) -> Result<()> {
+        let rejected = path.with_extension("rejected");
+        warn!("export batch set aside as {}", rejected.display());
+        std::fs::rename(path, &rejected).with_context(|| format!("failed to set aside {}", path.display()))
+    }
+    pub fn read(&self, path: &Path) -> Result<Option<ExportBatch>> {
+        let content = std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+        match serde_json::from_slice(&content) {
+            Ok(batch) => Ok(Some(batch)),
+            Err(err) => {
+                warn!("dropping unreadable export batch {}: {err}", path.display());
+                self.remove(path)?;
+                Ok(None)
+            }
+        }
+    }
+    pub fn remove(&self, path: &Path) -> Result<()> {
+        std::fs::remove_file(path).with_context(|| format!("failed to remove {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::postgres::ExportValue;
+
+    #[test]
+    fn test_export_queue() -> Result<()> {
+        let directory = tempfile::tempdir()?;
+        let batch = |id| ExportBatch {
+            table: "order_1".to_string(),
+            key: vec!["id".to_string()],
+            columns: vec!["id".to_string()],
+            rows: vec![vec![ExportValue::Int(id)]],
+        };
+        let mut queue = ExportQueue::new(directory.path().to_path_buf())?;
+        for id in 0..11 {
+            queue.push(&batch(id))?;
+        }
+        // reopened after a restart, new batches go after the old ones
+        let mut queue = ExportQueue::new(directory.path().to_path_buf())?;
+        queue.push(&batch(11))?;
+        let pending = queue.pending()?;
+        assert_eq!(pending.len(), 12);
+        for (id, path) in pending.iter().enumerate() {
+            assert_eq!(queue.read(path)?, Some(batch(id as _)));
+            queue.remove(path)?;
+        }
+        assert!(queue.is_empty()?);
+
+        for table in ["", "order-1", "../order_1", "1order"] {
+            assert!(validate_table_name(table).is_err(), "{table}");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,631 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use eyre::{bail, ensure, Context, ContextCompat, Result};
+use gluesql::core::store::{GStore, GStoreMut, Store};
+use gluesql::prelude::{Glue, Payload, Value};
+use gluesql::shared_memory_storage::SharedMemoryStorage;
+use gluesql_shared_sled_storage::SharedSledStorage;
+use lib::utils::get_time_milliseconds;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+use trading_exchange::utils::future::interval;
+use trading_model::{DurationMs, TimeStampMs, CANDLE_INTERVALS_MS};
+
+use crate::config::{DownsampleConfig, RetentionPolicyConfig};
+use crate::db::gluesql::backup::{insert_statements, sql_literal};
+use crate::db::gluesql::schema::common::TableName;
+use crate::metrics::{retention_deleted_bytes, retention_deleted_rows, retention_downsampled_rows};
+use crate::signals::candles::candle_retention_ms;
+use crate::signals::price_spread::{DbRowSignalBestBidAskAcrossExchanges, WorktableSignalBestBidAskAcrossExchanges};
+
+/// the price spread worktable is not an SQL table, policies address it by this name
+pub const PRICE_SPREAD_WORKTABLE: &str = "price_spread_worktable";
+/// policies without a `time_column` age the rows by this column, in milliseconds
+const TIME_COLUMN: &str = "datetime";
+/// rows sampled to estimate the size of the rows removed by age or by count
+const SIZE_SAMPLE_ROWS: usize = 100;
+/// buckets replaced by one DELETE statement when downsampling
+const DELETE_BUCKET_BATCH: usize = 100;
+
+fn time_column(policy: &RetentionPolicyConfig) -> &str {
+    policy.time_column.as_deref().unwrap_or(TIME_COLUMN)
+}
+/// condition selecting the rows of the policy
+fn scope(policy: &RetentionPolicyConfig) -> String {
+    match &policy.filter {
+        Some(filter) => format!("({filter})"),
+        None => "1 = 1".to_string(),
+    }
+}
+
+/// the tables that had their own limiter thread, kept for an hour, and the persistent market data tables
+pub fn default_policies(table_name: &TableName) -> Vec<RetentionPolicyConfig> {
+    let hour = 3_600_000;
+    let mut tables = vec![
+        table_name.spread.clone(),
+        table_name.signal_change.clone(),
+        table_name.event_price_spread_and_position.clone(),
+    ];
+    // common to strategy 0 and 1
+    tables.extend(
+        [0, 1]
+            .iter()
+            .filter_map(|id| table_name.signal_difference.get(id).cloned()),
+    );
+    // specific to strategy 1
+    tables.extend(table_name.event_price_change_and_diff.get(&1).cloned());
+    tables.extend(table_name.accuracy.get(&1).cloned());
+
+    let mut policies = vec![
+        RetentionPolicyConfig {
+            table: PRICE_SPREAD_WORKTABLE.to_string(),
+            max_age_ms: Some(hour),
+            max_rows: Some(100_000),
+            ..Default::default()
+        },
+        // persistent, liquidations are kept for a month
+        RetentionPolicyConfig {
+            table: table_name.liquidation.clone(),
+            max_age_ms: Some(30 * 24 * hour),
+            ..Default::default()
+        },
+        // persistent, settled rates and the funding attributed to the ledger are kept for a year
+        RetentionPolicyConfig {
+            table: table_name.funding_history.clone(),
+            max_age_ms: Some(365 * 24 * hour),
+            ..Default::default()
+        },
+        RetentionPolicyConfig {
+            table: table_name.funding_accrual.clone(),
+            max_age_ms: Some(365 * 24 * hour),
+            ..Default::default()
+        },
+        // latest rate per instrument, only delisted instruments stop being updated
+        RetentionPolicyConfig {
+            table: table_name.funding_rate.clone(),
+            time_column: Some("timestamp".to_string()),
+            max_age_ms: Some(24 * hour),
+            ..Default::default()
+        },
+    ];
+    // persistent, candles are kept longer the wider their interval
+    policies.extend(
+        CANDLE_INTERVALS_MS
+            .into_iter()
+            .map(|interval_ms| RetentionPolicyConfig {
+                table: table_name.candle.clone(),
+                filter: Some(format!("interval_ms = {interval_ms}")),
+                max_age_ms: Some(candle_retention_ms(interval_ms)),
+                ..Default::default()
+            }),
+    );
+    policies.extend(tables.into_iter().map(|table| RetentionPolicyConfig {
+        table,
+        max_age_ms: Some(hour),
+        ..Default::default()
+    }));
+    policies
+}
+
+/// configured policies replace the default policy of the same table and filter
+pub fn merge_policies(
+    mut policies: Vec<RetentionPolicyConfig>,
+    configured: &[RetentionPolicyConfig],
+) -> Vec<RetentionPolicyConfig> {
+    for policy in configured {
+        policies.retain(|x| x.table != policy.table || x.filter != policy.filter);
+        policies.push(policy.clone());
+    }
+    policies
+}
+
+/// what a single pass reclaimed from a table
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RetentionReport {
+    pub table: String,
+    /// removed by age, by count and by downsampling
+    pub deleted_rows: usize,
+    /// approximate size of the removed rows, see `value_size`. estimated from a sample of the rows removed
+    /// by age or by count
+    pub deleted_bytes: usize,
+    /// aggregated rows written to the downsampled table
+    pub downsampled_rows: usize,
+}
+impl RetentionReport {
+    pub fn is_empty(&self) -> bool {
+        self.deleted_rows == 0 && self.downsampled_rows == 0
+    }
+    fn record(&self) {
+        if self.is_empty() {
+            return;
+        }
+        retention_deleted_rows(&self.table).inc_by(self.deleted_rows as _);
+        retention_deleted_bytes(&self.table).inc_by(self.deleted_bytes as _);
+        retention_downsampled_rows(&self.table).inc_by(self.downsampled_rows as _);
+        info!(
+            "retention of {}: {} rows removed ({} bytes), {} rows downsampled",
+            self.table, self.deleted_rows, self.deleted_bytes, self.downsampled_rows
+        );
+    }
+}
+
+/// in-memory size of a value without the enum overhead, strings and bytes by their length
+fn value_size(value: &Value) -> usize {
+    match value {
+        Value::Null => 0,
+        Value::Bool(_) | Value::I8(_) | Value::U8(_) => 1,
+        Value::I16(_) | Value::U16(_) => 2,
+        Value::I32(_) | Value::U32(_) | Value::F32(_) => 4,
+        Value::I64(_) | Value::U64(_) | Value::F64(_) => 8,
+        Value::I128(_) | Value::U128(_) | Value::Decimal(_) => 16,
+        Value::Str(x) => x.len(),
+        Value::Bytea(x) => x.len(),
+        value => std::mem::size_of_val(value),
+    }
+}
+fn rows_size(rows: &[Vec<Value>]) -> usize {
+    rows.iter().flatten().map(value_size).sum()
+}
+
+fn value_i64(value: &Value) -> Option<i64> {
+    match *value {
+        Value::I8(x) => Some(x as _),
+        Value::I16(x) => Some(x as _),
+        Value::I32(x) => Some(x as _),
+        Value::I64(x) => Some(x),
+        Value::U8(x) => Some(x as _),
+        Value::U16(x) => Some(x as _),
+        Value::U32(x) => Some(x as _),
+        Value::U64(x) => Some(x as _),
+        _ => None,
+    }
+}
+
+fn column_index(labels: &[String], column: &str) -> Result<usize> {
+    labels
+        .iter()
+        .position(|x| x == column)
+        .with_context(|| format!("column {column} not found in {labels:?}"))
+}
+
+async fn execute<G: GStore + GStoreMut>(glue: &mut Glue<G>, sql: &str) -> Result<Payload> {
+    let payload = glue
+        .execute(sql)
+        .await
+        .with_context(|| format!("failed to execute {sql}"))?;
+    payload.into_iter().next().context("no payload")
+}
+async fn select<G: GStore + GStoreMut>(glue: &mut Glue<G>, sql: &str) -> Result<(Vec<String>, Vec<Vec<Value>>)> {
+    match execute(glue, sql).await? {
+        Payload::Select { labels, rows } => Ok((labels, rows)),
+        p => bail!("unexpected payload {p:?}"),
+    }
+}
+/// mean size of the rows of the policy matching `condition`, from a sample of them. None when there are none
+async fn sample_row_size<G: GStore + GStoreMut>(
+    glue: &mut Glue<G>,
+    policy: &RetentionPolicyConfig,
+    condition: &str,
+) -> Result<Option<f64>> {
+    let (table, scope) = (&policy.table, scope(policy));
+    let sql = format!("SELECT * FROM {table} WHERE {scope} AND {condition} LIMIT {SIZE_SAMPLE_ROWS}");
+    let (_, rows) = select(glue, &sql).await?;
+    if rows.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(rows_size(&rows) as f64 / rows.len() as f64))
+}
+/// deletes the rows of the policy up to `datetime`, inclusive when `inclusive`
+async fn delete_until<G: GStore + GStoreMut>(
+    glue: &mut Glue<G>,
+    policy: &RetentionPolicyConfig,
+    datetime: TimeStampMs,
+    inclusive: bool,
+) -> Result<usize> {
+    let (table, time_column, scope) = (&policy.table, time_column(policy), scope(policy));
+    let op = if inclusive { "<=" } else { "<" };
+    match execute(
+        glue,
+        &format!("DELETE FROM {table} WHERE {scope} AND {time_column} {op} {datetime}"),
+    )
+    .await?
+    {
+        Payload::Delete(count) => Ok(count),
+        p => bail!("unexpected payload {p:?}"),
+    }
+}
+
+/// running aggregate of a bucket: floats are averaged, every other column keeps its latest value
+struct Bucket {
+    sums: Vec<f64>,
+    counts: Vec<usize>,
+    last: Vec<Value>,
+}
+impl Bucket {
+    fn new(columns: usize) -> Self {
+        Self {
+            sums: vec![0.0; columns],
+            counts: vec![0; columns],
+            last: vec![],
+        }
+    }
+    fn add(&mut self, row: &[Value]) {
+        for (i, value) in row.iter().enumerate() {
+            let x = match *value {
+                Value::F64(x) => x,
+                Value::F32(x) => x as f64,
+                _ => continue,
+            };
+            self.sums[i] += x;
+            self.counts[i] += 1;
+        }
+        self.last = row.to_vec();
+    }
+    fn finish(self, time_column: usize, start: TimeStampMs) -> Vec<Value> {
+        let mut row = self.last;
+        for (i, value) in row.iter_mut().enumerate() {
+            if self.counts[i] == 0 {
+                continue;
+            }
+            let mean = self.sums[i] / self.counts[i] as f64;
+            match value {
+                Value::F64(x) => *x = mean,
+                Value::F32(x) => *x = mean as f32,
+                _ => {}
+            }
+        }
+        row[time_column] = Value::I64(start);
+        row
+    }
+}
+
+/// aggregates the rows into one row per bucket and `group_by` key, stamped with the start of the bucket
+fn downsample_rows(
+    labels: &[String],
+    rows: &[Vec<Value>],
+    time_column: &str,
+    rule: &DownsampleConfig,
+) -> Result<Vec<Vec<Value>>> {
+    ensure!(rule.bucket_ms > 0, "bucket_ms must be positive");
+    let time_column = column_index(labels, time_column)?;
+    let keys = rule
+        .group_by
+        .iter()
+        .map(|column| column_index(labels, column))
+        .collect::<Result<Vec<_>>>()?;
+    let mut buckets: BTreeMap<(TimeStampMs, Vec<String>), Bucket> = BTreeMap::new();
+    for row in rows {
+        let datetime =
+            value_i64(&row[time_column]).with_context(|| format!("invalid {} in {row:?}", labels[time_column]))?;
+        let start = datetime.div_euclid(rule.bucket_ms) * rule.bucket_ms;
+        let key = keys.iter().map(|&i| sql_literal(&row[i])).collect::<Result<Vec<_>>>()?;
+        buckets
+            .entry((start, key))
+            .or_insert_with(|| Bucket::new(row.len()))
+            .add(row);
+    }
+    Ok(buckets
+        .into_iter()
+        .map(|((start, _), bucket)| bucket.finish(time_column, start))
+        .collect())
+}
+
+/// DELETE statements of the rows already written for the buckets, matched on the bucket start and `group_by`
+fn delete_bucket_statements(
+    into: &str,
+    labels: &[String],
+    time_column: &str,
+    group_by: &[String],
+    buckets: &[Vec<Value>],
+) -> Result<Vec<String>> {
+    let columns = std::iter::once(time_column)
+        .chain(group_by.iter().map(String::as_str))
+        .map(|column| Ok((column, column_index(labels, column)?)))
+        .collect::<Result<Vec<_>>>()?;
+    let mut statements = vec![];
+    for chunk in buckets.chunks(DELETE_BUCKET_BATCH) {
+        let keys = chunk
+            .iter()
+            .map(|row| {
+                let conditions = columns
+                    .iter()
+                    .map(|&(column, i)| match &row[i] {
+                        Value::Null => Ok(format!("{column} IS NULL")),
+                        value => Ok(format!("{column} = {}", sql_literal(value)?)),
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(format!("({})", conditions.join(" AND ")))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        statements.push(format!("DELETE FROM {into} WHERE {}", keys.join(" OR ")));
+    }
+    Ok(statements)
+}
+/// writes the buckets into the downsampled table, replacing the rows of the same bucket and key.
+/// a pass stopped between the upsert and the delete of the source rows writes the same buckets again
+async fn upsert_buckets<G: GStore + GStoreMut>(
+    glue: &mut Glue<G>,
+    into: &str,
+    labels: &[String],
+    time_column: &str,
+    rule: &DownsampleConfig,
+    buckets: &[Vec<Value>],
+) -> Result<()> {
+    for statement in delete_bucket_statements(into, labels, time_column, &rule.group_by, buckets)? {
+        execute(glue, &statement).await?;
+    }
+    for statement in insert_statements(into, labels, buckets)? {
+        execute(glue, &statement).await?;
+    }
+    Ok(())
+}
+
+/// creates the downsampled table with the columns of the source table
+async fn ensure_downsample_table<G: GStore + GStoreMut>(glue: &mut Glue<G>, source: &str, into: &str) -> Result<()> {
+    if glue.storage.fetch_schema(into).await?.is_some() {
+        return Ok(());
+    }
+    let mut schema = glue
+        .storage
+        .fetch_schema(source)
+        .await?
+        .with_context(|| format!("table {source} not found"))?;
+    schema.table_name = into.to_string();
+    schema.indexes.clear();
+    execute(glue, &schema.to_ddl()).await?;
+    info!("created downsampled table {into} from {source}");
+    Ok(())
+}
+
+/// applies the policy once: downsampling first, then the age and the row limits.
+/// rows sharing the timestamp of the last row over `max_rows` are removed with it
+pub async fn enforce_policy<G: GStore + GStoreMut>(
+    glue: &mut Glue<G>,
+    policy: &RetentionPolicyConfig,
+    now_ms: TimeStampMs,
+) -> Result<RetentionReport> {
+    let table = policy.table.as_str();
+    let (time_column, scope) = (time_column(policy), scope(policy));
+    let mut report = RetentionReport {
+        table: table.to_string(),
+        ..Default::default()
+    };
+    if let Some(rule) = &policy.downsample {
+        ensure!(rule.bucket_ms > 0, "bucket_ms of {table} must be positive");
+        // only whole buckets, rows arriving later would replace the row of their bucket
+        let until = (now_ms - rule.after_ms).div_euclid(rule.bucket_ms) * rule.bucket_ms;
+        let sql = format!("SELECT * FROM {table} WHERE {scope} AND {time_column} < {until} ORDER BY {time_column}");
+        let (labels, rows) = select(glue, &sql).await?;
+        if !rows.is_empty() {
+            let buckets = downsample_rows(&labels, &rows, time_column, rule)?;
+            ensure_downsample_table(glue, table, &rule.into).await?;
+            upsert_buckets(glue, &rule.into, &labels, time_column, rule, &buckets).await?;
+            report.downsampled_rows += buckets.len();
+            report.deleted_bytes += rows_size(&rows);
+            report.deleted_rows += delete_until(glue, policy, until, false).await?;
+        }
+    }
+    if let Some(max_age_ms) = policy.max_age_ms {
+        let until = now_ms - max_age_ms;
+        if let Some(row_size) = sample_row_size(glue, policy, &format!("{time_column} < {until}")).await? {
+            let deleted = delete_until(glue, policy, until, false).await?;
+            report.deleted_bytes += (row_size * deleted as f64).round() as usize;
+            report.deleted_rows += deleted;
+        }
+    }
+    if let Some(max_rows) = policy.max_rows {
+        let (_, rows) = select(glue, &format!("SELECT COUNT(*) FROM {table} WHERE {scope}")).await?;
+        let count = rows
+            .first()
+            .and_then(|x| x.first())
+            .and_then(value_i64)
+            .unwrap_or_default() as usize;
+        if count > max_rows {
+            // timestamp of the last row over the limit
+            let sql = format!(
+                "SELECT {time_column} FROM {table} WHERE {scope} ORDER BY {time_column} LIMIT 1 OFFSET {}",
+                count - max_rows - 1
+            );
+            let (_, rows) = select(glue, &sql).await?;
+            if let Some(until) = rows.first().and_then(|x| x.first()).and_then(value_i64) {
+                let row_size = sample_row_size(glue, policy, &format!("{time_column} <= {until}")).await?;
+                let deleted = delete_until(glue, policy, until, true).await?;
+                report.deleted_bytes += (row_size.unwrap_or_default() * deleted as f64).round() as usize;
+                report.deleted_rows += deleted;
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// enforces every policy at the interval, over the volatile and the persistent storage
+pub struct RetentionScheduler {
+    pub volatile: SharedMemoryStorage,
+    pub persistent: SharedSledStorage,
+    pub price_spread_worktable: Arc<RwLock<WorktableSignalBestBidAskAcrossExchanges>>,
+    pub policies: Vec<RetentionPolicyConfig>,
+    pub interval_ms: DurationMs,
+}
+impl RetentionScheduler {
+    pub async fn run(self) -> Result<()> {
+        for policy in &self.policies {
+            if policy.table == PRICE_SPREAD_WORKTABLE && policy.downsample.is_some() {
+                warn!("{PRICE_SPREAD_WORKTABLE} can not be downsampled, the rule is ignored");
+            }
+            if policy.table == PRICE_SPREAD_WORKTABLE && policy.filter.is_some() {
+                warn!("{PRICE_SPREAD_WORKTABLE} can not be filtered, the filter is ignored");
+            }
+        }
+        let mut interval = interval(self.interval_ms);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    self.enforce().await;
+                },
+                _ = lib::signal::signal_received_silent() => return Ok(()),
+            }
+        }
+    }
+
+    /// one pass over every policy, tables missing from both storages are skipped
+    pub async fn enforce(&self) -> Vec<RetentionReport> {
+        let now_ms = get_time_milliseconds();
+        let mut reports = vec![];
+        for policy in &self.policies {
+            let result = if policy.table == PRICE_SPREAD_WORKTABLE {
+                Ok(self.enforce_worktable(policy, now_ms).await)
+            } else {
+                self.enforce_table(policy, now_ms).await
+            };
+            match result {
+                Ok(Some(report)) => {
+                    report.record();
+                    reports.push(report);
+                }
+                Ok(None) => {}
+                Err(err) => warn!("retention of {} failed: {err:?}", policy.table),
+            }
+        }
+        reports
+    }
+
+    async fn enforce_table(
+        &self,
+        policy: &RetentionPolicyConfig,
+        now_ms: TimeStampMs,
+    ) -> Result<Option<RetentionReport>> {
+        let mut volatile = Glue::new(self.volatile.clone());
+        if volatile.storage.fetch_schema(&policy.table).await?.is_some() {
+            return enforce_policy(&mut volatile, policy, now_ms).await.map(Some);
+        }
+        let mut persistent = Glue::new(self.persistent.clone());
+        if persistent.storage.fetch_schema(&policy.table).await?.is_some() {
+            return enforce_policy(&mut persistent, policy, now_ms).await.map(Some);
+        }
+        Ok(None)
+    }
+
+    async fn enforce_worktable(&self, policy: &RetentionPolicyConfig, now_ms: TimeStampMs) -> Option<RetentionReport> {
+        let until = policy
+            .max_age_ms
+            .map_or(TimeStampMs::MIN, |max_age_ms| now_ms - max_age_ms);
+        let count = self
+            .price_spread_worktable
+            .write()
+            .await
+            .remove_expired(until, policy.max_rows);
+        Some(RetentionReport {
+            table: policy.table.clone(),
+            deleted_rows: count,
+            deleted_bytes: count * std::mem::size_of::<DbRowSignalBestBidAskAcrossExchanges>(),
+            downsampled_rows: 0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::gluesql::backup::select_all;
+
+    #[tokio::test]
+    async fn test_enforce_policy() -> Result<()> {
+        let storage = SharedMemoryStorage::new();
+        let mut glue = Glue::new(storage.clone());
+        glue.execute("CREATE TABLE spread (id UINT64, asset UINT64, spread FLOAT, datetime INT)")
+            .await?;
+        // 1s rows of two assets over 3 minutes
+        let mut values = vec![];
+        for i in 0..180 {
+            values.push(format!("({}, 1, {}.0, {})", 2 * i, i, i * 1000));
+            values.push(format!("({}, 2, 1.0, {})", 2 * i + 1, i * 1000));
+        }
+        glue.execute(format!("INSERT INTO spread VALUES {}", values.join(", ")))
+            .await?;
+
+        let policy = RetentionPolicyConfig {
+            table: "spread".to_string(),
+            max_age_ms: None,
+            max_rows: Some(100),
+            downsample: Some(DownsampleConfig {
+                after_ms: 60_000,
+                bucket_ms: 60_000,
+                into: "spread_1m".to_string(),
+                group_by: vec!["asset".to_string()],
+            }),
+            ..Default::default()
+        };
+        // the first two minutes are downsampled, the row limit removes 10 more seconds
+        let report = enforce_policy(&mut glue, &policy, 180_500).await?;
+        assert_eq!(report.downsampled_rows, 4);
+        assert_eq!(report.deleted_rows, 240 + 20);
+        assert_eq!(report.deleted_bytes, 260 * 32);
+
+        let (_, rows) = select_all(&mut glue, "spread_1m").await?;
+        let mut rows = rows
+            .into_iter()
+            .map(|x| (value_i64(&x[3]).unwrap(), value_i64(&x[1]).unwrap(), x[2].clone()))
+            .collect::<Vec<_>>();
+        rows.sort_by_key(|x| (x.0, x.1));
+        assert_eq!(
+            rows,
+            vec![
+                (0, 1, Value::F64(29.5)),
+                (0, 2, Value::F64(1.0)),
+                (60_000, 1, Value::F64(89.5)),
+                (60_000, 2, Value::F64(1.0)),
+            ]
+        );
+        let (_, rows) = select_all(&mut glue, "spread").await?;
+        assert_eq!(rows.len(), 100);
+
+        let report = enforce_policy(&mut glue, &policy, 180_500).await?;
+        assert!(report.is_empty());
+
+        // a pass stopped after the upsert leaves the source rows of the first minute, written again they replace it
+        let values: Vec<String> = (0..60)
+            .map(|i| format!("({}, 1, {}.0, {})", 2 * i, i, i * 1000))
+            .collect();
+        glue.execute(format!("INSERT INTO spread VALUES {}", values.join(", ")))
+            .await?;
+        let report = enforce_policy(&mut glue, &policy, 180_500).await?;
+        assert_eq!(report.downsampled_rows, 1);
+        assert_eq!(report.deleted_rows, 60);
+        let (_, rows) = select_all(&mut glue, "spread_1m").await?;
+        assert_eq!(rows.len(), 4);
+        assert!(rows
+            .iter()
+            .any(|x| value_i64(&x[3]) == Some(0) && value_i64(&x[1]) == Some(1) && x[2] == Value::F64(29.5)));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_enforce_filtered_policy() -> Result<()> {
+        let mut glue = Glue::new(SharedMemoryStorage::new());
+        glue.execute("CREATE TABLE candle (interval_ms INT, close FLOAT, datetime INT)")
+            .await?;
+        glue.execute("INSERT INTO candle VALUES (1000, 1.0, 0), (1000, 1.0, 1000), (60000, 1.0, 0)")
+            .await?;
+        let policies = merge_policies(
+            default_policies(&TableName::new(&[])),
+            &[RetentionPolicyConfig {
+                table: "candle".to_string(),
+                filter: Some("interval_ms = 1000".to_string()),
+                max_age_ms: Some(500),
+                ..Default::default()
+            }],
+        );
+        let policy = policies
+            .iter()
+            .find(|x| x.filter.as_deref() == Some("interval_ms = 1000"))
+            .unwrap();
+        assert_eq!(policy.max_age_ms, Some(500));
+        // the 1m candle is as old but kept
+        let report = enforce_policy(&mut glue, policy, 1_000).await?;
+        assert_eq!(report.deleted_rows, 1);
+        let (_, rows) = select_all(&mut glue, "candle").await?;
+        assert_eq!(rows.len(), 2);
+        Ok(())
+    }
+}
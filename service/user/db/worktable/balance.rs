@@ -3,31 +3,36 @@ use std::ops::Deref;
 use eyre::{ContextCompat, Result};
 use worktable::{field, RowView, RowViewMut, Value, WorkTable};
 
+use trading_exchange::model::AccountId;
 use trading_model::Exchange;
 
 /// used by order placement to check if we have enough fund to generate an order
 /// if the fund is insufficient, we NSF the event
+/// every account of a venue has its own fund
 
 pub struct WorktableBalance {
     table: WorkTable,
 }
 field!(0, ExchangeCol: i64, "exchange_id");
 field!(1, AvailableFundCol: f64, "available_fund");
-// field!(2, ExpectedFund: f64, "expected_fund");
-// field!(3, UserIdCol: i64, "user_id");
+field!(2, AccountCol: i64, "account_id");
+// field!(3, ExpectedFund: f64, "expected_fund");
+// field!(4, UserIdCol: i64, "user_id");
 
 impl WorktableBalance {
     pub fn new() -> Self {
         let mut table = WorkTable::new();
         table.add_field(ExchangeCol);
         table.add_field(AvailableFundCol);
+        table.add_field(AccountCol);
         Self { table }
     }
-    pub fn insert(&mut self, exchange: Exchange, fund: f64) {
-        self.table.push([Value::Int(exchange as _), Value::Float(fund as _)]);
+    pub fn insert(&mut self, exchange: Exchange, account: AccountId, fund: f64) {
+        self.table
+            .push([Value::Int(exchange as _), Value::Float(fund as _), Value::Int(account)]);
     }
-    pub fn ensure(&mut self, exchange: Exchange) -> BalanceRowViewMut {
-        if let Some(row) = self.get_row_mut(exchange) {
+    pub fn ensure(&mut self, exchange: Exchange, account: AccountId) -> BalanceRowViewMut {
+        if let Some(row) = self.get_row_mut(exchange, account) {
             // SAFETY: rustc fool
             unsafe {
                 return std::mem::transmute(row);
@@ -37,49 +42,58 @@ impl WorktableBalance {
                 .insert()
                 .set(ExchangeCol, exchange as _)
                 .set(AvailableFundCol, 0.0)
+                .set(AccountCol, account)
                 .finish();
-            self.get_row_mut(exchange).unwrap()
+            self.get_row_mut(exchange, account).unwrap()
         }
     }
-    pub fn get_row_mut(&mut self, exchange: Exchange) -> Option<BalanceRowViewMut> {
+    pub fn get_row_mut(&mut self, exchange: Exchange, account: AccountId) -> Option<BalanceRowViewMut> {
         self.table
             .iter_mut()
             .map(BalanceRowViewMut)
-            .find(|x| x.exchange() == exchange)
+            .find(|x| x.exchange() == exchange && x.account() == account)
     }
-    pub fn get_row(&mut self, exchange: Exchange) -> Option<BalanceRowView> {
-        self.table.iter().map(BalanceRowView).find(|x| x.exchange() == exchange)
+    pub fn get_row(&mut self, exchange: Exchange, account: AccountId) -> Option<BalanceRowView> {
+        self.table
+            .iter()
+            .map(BalanceRowView)
+            .find(|x| x.exchange() == exchange && x.account() == account)
     }
 
-    pub fn get_fund(&mut self, exchange: Exchange) -> Option<f64> {
-        let fund = self.get_row(exchange)?.available_fund();
+    pub fn get_fund(&mut self, exchange: Exchange, account: AccountId) -> Option<f64> {
+        let fund = self.get_row(exchange, account)?.available_fund();
         Some(fund)
     }
 
-    pub fn add_fund(&mut self, exchange: Exchange, fund: f64) -> Result<f64> {
-        let current = self.get_fund(exchange).unwrap_or_default();
+    pub fn add_fund(&mut self, exchange: Exchange, account: AccountId, fund: f64) -> Result<f64> {
+        let current = self.get_fund(exchange, account).unwrap_or_default();
         let mut row = self
-            .find_by_exchange(exchange)
-            .with_context(|| format!("balance not initialized for exchange {}", exchange))?;
+            .find_by_account(exchange, account)
+            .with_context(|| format!("balance not initialized for exchange {} account {}", exchange, account))?;
         row.set(AvailableFundCol, current + fund);
         Ok(current + fund)
     }
 
-    pub fn deduct_fund(&mut self, exchange: Exchange, fund: f64) -> eyre::Result<f64> {
-        let current = self.get_fund(exchange).unwrap_or_default();
+    pub fn deduct_fund(&mut self, exchange: Exchange, account: AccountId, fund: f64) -> eyre::Result<f64> {
+        let current = self.get_fund(exchange, account).unwrap_or_default();
         if current < fund {
-            eyre::bail!("insufficient fund: {}, expected {}, got {}", exchange, fund, current)
+            eyre::bail!(
+                "insufficient fund: {} account {}, expected {}, got {}",
+                exchange,
+                account,
+                fund,
+                current
+            )
         }
-        let mut row = self.ensure(exchange);
+        let mut row = self.ensure(exchange, account);
         row.0.set(AvailableFundCol, current - fund);
         Ok(current - fund)
     }
 
-    /// get row with matching order IDs (either one match will return)
-    pub fn find_by_exchange(&mut self, exchange: Exchange) -> Option<RowViewMut> {
+    pub fn find_by_account(&mut self, exchange: Exchange, account: AccountId) -> Option<RowViewMut> {
         self.table
             .iter_mut()
-            .find(|row| *row.index(ExchangeCol) == exchange as i64)
+            .find(|row| *row.index(ExchangeCol) == exchange as i64 && *row.index(AccountCol) == account)
     }
 }
 pub struct BalanceRowView<'a>(RowView<'a>);
@@ -90,6 +104,9 @@ impl<'a> BalanceRowView<'a> {
     pub fn available_fund(&self) -> f64 {
         *self.0.index(AvailableFundCol)
     }
+    pub fn account(&self) -> AccountId {
+        *self.0.index(AccountCol)
+    }
 }
 
 pub struct BalanceRowViewMut<'a>(RowViewMut<'a>);
@@ -6,9 +6,7 @@ use std::collections::{HashSet, VecDeque};
 use std::fmt::Debug;
 use tracing::{info, warn};
 
-use crate::db::gluesql::schema::common::StrategyId;
 use crate::db::gluesql::schema::DbRowOrder;
-use crate::db::gluesql::StrategyTable;
 use trading_exchange::model::{gen_local_id, Order, OrderStatus, UpdateOrder};
 use trading_model::Time;
 use trading_model::{now, InstrumentCode, TimeStampNs, NANOSECONDS_PER_SECOND};
@@ -27,7 +25,7 @@ pub type SharedOrderManager = std::sync::Arc<tokio::sync::RwLock<OrderManager>>;
 pub struct OrderManager {
     pub orders: OrdersWorkTable,
     events: VecDeque<UpdateOrder>,
-    db_table: Option<StrategyTable<SharedSledStorage, DbRowOrder>>,
+    db_table: Option<Table<SharedSledStorage, DbRowOrder>>,
     last_clean_up: TimeStampNs,
 }
 impl Debug for OrderManager {
@@ -44,7 +42,7 @@ impl OrderManager {
             last_clean_up: now(),
         }
     }
-    pub fn set_db(&mut self, storage: StrategyTable<SharedSledStorage, DbRowOrder>) {
+    pub fn set_db(&mut self, storage: Table<SharedSledStorage, DbRowOrder>) {
         self.db_table = Some(storage);
     }
 
@@ -66,10 +64,8 @@ impl OrderManager {
                 // info!("inserted new order: {:?}", update);
                 self.events.push_back(update.clone());
 
-                if let Some(db_table) = self.db_table.as_ref() {
-                    if let Some(table) = db_table.get(&(update.strategy_id as StrategyId)) {
-                        Self::update_order_table_by_order(update.to_order(), table.clone()).await;
-                    }
+                if let Some(table) = self.db_table.as_ref() {
+                    Self::update_order_table_by_order(update.to_order(), table.clone()).await;
                 }
 
                 return;
@@ -128,11 +124,10 @@ impl OrderManager {
         update.ty = order.ty();
         update.strategy_id = order.strategy_id();
         update.opening_cloid = order.open_order_client_id();
+        update.account = order.account();
 
-        if let Some(db_table) = self.db_table.as_ref() {
-            if let Some(table) = db_table.get(&(update.strategy_id as StrategyId)) {
-                Self::update_order_table_by_order_view(order.clone(), table.clone()).await;
-            }
+        if let Some(table) = self.db_table.as_ref() {
+            Self::update_order_table_by_order_view(order.clone(), table.clone()).await;
         }
         // info!("updated order: {:?}", update);
         self.events.push_back(update);
@@ -201,6 +196,7 @@ impl OrderManager {
                     local_id: order.local_id().into(),
                     client_id: order.client_id().into(),
                     server_id: order.server_id().into(),
+                    account: order.account(),
                     size: order.size(),
                     price: order.price(),
                     status: OrderStatus::Expired,
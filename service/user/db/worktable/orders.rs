@@ -4,7 +4,7 @@ use std::fmt::Debug;
 use std::ops::Deref;
 use std::str::FromStr;
 use tracing::warn;
-use trading_exchange::model::{OrderStatus, OrderType, PositionEffect, RequestPlaceOrder, UpdateOrder};
+use trading_exchange::model::{AccountId, OrderStatus, OrderType, PositionEffect, RequestPlaceOrder, UpdateOrder};
 use trading_model::{now, Exchange, InstrumentSymbol, Side, Symbol, Time, TimeStampNs};
 use worktable::field;
 use worktable::{RowView, RowViewMut, WorkTable};
@@ -43,6 +43,7 @@ field!(14, OpenOrderClientId: String, "open_order_client_id");
 field!(15, EventId: i64, "event_id");
 field!(16, FilledSizeCol: f64, "filled_size");
 field!(17, UpdateTstCol: TimeStampNs, "update_tst");
+field!(18, AccountCol: i64, "account");
 
 impl OrdersWorkTable {
     pub fn new() -> Self {
@@ -65,6 +66,7 @@ impl OrdersWorkTable {
         worktable.add_field(EventId);
         worktable.add_field(FilledSizeCol);
         worktable.add_field(UpdateTstCol);
+        worktable.add_field(AccountCol);
         Self { worktable }
     }
     pub fn remove_by_cloid(&mut self, cloid: &str) {
@@ -112,6 +114,13 @@ impl OrdersWorkTable {
         let is_match = |row: &RowView| row.index(LocalIdCol) == local_id;
         self.worktable.iter().find(is_match).map(OrderRowView)
     }
+    pub fn get_row_by_server_id(&self, server_id: &str) -> Option<OrderRowView> {
+        if server_id.is_empty() {
+            return None;
+        }
+        let is_match = |row: &RowView| row.index(ServerIdCol) == server_id;
+        self.worktable.iter().find(is_match).map(OrderRowView)
+    }
     pub fn get_row_mut_by_cloid(&mut self, cloid: &str) -> Option<OrderRowViewMut> {
         let is_match = |row: &RowViewMut| row.index(ClientIdCol) == cloid;
         self.worktable.iter_mut().find(is_match).map(OrderRowViewMut)
@@ -180,6 +189,7 @@ impl OrdersWorkTable {
             .set(EventId, update.event_id as _)
             .set(FilledSizeCol, update.filled_size)
             .set(UpdateTstCol, update.update_tst.nanos())
+            .set(AccountCol, update.account)
             .finish();
     }
     pub fn insert_new_order_request(&mut self, request: &RequestPlaceOrder) {
@@ -205,6 +215,7 @@ impl OrdersWorkTable {
             .set(EventId, request.event_id as i64)
             .set(FilledSizeCol, 0.0)
             .set(UpdateTstCol, 0) // set to 0 to make sure it's being correctly updated
+            .set(AccountCol, request.account)
             .finish();
     }
     pub fn insert_order_row_view(&mut self, row: &OrderRowView) {
@@ -228,6 +239,7 @@ impl OrdersWorkTable {
             .set(EventId, row.event_id())
             .set(FilledSizeCol, row.filled_size())
             .set(UpdateTstCol, row.update_tst())
+            .set(AccountCol, row.account())
             .finish();
     }
 }
@@ -297,6 +309,9 @@ impl<'a> OrderRowView<'a> {
     pub fn update_tst(&self) -> i64 {
         *self.0.index(UpdateTstCol)
     }
+    pub fn account(&self) -> AccountId {
+        *self.0.index(AccountCol)
+    }
 }
 
 impl<'a> std::fmt::Display for OrderRowView<'a> {
@@ -407,6 +422,9 @@ impl OrderRowViewMut<'_> {
         if update.event_id != 0 {
             self.0.set(EventId, update.event_id as i64);
         }
+        if update.account != 0 {
+            self.0.set(AccountCol, update.account);
+        }
     }
     pub fn remove(self) {
         self.0.remove()
@@ -1,5 +1,5 @@
 use tracing::{debug, warn};
-use trading_exchange::model::{RequestPlaceOrder, UpdateOrder, UpdatePosition, UpdatePositions};
+use trading_exchange::model::{AccountId, RequestPlaceOrder, UpdateOrder, UpdatePosition, UpdatePositions};
 use trading_model::{Exchange, PriceType, Time};
 
 use crate::db::worktable::positions::{PositionRowView, PositionsTable};
@@ -46,13 +46,13 @@ impl PositionManager {
             // let update_usd = UpdateOrder {};
         }
     }
-    pub fn update_position(&mut self, position: &UpdatePosition, time: Time) {
+    pub fn update_position(&mut self, account: AccountId, position: &UpdatePosition, time: Time) {
         let exchange = position.instrument.get_exchange().unwrap();
         let symbol = position.instrument.get_asset_or_symbol().unwrap();
         // if symbol.starts_with("USD") {
         //     return;
         // }
-        match self.positions.get_position_by_symbol_mut(exchange, &symbol) {
+        match self.positions.get_position_by_symbol_mut(exchange, account, &symbol) {
             Some(mut row) => {
                 if let Some(set_values) = &position.set_values {
                     row.set_size(set_values.available);
@@ -65,19 +65,22 @@ impl PositionManager {
                 row.set_update_tst(time.nanos())
             }
             None => {
-                self.positions.push_position_update(position, time);
+                self.positions.push_position_update(account, position, time);
             }
         }
     }
     pub fn update_positions(&mut self, positions: &UpdatePositions) {
         for position in positions.positions.iter() {
-            self.update_position(position, positions.exchange_time);
+            self.update_position(positions.account, position, positions.exchange_time);
         }
         let exchange = positions.range.get_exchange().unwrap();
 
         self.positions.iter_mut().for_each(|x| {
-            // if same exchange, and update_lt is different from the last update_lt
-            if x.exchange() == exchange && x.update_tst() != positions.exchange_time.nanos() {
+            // if same account, and update_lt is different from the last update_lt
+            if x.exchange() == exchange
+                && x.account() == positions.account
+                && x.update_tst() != positions.exchange_time.nanos()
+            {
                 x.remove()
             }
         })
@@ -86,17 +89,18 @@ impl PositionManager {
     pub fn get_positions(&self) -> Vec<PositionRowView> {
         self.positions.iter().collect()
     }
-    /// count positions if the notional value is above a certain threshold
+    /// count positions of the account if the notional value is above a certain threshold
 
     pub fn count_positions_advanced(
         &self,
         exchange: Exchange,
+        account: AccountId,
         price: &LastPriceMap,
         threshold_notional_size: f64,
     ) -> usize {
         let mut count = 0;
         for position in self.positions.iter() {
-            if position.exchange() != exchange {
+            if position.exchange() != exchange || position.account() != account {
                 continue;
             }
             let symbol = position.symbol();
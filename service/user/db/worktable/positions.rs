@@ -4,7 +4,7 @@ use tracing::warn;
 
 use worktable::{field, RowView, RowViewMut, WorkTable};
 
-use trading_exchange::model::{gen_local_id, RequestPlaceOrder, UpdateOrder, UpdatePosition};
+use trading_exchange::model::{gen_local_id, AccountId, RequestPlaceOrder, UpdateOrder, UpdatePosition};
 use trading_model::{now, Exchange, Time};
 
 /// A position can be one of the followings:
@@ -21,6 +21,7 @@ field!(3, SymbolCol: String, "symbol");
 field!(4, SizeCol: f64, "size");
 field!(5, FilledSizeCol: f64, "filled_size");
 field!(6, UpdateTst: i64, "update_tst");
+field!(7, AccountCol: i64, "account");
 
 impl PositionsTable {
     pub fn new() -> Self {
@@ -32,6 +33,7 @@ impl PositionsTable {
         worktable.add_field(SizeCol);
         worktable.add_field(FilledSizeCol);
         worktable.add_field(UpdateTst);
+        worktable.add_field(AccountCol);
 
         Self { worktable }
     }
@@ -53,6 +55,7 @@ impl PositionsTable {
             .set(SizeCol, update.size)
             .set(FilledSizeCol, 0.0)
             .set(UpdateTst, now())
+            .set(AccountCol, update.account)
             .finish();
     }
     pub fn push_order_update(&mut self, update: &UpdateOrder) {
@@ -72,10 +75,11 @@ impl PositionsTable {
             .set(SizeCol, update.size)
             .set(FilledSizeCol, update.filled_size)
             .set(UpdateTst, update.update_lt.nanos())
+            .set(AccountCol, update.account)
             .finish();
     }
 
-    pub fn push_position_update(&mut self, update: &UpdatePosition, time: Time) {
+    pub fn push_position_update(&mut self, account: AccountId, update: &UpdatePosition, time: Time) {
         let symbol = update.instrument.get_asset_or_symbol().unwrap();
         let exchange = update.instrument.get_exchange().unwrap().to_string();
         let available = update
@@ -93,6 +97,7 @@ impl PositionsTable {
             .set(SizeCol, available)
             .set(FilledSizeCol, 0.0)
             .set(UpdateTst, time.nanos())
+            .set(AccountCol, account)
             .finish();
     }
     /// returns if the cloid exists and is removed
@@ -116,13 +121,23 @@ impl PositionsTable {
             .map(PositionRowViewMut)
             .find(|x| x.cloid() == Some(cloid))
     }
-    pub fn get_position_by_symbol_mut(&mut self, exchange: Exchange, symbol: &str) -> Option<PositionRowViewMut> {
+    pub fn get_position_by_symbol_mut(
+        &mut self,
+        exchange: Exchange,
+        account: AccountId,
+        symbol: &str,
+    ) -> Option<PositionRowViewMut> {
         self.iter_mut()
-            .find(|x| x.cloid().is_none() && x.exchange() == exchange && x.symbol() == symbol)
-    }
-    pub fn get_position_by_symbol(&self, exchange: Exchange, symbol: &str) -> Option<PositionRowView> {
+            .find(|x| x.cloid().is_none() && x.exchange() == exchange && x.account() == account && x.symbol() == symbol)
+    }
+    pub fn get_position_by_symbol(
+        &self,
+        exchange: Exchange,
+        account: AccountId,
+        symbol: &str,
+    ) -> Option<PositionRowView> {
         self.iter()
-            .find(|x| x.cloid().is_none() && x.exchange() == exchange && x.symbol() == symbol)
+            .find(|x| x.cloid().is_none() && x.exchange() == exchange && x.account() == account && x.symbol() == symbol)
     }
     pub fn iter(&self) -> impl Iterator<Item = PositionRowView> {
         self.worktable.iter().map(PositionRowView)
@@ -157,6 +172,9 @@ impl<'a> PositionRowView<'a> {
     pub fn update_tst(&self) -> i64 {
         *self.0.index(UpdateTst)
     }
+    pub fn account(&self) -> AccountId {
+        *self.0.index(AccountCol)
+    }
 }
 
 pub struct PositionRowViewMut<'a>(RowViewMut<'a>);
@@ -0,0 +1,53 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use build::model::{
+    EnumErrorCode, EnumRole, UserAcknowledgeAccountingHaltRequest, UserAcknowledgeAccountingHaltResponse,
+};
+use lib::handler::{RequestHandler, Response};
+use lib::toolbox::{CustomError, RequestContext};
+use trading_exchange::model::{ExecutionRequest, RequestAcknowledgeHalt};
+use trading_model::Exchange;
+
+use crate::endpoint_method::auth::ensure_user_role;
+use crate::execution::AccountingHalts;
+use crate::strategy::broadcast::AsyncBroadcaster;
+
+/// resumes trading on an account halted by its accounting, the connection takes its next snapshot as the new baseline
+#[derive(Clone)]
+pub struct MethodUserAcknowledgeAccountingHalt {
+    pub halts: Arc<AccountingHalts>,
+    pub tx_request: AsyncBroadcaster<ExecutionRequest>,
+}
+#[async_trait(?Send)]
+impl RequestHandler for MethodUserAcknowledgeAccountingHalt {
+    type Request = UserAcknowledgeAccountingHaltRequest;
+
+    async fn handle(&self, ctx: RequestContext, req: Self::Request) -> Response<Self::Request> {
+        ensure_user_role(ctx, EnumRole::User)?;
+        let bad_request = |x: String| CustomError::new(EnumErrorCode::BadRequest, x);
+        let exchange =
+            Exchange::from_str(&req.exchange).map_err(|_| bad_request(format!("unknown exchange {}", req.exchange)))?;
+        let account = req.account;
+        let Some(halt) = self.halts.get(exchange, account) else {
+            eyre::bail!(bad_request(format!(
+                "accounting of {exchange} account {account} is not halted"
+            )));
+        };
+        // the router forwards the acknowledgement to the connection and lifts the halt once the
+        // connection reports the account as no longer halted
+        self.tx_request
+            .broadcast(ExecutionRequest::AcknowledgeHalt(RequestAcknowledgeHalt {
+                exchange,
+                account,
+            }))?;
+        tracing::info!(
+            "accounting halt of {} account {} acknowledged by {}",
+            exchange,
+            account,
+            ctx.user_id
+        );
+        Ok(UserAcknowledgeAccountingHaltResponse { reason: halt.reason })
+    }
+}
@@ -0,0 +1,36 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use build::model::{EnumErrorCode, EnumRole, UserBackupDatabaseRequest, UserBackupDatabaseResponse};
+use eyre::Context;
+use gluesql_shared_sled_storage::SharedSledStorage;
+use lib::handler::{RequestHandler, Response};
+use lib::toolbox::{CustomError, RequestContext};
+
+use crate::db::gluesql::backup::{validate_backup_label, write_backup};
+use crate::endpoint_method::auth::ensure_user_role;
+
+/// point-in-time snapshot of the persistent database while the service keeps running
+#[derive(Clone)]
+pub struct MethodUserBackupDatabase {
+    pub storage: SharedSledStorage,
+    pub directory: PathBuf,
+}
+#[async_trait(?Send)]
+impl RequestHandler for MethodUserBackupDatabase {
+    type Request = UserBackupDatabaseRequest;
+
+    async fn handle(&self, ctx: RequestContext, req: Self::Request) -> Response<Self::Request> {
+        ensure_user_role(ctx, EnumRole::Admin)?;
+        let label = req.label.unwrap_or_else(|| "snapshot".to_string());
+        validate_backup_label(&label).map_err(|x| CustomError::new(EnumErrorCode::BadRequest, x.to_string()))?;
+        let path = write_backup(&self.storage, &self.directory, &label).await?;
+        let size = std::fs::metadata(&path)
+            .with_context(|| format!("failed to read backup {}", path.display()))?
+            .len();
+        Ok(UserBackupDatabaseResponse {
+            path: path.display().to_string(),
+            size: size as _,
+        })
+    }
+}
@@ -134,7 +134,7 @@ impl RequestHandler for MethodUserSubExchangeLatency {
             self.sub.write().unsubscribe(ctx.connection_id);
             return Ok(UserSubExchangeLatencyResponse { data: vec![] });
         }
-        self.sub.write().subscribe(ctx, (), |_| {});
+        self.sub.write().subscribe(ctx, (), |_| {})?;
 
         let mut this = self.clone();
         let mut time_filter = true.to_gluesql();
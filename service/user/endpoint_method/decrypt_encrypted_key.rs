@@ -10,13 +10,14 @@ use lib::gluesql::{QueryFilter, Table, TableSelectItem};
 use lib::handler::{RequestHandler, Response};
 use lib::toolbox::RequestContext;
 use std::str::FromStr;
+use trading_exchange::model::AccountId;
 use trading_exchange::utils::crypto::PrivateKey;
 use trading_model::Exchange;
 
 #[derive(Clone)]
 pub struct MethodUserDecryptEncryptedKey {
     pub table: Table<SharedSledStorage, DbRowKey>,
-    pub map: std::sync::Arc<parking_lot::RwLock<std::collections::HashMap<Exchange, ExecutionPrivateKey>>>,
+    pub map: std::sync::Arc<parking_lot::RwLock<std::collections::HashMap<(Exchange, AccountId), ExecutionPrivateKey>>>,
 }
 #[async_trait(?Send)]
 impl RequestHandler for MethodUserDecryptEncryptedKey {
@@ -57,12 +58,13 @@ impl MethodUserDecryptEncryptedKey {
         let key = PrivateKey::from_str(key)?;
         let key = ExecutionPrivateKey {
             exchange: Exchange::from_str(&row.exchange)?,
+            account: row.id as _,
             account_id: row.account_id,
             private_key: key,
         };
         // store execution key
         let mut map = this.map.write();
-        if let Some(original_key) = map.insert(key.exchange, key) {
+        if let Some(original_key) = map.insert((key.exchange, key.account), key) {
             tracing::debug!("replaced {}", original_key.account_id);
         }
         Ok(())
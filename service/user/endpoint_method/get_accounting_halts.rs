@@ -0,0 +1,34 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use build::model::{EnumRole, UserAccountingHalt, UserGetAccountingHaltsRequest, UserGetAccountingHaltsResponse};
+use lib::handler::{RequestHandler, Response};
+use lib::toolbox::RequestContext;
+
+use crate::endpoint_method::auth::ensure_user_role;
+use crate::execution::AccountingHalts;
+
+#[derive(Clone)]
+pub struct MethodUserGetAccountingHalts {
+    pub halts: Arc<AccountingHalts>,
+}
+#[async_trait(?Send)]
+impl RequestHandler for MethodUserGetAccountingHalts {
+    type Request = UserGetAccountingHaltsRequest;
+
+    async fn handle(&self, ctx: RequestContext, _req: Self::Request) -> Response<Self::Request> {
+        ensure_user_role(ctx, EnumRole::User)?;
+        let data = self
+            .halts
+            .list()
+            .into_iter()
+            .map(|x| UserAccountingHalt {
+                exchange: x.exchange.to_string(),
+                account: x.account,
+                reason: x.reason,
+                datetime: x.datetime.millis(),
+            })
+            .collect();
+        Ok(UserGetAccountingHaltsResponse { data })
+    }
+}
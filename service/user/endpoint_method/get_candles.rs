@@ -0,0 +1,57 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use gluesql_shared_sled_storage::SharedSledStorage;
+
+use build::model::{UserCandle, UserGetCandlesRequest, UserGetCandlesResponse};
+use lib::gluesql::{QueryFilter, Table, TableSelectItem};
+use lib::handler::{RequestHandler, Response};
+use lib::toolbox::RequestContext;
+use trading_model::{parse_candle_interval, Exchange, Symbol};
+
+use crate::db::gluesql::schema::candle::DbRowCandle;
+use crate::endpoint_method::auth::ensure_user_role;
+use crate::signals::candles::CandleMap;
+
+/// number of candles returned when no limit is given
+const CANDLE_DEFAULT_LIMIT: i32 = 500;
+
+#[derive(Clone)]
+pub struct MethodUserGetCandles {
+    pub table: Table<SharedSledStorage, DbRowCandle>,
+    pub candle_map: Arc<CandleMap>,
+}
+#[async_trait(?Send)]
+impl RequestHandler for MethodUserGetCandles {
+    type Request = UserGetCandlesRequest;
+
+    async fn handle(&self, ctx: RequestContext, req: Self::Request) -> Response<Self::Request> {
+        ensure_user_role(ctx, build::model::EnumRole::User)?;
+        let mut this = self.clone();
+
+        let exchange = Exchange::from_str(&req.exchange)?;
+        let symbol = Symbol::from(req.symbol.as_str());
+        let interval_ms = parse_candle_interval(&req.interval)?;
+        let limit = req.limit.unwrap_or(CANDLE_DEFAULT_LIMIT).max(0) as u64;
+        let filter = DbRowCandle::by_series(exchange, symbol._hash(), interval_ms as _)
+            .and(QueryFilter::range(req.time_start, req.time_end));
+        // latest candles first, so the limit keeps the most recent ones
+        let mut rows = this
+            .table
+            .select_limit(Some(filter), "datetime DESC", Some(limit))
+            .await?;
+        rows.reverse();
+        let mut data: Vec<UserCandle> = rows.into_iter().map(|row| row.into()).collect();
+        // the candle being built is only in memory
+        if let Some(candle) = this.candle_map.last(exchange, &symbol, interval_ms) {
+            let datetime = candle.exchange_time.millis();
+            let in_range =
+                req.time_start.map_or(true, |x| datetime >= x) && req.time_end.map_or(true, |x| datetime <= x);
+            if in_range && data.last().map_or(true, |x| x.datetime < datetime) {
+                data.push(DbRowCandle::try_from(candle)?.into());
+            }
+        }
+        Ok(UserGetCandlesResponse { data })
+    }
+}
@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use build::model::{
+    EnumRole, UserExecutionAccount, UserGetExecutionAccountsRequest, UserGetExecutionAccountsResponse,
+    UserStrategyAccount,
+};
+use lib::handler::{RequestHandler, Response};
+use lib::toolbox::RequestContext;
+
+use crate::endpoint_method::auth::ensure_user_role;
+use crate::execution::ExecutionAccounts;
+
+/// connected accounts and the account each strategy trades on
+#[derive(Clone)]
+pub struct MethodUserGetExecutionAccounts {
+    pub accounts: Arc<ExecutionAccounts>,
+}
+#[async_trait(?Send)]
+impl RequestHandler for MethodUserGetExecutionAccounts {
+    type Request = UserGetExecutionAccountsRequest;
+
+    async fn handle(&self, ctx: RequestContext, _req: Self::Request) -> Response<Self::Request> {
+        ensure_user_role(ctx, EnumRole::User)?;
+        let accounts = self
+            .accounts
+            .connected()
+            .into_iter()
+            .map(|(exchange, account, account_id)| UserExecutionAccount {
+                exchange: exchange.to_string(),
+                account,
+                account_id,
+            })
+            .collect();
+        let assignments = self
+            .accounts
+            .assignments()
+            .into_iter()
+            .map(|(strategy_id, exchange, account)| UserStrategyAccount {
+                strategy_id,
+                exchange: exchange.to_string(),
+                account,
+            })
+            .collect();
+        Ok(UserGetExecutionAccountsResponse { accounts, assignments })
+    }
+}
@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use build::model::{UserGetFundingCarryRequest, UserGetFundingCarryResponse};
+use lib::handler::{RequestHandler, Response};
+use lib::toolbox::RequestContext;
+use trading_model::Asset;
+
+use crate::endpoint_method::auth::ensure_user_role;
+use crate::signals::funding::FundingCarryMap;
+
+#[derive(Clone)]
+pub struct MethodUserGetFundingCarry {
+    pub carry_map: Arc<FundingCarryMap>,
+}
+#[async_trait(?Send)]
+impl RequestHandler for MethodUserGetFundingCarry {
+    type Request = UserGetFundingCarryRequest;
+
+    async fn handle(&self, ctx: RequestContext, req: Self::Request) -> Response<Self::Request> {
+        ensure_user_role(ctx, build::model::EnumRole::User)?;
+        let mut carries = match req.asset {
+            Some(asset) => self.carry_map.get(&Asset::from(asset)).into_iter().collect(),
+            None => self.carry_map.values(),
+        };
+        // best opportunities first
+        carries.sort_by(|a, b| b.yield_annual.total_cmp(&a.yield_annual));
+        Ok(UserGetFundingCarryResponse {
+            data: carries.into_iter().map(|x| x.into()).collect(),
+        })
+    }
+}
@@ -0,0 +1,36 @@
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use gluesql_shared_sled_storage::SharedSledStorage;
+
+use build::model::{UserGetFundingHistoryRequest, UserGetFundingHistoryResponse};
+use lib::gluesql::{QueryFilter, Table, TableSelectItem};
+use lib::handler::{RequestHandler, Response};
+use lib::toolbox::RequestContext;
+use trading_model::{Exchange, Symbol};
+
+use crate::db::gluesql::schema::funding_history::DbRowFundingHistory;
+use crate::endpoint_method::auth::ensure_user_role;
+
+#[derive(Clone)]
+pub struct MethodUserGetFundingHistory {
+    pub table: Table<SharedSledStorage, DbRowFundingHistory>,
+}
+#[async_trait(?Send)]
+impl RequestHandler for MethodUserGetFundingHistory {
+    type Request = UserGetFundingHistoryRequest;
+
+    async fn handle(&self, ctx: RequestContext, req: Self::Request) -> Response<Self::Request> {
+        ensure_user_role(ctx, build::model::EnumRole::User)?;
+        let mut this = self.clone();
+
+        let exchange = Exchange::from_str(&req.exchange)?;
+        let symbol = Symbol::from(req.symbol.as_str());
+        let filter = DbRowFundingHistory::by_instrument(exchange, symbol._hash())
+            .and(QueryFilter::range(req.time_start, req.time_end));
+        let rows = this.table.select(Some(filter), "datetime").await?;
+        Ok(UserGetFundingHistoryResponse {
+            data: rows.into_iter().map(|row| row.into()).collect(),
+        })
+    }
+}
@@ -1,20 +1,22 @@
 use async_trait::async_trait;
-use eyre::bail;
 use gluesql_shared_sled_storage::SharedSledStorage;
 
-use build::model::EnumErrorCode;
-use lib::gluesql::{QueryFilter, TableSelectItem};
+use std::collections::HashMap;
+
+use lib::gluesql::{Table, TableSelectItem};
 use lib::handler::{RequestHandler, Response};
-use lib::toolbox::{CustomError, RequestContext};
+use lib::toolbox::RequestContext;
 use trading_model::Symbol;
 
+use crate::db::gluesql::schema::funding_accrual::DbRowFundingAccrual;
+use crate::db::gluesql::schema::ledger::LedgerQuery;
 use crate::db::gluesql::schema::DbRowLedger;
-use crate::db::gluesql::StrategyTable;
 use crate::endpoint_method::auth::ensure_user_role;
 
 #[derive(Clone)]
 pub struct MethodUserGetLedger {
-    pub table: StrategyTable<SharedSledStorage, DbRowLedger>,
+    pub table: Table<SharedSledStorage, DbRowLedger>,
+    pub funding_table: Table<SharedSledStorage, DbRowFundingAccrual>,
 }
 #[async_trait(?Send)]
 impl RequestHandler for MethodUserGetLedger {
@@ -22,9 +24,7 @@ impl RequestHandler for MethodUserGetLedger {
 
     async fn handle(&self, ctx: RequestContext, req: Self::Request) -> Response<Self::Request> {
         ensure_user_role(ctx, build::model::EnumRole::User)?;
-        let Some(mut table) = self.table.get(&req.strategy_id).cloned() else {
-            bail!(CustomError::new(EnumErrorCode::NotFound, "strategy not found"));
-        };
+        let mut table = self.table.clone();
 
         let mut time_start = req.time_start;
         let mut time_end = req.time_end;
@@ -34,15 +34,15 @@ impl RequestHandler for MethodUserGetLedger {
             time_start = Some(now - dur);
             time_end = Some(now);
         }
-        let mut filter = QueryFilter::range(time_start, time_end);
-        // TODO include symbol as well
-        if let Some(symbol) = req.symbol {
-            filter = filter.and(QueryFilter::symbol_id(Symbol::from(symbol)._hash()));
-        }
-        if let Some(client_id) = req.client_id {
+        let query = LedgerQuery {
+            strategy_id: Some(req.strategy_id),
             // client ID is the event ID
-            filter = filter.and(QueryFilter::eq_string("client_id", client_id))
-        }
+            client_id: req.client_id,
+            symbol: req.symbol.map(|x| Symbol::from(x.as_str())),
+            time_start,
+            time_end,
+            ..Default::default()
+        };
 
         // FIXME: double check the column name
         // filled:0 is a ack, rule out if we do not want to see it
@@ -50,9 +50,26 @@ impl RequestHandler for MethodUserGetLedger {
         //     Some(true) => {}
         //     _ => filter = filter.and(QueryFilter::gt("filled", 0)),
         // };
-        let rows = table.select(Some(filter), "id DESC").await?;
+        let rows = table.select(Some(query.filter()), "id DESC").await?;
+        let mut funding_table = self.funding_table.clone();
+        let accruals = funding_table
+            .select(Some(DbRowFundingAccrual::by_strategy(req.strategy_id)), "id")
+            .await?;
+        let mut funding_usd: HashMap<u64, f64> = HashMap::new();
+        for accrual in accruals {
+            *funding_usd.entry(accrual.ledger_id).or_default() += accrual.amount_usd;
+        }
         Ok(build::model::UserGetLedgerResponse {
-            data: rows.into_iter().map(|x| x.into()).collect(),
+            data: rows
+                .into_iter()
+                .map(|x| {
+                    let funding = funding_usd.get(&x.id).copied().unwrap_or_default();
+                    build::model::UserLedger {
+                        funding_usd: funding,
+                        ..x.into()
+                    }
+                })
+                .collect(),
         })
     }
 }
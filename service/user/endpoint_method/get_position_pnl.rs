@@ -0,0 +1,31 @@
+use async_trait::async_trait;
+use gluesql_shared_sled_storage::SharedSledStorage;
+
+use build::model::{UserGetPositionPnlRequest, UserGetPositionPnlResponse};
+use lib::gluesql::{QueryFilter, Table, TableSelectItem};
+use lib::handler::{RequestHandler, Response};
+use lib::toolbox::RequestContext;
+
+use crate::db::gluesql::schema::position_pnl::DbRowPositionPnl;
+use crate::endpoint_method::auth::ensure_user_role;
+
+#[derive(Clone)]
+pub struct MethodUserGetPositionPnl {
+    pub table: Table<SharedSledStorage, DbRowPositionPnl>,
+}
+#[async_trait(?Send)]
+impl RequestHandler for MethodUserGetPositionPnl {
+    type Request = UserGetPositionPnlRequest;
+
+    async fn handle(&self, ctx: RequestContext, req: Self::Request) -> Response<Self::Request> {
+        ensure_user_role(ctx, build::model::EnumRole::User)?;
+        let mut this = self.clone();
+
+        let filter =
+            DbRowPositionPnl::by_strategy(req.strategy_id).and(QueryFilter::range(req.time_start, req.time_end));
+        let rows = this.table.select(Some(filter), "id DESC").await?;
+        Ok(UserGetPositionPnlResponse {
+            data: rows.into_iter().map(|row| row.into()).collect(),
+        })
+    }
+}
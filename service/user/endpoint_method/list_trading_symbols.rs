@@ -1,18 +1,20 @@
+use std::sync::Arc;
+
 use async_trait::async_trait;
 
 use build::model::{EnumRole, UserListTradingSymbolsRequest, UserListTradingSymbolsResponse, UserTradingSymbol};
 use lib::handler::{RequestHandler, Response};
 use lib::toolbox::RequestContext;
-use trading_model::SharedInstrumentManager;
 
 use crate::endpoint_method::auth::ensure_user_role;
+use crate::strategy::instrument_refresh::DynamicInstrumentManager;
 
 #[derive(Debug, Clone)]
 pub struct MethodUserListTradingSymbols {
-    pub symbols: SharedInstrumentManager,
+    pub symbols: Arc<DynamicInstrumentManager>,
 }
 impl MethodUserListTradingSymbols {
-    pub fn new(symbols: SharedInstrumentManager) -> Self {
+    pub fn new(symbols: Arc<DynamicInstrumentManager>) -> Self {
         Self { symbols }
     }
 }
@@ -25,6 +27,7 @@ impl RequestHandler for MethodUserListTradingSymbols {
         ensure_user_role(ctx, EnumRole::User)?;
         let symbols = self
             .symbols
+            .load()
             .iter()
             .map(|x| UserTradingSymbol {
                 exchange: x.exchange.to_string(),
@@ -11,29 +11,30 @@ use lib::handler::{RequestHandler, Response};
 use lib::toolbox::{CustomError, RequestContext};
 use trading_exchange::exchange::gen_order_cid;
 use trading_exchange::model::{
-    gen_local_id, OrderCid, OrderStatus, OrderType, PositionEffect, RequestCancelOrder, RequestPlaceOrder, TimeInForce,
-    UpdateOrder,
+    gen_local_id, AccountId, OrderCid, OrderStatus, OrderType, PositionEffect, RequestCancelOrder, RequestPlaceOrder,
+    TimeInForce, UpdateOrder,
 };
-use trading_model::{Asset, Exchange, InstrumentCode, PriceType, SharedInstrumentManager, Side, Symbol, Time};
+use trading_model::{Asset, Exchange, InstrumentCode, PriceType, Side, Symbol, Time};
 
 use crate::db::worktable::position_manager::PositionManager;
 use crate::endpoint_method::auth::ensure_user_role;
 use crate::execution::OrderRegistry;
 use crate::strategy::data_factory::{LastPriceMap, PriceSourceAsset};
 use crate::strategy::instrument::convert_asset_to_instrument;
+use crate::strategy::instrument_refresh::DynamicInstrumentManager;
 
 pub struct MethodUserCancelOrClosePosition {
     manual_trade_service: Arc<OrderRegistry>,
     portfolio: Arc<RwLock<PositionManager>>,
     prices: Arc<LastPriceMap>,
-    manager: SharedInstrumentManager,
+    manager: Arc<DynamicInstrumentManager>,
 }
 impl MethodUserCancelOrClosePosition {
     pub fn new(
         manual_trade_service: Arc<OrderRegistry>,
         portfolio: Arc<RwLock<PositionManager>>,
         prices: Arc<LastPriceMap>,
-        manager: SharedInstrumentManager,
+        manager: Arc<DynamicInstrumentManager>,
     ) -> Self {
         Self {
             manual_trade_service,
@@ -42,7 +43,13 @@ impl MethodUserCancelOrClosePosition {
             manager,
         }
     }
-    pub async fn close_position(&self, exchange: Exchange, asset: Asset, size: f64) -> Result<UpdateOrder> {
+    pub async fn close_position(
+        &self,
+        exchange: Exchange,
+        account: AccountId,
+        asset: Asset,
+        size: f64,
+    ) -> Result<UpdateOrder> {
         let side = if size > 0.0 { Side::Sell } else { Side::Buy };
         let asset: Asset = asset.trim_end_matches("USDT").into();
         let mut price = self
@@ -53,7 +60,7 @@ impl MethodUserCancelOrClosePosition {
                 price_type: PriceType::Bid,
             })
             .with_context(|| CustomError::new(EnumErrorCode::NotFound, "Price not found"))?;
-        let Some(symbol) = convert_asset_to_instrument(&self.manager, exchange, &asset) else {
+        let Some(symbol) = convert_asset_to_instrument(&self.manager.load(), exchange, &asset) else {
             bail!(CustomError::new(
                 EnumErrorCode::NotFound,
                 format!("Could not find the instrument for asset {} {}", exchange, asset)
@@ -76,7 +83,7 @@ impl MethodUserCancelOrClosePosition {
             side,
             effect: PositionEffect::Close,
             tif: TimeInForce::ImmediateOrCancel,
-            account: 0,
+            account,
             create_lt: Time::now(),
             ..RequestPlaceOrder::empty()
         };
@@ -89,13 +96,19 @@ impl MethodUserCancelOrClosePosition {
 
         Ok(response)
     }
-    pub async fn cancel_order(&self, exchange: Exchange, symbol: Symbol, cloid: OrderCid) -> Result<()> {
+    pub async fn cancel_order(
+        &self,
+        exchange: Exchange,
+        account: AccountId,
+        symbol: Symbol,
+        cloid: OrderCid,
+    ) -> Result<()> {
         let new_order = RequestCancelOrder {
             instrument: InstrumentCode::from_symbol(exchange, symbol),
             order_lid: gen_local_id(),
             order_cid: cloid,
             order_sid: "".into(),
-            account: 0,
+            account,
             strategy_id: 0,
             cancel_lt: Time::now(),
         };
@@ -119,9 +132,10 @@ impl RequestHandler for MethodUserCancelOrClosePosition {
             if position.cloid().is_none() {
                 let size = position.size();
                 let exchange = position.exchange();
+                let account = position.account();
                 let symbol = position.symbol().into();
                 drop(portfolio);
-                let update = self.close_position(exchange, symbol, size).await?;
+                let update = self.close_position(exchange, account, symbol, size).await?;
                 if update.status == OrderStatus::Rejected {
                     bail!(CustomError::new(
                         EnumErrorCode::InternalServerError,
@@ -131,9 +145,10 @@ impl RequestHandler for MethodUserCancelOrClosePosition {
             } else {
                 let cloid: OrderCid = position.cloid().unwrap().into();
                 let exchange = position.exchange();
+                let account = position.account();
                 let symbol: Symbol = position.symbol().into();
                 drop(portfolio);
-                self.cancel_order(exchange, symbol, cloid).await?;
+                self.cancel_order(exchange, account, symbol, cloid).await?;
             }
         } else {
             bail!(CustomError::new(EnumErrorCode::NotFound, "Position not found"))
@@ -33,6 +33,7 @@ impl RequestHandler for MethodUserCancelOrder {
         let order = RequestCancelOrder {
             instrument: InstrumentCode::from_symbol(exchange, req.symbol.into()),
             order_lid: local_id.into(),
+            account: req.account.unwrap_or_default(),
             ..RequestCancelOrder::empty()
         };
 
@@ -50,6 +50,8 @@ impl MethodUserPlaceOrder {
             size: req.size,
             ty,
             effect: PositionEffect::Manual,
+            // the router picks the account when none is given
+            account: req.account.unwrap_or_default(),
             ..RequestPlaceOrder::empty()
         };
 
@@ -120,6 +122,7 @@ impl RequestHandler for MethodUserPlaceOrderLimit {
                     price: req.price,
                     size: req.size,
                     local_id: req.local_id,
+                    account: req.account,
                 },
                 OrderType::Limit,
             )
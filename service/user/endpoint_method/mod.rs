@@ -1,18 +1,26 @@
 // reexport the methods
+pub use acknowledge_accounting_halt::*;
+pub use backup_database::*;
 pub use bench::*;
 use build::model::UserDebugLogRow;
 pub use decrypt_encrypted_key::*;
 pub use delete_encrypted_key::*;
+pub use get_accounting_halts::*;
 pub use get_accuracy_log::*;
 pub use get_best_bid_ask_cross::*;
 pub use get_best_bid_ask_cross_with_position::*;
+pub use get_candles::*;
 pub use get_debug_log::*;
 pub use get_encrypted_key::*;
 pub use get_event_1::*;
+pub use get_execution_accounts::*;
+pub use get_funding_carry::*;
+pub use get_funding_history::*;
 pub use get_hedged_orders::*;
 pub use get_ledger::*;
 pub use get_livetest_fill_1::*;
 pub use get_order_per_strategy::*;
+pub use get_position_pnl::*;
 pub use get_price_0::*;
 pub use get_price_difference::*;
 pub use get_signal_0::*;
@@ -25,14 +33,20 @@ pub use get_symbol_2::*;
 use lib::log_reader::LogEntry;
 pub use list_trading_symbols::*;
 pub use set_encrypted_key::*;
+pub use set_strategy_account::*;
 pub use set_strategy_status::*;
 pub use set_symbol_flag_1::*;
+pub use start_export::*;
 pub use start_service::*;
 pub use status::*;
 pub use sub_best_bid_ask_cross_position::*;
+pub use sub_candles::*;
 pub use sub_event_1::*;
+pub use sub_export_jobs::*;
+pub use sub_feed_health::*;
 pub use sub_funding_rate::*;
 pub use sub_ledger_1::*;
+pub use sub_liquidation::*;
 pub use sub_orders::*;
 pub use sub_position::*;
 pub use sub_price::*;
@@ -41,35 +55,45 @@ pub use sub_price_1::*;
 pub use sub_signal_0::*;
 pub use sub_signal_1::*;
 
+use std::collections::HashMap;
 use trading_exchange::model::PositionEffect;
 use trading_model::{Exchange, Symbol};
 use trading_model::{PriceType, Side};
 
 use crate::db::gluesql::schema::accuracy::DbRowLiveTestFillPrice;
+use crate::db::gluesql::schema::position_pnl::DbRowPositionPnl;
 use crate::db::gluesql::schema::{DbRowLedger, DbRowOrder};
 use crate::events::price_change_and_diff::DbRowEventPriceChangeAndDiff;
 use crate::signals::price_change::{DbRowSignalPriceChange, DbRowSignalPriceChangeImmediate};
 use crate::signals::price_difference::{DbRowSignalPriceDifference, DbRowSignalPriceDifferenceGeneric};
 use crate::signals::price_spread::DbRowSignalBestBidAskAcrossExchanges;
 
+mod acknowledge_accounting_halt;
 pub mod auth;
+mod backup_database;
 pub mod blacklist;
 mod decrypt_encrypted_key;
 mod delete_encrypted_key;
+mod get_accounting_halts;
 mod get_accuracy_log;
 mod get_debug_log;
 mod get_encrypted_key;
 mod get_event_1;
+mod get_execution_accounts;
 
 pub mod manual_trade;
 // mod get_order_2;
 mod bench;
 mod get_best_bid_ask_cross;
 mod get_best_bid_ask_cross_with_position;
+mod get_candles;
+mod get_funding_carry;
+mod get_funding_history;
 mod get_hedged_orders;
 mod get_ledger;
 mod get_livetest_fill_1;
 mod get_order_per_strategy;
+mod get_position_pnl;
 mod get_price_0;
 mod get_price_difference;
 mod get_signal_0;
@@ -83,14 +107,20 @@ mod get_symbol_2;
 mod list_trading_symbols;
 pub mod s3_capture_event;
 mod set_encrypted_key;
+mod set_strategy_account;
 mod set_strategy_status;
 mod set_symbol_flag_1;
+mod start_export;
 mod start_service;
 mod status;
 mod sub_best_bid_ask_cross_position;
+mod sub_candles;
 mod sub_event_1;
+mod sub_export_jobs;
+mod sub_feed_health;
 mod sub_funding_rate;
 mod sub_ledger_1;
+mod sub_liquidation;
 mod sub_orders;
 mod sub_position;
 mod sub_price;
@@ -138,6 +168,14 @@ pub enum SubsManagerKey {
     UserSubSignal1,
     UserSubSignal2,
     UserSubBestBidAskAcrossExchangesAndPosition,
+    UserSubLiquidation,
+    UserSubCandle,
+    UserSubFeedHealth,
+    UserSubExportJobs,
+    /// `WsStreamRemoval` of the snapshot-then-delta streams, keys are position ids
+    UserSubPositionsRemoved,
+    /// `WsStreamRemoval` of the snapshot-then-delta streams, keys are `<strategy_id>/<client_id>`
+    UserSubOrdersRemoved,
 }
 impl From<SubsManagerKey> for u32 {
     fn from(val: SubsManagerKey) -> Self {
@@ -145,6 +183,31 @@ impl From<SubsManagerKey> for u32 {
     }
 }
 
+/// number of stream messages kept for resuming a subscription
+pub const STREAM_REPLAY_CAPACITY: usize = 1024;
+
+/// rows that differ from the last published ones, `last` is updated with the current rows.
+/// returns the changed rows and the keys of rows that disappeared
+pub fn diff_stream_rows<'a, T: serde::Serialize>(
+    last: &mut HashMap<String, serde_json::Value>,
+    rows: &'a [T],
+    key: impl Fn(&T) -> String,
+) -> (Vec<&'a T>, Vec<String>) {
+    let mut changed = vec![];
+    let mut current = HashMap::with_capacity(rows.len());
+    for row in rows {
+        let value = serde_json::to_value(row).expect("serialize row");
+        let key = key(row);
+        if last.get(&key) != Some(&value) {
+            changed.push(row);
+        }
+        current.insert(key, value);
+    }
+    let removed = last.keys().filter(|x| !current.contains_key(*x)).cloned().collect();
+    *last = current;
+    (changed, removed)
+}
+
 impl From<DbRowSignalBestBidAskAcrossExchanges> for build::model::Price0 {
     fn from(x: DbRowSignalBestBidAskAcrossExchanges) -> Self {
         let diff_us = x.hyper_bid_price - x.hyper_mark;
@@ -411,6 +474,28 @@ impl From<DbRowLedger> for build::model::UserLedger {
             volume: row.volume,
             datetime: row.datetime,
             closed_profit: row.closed_profit_usd,
+            funding_usd: row.funding_usd,
+            closed_volume: row.closed_volume,
+            fee_usd: row.fee_usd,
+            unrealized_pnl_usd: row.unrealized_pnl_usd,
+        }
+    }
+}
+
+impl From<DbRowPositionPnl> for build::model::UserPositionPnl {
+    fn from(row: DbRowPositionPnl) -> Self {
+        build::model::UserPositionPnl {
+            id: row.id as _,
+            event_id: row.event_id as _,
+            datetime: row.datetime,
+            update_datetime: row.update_datetime,
+            total_pnl_usd: row.total_pnl_usd(),
+            legs: row.legs,
+            realized_pnl_usd: row.realized_pnl_usd,
+            unrealized_pnl_usd: row.unrealized_pnl_usd,
+            fee_usd: row.fee_usd,
+            funding_usd: row.funding_usd,
+            closed: row.closed,
         }
     }
 }
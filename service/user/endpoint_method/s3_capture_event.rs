@@ -23,12 +23,13 @@ use lib::toolbox::{ArcToolbox, CustomError, RequestContext, TOOLBOX};
 use lib::ws::{SubscriptionManager, WebsocketServer};
 use trading_exchange::model::{ExecutionRequest, OrderStatus, RequestCancelOrder, RequestPlaceOrder};
 use trading_exchange::utils::future::interval;
-use trading_model::{now, Exchange, InstrumentCode, SharedInstrumentManager, Time, NANOSECONDS_PER_MILLISECOND};
+use trading_model::{now, Exchange, InstrumentCode, Time, NANOSECONDS_PER_MILLISECOND};
 
 use crate::endpoint_method::auth::ensure_user_role;
 use crate::endpoint_method::SubsManagerKey;
 use crate::main_core::MainStruct;
 use crate::strategy::broadcast::AsyncBroadcaster;
+use crate::strategy::instrument_refresh::DynamicInstrumentManager;
 use crate::strategy::strategy_three::STRATEGY_ID;
 use crate::strategy::strategy_two::order_placement::Strategy2OrderPlacement;
 use crate::strategy::strategy_two_and_three::capture_event::CaptureCommon;
@@ -46,7 +47,7 @@ impl MethodUserS3CaptureEvent {
     pub fn new(
         events: Table<SharedMemoryStorage, DbRowBestBidAskAcrossExchangesAndPosition>,
         common: Arc<CaptureCommon>,
-        manager: SharedInstrumentManager,
+        manager: Arc<DynamicInstrumentManager>,
         ledger: Table<SharedSledStorage, DbRowLedger>,
         strategy_status: Arc<StrategyStatusMap>,
         tx_req: AsyncBroadcaster<ExecutionRequest>,
@@ -118,7 +119,7 @@ pub struct MethodUserS3ReleasePosition {
 impl MethodUserS3ReleasePosition {
     pub fn new(
         common: Arc<CaptureCommon>,
-        manager: SharedInstrumentManager,
+        manager: Arc<DynamicInstrumentManager>,
         table_ledger: Table<SharedSledStorage, DbRowLedger>,
         strategy_status: Arc<StrategyStatusMap>,
         tx_req: AsyncBroadcaster<ExecutionRequest>,
@@ -316,7 +317,7 @@ impl RequestHandler for MethodUserSubStrategy3PositionsOpening {
         }
         {
             let mut write = self.subscribe.write().await;
-            write.subscribe(ctx, (), |_| {}); // get all orders from captured events
+            write.subscribe(ctx, (), |_| {})?; // get all orders from captured events
         }
         let data = self.get_data().await?;
 
@@ -409,7 +410,7 @@ impl RequestHandler for MethodUserSubStrategy3PositionsClosing {
         }
         {
             let mut write = self.subscribe.write().await;
-            write.subscribe(ctx, (), |_| {}); // get all orders from captured events
+            write.subscribe(ctx, (), |_| {})?; // get all orders from captured events
         }
         let data = self.get_data().await?;
 
@@ -423,14 +424,14 @@ pub fn init_endpoints(server: &mut WebsocketServer, main_struct: &mut MainStruct
         main_struct.table_map.volatile.event_price_spread_and_position.clone(),
         common.clone(),
         main_struct.table_map.volatile.instruments.clone(),
-        main_struct.table_map.persistent.ledger.get(&3).cloned().unwrap(),
+        main_struct.table_map.persistent.ledger.clone(),
         main_struct.table_map.volatile.strategy_status.clone(),
         main_struct.registry.get_unwrap(),
     ));
     server.add_handler(MethodUserS3ReleasePosition::new(
         common.clone(),
         main_struct.table_map.volatile.instruments.clone(),
-        main_struct.table_map.persistent.ledger.get(&3).cloned().unwrap(),
+        main_struct.table_map.persistent.ledger.clone(),
         main_struct.table_map.volatile.strategy_status.clone(),
         main_struct.registry.get_unwrap(),
     ));
@@ -0,0 +1,88 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use build::model::{EnumErrorCode, EnumRole, UserSetStrategyAccountRequest, UserSetStrategyAccountResponse};
+use gluesql::core::ast_builder::col;
+use gluesql_shared_sled_storage::SharedSledStorage;
+use lib::gluesql::{Table, TableSelectItem};
+use lib::handler::{RequestHandler, Response};
+use lib::toolbox::{CustomError, RequestContext};
+use trading_model::Exchange;
+
+use crate::db::gluesql::schema::ledger::LedgerQuery;
+use crate::db::gluesql::schema::DbRowLedger;
+use crate::db::worktable::order_manager::SharedOrderManager;
+use crate::endpoint_method::auth::ensure_user_role;
+use crate::execution::ExecutionAccounts;
+
+/// moves the orders of a strategy on a venue to another account, account 0 falls back to the default account.
+/// refused while the strategy holds open lots or live orders on the venue, they stay on the account they were
+/// opened on and are closed there first
+#[derive(Clone)]
+pub struct MethodUserSetStrategyAccount {
+    pub accounts: Arc<ExecutionAccounts>,
+    pub table_ledger: Table<SharedSledStorage, DbRowLedger>,
+    pub order_manager: SharedOrderManager,
+}
+#[async_trait(?Send)]
+impl RequestHandler for MethodUserSetStrategyAccount {
+    type Request = UserSetStrategyAccountRequest;
+
+    async fn handle(&self, ctx: RequestContext, req: Self::Request) -> Response<Self::Request> {
+        ensure_user_role(ctx, EnumRole::User)?;
+        let bad_request = |x: String| CustomError::new(EnumErrorCode::BadRequest, x);
+        let exchange =
+            Exchange::from_str(&req.exchange).map_err(|_| bad_request(format!("unknown exchange {}", req.exchange)))?;
+        if req.account != 0 && !self.accounts.is_connected(exchange, req.account) {
+            eyre::bail!(bad_request(format!(
+                "account {} is not connected on {exchange}",
+                req.account
+            )));
+        }
+        let live_orders = self
+            .order_manager
+            .read()
+            .await
+            .orders
+            .iter()
+            .filter(|x| x.strategy_id() == req.strategy_id as u64 && x.exchange() == exchange)
+            .filter(|x| !x.status().is_dead())
+            .count();
+        if live_orders > 0 {
+            eyre::bail!(bad_request(format!(
+                "strategy {} has {live_orders} live orders on {exchange}, cancel them first",
+                req.strategy_id
+            )));
+        }
+        let open_lots = LedgerQuery {
+            strategy_id: Some(req.strategy_id),
+            exchange: Some(exchange),
+            ..Default::default()
+        };
+        let open_lots = self
+            .table_ledger
+            .clone()
+            .select(
+                Some(open_lots.filter().and(col("closed_volume").lt(col("volume")))),
+                "id",
+            )
+            .await?;
+        if !open_lots.is_empty() {
+            eyre::bail!(bad_request(format!(
+                "strategy {} has {} open lots on {exchange}, close them first",
+                req.strategy_id,
+                open_lots.len()
+            )));
+        }
+        self.accounts.assign(req.strategy_id, exchange, req.account);
+        tracing::info!(
+            "strategy {} trades on {} account {}, set by {}",
+            req.strategy_id,
+            exchange,
+            req.account,
+            ctx.user_id
+        );
+        Ok(UserSetStrategyAccountResponse { success: true })
+    }
+}
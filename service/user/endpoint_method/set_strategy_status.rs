@@ -1,6 +1,7 @@
 use std::str::FromStr;
 
 use crate::endpoint_method::auth::ensure_user_role;
+use crate::execution::OrderRecovery;
 use crate::strategy::{StrategyStatus, StrategyStatusMap};
 use async_trait::async_trait;
 use lib::handler::{RequestHandler, Response};
@@ -9,6 +10,8 @@ use lib::toolbox::RequestContext;
 #[derive(Clone)]
 pub struct MethodUserSetStrategyStatus {
     pub strategy_status: std::sync::Arc<StrategyStatusMap>,
+    /// strategies are not enabled while the in-flight orders of the last run are being resolved
+    pub recovery: std::sync::Arc<OrderRecovery>,
 }
 #[async_trait(?Send)]
 impl RequestHandler for MethodUserSetStrategyStatus {
@@ -25,6 +28,10 @@ impl RequestHandler for MethodUserSetStrategyStatus {
                         status_to_set.status,
                     )
                 };
+                let recovering = self.recovery.pending_accounts();
+                if status == StrategyStatus::Enabled && !recovering.is_empty() {
+                    eyre::bail!("orders on {:?} are being recovered, try again later", recovering);
+                }
                 self.strategy_status.set(status_to_set.id as _, status);
                 tracing::debug!(
                     "status of strategy {} has been set to {}",
@@ -0,0 +1,49 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use build::model::{EnumErrorCode, EnumRole, UserStartExportRequest, UserStartExportResponse};
+use lib::handler::{RequestHandler, Response};
+use lib::toolbox::{CustomError, RequestContext};
+use trading_model::Exchange;
+
+use crate::db::columnar::{ExportDataset, ExportFormat, ExportJobMap};
+use crate::endpoint_method::auth::ensure_user_role;
+
+/// starts a background Parquet/CSV export, its progress is streamed by UserSubExportJobs
+#[derive(Clone)]
+pub struct MethodUserStartExport {
+    pub jobs: Arc<ExportJobMap>,
+}
+#[async_trait(?Send)]
+impl RequestHandler for MethodUserStartExport {
+    type Request = UserStartExportRequest;
+
+    async fn handle(&self, ctx: RequestContext, req: Self::Request) -> Response<Self::Request> {
+        ensure_user_role(ctx, EnumRole::Admin)?;
+        let bad_request = |x: String| CustomError::new(EnumErrorCode::BadRequest, x);
+        let dataset = ExportDataset::from_str(&req.dataset)
+            .map_err(|_| bad_request(format!("unknown dataset {}", req.dataset)))?;
+        let format = match &req.format {
+            Some(format) => {
+                ExportFormat::from_str(format).map_err(|_| bad_request(format!("unknown format {format}")))?
+            }
+            None => ExportFormat::Parquet,
+        };
+        let exchange = match &req.exchange {
+            Some(exchange) => {
+                Some(Exchange::from_str(exchange).map_err(|_| bad_request(format!("unknown exchange {exchange}")))?)
+            }
+            None => None,
+        };
+        let label = req.label.unwrap_or_else(|| "export".to_string());
+        let job = self
+            .jobs
+            .start(&label, dataset, format, req.time_start, req.time_end, exchange)
+            .map_err(|x| bad_request(x.to_string()))?;
+        Ok(UserStartExportResponse {
+            job_id: job.id as _,
+            directory: job.request.directory.display().to_string(),
+        })
+    }
+}
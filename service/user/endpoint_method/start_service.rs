@@ -6,11 +6,12 @@ use crate::ServiceStarter;
 use async_trait::async_trait;
 use lib::handler::{RequestHandler, Response};
 use lib::toolbox::RequestContext;
+use trading_exchange::model::AccountId;
 use trading_model::Exchange;
 
 pub struct MethodUserStartService {
     pub starter: ServiceStarter,
-    pub map: std::sync::Arc<parking_lot::RwLock<std::collections::HashMap<Exchange, ExecutionPrivateKey>>>,
+    pub map: std::sync::Arc<parking_lot::RwLock<std::collections::HashMap<(Exchange, AccountId), ExecutionPrivateKey>>>,
     pub tx_key: kanal::AsyncSender<ExecutionKeys>,
 }
 #[async_trait(?Send)]
@@ -2,6 +2,7 @@ use async_trait::async_trait;
 use build::model::{UserStatusRequest, UserStatusResponse};
 use lib::handler::{RequestHandler, Response};
 use lib::toolbox::RequestContext;
+use lib::ws::RATE_LIMITER;
 use trading_model::{now, NANOSECONDS_PER_MILLISECOND};
 
 #[derive(Debug, Default)]
@@ -17,9 +18,15 @@ impl RequestHandler for MethodUserStatus {
     type Request = UserStatusRequest;
 
     async fn handle(&self, _ctx: RequestContext, _req: Self::Request) -> Response<Self::Request> {
+        let counters = RATE_LIMITER.counters();
         Ok(UserStatusResponse {
             status: "ok".to_string(),
             time: now() / NANOSECONDS_PER_MILLISECOND,
+            active_connections: counters.active_connections as _,
+            rejected_connections: counters.rejected_connections as _,
+            throttled_requests: counters.throttled_requests as _,
+            oversized_messages: counters.oversized_messages as _,
+            rejected_subscriptions: counters.rejected_subscriptions as _,
         })
     }
 }
@@ -54,7 +54,7 @@ impl MethodUserSubBestBidAskAcrossExchangesWithPositionEvent {
         &self,
         new_request: build::model::UserSubBestBidAskAcrossExchangesWithPositionEventRequest,
         ctx: RequestContext,
-    ) {
+    ) -> eyre::Result<()> {
         // if no symbol is passed, set symbol is "";
         let symbol = new_request.symbol.as_deref().unwrap_or("");
 
@@ -69,7 +69,7 @@ impl MethodUserSubBestBidAskAcrossExchangesWithPositionEvent {
             |sub| {
                 sub.settings.insert(symbol.to_string());
             },
-        );
+        )
     }
 
     /// fully remove request and request_by_symbol associated to connection_id
@@ -168,7 +168,7 @@ impl RequestHandler for MethodUserSubBestBidAskAcrossExchangesWithPositionEvent
         // unsubscribe from other symbols with the connections
         this.unsubscribe(conn_id).await;
         //subscribe
-        this.subscribe(req, ctx).await;
+        this.subscribe(req, ctx).await?;
         let rows = this.table_event.select(Some(filter), "datetime DESC").await?;
         Ok(
             build::model::UserSubBestBidAskAcrossExchangesWithPositionEventResponse {
@@ -0,0 +1,101 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+
+use build::model::{UserSubCandlesRequest, UserSubCandlesResponse};
+use lib::handler::{RequestHandler, Response};
+use lib::toolbox::{ArcToolbox, RequestContext, TOOLBOX};
+use lib::ws::SubscriptionManager;
+use trading_exchange::utils::future::interval;
+use trading_model::{parse_candle_interval, Exchange, Symbol, Time};
+
+use crate::db::gluesql::schema::candle::DbRowCandle;
+use crate::endpoint_method::auth::ensure_user_role;
+use crate::endpoint_method::SubsManagerKey;
+use crate::signals::candles::CandleMap;
+
+/// number of recent candles returned on subscribe
+const CANDLE_SNAPSHOT_LIMIT: usize = 200;
+
+#[derive(Clone)]
+pub struct MethodUserSubCandles {
+    subs: Arc<RwLock<SubscriptionManager<UserSubCandlesRequest>>>,
+    candle_map: Arc<CandleMap>,
+    toolbox: Arc<tokio::sync::OnceCell<ArcToolbox>>,
+}
+
+impl MethodUserSubCandles {
+    pub fn new(candle_map: Arc<CandleMap>) -> Self {
+        let this = Self {
+            candle_map,
+            subs: Arc::new(RwLock::new(SubscriptionManager::new(
+                SubsManagerKey::UserSubCandle as _,
+            ))),
+            toolbox: Arc::new(Default::default()),
+        };
+        this.spawn();
+        this
+    }
+
+    // publishes the candle being built when a trade updated it since the last tick
+    fn spawn(&self) {
+        let this = self.clone();
+        tokio::task::spawn_local(async move {
+            let mut interval = interval(500);
+            let mut last_tick = Time::now();
+            loop {
+                interval.tick().await;
+                let now = Time::now();
+                let since = std::mem::replace(&mut last_tick, now);
+                let Some(toolbox) = this.toolbox.get() else { continue };
+                this.subs.write().await.publish_with_filter(toolbox, |req| {
+                    let exchange = Exchange::from_str(&req.settings.exchange).ok()?;
+                    let interval_ms = parse_candle_interval(&req.settings.interval).ok()?;
+                    let symbol = Symbol::from(req.settings.symbol.as_str());
+                    let candle = this.candle_map.last(exchange, &symbol, interval_ms)?;
+                    if candle.received_time <= since {
+                        return None;
+                    }
+                    Some(UserSubCandlesResponse {
+                        data: vec![DbRowCandle::try_from(candle).ok()?.into()],
+                    })
+                });
+            }
+        });
+    }
+}
+#[async_trait(?Send)]
+impl RequestHandler for MethodUserSubCandles {
+    type Request = UserSubCandlesRequest;
+
+    async fn handle(&self, ctx: RequestContext, req: Self::Request) -> Response<Self::Request> {
+        ensure_user_role(ctx, build::model::EnumRole::User)?;
+        let this = self.clone();
+        let _ = this.toolbox.set(TOOLBOX.get());
+
+        if req.unsub.unwrap_or_default() {
+            this.subs.write().await.unsubscribe(ctx.connection_id);
+            return Ok(UserSubCandlesResponse { data: vec![] });
+        }
+        let exchange = Exchange::from_str(&req.exchange)?;
+        let interval_ms = parse_candle_interval(&req.interval)?;
+        let symbol = Symbol::from(req.symbol.as_str());
+        this.subs
+            .write()
+            .await
+            .subscribe(ctx, req.clone(), |req0| req0.settings.clone_from(&req))?;
+
+        let candles = this
+            .candle_map
+            .last_n(exchange, &symbol, interval_ms, CANDLE_SNAPSHOT_LIMIT);
+        Ok(UserSubCandlesResponse {
+            data: candles
+                .into_iter()
+                .filter_map(|x| DbRowCandle::try_from(x).ok())
+                .map(|x| x.into())
+                .collect(),
+        })
+    }
+}
@@ -9,11 +9,11 @@ use tokio::sync::RwLock;
 use trading_model::Symbol;
 
 use crate::endpoint_method::auth::ensure_user_role;
-use crate::endpoint_method::SubsManagerKey;
+use crate::endpoint_method::{SubsManagerKey, STREAM_REPLAY_CAPACITY};
 use crate::events::price_change_and_diff::DbRowEventPriceChangeAndDiff;
 use lib::gluesql::{QueryFilter, Table, TableSelectItem};
 use lib::handler::{RequestHandler, Response};
-use lib::toolbox::{ArcToolbox, RequestContext, TOOLBOX};
+use lib::toolbox::{ArcToolbox, NoResponseError, RequestContext, TOOLBOX};
 use lib::utils::get_time_milliseconds;
 
 #[derive(Clone)]
@@ -31,9 +31,9 @@ impl MethodUserSubEvent1 {
     ) -> Self {
         let this = Self {
             rx_event,
-            subs1: Arc::new(RwLock::new(SubscriptionManager::new(
-                SubsManagerKey::UserSubStrategyEvent as _,
-            ))),
+            subs1: Arc::new(RwLock::new(
+                SubscriptionManager::new(SubsManagerKey::UserSubStrategyEvent as _).with_replay(STREAM_REPLAY_CAPACITY),
+            )),
             table_event,
             toolbox: Arc::new(Default::default()),
         };
@@ -42,7 +42,11 @@ impl MethodUserSubEvent1 {
     }
 
     /// assign request_by_symbol and request
-    async fn subscribe(&self, new_request: build::model::UserSubEvent1Request, ctx: RequestContext) {
+    async fn subscribe(
+        &self,
+        new_request: build::model::UserSubEvent1Request,
+        ctx: RequestContext,
+    ) -> eyre::Result<()> {
         // if no symbol is passed, set symbol is "";
         let symbol = new_request.symbol.as_deref().unwrap_or("");
 
@@ -57,7 +61,7 @@ impl MethodUserSubEvent1 {
             |sub| {
                 sub.settings.insert(symbol.to_string());
             },
-        );
+        )
     }
 
     /// fully remove request and request_by_symbol associated to connection_id
@@ -124,14 +128,31 @@ impl RequestHandler for MethodUserSubEvent1 {
             filter = filter.and(QueryFilter::symbol_id(Symbol::from(symbol)._hash()));
         }
         let conn_id = ctx.connection_id;
+        let resume_from_seq = req.resume_from_seq;
         // TODO: add unsubscribe others parameter in req
         // unsubscribe from other symbols with the connections
         this.unsubscribe(conn_id).await;
         //subscribe
-        this.subscribe(req, ctx).await;
+        this.subscribe(req, ctx).await?;
+        let mut subs = this.subs1.write().await;
+        let seq = subs.last_seq();
+        if let Some(resume_from_seq) = resume_from_seq {
+            // missed events are replayed as stream messages after the response, no snapshot needed
+            let response = build::model::UserSubEvent1Response {
+                data: vec![],
+                seq: seq as _,
+                gap: false,
+            };
+            if subs.resume(&TOOLBOX.get(), conn_id, resume_from_seq as _, &response) {
+                return Err(NoResponseError.into());
+            }
+        }
+        drop(subs);
         let rows = this.table_event.select(Some(filter), "datetime DESC").await?;
         Ok(build::model::UserSubEvent1Response {
             data: rows.into_iter().map(|x| x.into()).collect(),
+            seq: seq as _,
+            gap: resume_from_seq.is_some(),
         })
     }
 }
@@ -0,0 +1,94 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+
+use build::model::{EnumRole, UserExportJob, UserSubExportJobsRequest, UserSubExportJobsResponse};
+use lib::handler::{RequestHandler, Response};
+use lib::toolbox::{ArcToolbox, RequestContext, TOOLBOX};
+use lib::ws::SubscriptionManager;
+use trading_exchange::utils::future::interval;
+
+use crate::db::columnar::{ExportJob, ExportJobMap};
+use crate::endpoint_method::auth::ensure_user_role;
+use crate::endpoint_method::SubsManagerKey;
+
+impl From<ExportJob> for UserExportJob {
+    fn from(job: ExportJob) -> Self {
+        Self {
+            job_id: job.id as _,
+            dataset: job.request.dataset.to_string(),
+            format: job.request.format.to_string(),
+            status: job.status.to_string(),
+            directory: job.request.directory.display().to_string(),
+            steps_done: job.progress.steps_done as _,
+            steps_total: job.progress.steps_total as _,
+            rows: job.progress.rows as _,
+            files: job.progress.files as _,
+            error: job.error,
+            started_at: job.started_at,
+            updated_at: job.updated_at,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MethodUserSubExportJobs {
+    subs: Arc<RwLock<SubscriptionManager<UserSubExportJobsRequest>>>,
+    jobs: Arc<ExportJobMap>,
+    toolbox: Arc<tokio::sync::OnceCell<ArcToolbox>>,
+}
+
+impl MethodUserSubExportJobs {
+    pub fn new(jobs: Arc<ExportJobMap>) -> Self {
+        let this = Self {
+            jobs,
+            subs: Arc::new(RwLock::new(SubscriptionManager::new(
+                SubsManagerKey::UserSubExportJobs as _,
+            ))),
+            toolbox: Arc::new(Default::default()),
+        };
+        this.spawn();
+        this
+    }
+    fn snapshot(&self) -> Vec<UserExportJob> {
+        self.jobs.jobs().into_iter().map(UserExportJob::from).collect()
+    }
+
+    // publishes the progress of every job each second
+    fn spawn(&self) {
+        let this = self.clone();
+        tokio::task::spawn_local(async move {
+            let mut interval = interval(1_000);
+            loop {
+                interval.tick().await;
+                let Some(toolbox) = this.toolbox.get() else { continue };
+                let data = this.snapshot();
+                this.subs
+                    .write()
+                    .await
+                    .publish_with_filter(toolbox, |_| Some(UserSubExportJobsResponse { data: data.clone() }));
+            }
+        });
+    }
+}
+#[async_trait(?Send)]
+impl RequestHandler for MethodUserSubExportJobs {
+    type Request = UserSubExportJobsRequest;
+
+    async fn handle(&self, ctx: RequestContext, req: Self::Request) -> Response<Self::Request> {
+        ensure_user_role(ctx, EnumRole::Admin)?;
+        let this = self.clone();
+        let _ = this.toolbox.set(TOOLBOX.get());
+
+        if req.unsub.unwrap_or_default() {
+            this.subs.write().await.unsubscribe(ctx.connection_id);
+            return Ok(UserSubExportJobsResponse { data: vec![] });
+        }
+        this.subs
+            .write()
+            .await
+            .subscribe(ctx, req.clone(), |req0| req0.settings.clone_from(&req))?;
+        Ok(UserSubExportJobsResponse { data: this.snapshot() })
+    }
+}
@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+
+use build::model::{UserFeedHealth, UserSubFeedHealthRequest, UserSubFeedHealthResponse};
+use lib::handler::{RequestHandler, Response};
+use lib::toolbox::{ArcToolbox, RequestContext, TOOLBOX};
+use lib::ws::SubscriptionManager;
+use trading_exchange::utils::future::interval;
+
+use crate::endpoint_method::auth::ensure_user_role;
+use crate::endpoint_method::SubsManagerKey;
+use crate::strategy::feed_health::FeedHealthMap;
+
+#[derive(Clone)]
+pub struct MethodUserSubFeedHealth {
+    subs: Arc<RwLock<SubscriptionManager<UserSubFeedHealthRequest>>>,
+    health: Arc<FeedHealthMap>,
+    toolbox: Arc<tokio::sync::OnceCell<ArcToolbox>>,
+}
+
+impl MethodUserSubFeedHealth {
+    pub fn new(health: Arc<FeedHealthMap>) -> Self {
+        let this = Self {
+            health,
+            subs: Arc::new(RwLock::new(SubscriptionManager::new(
+                SubsManagerKey::UserSubFeedHealth as _,
+            ))),
+            toolbox: Arc::new(Default::default()),
+        };
+        this.spawn();
+        this
+    }
+    fn snapshot(&self, exchange: Option<&str>) -> Vec<UserFeedHealth> {
+        self.health
+            .values()
+            .into_iter()
+            .map(UserFeedHealth::from)
+            .filter(|x| exchange.map_or(true, |exchange| x.exchange == exchange))
+            .collect()
+    }
+
+    // publishes the health of every subscribed instrument each second
+    fn spawn(&self) {
+        let this = self.clone();
+        tokio::task::spawn_local(async move {
+            let mut interval = interval(1_000);
+            loop {
+                interval.tick().await;
+                let Some(toolbox) = this.toolbox.get() else { continue };
+                this.subs.write().await.publish_with_filter(toolbox, |req| {
+                    let data = this.snapshot(req.settings.exchange.as_deref());
+                    Some(UserSubFeedHealthResponse { data })
+                });
+            }
+        });
+    }
+}
+#[async_trait(?Send)]
+impl RequestHandler for MethodUserSubFeedHealth {
+    type Request = UserSubFeedHealthRequest;
+
+    async fn handle(&self, ctx: RequestContext, req: Self::Request) -> Response<Self::Request> {
+        ensure_user_role(ctx, build::model::EnumRole::User)?;
+        let this = self.clone();
+        let _ = this.toolbox.set(TOOLBOX.get());
+
+        if req.unsub.unwrap_or_default() {
+            this.subs.write().await.unsubscribe(ctx.connection_id);
+            return Ok(UserSubFeedHealthResponse { data: vec![] });
+        }
+        this.subs
+            .write()
+            .await
+            .subscribe(ctx, req.clone(), |req0| req0.settings.clone_from(&req))?;
+        Ok(UserSubFeedHealthResponse {
+            data: this.snapshot(req.exchange.as_deref()),
+        })
+    }
+}
@@ -86,7 +86,7 @@ impl RequestHandler for MethodUserSubFundingRates {
         this.subs
             .write()
             .await
-            .subscribe(ctx, req.clone(), |req0| req0.settings.clone_from(&req));
+            .subscribe(ctx, req.clone(), |req0| req0.settings.clone_from(&req))?;
         let mut filter = true.to_gluesql();
         if let Some(exchange) = req.exchange {
             let exchange = Exchange::from_str(&exchange)? as u8;
@@ -1,13 +1,13 @@
 use crate::db::gluesql::schema::ledger::DbRowLedger;
 use crate::endpoint_method::auth::ensure_user_role;
-use crate::endpoint_method::SubsManagerKey;
+use crate::endpoint_method::{SubsManagerKey, STREAM_REPLAY_CAPACITY};
 use async_trait::async_trait;
 use build::model::EnumRole;
 use gluesql_shared_sled_storage::SharedSledStorage;
 use lib::gluesql::TableSelectItem;
 use lib::gluesql::{QueryFilter, Table};
 use lib::handler::{RequestHandler, Response};
-use lib::toolbox::{ArcToolbox, RequestContext};
+use lib::toolbox::{ArcToolbox, NoResponseError, RequestContext, TOOLBOX};
 use lib::ws::{ConnectionId, SubscriptionManager};
 use std::collections::HashSet;
 use std::sync::Arc;
@@ -23,9 +23,9 @@ pub struct MethodUserSubLedgerStrategyOne {
 impl MethodUserSubLedgerStrategyOne {
     pub fn new(table: Table<SharedSledStorage, DbRowLedger>) -> Self {
         let this = Self {
-            subs: Arc::new(RwLock::new(SubscriptionManager::new(
-                SubsManagerKey::UserSubStrategyEvent as _,
-            ))),
+            subs: Arc::new(RwLock::new(
+                SubscriptionManager::new(SubsManagerKey::UserSubStrategyEvent as _).with_replay(STREAM_REPLAY_CAPACITY),
+            )),
             table,
             toolbox: Arc::new(Default::default()),
         };
@@ -34,7 +34,11 @@ impl MethodUserSubLedgerStrategyOne {
     }
 
     /// assign request_by_symbol and request
-    async fn subscribe(&self, new_request: build::model::UserSubLedgerStrategyOneRequest, ctx: RequestContext) {
+    async fn subscribe(
+        &self,
+        new_request: build::model::UserSubLedgerStrategyOneRequest,
+        ctx: RequestContext,
+    ) -> eyre::Result<()> {
         // if no symbol is passed, set symbol is "";
         let symbol = new_request.symbol.as_deref().unwrap_or("");
 
@@ -49,7 +53,7 @@ impl MethodUserSubLedgerStrategyOne {
             |sub| {
                 sub.settings.insert(symbol.to_string());
             },
-        );
+        )
     }
 
     /// fully remove request and request_by_symbol associated to connection_id
@@ -107,6 +111,7 @@ impl RequestHandler for MethodUserSubLedgerStrategyOne {
     async fn handle(&self, ctx: RequestContext, req: Self::Request) -> Response<Self::Request> {
         ensure_user_role(ctx, EnumRole::User)?;
         let mut this = self.clone();
+        let _ = this.toolbox.set(TOOLBOX.get());
         let dur = 1000 * 60 * 60;
         let now = lib::utils::get_time_milliseconds();
         let time_start = Some(now - dur);
@@ -114,15 +119,32 @@ impl RequestHandler for MethodUserSubLedgerStrategyOne {
         let filter = QueryFilter::range(time_start, time_end);
 
         let conn_id = ctx.connection_id;
+        let resume_from_seq = req.resume_from_seq;
         // unsubscribe from other symbols with the connections
         this.unsubscribe(conn_id).await;
 
         // subscribe
-        this.subscribe(req, ctx).await;
+        this.subscribe(req, ctx).await?;
 
+        let mut subs = this.subs.write().await;
+        let seq = subs.last_seq();
+        if let Some(resume_from_seq) = resume_from_seq {
+            // missed events are replayed as stream messages after the response, no snapshot needed
+            let response = build::model::UserSubLedgerStrategyOneResponse {
+                data: vec![],
+                seq: seq as _,
+                gap: false,
+            };
+            if subs.resume(&TOOLBOX.get(), conn_id, resume_from_seq as _, &response) {
+                return Err(NoResponseError.into());
+            }
+        }
+        drop(subs);
         let rows = this.table.select(Some(filter), "id DESC").await?;
         Ok(build::model::UserSubLedgerStrategyOneResponse {
             data: rows.into_iter().map(|x| x.into()).collect(),
+            seq: seq as _,
+            gap: resume_from_seq.is_some(),
         })
     }
 }
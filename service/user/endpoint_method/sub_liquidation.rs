@@ -0,0 +1,124 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use gluesql::core::ast_builder::col;
+use gluesql_derive::ToGlueSql;
+use gluesql_shared_sled_storage::SharedSledStorage;
+use tokio::sync::RwLock;
+
+use build::model::{UserSubLiquidationsRequest, UserSubLiquidationsResponse};
+use lib::gluesql::{QueryFilter, Table, TableGetIndex, TableSelectItem};
+use lib::handler::{RequestHandler, Response};
+use lib::toolbox::{ArcToolbox, RequestContext, TOOLBOX};
+use lib::ws::SubscriptionManager;
+use trading_exchange::utils::future::interval;
+use trading_model::{Exchange, Symbol};
+
+use crate::db::gluesql::schema::liquidation::DbRowLiquidation;
+use crate::endpoint_method::auth::ensure_user_role;
+use crate::endpoint_method::SubsManagerKey;
+
+/// number of recent liquidations returned on subscribe
+const LIQUIDATION_SNAPSHOT_LIMIT: u64 = 200;
+
+#[derive(Clone)]
+pub struct MethodUserSubLiquidations {
+    subs: Arc<RwLock<SubscriptionManager<UserSubLiquidationsRequest>>>,
+    table: Table<SharedSledStorage, DbRowLiquidation>,
+    toolbox: Arc<tokio::sync::OnceCell<ArcToolbox>>,
+}
+
+impl MethodUserSubLiquidations {
+    pub fn new(table: Table<SharedSledStorage, DbRowLiquidation>) -> Self {
+        let this = Self {
+            table,
+            subs: Arc::new(RwLock::new(SubscriptionManager::new(
+                SubsManagerKey::UserSubLiquidation as _,
+            ))),
+            toolbox: Arc::new(Default::default()),
+        };
+        this.spawn();
+        this
+    }
+
+    // publishes liquidations inserted since the last tick
+    fn spawn(&self) {
+        let mut this = self.clone();
+        tokio::task::spawn_local(async move {
+            let mut interval = interval(500);
+            let mut last_id = this.table.get_last_index().await.ok().flatten().unwrap_or_default();
+            loop {
+                interval.tick().await;
+                let Some(toolbox) = this.toolbox.get() else { continue };
+                let rows = match this.table.select(Some(DbRowLiquidation::after_id(last_id)), "id").await {
+                    Ok(rows) => rows,
+                    Err(err) => {
+                        tracing::warn!("failed to select liquidations: {err}");
+                        continue;
+                    }
+                };
+                let Some(last) = rows.last() else { continue };
+                last_id = last.id;
+                this.subs.write().await.publish_with_filter(toolbox, |req| {
+                    let data: Vec<_> = rows
+                        .iter()
+                        .filter(|row| {
+                            req.settings
+                                .exchange
+                                .as_ref()
+                                .map_or(true, |exchange| row.exchange().to_string() == *exchange)
+                        })
+                        .filter(|row| {
+                            req.settings
+                                .symbol
+                                .as_ref()
+                                .map_or(true, |symbol| row.symbol().as_str() == symbol)
+                        })
+                        .map(|row| row.clone().into())
+                        .collect();
+                    if data.is_empty() {
+                        return None;
+                    }
+                    Some(UserSubLiquidationsResponse { data })
+                });
+            }
+        });
+    }
+}
+#[async_trait(?Send)]
+impl RequestHandler for MethodUserSubLiquidations {
+    type Request = UserSubLiquidationsRequest;
+
+    async fn handle(&self, ctx: RequestContext, req: Self::Request) -> Response<Self::Request> {
+        ensure_user_role(ctx, build::model::EnumRole::User)?;
+        let mut this = self.clone();
+        let _ = this.toolbox.set(TOOLBOX.get());
+
+        if req.unsub.unwrap_or_default() {
+            this.subs.write().await.unsubscribe(ctx.connection_id);
+            return Ok(UserSubLiquidationsResponse { data: vec![] });
+        }
+        this.subs
+            .write()
+            .await
+            .subscribe(ctx, req.clone(), |req0| req0.settings.clone_from(&req))?;
+        let mut filter = true.to_gluesql();
+        if let Some(exchange) = req.exchange {
+            let exchange = Exchange::from_str(&exchange)? as u8;
+            filter = filter.and(col("exchange_id").eq(exchange.to_gluesql()));
+        }
+        if let Some(symbol) = req.symbol {
+            filter = filter.and(QueryFilter::symbol_id(Symbol::from(symbol)._hash()));
+        }
+
+        let mut rows = this
+            .table
+            .select_limit(Some(filter), "id DESC", Some(LIQUIDATION_SNAPSHOT_LIMIT))
+            .await?;
+        rows.reverse();
+        Ok(UserSubLiquidationsResponse {
+            data: rows.into_iter().map(|i| i.into()).collect(),
+        })
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -7,10 +8,10 @@ use tracing::error;
 
 use crate::db::worktable::order_manager::OrderManager;
 use crate::endpoint_method::auth::ensure_user_role;
-use crate::endpoint_method::SubsManagerKey;
+use crate::endpoint_method::{diff_stream_rows, SubsManagerKey, STREAM_REPLAY_CAPACITY};
 use build::model::{UserOrder, UserSubOrdersRequest, UserSubOrdersResponse};
 use lib::handler::{RequestHandler, Response};
-use lib::toolbox::{ArcToolbox, RequestContext, TOOLBOX};
+use lib::toolbox::{ArcToolbox, NoResponseError, RequestContext, TOOLBOX};
 use lib::ws::SubscriptionManager;
 use trading_exchange::utils::future::interval;
 use trading_model::NANOSECONDS_PER_MILLISECOND;
@@ -26,6 +27,8 @@ fn filter_order(req: &UserSubOrdersRequest, order: &UserOrder) -> bool {
 #[derive(Clone)]
 pub struct MethodUserSubOrders {
     sub: Arc<RwLock<SubscriptionManager<UserSubOrdersRequest>>>,
+    /// snapshot-then-delta subscribers keyed by strategy id, None subscribes to all strategies
+    sub_delta: Arc<RwLock<SubscriptionManager<UserSubOrdersRequest, Option<i32>>>>,
     toolbox: Arc<tokio::sync::OnceCell<ArcToolbox>>,
     order_manager: Arc<RwLock<OrderManager>>,
 }
@@ -35,6 +38,9 @@ impl MethodUserSubOrders {
             sub: Arc::new(RwLock::new(SubscriptionManager::new(
                 SubsManagerKey::UserSubOrders as u32,
             ))),
+            sub_delta: Arc::new(RwLock::new(
+                SubscriptionManager::new(SubsManagerKey::UserSubOrders as u32).with_replay(STREAM_REPLAY_CAPACITY),
+            )),
             toolbox: Arc::new(tokio::sync::OnceCell::new()),
             order_manager,
         };
@@ -44,6 +50,7 @@ impl MethodUserSubOrders {
     pub fn spawn(self) {
         tokio::task::spawn_local(async move {
             let mut interval = interval(1_000);
+            let mut last = HashMap::new();
             loop {
                 interval.tick().await;
 
@@ -62,6 +69,33 @@ impl MethodUserSubOrders {
                     let result = list.iter().filter(|x| filter_order(&ctx.settings, x)).collect_vec();
                     Some(result)
                 });
+                drop(sub);
+
+                let (changed, removed) =
+                    diff_stream_rows(&mut last, &list, |x| format!("{}/{}", x.strategy_id, x.client_id));
+                let mut delta: Vec<UserOrder> = changed.into_iter().cloned().collect();
+                delta.sort_by_key(|x| x.strategy_id);
+                let mut sub_delta = self.sub_delta.write().await;
+                for (strategy_id, orders) in &delta.into_iter().group_by(|x| x.strategy_id) {
+                    let orders = orders.collect_vec();
+                    sub_delta.publish_to_keys(toolbox, &[&None, &Some(strategy_id)], &orders);
+                }
+                // an order dropped by the order manager is published once as an explicit removal
+                let removed = removed.into_iter().sorted().group_by(|key| {
+                    key.split_once('/')
+                        .and_then(|(strategy_id, _)| strategy_id.parse::<i32>().ok())
+                });
+                for (strategy_id, keys) in &removed {
+                    let Some(strategy_id) = strategy_id else {
+                        continue;
+                    };
+                    sub_delta.publish_removal_to_keys(
+                        toolbox,
+                        SubsManagerKey::UserSubOrdersRemoved as u32,
+                        &[&None, &Some(strategy_id)],
+                        keys.collect(),
+                    );
+                }
             }
         });
     }
@@ -112,14 +146,52 @@ impl RequestHandler for MethodUserSubOrders {
     async fn handle(&self, ctx: RequestContext, req: Self::Request) -> Response<Self::Request> {
         ensure_user_role(ctx, build::model::EnumRole::User)?;
         let _ = self.toolbox.set(TOOLBOX.get());
+        self.sub.write().await.unsubscribe(ctx.connection_id);
+        self.sub_delta.write().await.unsubscribe(ctx.connection_id);
         if req.unsubscribe.unwrap_or_default() {
-            self.sub.write().await.unsubscribe(ctx.connection_id);
-        } else {
+            return Ok(UserSubOrdersResponse {
+                data: vec![],
+                seq: 0,
+                gap: false,
+            });
+        }
+        // resuming is only possible on the delta stream
+        let delta = req.snapshot_then_delta.unwrap_or_default() || req.resume_from_seq.is_some();
+        if !delta {
             self.sub.write().await.subscribe(ctx, req.clone(), |sub| {
                 sub.settings.clone_from(&req);
+            })?;
+            let data = self.to_list().await?;
+            return Ok(UserSubOrdersResponse {
+                data,
+                seq: 0,
+                gap: false,
             });
         }
+
+        let mut sub = self.sub_delta.write().await;
+        sub.subscribe_with_keys(ctx, vec![req.strategy_id], req.clone(), |sub| {
+            sub.settings.clone_from(&req);
+        })?;
+        let seq = sub.last_seq();
+        if let Some(resume_from_seq) = req.resume_from_seq {
+            // missed deltas are replayed as stream messages after the response, no snapshot needed
+            let response = UserSubOrdersResponse {
+                data: vec![],
+                seq: seq as _,
+                gap: false,
+            };
+            if sub.resume(&TOOLBOX.get(), ctx.connection_id, resume_from_seq as _, &response) {
+                return Err(NoResponseError.into());
+            }
+        }
+        drop(sub);
         let data = self.to_list().await?;
-        Ok(UserSubOrdersResponse { data })
+        let data = data.into_iter().filter(|x| filter_order(&req, x)).collect();
+        Ok(UserSubOrdersResponse {
+            data,
+            seq: seq as _,
+            gap: req.resume_from_seq.is_some(),
+        })
     }
 }
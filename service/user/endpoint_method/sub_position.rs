@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -6,16 +7,18 @@ use tracing::error;
 
 use crate::db::worktable::position_manager::PositionManager;
 use crate::endpoint_method::auth::ensure_user_role;
-use crate::endpoint_method::SubsManagerKey;
+use crate::endpoint_method::{diff_stream_rows, SubsManagerKey, STREAM_REPLAY_CAPACITY};
 use build::model::{UserPosition, UserSubPositionRequest, UserSubPositionResponse};
 use lib::handler::{RequestHandler, Response};
-use lib::toolbox::{ArcToolbox, RequestContext, TOOLBOX};
+use lib::toolbox::{ArcToolbox, NoResponseError, RequestContext, TOOLBOX};
 use lib::ws::SubscriptionManager;
 use trading_exchange::utils::future::interval;
 
 #[derive(Clone)]
 pub struct MethodUserSubPosition {
     sub: Arc<RwLock<SubscriptionManager<UserSubPositionRequest>>>,
+    /// snapshot-then-delta subscribers, only receive changed positions and can resume
+    sub_delta: Arc<RwLock<SubscriptionManager<UserSubPositionRequest>>>,
     toolbox: Arc<tokio::sync::OnceCell<ArcToolbox>>,
     portfolio_manager: Arc<RwLock<PositionManager>>,
 }
@@ -25,6 +28,9 @@ impl MethodUserSubPosition {
             sub: Arc::new(RwLock::new(SubscriptionManager::new(
                 SubsManagerKey::UserSubPositions as u32,
             ))),
+            sub_delta: Arc::new(RwLock::new(
+                SubscriptionManager::new(SubsManagerKey::UserSubPositions as u32).with_replay(STREAM_REPLAY_CAPACITY),
+            )),
             toolbox: Arc::new(tokio::sync::OnceCell::new()),
             portfolio_manager,
         };
@@ -34,6 +40,7 @@ impl MethodUserSubPosition {
     pub fn spawn(self) {
         tokio::task::spawn_local(async move {
             let mut interval = interval(1_000);
+            let mut last = HashMap::new();
             loop {
                 interval.tick().await;
 
@@ -49,6 +56,20 @@ impl MethodUserSubPosition {
                 };
 
                 self.sub.write().await.publish_to_all(toolbox, &list);
+
+                let (changed, removed) = diff_stream_rows(&mut last, &list, |x| x.id.to_string());
+                if changed.is_empty() && removed.is_empty() {
+                    continue;
+                }
+                let delta: Vec<UserPosition> = changed.into_iter().cloned().collect();
+                let mut sub_delta = self.sub_delta.write().await;
+                if !delta.is_empty() {
+                    sub_delta.publish_to_all(toolbox, &delta);
+                }
+                // a closed position is published once as an explicit removal
+                if !removed.is_empty() {
+                    sub_delta.publish_removal_to_all(toolbox, SubsManagerKey::UserSubPositionsRemoved as u32, removed);
+                }
             }
         });
     }
@@ -59,6 +80,7 @@ impl MethodUserSubPosition {
                 id: pos.id() as _,
                 cloid: pos.cloid().map(|x| x.to_string()),
                 exchange: pos.exchange().to_string(),
+                account: pos.account(),
                 symbol: pos.symbol().to_string(),
                 size: pos.size(),
                 filled_size: pos.filled_size(),
@@ -86,14 +108,51 @@ impl RequestHandler for MethodUserSubPosition {
     async fn handle(&self, ctx: RequestContext, req: Self::Request) -> Response<Self::Request> {
         ensure_user_role(ctx, build::model::EnumRole::User)?;
         let _ = self.toolbox.set(TOOLBOX.get());
+        self.sub.write().await.unsubscribe(ctx.connection_id);
+        self.sub_delta.write().await.unsubscribe(ctx.connection_id);
         if req.unsubscribe.unwrap_or_default() {
-            self.sub.write().await.unsubscribe(ctx.connection_id);
-        } else {
+            return Ok(UserSubPositionResponse {
+                data: vec![],
+                seq: 0,
+                gap: false,
+            });
+        }
+        // resuming is only possible on the delta stream
+        let delta = req.snapshot_then_delta.unwrap_or_default() || req.resume_from_seq.is_some();
+        if !delta {
             self.sub.write().await.subscribe(ctx, req.clone(), |sub| {
                 sub.settings.clone_from(&req);
+            })?;
+            let data = self.to_list().await?;
+            return Ok(UserSubPositionResponse {
+                data,
+                seq: 0,
+                gap: false,
             });
         }
+
+        let mut sub = self.sub_delta.write().await;
+        sub.subscribe(ctx, req.clone(), |sub| {
+            sub.settings.clone_from(&req);
+        })?;
+        let seq = sub.last_seq();
+        if let Some(resume_from_seq) = req.resume_from_seq {
+            // missed deltas are replayed as stream messages after the response, no snapshot needed
+            let response = UserSubPositionResponse {
+                data: vec![],
+                seq: seq as _,
+                gap: false,
+            };
+            if sub.resume(&TOOLBOX.get(), ctx.connection_id, resume_from_seq as _, &response) {
+                return Err(NoResponseError.into());
+            }
+        }
+        drop(sub);
         let data = self.to_list().await?;
-        Ok(UserSubPositionResponse { data })
+        Ok(UserSubPositionResponse {
+            data,
+            seq: seq as _,
+            gap: req.resume_from_seq.is_some(),
+        })
     }
 }
@@ -1,6 +1,7 @@
 use crate::db::gluesql::schema::DbRowPriceVolume;
 use crate::db::gluesql::AssetIndexTable;
 use crate::endpoint_method::SubsManagerKey;
+use crate::strategy::instrument_refresh::DynamicInstrumentManager;
 use async_trait::async_trait;
 use build::model::{EnumErrorCode, Price, SubS3TerminalBestAskBestBidRequest, SubS3TerminalBestAskBestBidResponse};
 use eyre::{ContextCompat, Result};
@@ -19,7 +20,7 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::*;
 use trading_exchange::utils::future::interval;
-use trading_model::{Asset, Exchange, SharedInstrumentManager, Symbol};
+use trading_model::{Asset, Exchange, Symbol};
 
 #[derive(Clone)]
 pub struct MethodSubS3TerminalBestAskBestBid {
@@ -27,13 +28,13 @@ pub struct MethodSubS3TerminalBestAskBestBid {
     pub index_table: Arc<AssetIndexTable<SharedMemoryStorage, DbRowPriceVolume>>,
 
     toolbox: Arc<tokio::sync::OnceCell<ArcToolbox>>,
-    instruments: SharedInstrumentManager,
+    instruments: Arc<DynamicInstrumentManager>,
 }
 
 impl MethodSubS3TerminalBestAskBestBid {
     pub fn new(
         index_table: AssetIndexTable<SharedMemoryStorage, DbRowPriceVolume>,
-        instruments: SharedInstrumentManager,
+        instruments: Arc<DynamicInstrumentManager>,
     ) -> Self {
         let this = Self {
             index_table: Arc::new(index_table),
@@ -47,7 +48,7 @@ impl MethodSubS3TerminalBestAskBestBid {
     }
 
     /// assign request_by_symbol and request
-    async fn subscribe(&self, asset: Asset, ctx: RequestContext) {
+    async fn subscribe(&self, asset: Asset, ctx: RequestContext) -> eyre::Result<()> {
         self.subs.write().await.subscribe_with(
             ctx,
             vec![asset.to_string()],
@@ -59,7 +60,7 @@ impl MethodSubS3TerminalBestAskBestBid {
             |sub| {
                 sub.settings.insert(asset.to_string());
             },
-        );
+        )
     }
 
     /// fully remove request and request_by_symbol associated to connection_id
@@ -151,7 +152,9 @@ impl RequestHandler for MethodSubS3TerminalBestAskBestBid {
         let symbol = Symbol::from_str(req.symbol.as_str()).unwrap();
         let ins = self
             .instruments
+            .load()
             .get(&(Exchange::BinanceFutures, symbol.clone()))
+            .cloned()
             .with_context(|| CustomError::new(EnumErrorCode::NotFound, format!("symbol not found: {}", symbol)))?;
 
         let now_ms = get_time_milliseconds();
@@ -162,7 +165,7 @@ impl RequestHandler for MethodSubS3TerminalBestAskBestBid {
             // unsubscribe from other symbols with the connections
             this.unsubscribe(conn_id).await;
         }
-        this.subscribe(ins.base.asset.clone(), ctx).await;
+        this.subscribe(ins.base.asset.clone(), ctx).await?;
 
         let table = this.index_table.tables.get(&ins.base.asset).unwrap().clone();
         let rows = Self::query_by_symbol_and_time(table, req.symbol.as_str(), now_ms - 300_000, now_ms).await?;
@@ -36,7 +36,7 @@ impl MethodUserSubPrice0 {
     }
 
     /// assign request_by_symbol and request
-    async fn subscribe(&self, new_request: UserSubPrice0Request, ctx: RequestContext) {
+    async fn subscribe(&self, new_request: UserSubPrice0Request, ctx: RequestContext) -> eyre::Result<()> {
         self.subs.write().await.subscribe_with(
             ctx,
             vec![new_request.symbol.clone()],
@@ -48,7 +48,7 @@ impl MethodUserSubPrice0 {
             |sub| {
                 sub.settings.insert(new_request.symbol.clone());
             },
-        );
+        )
     }
 
     /// fully remove request and request_by_symbol associated to connection_id
@@ -107,7 +107,7 @@ impl RequestHandler for MethodUserSubPrice0 {
             // unsubscribe from other symbols with the connections
             this.unsubscribe(conn_id).await;
         }
-        this.subscribe(req, ctx).await;
+        this.subscribe(req, ctx).await?;
         let worktable = this.worktable.read().await;
         let rows = worktable.select_between(now_ms - 300_000, now_ms, Some(&symbol_id));
         Ok(UserSubPrice0Response {
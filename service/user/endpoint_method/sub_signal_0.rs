@@ -43,7 +43,7 @@ impl MethodUserSubSignal0 {
     }
 
     /// assign request_by_symbol and request
-    async fn subscribe(&self, new_request: UserSubSignal0Request, ctx: RequestContext) {
+    async fn subscribe(&self, new_request: UserSubSignal0Request, ctx: RequestContext) -> eyre::Result<()> {
         // if no symbol is passed, set symbol is "";
         let symbol = new_request.symbol.clone().unwrap_or("".to_string());
 
@@ -58,7 +58,7 @@ impl MethodUserSubSignal0 {
             |sub| {
                 sub.settings.insert(symbol.clone());
             },
-        );
+        )
     }
 
     /// fully remove request and request_by_symbol associated to connection_id
@@ -123,7 +123,7 @@ impl RequestHandler for MethodUserSubSignal0 {
             // unsubscribe from other symbols with the connections
             this.unsubscribe(conn_id).await;
         }
-        this.subscribe(req, ctx).await;
+        this.subscribe(req, ctx).await?;
 
         let rows = this.table.select(Some(filter), "datetime DESC").await?;
         Ok(build::model::UserSubSignal0Response {
@@ -40,7 +40,11 @@ impl MethodUserSubSignal1 {
     }
 
     /// assign request_by_symbol and request
-    async fn subscribe(&self, new_request: build::model::UserSubSignal1Request, ctx: RequestContext) {
+    async fn subscribe(
+        &self,
+        new_request: build::model::UserSubSignal1Request,
+        ctx: RequestContext,
+    ) -> eyre::Result<()> {
         let symbol = new_request.symbol.clone().unwrap_or("".to_string());
 
         self.subs.write().await.subscribe_with(
@@ -54,7 +58,7 @@ impl MethodUserSubSignal1 {
             |sub| {
                 sub.settings.insert(symbol.clone());
             },
-        );
+        )
     }
 
     /// fully remove request and request_by_symbol associated to connection_id
@@ -133,7 +137,7 @@ impl RequestHandler for MethodUserSubSignal1 {
         this.unsubscribe(conn_id).await;
 
         //subscribe
-        this.subscribe(req, ctx).await;
+        this.subscribe(req, ctx).await?;
 
         let order = "datetime DESC";
         let row_change = this.table_change.select(Some(filter.clone()), order).await?;
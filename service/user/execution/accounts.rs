@@ -0,0 +1,97 @@
+use std::collections::{BTreeMap, HashMap};
+
+use parking_lot::RwLock;
+use trading_exchange::model::AccountId;
+use trading_model::Exchange;
+
+use crate::config::StrategyAccountConfig;
+use crate::db::gluesql::schema::common::StrategyId;
+
+/// the accounts connected on every venue and the account each strategy trades on.
+/// account ids are the ids of the stored keys, 0 is no account
+#[derive(Default)]
+pub struct ExecutionAccounts {
+    /// venue account id (api key or address) of the connected accounts
+    connected: RwLock<BTreeMap<(Exchange, AccountId), String>>,
+    assigned: RwLock<HashMap<(StrategyId, Exchange), AccountId>>,
+}
+impl ExecutionAccounts {
+    pub fn new(assignments: &[StrategyAccountConfig]) -> Self {
+        let this = Self::default();
+        for x in assignments {
+            this.assign(x.strategy_id, x.exchange, x.account);
+        }
+        this
+    }
+    pub fn connect(&self, exchange: Exchange, account: AccountId, account_id: &str) {
+        self.connected
+            .write()
+            .insert((exchange, account), account_id.to_string());
+    }
+    pub fn is_connected(&self, exchange: Exchange, account: AccountId) -> bool {
+        self.connected.read().contains_key(&(exchange, account))
+    }
+    pub fn connected(&self) -> Vec<(Exchange, AccountId, String)> {
+        self.connected
+            .read()
+            .iter()
+            .map(|((exchange, account), account_id)| (*exchange, *account, account_id.clone()))
+            .collect()
+    }
+    /// account 0 removes the assignment
+    pub fn assign(&self, strategy_id: StrategyId, exchange: Exchange, account: AccountId) {
+        let mut assigned = self.assigned.write();
+        if account == 0 {
+            assigned.remove(&(strategy_id, exchange));
+        } else {
+            assigned.insert((strategy_id, exchange), account);
+        }
+    }
+    pub fn assignments(&self) -> Vec<(StrategyId, Exchange, AccountId)> {
+        let mut assignments: Vec<_> = self
+            .assigned
+            .read()
+            .iter()
+            .map(|((strategy_id, exchange), account)| (*strategy_id, *exchange, *account))
+            .collect();
+        assignments.sort();
+        assignments
+    }
+    /// the account assigned to the strategy, otherwise the connected account of the venue with the lowest id
+    pub fn resolve(&self, strategy_id: StrategyId, exchange: Exchange) -> AccountId {
+        if let Some(account) = self.assigned.read().get(&(strategy_id, exchange)) {
+            return *account;
+        }
+        self.connected
+            .read()
+            .keys()
+            .find(|(x, _)| *x == exchange)
+            .map(|(_, account)| *account)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_assigned_or_default_account() {
+        let accounts = ExecutionAccounts::new(&[StrategyAccountConfig {
+            strategy_id: 2,
+            exchange: Exchange::Hyperliquid,
+            account: 7,
+        }]);
+        assert_eq!(accounts.resolve(1, Exchange::Hyperliquid), 0);
+
+        accounts.connect(Exchange::Hyperliquid, 7, "0xsub");
+        accounts.connect(Exchange::Hyperliquid, 3, "0xmain");
+        assert_eq!(accounts.resolve(1, Exchange::Hyperliquid), 3);
+        assert_eq!(accounts.resolve(2, Exchange::Hyperliquid), 7);
+        assert_eq!(accounts.resolve(2, Exchange::BinanceFutures), 0);
+
+        accounts.assign(2, Exchange::Hyperliquid, 0);
+        assert_eq!(accounts.resolve(2, Exchange::Hyperliquid), 3);
+        assert!(accounts.assignments().is_empty());
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use parking_lot::Mutex;
+use trading_exchange::model::{AccountId, UpdateBook};
+use trading_model::{Exchange, Time};
+
+/// what raised a halt, and so what lifts it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HaltKind {
+    /// reported by the accounting of the connection, lifted by its next book once acknowledged
+    Accounting,
+    /// raised by the router, which no book reports. lifted by the acknowledgement itself
+    Router,
+}
+
+#[derive(Debug, Clone)]
+pub struct AccountingHalt {
+    pub exchange: Exchange,
+    pub account: AccountId,
+    pub kind: HaltKind,
+    pub reason: String,
+    pub datetime: Time,
+    /// acknowledged by a human, lifted once the connection reports the account as no longer halted
+    pub acknowledged: bool,
+}
+
+/// halts raised and lifted by a book
+#[derive(Debug, Default)]
+pub struct HaltChanges {
+    pub halted: Vec<AccountingHalt>,
+    pub lifted: Vec<AccountingHalt>,
+}
+
+/// accounts whose accounting found an inconsistency, no order is placed on them until a human acknowledges it
+#[derive(Default)]
+pub struct AccountingHalts {
+    halted: Mutex<HashMap<(Exchange, AccountId), AccountingHalt>>,
+}
+impl AccountingHalts {
+    pub fn new() -> Self {
+        Self {
+            halted: Mutex::new(HashMap::new()),
+        }
+    }
+    /// the halts reported by the book. acknowledged halts are lifted once the book no longer reports them,
+    /// a book sent before the connection processed the acknowledgement keeps them
+    pub fn update(&self, book: &UpdateBook) -> HaltChanges {
+        let mut halted = self.halted.lock();
+        let mut changes = HaltChanges::default();
+        for (exchange, status) in &book.source_status {
+            let key = (*exchange, book.account);
+            let Some(reason) = &status.halt_reason else {
+                if halted
+                    .get(&key)
+                    .is_some_and(|x| x.kind == HaltKind::Accounting && x.acknowledged)
+                {
+                    changes.lifted.extend(halted.remove(&key));
+                }
+                continue;
+            };
+            if halted.contains_key(&key) {
+                continue;
+            }
+            let halt = AccountingHalt {
+                exchange: *exchange,
+                account: book.account,
+                kind: HaltKind::Accounting,
+                reason: reason.clone(),
+                datetime: Time::now(),
+                acknowledged: false,
+            };
+            halted.insert(key, halt.clone());
+            changes.halted.push(halt);
+        }
+        changes
+    }
+    pub fn get(&self, exchange: Exchange, account: AccountId) -> Option<AccountingHalt> {
+        self.halted.lock().get(&(exchange, account)).cloned()
+    }
+    pub fn list(&self) -> Vec<AccountingHalt> {
+        let mut halts: Vec<_> = self.halted.lock().values().cloned().collect();
+        halts.sort_by_key(|x| x.datetime);
+        halts
+    }
+    /// halts the account for a reason found outside of its accounting, returns the halt when it was not halted yet
+    pub fn halt(&self, exchange: Exchange, account: AccountId, reason: String) -> Option<AccountingHalt> {
+        let mut halted = self.halted.lock();
+        if halted.contains_key(&(exchange, account)) {
+            return None;
+        }
+        let halt = AccountingHalt {
+            exchange,
+            account,
+            kind: HaltKind::Router,
+            reason,
+            datetime: Time::now(),
+            acknowledged: false,
+        };
+        halted.insert((exchange, account), halt.clone());
+        Some(halt)
+    }
+    /// a halt of the router is lifted right away. orders stay rejected on an accounting halt until
+    /// the next book of the account lifts it
+    pub fn acknowledge(&self, exchange: Exchange, account: AccountId) -> Option<AccountingHalt> {
+        let mut halted = self.halted.lock();
+        let halt = halted.get_mut(&(exchange, account))?;
+        halt.acknowledged = true;
+        let halt = halt.clone();
+        if halt.kind == HaltKind::Router {
+            halted.remove(&(exchange, account));
+        }
+        Some(halt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use trading_exchange::model::SourceStatus;
+
+    use super::*;
+
+    #[test]
+    fn test_halt_until_acknowledged() {
+        let halts = AccountingHalts::new();
+        let mut book = UpdateBook {
+            account: 3,
+            ..UpdateBook::default()
+        };
+        book.source_status.insert(
+            Exchange::BinanceFutures,
+            SourceStatus {
+                halt_reason: Some("position mismatch".to_string()),
+                ..SourceStatus::default()
+            },
+        );
+        book.source_status
+            .insert(Exchange::Hyperliquid, SourceStatus::default());
+
+        assert_eq!(halts.update(&book).halted.len(), 1);
+        assert!(halts.update(&book).halted.is_empty());
+        assert!(halts.get(Exchange::Hyperliquid, 3).is_none());
+        assert!(halts.get(Exchange::BinanceFutures, 4).is_none());
+        assert_eq!(
+            halts.get(Exchange::BinanceFutures, 3).unwrap().reason,
+            "position mismatch"
+        );
+
+        assert!(halts.acknowledge(Exchange::BinanceFutures, 3).is_some());
+        // the book was sent before the connection took the acknowledgement
+        let changes = halts.update(&book);
+        assert!(changes.halted.is_empty() && changes.lifted.is_empty());
+        assert!(halts.get(Exchange::BinanceFutures, 3).is_some());
+
+        book.source_status
+            .insert(Exchange::BinanceFutures, SourceStatus::default());
+        assert_eq!(halts.update(&book).lifted.len(), 1);
+        assert!(halts.list().is_empty());
+    }
+
+    #[test]
+    fn test_router_halt_lifted_by_acknowledgement() {
+        let halts = AccountingHalts::new();
+        let halt = halts.halt(Exchange::Hyperliquid, 3, "orders left unresolved".to_string());
+        assert_eq!(halt.unwrap().kind, HaltKind::Router);
+        assert!(halts.halt(Exchange::Hyperliquid, 3, "again".to_string()).is_none());
+        // no book reports it, a clean one does not lift it either
+        let mut book = UpdateBook {
+            account: 3,
+            ..UpdateBook::default()
+        };
+        book.source_status
+            .insert(Exchange::Hyperliquid, SourceStatus::default());
+        assert!(halts.update(&book).lifted.is_empty());
+        assert!(halts.get(Exchange::Hyperliquid, 3).is_some());
+
+        assert!(halts.acknowledge(Exchange::Hyperliquid, 3).is_some());
+        assert!(halts.get(Exchange::Hyperliquid, 3).is_none());
+    }
+}
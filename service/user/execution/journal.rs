@@ -0,0 +1,334 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use eyre::{Context, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use trading_exchange::model::{
+    AccountId, OrderCid, RequestCancelOrder, RequestPlaceOrder, RequestQueryOrder, SyncOrders, UpdateOrder,
+};
+use trading_model::{Exchange, Time};
+
+/// the journal is rewritten with the in-flight orders only once this many records were appended
+const COMPACT_RECORDS: usize = 100_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum JournalRecord {
+    PlaceOrder(RequestPlaceOrder),
+    CancelOrder(RequestCancelOrder),
+    UpdateOrder(UpdateOrder),
+}
+
+/// append-only journal of the order requests and updates, one JSON record per line.
+/// every record is synced to disk before `append` returns, so no request reaches a venue untracked.
+/// the writes run on the blocking pool, the runtime thread is not held while the disk syncs
+pub struct OrderJournal {
+    path: PathBuf,
+    file: Arc<File>,
+    /// latest state of the orders that are not dead yet, keyed by client id
+    orders: HashMap<OrderCid, UpdateOrder>,
+    appended: usize,
+}
+impl OrderJournal {
+    /// replays the journal and compacts it down to the in-flight orders
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let mut orders = HashMap::new();
+        if path.exists() {
+            let file = File::open(&path).with_context(|| format!("failed to open {}", path.display()))?;
+            for (i, line) in BufReader::new(file).lines().enumerate() {
+                let line = line.with_context(|| format!("failed to read {}", path.display()))?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str::<JournalRecord>(&line) {
+                    Ok(record) => apply(&mut orders, &record),
+                    // the last record is torn when the process died while writing it
+                    Err(err) => warn!("skipping record {} of {}: {err}", i + 1, path.display()),
+                }
+            }
+        } else if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        let file = write_compacted(&path, &orders)?;
+        info!(
+            "order journal {} replayed, {} orders in flight",
+            path.display(),
+            orders.len()
+        );
+        Ok(Self {
+            path,
+            file: Arc::new(file),
+            orders,
+            appended: 0,
+        })
+    }
+    /// orders that were neither filled nor cancelled when the journal was last written
+    pub fn in_flight(&self) -> Vec<UpdateOrder> {
+        self.orders.values().cloned().collect()
+    }
+    pub async fn append(&mut self, record: &JournalRecord) -> Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        let file = self.file.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut file = &*file;
+            file.write_all(&line).and_then(|_| file.sync_data())
+        })
+        .await?
+        .with_context(|| format!("failed to append to {}", self.path.display()))?;
+        apply(&mut self.orders, record);
+        self.appended += 1;
+        if self.appended >= COMPACT_RECORDS {
+            let path = self.path.clone();
+            let orders = self.orders.clone();
+            // the record is on disk already, a failed compaction is retried with the next one
+            match tokio::task::spawn_blocking(move || write_compacted(&path, &orders)).await? {
+                Ok(file) => {
+                    self.file = Arc::new(file);
+                    self.appended = 0;
+                }
+                Err(err) => warn!("failed to compact order journal: {err:?}"),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// rewrites the journal with one update per in-flight order, returns the file to append to
+fn write_compacted(path: &Path, orders: &HashMap<OrderCid, UpdateOrder>) -> Result<File> {
+    let staging = path.with_extension("compact");
+    {
+        let mut file = File::create(&staging).with_context(|| format!("failed to create {}", staging.display()))?;
+        for order in orders.values() {
+            serde_json::to_writer(&mut file, &JournalRecord::UpdateOrder(order.clone()))?;
+            file.write_all(b"\n")?;
+        }
+        file.sync_all()?;
+    }
+    std::fs::rename(&staging, path).with_context(|| format!("failed to replace {}", path.display()))?;
+    OpenOptions::new()
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to open {}", path.display()))
+}
+
+fn apply(orders: &mut HashMap<OrderCid, UpdateOrder>, record: &JournalRecord) {
+    match record {
+        JournalRecord::PlaceOrder(request) => {
+            orders.insert(request.order_cid.clone(), request.to_update());
+        }
+        JournalRecord::CancelOrder(request) => merge(orders, &request.to_update()),
+        JournalRecord::UpdateOrder(update) => merge(orders, update),
+    }
+}
+/// updates of orders that were not placed through the journal are ignored
+fn merge(orders: &mut HashMap<OrderCid, UpdateOrder>, update: &UpdateOrder) {
+    let key = if orders.contains_key(&update.client_id) {
+        update.client_id.clone()
+    } else {
+        let found = orders
+            .iter()
+            .find(|(_, x)| !update.server_id.is_empty() && x.server_id == update.server_id);
+        match found {
+            Some((key, _)) => key.clone(),
+            None => return,
+        }
+    };
+    let order = orders.get_mut(&key).unwrap();
+    if !update.server_id.is_empty() {
+        order.server_id = update.server_id.clone();
+    }
+    if update.status > order.status {
+        order.status = update.status;
+    }
+    order.filled_size = order.filled_size.max(update.filled_size);
+    order.update_lt = update.update_lt;
+    if order.status.is_dead() {
+        orders.remove(&key);
+    }
+}
+
+/// what the first snapshot of open orders of an account resolved
+pub struct RecoveryResolution {
+    pub exchange: Exchange,
+    pub account: AccountId,
+    /// adopted orders
+    pub updates: Vec<UpdateOrder>,
+    pub cancels: Vec<RequestCancelOrder>,
+    /// orders no longer open, whose final state the venue is asked for
+    pub queries: Vec<RequestQueryOrder>,
+}
+
+/// in-flight orders of the journal, per venue and account, until the account reports its open orders
+pub struct OrderRecovery {
+    pending: Mutex<HashMap<(Exchange, AccountId), Vec<UpdateOrder>>>,
+}
+impl OrderRecovery {
+    pub fn new(orders: Vec<UpdateOrder>) -> Self {
+        let mut pending: HashMap<(Exchange, AccountId), Vec<UpdateOrder>> = HashMap::new();
+        for order in orders {
+            match order.instrument.get_exchange() {
+                Some(exchange) => pending.entry((exchange, order.account)).or_default().push(order),
+                None => warn!("in-flight order without exchange dropped: {:?}", order),
+            }
+        }
+        Self {
+            pending: Mutex::new(pending),
+        }
+    }
+    pub fn is_pending(&self, exchange: Exchange, account: AccountId) -> bool {
+        self.pending.lock().contains_key(&(exchange, account))
+    }
+    pub fn pending_accounts(&self) -> Vec<(Exchange, AccountId)> {
+        self.pending.lock().keys().copied().collect()
+    }
+    /// orders still open on the venue are adopted, or cancelled when `cancel_open` or a cancel was in flight.
+    /// the others may have filled or been cancelled while down, the venue is queried for their state
+    pub fn resolve(&self, sync: &SyncOrders, cancel_open: bool) -> Option<RecoveryResolution> {
+        if !sync.full {
+            return None;
+        }
+        let exchange = sync.range.get_exchange()?;
+        let account = sync.account;
+        let orders = self.pending.lock().remove(&(exchange, account))?;
+        let now = Time::now();
+        let mut updates = vec![];
+        let mut cancels = vec![];
+        let mut queries = vec![];
+        for order in orders {
+            let open = sync.orders.iter().find(|x| {
+                (!order.client_id.is_empty() && x.client_id == order.client_id)
+                    || (!order.server_id.is_empty() && x.server_id == order.server_id)
+            });
+            let Some(open) = open else {
+                queries.push(RequestQueryOrder::from_update(&order));
+                continue;
+            };
+            let mut update = order.clone();
+            update.update_lt = now;
+            update.update_est = now;
+            update.update_tst = now;
+            update.status = open.status;
+            update.server_id = open.server_id.clone();
+            update.filled_size = open.filled_size.max(order.filled_size);
+            update.reason = "adopted after restart".to_string();
+            if cancel_open || order.status.is_cancel() {
+                cancels.push(RequestCancelOrder {
+                    instrument: order.instrument.clone(),
+                    order_lid: order.local_id.clone(),
+                    order_cid: order.client_id.clone(),
+                    order_sid: open.server_id.clone(),
+                    account: order.account,
+                    strategy_id: order.strategy_id,
+                    cancel_lt: now,
+                });
+            }
+            updates.push(update);
+        }
+        Some(RecoveryResolution {
+            exchange,
+            account,
+            updates,
+            cancels,
+            queries,
+        })
+    }
+    /// gives up on the accounts that did not report their open orders, returns them with their order count.
+    /// the orders stay in flight in the journal and are recovered again on the next start
+    pub fn expire(&self) -> Vec<(Exchange, AccountId, usize)> {
+        self.pending
+            .lock()
+            .drain()
+            .map(|((exchange, account), orders)| (exchange, account, orders.len()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trading_exchange::model::{Order, OrderStatus};
+    use trading_model::InstrumentCode;
+
+    /// the stored key the orders were placed with
+    const ACCOUNT: AccountId = 3;
+
+    fn place(cid: &str) -> RequestPlaceOrder {
+        RequestPlaceOrder {
+            instrument: InstrumentCode::from_symbol(Exchange::Hyperliquid, "WIF".into()),
+            order_lid: cid.into(),
+            order_cid: cid.into(),
+            size: 4.0,
+            price: 3.0,
+            strategy_id: 1,
+            account: ACCOUNT,
+            ..RequestPlaceOrder::empty()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_journal_replay_and_recovery() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("order_journal.jsonl");
+        {
+            let mut journal = OrderJournal::open(&path)?;
+            for cid in ["a", "b", "c"] {
+                journal.append(&JournalRecord::PlaceOrder(place(cid))).await?;
+            }
+            let mut filled = place("a").to_update();
+            filled.status = OrderStatus::Filled;
+            filled.filled_size = 4.0;
+            journal.append(&JournalRecord::UpdateOrder(filled)).await?;
+        }
+        // torn record of a crash
+        OpenOptions::new()
+            .append(true)
+            .open(&path)?
+            .write_all(b"{\"UpdateOrder\":{")?;
+
+        let journal = OrderJournal::open(&path)?;
+        let mut in_flight = journal.in_flight();
+        in_flight.sort_by(|a, b| a.client_id.0.cmp(&b.client_id.0));
+        assert_eq!(in_flight.len(), 2);
+        assert_eq!(in_flight[0].client_id, OrderCid::from("b"));
+
+        // left unresolved, the account of the orders is returned
+        let expired = OrderRecovery::new(journal.in_flight());
+        assert_eq!(expired.expire(), vec![(Exchange::Hyperliquid, ACCOUNT, 2)]);
+        assert!(!expired.is_pending(Exchange::Hyperliquid, ACCOUNT));
+
+        let recovery = OrderRecovery::new(in_flight);
+        assert!(recovery.is_pending(Exchange::Hyperliquid, ACCOUNT));
+        // the open orders of another account resolve nothing
+        let other = SyncOrders::new(Exchange::Hyperliquid, None).with_account(1);
+        assert!(recovery.resolve(&other, false).is_none());
+        assert!(recovery.is_pending(Exchange::Hyperliquid, ACCOUNT));
+        // the snapshot as the connection of the account reports it
+        let mut sync = SyncOrders::new(Exchange::Hyperliquid, None).with_account(ACCOUNT);
+        sync.orders.push(Order {
+            client_id: "b".into(),
+            server_id: "42".into(),
+            status: OrderStatus::Open,
+            account: ACCOUNT,
+            ..Order::empty()
+        });
+        let resolution = recovery.resolve(&sync, false).unwrap();
+        assert_eq!(resolution.account, ACCOUNT);
+        assert!(!recovery.is_pending(Exchange::Hyperliquid, ACCOUNT));
+        assert!(recovery.pending_accounts().is_empty());
+        assert!(resolution.cancels.is_empty());
+        assert_eq!(resolution.updates.len(), 1);
+        assert_eq!(resolution.updates[0].client_id, OrderCid::from("b"));
+        assert_eq!(resolution.updates[0].status, OrderStatus::Open);
+        // the order not reported open is queried rather than assumed gone
+        assert_eq!(resolution.queries.len(), 1);
+        assert_eq!(resolution.queries[0].order_cid, OrderCid::from("c"));
+        assert_eq!(resolution.queries[0].to_update().status, OrderStatus::Absent);
+        Ok(())
+    }
+}
@@ -1,20 +1,28 @@
 use serde::Deserialize;
 
-use trading_exchange::model::ExecutionConfig;
+use trading_exchange::model::{AccountId, ExecutionConfig};
 use trading_exchange::utils::crypto::PrivateKey;
 use trading_model::Exchange;
 
+mod accounts;
 mod batch;
+mod halt;
+mod journal;
 mod registry;
 mod router;
 
+pub use accounts::*;
 pub use batch::*;
+pub use halt::*;
+pub use journal::*;
 pub use registry::*;
 pub use router::*;
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct ExecutionPrivateKey {
     pub exchange: Exchange,
+    /// id of the stored key
+    pub account: AccountId,
     pub account_id: String,
     pub private_key: PrivateKey,
 }
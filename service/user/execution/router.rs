@@ -1,23 +1,29 @@
+use async_trait::async_trait;
 use eyre::bail;
 use futures::FutureExt;
 use gluesql::core::sqlparser::keywords::NULL;
 use kanal::AsyncReceiver;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
 use trading_exchange::model::{
-    BoxedServiceAsync, ExecutionConfig, ExecutionRequest, ExecutionResource, ExecutionResponse, ExecutionService,
-    ExecutionServiceBuilder, OrderStatus, PositionEffect, RequestUpdateLeverage, SigningAddressPrivateKey,
-    SigningApiKeySecret, UpdateOrder,
+    AccountId, BoxedServiceAsync, ExecutionConfig, ExecutionRequest, ExecutionResource, ExecutionResponse,
+    ExecutionService, ExecutionServiceBuilder, OrderCid, OrderStatus, PositionEffect, RequestUpdateLeverage,
+    ServiceAsync, SigningAddressPrivateKey, SigningApiKeySecret, SyncOrders, UpdateOrder,
 };
-use trading_model::Exchange;
+use trading_model::{Exchange, InstrumentCode, MarketEvent};
 
 use crate::balance_manager::BalanceManager;
+use crate::config::JournalConfig;
 use crate::db::worktable::order_manager::OrderManager;
 use crate::db::worktable::position_manager::PositionManager;
-use crate::execution::ExecutionKeys;
+use crate::execution::{
+    AccountingHalts, ExecutionAccounts, ExecutionKeys, HaltKind, JournalRecord, OrderJournal, OrderRecovery,
+};
+use crate::metrics::{accounting_halted, open_positions, order_rejected, order_round_trip_ms, strategy_orders};
 use lib::warn::WarnManager;
 use trading_exchange::exchange::binance::execution::BinanceExecutionBuilder;
 use trading_exchange::exchange::hyperliquid::execution::HyperliquidExecutionServiceBuilder;
@@ -26,7 +32,42 @@ use trading_exchange::utils::crypto::{PrivateKey, PrivateKeyOptions};
 use trading_exchange::utils::future::interval;
 
 use crate::strategy::broadcast::AsyncBroadcaster;
+use crate::strategy::instrument_refresh::DynamicInstrumentManager;
 use crate::strategy::{StrategyStatus, StrategyStatusMap};
+
+/// an execution connection of one account. its open orders snapshots are stamped with the account,
+/// not every venue reports which account it queried and the order recovery is keyed by it
+struct AccountConnection {
+    account: AccountId,
+    service: BoxedServiceAsync<ExecutionRequest, ExecutionResponse>,
+}
+fn stamp_account(response: &mut ExecutionResponse, account: AccountId) {
+    match response {
+        ExecutionResponse::SyncOrders(sync) => sync.account = account,
+        ExecutionResponse::Group(group) => group.iter_mut().for_each(|x| stamp_account(x, account)),
+        _ => {}
+    }
+}
+#[async_trait(?Send)]
+impl ServiceAsync for AccountConnection {
+    type Request = ExecutionRequest;
+    type Response = ExecutionResponse;
+
+    fn accept(&self, request: &ExecutionRequest) -> bool {
+        self.service.accept(request)
+    }
+    async fn request(&mut self, request: &ExecutionRequest) -> eyre::Result<()> {
+        self.service.request(request).await
+    }
+    async fn next(&mut self) -> Option<eyre::Result<ExecutionResponse>> {
+        let mut response = self.service.next().await;
+        if let Some(Ok(response)) = response.as_mut() {
+            stamp_account(response, self.account);
+        }
+        response
+    }
+}
+
 /// receive new order from strategies and
 /// send fill info to strategy
 /// balance manager behaviour
@@ -36,44 +77,170 @@ pub struct ExecutionRouter {
     rx_request: AsyncReceiver<ExecutionRequest>,
     tx_response: AsyncBroadcaster<ExecutionResponse>,
     tx_updates: AsyncBroadcaster<UpdateOrder>,
+    /// liquidations reported by the execution connections are fed back to the market feed consumers
+    tx_market: AsyncBroadcaster<MarketEvent>,
     balance_manager: BalanceManager,
     strategy_status: Arc<StrategyStatusMap>,
     select: SelectExecution,
     order_manager: Arc<RwLock<OrderManager>>,
     portfolio_manager: Arc<RwLock<PositionManager>>,
+    /// opening orders on delisted instruments are rejected
+    instruments: Arc<DynamicInstrumentManager>,
     warn_manager: WarnManager,
     rx_config: AsyncReceiver<ExecutionKeys>,
-    live_connections: HashSet<Exchange>,
+    live_connections: HashSet<(Exchange, AccountId)>,
+    /// orders without an account are sent to the account of their strategy
+    accounts: Arc<ExecutionAccounts>,
+    /// send time of orders waiting for their first update from the venue, for the round trip latency
+    sent_orders: HashMap<OrderCid, (Exchange, Instant)>,
+    /// requests are appended before they are forwarded, updates as they arrive
+    journal: Option<OrderJournal>,
+    /// opening orders on a venue wait for its in-flight orders of the last run to be resolved
+    recovery: Arc<OrderRecovery>,
+    recovery_timeout: Duration,
+    cancel_recovered: bool,
+    /// orders on an account are rejected while its accounting is halted
+    halts: Arc<AccountingHalts>,
 }
 impl ExecutionRouter {
     pub fn new(
         rx_request: AsyncReceiver<ExecutionRequest>,
         tx_response: AsyncBroadcaster<ExecutionResponse>,
         tx_updates: AsyncBroadcaster<UpdateOrder>,
+        tx_market: AsyncBroadcaster<MarketEvent>,
         balance_manager: BalanceManager,
         strategy_status: Arc<StrategyStatusMap>,
         order_manager: Arc<RwLock<OrderManager>>,
         portfolio_manager: Arc<RwLock<PositionManager>>,
+        instruments: Arc<DynamicInstrumentManager>,
         rx_config: AsyncReceiver<ExecutionKeys>,
     ) -> Self {
         Self {
             rx_request,
             tx_response,
             tx_updates,
+            tx_market,
             balance_manager,
             strategy_status,
             select: SelectExecution::empty(),
             order_manager,
             portfolio_manager,
+            instruments,
             warn_manager: WarnManager::new(),
             rx_config,
             live_connections: HashSet::new(),
+            accounts: Arc::new(ExecutionAccounts::default()),
+            sent_orders: HashMap::new(),
+            journal: None,
+            recovery: Arc::new(OrderRecovery::new(vec![])),
+            recovery_timeout: Duration::ZERO,
+            cancel_recovered: false,
+            halts: Arc::new(AccountingHalts::new()),
+        }
+    }
+    pub fn with_journal(
+        mut self,
+        journal: Option<OrderJournal>,
+        recovery: Arc<OrderRecovery>,
+        config: &JournalConfig,
+    ) -> Self {
+        self.journal = journal;
+        self.recovery = recovery;
+        self.recovery_timeout = Duration::from_millis(config.recovery_timeout_ms.max(0) as _);
+        self.cancel_recovered = config.cancel_recovered;
+        self
+    }
+    pub fn with_halts(mut self, halts: Arc<AccountingHalts>) -> Self {
+        self.halts = halts;
+        self
+    }
+    pub fn with_accounts(mut self, accounts: Arc<ExecutionAccounts>) -> Self {
+        self.accounts = accounts;
+        self
+    }
+    async fn append_journal(&mut self, req: &ExecutionRequest) -> eyre::Result<()> {
+        let Some(journal) = self.journal.as_mut() else {
+            return Ok(());
+        };
+        match req {
+            ExecutionRequest::PlaceOrder(order) => journal.append(&JournalRecord::PlaceOrder(order.clone())).await,
+            ExecutionRequest::CancelOrder(cancel) => journal.append(&JournalRecord::CancelOrder(cancel.clone())).await,
+            _ => Ok(()),
+        }
+    }
+    async fn journal_update(&mut self, update: &UpdateOrder) {
+        let Some(journal) = self.journal.as_mut() else {
+            return;
+        };
+        if let Err(err) = journal.append(&JournalRecord::UpdateOrder(update.clone())).await {
+            error!("failed to journal order update {}: {err:?}", update.client_id);
+        }
+    }
+    /// adopts, cancels or queries the in-flight orders of the last run once the venue reports its open orders
+    async fn recover_orders(&mut self, sync: &SyncOrders) {
+        let Some(resolution) = self.recovery.resolve(sync, self.cancel_recovered) else {
+            return;
+        };
+        info!(
+            "recovered {} orders on {} account {}, cancelling {}, querying {}",
+            resolution.updates.len(),
+            resolution.exchange,
+            resolution.account,
+            resolution.cancels.len(),
+            resolution.queries.len()
+        );
+        for update in resolution.updates {
+            self.journal_update(&update).await;
+            self.order_manager.write().await.insert_update(update).await;
+        }
+        for cancel in resolution.cancels {
+            let req = ExecutionRequest::CancelOrder(cancel.clone());
+            let result = match self.append_journal(&req).await {
+                Ok(()) => {
+                    self.order_manager.write().await.insert_update(cancel.to_update()).await;
+                    self.portfolio_manager.write().await.cancel_order(&cancel.order_cid);
+                    self.select
+                        .request_or_else(&req, || {
+                            bail!("no execution connection for exchange: {:?}", req);
+                        })
+                        .await
+                }
+                Err(err) => Err(err),
+            };
+            if let Err(err) = result {
+                error!("failed to cancel recovered order {}: {err:?}", cancel.order_cid);
+            }
+        }
+        // the answers arrive as order updates, an order that cannot be queried is taken as gone
+        for query in resolution.queries {
+            let req = ExecutionRequest::QueryOrder(query.clone());
+            let result = self
+                .select
+                .request_or_else(&req, || {
+                    bail!("no execution connection for exchange: {:?}", req);
+                })
+                .await;
+            if let Err(err) = result {
+                warn!(
+                    "failed to query recovered order {}, marked absent: {err:?}",
+                    query.order_cid
+                );
+                let update = query.to_update();
+                self.journal_update(&update).await;
+                self.order_manager.write().await.insert_update(update).await;
+            }
         }
     }
     async fn send_update_orders(&mut self) {
         for update in self.order_manager.write().await.drain() {
             // info!("Sending order update: {:?}", update);
             self.portfolio_manager.write().await.update_order(&update);
+            if update.status == OrderStatus::Rejected {
+                order_rejected(
+                    update.instrument.get_exchange().unwrap_or(Exchange::Null),
+                    &update.reason,
+                );
+            }
             if let Err(e) = self
                 .balance_manager
                 .add_balance(update.instrument.get_exchange().unwrap(), update.clone())
@@ -88,12 +255,47 @@ impl ExecutionRouter {
         }
     }
 
-    async fn handle_request(&mut self, req: ExecutionRequest) {
+    fn is_delisted(&self, instrument: &InstrumentCode) -> bool {
+        match instrument {
+            InstrumentCode::Symbol(symbol) => self.instruments.is_delisted(symbol),
+            _ => false,
+        }
+    }
+    /// orders without an account go to the account of their strategy, cancels to the account of the order
+    async fn resolve_account(&self, req: &mut ExecutionRequest) {
+        match req {
+            ExecutionRequest::PlaceOrder(order) if order.account == 0 => {
+                let exchange = order.instrument.get_exchange().unwrap_or(Exchange::Null);
+                order.account = self.accounts.resolve(order.strategy_id as _, exchange);
+            }
+            ExecutionRequest::CancelOrder(cancel) if cancel.account == 0 => {
+                let exchange = cancel.instrument.get_exchange().unwrap_or(Exchange::Null);
+                let placed = self
+                    .order_manager
+                    .read()
+                    .await
+                    .orders
+                    .get_row_by_cloid(&cancel.order_cid)
+                    .map(|x| x.account())
+                    .filter(|x| *x != 0);
+                cancel.account = match placed {
+                    Some(account) => account,
+                    None => self.accounts.resolve(cancel.strategy_id as _, exchange),
+                };
+            }
+            _ => {}
+        }
+    }
+    async fn handle_request(&mut self, mut req: ExecutionRequest) {
         info!("Handling request from strategy: {:?}", req);
+        self.resolve_account(&mut req).await;
         match &req {
             // if the request is NewOrder and the strategy is not enabled, return early
+            // a disabled strategy may still close what it holds
             ExecutionRequest::PlaceOrder(order) => {
-                if self.strategy_status.get(order.strategy_id as _) != Some(StrategyStatus::Enabled) {
+                let status = self.strategy_status.get(order.strategy_id as _);
+                let closing = status == Some(StrategyStatus::Disabled) && order.effect == PositionEffect::Close;
+                if status != Some(StrategyStatus::Enabled) && !closing {
                     info!("Strategy {} not enabled, skipping order", order.strategy_id);
                     let mut err_resp = order.to_update();
                     err_resp.status = OrderStatus::Rejected;
@@ -101,7 +303,65 @@ impl ExecutionRouter {
                     self.order_manager.write().await.insert_update(err_resp).await;
                     return;
                 }
+                if order.effect == PositionEffect::Open && self.is_delisted(&order.instrument) {
+                    warn!("{} is delisted, skipping opening order", order.instrument);
+                    let mut err_resp = order.to_update();
+                    err_resp.status = OrderStatus::Rejected;
+                    err_resp.reason = "instrument delisted".to_string();
+                    self.order_manager.write().await.insert_update(err_resp).await;
+                    return;
+                }
+                let exchange = order.instrument.get_exchange().unwrap_or(Exchange::Null);
+                if self.recovery.is_pending(exchange, order.account) {
+                    warn!(
+                        "orders on {} account {} are being recovered, skipping order",
+                        exchange, order.account
+                    );
+                    let mut err_resp = order.to_update();
+                    err_resp.status = OrderStatus::Rejected;
+                    err_resp.reason = "order recovery in progress".to_string();
+                    self.order_manager.write().await.insert_update(err_resp).await;
+                    return;
+                }
+                if !self.accounts.is_connected(exchange, order.account) {
+                    warn!(
+                        "account {} is not connected on {}, skipping order",
+                        order.account, exchange
+                    );
+                    let mut err_resp = order.to_update();
+                    err_resp.status = OrderStatus::Rejected;
+                    err_resp.reason = format!("account {} not connected", order.account);
+                    self.order_manager.write().await.insert_update(err_resp).await;
+                    return;
+                }
+                if let Some(halt) = self.halts.get(exchange, order.account) {
+                    warn!(
+                        "accounting of {} account {} is halted, skipping order",
+                        exchange, order.account
+                    );
+                    let mut err_resp = order.to_update();
+                    err_resp.status = OrderStatus::Rejected;
+                    err_resp.reason = format!("accounting halted: {}", halt.reason);
+                    self.order_manager.write().await.insert_update(err_resp).await;
+                    return;
+                }
             }
+            ExecutionRequest::AcknowledgeHalt(ack) => match self.halts.acknowledge(ack.exchange, ack.account) {
+                Some(halt) if halt.kind == HaltKind::Router => {
+                    info!(
+                        "halt of {} account {} acknowledged and lifted: {}",
+                        ack.exchange, ack.account, halt.reason
+                    );
+                    accounting_halted(ack.exchange, ack.account).set(0.0);
+                }
+                Some(halt) => {
+                    info!(
+                        "accounting halt of {} account {} acknowledged, lifted with the next book: {}",
+                        ack.exchange, ack.account, halt.reason
+                    );
+                }
+                None => {}
+            },
             ExecutionRequest::CancelOrder(cancel) => {
                 if self.strategy_status.get(cancel.strategy_id as _) != Some(StrategyStatus::Enabled) {
                     info!("Strategy {} not enabled, skipping order", cancel.strategy_id);
@@ -120,6 +380,7 @@ impl ExecutionRouter {
 
         match &req {
             ExecutionRequest::PlaceOrder(order) => {
+                strategy_orders(order.strategy_id).inc();
                 let is_opening = order.effect == PositionEffect::Open;
                 let exchange = order.instrument.get_exchange().unwrap();
                 // only deduct fund when it is an opening order
@@ -128,7 +389,7 @@ impl ExecutionRouter {
 
                     match self
                         .balance_manager
-                        .deduct_balance(exchange, order.price * order.size)
+                        .deduct_balance(exchange, order.account, order.price * order.size)
                         .await
                     {
                         Err(e) => {
@@ -137,7 +398,7 @@ impl ExecutionRouter {
                         Ok(false) => {
                             let current = self
                                 .balance_manager
-                                .get_balance(exchange)
+                                .get_balance(exchange, order.account)
                                 .await
                                 .map(|x| x.amount_usd)
                                 .unwrap_or_default();
@@ -165,12 +426,22 @@ impl ExecutionRouter {
             }
             _ => {}
         }
-        let result = self
-            .select
-            .request_or_else(&req, || {
-                bail!("no execution connection for exchange: {:?}", req);
-            })
-            .await;
+        if let ExecutionRequest::PlaceOrder(order) = &req {
+            let exchange = order.instrument.get_exchange().unwrap_or(Exchange::Null);
+            self.sent_orders
+                .insert(order.order_cid.clone(), (exchange, Instant::now()));
+        }
+        // nothing reaches a venue without being journaled first
+        let result = match self.append_journal(&req).await {
+            Ok(()) => {
+                self.select
+                    .request_or_else(&req, || {
+                        bail!("no execution connection for exchange: {:?}", req);
+                    })
+                    .await
+            }
+            Err(err) => Err(err),
+        };
         if let Err(err) = result {
             error!("execution request error: {}", err);
             match req {
@@ -178,6 +449,8 @@ impl ExecutionRouter {
                     let mut update = order.to_update();
                     update.status = OrderStatus::Rejected;
                     update.reason = format!("error sending order: {}", err);
+                    // the order was journaled, it never reached the venue
+                    self.journal_update(&update).await;
                     self.order_manager.write().await.insert_update(update).await;
                 }
                 ExecutionRequest::CancelOrder(cancel) => {
@@ -207,10 +480,18 @@ impl ExecutionRouter {
                 if update.status.is_dead() {
                     warn!("dead order: {:?}", update);
                 }
+                if let Some((exchange, sent_at)) = self.sent_orders.remove(&update.client_id) {
+                    order_round_trip_ms(exchange).observe(sent_at.elapsed().as_secs_f64() * 1000.0);
+                }
+                self.journal_update(update).await;
                 self.order_manager.write().await.insert_update(update.clone()).await;
             }
+            ExecutionResponse::SyncOrders(sync) => {
+                self.recover_orders(sync).await;
+            }
             ExecutionResponse::UpdatePosition(position) => {
                 self.portfolio_manager.write().await.update_position(
+                    position.account,
                     position,
                     trading_model::Time::from_nanos(position.times.transaction_time),
                 );
@@ -218,6 +499,29 @@ impl ExecutionRouter {
             ExecutionResponse::UpdatePositions(positions) => {
                 self.portfolio_manager.write().await.update_positions(positions);
             }
+            ExecutionResponse::UpdateBook(book) => {
+                let changes = self.halts.update(book);
+                for halt in changes.halted {
+                    error!(
+                        "accounting of {} account {} halted, trading stopped: {}",
+                        halt.exchange, halt.account, halt.reason
+                    );
+                    accounting_halted(halt.exchange, halt.account).set(1.0);
+                }
+                for halt in changes.lifted {
+                    info!(
+                        "accounting halt of {} account {} lifted: {}",
+                        halt.exchange, halt.account, halt.reason
+                    );
+                    accounting_halted(halt.exchange, halt.account).set(0.0);
+                }
+            }
+            ExecutionResponse::Liquidation(liquidation) => {
+                warn!("liquidation: {:?}", liquidation);
+                if let Err(err) = self.tx_market.broadcast(MarketEvent::Liquidation(liquidation.clone())) {
+                    self.warn_manager.warn(&format!("error broadcast liquidation {}", err));
+                }
+            }
             ExecutionResponse::Group(updates) => {
                 for update in updates {
                     self.handle_execution_response(update).boxed_local().await
@@ -226,9 +530,14 @@ impl ExecutionRouter {
             _ => {}
         }
     }
-    fn try_push(&mut self, exchange: Exchange, service: BoxedServiceAsync<ExecutionRequest, ExecutionResponse>) {
-        if self.live_connections.insert(exchange) {
-            self.select.push(service);
+    fn try_push(
+        &mut self,
+        exchange: Exchange,
+        account: AccountId,
+        service: BoxedServiceAsync<ExecutionRequest, ExecutionResponse>,
+    ) {
+        if self.live_connections.insert((exchange, account)) {
+            self.select.push(Box::new(AccountConnection { account, service }));
         }
     }
     pub async fn add_config(&mut self, keys: ExecutionKeys) -> eyre::Result<()> {
@@ -240,6 +549,7 @@ impl ExecutionRouter {
                 continue;
             };
             let private_key = PrivateKey::new(key_exchange, PrivateKeyOptions::NONE)?;
+            let account_id = key.account_id.clone();
             // update config in the arc mutex
             let mut config = ExecutionConfig {
                 exchange: key.exchange,
@@ -247,7 +557,7 @@ impl ExecutionRouter {
                 network: Default::default(),
                 resources: vec![ExecutionResource::Execution, ExecutionResource::Accounting],
                 symbols: vec![],
-                account: 0,
+                account: key.account,
                 extra: Default::default(),
                 ..ExecutionConfig::empty()
             };
@@ -263,7 +573,7 @@ impl ExecutionRouter {
                         .to_value(),
                     );
                     let conn = BinanceExecutionBuilder::new().build(&config).await?;
-                    self.try_push(key.exchange, Box::new(conn));
+                    self.try_push(key.exchange, key.account, Box::new(conn));
                 }
                 Exchange::Hyperliquid => {
                     config.extra.inject(
@@ -285,26 +595,55 @@ impl ExecutionRouter {
                     {
                         tracing::warn!("failed to update leverage: {:?}", err);
                     }
-                    self.try_push(config.exchange, Box::new(conn));
+                    self.try_push(config.exchange, key.account, Box::new(conn));
                 }
                 _ => {
                     tracing::warn!("exchange not supported {:?}", key.exchange);
                     continue;
                 }
             }
-            info!("updated {} config", key.exchange);
+            self.accounts.connect(key.exchange, key.account, &account_id);
+            info!("updated {} config of account {}", key.exchange, key.account);
         }
         Ok(())
     }
 }
 
 impl ExecutionRouter {
+    async fn update_position_metrics(&mut self) {
+        // orders that never got an update are not part of the round trip latency
+        self.sent_orders
+            .retain(|_, (_, sent_at)| sent_at.elapsed() < Duration::from_secs(60));
+        let mut counts: HashMap<Exchange, usize> = self.live_connections.iter().map(|(x, _)| (*x, 0)).collect();
+        for position in self.portfolio_manager.read().await.positions.iter() {
+            *counts.entry(position.exchange()).or_default() += 1;
+        }
+        for (exchange, count) in counts {
+            open_positions(exchange).set(count as _);
+        }
+    }
     pub async fn run(&mut self) -> eyre::Result<()> {
         let mut interval = interval(5_000);
+        let mut recovery_deadline = Some(Instant::now() + self.recovery_timeout);
         loop {
             tokio::select! {
                 _ = interval.tick() => {
+                    if recovery_deadline.is_some_and(|x| x <= Instant::now()) {
+                        recovery_deadline = None;
+                        // the orders may still be open, the accounts stay halted until an operator checked them
+                        for (exchange, account, count) in self.recovery.expire() {
+                            let reason = format!("{count} in-flight orders of the last run left unresolved");
+                            if let Some(halt) = self.halts.halt(exchange, account, reason) {
+                                error!(
+                                    "{exchange} did not report the open orders of account {account}, trading stopped: {}",
+                                    halt.reason
+                                );
+                                accounting_halted(exchange, account).set(1.0);
+                            }
+                        }
+                    }
                     self.order_manager.write().await.soft_cleanup();
+                    self.update_position_metrics().await;
                     debug!("Orders:");
                     for order in self.order_manager.read().await.orders.iter() {
                         debug!("order: {}", order)
@@ -322,6 +661,8 @@ impl ExecutionRouter {
                         }
                     };
                     self.handle_request(req).await;
+                    // rejections of the router reach the strategies without waiting for the next response
+                    self.send_update_orders().await;
                 }
                 resp = self.select.next() => {
                     let resp = match resp {
@@ -346,3 +687,95 @@ impl ExecutionRouter {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::worktable::balance::WorktableBalance;
+    use trading_exchange::model::{Order, RequestPlaceOrder};
+    use trading_model::InstrumentManager;
+
+    /// reports its open orders the way the venues do, without the account that was queried
+    #[derive(Debug)]
+    struct OpenOrdersVenue {
+        responses: Vec<ExecutionResponse>,
+    }
+    #[async_trait(?Send)]
+    impl ServiceAsync for OpenOrdersVenue {
+        type Request = ExecutionRequest;
+        type Response = ExecutionResponse;
+
+        fn accept(&self, _request: &ExecutionRequest) -> bool {
+            false
+        }
+        async fn request(&mut self, _request: &ExecutionRequest) -> eyre::Result<()> {
+            Ok(())
+        }
+        async fn next(&mut self) -> Option<eyre::Result<ExecutionResponse>> {
+            self.responses.pop().map(Ok)
+        }
+    }
+
+    fn router(recovery: Arc<OrderRecovery>) -> ExecutionRouter {
+        let accounts = Arc::new(ExecutionAccounts::default());
+        let (_, rx_request) = kanal::unbounded_async();
+        let (_, rx_config) = kanal::unbounded_async();
+        ExecutionRouter::new(
+            rx_request,
+            AsyncBroadcaster::new(16),
+            AsyncBroadcaster::new(16),
+            AsyncBroadcaster::new(16),
+            BalanceManager::new(Arc::new(RwLock::new(WorktableBalance::new())), accounts.clone()),
+            Arc::new(StrategyStatusMap::new()),
+            Arc::new(RwLock::new(OrderManager::new())),
+            Arc::new(RwLock::new(PositionManager::new())),
+            Arc::new(DynamicInstrumentManager::new(Arc::new(InstrumentManager::new()))),
+            rx_config,
+        )
+        .with_journal(None, recovery, &JournalConfig::default())
+        .with_accounts(accounts)
+    }
+
+    #[tokio::test]
+    async fn test_recover_orders_of_account() -> eyre::Result<()> {
+        let account = 7;
+        let order = RequestPlaceOrder {
+            instrument: InstrumentCode::from_symbol(Exchange::Hyperliquid, "WIF".into()),
+            order_lid: "a".into(),
+            order_cid: "a".into(),
+            size: 4.0,
+            price: 3.0,
+            strategy_id: 1,
+            account,
+            ..RequestPlaceOrder::empty()
+        };
+        let recovery = Arc::new(OrderRecovery::new(vec![order.to_update()]));
+        let mut router = router(recovery.clone());
+
+        let mut sync = SyncOrders::new(Exchange::Hyperliquid, None);
+        sync.orders.push(Order {
+            instrument: order.instrument.clone(),
+            client_id: "a".into(),
+            server_id: "42".into(),
+            status: OrderStatus::Open,
+            ..Order::empty()
+        });
+        router.try_push(
+            Exchange::Hyperliquid,
+            account,
+            Box::new(OpenOrdersVenue {
+                responses: vec![ExecutionResponse::SyncOrders(sync)],
+            }),
+        );
+        assert!(recovery.is_pending(Exchange::Hyperliquid, account));
+
+        let response = router.select.next().await?;
+        router.handle_execution_response(&response).await;
+        assert!(!recovery.is_pending(Exchange::Hyperliquid, account));
+        let order_manager = router.order_manager.read().await;
+        let row = order_manager.orders.get_row_by_cloid("a").unwrap();
+        assert_eq!(row.server_id(), "42");
+        assert_eq!(row.account(), account);
+        Ok(())
+    }
+}
@@ -1,81 +1,491 @@
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
 
 use eyre::Result;
+use gluesql::core::ast_builder::col;
 use gluesql_shared_sled_storage::SharedSledStorage;
 use kanal::AsyncReceiver;
-use tracing::warn;
+use tracing::{debug, error, info, warn};
 
-use crate::db::gluesql::schema::common::StrategyId;
-use lib::gluesql::TableUpdateItem;
-use trading_exchange::model::{OrderStatus, PositionEffect, UpdateOrder};
-use trading_model::InstrumentSymbol;
+use lib::gluesql::{Table, TableOverwriteItem, TableSelectItem, TableUpdateItem};
+use trading_exchange::model::{
+    ExecutionResponse, FundingPayment, OrderLid, OrderTrade, OrderType, TimeInForce, UpdateOrder,
+};
+use trading_model::{InstrumentManager, InstrumentSymbol, PriceType, Time};
 
+use crate::config::{FeeSchedule, LedgerConfig};
+use crate::db::gluesql::schema::funding_accrual::DbRowFundingAccrual;
+use crate::db::gluesql::schema::position_pnl::DbRowPositionPnl;
 use crate::db::gluesql::schema::DbRowLedger;
-use crate::db::gluesql::StrategyTable;
 use crate::db::worktable::order_manager::SharedOrderManager;
+use crate::metrics::{realized_pnl_usd, unrealized_pnl_usd};
+use crate::position_ledger::{Fill, LedgerChanges, PositionLedger};
+use crate::strategy::data_factory::{LastPriceMap, PriceSourceAsset};
+use crate::strategy::instrument_refresh::DynamicInstrumentManager;
 
+/// dead orders are forgotten after this, even when their trades did not add up to the filled size
+const DEAD_ORDER_TTL_MS: i64 = 3_600_000;
+
+/// fills of an order booked so far
+struct OrderFills {
+    update: UpdateOrder,
+    booked_size: f64,
+    booked_notional: f64,
+}
+
+fn is_taker(ty: OrderType, tif: TimeInForce) -> bool {
+    ty == OrderType::Market || matches!(tif, TimeInForce::ImmediateOrCancel | TimeInForce::FillOrKill)
+}
+
+fn fill_from_update(update: &UpdateOrder, price: f64, size: f64, fee_usd: f64, datetime: i64) -> Option<Fill> {
+    let instrument = InstrumentSymbol::new(update.instrument.get_exchange()?, update.instrument.get_symbol()?);
+    Some(Fill {
+        strategy_id: update.strategy_id as _,
+        account: update.account,
+        event_id: update.event_id,
+        instrument,
+        side: update.side,
+        effect: update.effect,
+        ty: update.ty,
+        local_id: update.local_id.to_string(),
+        client_id: update.client_id.to_string(),
+        price,
+        size,
+        fee_usd,
+        datetime,
+    })
+}
+
+/// the server id part of an order id of the form `<venue>|<server id>`, as the Hyperliquid trades
+/// refer to their order
+fn trade_server_id(order_lid: &OrderLid) -> &str {
+    order_lid.rsplit('|').next().unwrap_or_default()
+}
+
+/// mid of the book ticker, or the last trade
+fn mark_price(instruments: &InstrumentManager, price_map: &LastPriceMap, instrument: &InstrumentSymbol) -> Option<f64> {
+    let details = instruments.get_by_symbol(instrument.exchange, instrument.symbol.clone())?;
+    let price = |price_type| {
+        price_map.get(&PriceSourceAsset {
+            asset: details.base.asset.clone(),
+            exchange: instrument.exchange,
+            price_type,
+        })
+    };
+    match (price(PriceType::Bid), price(PriceType::Ask)) {
+        (Some(bid), Some(ask)) => Some((bid.price + ask.price) / 2.0),
+        _ => price(PriceType::Trade).map(|x| x.price),
+    }
+}
+
+/// books the fills of every strategy into ledger lots, realizes them on the opposite fills
+/// and marks the open ones to the last prices
 pub struct LedgerManager {
-    // TODO: use single table when the right time comes
-    ledger_table: StrategyTable<SharedSledStorage, DbRowLedger>,
+    ledger_table: Table<SharedSledStorage, DbRowLedger>,
+    funding_table: Table<SharedSledStorage, DbRowFundingAccrual>,
+    pnl_table: Table<SharedSledStorage, DbRowPositionPnl>,
     order_manager: SharedOrderManager,
-    open_order_map: HashMap<InstrumentSymbol, Vec<DbRowLedger>>,
+    instruments: Arc<DynamicInstrumentManager>,
+    price_map: Arc<LastPriceMap>,
+    config: LedgerConfig,
+    fees: FeeSchedule,
+    ledger: PositionLedger,
+    orders: HashMap<OrderLid, OrderFills>,
+    /// local ids by server id, for the venues whose trades refer to the order as `<venue>|<server id>`
+    server_ids: HashMap<String, OrderLid>,
 }
 
 impl LedgerManager {
-    pub fn new(ledger_table: StrategyTable<SharedSledStorage, DbRowLedger>, order_manager: SharedOrderManager) -> Self {
+    pub fn new(
+        ledger_table: Table<SharedSledStorage, DbRowLedger>,
+        funding_table: Table<SharedSledStorage, DbRowFundingAccrual>,
+        pnl_table: Table<SharedSledStorage, DbRowPositionPnl>,
+        order_manager: SharedOrderManager,
+        instruments: Arc<DynamicInstrumentManager>,
+        price_map: Arc<LastPriceMap>,
+        config: LedgerConfig,
+        fees: FeeSchedule,
+    ) -> Self {
+        let ledger = {
+            let ledger_ids = ledger_table.clone();
+            let pnl_ids = pnl_table.clone();
+            PositionLedger::new(
+                config.lot_method,
+                Box::new(move || ledger_ids.next_index()),
+                Box::new(move || pnl_ids.next_index()),
+            )
+        };
         Self {
             ledger_table,
+            funding_table,
+            pnl_table,
             order_manager,
-            open_order_map: Default::default(),
+            instruments,
+            price_map,
+            config,
+            fees,
+            ledger,
+            orders: Default::default(),
+            server_ids: Default::default(),
         }
     }
+    /// restores the lots that are still open
+    pub async fn load(&mut self) -> Result<()> {
+        let lots = self
+            .ledger_table
+            .select(Some(col("closed_volume").lt(col("volume"))), "id")
+            .await?;
+        let pnl = self.pnl_table.select(Some(DbRowPositionPnl::open()), "id").await?;
+        info!("ledger loaded {} open lots and {} open PnL rows", lots.len(), pnl.len());
+        self.ledger.load(lots, pnl);
+        Ok(())
+    }
+    async fn write(&mut self, changes: LedgerChanges) -> Result<()> {
+        for lot in &changes.new_lots {
+            self.ledger_table.insert(lot.clone()).await?;
+        }
+        for lot in &changes.updated_lots {
+            // default filter by strategy and id
+            self.ledger_table.update(lot.clone(), None).await?;
+        }
+        for row in &changes.new_pnl {
+            self.pnl_table.insert(row.clone()).await?;
+        }
+        for row in &changes.updated_pnl {
+            self.pnl_table.overwrite(row.id, row).await?;
+        }
+        self.ledger.persisted(&changes);
+        for (strategy_id, realized) in &changes.realized_usd {
+            realized_pnl_usd(*strategy_id as _).add(*realized);
+        }
+        Ok(())
+    }
+    async fn apply_fill(&mut self, fill: Fill) -> Result<()> {
+        debug!("ledger fill: {:?}", fill);
+        let changes = self.ledger.apply_fill(&fill);
+        self.write(changes).await
+    }
+    fn fee_usd(&self, update: &UpdateOrder, notional: f64) -> f64 {
+        let Some(exchange) = update.instrument.get_exchange() else {
+            return 0.0;
+        };
+        notional * self.fees.fee_rate(exchange, is_taker(update.ty, update.tif))
+    }
+    /// venues without a trade stream are booked from the growth of the filled size
     pub async fn handle_order_update(&mut self, update: UpdateOrder) -> Result<()> {
-        // TODO: handle partially filled case
-        // if it's open order and filled, insert new ledger to both the map and table
-        if update.effect == PositionEffect::Open && update.status == OrderStatus::Filled {
-            let lock = self.order_manager.read().await;
-            let order = lock.orders.get_row_by_cloid(&update.client_id).unwrap();
-            let instrument_symbol = order.instrument_symbol();
-            let table = self.ledger_table.get_mut(&(update.strategy_id as StrategyId)).unwrap();
-            let mut ledger = DbRowLedger::from_open_order(order);
-            ledger.id = table.next_index();
-            self.open_order_map
-                .entry(instrument_symbol)
-                .or_default()
-                .push(ledger.clone());
-            table.insert(ledger).await?;
-        }
-
-        // if it's close order and filled, update the ledger in both the map(last one) and the table
-        if update.effect == PositionEffect::Close && update.status == OrderStatus::Filled {
-            let lock = self.order_manager.read().await;
-            let order = lock.orders.get_row_by_cloid(&update.client_id).unwrap();
-
-            let Some(ledgers) = self.open_order_map.get_mut(&order.instrument_symbol()) else {
-                warn!("no open order found for close order: {}", order);
-                return Ok(());
+        let Some(exchange) = update.instrument.get_exchange() else {
+            return Ok(());
+        };
+        let order = self
+            .orders
+            .entry(update.local_id.clone())
+            .or_insert_with(|| OrderFills {
+                update: update.clone(),
+                booked_size: 0.0,
+                booked_notional: 0.0,
+            });
+        order.update = update.clone();
+        if !update.server_id.is_empty() {
+            self.server_ids
+                .insert(update.server_id.to_string(), update.local_id.clone());
+        }
+        let mut fill = None;
+        let size = update.filled_size - order.booked_size;
+        if !self.config.trade_fills.contains(&exchange) && size > 0.0 {
+            let price = if update.average_filled_price > 0.0 {
+                (update.average_filled_price * update.filled_size - order.booked_notional) / size
+            } else if update.last_filled_price > 0.0 {
+                update.last_filled_price
+            } else {
+                update.price
             };
-            let Some(last_ledger) = ledgers.last_mut() else {
-                warn!("no open order found for close order: {}", order);
-                return Ok(());
+            order.booked_size = update.filled_size;
+            order.booked_notional += price * size;
+            fill = Some((price, size));
+        }
+        if update.status.is_dead() && order.booked_size >= update.filled_size {
+            self.remove_order(&update.local_id);
+        }
+        if let Some((price, size)) = fill {
+            let fee = self.fee_usd(&update, price * size);
+            if let Some(fill) = fill_from_update(&update, price, size, fee, update.update_est.millis()) {
+                self.apply_fill(fill).await?;
+            }
+        }
+        Ok(())
+    }
+    fn remove_order(&mut self, local_id: &OrderLid) {
+        if let Some(order) = self.orders.remove(local_id) {
+            self.server_ids.remove(order.update.server_id.as_str());
+        }
+    }
+    /// trades of the venues in `trade_fills`, matched to their order by local id or by server id
+    pub async fn handle_trade(&mut self, trade: OrderTrade) -> Result<()> {
+        let Some(exchange) = trade.instrument.get_exchange() else {
+            return Ok(());
+        };
+        if !self.config.trade_fills.contains(&exchange) {
+            return Ok(());
+        }
+        let server_id = trade_server_id(&trade.order_lid);
+        let local_id = match self.orders.contains_key(&trade.order_lid) {
+            true => Some(trade.order_lid.clone()),
+            false => self.server_ids.get(server_id).cloned(),
+        };
+        let update = match local_id.as_ref().and_then(|x| self.orders.get_mut(x)) {
+            Some(order) => {
+                order.booked_size += trade.size;
+                order.booked_notional += trade.price * trade.size;
+                let update = order.update.clone();
+                if update.status.is_dead() && order.booked_size >= update.filled_size {
+                    self.remove_order(&update.local_id);
+                }
+                update
+            }
+            // the trade came before the first update of the order
+            None => {
+                let lock = self.order_manager.read().await;
+                let row = lock
+                    .orders
+                    .get_row_by_local_id(&trade.order_lid)
+                    .or_else(|| lock.orders.get_row_by_server_id(server_id));
+                let Some(row) = row else {
+                    warn!("no order found for trade: {:?}", trade);
+                    return Ok(());
+                };
+                UpdateOrder {
+                    instrument: trade.instrument.clone(),
+                    local_id: row.local_id().into(),
+                    client_id: row.client_id().into(),
+                    side: row.side().unwrap_or(trade.side),
+                    effect: row.position_effect(),
+                    ty: row.ty(),
+                    strategy_id: row.strategy_id(),
+                    event_id: row.event_id() as _,
+                    account: trade.account,
+                    ..UpdateOrder::empty()
+                }
+            }
+        };
+        let fee = if matches!(trade.fee_asset.as_str(), "USD" | "USDC" | "USDT") {
+            trade.fee
+        } else {
+            self.fee_usd(&update, trade.price * trade.size)
+        };
+        let datetime = trade.exchange_time.millis();
+        if let Some(fill) = fill_from_update(&update, trade.price, trade.size, fee, datetime) {
+            self.apply_fill(fill).await?;
+        }
+        Ok(())
+    }
+    /// splits the payment across the open lots of the account on the instrument by volume
+    pub async fn handle_funding(&mut self, funding: FundingPayment) -> Result<()> {
+        let instrument = &funding.instrument;
+        let (Some(exchange), Some(symbol)) = (instrument.get_exchange(), instrument.get_symbol()) else {
+            return Ok(());
+        };
+        let datetime = funding.source_timestamp.millis();
+        let changes = self.ledger.apply_funding(
+            &InstrumentSymbol::new(exchange, symbol),
+            funding.account,
+            funding.quantity,
+            datetime,
+        );
+        if changes.funding.is_empty() {
+            debug!("no open ledger for funding payment: {:?}", funding);
+            return Ok(());
+        }
+        for (strategy_id, ledger_id, amount_usd) in &changes.funding {
+            let row = DbRowFundingAccrual {
+                id: self.funding_table.next_index(),
+                strategy_id: *strategy_id as _,
+                ledger_id: *ledger_id,
+                funding_lid: funding.funding_lid.to_string(),
+                exchange_id: exchange as u8,
+                symbol_id: symbol._hash(),
+                amount_usd: *amount_usd,
+                datetime,
             };
-            *last_ledger = last_ledger.clone().with_close_order(order);
-            let table = self.ledger_table.get_mut(&(update.strategy_id as StrategyId)).unwrap();
-
-            // default filter by id
-            table.update(last_ledger.clone(), None).await?;
-
-            // TODO: double check this condition
-            if last_ledger.volume == update.filled_size {
-                ledgers.pop();
+            self.funding_table.insert(row).await?;
+        }
+        self.write(changes).await
+    }
+    pub async fn mark(&mut self) -> Result<()> {
+        let now = Time::now().millis();
+        let instruments = self.instruments.load();
+        let price_map = self.price_map.clone();
+        let changes = self
+            .ledger
+            .mark(|instrument| mark_price(&instruments, &price_map, instrument), now);
+        self.write(changes).await?;
+        for (strategy_id, unrealized) in self.ledger.unrealized_usd() {
+            unrealized_pnl_usd(strategy_id as _).set(unrealized);
+        }
+        self.orders
+            .retain(|_, x| !x.update.status.is_dead() || now - x.update.update_lt.millis() < DEAD_ORDER_TTL_MS);
+        let orders = &self.orders;
+        self.server_ids.retain(|_, local_id| orders.contains_key(local_id));
+        Ok(())
+    }
+    pub async fn handle_response(&mut self, response: ExecutionResponse) -> Result<()> {
+        let mut responses = vec![response];
+        while let Some(response) = responses.pop() {
+            match response {
+                ExecutionResponse::TradeOrder(trade) => self.handle_trade(trade).await?,
+                ExecutionResponse::UpdateFunding(funding) => self.handle_funding(funding).await?,
+                ExecutionResponse::Group(group) => responses.extend(group.into_iter().rev()),
+                _ => {}
             }
         }
         Ok(())
     }
-    pub async fn run(&mut self, rx_update: AsyncReceiver<UpdateOrder>) -> Result<()> {
-        while let Ok(update) = rx_update.recv().await {
-            self.handle_order_update(update).await?;
+    /// runs until the order updates or the execution responses stop. a failed write is logged and the
+    /// event skipped, the ledger keeps booking the next ones
+    pub async fn run(
+        &mut self,
+        rx_update: AsyncReceiver<UpdateOrder>,
+        rx_response: AsyncReceiver<ExecutionResponse>,
+    ) -> Result<()> {
+        self.load().await?;
+        let mut mark_interval = tokio::time::interval(Duration::from_millis(self.config.mark_interval_ms as _));
+        loop {
+            tokio::select! {
+                update = rx_update.recv() => {
+                    let Ok(update) = update else {
+                        info!("order updates closed, ledger stopped");
+                        return Ok(());
+                    };
+                    let local_id = update.local_id.clone();
+                    if let Err(err) = self.handle_order_update(update).await {
+                        error!("failed to book order update {local_id}: {err:?}");
+                    }
+                }
+                response = rx_response.recv() => {
+                    let Ok(response) = response else {
+                        info!("execution responses closed, ledger stopped");
+                        return Ok(());
+                    };
+                    if let Err(err) = self.handle_response(response).await {
+                        error!("failed to book execution response: {err:?}");
+                    }
+                }
+                _ = mark_interval.tick() => {
+                    if let Err(err) = self.mark().await {
+                        error!("failed to mark the ledger: {err:?}");
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::gluesql::schema::common::TableName;
+    use crate::db::worktable::order_manager::OrderManager;
+    use crate::main_core::STRATEGY_IDS;
+    use gluesql_shared_sled_storage::Config as SledConfig;
+    use lib::gluesql::TableCreate;
+    use trading_exchange::model::{OrderStatus, PositionEffect};
+    use trading_model::{Exchange, InstrumentCode, Side};
+
+    async fn ledger_manager(
+        storage: &SharedSledStorage,
+        order_manager: SharedOrderManager,
+    ) -> Result<(LedgerManager, Table<SharedSledStorage, DbRowLedger>)> {
+        let table_name = TableName::new(&STRATEGY_IDS);
+        let mut ledger: Table<SharedSledStorage, DbRowLedger> = Table::new(&table_name.ledger, storage.clone());
+        ledger.create_table().await?;
+        let mut funding: Table<SharedSledStorage, DbRowFundingAccrual> =
+            Table::new(&table_name.funding_accrual, storage.clone());
+        funding.create_table().await?;
+        let mut pnl: Table<SharedSledStorage, DbRowPositionPnl> = Table::new(&table_name.position_pnl, storage.clone());
+        pnl.create_table().await?;
+        let manager = LedgerManager::new(
+            ledger.clone(),
+            funding,
+            pnl,
+            order_manager,
+            Arc::new(DynamicInstrumentManager::new(Arc::new(InstrumentManager::new()))),
+            Arc::new(LastPriceMap::new()),
+            LedgerConfig::default(),
+            FeeSchedule::default(),
+        );
+        Ok((manager, ledger))
+    }
+
+    fn order(local_id: &str, server_id: u64) -> UpdateOrder {
+        UpdateOrder {
+            account: 1,
+            instrument: InstrumentCode::from_symbol(Exchange::Hyperliquid, "BTC".into()),
+            local_id: local_id.into(),
+            client_id: local_id.into(),
+            server_id: server_id.into(),
+            side: Side::Buy,
+            effect: PositionEffect::Open,
+            ty: OrderType::Limit,
+            tif: TimeInForce::GoodTilCancel,
+            status: OrderStatus::Open,
+            size: 2.0,
+            price: 100.0,
+            strategy_id: 2,
+            ..UpdateOrder::empty()
+        }
+    }
+
+    /// the trades as the Hyperliquid connector reports them, by the server id of the order
+    fn hyperliquid_trade(server_id: u64, size: f64) -> OrderTrade {
+        OrderTrade {
+            account: 1,
+            instrument: InstrumentCode::from_symbol(Exchange::Hyperliquid, "BTC".into()),
+            price: 100.0,
+            size,
+            side: Side::Buy,
+            fee: 0.01,
+            fee_asset: "USD".into(),
+            order_lid: format!("HYPERLIQUID|{server_id}").into(),
+            exchange_time: Time::from_millis(1),
+            ..OrderTrade::empty()
         }
+    }
+
+    #[tokio::test]
+    async fn test_hyperliquid_trades_are_booked_by_server_id() -> Result<()> {
+        let database = tempfile::tempdir()?;
+        let storage = SharedSledStorage::new(SledConfig::default().path(database.path()), true)?;
+        let order_manager: SharedOrderManager = Arc::new(tokio::sync::RwLock::new(OrderManager::new()));
+        let (mut manager, mut ledger) = ledger_manager(&storage, order_manager.clone()).await?;
+
+        // the update of the order came first
+        manager.handle_order_update(order("a", 123)).await?;
+        manager.handle_trade(hyperliquid_trade(123, 1.0)).await?;
+        let lots = ledger.select(None, "id").await?;
+        assert_eq!(lots.len(), 1);
+        assert_eq!(lots[0].open_order_id, "a");
+        assert_eq!(lots[0].volume, 1.0);
+
+        // the trade came before the first update, the order is found in the order manager
+        order_manager.write().await.orders.insert_update(&order("b", 456));
+        manager.handle_trade(hyperliquid_trade(456, 2.0)).await?;
+        let lots = ledger.select(None, "id").await?;
+        assert_eq!(lots.len(), 2);
+        assert_eq!(lots[1].open_order_id, "b");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_run_stops_when_the_channels_close() -> Result<()> {
+        let database = tempfile::tempdir()?;
+        let storage = SharedSledStorage::new(SledConfig::default().path(database.path()), true)?;
+        let order_manager: SharedOrderManager = Arc::new(tokio::sync::RwLock::new(OrderManager::new()));
+        let (mut manager, _) = ledger_manager(&storage, order_manager).await?;
+        let (tx_update, rx_update) = kanal::unbounded_async();
+        let (tx_response, rx_response) = kanal::unbounded_async();
+        drop(tx_update);
+        drop(tx_response);
+        tokio::time::timeout(Duration::from_secs(5), manager.run(rx_update, rx_response)).await??;
         Ok(())
     }
 }
@@ -1,6 +1,7 @@
 extern crate core;
 
-pub const APP_VERSION: u64 = 1;
+/// schema version of the persistent database, bumped with each migration in `db::gluesql::migration`
+pub const APP_VERSION: u64 = 4;
 use std::sync::Arc;
 
 /// config
@@ -15,11 +16,15 @@ pub mod execution;
 
 /// core runner
 pub mod main_core;
+/// prometheus metrics
+pub mod metrics;
 /// shared across services
 pub mod signals;
 
 pub mod balance_manager;
 pub mod leger_manager;
+/// lots, realized and unrealized PnL of the strategy positions
+pub mod position_ledger;
 /// strategy trait and implementation
 pub mod strategy;
 pub mod task;
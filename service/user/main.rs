@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use gluesql_shared_sled_storage::SharedSledStorage;
 use lib::gluesql::{Table, TableCreate};
 use lib::log::setup_logs;
@@ -10,11 +10,16 @@ use lib::ws::WebsocketServer;
 use parking_lot::RwLock;
 use tracing::info;
 use trading_be::config::Config;
+use trading_be::db::columnar::{job_directory, run_export, ExportDataset, ExportFormat, ExportRequest, ExportStorage};
+use trading_be::db::gluesql::backup::{restore_backup, write_backup};
+use trading_be::db::gluesql::migration::MigrationDryRunFinished;
+use trading_be::db::gluesql::schema::common::TableName;
 use trading_be::db::gluesql::schema::settings::{CheckAppVersion, DbRowApplicationSetting, APP_SETTINGS};
 use trading_be::endpoint_method::get_spread_mean::MethodUserGet5MinSpreadMean;
 use trading_be::endpoint_method::*;
-use trading_be::main_core::{get_sled_storage, main_core, MainStruct};
+use trading_be::main_core::{get_sled_storage, main_core, MainStruct, STRATEGY_IDS};
 use trading_be::APP_VERSION;
+use trading_model::{Exchange, TimeStampMs};
 
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
@@ -24,6 +29,49 @@ pub struct CliArgument {
     pub config: PathBuf,
     /// the location to read the log file
     pub log_file: Option<PathBuf>,
+    /// apply pending database migrations to an in-memory copy and exit
+    #[clap(long)]
+    pub migration_dry_run: bool,
+    #[clap(subcommand)]
+    pub command: Option<CliCommand>,
+}
+
+#[derive(Subcommand)]
+pub enum CliCommand {
+    /// snapshot the database into the backup directory and exit, the service must be stopped.
+    /// a running service is snapshotted through the UserBackupDatabase endpoint
+    Backup {
+        #[clap(long, default_value = "manual")]
+        label: String,
+    },
+    /// verify a backup and swap it in place of the database directory, the service must be stopped
+    Restore {
+        #[clap(value_name = "FILE")]
+        file: PathBuf,
+        /// only check the backup can be restored, the database is left untouched
+        #[clap(long)]
+        verify_only: bool,
+    },
+    /// write a time range of the persistent history as partitioned Parquet/CSV files and exit.
+    /// the volatile datasets (prices, signals, events) are exported through the UserStartExport endpoint
+    Export {
+        /// candles, liquidations, funding, orders or ledger
+        #[clap(long)]
+        dataset: ExportDataset,
+        #[clap(long, default_value = "parquet")]
+        format: ExportFormat,
+        /// inclusive, ms
+        #[clap(long)]
+        time_start: TimeStampMs,
+        /// exclusive, ms
+        #[clap(long)]
+        time_end: TimeStampMs,
+        #[clap(long)]
+        exchange: Option<Exchange>,
+        /// defaults to the export directory of the config
+        #[clap(long)]
+        output: Option<PathBuf>,
+    },
 }
 
 #[tokio::main]
@@ -31,7 +79,8 @@ async fn main() -> eyre::Result<()> {
     let (mut sigterm, mut sigint) = lib::signal::init_signals().expect("signals could not be generated");
     let cli_args: CliArgument = CliArgument::parse();
     let config_path = cli_args.config;
-    let config = Config::try_from(config_path).expect("failed parsing config");
+    let mut config = Config::try_from(config_path).expect("failed parsing config");
+    config.database.migration_dry_run |= cli_args.migration_dry_run;
     println!("{config:#?}");
 
     let guard = setup_logs(config.log.level, config.log.file.clone()).expect("failed setting up logs");
@@ -39,6 +88,63 @@ async fn main() -> eyre::Result<()> {
     let localset = tokio::task::LocalSet::new();
     let _enter = localset.enter();
 
+    match cli_args.command {
+        Some(CliCommand::Backup { label }) => {
+            let storage = get_sled_storage(&config).await?;
+            let path = write_backup(&storage, &config.database.backup_directory(), &label).await?;
+            info!("backup written to {}", path.display());
+            return Ok(());
+        }
+        Some(CliCommand::Restore { file, verify_only }) => {
+            let table_name = TableName::new(&STRATEGY_IDS);
+            let version = restore_backup(&file, &config.database, &table_name, verify_only).await?;
+            info!(
+                "backup {} at version {} restored (verify only: {})",
+                file.display(),
+                version,
+                verify_only
+            );
+            return Ok(());
+        }
+        Some(CliCommand::Export {
+            dataset,
+            format,
+            time_start,
+            time_end,
+            exchange,
+            output,
+        }) => {
+            let root = output.unwrap_or_else(|| config.database.export_directory());
+            let storage = ExportStorage {
+                persistent: get_sled_storage(&config).await?,
+                volatile: None,
+                table_name: TableName::new(&STRATEGY_IDS),
+                // the instruments are not loaded, assets and symbols are partitioned by id
+                names: HashMap::new(),
+            };
+            let request = ExportRequest {
+                dataset,
+                format,
+                time_start,
+                time_end,
+                exchange,
+                directory: job_directory(&root, "cli", dataset),
+            };
+            let progress = run_export(&storage, &request, &mut |progress| {
+                info!("export {}/{}", progress.steps_done, progress.steps_total)
+            })
+            .await?;
+            info!(
+                "{} rows exported into {} files under {}",
+                progress.rows,
+                progress.files,
+                request.directory.display()
+            );
+            return Ok(());
+        }
+        None => {}
+    }
+
     let storage = get_sled_storage(&config).await?;
     let mut table: Table<SharedSledStorage, DbRowApplicationSetting> = Table::new(APP_SETTINGS, storage.clone());
     table.create_table().await?;
@@ -47,14 +153,27 @@ async fn main() -> eyre::Result<()> {
         std::process::exit(10);
     }
 
-    let mut main_struct: MainStruct = main_core(config.clone(), storage, false)
-        .await
-        .expect("main_core failed gathering data");
+    let mut main_struct: MainStruct = match main_core(config.clone(), storage, false).await {
+        Ok(main_struct) => main_struct,
+        Err(err) if err.downcast_ref::<MigrationDryRunFinished>().is_some() => {
+            info!("{err}");
+            return Ok(());
+        }
+        Err(err) => panic!("main_core failed gathering data: {err:?}"),
+    };
     if config.skip_key {
         // bypass UserStartService, number of permits doesn't matter
         main_struct.start_service.add_permits(1000);
     }
 
+    if let Some(address) = config.metrics_address.clone() {
+        tokio::spawn(async move {
+            if let Err(err) = lib::metrics::serve_metrics(address).await {
+                tracing::error!("metrics server terminated, {err:?}");
+            }
+        });
+    }
+
     let map_key = Arc::new(RwLock::new(HashMap::default()));
     let mut server = WebsocketServer::new(config.server.clone());
 
@@ -117,6 +236,7 @@ async fn main() -> eyre::Result<()> {
     server.add_handler(MethodUserStatus::new());
     server.add_handler(MethodUserSetStrategyStatus {
         strategy_status: main_struct.table_map.volatile.strategy_status.clone(),
+        recovery: main_struct.registry.get_unwrap(),
     });
     blacklist::init_endpoints(&mut server, &mut main_struct);
 
@@ -199,6 +319,10 @@ async fn main() -> eyre::Result<()> {
 
         server.add_handler(MethodUserGetLedger {
             table: main_struct.table_map.persistent.ledger.clone(),
+            funding_table: main_struct.table_map.persistent.funding_accrual.clone(),
+        });
+        server.add_handler(MethodUserGetPositionPnl {
+            table: main_struct.table_map.persistent.position_pnl.clone(),
         });
         server.add_handler(MethodUserGetStrategyOneAccuracy {
             table_accuracy: main_struct.table_map.volatile.accuracy[&strategy_id].clone(),
@@ -243,6 +367,23 @@ async fn main() -> eyre::Result<()> {
     server.add_handler(MethodUserSubFundingRates::new(
         main_struct.table_map.volatile.funding_rate.clone(),
     ));
+    server.add_handler(MethodUserSubLiquidations::new(
+        main_struct.table_map.persistent.liquidation.clone(),
+    ));
+    server.add_handler(MethodUserGetCandles {
+        table: main_struct.table_map.persistent.candle.clone(),
+        candle_map: main_struct.table_map.volatile.candle_map.clone(),
+    });
+    server.add_handler(MethodUserSubCandles::new(
+        main_struct.table_map.volatile.candle_map.clone(),
+    ));
+    server.add_handler(MethodUserSubFeedHealth::new(main_struct.registry.get_unwrap()));
+    server.add_handler(MethodUserGetFundingHistory {
+        table: main_struct.table_map.persistent.funding_history.clone(),
+    });
+    server.add_handler(MethodUserGetFundingCarry {
+        carry_map: main_struct.table_map.volatile.funding_carry_map.clone(),
+    });
     server.add_handler(MethodUserSubPosition::new(
         main_struct.table_map.volatile.position_manager.clone(),
     ));
@@ -264,6 +405,29 @@ async fn main() -> eyre::Result<()> {
     server.add_handler(MethodUserGet5MinSpreadMean::new(
         main_struct.table_map.volatile.spread_mean.clone(),
     ));
+    server.add_handler(MethodUserBackupDatabase {
+        storage: main_struct.registry.get_unwrap(),
+        directory: config.database.backup_directory(),
+    });
+    server.add_handler(MethodUserStartExport {
+        jobs: main_struct.registry.get_unwrap(),
+    });
+    server.add_handler(MethodUserSubExportJobs::new(main_struct.registry.get_unwrap()));
+    server.add_handler(MethodUserGetAccountingHalts {
+        halts: main_struct.registry.get_unwrap(),
+    });
+    server.add_handler(MethodUserAcknowledgeAccountingHalt {
+        halts: main_struct.registry.get_unwrap(),
+        tx_request: main_struct.registry.get_unwrap(),
+    });
+    server.add_handler(MethodUserGetExecutionAccounts {
+        accounts: main_struct.registry.get_unwrap(),
+    });
+    server.add_handler(MethodUserSetStrategyAccount {
+        accounts: main_struct.registry.get_unwrap(),
+        table_ledger: main_struct.table_map.persistent.ledger.clone(),
+        order_manager: main_struct.table_map.volatile.order_manager.clone(),
+    });
 
     localset
         .run_until(async {
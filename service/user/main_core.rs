@@ -1,21 +1,36 @@
 use crate::balance_manager::BalanceManager;
+use crate::config::DatabaseConfig;
+use crate::db::columnar::{ExportJobMap, ExportStorage};
+use crate::db::gluesql::backup::BackupScheduler;
 use crate::db::gluesql::schema::common::{StrategyId, TableName};
 use crate::db::gluesql::schema::price_volume::PriceVolumeManager;
 use crate::db::gluesql::schema::DbRowPriceVolume;
 use crate::db::gluesql::TableMap;
+use crate::db::postgres::exporter::{ExportMode, ExportSource, PostgresExporter};
+use crate::db::retention::{self, RetentionScheduler};
 use crate::events::price_change_and_diff::DbRowEventPriceChangeAndDiff;
 use crate::execution::{
-    BatchOrderManager, ExecutionKeys, ExecutionRouter, OrderRegistry, PlaceBatchOrders, SharedBatchOrders,
+    AccountingHalts, BatchOrderManager, ExecutionAccounts, ExecutionKeys, ExecutionRouter, OrderJournal, OrderRecovery,
+    OrderRegistry, PlaceBatchOrders, SharedBatchOrders,
 };
 use crate::leger_manager::LedgerManager;
+use crate::signals::candles::CandleService;
+use crate::signals::executable_price::ExecutablePriceModel;
+use crate::signals::fair_price::{DbRowSignalFairPriceDifference, FairPriceEngine};
+use crate::signals::funding::{FundingCarryCalculator, FundingCarrySignal, FundingHistoryService, FundingIntervalMap};
 use crate::signals::price_change::{DbRowSignalPriceChange, DbRowSignalPriceChangeImmediate};
 use crate::signals::price_difference::{
     DbRowSignalPriceDifference, DbRowSignalPriceDifferenceGeneric, PriceDifferenceCalculator,
 };
 use crate::signals::price_manager::PriceManager;
 use crate::signals::price_spread::{DbRowSignalBestBidAskAcrossExchanges, SignalSpreadAccumulator};
+use crate::signals::trade_flow::{DbRowSignalTradeFlow, TradeFlowSignalGenerator};
 use crate::strategy::broadcast::AsyncBroadcaster;
 use crate::strategy::data_factory::{get_instrument_manager, BuffferedPriceUpdateConverter};
+use crate::strategy::feed_health::{FeedHealthMap, FeedSupervisor};
+use crate::strategy::funding_carry::order_placement::FundingCarryOrderPlacement;
+use crate::strategy::instrument::convert_asset_to_instrument;
+use crate::strategy::instrument_refresh::{InstrumentChange, InstrumentRefresher};
 use crate::strategy::strategy_one::bin_bid_predict_hyper_bid::{DetectSignalPriceChange, DetectSignalPriceDifference};
 use crate::strategy::strategy_one::order_placement::StrategyOneResponseHandler;
 use crate::strategy::strategy_one::testing::{LiveTestFillPrice, StrategyOneTest};
@@ -23,7 +38,10 @@ use crate::strategy::strategy_two::order_placement::Strategy2OrderPlacement;
 use crate::strategy::strategy_two_and_three::capture_event::CaptureCommon;
 use crate::strategy::strategy_two_and_three::event::BestBidAskAcrossExchangesAndPositionEventGenerator;
 use crate::strategy::strategy_two_and_three::StrategyTwoAndThreeEvent;
-use crate::strategy::{data_factory, strategy_debug, strategy_one, strategy_zero, table_limiter, StrategyStatusMap};
+use crate::strategy::{
+    data_factory, funding_carry, strategy_constants, strategy_debug, strategy_one, strategy_two_and_three,
+    strategy_zero, StrategyStatusMap,
+};
 use crate::task::{Registry, TaskBuilder};
 use crate::ServiceStarter;
 use eyre::{bail, Context};
@@ -92,6 +110,9 @@ macro_rules! single_thread_spawn {
     }};
 }
 
+/// strategies with their own tables
+pub const STRATEGY_IDS: [StrategyId; 5] = [0, 1, 2, 3, funding_carry::STRATEGY_ID as StrategyId];
+
 pub async fn get_sled_storage(config: &crate::config::Config) -> eyre::Result<SharedSledStorage> {
     let path_persistent_db = if can_create_file_in_directory(config.database.directory.to_str().unwrap()) {
         config.database.directory.clone()
@@ -112,6 +133,7 @@ pub async fn build_table_map(
     assets: Vec<Asset>,
     strategies: &[StrategyId],
     instruments: SharedInstrumentManager,
+    database: &DatabaseConfig,
 ) -> eyre::Result<TableMap> {
     let table_name = TableName::new(strategies);
     let table_map = TableMap::new(
@@ -120,18 +142,19 @@ pub async fn build_table_map(
         &table_name,
         assets.clone(),
         instruments,
+        database,
     )
-    .await;
+    .await?;
     Ok(table_map)
 }
 
 /// generator for main struct to be used by the server
 pub async fn main_core(
-    _config: crate::config::Config,
+    config: crate::config::Config,
     storage: SharedSledStorage,
     bind_core: bool,
 ) -> eyre::Result<MainStruct> {
-    let strategies = [0, 1, 2, 3];
+    let strategies = STRATEGY_IDS;
 
     let mut registry = Registry::new();
     let start_service = Arc::new(Semaphore::new(0));
@@ -146,21 +169,61 @@ pub async fn main_core(
         }
     }
 
-    let exchanges = vec![Exchange::BinanceFutures, Exchange::Hyperliquid];
+    let traded_exchanges = vec![Exchange::BinanceFutures, Exchange::Hyperliquid];
+    // the venues only feeding the fair price are loaded and refreshed with the traded ones
+    let mut exchanges = traded_exchanges.clone();
+    for source in config.fair_price.sources.iter() {
+        if !exchanges.contains(&source.exchange) {
+            exchanges.push(source.exchange);
+        }
+    }
     let instruments = get_instrument_manager(exchanges.clone())
         .await
         .context("failed obtaining instruments from exchange")?;
 
     // assets that exists on all selected exchanges
     let mut assets = vec![];
-    for instrument in instruments.iter() {
+    for instrument in instruments.iter().filter(|x| traded_exchanges.contains(&x.exchange)) {
         assets.push(instrument.base.asset.clone());
         assets.push(instrument.quote.asset.clone());
     }
     assets.sort();
     assets.dedup();
 
-    let table_map = build_table_map(storage, assets.clone(), &strategies, instruments.clone()).await?;
+    // the persistent storage is shared with the backup endpoint
+    registry.add_cloned(storage.clone());
+    let table_map = build_table_map(
+        storage.clone(),
+        assets.clone(),
+        &strategies,
+        instruments.clone(),
+        &config.database,
+    )
+    .await?;
+    // in-flight orders of the last run, resolved by the execution router before opening orders are accepted
+    let journal = if config.journal.enabled {
+        let journal = OrderJournal::open(config.journal.path(&config.database))?;
+        let mut order_manager = table_map.volatile.order_manager.write().await;
+        for update in journal.in_flight() {
+            order_manager.insert_update(update).await;
+        }
+        // replayed orders are not news to the strategies
+        order_manager.drain().for_each(drop);
+        Some(journal)
+    } else {
+        None
+    };
+    let recovery = Arc::new(OrderRecovery::new(
+        journal.as_ref().map(|x| x.in_flight()).unwrap_or_default(),
+    ));
+    registry.add_cloned(recovery.clone());
+    registry.add_cloned(Arc::new(AccountingHalts::new()));
+    registry.add_cloned(Arc::new(ExecutionAccounts::new(&config.strategy_accounts)));
+    let feed_health = Arc::new(FeedHealthMap::new(
+        table_map.volatile.instruments.clone(),
+        config.feed.stale_ms,
+    ));
+    registry.add_cloned(feed_health.clone());
 
     {
         // gather channels and handles, make it bounded to prevent memory overflow
@@ -170,6 +233,12 @@ pub async fn main_core(
         registry.add_cloned(tx_strategy_status);
         registry.add_taken(rx_strategy_status);
     }
+    {
+        // listings, delistings and parameter changes found by the instrument refresher
+        let tx: AsyncBroadcaster<InstrumentChange> = AsyncBroadcaster::new(BUFFER_SIZE);
+        registry.add_cloned(tx.clone());
+        registry.add_fn(move || tx.subscribe());
+    }
     {
         // market feed produced by exchange client
         let channel_feed = AsyncBroadcaster::<MarketEvent>::new(BUFFER_SIZE);
@@ -204,6 +273,19 @@ pub async fn main_core(
         registry.add_cloned(tx_signal_diff_generic.clone());
         registry.add_fn(move || tx_signal_diff_generic.subscribe());
     }
+    {
+        // order-flow signal from the trades of the feeds
+        let tx_signal_trade_flow: AsyncBroadcaster<DbRowSignalTradeFlow> = AsyncBroadcaster::new(BUFFER_SIZE_MINIMAL);
+        registry.add_cloned(tx_signal_trade_flow.clone());
+        registry.add_fn(move || tx_signal_trade_flow.subscribe());
+    }
+    {
+        // venues deviating from the fair price
+        let tx_signal_fair_price: AsyncBroadcaster<DbRowSignalFairPriceDifference> =
+            AsyncBroadcaster::new(BUFFER_SIZE_MINIMAL);
+        registry.add_cloned(tx_signal_fair_price.clone());
+        registry.add_fn(move || tx_signal_fair_price.subscribe());
+    }
     {
         // event in strategy 1
         let tx_event_one: AsyncBroadcaster<DbRowEventPriceChangeAndDiff> = AsyncBroadcaster::new(BUFFER_SIZE_MINIMAL);
@@ -242,7 +324,7 @@ pub async fn main_core(
         registry.add_fn(move || update_order_broadcast.subscribe());
     }
     {
-        let balance_manager = BalanceManager::new(table_map.volatile.worktable_balance.clone());
+        let balance_manager = BalanceManager::new(table_map.volatile.worktable_balance.clone(), registry.get_unwrap());
         registry.add_cloned(balance_manager.clone());
     }
 
@@ -261,16 +343,19 @@ pub async fn main_core(
         // let tx_feed = tx_market.clone();
         let assets = instruments
             .iter()
-            .filter(|x| x.exchange == Exchange::Hyperliquid && x.quote.asset.as_str() == "USD" && !x.ty.is_delivery())
+            .filter(|x| data_factory::is_hyper_feed_instrument(x))
             .map(|x| x.instrument_symbol.clone())
             .collect();
+        let health = feed_health.clone();
+        let feed_config = config.feed.clone();
+        let rx_changes = registry.get_unwrap();
         single_thread_spawn!(
             start_service.clone(),
             thread_name,
             thread_names,
             &tx_thread_term,
             None,
-            data_factory::market_feed_hyper(tx_feed, assets)
+            data_factory::market_feed_hyper(tx_feed, assets, health, feed_config, rx_changes)
         );
     }
     {
@@ -292,21 +377,165 @@ pub async fn main_core(
         let tx_feed = registry.get_unwrap();
         let symbols = instruments
             .iter()
-            .filter(|x| {
-                x.exchange == Exchange::BinanceFutures && x.quote.asset.as_str() == "USDT" && !x.ty.is_delivery()
-            })
+            .filter(|x| data_factory::is_binance_feed_instrument(x))
+            .map(|x| x.instrument_symbol.clone())
+            .collect();
+        let health = feed_health.clone();
+        let feed_config = config.feed.clone();
+        let rx_changes = registry.get_unwrap();
+        single_thread_spawn!(
+            start_service.clone(),
+            thread_name,
+            thread_names,
+            &tx_thread_term,
+            None,
+            data_factory::market_feed_binance(tx_feed, symbols, health, feed_config, rx_changes)
+        );
+    }
+    for &exchange in exchanges.iter().filter(|x| !traded_exchanges.contains(x)) {
+        // venues feeding the fair price, on the assets traded above
+        let thread_name = format!("market_feed_{}_ws", exchange.ticker().to_lowercase());
+        let tx_feed = registry.get_unwrap();
+        let symbols = instruments
+            .iter()
+            .filter(|x| x.exchange == exchange && data_factory::is_fair_price_feed_instrument(x))
+            .filter(|x| assets.contains(&x.base.asset))
             .map(|x| x.instrument_symbol.clone())
             .collect();
+        let health = feed_health.clone();
+        let feed_config = config.feed.clone();
+        let rx_changes = registry.get_unwrap();
+        single_thread_spawn!(
+            start_service.clone(),
+            thread_name,
+            thread_names,
+            &tx_thread_term,
+            None,
+            data_factory::market_feed_fair_price_source(tx_feed, exchange, symbols, health, feed_config, rx_changes)
+        );
+    }
+    {
+        // reload of the instruments, new listings are subscribed by the feeds above
+        let thread_name = "instrument_refresher".to_string();
+        let refresher = InstrumentRefresher {
+            instruments: table_map.volatile.instruments.clone(),
+            exchanges: exchanges.clone(),
+            tx: registry.get_unwrap(),
+        };
+        single_thread_spawn!(
+            start_service.clone(),
+            thread_name,
+            thread_names,
+            &tx_thread_term,
+            None,
+            refresher.run()
+        );
+    }
+    {
+        // staleness of the feeds above
+        let thread_name = "feed_supervisor".to_string();
+        let supervisor = FeedSupervisor {
+            health: feed_health.clone(),
+            exchanges: exchanges.clone(),
+        };
         single_thread_spawn!(
             start_service.clone(),
             thread_name,
             thread_names,
             &tx_thread_term,
             None,
-            data_factory::market_feed_binance(tx_feed, symbols)
+            supervisor.run()
         );
     }
 
+    {
+        // candle history of the subscribed instruments
+        let thread_name = "candle_service".to_string();
+        let candle_service = CandleService {
+            config: config.candles.clone(),
+            table: table_map.persistent.candle.clone(),
+            candle_map: table_map.volatile.candle_map.clone(),
+            instruments: instruments
+                .iter()
+                .filter(|x| {
+                    (x.exchange == Exchange::Hyperliquid && x.quote.asset.as_str() == "USD"
+                        || x.exchange == Exchange::BinanceFutures && x.quote.asset.as_str() == "USDT")
+                        && !x.ty.is_delivery()
+                })
+                .map(|x| x.instrument_symbol.clone())
+                .collect(),
+        };
+        single_thread_spawn!(
+            start_service.clone(),
+            thread_name,
+            thread_names,
+            &tx_thread_term,
+            None,
+            candle_service.run()
+        );
+    }
+    // funding intervals listed by the venues, refreshed with the history
+    let funding_intervals = Arc::new(FundingIntervalMap::new());
+    {
+        // settled funding rates of the subscribed instruments
+        let thread_name = "funding_history_service".to_string();
+        let funding_history_service = FundingHistoryService {
+            config: config.funding.clone(),
+            table: table_map.persistent.funding_history.clone(),
+            intervals: funding_intervals.clone(),
+            instruments: instruments
+                .iter()
+                .filter(|x| {
+                    (x.exchange == Exchange::Hyperliquid && x.quote.asset.as_str() == "USD"
+                        || x.exchange == Exchange::BinanceFutures && x.quote.asset.as_str() == "USDT")
+                        && !x.ty.is_delivery()
+                })
+                .map(|x| x.instrument_symbol.clone())
+                .collect(),
+        };
+        single_thread_spawn!(
+            start_service.clone(),
+            thread_name,
+            thread_names,
+            &tx_thread_term,
+            None,
+            funding_history_service.run()
+        );
+    }
+    {
+        let tx: AsyncBroadcaster<FundingCarrySignal> = AsyncBroadcaster::new(BUFFER_SIZE);
+        registry.add_cloned(tx.clone());
+        registry.add_fn(move || tx.subscribe());
+    }
+    {
+        // predicted funding of the assets perpetual on both venues
+        let thread_name = "funding_carry".to_string();
+        let pairs = instruments
+            .iter()
+            .filter(|x| x.exchange == Exchange::Hyperliquid && x.quote.asset.as_str() == "USD" && !x.ty.is_delivery())
+            .filter_map(|hyper| {
+                let asset = hyper.base.asset.clone();
+                let binance = convert_asset_to_instrument(&instruments, Exchange::BinanceFutures, &asset)?;
+                Some((asset, binance.code_symbol.clone(), hyper.code_symbol.clone()))
+            })
+            .collect();
+        let calculator = FundingCarryCalculator {
+            derivatives_map: table_map.volatile.derivatives_map.clone(),
+            carry_map: table_map.volatile.funding_carry_map.clone(),
+            pairs,
+            tx: registry.get_unwrap(),
+            intervals: funding_intervals.clone(),
+            feed_health: feed_health.clone(),
+        };
+        single_thread_spawn!(
+            start_service.clone(),
+            thread_name,
+            thread_names,
+            &tx_thread_term,
+            None,
+            calculator.run()
+        );
+    }
     {
         // price manager
         let mut price_manager = PriceManager {
@@ -319,6 +548,13 @@ pub async fn main_core(
                 table_map.volatile.instruments.clone(),
             ),
             table_candlestick: table_map.volatile.candlestick.clone(),
+            table_liquidation: table_map.persistent.liquidation.clone(),
+            table_candle: table_map.persistent.candle.clone(),
+            candle_map: table_map.volatile.candle_map.clone(),
+            trade_flow_map: table_map.volatile.trade_flow_map.clone(),
+            derivatives_map: table_map.volatile.derivatives_map.clone(),
+            orderbook_map: table_map.volatile.orderbook_map.clone(),
+            feed_health: feed_health.clone(),
             orderbooks: Default::default(),
         };
         let thread_name = "price_manager".to_string();
@@ -331,6 +567,129 @@ pub async fn main_core(
             price_manager.run()
         );
     }
+    {
+        // trade flow signal
+        let generator = TradeFlowSignalGenerator {
+            trade_flow: table_map.volatile.trade_flow_map.clone(),
+            tx: registry.get_unwrap(),
+            table: table_map.volatile.signal_trade_flow.clone(),
+            feed_health: feed_health.clone(),
+        };
+        let thread_name = "trade_flow".to_string();
+        single_thread_spawn!(
+            start_service.clone(),
+            thread_name,
+            thread_names,
+            &tx_thread_term,
+            None,
+            generator.run()
+        );
+    }
+    {
+        // composite fair price, tradable venues are signalled against it
+        let engine = FairPriceEngine {
+            config: config.fair_price.clone(),
+            price_map: table_map.volatile.price_map.clone(),
+            fair_price_map: table_map.volatile.fair_price_map.clone(),
+            instruments: table_map.volatile.instruments.clone(),
+            tx: registry.get_unwrap(),
+            table: table_map.volatile.signal_fair_price_diff.clone(),
+        };
+        let thread_name = "fair_price".to_string();
+        single_thread_spawn!(
+            start_service.clone(),
+            thread_name,
+            thread_names,
+            &tx_thread_term,
+            None,
+            engine.run()
+        );
+    }
+    if config.postgres_export.url.is_some() {
+        // analytics copy of orders, ledgers, funding, signals, events and fills
+        let persistent = &table_map.persistent;
+        let volatile = &table_map.volatile;
+        let persistent_sources = vec![
+            ExportSource::append(persistent.funding_history.clone()),
+            ExportSource::append(persistent.funding_accrual.clone()),
+            ExportSource::mutable(persistent.order.clone()),
+            ExportSource::mutable(persistent.ledger.clone()),
+            // one id sequence since the table was added
+            ExportSource::new(persistent.position_pnl.clone(), &["id"], ExportMode::Mutable),
+        ];
+        let mut volatile_sources = vec![
+            ExportSource::append(volatile.signal_price_change.clone()),
+            ExportSource::append(volatile.signal_price_difference_generic.clone()),
+            ExportSource::append(volatile.signal_price_change_immediate.clone()),
+            ExportSource::append(volatile.signal_trade_flow.clone()),
+            ExportSource::append(volatile.signal_fair_price_diff.clone()),
+            // no id, tailed by time
+            ExportSource::new(
+                volatile.livetest_fill.clone(),
+                &["symbol_id", "datetime"],
+                ExportMode::Append { cursor: "datetime" },
+            ),
+        ];
+        volatile_sources.extend(
+            volatile
+                .signal_price_difference
+                .values()
+                .cloned()
+                .map(ExportSource::append),
+        );
+        volatile_sources.extend(volatile.event_price_change.values().cloned().map(ExportSource::append));
+        let exporter = PostgresExporter::new(
+            config.postgres_export.clone(),
+            config.postgres_export.queue_directory(&config.database),
+            persistent_sources,
+            volatile_sources,
+        )?;
+        let thread_name = "postgres_export".to_string();
+        single_thread_spawn!(
+            start_service.clone(),
+            thread_name,
+            thread_names,
+            &tx_thread_term,
+            None,
+            exporter.run()
+        );
+    }
+    {
+        // Parquet/CSV exports started from the endpoint
+        let mut names = HashMap::new();
+        for instrument in instruments.iter() {
+            names.insert(instrument.symbol._hash(), instrument.symbol.to_string());
+            names.insert(instrument.base.asset._hash(), instrument.base.asset.to_string());
+        }
+        let export_storage = ExportStorage {
+            persistent: storage.clone(),
+            volatile: Some(table_map.volatile.price_volume.storage.clone()),
+            table_name: TableName::new(&strategies),
+            names,
+        };
+        registry.add_cloned(Arc::new(ExportJobMap::new(
+            export_storage,
+            config.database.export_directory(),
+        )));
+    }
+    if let Some(interval_ms) = config.database.backup_interval_ms {
+        // scheduled snapshots of the persistent database
+        let scheduler = BackupScheduler {
+            storage: storage.clone(),
+            directory: config.database.backup_directory(),
+            interval_ms,
+            retention: config.database.backup_retention,
+        };
+        let thread_name = "backup".to_string();
+        single_thread_spawn!(
+            start_service.clone(),
+            thread_name,
+            thread_names,
+            &tx_thread_term,
+            None,
+            scheduler.run()
+        );
+    }
     {
         let broadcast: AsyncBroadcaster<DbRowSignalPriceDifference> = AsyncBroadcaster::new(BUFFER_SIZE_MINIMAL);
         registry.add_cloned(broadcast.clone());
@@ -363,7 +722,7 @@ pub async fn main_core(
         tx_price_volume,
         pv.clone(),
         index_pv.clone(),
-        table_map.volatile.instruments.clone(),
+        table_map.volatile.instruments.load(),
     );
     single_thread_spawn!(
         start_service.clone(),
@@ -427,6 +786,13 @@ pub async fn main_core(
             symbol_flags: table_map.persistent.symbol_flag[&strategy_id].clone(),
             symbol_flags_cache: Default::default(),
             strategy_status: table_map.volatile.strategy_status.clone(),
+            executable: ExecutablePriceModel::new(
+                table_map.volatile.orderbook_map.clone(),
+                table_map.volatile.instruments.clone(),
+                strategy_constants::DIFFERENCE_EXECUTABLE_NOTIONAL,
+                config.fees.clone(),
+            ),
+            fair_price_map: table_map.volatile.fair_price_map.clone(),
         };
         single_thread_spawn!(
             start_service.clone(),
@@ -473,7 +839,7 @@ pub async fn main_core(
             best_bid_ask: best_bid_ask_map.clone(),
             rx_closing_order,
             orders_to_close: Vec::new(),
-            table_order: table_map.persistent.order[&strategy_id].clone(),
+            table_order: table_map.persistent.order.clone(),
             worktable_live_order: table_map.volatile.order_manager.clone(),
             balance_manager: registry.get_unwrap(),
             table_event: table_map.volatile.event_price_change[&strategy_id].clone(),
@@ -532,11 +898,16 @@ pub async fn main_core(
         let rx_request: AsyncReceiver<ExecutionRequest> = registry.get_unwrap();
         let tx_response: AsyncBroadcaster<ExecutionResponse> = registry.get_unwrap();
         let tx_updates: AsyncBroadcaster<UpdateOrder> = registry.get_unwrap();
+        let tx_market: AsyncBroadcaster<MarketEvent> = registry.get_unwrap();
         let balance_manager = registry.get_unwrap();
         let strategy_status: Arc<StrategyStatusMap> = table_map.volatile.strategy_status.clone();
         let order_manager = table_map.volatile.order_manager.clone();
         let portfolio_manager = table_map.volatile.position_manager.clone();
+        let instruments = table_map.volatile.instruments.clone();
         let rx_config = rx_key;
+        let halts: Arc<AccountingHalts> = registry.get_unwrap();
+        let accounts: Arc<ExecutionAccounts> = registry.get_unwrap();
+        let journal_config = config.journal.clone();
         single_thread_spawn!(
             start_service.clone(),
             thread_name,
@@ -548,12 +919,17 @@ pub async fn main_core(
                     rx_request,
                     tx_response,
                     tx_updates,
+                    tx_market,
                     balance_manager,
                     strategy_status,
                     order_manager,
                     portfolio_manager,
+                    instruments,
                     rx_config,
-                );
+                )
+                .with_journal(journal, recovery, &journal_config)
+                .with_halts(halts)
+                .with_accounts(accounts);
                 manager.run().await
             }
         );
@@ -605,7 +981,7 @@ pub async fn main_core(
             price_spread: table_map.volatile.signal_price_spread_worktable.clone(),
             pricemap: table_map.volatile.price_map.clone().clone(),
             table_event: table_map.volatile.event_price_change[&strategy_id].clone(),
-            table_order: table_map.persistent.order[&strategy_id].clone(),
+            table_order: table_map.persistent.order.clone(),
             table_test: table_map.volatile.livetest_fill.clone(),
             table_candlestick: table_map.volatile.candlestick.clone(),
         };
@@ -634,105 +1010,24 @@ pub async fn main_core(
             strategy_debug::yield_monitor(indextable, assets_clone)
         );
     }
-    ////////////////////////////// TABLE LIMITER
-    let ms_sec = 1000;
-    let ms_min = 60 * ms_sec;
-    let ms_hr = 60 * ms_min;
-
-    // intervals
-    let ms_interval = 10 * ms_sec;
-
-    let thread_name_limiter = |id: StrategyId, item: &str| format!("{item}_table_limit_{id}");
+    ////////////////////////////// RETENTION
     {
-        let thread_name = thread_name_limiter(0, "price");
-
-        let indextable = table_map.volatile.signal_price_spread_worktable.clone();
-        single_thread_spawn!(
-            start_service.clone(),
-            thread_name,
-            thread_names,
-            &tx_thread_term,
-            None,
-            table_limiter::price_table_limiter(indextable, ms_hr, ms_interval)
-        );
-    }
-    {
-        let thread_name = thread_name_limiter(0, "spread");
-
-        let indextable = table_map.volatile.spread_table.clone();
-        single_thread_spawn!(
-            start_service.clone(),
-            thread_name,
-            thread_names,
-            &tx_thread_term,
-            None,
-            table_limiter::table_limiter(indextable, ms_hr, ms_interval)
-        );
-    }
-    // common to strategy 0 and 1
-    let strategy_ids = [0, 1];
-    for strategy_id in strategy_ids {
-        let thread_name = thread_name_limiter(strategy_id, "diff_0");
-        let table = table_map.volatile.signal_price_difference[&strategy_id].clone();
-        single_thread_spawn!(
-            start_service.clone(),
-            thread_name,
-            thread_names,
-            &tx_thread_term,
-            None,
-            table_limiter::table_limiter(table, ms_hr, ms_interval)
-        );
-    }
-    let strategy_id = 1;
-    // specific to strategy 1
-    {
-        let thread_name = thread_name_limiter(strategy_id, "change");
-        let tb = table_map.volatile.signal_price_change.clone();
-        single_thread_spawn!(
-            start_service.clone(),
-            thread_name,
-            thread_names,
-            &tx_thread_term,
-            None,
-            table_limiter::table_limiter(tb, ms_hr, ms_interval)
-        );
-    }
-    {
-        let thread_name = thread_name_limiter(strategy_id, "event");
-        let tb = table_map.volatile.event_price_change[&strategy_id].clone();
-        single_thread_spawn!(
-            start_service.clone(),
-            thread_name,
-            thread_names,
-            &tx_thread_term,
-            None,
-            table_limiter::table_limiter(tb, ms_hr, ms_interval)
-        );
-    }
-    {
-        let thread_name = thread_name_limiter(strategy_id, "accuracy");
-        let tb = table_map.volatile.accuracy[&strategy_id].clone();
-        single_thread_spawn!(
-            start_service.clone(),
-            thread_name,
-            thread_names,
-            &tx_thread_term,
-            None,
-            table_limiter::table_limiter(tb, ms_hr, ms_interval)
-        );
-    }
-
-    {
-        // s2 event
-        let thread_name = thread_name_limiter(2, "event");
-        let tb = table_map.volatile.event_price_spread_and_position.clone();
+        let policies = retention::default_policies(&TableName::new(&strategies));
+        let scheduler = RetentionScheduler {
+            volatile: table_map.volatile.price_volume.storage.clone(),
+            persistent: storage.clone(),
+            price_spread_worktable: table_map.volatile.signal_price_spread_worktable.clone(),
+            policies: retention::merge_policies(policies, &config.retention.tables),
+            interval_ms: config.retention.interval_ms,
+        };
+        let thread_name = "retention".to_string();
         single_thread_spawn!(
             start_service.clone(),
             thread_name,
             thread_names,
             &tx_thread_term,
             None,
-            table_limiter::table_limiter(tb, ms_hr, ms_interval)
+            scheduler.run()
         );
     }
 
@@ -775,13 +1070,22 @@ pub async fn main_core(
             table: table_map.volatile.event_price_spread_and_position.clone(),
             tx: registry.get_unwrap(),
             balance_manager: registry.get_unwrap(),
-            instruments: instruments.clone(),
+            strategy_id: 2,
+            accounts: registry.get_unwrap(),
+            instruments: table_map.volatile.instruments.clone(),
             cooldown: HashMap::new(),
             mean_spread: table_map.volatile.spread_mean.clone(),
             common: common.clone(),
             warn_manager: WarnManager::new(),
             spread: None,
             price_map: table_map.volatile.price_map.clone().clone(),
+            executable: ExecutablePriceModel::new(
+                table_map.volatile.orderbook_map.clone(),
+                table_map.volatile.instruments.clone(),
+                strategy_two_and_three::constants::MAX_SIZE_NOTIONAL,
+                config.fees.clone(),
+            ),
+            fair_price_map: table_map.volatile.fair_price_map.clone(),
             symbol_flags: table_map.persistent.symbol_flag[&2].clone(),
             symbol_flags_cache: Default::default(),
             symbol_flags_interval: interval(1000),
@@ -803,8 +1107,8 @@ pub async fn main_core(
         let mut strategy = Strategy2OrderPlacement {
             rx: registry.get_unwrap(),
             capture_common: common.clone(),
-            instruments: instruments.clone(),
-            table_ledger: table_map.persistent.ledger[&strategy_id].clone(),
+            instruments: table_map.volatile.instruments.clone(),
+            table_ledger: table_map.persistent.ledger.clone(),
             strategy_id: strategy_id as _,
             strategy_status: table_map.volatile.strategy_status.clone(),
             tx_req: registry.get_unwrap(),
@@ -822,16 +1126,43 @@ pub async fn main_core(
         let thread_name = "ledger_manager";
         let mut ledger_manager = LedgerManager::new(
             table_map.persistent.ledger.clone(),
+            table_map.persistent.funding_accrual.clone(),
+            table_map.persistent.position_pnl.clone(),
             table_map.volatile.order_manager.clone(),
+            table_map.volatile.instruments.clone(),
+            table_map.volatile.price_map.clone(),
+            config.ledger.clone(),
+            config.fees.clone(),
+        );
+        let rx_update = registry.get_unwrap();
+        let rx_response = registry.get_unwrap();
+        single_thread_spawn!(
+            start_service.clone(),
+            thread_name,
+            thread_names,
+            &tx_thread_term,
+            None,
+            ledger_manager.run(rx_update, rx_response)
+        );
+    }
+    {
+        let thread_name = format!("order_placement_{}", funding_carry::STRATEGY_ID);
+        let mut strategy = FundingCarryOrderPlacement::new(
+            registry.get_unwrap(),
+            registry.get_unwrap(),
+            registry.get_unwrap(),
+            table_map.volatile.instruments.clone(),
+            table_map.volatile.derivatives_map.clone(),
+            table_map.volatile.strategy_status.clone(),
+            table_map.persistent.ledger.clone(),
         );
-        let tx = registry.get_unwrap();
         single_thread_spawn!(
             start_service.clone(),
             thread_name,
             thread_names,
             &tx_thread_term,
             None,
-            ledger_manager.run(tx)
+            strategy.run()
         );
     }
     {
@@ -840,6 +1171,14 @@ pub async fn main_core(
             table_map.volatile.spread_table.clone(),
             table_map.volatile.spread_mean.clone(),
             registry.get_unwrap(),
+            // same notional and fees as strategy 2, which compares its spreads against the mean
+            ExecutablePriceModel::new(
+                table_map.volatile.orderbook_map.clone(),
+                table_map.volatile.instruments.clone(),
+                strategy_two_and_three::constants::MAX_SIZE_NOTIONAL,
+                config.fees.clone(),
+            ),
+            table_map.volatile.fair_price_map.clone(),
         );
         single_thread_spawn!(
             start_service.clone(),
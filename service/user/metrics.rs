@@ -0,0 +1,172 @@
+use std::sync::Arc;
+
+use lib::metrics::{Counter, Gauge, Histogram, LATENCY_BUCKETS_MS, METRICS};
+use trading_model::wire::PerfRecord;
+use trading_model::{Exchange, MarketEvent, NANOSECONDS_PER_MILLISECOND};
+
+/// handles to the market feed metrics of one exchange, kept by the feed loop
+pub struct FeedMetrics {
+    messages: Arc<Counter>,
+    lag_ms: Arc<Histogram>,
+    perf: PerfRecord,
+}
+impl FeedMetrics {
+    pub fn new(exchange: Exchange) -> Self {
+        let exchange = exchange.to_string();
+        let labels = [("exchange", exchange.as_str())];
+        Self {
+            messages: METRICS.counter("market_feed_messages_total", "Market feed messages received", &labels),
+            lag_ms: METRICS.histogram(
+                "market_feed_lag_ms",
+                "Packet received time minus exchange time of market feed messages",
+                LATENCY_BUCKETS_MS,
+                &labels,
+            ),
+            perf: PerfRecord::new(),
+        }
+    }
+    pub fn on_market_event(&mut self, event: &MarketEvent) {
+        self.messages.inc();
+        self.perf.clear();
+        self.perf.on_market_event(event);
+        let exchange_time = self.perf.event_exchange_time.nanos();
+        let received_time = self.perf.event_received_time.nanos();
+        if exchange_time > 0 && received_time > 0 {
+            let lag = (received_time - exchange_time) as f64 / NANOSECONDS_PER_MILLISECOND as f64;
+            self.lag_ms.observe(lag.max(0.0));
+        }
+    }
+}
+
+pub fn order_round_trip_ms(exchange: Exchange) -> Arc<Histogram> {
+    METRICS.histogram(
+        "order_round_trip_ms",
+        "Time from sending an order request to the first update from the venue",
+        LATENCY_BUCKETS_MS,
+        &[("exchange", exchange.to_string().as_str())],
+    )
+}
+
+/// free text reasons are cut at the first ':' to keep the label set small
+pub fn order_rejected(exchange: Exchange, reason: &str) {
+    let reason = reason.split(':').next().unwrap_or_default().trim();
+    let reason = match reason.char_indices().nth(48) {
+        _ if reason.is_empty() => "unknown",
+        Some((end, _)) => &reason[..end],
+        None => reason,
+    };
+    METRICS
+        .counter(
+            "order_rejects_total",
+            "Rejected orders by venue and reason",
+            &[("exchange", exchange.to_string().as_str()), ("reason", reason)],
+        )
+        .inc();
+}
+
+pub fn strategy_orders(strategy_id: u64) -> Arc<Counter> {
+    METRICS.counter(
+        "strategy_orders_total",
+        "Orders placed per strategy",
+        &[("strategy_id", strategy_id.to_string().as_str())],
+    )
+}
+
+pub fn strategy_signals(strategy_id: u64) -> Arc<Counter> {
+    METRICS.counter(
+        "strategy_signals_total",
+        "Signals and events emitted per strategy",
+        &[("strategy_id", strategy_id.to_string().as_str())],
+    )
+}
+
+pub fn open_positions(exchange: Exchange) -> Arc<Gauge> {
+    METRICS.gauge(
+        "open_positions",
+        "Open positions and pending position orders",
+        &[("exchange", exchange.to_string().as_str())],
+    )
+}
+
+pub fn realized_pnl_usd(strategy_id: u64) -> Arc<Gauge> {
+    METRICS.gauge(
+        "realized_pnl_usd",
+        "Closed profit in usd since the service started",
+        &[("strategy_id", strategy_id.to_string().as_str())],
+    )
+}
+
+pub fn unrealized_pnl_usd(strategy_id: u64) -> Arc<Gauge> {
+    METRICS.gauge(
+        "unrealized_pnl_usd",
+        "Profit in usd of the open ledger lots, marked to the last prices",
+        &[("strategy_id", strategy_id.to_string().as_str())],
+    )
+}
+
+pub fn broadcast_backlog(channel: &str) -> Arc<Gauge> {
+    METRICS.gauge(
+        "broadcast_backlog",
+        "Largest number of queued messages among the subscribers of a broadcaster",
+        &[("channel", channel)],
+    )
+}
+
+pub fn market_feed_reconnects(exchange: Exchange) -> Arc<Counter> {
+    METRICS.counter(
+        "market_feed_reconnects_total",
+        "Market feed connections re-established by the supervisor",
+        &[("exchange", exchange.to_string().as_str())],
+    )
+}
+
+pub fn market_feed_stale_instruments(exchange: Exchange) -> Arc<Gauge> {
+    METRICS.gauge(
+        "market_feed_stale_instruments",
+        "Subscribed instruments without an update within the stale threshold",
+        &[("exchange", exchange.to_string().as_str())],
+    )
+}
+
+pub fn instrument_changes(exchange: Exchange, kind: &str) -> Arc<Counter> {
+    METRICS.counter(
+        "instrument_changes_total",
+        "Instruments added, removed or changed by the periodic refresh",
+        &[("exchange", exchange.to_string().as_str()), ("kind", kind)],
+    )
+}
+
+pub fn retention_deleted_rows(table: &str) -> Arc<Counter> {
+    METRICS.counter(
+        "retention_deleted_rows_total",
+        "Rows removed by the retention policies, including the downsampled ones",
+        &[("table", table)],
+    )
+}
+
+pub fn retention_deleted_bytes(table: &str) -> Arc<Counter> {
+    METRICS.counter(
+        "retention_deleted_bytes_total",
+        "Approximate size of the rows removed by the retention policies",
+        &[("table", table)],
+    )
+}
+
+pub fn retention_downsampled_rows(table: &str) -> Arc<Counter> {
+    METRICS.counter(
+        "retention_downsampled_rows_total",
+        "Aggregated rows written by the downsampling rules",
+        &[("table", table)],
+    )
+}
+
+pub fn accounting_halted(exchange: Exchange, account: i64) -> Arc<Gauge> {
+    METRICS.gauge(
+        "accounting_halted",
+        "1 while trading on the account is halted by an accounting inconsistency",
+        &[
+            ("exchange", exchange.to_string().as_str()),
+            ("account", account.to_string().as_str()),
+        ],
+    )
+}
@@ -0,0 +1,512 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+use trading_exchange::model::{AccountId, OrderType, PositionEffect};
+use trading_model::{Exchange, InstrumentSymbol, Side, Symbol, TimeStampMs};
+
+use crate::config::LotMethod;
+use crate::db::gluesql::schema::common::StrategyId;
+use crate::db::gluesql::schema::position_pnl::DbRowPositionPnl;
+use crate::db::gluesql::schema::DbRowLedger;
+
+/// open volume below this is closed, it absorbs the rounding of the partial fills
+const VOLUME_EPSILON: f64 = 1e-9;
+
+/// single execution of an order, from a trade or derived from the filled size of an order update
+#[derive(Debug, Clone)]
+pub struct Fill {
+    pub strategy_id: StrategyId,
+    pub account: AccountId,
+    /// event captured by the order, shared by the legs of a hedged pair. 0 for manual orders
+    pub event_id: u64,
+    pub instrument: InstrumentSymbol,
+    pub side: Side,
+    pub effect: PositionEffect,
+    pub ty: OrderType,
+    pub local_id: String,
+    pub client_id: String,
+    pub price: f64,
+    pub size: f64,
+    pub fee_usd: f64,
+    /// ms
+    pub datetime: TimeStampMs,
+}
+
+/// rows changed by a fill, a funding payment or a mark, to be written back
+#[derive(Debug, Default)]
+pub struct LedgerChanges {
+    pub new_lots: Vec<DbRowLedger>,
+    pub updated_lots: Vec<DbRowLedger>,
+    pub new_pnl: Vec<DbRowPositionPnl>,
+    pub updated_pnl: Vec<DbRowPositionPnl>,
+    /// change of the realized PnL per strategy
+    pub realized_usd: Vec<(StrategyId, f64)>,
+    /// funding share of each lot: strategy, ledger id and amount
+    pub funding: Vec<(StrategyId, u64, f64)>,
+}
+
+/// open lots of a strategy on one instrument of an account, all on the same side
+#[derive(Debug, Default)]
+struct Position {
+    lots: VecDeque<DbRowLedger>,
+}
+impl Position {
+    fn side(&self) -> Option<Side> {
+        self.lots.front().map(|x| x.side())
+    }
+    fn open_volume(&self) -> f64 {
+        self.lots.iter().map(|x| x.open_volume()).sum()
+    }
+    fn average_price(&self) -> f64 {
+        let volume = self.open_volume();
+        if volume <= VOLUME_EPSILON {
+            return 0.0;
+        }
+        self.lots
+            .iter()
+            .map(|x| x.open_volume() * x.open_price_usd)
+            .sum::<f64>()
+            / volume
+    }
+}
+
+/// lots opened for one event, written as one PnL row
+#[derive(Debug)]
+struct PnlGroup {
+    row: DbRowPositionPnl,
+    persisted: bool,
+    legs: BTreeSet<String>,
+    /// open lots by ledger id
+    lots: HashMap<u64, DbRowLedger>,
+    /// realized PnL, fees and funding of the lots closed already
+    settled_realized_usd: f64,
+    settled_fee_usd: f64,
+    settled_funding_usd: f64,
+}
+impl PnlGroup {
+    fn refresh(&mut self, now: TimeStampMs) {
+        let row = &mut self.row;
+        row.realized_pnl_usd = self.settled_realized_usd + self.lots.values().map(|x| x.closed_profit_usd).sum::<f64>();
+        row.fee_usd = self.settled_fee_usd + self.lots.values().map(|x| x.fee_usd).sum::<f64>();
+        row.funding_usd = self.settled_funding_usd + self.lots.values().map(|x| x.funding_usd).sum::<f64>();
+        row.unrealized_pnl_usd = self.lots.values().map(|x| x.unrealized_pnl_usd).sum();
+        row.legs = self.legs.iter().cloned().collect::<Vec<_>>().join(",");
+        row.closed = self.lots.is_empty();
+        row.update_datetime = now;
+    }
+}
+
+fn lot_instrument(lot: &DbRowLedger) -> InstrumentSymbol {
+    let exchange = Exchange::try_from(lot.exchange_id).unwrap_or(Exchange::Null);
+    let symbol = unsafe { Symbol::from_hash(lot.symbol_id) };
+    InstrumentSymbol::new(exchange, symbol)
+}
+
+/// closes `volume` of the lot at the fill price against `basis`, returns the realized PnL
+fn close_lot(lot: &mut DbRowLedger, fill: &Fill, volume: f64, basis: f64, fee_per_volume: f64) -> f64 {
+    let sign = if lot.side() == Side::Buy { 1.0 } else { -1.0 };
+    let open_volume = lot.open_volume();
+    let gross = (fill.price - basis) * volume * sign;
+    let fee = fee_per_volume * volume;
+    lot.close_price_usd =
+        (lot.close_price_usd * lot.closed_volume + fill.price * volume) / (lot.closed_volume + volume);
+    lot.closed_volume += volume;
+    lot.unrealized_pnl_usd *= ((open_volume - volume) / open_volume).max(0.0);
+    if lot.open_volume() <= VOLUME_EPSILON {
+        lot.closed_volume = lot.volume;
+        lot.unrealized_pnl_usd = 0.0;
+    }
+    lot.close_order_id = fill.local_id.clone();
+    lot.close_order_cloid = fill.client_id.clone();
+    lot.fee_usd += fee;
+    lot.closed_profit_usd += gross - fee;
+    gross - fee
+}
+
+/// lots of every position, realized when closed by an opposite fill and marked to the last price while open.
+/// a fill beyond the open volume flips the position into a new lot
+pub struct PositionLedger {
+    method: LotMethod,
+    /// the accounts of a venue hold their positions apart
+    positions: HashMap<(StrategyId, AccountId, InstrumentSymbol), Position>,
+    groups: HashMap<(StrategyId, u64), PnlGroup>,
+    next_ledger_id: Box<dyn FnMut() -> u64 + Send>,
+    next_pnl_id: Box<dyn FnMut() -> u64 + Send>,
+}
+impl PositionLedger {
+    pub fn new(
+        method: LotMethod,
+        next_ledger_id: Box<dyn FnMut() -> u64 + Send>,
+        next_pnl_id: Box<dyn FnMut() -> u64 + Send>,
+    ) -> Self {
+        Self {
+            method,
+            positions: Default::default(),
+            groups: Default::default(),
+            next_ledger_id,
+            next_pnl_id,
+        }
+    }
+    /// restores the open lots and the PnL rows of the groups they belong to
+    pub fn load(&mut self, mut lots: Vec<DbRowLedger>, pnl: Vec<DbRowPositionPnl>) {
+        lots.sort_by_key(|x| x.id);
+        for row in pnl.into_iter().filter(|x| !x.closed) {
+            let key = (row.strategy_id as StrategyId, row.event_id);
+            let open: Vec<&DbRowLedger> = lots
+                .iter()
+                .filter(|x| x.strategy_id == row.strategy_id && x.event_id == row.event_id)
+                .collect();
+            let group = PnlGroup {
+                settled_realized_usd: row.realized_pnl_usd - open.iter().map(|x| x.closed_profit_usd).sum::<f64>(),
+                settled_fee_usd: row.fee_usd - open.iter().map(|x| x.fee_usd).sum::<f64>(),
+                settled_funding_usd: row.funding_usd - open.iter().map(|x| x.funding_usd).sum::<f64>(),
+                legs: row
+                    .legs
+                    .split(',')
+                    .filter(|x| !x.is_empty())
+                    .map(|x| x.to_string())
+                    .collect(),
+                lots: open.into_iter().map(|x| (x.id, x.clone())).collect(),
+                persisted: true,
+                row,
+            };
+            self.groups.insert(key, group);
+        }
+        for lot in lots {
+            let key = (lot.strategy_id as StrategyId, lot.account, lot_instrument(&lot));
+            self.positions.entry(key).or_default().lots.push_back(lot);
+        }
+    }
+    pub fn open_lots(&self) -> usize {
+        self.positions.values().map(|x| x.lots.len()).sum()
+    }
+    /// unrealized PnL of the open lots per strategy
+    pub fn unrealized_usd(&self) -> HashMap<StrategyId, f64> {
+        let mut result: HashMap<StrategyId, f64> = HashMap::new();
+        for ((strategy_id, _, _), position) in &self.positions {
+            *result.entry(*strategy_id).or_default() += position.lots.iter().map(|x| x.unrealized_pnl_usd).sum::<f64>();
+        }
+        result
+    }
+    pub fn apply_fill(&mut self, fill: &Fill) -> LedgerChanges {
+        let mut changes = LedgerChanges::default();
+        if fill.size <= VOLUME_EPSILON {
+            return changes;
+        }
+        let fee_per_volume = fill.fee_usd / fill.size;
+        let mut realized = 0.0;
+        let mut touched = vec![];
+        let position = self
+            .positions
+            .entry((fill.strategy_id, fill.account, fill.instrument.clone()))
+            .or_default();
+        let mut remaining = fill.size;
+        if position.side().is_some_and(|side| side != fill.side) {
+            let open_volume = position.open_volume();
+            let closing = remaining.min(open_volume);
+            match self.method {
+                LotMethod::Fifo => {
+                    let mut left = closing;
+                    for lot in position.lots.iter_mut() {
+                        if left <= VOLUME_EPSILON {
+                            break;
+                        }
+                        let volume = left.min(lot.open_volume());
+                        let basis = lot.open_price_usd;
+                        realized += close_lot(lot, fill, volume, basis, fee_per_volume);
+                        left -= volume;
+                        touched.push(lot.clone());
+                    }
+                }
+                LotMethod::AverageCost => {
+                    let basis = position.average_price();
+                    for lot in position.lots.iter_mut() {
+                        let volume = closing * lot.open_volume() / open_volume;
+                        realized += close_lot(lot, fill, volume, basis, fee_per_volume);
+                        touched.push(lot.clone());
+                    }
+                }
+            }
+            position.lots.retain(|x| x.open_volume() > VOLUME_EPSILON);
+            remaining -= closing;
+        }
+        if remaining > VOLUME_EPSILON {
+            let fee = fee_per_volume * remaining;
+            realized -= fee;
+            // the partial fills of an open order add to its lot
+            let last = position
+                .lots
+                .back_mut()
+                .filter(|x| x.open_order_id == fill.local_id && x.closed_volume <= VOLUME_EPSILON);
+            match last {
+                Some(lot) => {
+                    lot.open_price_usd =
+                        (lot.open_price_usd * lot.volume + fill.price * remaining) / (lot.volume + remaining);
+                    lot.volume += remaining;
+                    lot.fee_usd += fee;
+                    lot.closed_profit_usd -= fee;
+                    touched.push(lot.clone());
+                }
+                None => {
+                    let lot = DbRowLedger {
+                        id: (self.next_ledger_id)(),
+                        open_order_id: fill.local_id.clone(),
+                        close_order_id: "".to_string(),
+                        open_order_cloid: fill.client_id.clone(),
+                        close_order_cloid: "".to_string(),
+                        datetime: fill.datetime,
+                        exchange_id: fill.instrument.exchange as u8,
+                        symbol_id: fill.instrument.symbol._hash(),
+                        open_order_position_type_id: fill.effect as u8,
+                        volume: remaining,
+                        order_type_id: fill.ty as u8,
+                        open_order_side_id: fill.side as u8,
+                        open_price_usd: fill.price,
+                        close_price_usd: 0.0,
+                        closed_profit_usd: -fee,
+                        strategy_id: fill.strategy_id as _,
+                        closed_volume: 0.0,
+                        fee_usd: fee,
+                        funding_usd: 0.0,
+                        unrealized_pnl_usd: 0.0,
+                        event_id: fill.event_id,
+                        account: fill.account,
+                    };
+                    position.lots.push_back(lot.clone());
+                    changes.new_lots.push(lot);
+                }
+            }
+        }
+        if position.lots.is_empty() {
+            self.positions
+                .remove(&(fill.strategy_id, fill.account, fill.instrument.clone()));
+        }
+        changes.realized_usd.push((fill.strategy_id, realized));
+        for lot in changes.new_lots.clone().iter().chain(touched.iter()) {
+            self.track(lot, fill.datetime, &mut changes);
+        }
+        changes.updated_lots = touched;
+        changes
+    }
+    /// splits the payment of the account across its open lots of the instrument by open volume.
+    /// the lots of an unknown account take a share of the payments of every account
+    pub fn apply_funding(
+        &mut self,
+        instrument: &InstrumentSymbol,
+        account: AccountId,
+        amount_usd: f64,
+        now: TimeStampMs,
+    ) -> LedgerChanges {
+        let mut changes = LedgerChanges::default();
+        let lots = self
+            .positions
+            .iter_mut()
+            .filter(|((_, x, y), _)| (*x == account || *x == 0) && y == instrument)
+            .flat_map(|(_, position)| position.lots.iter_mut());
+        let mut lots: Vec<&mut DbRowLedger> = lots.collect();
+        let total_volume: f64 = lots.iter().map(|x| x.open_volume()).sum();
+        if total_volume <= VOLUME_EPSILON {
+            return changes;
+        }
+        for lot in lots.iter_mut() {
+            let share = amount_usd * lot.open_volume() / total_volume;
+            lot.funding_usd += share;
+            lot.closed_profit_usd += share;
+            let strategy_id = lot.strategy_id as StrategyId;
+            changes.funding.push((strategy_id, lot.id, share));
+            changes.realized_usd.push((strategy_id, share));
+            changes.updated_lots.push(lot.clone());
+        }
+        for lot in changes.updated_lots.clone() {
+            self.track(&lot, now, &mut changes);
+        }
+        changes
+    }
+    /// marks the open lots to `price`, instruments without a price keep their last mark
+    pub fn mark(&mut self, price: impl Fn(&InstrumentSymbol) -> Option<f64>, now: TimeStampMs) -> LedgerChanges {
+        let mut changes = LedgerChanges::default();
+        for ((_, _, instrument), position) in self.positions.iter_mut() {
+            let Some(price) = price(instrument) else {
+                continue;
+            };
+            let average_price = position.average_price();
+            for lot in position.lots.iter_mut() {
+                let sign = if lot.side() == Side::Buy { 1.0 } else { -1.0 };
+                let basis = match self.method {
+                    LotMethod::Fifo => lot.open_price_usd,
+                    LotMethod::AverageCost => average_price,
+                };
+                lot.unrealized_pnl_usd = (price - basis) * lot.open_volume() * sign;
+                changes.updated_lots.push(lot.clone());
+            }
+        }
+        for lot in changes.updated_lots.clone() {
+            self.track(&lot, now, &mut changes);
+        }
+        changes
+    }
+    /// folds the lot into the PnL row of its event, manual orders have none
+    fn track(&mut self, lot: &DbRowLedger, now: TimeStampMs, changes: &mut LedgerChanges) {
+        if lot.event_id == 0 {
+            return;
+        }
+        let key = (lot.strategy_id as StrategyId, lot.event_id);
+        let group = self.groups.entry(key).or_insert_with(|| PnlGroup {
+            row: DbRowPositionPnl {
+                id: (self.next_pnl_id)(),
+                strategy_id: lot.strategy_id,
+                event_id: lot.event_id,
+                datetime: lot.datetime,
+                ..Default::default()
+            },
+            persisted: false,
+            legs: Default::default(),
+            lots: Default::default(),
+            settled_realized_usd: 0.0,
+            settled_fee_usd: 0.0,
+            settled_funding_usd: 0.0,
+        });
+        group.legs.insert(lot_instrument(lot).to_string());
+        if lot.open_volume() <= VOLUME_EPSILON {
+            group.lots.remove(&lot.id);
+            group.settled_realized_usd += lot.closed_profit_usd;
+            group.settled_fee_usd += lot.fee_usd;
+            group.settled_funding_usd += lot.funding_usd;
+        } else {
+            group.lots.insert(lot.id, lot.clone());
+        }
+        group.refresh(now);
+        let row = group.row.clone();
+        // one row per group and change
+        changes.new_pnl.retain(|x| x.id != row.id);
+        changes.updated_pnl.retain(|x| x.id != row.id);
+        if group.persisted {
+            changes.updated_pnl.push(row.clone());
+        } else {
+            changes.new_pnl.push(row.clone());
+        }
+        if row.closed {
+            self.groups.remove(&key);
+        }
+    }
+    /// the new PnL rows were written, the next changes update them
+    pub fn persisted(&mut self, changes: &LedgerChanges) {
+        for row in &changes.new_pnl {
+            if let Some(group) = self.groups.get_mut(&(row.strategy_id as StrategyId, row.event_id)) {
+                group.persisted = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ledger(method: LotMethod) -> PositionLedger {
+        let mut ledger_id = 0;
+        let mut pnl_id = 0;
+        PositionLedger::new(
+            method,
+            Box::new(move || {
+                ledger_id += 1;
+                ledger_id
+            }),
+            Box::new(move || {
+                pnl_id += 1;
+                pnl_id
+            }),
+        )
+    }
+    fn fill(exchange: Exchange, local_id: &str, side: Side, price: f64, size: f64) -> Fill {
+        Fill {
+            strategy_id: 2,
+            account: 1,
+            event_id: 7,
+            instrument: InstrumentSymbol::new(exchange, "BTC".into()),
+            side,
+            effect: PositionEffect::Open,
+            ty: OrderType::Limit,
+            local_id: local_id.to_string(),
+            client_id: local_id.to_string(),
+            price,
+            size,
+            fee_usd: price * size * 0.001,
+            datetime: 1,
+        }
+    }
+
+    #[test]
+    fn test_partial_fills_and_hedged_pnl() {
+        let mut ledger = ledger(LotMethod::Fifo);
+        // two partial fills of the same order make one lot
+        let changes = ledger.apply_fill(&fill(Exchange::Hyperliquid, "a", Side::Buy, 100.0, 1.0));
+        assert_eq!(changes.new_lots.len(), 1);
+        assert_eq!(changes.new_pnl.len(), 1);
+        ledger.persisted(&changes);
+        ledger.apply_fill(&fill(Exchange::Hyperliquid, "a", Side::Buy, 110.0, 1.0));
+        ledger.apply_fill(&fill(Exchange::Hyperliquid, "b", Side::Buy, 120.0, 1.0));
+        assert_eq!(ledger.open_lots(), 2);
+        // short leg of the pair on another venue
+        ledger.apply_fill(&fill(Exchange::BinanceFutures, "c", Side::Sell, 121.0, 3.0));
+
+        // FIFO: closes the first lot at 105 and half of the second at 120
+        let changes = ledger.apply_fill(&fill(Exchange::Hyperliquid, "d", Side::Sell, 130.0, 2.5));
+        let first = &changes.updated_lots[0];
+        assert_eq!(first.closed_volume, 2.0);
+        assert!((first.open_price_usd - 105.0).abs() < 1e-9);
+        let fees = 0.21 + 0.26;
+        assert!((first.closed_profit_usd - (50.0 - fees)).abs() < 1e-9);
+        assert!((changes.updated_lots[1].closed_volume - 0.5).abs() < 1e-9);
+
+        let changes = ledger.mark(
+            |x| match x.exchange {
+                Exchange::Hyperliquid => Some(130.0),
+                _ => Some(131.0),
+            },
+            2,
+        );
+        assert_eq!(changes.updated_pnl.len(), 1);
+        let pnl = &changes.updated_pnl[0];
+        assert_eq!(pnl.legs, "BinanceFutures:BTC,Hyperliquid:BTC");
+        assert!((pnl.unrealized_pnl_usd - (5.0 - 30.0)).abs() < 1e-9);
+        assert!(!pnl.closed);
+
+        // funding paid by the short leg, another account holds none of it
+        let instrument = InstrumentSymbol::new(Exchange::BinanceFutures, "BTC".into());
+        assert!(ledger.apply_funding(&instrument, 2, -1.5, 3).funding.is_empty());
+        let changes = ledger.apply_funding(&instrument, 1, -1.5, 3);
+        assert_eq!(changes.funding, vec![(2, 3, -1.5)]);
+        assert!((changes.updated_pnl[0].funding_usd + 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_accounts_hold_positions_apart() {
+        let mut ledger = ledger(LotMethod::Fifo);
+        ledger.apply_fill(&fill(Exchange::Hyperliquid, "a", Side::Buy, 100.0, 1.0));
+        // the sell on another account opens a short there rather than closing the long
+        let changes = ledger.apply_fill(&Fill {
+            account: 2,
+            ..fill(Exchange::Hyperliquid, "b", Side::Sell, 110.0, 1.0)
+        });
+        assert!(changes.updated_lots.is_empty());
+        assert_eq!(changes.new_lots[0].account, 2);
+        assert_eq!(ledger.open_lots(), 2);
+    }
+
+    #[test]
+    fn test_average_cost_flip() {
+        let mut ledger = ledger(LotMethod::AverageCost);
+        ledger.apply_fill(&fill(Exchange::Hyperliquid, "a", Side::Buy, 100.0, 1.0));
+        ledger.apply_fill(&fill(Exchange::Hyperliquid, "b", Side::Buy, 120.0, 1.0));
+        // closes both lots at the average of 110 and opens a short of 1
+        let changes = ledger.apply_fill(&Fill {
+            fee_usd: 0.0,
+            ..fill(Exchange::Hyperliquid, "c", Side::Sell, 130.0, 3.0)
+        });
+        let realized: f64 = changes.realized_usd.iter().map(|(_, x)| x).sum();
+        assert!((realized - 40.0).abs() < 1e-9);
+        assert_eq!(changes.new_lots.len(), 1);
+        assert_eq!(changes.new_lots[0].side(), Side::Sell);
+        assert_eq!(ledger.open_lots(), 1);
+    }
+}
@@ -0,0 +1,219 @@
+use dashmap::DashMap;
+use eyre::{ContextCompat, Result};
+use gluesql_shared_sled_storage::SharedSledStorage;
+use lib::gluesql::{Table, TableSelectItem};
+use lib::signal::get_terminate_flag;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::{info, warn};
+use trading_exchange::exchange::hyperliquid::model::exchange::request::HyperliquidChain;
+use trading_exchange::exchange::hyperliquid::HyperliquidInfoClient;
+use trading_model::{
+    format_candle_interval, CandleAggregator, Exchange, InstrumentCode, InstrumentSymbol, MarketTrade, Symbol, Time,
+    OHLCVT,
+};
+
+use crate::config::CandleConfig;
+use crate::db::gluesql::schema::candle::DbRowCandle;
+
+/// candles kept in memory per instrument and interval
+pub const CANDLE_CAPACITY: usize = 1000;
+/// intervals fetched from the exchanges at startup, neither venue serves 1s history on perpetuals
+pub const CANDLE_BACKFILL_INTERVALS_MS: [i32; 3] = [60_000, 300_000, 3_600_000];
+/// number of candles fetched per instrument and interval
+pub const CANDLE_BACKFILL_LIMIT: i64 = 500;
+
+/// how long candles of an interval are kept in sled, enforced by `db::retention`
+pub fn candle_retention_ms(interval_ms: i32) -> i64 {
+    const DAY_MS: i64 = 86_400_000;
+    match interval_ms {
+        x if x < 60_000 => DAY_MS,
+        x if x < 300_000 => 7 * DAY_MS,
+        x if x < 3_600_000 => 30 * DAY_MS,
+        _ => 365 * DAY_MS,
+    }
+}
+
+/// live candles per instrument, built by price manager from the trades of the feeds
+pub struct CandleMap {
+    map: DashMap<(Exchange, Symbol), CandleAggregator>,
+}
+impl CandleMap {
+    pub fn new() -> Self {
+        Self {
+            map: Default::default(),
+        }
+    }
+    fn key(instrument: &InstrumentCode) -> Option<(Exchange, Symbol)> {
+        Some((instrument.get_exchange()?, instrument.get_symbol()?))
+    }
+    /// returns the candles closed by the trade
+    pub fn on_trade(&self, trade: &MarketTrade) -> Vec<OHLCVT> {
+        let Some(key) = Self::key(&trade.instrument) else {
+            return vec![];
+        };
+        self.map
+            .entry(key)
+            .or_insert_with(|| CandleAggregator::new(trade.instrument.clone(), CANDLE_CAPACITY))
+            .on_trade(trade)
+    }
+    pub fn on_candle(&self, candle: OHLCVT) {
+        let Some(key) = Self::key(&candle.instrument) else {
+            return;
+        };
+        self.map
+            .entry(key)
+            .or_insert_with(|| CandleAggregator::new(candle.instrument.clone(), CANDLE_CAPACITY))
+            .on_candle(candle);
+    }
+    /// the candle currently being built
+    pub fn last(&self, exchange: Exchange, symbol: &Symbol, interval_ms: i32) -> Option<OHLCVT> {
+        let aggregator = self.map.get(&(exchange, symbol.clone()))?;
+        aggregator.last(interval_ms).cloned()
+    }
+    pub fn last_n(&self, exchange: Exchange, symbol: &Symbol, interval_ms: i32, n: usize) -> Vec<OHLCVT> {
+        match self.map.get(&(exchange, symbol.clone())) {
+            Some(aggregator) => aggregator.last_n(interval_ms, n),
+            None => vec![],
+        }
+    }
+}
+
+/// backfills candle history from the exchanges at startup
+pub struct CandleService {
+    pub table: Table<SharedSledStorage, DbRowCandle>,
+    pub candle_map: Arc<CandleMap>,
+    pub instruments: Vec<InstrumentSymbol>,
+    pub config: CandleConfig,
+}
+impl CandleService {
+    pub async fn run(mut self) -> Result<()> {
+        self.backfill().await;
+        lib::signal::signal_received_silent().await;
+        Ok(())
+    }
+    async fn backfill(&mut self) {
+        let hyper = HyperliquidInfoClient::new(HyperliquidChain::from(self.config.hyperliquid_network));
+        for instrument in self.instruments.clone() {
+            for interval_ms in CANDLE_BACKFILL_INTERVALS_MS {
+                if get_terminate_flag() {
+                    return;
+                }
+                let candles = match instrument.exchange {
+                    Exchange::BinanceFutures => {
+                        fetch_binance_futures_klines(&self.config.binance_futures_url, &instrument.symbol, interval_ms)
+                            .await
+                    }
+                    Exchange::Hyperliquid => fetch_hyperliquid_candles(&hyper, &instrument.symbol, interval_ms).await,
+                    _ => continue,
+                };
+                let candles = match candles {
+                    Ok(candles) => candles,
+                    Err(err) => {
+                        warn!(
+                            "failed to backfill {} candles of {}: {err}",
+                            format_candle_interval(interval_ms),
+                            instrument
+                        );
+                        continue;
+                    }
+                };
+                if let Err(err) = self.store_backfill(&instrument, interval_ms, candles).await {
+                    warn!("failed to store candles of {}: {err}", instrument);
+                }
+            }
+        }
+        info!("candle backfill done for {} instruments", self.instruments.len());
+    }
+    /// inserts the closed candles newer than the stored ones, the still open candle only seeds the live series
+    async fn store_backfill(
+        &mut self,
+        instrument: &InstrumentSymbol,
+        interval_ms: i32,
+        mut candles: Vec<OHLCVT>,
+    ) -> Result<()> {
+        candles.sort_by_key(|x| x.exchange_time);
+        let filter = DbRowCandle::by_series(instrument.exchange, instrument.symbol._hash(), interval_ms as _);
+        let stored = self.table.select_limit(Some(filter), "datetime DESC", Some(1)).await?;
+        let last_stored = stored.first().map_or(i64::MIN, |x| x.datetime);
+        let now = Time::now();
+        for candle in candles {
+            if candle.close_time() > now {
+                self.candle_map.on_candle(candle);
+                continue;
+            }
+            if candle.exchange_time.millis() > last_stored {
+                self.table.insert(candle.clone().try_into()?).await?;
+            }
+            self.candle_map.on_candle(candle);
+        }
+        Ok(())
+    }
+}
+
+/// `/fapi/v1/klines`, rows are [open time, open, high, low, close, volume, close time, ...]
+async fn fetch_binance_futures_klines(base_url: &str, symbol: &Symbol, interval_ms: i32) -> Result<Vec<OHLCVT>> {
+    let url = format!(
+        "{}/fapi/v1/klines?symbol={}&interval={}&limit={}",
+        base_url.trim_end_matches('/'),
+        symbol,
+        format_candle_interval(interval_ms),
+        CANDLE_BACKFILL_LIMIT
+    );
+    let text = reqwest::get(&url).await?.text().await?;
+    let rows: Vec<Vec<serde_json::Value>> = serde_json::from_str(&text)?;
+    let instrument = InstrumentCode::from_symbol(Exchange::BinanceFutures, symbol.clone());
+    let received_time = Time::now();
+    let number = |row: &[serde_json::Value], i: usize| -> Result<f64> {
+        let value = row.get(i).and_then(|x| x.as_str()).context("missing kline field")?;
+        Ok(f64::from_str(value)?)
+    };
+    rows.iter()
+        .map(|row| {
+            Ok(OHLCVT {
+                instrument: instrument.clone(),
+                open: number(row, 1)?,
+                high: number(row, 2)?,
+                low: number(row, 3)?,
+                close: number(row, 4)?,
+                volume: number(row, 5)?,
+                exchange_time: Time::from_millis(row[0].as_i64().context("missing kline open time")?),
+                received_time,
+                interval_ms,
+            })
+        })
+        .collect()
+}
+
+async fn fetch_hyperliquid_candles(
+    client: &HyperliquidInfoClient,
+    symbol: &Symbol,
+    interval_ms: i32,
+) -> Result<Vec<OHLCVT>> {
+    let end_time = Time::now().millis();
+    let start_time = end_time - interval_ms as i64 * CANDLE_BACKFILL_LIMIT;
+    let candles = client
+        .candle_snapshot(
+            symbol.to_string(),
+            format_candle_interval(interval_ms),
+            start_time as _,
+            end_time as _,
+        )
+        .await?;
+    let instrument = InstrumentCode::from_symbol(Exchange::Hyperliquid, symbol.clone());
+    let received_time = Time::now();
+    Ok(candles
+        .into_iter()
+        .map(|candle| OHLCVT {
+            instrument: instrument.clone(),
+            open: candle.open,
+            high: candle.high,
+            low: candle.low,
+            close: candle.close,
+            volume: candle.volume,
+            exchange_time: Time::from_millis(candle.time_begin),
+            received_time,
+            interval_ms,
+        })
+        .collect())
+}
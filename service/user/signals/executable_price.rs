@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use trading_model::{Asset, BookSweep, Exchange, Intent};
+
+use crate::config::FeeSchedule;
+use crate::signals::price_spread::DbRowSignalBestBidAskAcrossExchanges;
+use crate::strategy::data_factory::OrderBookMap;
+use crate::strategy::instrument::convert_asset_to_instrument;
+use crate::strategy::instrument_refresh::DynamicInstrumentManager;
+
+/// replaces the touch prices of a signal with the prices a taker order of `notional` would actually get,
+/// walking the maintained books and charging the taker fee of each venue.
+/// a signal with a side whose book is missing or too thin for the notional is not executable
+pub struct ExecutablePriceModel {
+    pub orderbooks: Arc<OrderBookMap>,
+    pub instruments: Arc<DynamicInstrumentManager>,
+    /// order size in quote asset
+    pub notional: f64,
+    pub fees: FeeSchedule,
+}
+
+impl ExecutablePriceModel {
+    pub fn new(
+        orderbooks: Arc<OrderBookMap>,
+        instruments: Arc<DynamicInstrumentManager>,
+        notional: f64,
+        fees: FeeSchedule,
+    ) -> Self {
+        Self {
+            orderbooks,
+            instruments,
+            notional,
+            fees,
+        }
+    }
+    pub fn taker_fee(&self, exchange: Exchange) -> f64 {
+        self.fees.fee_rate(exchange, true)
+    }
+    /// sweep of `intent` levels for the configured notional
+    pub fn sweep(&self, exchange: Exchange, asset: &Asset, intent: Intent) -> Option<BookSweep> {
+        let instrument = convert_asset_to_instrument(&self.instruments.load(), exchange, asset)?;
+        let sweep = self
+            .orderbooks
+            .depth_at_notional(&instrument.code_simple, intent, self.notional)?;
+        sweep.filled.then_some(sweep)
+    }
+    /// price received by selling (consumes bids) or paid by buying (consumes asks), fee included
+    fn net_price(&self, exchange: Exchange, asset: &Asset, intent: Intent) -> Option<f64> {
+        let sweep = self.sweep(exchange, asset, intent)?;
+        let fee = self.taker_fee(exchange);
+        Some(match intent {
+            Intent::Bid => sweep.average_price * (1.0 - fee),
+            Intent::Ask => sweep.average_price * (1.0 + fee),
+        })
+    }
+    /// the signal with bid/ask prices net of depth and fees, so spreads computed from it are executable.
+    /// the sizes stay those of the touch. None when a side cannot fill the notional
+    pub fn net_of_depth(
+        &self,
+        signal: &DbRowSignalBestBidAskAcrossExchanges,
+    ) -> Option<DbRowSignalBestBidAskAcrossExchanges> {
+        let mut signal = signal.clone();
+        let asset = signal.asset.clone();
+        signal.binance_ask_price = self.net_price(Exchange::BinanceFutures, &asset, Intent::Ask)?;
+        signal.binance_bid_price = self.net_price(Exchange::BinanceFutures, &asset, Intent::Bid)?;
+        signal.hyper_ask_price = self.net_price(Exchange::Hyperliquid, &asset, Intent::Ask)?;
+        signal.hyper_bid_price = self.net_price(Exchange::Hyperliquid, &asset, Intent::Bid)?;
+        Some(signal)
+    }
+}
@@ -0,0 +1,385 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+use eyre::Result;
+use gluesql::core::store::{GStore, GStoreMut};
+use gluesql_derive::{FromGlueSqlRow, ReflectGlueSqlRow, ToGlueSqlRow};
+use tracing::warn;
+
+use lib::gluesql::{Table, TableCreate, TableGetIndex, TableInfo};
+use lib::signal::get_terminate_flag;
+use lib::utils::get_time_milliseconds;
+use trading_exchange::utils::future::interval;
+use trading_model::{Asset, DurationMs, Exchange, PriceType, TimeStampMs};
+
+use crate::config::FairPriceConfig;
+use crate::signals::SignalLevel;
+use crate::strategy::broadcast::AsyncBroadcaster;
+use crate::strategy::data_factory::{LastPriceMap, PriceSourceAsset};
+use crate::strategy::instrument_refresh::DynamicInstrumentManager;
+use crate::strategy::strategy_constants;
+
+/// how often the fair price of every asset is recomputed
+const FAIR_PRICE_INTERVAL_MS: DurationMs = 200;
+/// fair prices older than this are not used by the detectors
+pub const FAIR_PRICE_STALE_MS: DurationMs = 1_000;
+
+/// price of one venue as seen by the engine
+#[derive(Debug, Clone)]
+pub struct FairPriceInput {
+    pub exchange: Exchange,
+    pub price: f64,
+    pub weight: f64,
+    pub age_ms: i64,
+}
+
+/// composite price of an asset across the configured venues
+#[derive(Debug, Clone)]
+pub struct FairPrice {
+    pub asset: Asset,
+    pub price: f64,
+    /// always `PriceType::Fair`, tags the price when stored next to venue prices
+    pub price_type: PriceType,
+    /// venues used after staleness and outlier filtering
+    pub sources: usize,
+    /// venues rejected as outliers
+    pub rejected: usize,
+    pub datetime: TimeStampMs,
+}
+
+/// weighted median, the weights need not be normalized
+fn weighted_median(inputs: &[(f64, f64)]) -> Option<f64> {
+    let mut inputs: Vec<_> = inputs.iter().filter(|(_, w)| *w > 0.0).copied().collect();
+    inputs.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let total: f64 = inputs.iter().map(|(_, w)| w).sum();
+    let mut cumulative = 0.0;
+    for (price, weight) in inputs {
+        cumulative += weight;
+        if cumulative * 2.0 >= total {
+            return Some(price);
+        }
+    }
+    None
+}
+
+/// weighted mean of the sources with the weights decayed by age, after rejecting the sources
+/// further than `outlier_bp` from the weighted median. returns (price, used, rejected)
+pub fn compose_fair_price(inputs: &[FairPriceInput], config: &FairPriceConfig) -> Option<(f64, usize, usize)> {
+    let weighted: Vec<(f64, f64)> = inputs
+        .iter()
+        .filter(|x| x.price > 0.0 && x.age_ms <= config.max_age_ms)
+        .map(|x| {
+            let decay = 0.5f64.powf(x.age_ms.max(0) as f64 / config.half_life_ms.max(1) as f64);
+            (x.price, x.weight * decay)
+        })
+        .collect();
+    let median = weighted_median(&weighted)?;
+    let mut sum = 0.0;
+    let mut sum_weight = 0.0;
+    let mut used = 0;
+    let mut rejected = 0;
+    for (price, weight) in weighted {
+        if ((price / median - 1.0) * 10_000.0).abs() > config.outlier_bp {
+            rejected += 1;
+            continue;
+        }
+        sum += price * weight;
+        sum_weight += weight;
+        used += 1;
+    }
+    if sum_weight <= 0.0 {
+        return None;
+    }
+    Some((sum / sum_weight, used, rejected))
+}
+
+/// latest fair price per asset, written by the engine, read by the signal generators
+pub struct FairPriceMap {
+    map: DashMap<Asset, FairPrice>,
+}
+impl FairPriceMap {
+    pub fn new() -> Self {
+        Self {
+            map: Default::default(),
+        }
+    }
+    pub fn insert(&self, price: FairPrice) {
+        self.map.insert(price.asset.clone(), price);
+    }
+    pub fn get(&self, asset: &Asset) -> Option<FairPrice> {
+        self.map.get(asset).map(|x| x.value().clone())
+    }
+    pub fn values(&self) -> Vec<FairPrice> {
+        self.map.iter().map(|x| x.value().clone()).collect()
+    }
+    /// the fair price of an asset unless it is older than `FAIR_PRICE_STALE_MS`.
+    /// the detectors measure their spreads against it and do not signal without it
+    pub fn fresh(&self, asset: &Asset, now: TimeStampMs) -> Option<f64> {
+        let fair = self.map.get(asset)?;
+        if now - fair.datetime > FAIR_PRICE_STALE_MS {
+            return None;
+        }
+        Some(fair.price)
+    }
+}
+
+/// venue deviating from the fair price
+#[derive(Debug, Clone, Copy, FromGlueSqlRow, ReflectGlueSqlRow, ToGlueSqlRow)]
+pub struct DbRowSignalFairPriceDifference {
+    pub id: u64,
+    pub datetime: TimeStampMs,
+    pub exchange: u8,
+    pub asset_id: u64,
+    pub signal_level: u8,
+    pub used: bool,
+    /// the ask of a venue lagging a fall, the bid of a venue lagging a rise
+    pub price: f64,
+    pub price_type: u8,
+    pub fair_price: f64,
+    /// price / fair price
+    pub ratio: f64,
+    /// venues the fair price was composed of
+    pub sources: u64,
+}
+impl DbRowSignalFairPriceDifference {
+    pub fn exchange(&self) -> Exchange {
+        Exchange::from_repr(self.exchange).unwrap()
+    }
+    pub fn asset(&self) -> Asset {
+        unsafe { Asset::from_hash(self.asset_id) }
+    }
+}
+
+#[async_trait(?Send)]
+impl<T: GStore + GStoreMut + Clone> TableCreate<DbRowSignalFairPriceDifference>
+    for Table<T, DbRowSignalFairPriceDifference>
+{
+    async fn create_table(&mut self) -> eyre::Result<()> {
+        let sql = DbRowSignalFairPriceDifference::get_ddl(self.table_name());
+        let _res = self.glue().execute(sql.as_str()).await?;
+        let last_index = self.get_last_index().await?;
+        self.set_index(last_index.unwrap_or_default());
+        Ok(())
+    }
+}
+
+/// signal when a venue ask / fair < T2 (venue lags a fall) or a venue bid / fair > T1 (venue lags a rise)
+pub struct VenueFairDiffSignalGenerator {
+    threshold_fall: f64,
+    threshold_rise: f64,
+    cooldown_ms: i64,
+    last_signals: HashMap<(Exchange, Asset), TimeStampMs>,
+}
+impl Default for VenueFairDiffSignalGenerator {
+    fn default() -> Self {
+        VenueFairDiffSignalGenerator {
+            threshold_fall: 0.997,
+            threshold_rise: 1.003,
+            cooldown_ms: strategy_constants::DIFFERENCE_COOLDOWN_MS as _,
+            last_signals: HashMap::new(),
+        }
+    }
+}
+impl VenueFairDiffSignalGenerator {
+    pub fn generate(
+        &mut self,
+        exchange: Exchange,
+        bid: f64,
+        ask: f64,
+        fair: &FairPrice,
+    ) -> Option<DbRowSignalFairPriceDifference> {
+        let ratio_ask = ask / fair.price;
+        let ratio_bid = bid / fair.price;
+        let (ratio, price, price_type) = if ratio_ask < self.threshold_fall {
+            (ratio_ask, ask, PriceType::Ask)
+        } else if ratio_bid > self.threshold_rise {
+            (ratio_bid, bid, PriceType::Bid)
+        } else {
+            return None;
+        };
+        let key = (exchange, fair.asset.clone());
+        if let Some(&last) = self.last_signals.get(&key) {
+            if fair.datetime < last + self.cooldown_ms {
+                return None;
+            }
+        }
+        self.last_signals.insert(key, fair.datetime);
+        Some(DbRowSignalFairPriceDifference {
+            // id being fed by the engine instead of generator
+            id: 0,
+            datetime: fair.datetime,
+            exchange: exchange as u8,
+            asset_id: fair.asset._hash(),
+            signal_level: SignalLevel::High as u8,
+            used: false,
+            price,
+            price_type: price_type as u8,
+            fair_price: fair.price,
+            ratio,
+            sources: fair.sources as u64,
+        })
+    }
+}
+
+/// combines the last prices of the configured venues into a fair price per asset,
+/// and signals the tradable venues deviating from it
+pub struct FairPriceEngine<T: GStore + GStoreMut + Clone> {
+    pub config: FairPriceConfig,
+    pub price_map: Arc<LastPriceMap>,
+    pub fair_price_map: Arc<FairPriceMap>,
+    pub instruments: Arc<DynamicInstrumentManager>,
+    pub tx: AsyncBroadcaster<DbRowSignalFairPriceDifference>,
+    pub table: Table<T, DbRowSignalFairPriceDifference>,
+}
+impl<T: GStore + GStoreMut + Clone> FairPriceEngine<T> {
+    pub async fn run(mut self) -> Result<()> {
+        let mut generator = VenueFairDiffSignalGenerator::default();
+        let mut interval = interval(FAIR_PRICE_INTERVAL_MS);
+        loop {
+            interval.tick().await;
+            if get_terminate_flag() {
+                return Ok(());
+            }
+            // assets of the tradable venues, the sources may list many more
+            let assets: HashSet<Asset> = self
+                .instruments
+                .load()
+                .iter()
+                .filter(|x| self.config.tradable.contains(&x.exchange))
+                .map(|x| x.base.asset.clone())
+                .collect();
+            let now = get_time_milliseconds();
+            for asset in assets {
+                let Some(fair) = self.calculate(&asset, now) else {
+                    continue;
+                };
+                self.fair_price_map.insert(fair.clone());
+                for &exchange in self.config.tradable.iter() {
+                    let Some((bid, ask)) = self.bid_ask(&asset, exchange) else {
+                        continue;
+                    };
+                    let Some(mut signal) = generator.generate(exchange, bid, ask, &fair) else {
+                        continue;
+                    };
+                    signal.id = self.table.next_index();
+                    if let Err(err) = self.table.insert(signal).await {
+                        warn!("failed to insert fair price signal: {err}");
+                        continue;
+                    }
+                    if let Err(err) = self.tx.broadcast(signal) {
+                        warn!("failed to broadcast fair price signal: {err:?}");
+                    }
+                }
+            }
+        }
+    }
+    fn last_price(&self, asset: &Asset, exchange: Exchange, price_type: PriceType) -> Option<(TimeStampMs, f64)> {
+        let price = self.price_map.get(&PriceSourceAsset {
+            asset: asset.clone(),
+            exchange,
+            price_type,
+        })?;
+        Some((price.activeness.time(), price.price))
+    }
+    fn bid_ask(&self, asset: &Asset, exchange: Exchange) -> Option<(f64, f64)> {
+        let (_, bid) = self.last_price(asset, exchange, PriceType::Bid)?;
+        let (_, ask) = self.last_price(asset, exchange, PriceType::Ask)?;
+        Some((bid, ask))
+    }
+    /// mid of the book ticker, or the last trade when it is more recent
+    fn source_price(&self, asset: &Asset, exchange: Exchange) -> Option<(TimeStampMs, f64)> {
+        let book = match (
+            self.last_price(asset, exchange, PriceType::Bid),
+            self.last_price(asset, exchange, PriceType::Ask),
+        ) {
+            (Some((bid_time, bid)), Some((ask_time, ask))) => Some((bid_time.min(ask_time), (bid + ask) / 2.0)),
+            _ => None,
+        };
+        let trade = self.last_price(asset, exchange, PriceType::Trade);
+        match (book, trade) {
+            (Some(book), Some(trade)) if trade.0 > book.0 => Some(trade),
+            (Some(book), _) => Some(book),
+            (None, trade) => trade,
+        }
+    }
+    fn calculate(&self, asset: &Asset, now: TimeStampMs) -> Option<FairPrice> {
+        let inputs: Vec<FairPriceInput> = self
+            .config
+            .sources
+            .iter()
+            .filter_map(|source| {
+                let (time, price) = self.source_price(asset, source.exchange)?;
+                Some(FairPriceInput {
+                    exchange: source.exchange,
+                    price,
+                    weight: source.weight,
+                    age_ms: now - time,
+                })
+            })
+            .collect();
+        let (price, sources, rejected) = compose_fair_price(&inputs, &self.config)?;
+        Some(FairPrice {
+            asset: asset.clone(),
+            price,
+            price_type: PriceType::Fair,
+            sources,
+            rejected,
+            datetime: now,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(exchange: Exchange, price: f64, weight: f64, age_ms: i64) -> FairPriceInput {
+        FairPriceInput {
+            exchange,
+            price,
+            weight,
+            age_ms,
+        }
+    }
+
+    #[test]
+    fn test_compose_fair_price() {
+        let config = FairPriceConfig::default();
+        let inputs = [
+            input(Exchange::BinanceFutures, 100.0, 2.0, 0),
+            input(Exchange::Bybit, 100.2, 1.0, 0),
+            // one half life old, weight halves
+            input(Exchange::Hyperliquid, 100.1, 2.0, config.half_life_ms),
+            // 100bp off the median
+            input(Exchange::GateioPerpetual, 101.0, 1.0, 0),
+            // older than max age
+            input(Exchange::Bitget, 90.0, 1.0, config.max_age_ms + 1),
+        ];
+        let (price, used, rejected) = compose_fair_price(&inputs, &config).unwrap();
+        assert_eq!(used, 3);
+        assert_eq!(rejected, 1);
+        let expected = (100.0 * 2.0 + 100.2 * 1.0 + 100.1 * 1.0) / 4.0;
+        assert!((price - expected).abs() < 1e-9);
+
+        assert!(compose_fair_price(&inputs[4..], &config).is_none());
+    }
+
+    #[test]
+    fn test_stale_fair_price_is_not_used() {
+        let map = FairPriceMap::new();
+        let asset: Asset = "BTC".into();
+        assert_eq!(map.fresh(&asset, 10_000), None);
+        map.insert(FairPrice {
+            asset: asset.clone(),
+            price: 100.0,
+            price_type: PriceType::Fair,
+            sources: 2,
+            rejected: 0,
+            datetime: 10_000,
+        });
+        assert_eq!(map.fresh(&asset, 10_000 + FAIR_PRICE_STALE_MS), Some(100.0));
+        assert_eq!(map.fresh(&asset, 10_000 + FAIR_PRICE_STALE_MS + 1), None);
+    }
+}
@@ -0,0 +1,357 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use eyre::Result;
+use gluesql_shared_sled_storage::SharedSledStorage;
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use build::model::UserFundingCarry;
+use lib::gluesql::{Table, TableSelectItem};
+use lib::signal::get_terminate_flag;
+use trading_exchange::exchange::hyperliquid::model::exchange::request::HyperliquidChain;
+use trading_exchange::exchange::hyperliquid::HyperliquidInfoClient;
+use trading_exchange::utils::future::interval;
+use trading_model::{Asset, DurationMs, Exchange, InstrumentCode, InstrumentSymbol, Symbol, Time};
+
+use crate::config::FundingConfig;
+use crate::db::gluesql::schema::funding_history::DbRowFundingHistory;
+use crate::strategy::broadcast::AsyncBroadcaster;
+use crate::strategy::data_factory::LastDerivativesContextMap;
+use crate::strategy::feed_health::FeedHealthMap;
+
+/// how far back the funding history is fetched when an instrument has none stored
+pub const FUNDING_BACKFILL_MS: i64 = 30 * 86_400_000;
+/// how often settled funding rates are fetched after the backfill
+const FUNDING_REFRESH_MS: DurationMs = 3_600_000;
+/// how often the carry between the venues is recomputed from the predicted rates
+const FUNDING_CARRY_INTERVAL_MS: DurationMs = 10_000;
+/// rows returned per request by `/fapi/v1/fundingRate`
+const BINANCE_FUNDING_LIMIT: usize = 1000;
+
+/// hours between two funding settlements of the instruments a venue lists no interval for
+pub fn default_funding_interval_hours(exchange: Exchange) -> f64 {
+    match exchange {
+        Exchange::Hyperliquid => 1.0,
+        _ => 8.0,
+    }
+}
+/// funding rate of one interval as a yearly rate
+pub fn annualize_funding_rate(interval_hours: f64, rate: f64) -> f64 {
+    rate * 24.0 / interval_hours * 365.0
+}
+
+/// hours between two funding settlements per instrument, as listed by the venues.
+/// refreshed by the history service, the others settle at the default interval of their venue
+pub struct FundingIntervalMap {
+    map: DashMap<(Exchange, Symbol), f64>,
+}
+impl FundingIntervalMap {
+    pub fn new() -> Self {
+        Self {
+            map: Default::default(),
+        }
+    }
+    pub fn insert(&self, exchange: Exchange, symbol: Symbol, hours: f64) {
+        self.map.insert((exchange, symbol), hours);
+    }
+    pub fn get(&self, instrument: &InstrumentCode) -> Option<f64> {
+        let exchange = instrument.get_exchange()?;
+        let symbol = instrument.get_symbol()?;
+        let hours = self
+            .map
+            .get(&(exchange, symbol))
+            .map(|x| *x.value())
+            .unwrap_or_else(|| default_funding_interval_hours(exchange));
+        Some(hours)
+    }
+}
+
+/// backfills settled funding rates from the exchanges and keeps fetching the new ones every hour,
+/// along with the funding intervals of the instruments
+pub struct FundingHistoryService {
+    pub config: FundingConfig,
+    pub table: Table<SharedSledStorage, DbRowFundingHistory>,
+    pub intervals: Arc<FundingIntervalMap>,
+    pub instruments: Vec<InstrumentSymbol>,
+}
+impl FundingHistoryService {
+    pub async fn run(mut self) -> Result<()> {
+        let hyper = HyperliquidInfoClient::new(HyperliquidChain::from(self.config.hyperliquid_network));
+        let mut interval = interval(FUNDING_REFRESH_MS);
+        loop {
+            interval.tick().await;
+            if get_terminate_flag() {
+                return Ok(());
+            }
+            self.sync(&hyper).await;
+        }
+    }
+    async fn sync(&mut self, hyper: &HyperliquidInfoClient) {
+        if self.instruments.iter().any(|x| x.exchange == Exchange::BinanceFutures) {
+            match fetch_binance_funding_intervals(&self.config.binance_futures_url).await {
+                Ok(intervals) => {
+                    for (symbol, hours) in intervals {
+                        self.intervals.insert(Exchange::BinanceFutures, symbol, hours);
+                    }
+                }
+                Err(err) => warn!("failed to fetch binance funding intervals: {err}"),
+            }
+        }
+        let mut inserted = 0;
+        for instrument in self.instruments.clone() {
+            if get_terminate_flag() {
+                return;
+            }
+            match self.sync_instrument(hyper, &instrument).await {
+                Ok(n) => inserted += n,
+                Err(err) => warn!("failed to fetch funding history of {}: {err}", instrument),
+            }
+        }
+        info!(
+            "stored {inserted} funding rates of {} instruments",
+            self.instruments.len()
+        );
+    }
+    /// inserts the rates settled after the last stored one
+    async fn sync_instrument(&mut self, hyper: &HyperliquidInfoClient, instrument: &InstrumentSymbol) -> Result<usize> {
+        let filter = DbRowFundingHistory::by_instrument(instrument.exchange, instrument.symbol._hash());
+        let stored = self.table.select_limit(Some(filter), "datetime DESC", Some(1)).await?;
+        let start_time = match stored.first() {
+            Some(row) => row.datetime + 1,
+            None => Time::now().millis() - FUNDING_BACKFILL_MS,
+        };
+        let rates = match instrument.exchange {
+            Exchange::BinanceFutures => {
+                fetch_binance_funding_rates(&self.config.binance_futures_url, &instrument.symbol, start_time).await?
+            }
+            Exchange::Hyperliquid => fetch_hyperliquid_funding_rates(hyper, &instrument.symbol, start_time).await?,
+            _ => return Ok(0),
+        };
+        let mut inserted = 0;
+        for (datetime, funding_rate) in rates {
+            if datetime < start_time {
+                continue;
+            }
+            let row = DbRowFundingHistory {
+                id: self.table.next_index(),
+                exchange_id: instrument.exchange as _,
+                symbol_id: instrument.symbol._hash(),
+                funding_rate,
+                datetime,
+            };
+            self.table.insert(row).await?;
+            inserted += 1;
+        }
+        Ok(inserted)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceFundingRate {
+    funding_rate: String,
+    funding_time: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceFundingInfo {
+    symbol: String,
+    funding_interval_hours: f64,
+}
+
+/// `/fapi/v1/fundingInfo`, lists only the symbols whose funding differs from the defaults
+async fn fetch_binance_funding_intervals(base_url: &str) -> Result<Vec<(Symbol, f64)>> {
+    let url = format!("{}/fapi/v1/fundingInfo", base_url.trim_end_matches('/'));
+    let text = reqwest::get(&url).await?.text().await?;
+    let info: Vec<BinanceFundingInfo> = serde_json::from_str(&text)?;
+    Ok(info
+        .into_iter()
+        .filter(|x| x.funding_interval_hours > 0.0)
+        .map(|x| (Symbol::from(x.symbol.as_str()), x.funding_interval_hours))
+        .collect())
+}
+
+/// `/fapi/v1/fundingRate`, paged forward from `start_time`. returns (funding time, rate)
+async fn fetch_binance_funding_rates(base_url: &str, symbol: &Symbol, mut start_time: i64) -> Result<Vec<(i64, f64)>> {
+    let mut rates = vec![];
+    loop {
+        let url = format!(
+            "{}/fapi/v1/fundingRate?symbol={}&startTime={}&limit={}",
+            base_url.trim_end_matches('/'),
+            symbol,
+            start_time,
+            BINANCE_FUNDING_LIMIT
+        );
+        let text = reqwest::get(&url).await?.text().await?;
+        let page: Vec<BinanceFundingRate> = serde_json::from_str(&text)?;
+        let len = page.len();
+        for row in page {
+            start_time = start_time.max(row.funding_time + 1);
+            rates.push((row.funding_time, f64::from_str(&row.funding_rate)?));
+        }
+        if len < BINANCE_FUNDING_LIMIT {
+            return Ok(rates);
+        }
+    }
+}
+
+/// `fundingHistory` of the info endpoint, paged forward from `start_time`. returns (funding time, rate)
+async fn fetch_hyperliquid_funding_rates(
+    client: &HyperliquidInfoClient,
+    symbol: &Symbol,
+    mut start_time: i64,
+) -> Result<Vec<(i64, f64)>> {
+    let mut rates = vec![];
+    loop {
+        let page = client
+            .funding_history(symbol.to_string(), start_time as _, None)
+            .await?;
+        let last_start = start_time;
+        for row in page {
+            start_time = start_time.max(row.time as i64 + 1);
+            rates.push((row.time as i64, f64::from_str(&row.funding_rate)?));
+        }
+        if start_time == last_start {
+            return Ok(rates);
+        }
+    }
+}
+
+/// cross-venue funding carry of an asset: long the leg paying less funding, short the one paying more
+#[derive(Debug, Clone)]
+pub struct FundingCarrySignal {
+    pub asset: Asset,
+    pub long_exchange: Exchange,
+    pub short_exchange: Exchange,
+    /// annualized predicted funding rate of the long leg
+    pub long_rate_annual: f64,
+    /// annualized predicted funding rate of the short leg
+    pub short_rate_annual: f64,
+    /// funding received by holding both legs, annualized
+    pub yield_annual: f64,
+    pub datetime: Time,
+}
+impl From<FundingCarrySignal> for UserFundingCarry {
+    fn from(value: FundingCarrySignal) -> Self {
+        UserFundingCarry {
+            asset: value.asset.to_string(),
+            long_exchange: value.long_exchange.to_string(),
+            short_exchange: value.short_exchange.to_string(),
+            long_rate_annual: value.long_rate_annual,
+            short_rate_annual: value.short_rate_annual,
+            yield_annual: value.yield_annual,
+            datetime: value.datetime.millis(),
+        }
+    }
+}
+
+/// latest carry per asset, written by the calculator, read by the endpoint
+pub struct FundingCarryMap {
+    map: DashMap<Asset, FundingCarrySignal>,
+}
+impl FundingCarryMap {
+    pub fn new() -> Self {
+        Self {
+            map: Default::default(),
+        }
+    }
+    pub fn insert(&self, signal: FundingCarrySignal) {
+        self.map.insert(signal.asset.clone(), signal);
+    }
+    pub fn get(&self, asset: &Asset) -> Option<FundingCarrySignal> {
+        self.map.get(asset).map(|x| x.value().clone())
+    }
+    pub fn values(&self) -> Vec<FundingCarrySignal> {
+        self.map.iter().map(|x| x.value().clone()).collect()
+    }
+}
+
+/// compares the predicted funding of every asset listed on both binance futures and hyperliquid
+pub struct FundingCarryCalculator {
+    pub derivatives_map: Arc<LastDerivativesContextMap>,
+    pub carry_map: Arc<FundingCarryMap>,
+    /// asset, binance futures instrument, hyperliquid instrument
+    pub pairs: Vec<(Asset, InstrumentCode, InstrumentCode)>,
+    pub tx: AsyncBroadcaster<FundingCarrySignal>,
+    pub intervals: Arc<FundingIntervalMap>,
+    /// predicted rates of a stale feed are not traded on
+    pub feed_health: Arc<FeedHealthMap>,
+}
+impl FundingCarryCalculator {
+    pub async fn run(self) -> Result<()> {
+        let mut interval = interval(FUNDING_CARRY_INTERVAL_MS);
+        loop {
+            interval.tick().await;
+            if get_terminate_flag() {
+                return Ok(());
+            }
+            for (asset, binance, hyper) in self.pairs.iter() {
+                let Some(signal) = self.calculate(asset, binance, hyper) else {
+                    continue;
+                };
+                self.carry_map.insert(signal.clone());
+                if let Err(err) = self.tx.broadcast(signal) {
+                    warn!("failed to broadcast funding carry: {err:?}");
+                }
+            }
+        }
+    }
+    fn calculate(&self, asset: &Asset, binance: &InstrumentCode, hyper: &InstrumentCode) -> Option<FundingCarrySignal> {
+        if self.feed_health.is_stale(Exchange::BinanceFutures, asset)
+            || self.feed_health.is_stale(Exchange::Hyperliquid, asset)
+        {
+            return None;
+        }
+        let binance_rate = self.derivatives_map.get(binance)?.funding_rate?;
+        let hyper_rate = self.derivatives_map.get(hyper)?.funding_rate?;
+        let binance_annual = annualize_funding_rate(self.intervals.get(binance)?, binance_rate);
+        let hyper_annual = annualize_funding_rate(self.intervals.get(hyper)?, hyper_rate);
+        let ((long_exchange, long_rate_annual), (short_exchange, short_rate_annual)) = if binance_annual <= hyper_annual
+        {
+            (
+                (Exchange::BinanceFutures, binance_annual),
+                (Exchange::Hyperliquid, hyper_annual),
+            )
+        } else {
+            (
+                (Exchange::Hyperliquid, hyper_annual),
+                (Exchange::BinanceFutures, binance_annual),
+            )
+        };
+        Some(FundingCarrySignal {
+            asset: asset.clone(),
+            long_exchange,
+            short_exchange,
+            long_rate_annual,
+            short_rate_annual,
+            yield_annual: short_rate_annual - long_rate_annual,
+            datetime: Time::now(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annualize_funding_rate() {
+        // 0.01% every 8 hours
+        assert!((annualize_funding_rate(8.0, 0.0001) - 0.1095).abs() < 1e-9);
+        // 0.00125% every hour
+        assert!((annualize_funding_rate(1.0, 0.0000125) - 0.1095).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_funding_interval_of_the_venue_or_listed() {
+        let intervals = FundingIntervalMap::new();
+        intervals.insert(Exchange::BinanceFutures, "BLZUSDT".into(), 4.0);
+        let code = |exchange, symbol: &str| InstrumentCode::from_symbol(exchange, symbol.into());
+        assert_eq!(intervals.get(&code(Exchange::BinanceFutures, "BLZUSDT")), Some(4.0));
+        assert_eq!(intervals.get(&code(Exchange::BinanceFutures, "BTCUSDT")), Some(8.0));
+        assert_eq!(intervals.get(&code(Exchange::Hyperliquid, "BTC")), Some(1.0));
+    }
+}
@@ -3,6 +3,14 @@ use std::str::FromStr;
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use serde::{Deserialize, Serialize};
 
+/// candles aggregated from trades
+pub mod candles;
+/// executable prices from the maintained books
+pub mod executable_price;
+/// composite fair price across venues
+pub mod fair_price;
+/// funding history and cross-venue funding carry
+pub mod funding;
 /// price
 pub mod price;
 /// sig price change
@@ -12,6 +20,8 @@ pub mod price_difference;
 pub mod price_manager;
 /// price pair
 pub mod price_spread;
+/// order-flow imbalance, trade rate and large trades
+pub mod trade_flow;
 
 #[derive(
     Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, TryFromPrimitive, IntoPrimitive, Deserialize,
@@ -248,11 +248,13 @@ impl BinHyperDifferenceConverter {
             filter: SignalCooldownFilter::new(Duration::from_millis(cooldown_ms)),
         }
     }
+    /// the hyper bid against the fair price of the asset
     pub fn convert_price_to_difference(
         &mut self,
         input: &DbRowSignalBestBidAskAcrossExchanges,
+        fair_price: f64,
     ) -> Option<DbRowSignalPriceDifference> {
-        let bp: f64 = get_basis_point(input.hyper_bid_price, input.hyper_mark);
+        let bp: f64 = get_basis_point(input.hyper_bid_price, fair_price);
         let level: SignalLevel = if bp.abs() < self.threshold_high {
             SignalLevel::Normal
         } else if bp.abs() < self.threshold_crit {
@@ -271,8 +273,7 @@ impl BinHyperDifferenceConverter {
             hyper: input.hyper_bid_price,
             hyper_oracle: input.hyper_oracle,
             hyper_mark: input.hyper_mark,
-            // FIXME: double check meaning of difference
-            difference: input.hyper_bid_price - input.hyper_mark,
+            difference: input.hyper_bid_price - fair_price,
             bp,
             signal_level: level as _,
             used: false,
@@ -1,20 +1,26 @@
 use crate::db::gluesql::schema::canclestack::DbRowCandlestick;
+use crate::db::gluesql::schema::candle::DbRowCandle;
 use crate::db::gluesql::schema::funding_rate::DbRowFundingRate;
+use crate::db::gluesql::schema::liquidation::DbRowLiquidation;
+use crate::signals::candles::CandleMap;
 use crate::signals::price_spread::{DbRowSignalBestBidAskAcrossExchanges, WorktableSignalBestBidAskAcrossExchanges};
+use crate::signals::trade_flow::TradeFlowMap;
 use crate::strategy::broadcast::AsyncBroadcaster;
-use crate::strategy::data_factory::BuffferedPriceUpdateConverter;
+use crate::strategy::data_factory::{BuffferedPriceUpdateConverter, LastDerivativesContextMap, OrderBookMap};
+use crate::strategy::feed_health::FeedHealthMap;
 use eyre::bail;
 use eyre::Result;
 use gluesql::core::ast_builder::col;
 use gluesql::core::store::{GStore, GStoreMut};
 use gluesql_derive::ToGlueSql;
+use gluesql_shared_sled_storage::SharedSledStorage;
 use kanal::AsyncReceiver;
 use lib::gluesql::Table;
 use lib::warn::WarnManager;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
-use trading_model::{FundingRateEvent, MarketEvent, PriceType, Quotes};
+use trading_model::{Exchange, FundingRateEvent, LiquidationEvent, MarketEvent, MarketTrade, PriceType, Quotes};
 use trading_model::{InstrumentCode, L2OrderBook};
 
 /// generates PriceUpdate event for the strategy, and
@@ -26,6 +32,16 @@ pub struct PriceManager<S1: GStore + GStoreMut + Clone> {
     pub tx_price: AsyncBroadcaster<DbRowSignalBestBidAskAcrossExchanges>,
     pub factory: BuffferedPriceUpdateConverter,
     pub table_candlestick: Table<S1, DbRowCandlestick>,
+    pub table_liquidation: Table<SharedSledStorage, DbRowLiquidation>,
+    /// closed candles of every interval
+    pub table_candle: Table<SharedSledStorage, DbRowCandle>,
+    pub candle_map: Arc<CandleMap>,
+    pub trade_flow_map: Arc<TradeFlowMap>,
+    pub derivatives_map: Arc<LastDerivativesContextMap>,
+    /// full-depth books shared with the strategies
+    pub orderbook_map: Arc<OrderBookMap>,
+    /// spreads of assets with a stale feed on either venue are not generated
+    pub feed_health: Arc<FeedHealthMap>,
     // TODO: duplicate computation but fine for now
     pub orderbooks: HashMap<InstrumentCode, L2OrderBook<100>>,
 }
@@ -39,6 +55,22 @@ impl<S1: GStore + GStoreMut + Clone> PriceManager<S1> {
         self.table_funding_rate.upsert(row, Some(filter)).await?;
         Ok(())
     }
+    async fn insert_liquidation(&mut self, liquidation: LiquidationEvent) -> Result<()> {
+        // malformed events are skipped, the error is logged by the caller
+        let mut row = DbRowLiquidation::try_from(liquidation)?;
+        row.id = self.table_liquidation.next_index();
+        self.table_liquidation.insert(row).await
+    }
+    /// aggregates the trade into the live candles, the trade flow and the last prices, and persists the candles it closes
+    async fn insert_trade(&mut self, trade: &MarketTrade) -> Result<()> {
+        self.trade_flow_map.on_trade(trade);
+        self.factory
+            .insert_price(&trade.instrument, PriceType::Trade, trade.price, Some(trade.size));
+        for candle in self.candle_map.on_trade(trade) {
+            self.table_candle.insert(candle.try_into()?).await?;
+        }
+        Ok(())
+    }
     pub async fn run(&mut self) -> Result<()> {
         let timeout_duration_s = 10;
         let mut count = 0;
@@ -62,6 +94,7 @@ impl<S1: GStore + GStoreMut + Clone> PriceManager<S1> {
                     };
                     match result_feed {
                         MarketEvent::Quotes(quotes) => {
+                            self.orderbook_map.apply(&quotes);
                             // bid ask
                             let Some(price) = self.generate_price_spread_from_quotes(quotes).await else {
                                 continue
@@ -76,6 +109,18 @@ impl<S1: GStore + GStoreMut + Clone> PriceManager<S1> {
                             }
                             warn_manager.warn(format!("failed to broadcast price update: {e}"));
                         },
+                        MarketEvent::Trade(trade) => {
+                            if let Err(err) = self.insert_trade(&trade).await {
+                                warn_manager.warn(&format!("insert candle error: {err}"));
+                            }
+                        },
+                        MarketEvent::Trades(trades) => {
+                            for trade in trades {
+                                if let Err(err) = self.insert_trade(&trade).await {
+                                    warn_manager.warn(&format!("insert candle error: {err}"));
+                                }
+                            }
+                        },
                         MarketEvent::Price(price) => {
                             self.factory.insert_price_event(&price);
                         },
@@ -87,6 +132,14 @@ impl<S1: GStore + GStoreMut + Clone> PriceManager<S1> {
                                 self.insert_funding_rate(rate).await?;
                             }
                         },
+                        MarketEvent::Liquidation(liquidation) => {
+                            if let Err(err) = self.insert_liquidation(liquidation).await {
+                                warn_manager.warn(&format!("insert liquidation error: {err}"));
+                            }
+                        },
+                        MarketEvent::DerivativesContext(context) => {
+                            self.derivatives_map.update(context);
+                        },
                         MarketEvent::OHLCVT(ohlcvt) => {
                             let row: DbRowCandlestick = ohlcvt.into();
                             if let Err(err) = self.table_candlestick.upsert(row, None).await {
@@ -119,10 +172,22 @@ impl<S1: GStore + GStoreMut + Clone> PriceManager<S1> {
         self.factory
             .insert_price(&quotes.instrument, PriceType::Bid, best_bid.price, Some(best_bid.size));
 
+        // the other venues only feed the last prices of the fair price
+        if !matches!(
+            quotes.instrument.get_exchange(),
+            Some(Exchange::BinanceFutures | Exchange::Hyperliquid)
+        ) {
+            return None;
+        }
         let Some(price_spread) = self.factory.convert(&quotes.instrument) else {
             // proceed when all price data is stored
             return None;
         };
+        if self.feed_health.is_stale(Exchange::BinanceFutures, &price_spread.asset)
+            || self.feed_health.is_stale(Exchange::Hyperliquid, &price_spread.asset)
+        {
+            return None;
+        }
 
         self.price_pair_worktable.write().await.insert(price_spread.clone());
 
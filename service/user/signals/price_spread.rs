@@ -1,4 +1,6 @@
 use crate::db::gluesql::schema::spread::{DbRowSpread, DbRowSpreadExt};
+use crate::signals::executable_price::ExecutablePriceModel;
+use crate::signals::fair_price::FairPriceMap;
 use build::model::PriceSpread;
 use chrono::TimeZone;
 use dashmap::DashMap;
@@ -103,6 +105,21 @@ impl WorktableSignalBestBidAskAcrossExchanges {
         self.table.iter_mut().skip(len).for_each(|row| row.remove());
         self.table.sort_by_column(IdCol::NAME);
     }
+    /// removes the oldest rows, the ones before `datetime` and the ones above `max_rows`. returns the rows removed
+    pub fn remove_expired(&mut self, datetime: TimeStampMs, max_rows: Option<usize>) -> usize {
+        let expired = self
+            .table
+            .iter()
+            .take_while(|row| *row.index(DatetimeCol) < datetime)
+            .count();
+        let excess = max_rows.map_or(0, |max_rows| self.table.len().saturating_sub(max_rows));
+        let count = expired.max(excess);
+        if count > 0 {
+            self.table.iter_mut().take(count).for_each(|row| row.remove());
+            self.table.sort_by_column(IdCol::NAME);
+        }
+        count
+    }
 }
 pub struct WorktableSignalPricePairRowView<'a>(RowView<'a>);
 impl WorktableSignalPricePairRowView<'_> {
@@ -196,6 +213,14 @@ impl DbRowSignalBestBidAskAcrossExchanges {
     pub fn spread_buy_hyper(&self) -> f64 {
         self.binance_bid_price / self.hyper_ask_price - 1.0
     }
+    /// what selling the hyper bid earns against the fair price
+    pub fn spread_sell_hyper_fair(&self, fair: f64) -> f64 {
+        self.hyper_bid_price / fair - 1.0
+    }
+    /// what buying the hyper ask earns against the fair price
+    pub fn spread_buy_hyper_fair(&self, fair: f64) -> f64 {
+        fair / self.hyper_ask_price - 1.0
+    }
 }
 impl Display for DbRowSignalBestBidAskAcrossExchanges {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -213,18 +238,30 @@ impl Display for DbRowSignalBestBidAskAcrossExchanges {
         )
     }
 }
+/// accumulates the hyper spreads against the fair price net of depth and fees,
+/// the same basis the strategies compare the current spread on
 pub struct SignalSpreadAccumulator {
     table: Table<SharedMemoryStorage, DbRowSpread>,
     table2: SpreadMeanTable,
     rx: AsyncReceiver<DbRowSignalBestBidAskAcrossExchanges>,
+    executable: ExecutablePriceModel,
+    fair_price_map: Arc<FairPriceMap>,
 }
 impl SignalSpreadAccumulator {
     pub fn new(
         table: Table<SharedMemoryStorage, DbRowSpread>,
         table2: SpreadMeanTable,
         rx: AsyncReceiver<DbRowSignalBestBidAskAcrossExchanges>,
+        executable: ExecutablePriceModel,
+        fair_price_map: Arc<FairPriceMap>,
     ) -> Self {
-        Self { table, rx, table2 }
+        Self {
+            table,
+            rx,
+            table2,
+            executable,
+            fair_price_map,
+        }
     }
 
     pub async fn update_mean_spread(&self) -> Result<()> {
@@ -263,13 +300,21 @@ impl SignalSpreadAccumulator {
                     }
                 },
                 Ok(signal) = self.rx.recv() => {
+                    // books too thin for the order are not a spread
+                    let Some(signal) = self.executable.net_of_depth(&signal) else {
+                        continue;
+                    };
+                    let Some(fair) = self.fair_price_map.fresh(&signal.asset, signal.datetime) else {
+                        continue;
+                    };
                     let mut spread = DbRowSpread {
                         id: 0,
                         asset: signal.asset._hash(),
                         exchange_1: Exchange::Hyperliquid as _,
-                        exchange_2: Exchange::BinanceFutures as _,
-                        spread_buy_1: signal.spread_sell_hyper(),
-                        spread_sell_1: signal.spread_buy_hyper(),
+                        // the fair price, not a venue
+                        exchange_2: Exchange::Null as _,
+                        spread_buy_1: signal.spread_sell_hyper_fair(fair),
+                        spread_sell_1: signal.spread_buy_hyper_fair(fair),
                         datetime: signal.datetime,
                     };
                     spread.id = self.table.next_index();
@@ -0,0 +1,416 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+use eyre::Result;
+use gluesql::core::store::{GStore, GStoreMut};
+use gluesql_derive::{FromGlueSqlRow, ReflectGlueSqlRow, ToGlueSqlRow};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use lib::gluesql::{Table, TableCreate, TableGetIndex, TableInfo};
+use lib::signal::get_terminate_flag;
+use trading_exchange::utils::future::interval;
+use trading_model::{Asset, BucketSeries, DurationMs, Exchange, MarketTrade, SeriesRow, Side, TickSeries, Time};
+
+use crate::signals::SignalLevel;
+use crate::strategy::broadcast::AsyncBroadcaster;
+use crate::strategy::feed_health::FeedHealthMap;
+use crate::strategy::instrument_refresh::DynamicInstrumentManager;
+use crate::strategy::strategy_constants;
+
+/// width of the buckets trades are aggregated into
+pub const TRADE_FLOW_BUCKET_MS: DurationMs = 1_000;
+/// rolling window the flow is computed over
+pub const TRADE_FLOW_WINDOW_MS: DurationMs = 60_000;
+/// buckets kept per instrument
+const TRADE_FLOW_BUCKET_CAPACITY: usize = 300;
+/// large trades kept per instrument
+const LARGE_TRADE_CAPACITY: usize = 100;
+/// a trade is large when its notional is this many times the mean notional of the window
+const LARGE_TRADE_MULTIPLIER: f64 = 10.0;
+/// trades in the window before large trades are detected, the mean is meaningless before
+const LARGE_TRADE_MIN_COUNT: u64 = 20;
+/// trades in the window before a signal is generated
+const TRADE_FLOW_MIN_COUNT: u64 = 10;
+/// how often the flow of every instrument is evaluated
+const TRADE_FLOW_INTERVAL_MS: DurationMs = 1_000;
+
+/// trades of one bucket, split by taker side
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeFlowBucket {
+    pub time: Time,
+    pub buy_volume: f64,
+    pub sell_volume: f64,
+    pub buy_notional: f64,
+    pub sell_notional: f64,
+    pub count: u64,
+}
+impl TradeFlowBucket {
+    fn new(time: Time) -> Self {
+        Self {
+            time,
+            buy_volume: 0.0,
+            sell_volume: 0.0,
+            buy_notional: 0.0,
+            sell_notional: 0.0,
+            count: 0,
+        }
+    }
+    fn add(&mut self, trade: &MarketTrade) {
+        match trade.side {
+            Side::Buy => {
+                self.buy_volume += trade.size;
+                self.buy_notional += trade.cost();
+            }
+            Side::Sell => {
+                self.sell_volume += trade.size;
+                self.sell_notional += trade.cost();
+            }
+            _ => return,
+        }
+        self.count += 1;
+    }
+}
+impl SeriesRow for TradeFlowBucket {
+    fn get_timestamp(&self) -> Time {
+        self.time
+    }
+}
+
+/// trade flow of an instrument over the rolling window
+#[derive(Debug, Clone)]
+pub struct TradeFlowStats {
+    pub exchange: Exchange,
+    pub asset: Asset,
+    pub buy_volume: f64,
+    pub sell_volume: f64,
+    /// taker buy volume minus taker sell volume
+    pub signed_volume: f64,
+    /// signed volume over total volume, from -1 (only sellers) to 1 (only buyers)
+    pub imbalance: f64,
+    /// trades per second
+    pub trade_rate: f64,
+    pub vwap: f64,
+    pub trade_count: u64,
+    pub large_trade_count: u64,
+    /// taker buy volume minus taker sell volume of the large trades
+    pub large_trade_signed_volume: f64,
+    pub datetime: Time,
+}
+
+/// 1s buckets and recent large trades of one instrument
+#[derive(Debug, Clone)]
+pub struct InstrumentTradeFlow {
+    buckets: BucketSeries<TradeFlowBucket>,
+    large_trades: TickSeries<MarketTrade>,
+}
+impl InstrumentTradeFlow {
+    pub fn new() -> Self {
+        Self {
+            buckets: BucketSeries::new_bucket(
+                TRADE_FLOW_BUCKET_CAPACITY,
+                Duration::from_millis(TRADE_FLOW_BUCKET_MS as _),
+            ),
+            large_trades: TickSeries::new_tick(LARGE_TRADE_CAPACITY),
+        }
+    }
+    fn trade_time(trade: &MarketTrade) -> Time {
+        if trade.exchange_time == Time::NULL {
+            trade.received_time
+        } else {
+            trade.exchange_time
+        }
+    }
+    fn window(&self, now: Time) -> impl Iterator<Item = &TradeFlowBucket> {
+        let start = now.millis() - TRADE_FLOW_WINDOW_MS;
+        self.buckets.iter().rev().take_while(move |x| x.time.millis() > start)
+    }
+    /// mean notional per trade over the window, none until enough trades are seen
+    fn mean_notional(&self, now: Time) -> Option<f64> {
+        let (notional, count) = self.window(now).fold((0.0, 0), |(notional, count), x| {
+            (notional + x.buy_notional + x.sell_notional, count + x.count)
+        });
+        if count < LARGE_TRADE_MIN_COUNT {
+            return None;
+        }
+        Some(notional / count as f64)
+    }
+    pub fn on_trade(&mut self, trade: &MarketTrade) {
+        let time = Self::trade_time(trade);
+        if let Some(mean) = self.mean_notional(time) {
+            if trade.cost() >= mean * LARGE_TRADE_MULTIPLIER {
+                self.large_trades.push(trade.clone());
+            }
+        }
+        let bucket_id = time.millis() / TRADE_FLOW_BUCKET_MS;
+        // pushing into the bucket of the last item overwrites it, so the trade is merged first.
+        // late trades are counted in the last bucket
+        let mut bucket = match self.buckets.get(0) {
+            Some(last) if last.time.millis() / TRADE_FLOW_BUCKET_MS >= bucket_id => last.clone(),
+            _ => TradeFlowBucket::new(time),
+        };
+        bucket.add(trade);
+        self.buckets.push(bucket);
+    }
+    pub fn stats(&self, exchange: Exchange, asset: Asset, now: Time) -> Option<TradeFlowStats> {
+        let mut total = TradeFlowBucket::new(now);
+        for x in self.window(now) {
+            total.buy_volume += x.buy_volume;
+            total.sell_volume += x.sell_volume;
+            total.buy_notional += x.buy_notional;
+            total.sell_notional += x.sell_notional;
+            total.count += x.count;
+        }
+        let volume = total.buy_volume + total.sell_volume;
+        if total.count == 0 || volume <= 0.0 {
+            return None;
+        }
+        let start = now.millis() - TRADE_FLOW_WINDOW_MS;
+        let mut large_trade_count = 0;
+        let mut large_trade_signed_volume = 0.0;
+        for trade in self
+            .large_trades
+            .iter()
+            .rev()
+            .take_while(|x| Self::trade_time(x).millis() > start)
+        {
+            large_trade_count += 1;
+            large_trade_signed_volume += if trade.buyer_taker() { trade.size } else { -trade.size };
+        }
+        let signed_volume = total.buy_volume - total.sell_volume;
+        Some(TradeFlowStats {
+            exchange,
+            asset,
+            buy_volume: total.buy_volume,
+            sell_volume: total.sell_volume,
+            signed_volume,
+            imbalance: signed_volume / volume,
+            trade_rate: total.count as f64 * 1000.0 / TRADE_FLOW_WINDOW_MS as f64,
+            vwap: (total.buy_notional + total.sell_notional) / volume,
+            trade_count: total.count,
+            large_trade_count,
+            large_trade_signed_volume,
+            datetime: now,
+        })
+    }
+}
+
+/// rolling trade flow per instrument, keyed by exchange and base asset like the price map.
+/// written by price manager from the trades of the feeds, read by the signal generator and the strategies
+pub struct TradeFlowMap {
+    manager: Arc<DynamicInstrumentManager>,
+    map: DashMap<(Exchange, Asset), InstrumentTradeFlow>,
+}
+impl TradeFlowMap {
+    pub fn new(manager: Arc<DynamicInstrumentManager>) -> Self {
+        Self {
+            manager,
+            map: Default::default(),
+        }
+    }
+    pub fn on_trade(&self, trade: &MarketTrade) {
+        let manager = self.manager.load();
+        let Some(instrument) = manager.get(&trade.instrument) else {
+            return;
+        };
+        self.map
+            .entry((instrument.exchange, instrument.base.asset.clone()))
+            .or_insert_with(InstrumentTradeFlow::new)
+            .on_trade(trade);
+    }
+    pub fn stats(&self, exchange: Exchange, asset: &Asset) -> Option<TradeFlowStats> {
+        let flow = self.map.get(&(exchange, asset.clone()))?;
+        flow.stats(exchange, asset.clone(), Time::now())
+    }
+    pub fn values(&self) -> Vec<TradeFlowStats> {
+        let now = Time::now();
+        self.map
+            .iter()
+            .filter_map(|x| {
+                let (exchange, asset) = x.key();
+                x.value().stats(*exchange, asset.clone(), now)
+            })
+            .collect()
+    }
+}
+
+////////////////////////////// TRADE FLOW SIGNAL
+
+#[derive(Debug, Clone, Copy, FromGlueSqlRow, ReflectGlueSqlRow, ToGlueSqlRow)]
+pub struct DbRowSignalTradeFlow {
+    pub id: u64,
+    pub datetime: i64,
+    pub exchange: u8,
+    pub asset_id: u64,
+    pub signal_level: u8,
+    pub used: bool,
+    pub is_buy_pressure: bool,
+    pub signed_volume: f64,
+    pub imbalance: f64,
+    pub trade_rate: f64,
+    pub vwap: f64,
+    pub large_trade_count: u64,
+    pub large_trade_signed_volume: f64,
+}
+
+impl DbRowSignalTradeFlow {
+    pub fn exchange(&self) -> Exchange {
+        Exchange::from_repr(self.exchange).unwrap()
+    }
+    pub fn asset(&self) -> Asset {
+        unsafe { Asset::from_hash(self.asset_id) }
+    }
+}
+
+#[async_trait(?Send)]
+impl<T: GStore + GStoreMut + Clone> TableCreate<DbRowSignalTradeFlow> for Table<T, DbRowSignalTradeFlow> {
+    async fn create_table(&mut self) -> eyre::Result<()> {
+        let sql = DbRowSignalTradeFlow::get_ddl(self.table_name());
+        let _res = self.glue().execute(sql.as_str()).await?;
+        let last_index = self.get_last_index().await?;
+        self.set_index(last_index.unwrap_or_default());
+        Ok(())
+    }
+}
+
+/// signals one-sided flow, one per exchange and asset per cooldown
+pub struct TradeFlowToSignalConverter {
+    threshold_high: f64,
+    threshold_crit: f64,
+    cooldown_ms: i64,
+    last_signals: HashMap<(Exchange, Asset), i64>,
+}
+impl TradeFlowToSignalConverter {
+    pub fn new(threshold_high: f64, threshold_crit: f64, cooldown_ms: u64) -> Self {
+        Self {
+            threshold_high,
+            threshold_crit,
+            cooldown_ms: cooldown_ms as _,
+            last_signals: HashMap::new(),
+        }
+    }
+    pub fn convert(&mut self, input: &TradeFlowStats) -> Option<DbRowSignalTradeFlow> {
+        if input.trade_count < TRADE_FLOW_MIN_COUNT {
+            return None;
+        }
+        let imbalance_abs = input.imbalance.abs();
+        let level = if imbalance_abs < self.threshold_high {
+            return None;
+        } else if imbalance_abs < self.threshold_crit {
+            SignalLevel::High
+        } else {
+            SignalLevel::Critical
+        };
+        let datetime = input.datetime.millis();
+        let key = (input.exchange, input.asset.clone());
+        if let Some(&last) = self.last_signals.get(&key) {
+            if datetime < last + self.cooldown_ms {
+                return None;
+            }
+        }
+        self.last_signals.insert(key, datetime);
+        Some(DbRowSignalTradeFlow {
+            id: 0,
+            datetime,
+            exchange: input.exchange as _,
+            asset_id: input.asset._hash(),
+            signal_level: level as _,
+            used: false,
+            is_buy_pressure: input.signed_volume > 0.0,
+            signed_volume: input.signed_volume,
+            imbalance: input.imbalance,
+            trade_rate: input.trade_rate,
+            vwap: input.vwap,
+            large_trade_count: input.large_trade_count,
+            large_trade_signed_volume: input.large_trade_signed_volume,
+        })
+    }
+}
+
+/// evaluates the trade flow every second, stores and broadcasts the signals
+pub struct TradeFlowSignalGenerator<T: GStore + GStoreMut + Clone> {
+    pub trade_flow: Arc<TradeFlowMap>,
+    pub tx: AsyncBroadcaster<DbRowSignalTradeFlow>,
+    pub table: Table<T, DbRowSignalTradeFlow>,
+    /// flow of a stale feed is not signalled
+    pub feed_health: Arc<FeedHealthMap>,
+}
+impl<T: GStore + GStoreMut + Clone> TradeFlowSignalGenerator<T> {
+    pub async fn run(mut self) -> Result<()> {
+        let mut converter = TradeFlowToSignalConverter::new(
+            strategy_constants::TRADE_FLOW_IMBALANCE_HIGH,
+            strategy_constants::TRADE_FLOW_IMBALANCE_CRITICAL,
+            strategy_constants::TRADE_FLOW_COOLDOWN_MS,
+        );
+        let mut interval = interval(TRADE_FLOW_INTERVAL_MS);
+        loop {
+            interval.tick().await;
+            if get_terminate_flag() {
+                return Ok(());
+            }
+            for stats in self.trade_flow.values() {
+                if self.feed_health.is_stale(stats.exchange, &stats.asset) {
+                    continue;
+                }
+                let Some(mut signal) = converter.convert(&stats) else {
+                    continue;
+                };
+                signal.id = self.table.next_index();
+                if let Err(err) = self.table.insert(signal).await {
+                    warn!("failed to insert trade flow signal: {err}");
+                    continue;
+                }
+                if let Err(err) = self.tx.broadcast(signal) {
+                    warn!("failed to broadcast trade flow signal: {err:?}");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(millis: i64, side: Side, price: f64, size: f64) -> MarketTrade {
+        MarketTrade {
+            price,
+            size,
+            side,
+            exchange_time: Time::from_millis(millis),
+            ..MarketTrade::empty()
+        }
+    }
+
+    #[test]
+    fn test_instrument_trade_flow() {
+        let mut flow = InstrumentTradeFlow::new();
+        let start = 1_700_000_000_000;
+        for i in 0..30 {
+            flow.on_trade(&trade(start + i * 100, Side::Buy, 100.0, 1.0));
+        }
+        for i in 0..10 {
+            flow.on_trade(&trade(start + 3_000 + i * 100, Side::Sell, 110.0, 1.0));
+        }
+        // 10x the mean notional
+        flow.on_trade(&trade(start + 5_000, Side::Buy, 100.0, 20.0));
+
+        let now = Time::from_millis(start + 6_000);
+        let stats = flow.stats(Exchange::BinanceFutures, Asset::from("BTC"), now).unwrap();
+        assert_eq!(stats.trade_count, 41);
+        assert_eq!(stats.buy_volume, 50.0);
+        assert_eq!(stats.sell_volume, 10.0);
+        assert_eq!(stats.signed_volume, 40.0);
+        assert!((stats.imbalance - 40.0 / 60.0).abs() < 1e-9);
+        assert!((stats.vwap - (5000.0 + 1100.0) / 60.0).abs() < 1e-9);
+        assert_eq!(stats.large_trade_count, 1);
+        assert_eq!(stats.large_trade_signed_volume, 20.0);
+
+        // everything is out of the window
+        let now = Time::from_millis(start + 5_000 + TRADE_FLOW_WINDOW_MS);
+        assert!(flow.stats(Exchange::BinanceFutures, Asset::from("BTC"), now).is_none());
+    }
+}
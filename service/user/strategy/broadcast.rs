@@ -1,6 +1,8 @@
+use crate::metrics::broadcast_backlog;
 use arrayvec::ArrayVec;
 use eyre::Result;
 use kanal::{AsyncReceiver, AsyncSender};
+use lib::metrics::Gauge;
 use parking_lot::RwLock;
 use std::sync::Arc;
 
@@ -14,6 +16,8 @@ struct AsyncBroadcasterInner<T: Clone> {
     subscribers: ArrayVec<(u8, AsyncSender<T>), CAPACITY>,
     buffer_size: usize,
     broadcaster_id: u8,
+    /// labelled with the message type, which identifies the channel
+    backlog: Arc<Gauge>,
 }
 impl<T: Clone> AsyncBroadcasterInner<T> {
     pub fn new(buffer_size: usize) -> Self {
@@ -21,6 +25,7 @@ impl<T: Clone> AsyncBroadcasterInner<T> {
             buffer_size,
             subscribers: ArrayVec::new(),
             broadcaster_id: 0,
+            backlog: broadcast_backlog(&short_type_name::<T>()),
         }
     }
     /// subscribe to the broadcaster
@@ -36,9 +41,11 @@ impl<T: Clone> AsyncBroadcasterInner<T> {
     pub fn broadcast(&self, data: T) -> Result<()> {
         let mut fail_by_full = ArrayVec::<u8, CAPACITY>::new();
         let mut fail_by_gone = ArrayVec::<u8, CAPACITY>::new();
+        let mut backlog = 0;
         // broadcast should be done for all channels no matter if it failed one or not
 
         for (i, subscriber) in self.subscribers.iter() {
+            backlog = backlog.max(subscriber.len());
             match subscriber.try_send(data.clone()) {
                 Ok(true) => {}
                 Ok(false) => {
@@ -49,6 +56,7 @@ impl<T: Clone> AsyncBroadcasterInner<T> {
                 }
             }
         }
+        self.backlog.set(backlog as _);
         if fail_by_gone.len() + fail_by_full.len() == 0 {
             Ok(())
         } else {
@@ -57,6 +65,23 @@ impl<T: Clone> AsyncBroadcasterInner<T> {
     }
 }
 
+/// type name without module paths, e.g. "(RequestPlaceOrder, String)"
+fn short_type_name<T>() -> String {
+    let mut name = String::new();
+    let mut path = String::new();
+    for c in std::any::type_name::<T>().chars() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            path.push(c);
+            continue;
+        }
+        name.push_str(path.rsplit("::").next().unwrap_or_default());
+        path.clear();
+        name.push(c);
+    }
+    name.push_str(path.rsplit("::").next().unwrap_or_default());
+    name
+}
+
 #[derive(Clone)]
 pub struct AsyncBroadcaster<T: Clone> {
     inner: Arc<RwLock<AsyncBroadcasterInner<T>>>,
@@ -1,7 +1,13 @@
+use crate::config::FeedConfig;
+use crate::metrics::{market_feed_reconnects, FeedMetrics};
 use crate::signals::price_spread::DbRowSignalBestBidAskAcrossExchanges;
 use crate::strategy::broadcast::AsyncBroadcaster;
+use crate::strategy::feed_health::{FeedDeduplicator, FeedHealthMap};
+use crate::strategy::instrument_refresh::{DynamicInstrumentManager, InstrumentChange};
+use dashmap::mapref::entry::Entry;
 use dashmap::DashMap;
 use eyre::{bail, Result};
+use kanal::AsyncReceiver;
 use lib::signal::get_terminate_flag;
 use lib::warn::WarnManager;
 use num_traits::Zero;
@@ -16,6 +22,10 @@ use tracing::{error, info, warn};
 
 use crate::strategy::instrument::convert_asset_to_normalized_form;
 use trading_exchange::exchange::binance::market::BinanceMarketFeedConnection;
+use trading_exchange::exchange::bitget::market::BitGetMarketFeedConnection;
+use trading_exchange::exchange::bybit::market::ByBitMarketFeedConnection;
+use trading_exchange::exchange::coinbase::CoinbaseMarketFeedConnection;
+use trading_exchange::exchange::gateio::market::GateioMarketFeedConnection;
 use trading_exchange::exchange::get_instrument_loader_manager;
 use trading_exchange::exchange::hyperliquid::market::HyperliquidMarketFeedConnection;
 use trading_exchange::exchange::hyperliquid::model::exchange::request::HyperliquidChain;
@@ -24,8 +34,9 @@ use trading_exchange::exchange::hyperliquid::HyperliquidInfoClient;
 use trading_exchange::model::{InstrumentsMultiConfig, MarketFeedConfig, MarketFeedService};
 use trading_exchange::utils::future::interval;
 use trading_model::{
-    Asset, InstrumentSymbol, MarketEvent, MarketFeedDepthSelector, MarketFeedSelector, NetworkSelector, PriceEvent,
-    PriceType, SharedInstrumentManager, Time,
+    Asset, BookSweep, DerivativesContextEvent, InstrumentDetails, InstrumentSymbol, Intent, Level, MarketEvent,
+    MarketFeedDepthSelector, MarketFeedSelector, NetworkSelector, OrderBook, PriceEvent, PriceType, Quotes,
+    SharedInstrumentManager, Time,
 };
 use trading_model::{Exchange, InstrumentCode, TimeStampMs};
 
@@ -57,76 +68,271 @@ pub async fn get_instrument_manager(exchanges: Vec<Exchange>) -> Result<SharedIn
         .await
 }
 
-/// subscribe to bookticker on binance
+/// usdt perpetuals on binance futures, the instruments subscribed by `market_feed_binance`
+pub fn is_binance_feed_instrument(instrument: &InstrumentDetails) -> bool {
+    instrument.exchange == Exchange::BinanceFutures
+        && instrument.quote.asset.as_str() == "USDT"
+        && !instrument.ty.is_delivery()
+}
+/// usd perpetuals on hyperliquid, the instruments subscribed by `market_feed_hyper`
+pub fn is_hyper_feed_instrument(instrument: &InstrumentDetails) -> bool {
+    instrument.exchange == Exchange::Hyperliquid
+        && instrument.quote.asset.as_str() == "USD"
+        && !instrument.ty.is_delivery()
+}
+
+/// usdt perpetuals, or the usd pairs of coinbase, on the venues only feeding the fair price
+pub fn is_fair_price_feed_instrument(instrument: &InstrumentDetails) -> bool {
+    match instrument.exchange {
+        Exchange::Coinbase => instrument.quote.asset.as_str() == "USD",
+        Exchange::Bybit | Exchange::Bitget | Exchange::GateioPerpetual => {
+            instrument.quote.asset.as_str() == "USDT" && instrument.ty.is_perpetual()
+        }
+        _ => false,
+    }
+}
+
+/// subscribe to bookticker, trades, full-depth l2, liquidations and mark price on binance
 pub async fn market_feed_binance(
     tx: AsyncBroadcaster<MarketEvent>,
     instruments: Vec<InstrumentSymbol>,
+    health: Arc<FeedHealthMap>,
+    config: FeedConfig,
+    rx_changes: AsyncReceiver<InstrumentChange>,
 ) -> Result<(), eyre::Error> {
     // format base into symbol (spot)
     let market_feed_selectors = vec![
         MarketFeedSelector::BookTicker,
         MarketFeedSelector::Trade,
-        MarketFeedSelector::Depth(MarketFeedDepthSelector::depth_snapshot_l5()),
+        MarketFeedSelector::Depth(MarketFeedDepthSelector::depth_update()),
+        MarketFeedSelector::Liquidation,
+        MarketFeedSelector::DerivativesContext,
     ];
-    market_feed(tx, Exchange::BinanceFutures, market_feed_selectors, instruments).await
+    let subscription = FeedSubscription {
+        tx,
+        exchange: Exchange::BinanceFutures,
+        market_feed_selectors,
+        health,
+        config,
+    };
+    market_feed(subscription, instruments, rx_changes, is_binance_feed_instrument).await
 }
 
-/// subscribe to bookticker and l2 on hyper
+/// subscribe to candles, trades, l2 and asset context on hyper, which publishes snapshots of the top levels only
 pub async fn market_feed_hyper(
     tx: AsyncBroadcaster<MarketEvent>,
     base_assets: Vec<InstrumentSymbol>,
+    health: Arc<FeedHealthMap>,
+    config: FeedConfig,
+    rx_changes: AsyncReceiver<InstrumentChange>,
 ) -> Result<(), eyre::Error> {
     let market_feed_selectors = vec![
         MarketFeedSelector::OHLCVT,
-        MarketFeedSelector::Depth(MarketFeedDepthSelector::depth_snapshot_l5()),
+        MarketFeedSelector::Trade,
+        MarketFeedSelector::Depth(MarketFeedDepthSelector::depth_update()),
+        MarketFeedSelector::DerivativesContext,
     ];
-    market_feed(tx, Exchange::Hyperliquid, market_feed_selectors, base_assets).await
+    let subscription = FeedSubscription {
+        tx,
+        exchange: Exchange::Hyperliquid,
+        market_feed_selectors,
+        health,
+        config,
+    };
+    market_feed(subscription, base_assets, rx_changes, is_hyper_feed_instrument).await
 }
 
-/// subscribe
-pub async fn market_feed(
+/// subscribe to full-depth l2 and, where published, trades of a venue feeding the fair price.
+/// the connectors keep the books from a snapshot and the incremental updates, resynced on gaps
+pub async fn market_feed_fair_price_source(
     tx: AsyncBroadcaster<MarketEvent>,
     exchange: Exchange,
-    market_feed_selectors: Vec<MarketFeedSelector>,
     symbols: Vec<InstrumentSymbol>,
+    health: Arc<FeedHealthMap>,
+    config: FeedConfig,
+    rx_changes: AsyncReceiver<InstrumentChange>,
+) -> Result<(), eyre::Error> {
+    let mut market_feed_selectors = vec![MarketFeedSelector::Depth(MarketFeedDepthSelector::depth_update())];
+    // bitget publishes no trades
+    if exchange != Exchange::Bitget {
+        market_feed_selectors.push(MarketFeedSelector::Trade);
+    }
+    let subscription = FeedSubscription {
+        tx,
+        exchange,
+        market_feed_selectors,
+        health,
+        config,
+    };
+    market_feed(subscription, symbols, rx_changes, is_fair_price_feed_instrument).await
+}
+
+/// resources subscribed on one exchange, shared by all its connections
+pub struct FeedSubscription {
+    pub tx: AsyncBroadcaster<MarketEvent>,
+    pub exchange: Exchange,
+    pub market_feed_selectors: Vec<MarketFeedSelector>,
+    pub health: Arc<FeedHealthMap>,
+    pub config: FeedConfig,
+}
+impl FeedSubscription {
+    /// every chunk of symbols runs `config.redundancy` supervised connections
+    fn spawn_connections(&self, symbols: &[InstrumentSymbol], dedup: &Option<Arc<FeedDeduplicator>>) {
+        let symbols_per_connection = 30;
+        self.health.register(symbols);
+        for bases in symbols.chunks(symbols_per_connection) {
+            let mut feed_config = MarketFeedConfig::new(self.exchange);
+            feed_config.symbols = bases.to_vec();
+            feed_config.resources = self.market_feed_selectors.clone();
+            for connection in 0..self.config.redundancy.max(1) {
+                let supervised = SupervisedFeed {
+                    tx: self.tx.clone(),
+                    exchange: self.exchange,
+                    feed_config: feed_config.clone(),
+                    health: self.health.clone(),
+                    dedup: dedup.clone().map(|dedup| (dedup, connection)),
+                    reconnect_ms: self.config.reconnect_ms,
+                };
+                tokio::task::spawn_local(supervised.run());
+            }
+        }
+    }
+}
+
+/// subscribe, then subscribe the instruments listed later on new connections
+pub async fn market_feed(
+    subscription: FeedSubscription,
+    symbols: Vec<InstrumentSymbol>,
+    rx_changes: AsyncReceiver<InstrumentChange>,
+    accept: fn(&InstrumentDetails) -> bool,
 ) -> Result<(), eyre::Error> {
-    let symbols_per_connection = 30;
-    // generate list of configs used for subscription
-    let mut configs = vec![];
-    for bases in symbols.chunks(symbols_per_connection) {
-        let mut config = MarketFeedConfig::new(exchange);
-        config.symbols = bases.to_vec();
-        config.resources = market_feed_selectors.clone();
-        configs.push(config);
+    let exchange = subscription.exchange;
+    if !matches!(
+        exchange,
+        Exchange::BinanceSpot
+            | Exchange::BinanceFutures
+            | Exchange::Hyperliquid
+            | Exchange::Bybit
+            | Exchange::Bitget
+            | Exchange::Coinbase
+            | Exchange::GateioPerpetual
+    ) {
+        bail!("unrecognised exchange {}", exchange);
     }
+    let dedup = (subscription.config.redundancy > 1).then(|| Arc::new(FeedDeduplicator::new()));
     // joinset to subscribe feeds
     let set = LocalSet::new();
-    for config in configs {
-        match exchange {
-            Exchange::BinanceSpot | Exchange::BinanceFutures => {
-                let tx = tx.clone();
-                set.spawn_local(async move {
-                    let conn = BinanceMarketFeedConnection::new(config).await.unwrap();
-                    subscribe_market_feed_event_with_config(tx, conn).await.unwrap()
-                });
-            }
-            Exchange::Hyperliquid => {
-                let tx = tx.clone();
-                set.spawn_local(async move {
-                    let conn = HyperliquidMarketFeedConnection::new(config).await.unwrap();
-                    subscribe_market_feed_event_with_config(tx, conn).await.unwrap()
-                });
+    set.run_until(async {
+        subscription.spawn_connections(&symbols, &dedup);
+        info!("{} connection initialized", exchange);
+        while let Ok(change) = rx_changes.recv().await {
+            let mut changes = vec![change];
+            // a refresh emits its changes at once, subscribe them on the same connections
+            while let Ok(Some(change)) = rx_changes.try_recv() {
+                changes.push(change);
             }
-            _ => {
-                bail!("unrecognised exchange {}", exchange);
+            let added: Vec<InstrumentSymbol> = changes
+                .iter()
+                .filter_map(|change| match change {
+                    InstrumentChange::Added(instrument) if instrument.exchange == exchange && accept(instrument) => {
+                        Some(instrument.instrument_symbol.clone())
+                    }
+                    _ => None,
+                })
+                .collect();
+            if !added.is_empty() {
+                info!("subscribing {} new {} instruments", added.len(), exchange);
+                subscription.spawn_connections(&added, &dedup);
             }
         }
-    }
-    info!("{} connection initialized", exchange);
+    })
+    .await;
     set.await;
     Ok(())
 }
 
+/// one market feed connection, re-established when it errors or goes silent
+struct SupervisedFeed {
+    tx: AsyncBroadcaster<MarketEvent>,
+    exchange: Exchange,
+    feed_config: MarketFeedConfig,
+    health: Arc<FeedHealthMap>,
+    /// shared deduplicator and index of this connection, when running redundant connections
+    dedup: Option<(Arc<FeedDeduplicator>, usize)>,
+    reconnect_ms: i64,
+}
+impl SupervisedFeed {
+    async fn run(self) {
+        let mut backoff = Duration::from_secs(1);
+        let max_backoff = Duration::from_secs(60);
+        let mut first = true;
+        while !get_terminate_flag() {
+            if !first {
+                market_feed_reconnects(self.exchange).inc();
+                tokio::time::sleep(backoff).await;
+            }
+            first = false;
+            let result = match self.exchange {
+                Exchange::BinanceSpot | Exchange::BinanceFutures => {
+                    match BinanceMarketFeedConnection::new(self.feed_config.clone()).await {
+                        Ok(conn) => self.subscribe(conn).await,
+                        Err(err) => Err(err),
+                    }
+                }
+                Exchange::Hyperliquid => match HyperliquidMarketFeedConnection::new(self.feed_config.clone()).await {
+                    Ok(conn) => self.subscribe(conn).await,
+                    Err(err) => Err(err),
+                },
+                Exchange::Bybit => match ByBitMarketFeedConnection::new(self.feed_config.clone()).await {
+                    Ok(conn) => self.subscribe(conn).await,
+                    Err(err) => Err(err),
+                },
+                Exchange::Bitget => match BitGetMarketFeedConnection::new(&self.feed_config).await {
+                    Ok(conn) => self.subscribe(conn).await,
+                    Err(err) => Err(err),
+                },
+                Exchange::Coinbase => match CoinbaseMarketFeedConnection::new(self.feed_config.clone()).await {
+                    Ok(conn) => self.subscribe(conn).await,
+                    Err(err) => Err(err),
+                },
+                Exchange::GateioPerpetual => match GateioMarketFeedConnection::new(self.feed_config.clone()).await {
+                    Ok(conn) => self.subscribe(conn).await,
+                    Err(err) => Err(err),
+                },
+                _ => unreachable!(),
+            };
+            match result {
+                // the connection received messages before giving up, retry at once
+                Ok(()) => backoff = Duration::from_secs(1),
+                Err(err) => {
+                    error!("{} market feed connection failed, {err}", self.exchange);
+                    backoff = (backoff * 2).min(max_backoff);
+                }
+            }
+            if !get_terminate_flag() {
+                warn!(
+                    "reconnecting {} market feed of {} symbols in {:?}",
+                    self.exchange,
+                    self.feed_config.symbols.len(),
+                    backoff
+                );
+            }
+        }
+    }
+    async fn subscribe(&self, connection: impl MarketFeedService) -> Result<()> {
+        subscribe_market_feed_event_with_config(
+            self.tx.clone(),
+            self.exchange,
+            connection,
+            &self.feed_config.symbols,
+            &self.health,
+            self.dedup.as_ref().map(|(dedup, i)| (dedup.as_ref(), *i)),
+            self.reconnect_ms,
+        )
+        .await
+    }
+}
+
 /// get hyperliquid mark price from websocket, insert into the storage
 pub async fn hyperliquid_context(tx: AsyncBroadcaster<MarketEvent>) -> Result<(), eyre::Error> {
     // bases used just to limit the tx
@@ -209,15 +415,28 @@ pub async fn hyperliquid_context(tx: AsyncBroadcaster<MarketEvent>) -> Result<()
     }
 }
 pub static DETAILED_LOG: AtomicBool = AtomicBool::new(false);
-/// subscribe to the market feed and convert to events upon receiving quotes from websocket
+/// consecutive receive errors after which the connection is re-established
+const MAX_CONSECUTIVE_FEED_ERRORS: usize = 10;
+/// subscribe to the market feed and convert to events upon receiving quotes from websocket.
+/// returns when the connection should be re-established: nothing received or every symbol stale for `reconnect_ms`
+#[allow(clippy::too_many_arguments)]
 async fn subscribe_market_feed_event_with_config(
     tx: AsyncBroadcaster<MarketEvent>,
+    exchange: Exchange,
     mut connection: impl MarketFeedService,
+    symbols: &[InstrumentSymbol],
+    health: &FeedHealthMap,
+    dedup: Option<(&FeedDeduplicator, usize)>,
+    reconnect_ms: i64,
 ) -> Result<()> {
+    let mut feed_metrics = FeedMetrics::new(exchange);
     // periodically monitor signal
     let s_timeout = 10;
     let duration_timeout = Duration::from_secs(s_timeout);
     let mut warn_manager = WarnManager::new();
+    let connected = Time::now();
+    let mut last_received = connected;
+    let mut consecutive_errors = 0;
     // NOTE: set below to true when we want to check the hyperliquid snapshot frequency
     let test_frequency = true;
     let mut time_start = chrono::Utc::now();
@@ -227,10 +446,28 @@ async fn subscribe_market_feed_event_with_config(
         tokio::select! {
             _ = timeout => {
                 warn_manager.warn(format!("no feed received in the last {s_timeout}s"));
+                let now = Time::now();
+                if now.millis() - last_received.millis() > reconnect_ms {
+                    warn!("no {} feed received in the last {}ms", exchange, reconnect_ms);
+                    return Ok(());
+                }
+                if now.millis() - connected.millis() > reconnect_ms && health.all_stale(symbols) {
+                    warn!("every {} symbol of the connection is stale", exchange);
+                    return Ok(());
+                }
             }
             feed = connection.next() => {
                 match feed {
                     Ok(feed) => {
+                        last_received = Time::now();
+                        consecutive_errors = 0;
+                        if let Some((dedup, index)) = dedup {
+                            if !dedup.accept(index, &feed) {
+                                continue;
+                            }
+                        }
+                        feed_metrics.on_market_event(&feed);
+                        health.on_market_event(&feed);
                         match feed {
                             MarketEvent::Quotes(q) => {
                                 if test_frequency  {
@@ -282,6 +519,10 @@ async fn subscribe_market_feed_event_with_config(
                     }
                     Err(e) => {
                         error!("failed to receive feed, {e}");
+                        consecutive_errors += 1;
+                        if consecutive_errors >= MAX_CONSECUTIVE_FEED_ERRORS {
+                            return Err(e);
+                        }
                     }
                 }
             }
@@ -306,6 +547,10 @@ impl ActivenessMeta {
     pub fn is_active(&self, last: &ActivenessMeta) -> bool {
         self.updates_per_minute(last) > 55.0
     }
+    /// time of the last update
+    pub fn time(&self) -> TimeStampMs {
+        self.time
+    }
 }
 #[derive(Clone, Default)]
 pub struct LastPriceVolume {
@@ -362,18 +607,88 @@ impl LastPriceMap {
     }
 }
 
+/// latest derivatives context per instrument, inserted by price manager, read by signals
+pub struct LastDerivativesContextMap {
+    map: DashMap<InstrumentCode, DerivativesContextEvent>,
+}
+impl LastDerivativesContextMap {
+    pub fn new() -> Self {
+        Self {
+            map: Default::default(),
+        }
+    }
+    pub fn update(&self, context: DerivativesContextEvent) {
+        match self.map.entry(context.instrument.clone()) {
+            Entry::Occupied(mut entry) => entry.get_mut().merge(&context),
+            Entry::Vacant(entry) => {
+                entry.insert(context);
+            }
+        }
+    }
+    pub fn get(&self, instrument: &InstrumentCode) -> Option<DerivativesContextEvent> {
+        self.map.get(instrument).map(|x| x.value().clone())
+    }
+    // mark minus index (oracle on hyperliquid and drift)
+    pub fn get_basis(&self, instrument: &InstrumentCode) -> Option<f64> {
+        self.map.get(instrument).and_then(|x| x.basis())
+    }
+    pub fn get_premium(&self, instrument: &InstrumentCode) -> Option<f64> {
+        self.map.get(instrument).and_then(|x| x.premium())
+    }
+}
+
+/// full-depth order book per instrument, maintained by price manager from the quotes of the feeds.
+/// the connectors resync on gaps, so the quotes applied here are always consistent
+pub struct OrderBookMap {
+    map: DashMap<InstrumentCode, OrderBook>,
+}
+impl OrderBookMap {
+    pub fn new() -> Self {
+        Self {
+            map: Default::default(),
+        }
+    }
+    pub fn apply(&self, quotes: &Quotes) {
+        self.map
+            .entry(quotes.instrument.clone())
+            .or_insert_with(|| OrderBook::new(quotes.instrument.clone()))
+            .apply_quotes(quotes);
+    }
+    pub fn snapshot(&self, instrument: &InstrumentCode) -> Option<OrderBook> {
+        self.map.get(instrument).map(|x| x.value().clone())
+    }
+    pub fn top_levels(&self, instrument: &InstrumentCode, intent: Intent, n: usize) -> Vec<Level> {
+        self.map
+            .get(instrument)
+            .map(|x| x.top_levels(intent, n))
+            .unwrap_or_default()
+    }
+    pub fn mid_price(&self, instrument: &InstrumentCode) -> Option<f64> {
+        self.map.get(instrument).and_then(|x| x.mid_price())
+    }
+    /// a buy sweeps `Intent::Ask`, a sell sweeps `Intent::Bid`
+    pub fn depth_at_notional(&self, instrument: &InstrumentCode, intent: Intent, notional: f64) -> Option<BookSweep> {
+        self.map
+            .get(instrument)
+            .and_then(|x| x.depth_at_notional(intent, notional))
+    }
+    pub fn depth_at_size(&self, instrument: &InstrumentCode, intent: Intent, size: f64) -> Option<BookSweep> {
+        self.map.get(instrument).and_then(|x| x.depth_at_size(intent, size))
+    }
+}
+
 /// buffer that stores the latest price, then convert from feed to price update
 pub struct BuffferedPriceUpdateConverter {
     buffer: Arc<LastPriceMap>,
-    manager: SharedInstrumentManager,
+    manager: Arc<DynamicInstrumentManager>,
 }
 impl BuffferedPriceUpdateConverter {
-    pub fn new(buffer: Arc<LastPriceMap>, manager: SharedInstrumentManager) -> Self {
+    pub fn new(buffer: Arc<LastPriceMap>, manager: Arc<DynamicInstrumentManager>) -> Self {
         BuffferedPriceUpdateConverter { buffer, manager }
     }
     pub fn insert_price_event(&mut self, price: &PriceEvent) {
         let time = lib::utils::get_time_milliseconds();
-        let Some(instrument) = self.manager.get(&price.instrument) else {
+        let Some(instrument) = self.manager.load().get(&price.instrument).cloned() else {
             warn!("instrument not found in manager, {}", price.instrument);
             return;
         };
@@ -389,7 +704,7 @@ impl BuffferedPriceUpdateConverter {
     // this is to cater for cases like feeding best 5 bid mean
     pub fn insert_price(&mut self, instrument: &InstrumentCode, price_type: PriceType, price: f64, size: Option<f64>) {
         let time = lib::utils::get_time_milliseconds();
-        let Some(instrument) = self.manager.get(instrument) else {
+        let Some(instrument) = self.manager.load().get(instrument).cloned() else {
             warn!("instrument not found in manager, {}", instrument);
             return;
         };
@@ -405,7 +720,7 @@ impl BuffferedPriceUpdateConverter {
 }
 impl BuffferedPriceUpdateConverter {
     pub fn convert(&mut self, instrument: &InstrumentCode) -> Option<DbRowSignalBestBidAskAcrossExchanges> {
-        let Some(instrument) = self.manager.get(instrument) else {
+        let Some(instrument) = self.manager.load().get(instrument).cloned() else {
             warn!("instrument not found in manager, {}", instrument);
             return None;
         };
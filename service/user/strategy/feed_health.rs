@@ -0,0 +1,210 @@
+use std::mem::Discriminant;
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use eyre::Result;
+use tracing::{info, warn};
+
+use build::model::UserFeedHealth;
+use lib::signal::get_terminate_flag;
+use trading_exchange::utils::future::interval;
+use trading_model::{Asset, DurationMs, Exchange, InstrumentCode, InstrumentSymbol, MarketEvent, Time};
+
+use crate::metrics::market_feed_stale_instruments;
+use crate::strategy::instrument_refresh::DynamicInstrumentManager;
+
+/// how often the supervisor refreshes message rates and stale flags
+const FEED_HEALTH_INTERVAL_MS: DurationMs = 1_000;
+
+#[derive(Debug, Clone)]
+pub struct InstrumentFeedHealth {
+    pub instrument: InstrumentSymbol,
+    /// received time of the last event, NULL until the first one
+    pub last_update: Time,
+    pub messages_per_second: f64,
+    pub stale: bool,
+    /// set once the first update arrived, state changes before are not reported
+    live: bool,
+    window_start: Time,
+    window_messages: u64,
+}
+impl InstrumentFeedHealth {
+    fn new(instrument: InstrumentSymbol) -> Self {
+        Self {
+            instrument,
+            last_update: Time::NULL,
+            messages_per_second: 0.0,
+            // nothing received yet
+            stale: true,
+            live: false,
+            window_start: Time::now(),
+            window_messages: 0,
+        }
+    }
+    pub fn age_ms(&self, now: Time) -> i64 {
+        if self.last_update == Time::NULL {
+            return i64::MAX;
+        }
+        now.millis() - self.last_update.millis()
+    }
+}
+impl From<InstrumentFeedHealth> for UserFeedHealth {
+    fn from(value: InstrumentFeedHealth) -> Self {
+        let now = Time::now();
+        UserFeedHealth {
+            exchange: value.instrument.exchange.to_string(),
+            symbol: value.instrument.symbol.to_string(),
+            last_update: value.last_update.millis(),
+            age_ms: value.age_ms(now),
+            messages_per_second: value.messages_per_second,
+            stale: value.stale,
+        }
+    }
+}
+
+/// last update age and message rate per subscribed instrument, keyed by exchange and base asset like the price map.
+/// written by the feed connections, refreshed by the supervisor and read by the signals
+pub struct FeedHealthMap {
+    manager: Arc<DynamicInstrumentManager>,
+    map: DashMap<(Exchange, Asset), InstrumentFeedHealth>,
+    stale_ms: i64,
+}
+impl FeedHealthMap {
+    pub fn new(manager: Arc<DynamicInstrumentManager>, stale_ms: i64) -> Self {
+        Self {
+            manager,
+            map: Default::default(),
+            stale_ms,
+        }
+    }
+    /// instruments are stale until their first update
+    pub fn register(&self, symbols: &[InstrumentSymbol]) {
+        let manager = self.manager.load();
+        for symbol in symbols {
+            let Some(instrument) = manager.get_by_instrument_symbol(symbol) else {
+                continue;
+            };
+            self.map
+                .entry((instrument.exchange, instrument.base.asset.clone()))
+                .or_insert_with(|| InstrumentFeedHealth::new(symbol.clone()));
+        }
+    }
+    pub fn on_market_event(&self, event: &MarketEvent) {
+        let Some(instrument) = event.get_instrument() else {
+            return;
+        };
+        let manager = self.manager.load();
+        let Some(instrument) = manager.get(&instrument) else {
+            return;
+        };
+        let Some(mut health) = self.map.get_mut(&(instrument.exchange, instrument.base.asset.clone())) else {
+            return;
+        };
+        health.last_update = Time::now();
+        health.window_messages += 1;
+    }
+    /// instruments no feed registered are stale, a feed that never connected is not healthy
+    pub fn is_stale(&self, exchange: Exchange, asset: &Asset) -> bool {
+        self.map.get(&(exchange, asset.clone())).map_or(true, |x| x.stale)
+    }
+    /// true when none of the symbols received an update within the stale threshold
+    pub fn all_stale(&self, symbols: &[InstrumentSymbol]) -> bool {
+        let now = Time::now();
+        let manager = self.manager.load();
+        symbols.iter().all(|symbol| {
+            let Some(instrument) = manager.get_by_instrument_symbol(symbol) else {
+                return true;
+            };
+            self.map
+                .get(&(instrument.exchange, instrument.base.asset.clone()))
+                .map_or(true, |x| x.age_ms(now) > self.stale_ms)
+        })
+    }
+    pub fn values(&self) -> Vec<InstrumentFeedHealth> {
+        self.map.iter().map(|x| x.value().clone()).collect()
+    }
+    /// updates the message rates and stale flags, returns the instruments that changed state
+    fn refresh(&self, now: Time) -> Vec<InstrumentFeedHealth> {
+        let mut changed = vec![];
+        for mut health in self.map.iter_mut() {
+            let elapsed_ms = now.millis() - health.window_start.millis();
+            if elapsed_ms > 0 {
+                health.messages_per_second = health.window_messages as f64 * 1000.0 / elapsed_ms as f64;
+            }
+            health.window_start = now;
+            health.window_messages = 0;
+            let stale = health.age_ms(now) > self.stale_ms;
+            if stale != health.stale {
+                health.stale = stale;
+                if health.live {
+                    changed.push(health.clone());
+                }
+                health.live = true;
+            }
+        }
+        changed
+    }
+}
+
+/// drops the copies of an event received on redundant connections.
+/// an event is kept when it is newer than the last kept one of the same instrument and kind,
+/// or as recent and from the connection that delivered it first
+pub struct FeedDeduplicator {
+    last: DashMap<(InstrumentCode, Discriminant<MarketEvent>), (Time, usize)>,
+}
+impl FeedDeduplicator {
+    pub fn new() -> Self {
+        Self {
+            last: Default::default(),
+        }
+    }
+    pub fn accept(&self, connection: usize, event: &MarketEvent) -> bool {
+        let Some(instrument) = event.get_instrument() else {
+            return true;
+        };
+        let time = event.get_timestamp();
+        if time == Time::NULL {
+            return true;
+        }
+        let key = (instrument, std::mem::discriminant(event));
+        let mut last = self.last.entry(key).or_insert((Time::NULL, connection));
+        let (last_time, last_connection) = *last;
+        if time > last_time || (time == last_time && connection == last_connection) {
+            *last = (time, connection);
+            return true;
+        }
+        false
+    }
+}
+
+/// refreshes the feed health every second, logs state changes and exports the stale count
+pub struct FeedSupervisor {
+    pub health: Arc<FeedHealthMap>,
+    pub exchanges: Vec<Exchange>,
+}
+impl FeedSupervisor {
+    pub async fn run(self) -> Result<()> {
+        let mut interval = interval(FEED_HEALTH_INTERVAL_MS);
+        loop {
+            interval.tick().await;
+            if get_terminate_flag() {
+                return Ok(());
+            }
+            for health in self.health.refresh(Time::now()) {
+                if health.stale {
+                    warn!("market feed of {} is stale", health.instrument);
+                } else {
+                    info!("market feed of {} recovered", health.instrument);
+                }
+            }
+            let values = self.health.values();
+            for &exchange in self.exchanges.iter() {
+                let stale = values
+                    .iter()
+                    .filter(|x| x.instrument.exchange == exchange && x.stale)
+                    .count();
+                market_feed_stale_instruments(exchange).set(stale as f64);
+            }
+        }
+    }
+}
@@ -0,0 +1,12 @@
+pub mod order_placement;
+
+pub const STRATEGY_ID: u64 = 6;
+
+/// annualized funding yield needed to open a carry pair
+pub const ENTRY_YIELD_ANNUAL: f64 = 0.20;
+/// the pair is closed once the annualized yield of the held direction falls below this
+pub const EXIT_YIELD_ANNUAL: f64 = 0.05;
+/// notional of each leg in USD
+pub const LEG_NOTIONAL_USD: f64 = 25.0;
+/// maximum number of assets held at once
+pub const MAXIMUM_POSITION_COUNT: usize = 5;
@@ -0,0 +1,336 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use eyre::{Context, ContextCompat, Result};
+use gluesql::core::ast_builder::col;
+use gluesql_shared_sled_storage::SharedSledStorage;
+use kanal::{AsyncReceiver, AsyncSender};
+use tracing::{info, warn};
+
+use build::model::EnumErrorCode;
+use lib::gluesql::{Table, TableSelectItem};
+use lib::toolbox::CustomError;
+use trading_exchange::exchange::gen_order_cid;
+use trading_exchange::model::{gen_local_id, OrderLid, OrderType, PositionEffect, RequestPlaceOrder, UpdateOrder};
+use trading_model::{Asset, Exchange, InstrumentSymbol, SharedInstrumentDetails, Side, Symbol};
+
+use crate::db::gluesql::schema::ledger::LedgerQuery;
+use crate::db::gluesql::schema::DbRowLedger;
+use crate::execution::PlaceBatchOrders;
+use crate::signals::funding::FundingCarrySignal;
+use crate::strategy::data_factory::LastDerivativesContextMap;
+use crate::strategy::funding_carry::{
+    ENTRY_YIELD_ANNUAL, EXIT_YIELD_ANNUAL, LEG_NOTIONAL_USD, MAXIMUM_POSITION_COUNT, STRATEGY_ID,
+};
+use crate::strategy::instrument::{convert_asset_to_instrument, convert_asset_to_normalized_form};
+use crate::strategy::instrument_refresh::DynamicInstrumentManager;
+use crate::strategy::{StrategyStatus, StrategyStatusMap};
+
+/// sizes below this are flat
+const FLAT_SIZE: f64 = 1e-9;
+
+/// carry pair held by the strategy, sized by the fills of its legs
+#[derive(Debug, Clone)]
+struct CarryPair {
+    long_exchange: Exchange,
+    short_exchange: Exchange,
+    /// filled size held on the long venue
+    long_size: f64,
+    /// filled size held on the short venue
+    short_size: f64,
+    /// shared by the orders of both legs, groups their PnL
+    event_id: u64,
+    /// orders of the pair that are not dead yet
+    live_orders: HashSet<OrderLid>,
+    /// the close legs were sent
+    closing: bool,
+}
+impl CarryPair {
+    fn is_flat(&self) -> bool {
+        self.long_size.abs() < FLAT_SIZE && self.short_size.abs() < FLAT_SIZE
+    }
+    /// one leg is held without the other, e.g. one open order was rejected
+    fn is_unhedged(&self) -> bool {
+        (self.long_size.abs() < FLAT_SIZE) != (self.short_size.abs() < FLAT_SIZE)
+    }
+}
+
+/// opens a pair of market orders when the funding yield between the venues crosses the entry threshold,
+/// and closes it once the yield of the held direction drops below the exit threshold
+pub struct FundingCarryOrderPlacement {
+    pub rx: AsyncReceiver<FundingCarrySignal>,
+    pub rx_update: AsyncReceiver<UpdateOrder>,
+    pub tx_order: AsyncSender<PlaceBatchOrders>,
+    pub instruments: Arc<DynamicInstrumentManager>,
+    pub derivatives_map: Arc<LastDerivativesContextMap>,
+    pub strategy_status: Arc<StrategyStatusMap>,
+    pub ledger_table: Table<SharedSledStorage, DbRowLedger>,
+    pairs: HashMap<Asset, CarryPair>,
+    /// filled size seen so far of the live orders
+    filled: HashMap<OrderLid, f64>,
+}
+
+impl FundingCarryOrderPlacement {
+    pub fn new(
+        rx: AsyncReceiver<FundingCarrySignal>,
+        rx_update: AsyncReceiver<UpdateOrder>,
+        tx_order: AsyncSender<PlaceBatchOrders>,
+        instruments: Arc<DynamicInstrumentManager>,
+        derivatives_map: Arc<LastDerivativesContextMap>,
+        strategy_status: Arc<StrategyStatusMap>,
+        ledger_table: Table<SharedSledStorage, DbRowLedger>,
+    ) -> Self {
+        Self {
+            rx,
+            rx_update,
+            tx_order,
+            instruments,
+            derivatives_map,
+            strategy_status,
+            ledger_table,
+            pairs: HashMap::new(),
+            filled: HashMap::new(),
+        }
+    }
+    /// rebuilds the pairs from the open lots of the ledger, the legs of a pair share the event id
+    pub async fn load(&mut self) -> Result<()> {
+        let query = LedgerQuery {
+            strategy_id: Some(STRATEGY_ID as _),
+            ..LedgerQuery::default()
+        };
+        let lots = self
+            .ledger_table
+            .select(Some(query.filter().and(col("closed_volume").lt(col("volume")))), "id")
+            .await?;
+        let manager = self.instruments.load();
+        for lot in lots {
+            let exchange = Exchange::try_from(lot.exchange_id).unwrap_or(Exchange::Null);
+            let symbol = InstrumentSymbol::new(exchange, unsafe { Symbol::from_hash(lot.symbol_id) });
+            let Some(instrument) = manager.get(&symbol) else {
+                warn!("funding carry: no instrument for the open lot {} of {}", lot.id, symbol);
+                continue;
+            };
+            let asset = convert_asset_to_normalized_form(instrument.base.asset.clone());
+            let pair = self.pairs.entry(asset).or_insert_with(|| CarryPair {
+                long_exchange: Exchange::Null,
+                short_exchange: Exchange::Null,
+                long_size: 0.0,
+                short_size: 0.0,
+                event_id: lot.event_id,
+                live_orders: HashSet::new(),
+                closing: false,
+            });
+            match lot.side() {
+                Side::Buy => {
+                    pair.long_exchange = exchange;
+                    pair.long_size += lot.open_volume();
+                }
+                _ => {
+                    pair.short_exchange = exchange;
+                    pair.short_size += lot.open_volume();
+                }
+            }
+        }
+        for (asset, pair) in &self.pairs {
+            info!("funding carry loaded: asset={} pair={:?}", asset, pair);
+        }
+        Ok(())
+    }
+    fn instrument(&self, exchange: Exchange, asset: &Asset) -> Result<SharedInstrumentDetails> {
+        convert_asset_to_instrument(&self.instruments.load(), exchange, asset).with_context(|| {
+            CustomError::new(
+                EnumErrorCode::NotFound,
+                format!("symbol not found for {} {}", exchange, asset),
+            )
+        })
+    }
+    fn leg(
+        &self,
+        instrument: &SharedInstrumentDetails,
+        side: Side,
+        size: f64,
+        effect: PositionEffect,
+        event_id: u64,
+    ) -> Result<RequestPlaceOrder> {
+        let price = self
+            .derivatives_map
+            .get(&instrument.code_symbol)
+            .and_then(|x| x.mark_price)
+            .with_context(|| format!("no mark price for {}", instrument.code_symbol))?;
+        Ok(RequestPlaceOrder {
+            instrument: instrument.code_symbol.clone(),
+            order_lid: gen_local_id(),
+            order_cid: gen_order_cid(instrument.exchange),
+            side,
+            price,
+            size,
+            ty: OrderType::Market,
+            effect,
+            strategy_id: STRATEGY_ID as _,
+            event_id,
+            ..RequestPlaceOrder::empty()
+        })
+    }
+    async fn open_pair(&mut self, signal: &FundingCarrySignal) -> Result<()> {
+        let long = self.instrument(signal.long_exchange, &signal.asset)?;
+        let short = self.instrument(signal.short_exchange, &signal.asset)?;
+        let price = self
+            .derivatives_map
+            .get(&long.code_symbol)
+            .and_then(|x| x.mark_price)
+            .with_context(|| format!("no mark price for {}", long.code_symbol))?;
+        let mut size = LEG_NOTIONAL_USD / price;
+        size = long.size.round(size);
+        size = short.size.round(size);
+        if size <= 0.0 {
+            warn!("carry size of {} rounds to zero", signal.asset);
+            return Ok(());
+        }
+        let event_id = signal.datetime.nanos() as u64;
+        let legs = vec![
+            self.leg(&long, Side::Buy, size, PositionEffect::Open, event_id)?,
+            self.leg(&short, Side::Sell, size, PositionEffect::Open, event_id)?,
+        ];
+        info!(
+            "opening funding carry: asset={} long={} short={} size={} yield_annual={:.4}",
+            signal.asset, signal.long_exchange, signal.short_exchange, size, signal.yield_annual
+        );
+        let live_orders = legs.iter().map(|x| x.order_lid.clone()).collect();
+        self.tx_order
+            .send(PlaceBatchOrders::new(signal.asset.clone(), legs))
+            .await
+            .context("failed to send carry pair")?;
+        // held sizes follow the fills, the pair is dropped again if neither leg fills
+        self.pairs.insert(
+            signal.asset.clone(),
+            CarryPair {
+                long_exchange: signal.long_exchange,
+                short_exchange: signal.short_exchange,
+                long_size: 0.0,
+                short_size: 0.0,
+                event_id,
+                live_orders,
+                closing: false,
+            },
+        );
+        Ok(())
+    }
+    async fn close_pair(&mut self, asset: &Asset) -> Result<()> {
+        let Some(pair) = self.pairs.get(asset).cloned() else {
+            return Ok(());
+        };
+        if pair.closing || !pair.live_orders.is_empty() {
+            return Ok(());
+        }
+        let mut legs = vec![];
+        if pair.long_size >= FLAT_SIZE {
+            let long = self.instrument(pair.long_exchange, asset)?;
+            legs.push(self.leg(&long, Side::Sell, pair.long_size, PositionEffect::Close, pair.event_id)?);
+        }
+        if pair.short_size >= FLAT_SIZE {
+            let short = self.instrument(pair.short_exchange, asset)?;
+            legs.push(self.leg(&short, Side::Buy, pair.short_size, PositionEffect::Close, pair.event_id)?);
+        }
+        info!("closing funding carry: asset={} pair={:?}", asset, pair);
+        let live_orders: HashSet<_> = legs.iter().map(|x| x.order_lid.clone()).collect();
+        self.tx_order
+            .send(PlaceBatchOrders::new(asset.clone(), legs))
+            .await
+            .context("failed to send carry pair")?;
+        // the pair is dropped once the close legs filled
+        if let Some(pair) = self.pairs.get_mut(asset) {
+            pair.live_orders = live_orders;
+            pair.closing = true;
+        }
+        Ok(())
+    }
+    async fn handle_signal(&mut self, signal: FundingCarrySignal) -> Result<()> {
+        let status = self.strategy_status.get(STRATEGY_ID as _);
+        if status == Some(StrategyStatus::Disabled) {
+            let assets: Vec<_> = self.pairs.keys().cloned().collect();
+            for asset in assets {
+                self.close_pair(&asset).await?;
+            }
+            return Ok(());
+        }
+        match self.pairs.get(&signal.asset) {
+            Some(pair) => {
+                // yield of the held direction, negative once the venues swapped
+                let held_yield = if pair.long_exchange == signal.long_exchange {
+                    signal.yield_annual
+                } else {
+                    -signal.yield_annual
+                };
+                if held_yield < EXIT_YIELD_ANNUAL || pair.is_unhedged() {
+                    self.close_pair(&signal.asset).await?;
+                }
+            }
+            None => {
+                if status == Some(StrategyStatus::Enabled)
+                    && signal.yield_annual >= ENTRY_YIELD_ANNUAL
+                    && self.pairs.len() < MAXIMUM_POSITION_COUNT
+                {
+                    self.open_pair(&signal).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+    /// applies the fills of the legs to the held sizes and forgets the pair once it is flat
+    fn handle_update(&mut self, update: UpdateOrder) {
+        if update.strategy_id != STRATEGY_ID {
+            return;
+        }
+        let Some((asset, pair)) = self.pairs.iter_mut().find(|(_, x)| x.event_id == update.event_id) else {
+            return;
+        };
+        let exchange = update.instrument.get_exchange().unwrap_or(Exchange::Null);
+        let seen = self.filled.get(&update.local_id).copied().unwrap_or_default();
+        let delta = update.filled_size - seen;
+        if delta > 0.0 {
+            self.filled.insert(update.local_id.clone(), update.filled_size);
+            let sign = if update.side == Side::Buy { 1.0 } else { -1.0 };
+            if exchange == pair.long_exchange {
+                pair.long_size += delta * sign;
+            } else if exchange == pair.short_exchange {
+                pair.short_size -= delta * sign;
+            }
+        }
+        if !update.status.is_dead() {
+            return;
+        }
+        self.filled.remove(&update.local_id);
+        pair.live_orders.remove(&update.local_id);
+        if !pair.live_orders.is_empty() {
+            return;
+        }
+        if pair.is_flat() {
+            info!("funding carry closed: asset={}", asset);
+            let asset = asset.clone();
+            self.pairs.remove(&asset);
+        } else if pair.closing {
+            // the close legs died without filling, they are sent again on the next signal
+            warn!("funding carry close incomplete: asset={} pair={:?}", asset, pair);
+            pair.closing = false;
+        }
+    }
+    pub async fn run(&mut self) -> Result<()> {
+        self.load().await?;
+        loop {
+            tokio::select! {
+                signal = self.rx.recv() => {
+                    let Ok(signal) = signal else {
+                        break;
+                    };
+                    if let Err(err) = self.handle_signal(signal).await {
+                        warn!("funding carry: {err}");
+                    }
+                }
+                Ok(update) = self.rx_update.recv() => {
+                    self.handle_update(update);
+                }
+            }
+        }
+        Ok(())
+    }
+}
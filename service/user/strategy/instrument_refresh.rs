@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use dashmap::DashSet;
+use eyre::{bail, Result};
+use parking_lot::RwLock;
+use tracing::{info, warn};
+
+use lib::signal::get_terminate_flag;
+use trading_exchange::exchange::get_instrument_loader_manager;
+use trading_exchange::model::InstrumentsMultiConfig;
+use trading_exchange::utils::future::interval;
+use trading_model::{
+    DurationMs, Exchange, InstrumentDetails, InstrumentManager, InstrumentStatus, InstrumentSymbol, NetworkSelector,
+    SharedInstrumentDetails, SharedInstrumentManager,
+};
+
+use crate::metrics::instrument_changes;
+use crate::strategy::broadcast::AsyncBroadcaster;
+
+/// how often the instruments are reloaded from the exchanges
+const INSTRUMENT_REFRESH_MS: DurationMs = 300_000;
+/// a reload removing more than this fraction of the tradable instruments of a venue is held back,
+/// a venue returning a partial or empty list must not delist everything held there
+const MAXIMUM_REMOVED_FRACTION: f64 = 0.2;
+
+/// difference between two loads of the instruments
+#[derive(Debug, Clone)]
+pub enum InstrumentChange {
+    /// newly listed, or open for trading again
+    Added(SharedInstrumentDetails),
+    /// delisted, or no longer open for trading
+    Removed(SharedInstrumentDetails),
+    /// precision or limits changed
+    Changed {
+        previous: SharedInstrumentDetails,
+        current: SharedInstrumentDetails,
+    },
+}
+impl InstrumentChange {
+    pub fn instrument(&self) -> &SharedInstrumentDetails {
+        match self {
+            Self::Added(instrument) | Self::Removed(instrument) => instrument,
+            Self::Changed { current, .. } => current,
+        }
+    }
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Added(_) => "added",
+            Self::Removed(_) => "removed",
+            Self::Changed { .. } => "changed",
+        }
+    }
+}
+
+fn is_tradable(instrument: &InstrumentDetails) -> bool {
+    instrument.status == InstrumentStatus::Open
+}
+fn is_parameter_changed(previous: &InstrumentDetails, current: &InstrumentDetails) -> bool {
+    previous.size != current.size
+        || previous.price != current.price
+        || previous.lot != current.lot
+        || previous.tick != current.tick
+        || previous.max_leverage != current.max_leverage
+        || previous.amount_limits_min_notional != current.amount_limits_min_notional
+}
+
+/// added, removed and changed instruments between two loads
+pub fn diff_instruments(previous: &InstrumentManager, current: &InstrumentManager) -> Vec<InstrumentChange> {
+    let previous: HashMap<&InstrumentSymbol, &SharedInstrumentDetails> = previous
+        .iter()
+        .filter(|x| is_tradable(x))
+        .map(|x| (&x.instrument_symbol, x))
+        .collect();
+    let current: HashMap<&InstrumentSymbol, &SharedInstrumentDetails> = current
+        .iter()
+        .filter(|x| is_tradable(x))
+        .map(|x| (&x.instrument_symbol, x))
+        .collect();
+    let mut changes = vec![];
+    for (symbol, &instrument) in current.iter() {
+        match previous.get(symbol) {
+            None => changes.push(InstrumentChange::Added(instrument.clone())),
+            Some(&previous) if is_parameter_changed(previous, instrument) => changes.push(InstrumentChange::Changed {
+                previous: previous.clone(),
+                current: instrument.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for (symbol, &instrument) in previous.iter() {
+        if !current.contains_key(symbol) {
+            changes.push(InstrumentChange::Removed(instrument.clone()));
+        }
+    }
+    changes
+}
+
+/// fails when the reload removed too many of the tradable instruments of a venue
+pub fn check_reload(previous: &InstrumentManager, changes: &[InstrumentChange]) -> Result<()> {
+    let mut tradable: HashMap<Exchange, usize> = HashMap::new();
+    for instrument in previous.iter().filter(|x| is_tradable(x)) {
+        *tradable.entry(instrument.exchange).or_default() += 1;
+    }
+    let mut removed: HashMap<Exchange, usize> = HashMap::new();
+    for change in changes {
+        if let InstrumentChange::Removed(instrument) = change {
+            *removed.entry(instrument.exchange).or_default() += 1;
+        }
+    }
+    for (exchange, removed) in removed {
+        let tradable = tradable.get(&exchange).copied().unwrap_or_default();
+        if removed as f64 > tradable as f64 * MAXIMUM_REMOVED_FRACTION {
+            bail!(
+                "reload of {} removes {} of {} tradable instruments, keeping the previous ones",
+                exchange,
+                removed,
+                tradable
+            );
+        }
+    }
+    Ok(())
+}
+
+/// instruments of the running service, swapped as a whole by the refresher.
+/// readers take a snapshot with `load` and keep it for the duration of one computation
+#[derive(Debug)]
+pub struct DynamicInstrumentManager {
+    current: RwLock<SharedInstrumentManager>,
+    /// removed since the service started, no new position is opened on them
+    delisted: DashSet<InstrumentSymbol>,
+}
+impl DynamicInstrumentManager {
+    pub fn new(manager: SharedInstrumentManager) -> Self {
+        Self {
+            current: RwLock::new(manager),
+            delisted: Default::default(),
+        }
+    }
+    pub fn load(&self) -> SharedInstrumentManager {
+        self.current.read().clone()
+    }
+    pub fn is_delisted(&self, symbol: &InstrumentSymbol) -> bool {
+        self.delisted.contains(symbol)
+    }
+    fn swap(&self, manager: SharedInstrumentManager, changes: &[InstrumentChange]) {
+        for change in changes {
+            let symbol = &change.instrument().instrument_symbol;
+            match change {
+                InstrumentChange::Added(_) => {
+                    self.delisted.remove(symbol);
+                }
+                InstrumentChange::Removed(_) => {
+                    self.delisted.insert(symbol.clone());
+                }
+                InstrumentChange::Changed { .. } => {}
+            }
+        }
+        *self.current.write() = manager;
+    }
+}
+
+/// periodically reloads the instruments, swaps them into the manager and broadcasts the changes
+pub struct InstrumentRefresher {
+    pub instruments: Arc<DynamicInstrumentManager>,
+    pub exchanges: Vec<Exchange>,
+    pub tx: AsyncBroadcaster<InstrumentChange>,
+}
+impl InstrumentRefresher {
+    pub async fn run(self) -> Result<()> {
+        let mut interval = interval(INSTRUMENT_REFRESH_MS);
+        // the instruments were just loaded on startup
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if get_terminate_flag() {
+                return Ok(());
+            }
+            if let Err(err) = self.refresh().await {
+                warn!("failed to refresh instruments: {err}");
+            }
+        }
+    }
+    async fn refresh(&self) -> Result<()> {
+        let manager = get_instrument_loader_manager()
+            .reload_instruments_multi(&InstrumentsMultiConfig {
+                network: NetworkSelector::mainnet(),
+                exchanges: self.exchanges.clone(),
+            })
+            .await?;
+        let previous = self.instruments.load();
+        let changes = diff_instruments(&previous, &manager);
+        if changes.is_empty() {
+            return Ok(());
+        }
+        check_reload(&previous, &changes)?;
+        self.instruments.swap(manager, &changes);
+        for change in changes {
+            let instrument = change.instrument();
+            info!("instrument {} {}", instrument.instrument_symbol, change.kind());
+            instrument_changes(instrument.exchange, change.kind()).inc();
+            if let Err(err) = self.tx.broadcast(change) {
+                warn!("failed to broadcast instrument change: {err:?}");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trading_model::math::size::Size;
+    use trading_model::{AssetInfo, InstrumentDetailsBuilder};
+
+    fn instrument(symbol: &str, decimals: i32, status: InstrumentStatus) -> InstrumentDetails {
+        InstrumentDetailsBuilder {
+            exchange: Exchange::Hyperliquid,
+            symbol: symbol.into(),
+            base: AssetInfo::new_one(symbol.into()),
+            quote: AssetInfo::new_one("USD".into()),
+            size: Size::from_decimals(decimals),
+            status,
+            ..InstrumentDetailsBuilder::empty()
+        }
+        .build()
+    }
+
+    #[test]
+    fn test_diff_instruments() {
+        let previous = InstrumentManager::from_instruments([
+            instrument("BTC", 5, InstrumentStatus::Open),
+            instrument("ETH", 4, InstrumentStatus::Open),
+            instrument("SOL", 2, InstrumentStatus::Open),
+        ]);
+        let current = InstrumentManager::from_instruments([
+            instrument("BTC", 5, InstrumentStatus::Open),
+            instrument("ETH", 3, InstrumentStatus::Open),
+            instrument("SOL", 2, InstrumentStatus::Close),
+            instrument("HYPE", 2, InstrumentStatus::Open),
+        ]);
+        let mut changes: Vec<_> = diff_instruments(&previous, &current)
+            .iter()
+            .map(|x| (x.instrument().symbol.to_string(), x.kind()))
+            .collect();
+        changes.sort();
+        assert_eq!(
+            changes,
+            vec![
+                ("ETH".to_string(), "changed"),
+                ("HYPE".to_string(), "added"),
+                ("SOL".to_string(), "removed"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_reload() {
+        let previous = InstrumentManager::from_instruments(
+            (0..10).map(|i| instrument(&format!("A{i}"), 2, InstrumentStatus::Open)),
+        );
+        let one_removed = InstrumentManager::from_instruments(
+            (1..10).map(|i| instrument(&format!("A{i}"), 2, InstrumentStatus::Open)),
+        );
+        assert!(check_reload(&previous, &diff_instruments(&previous, &one_removed)).is_ok());
+        let half_removed = InstrumentManager::from_instruments(
+            (5..10).map(|i| instrument(&format!("A{i}"), 2, InstrumentStatus::Open)),
+        );
+        assert!(check_reload(&previous, &diff_instruments(&previous, &half_removed)).is_err());
+        let empty = InstrumentManager::new();
+        assert!(check_reload(&previous, &diff_instruments(&previous, &empty)).is_err());
+    }
+}
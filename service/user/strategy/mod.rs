@@ -6,7 +6,13 @@ use crate::db::gluesql::schema::common::StrategyId;
 
 pub mod broadcast;
 pub mod data_factory;
+/// staleness and message rates of the market feeds
+pub mod feed_health;
+/// long the low-funding leg, short the high-funding leg
+pub mod funding_carry;
 pub mod instrument;
+/// periodic reload of listings, delistings and precision changes
+pub mod instrument_refresh;
 pub mod manual_trade;
 /// constants
 pub mod strategy_constants;
@@ -20,7 +26,6 @@ pub mod strategy_two;
 pub mod strategy_two_and_three;
 /// hyper bid cross hyper mark
 pub mod strategy_zero;
-pub mod table_limiter;
 
 #[derive(Debug, Display, PartialEq, EnumString, Clone, Copy, FromRepr)]
 #[repr(u8)]
@@ -2,9 +2,15 @@
 pub const DIFFERENCE_THRESHOLD_BP_HIGH: f64 = 17.0;
 pub const DIFFERENCE_THRESHOLD_BP_CRITCAL: f64 = 20.0;
 pub const DIFFERENCE_COOLDOWN_MS: u64 = 2000;
+/// notional the difference detector prices hyperliquid at, net of depth and fees
+pub const DIFFERENCE_EXECUTABLE_NOTIONAL: f64 = 15.0;
 /// change
 pub const CHANGE_THRESHOLD_BP_HIGH: f64 = 17.0;
 pub const CHANGE_THRESHOLD_BP_CRITICAL: f64 = 20.0;
 pub const CHANGE_COOLDOWN_MS: u64 = 3000;
+/// trade flow
+pub const TRADE_FLOW_IMBALANCE_HIGH: f64 = 0.6;
+pub const TRADE_FLOW_IMBALANCE_CRITICAL: f64 = 0.8;
+pub const TRADE_FLOW_COOLDOWN_MS: u64 = 3000;
 
 pub const CLOSE_POSITION_LIMIT_PROFIT_RATIO: f64 = 1.0005; // 5bp
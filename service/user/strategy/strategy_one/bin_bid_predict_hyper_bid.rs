@@ -1,5 +1,8 @@
 use crate::db::gluesql::schema::DbRowSymbolFlag;
 use crate::events::price_change_and_diff::DbRowEventPriceChangeAndDiff;
+use crate::metrics::strategy_signals;
+use crate::signals::executable_price::ExecutablePriceModel;
+use crate::signals::fair_price::FairPriceMap;
 use crate::signals::price_change::{BestBidAskAcrossExchangesToChangeConverter, DbRowSignalPriceChange};
 use crate::signals::price_difference::{BinHyperDifferenceConverter, DbRowSignalPriceDifference};
 use crate::signals::price_spread::{DbRowSignalBestBidAskAcrossExchanges, WorktableSignalBestBidAskAcrossExchanges};
@@ -56,6 +59,7 @@ impl<T: GStore + GStoreMut + Clone> BinPredictHyperStrategy<T> {
         if let Err(e) = self.table_event.insert(event.clone()).await {
             eyre::bail!("insert, {e}")
         }
+        strategy_signals(1).inc();
         if let Err(err) = self.tx_event.broadcast(event) {
             tracing::error!("new order aborted, order placement is busy: {}", err);
         }
@@ -190,6 +194,10 @@ pub struct DetectSignalPriceDifference<VOLATILE: GStore + GStoreMut + Clone, PER
     pub symbol_flags: Table<PERSISTENT, DbRowSymbolFlag>,
     pub symbol_flags_cache: HashMap<Asset, bool>,
     pub strategy_status: Arc<StrategyStatusMap>,
+    /// the hyper bid is compared to the mark at the price an order would actually get
+    pub executable: ExecutablePriceModel,
+    /// the hyper bid is measured against the fair price, not the hyper mark
+    pub fair_price_map: Arc<FairPriceMap>,
 }
 
 impl<VOLATILE: GStore + GStoreMut + Clone, PERSISTENT: GStore + GStoreMut + Clone>
@@ -254,7 +262,14 @@ impl<VOLATILE: GStore + GStoreMut + Clone, PERSISTENT: GStore + GStoreMut + Clon
                     if !symbol_flag {
                         continue;
                     }
-                    let Some(mut signal) = price_difference_signal_converter.convert_price_to_difference(&price_update) else {
+                    let Some(price_update) = self.executable.net_of_depth(&price_update) else {
+                        continue;
+                    };
+                    // no difference is measured without a recent fair price
+                    let Some(fair) = self.fair_price_map.fresh(&asset, price_update.datetime) else {
+                        continue;
+                    };
+                    let Some(mut signal) = price_difference_signal_converter.convert_price_to_difference(&price_update, fair) else {
                         continue;
                     };
                     signal.id = self.table_price_diff_signal.next_index();
@@ -19,6 +19,7 @@ use tracing::{debug, error, info, warn};
 use trading_exchange::exchange::gen_order_cid;
 
 use crate::db::worktable::order_manager::OrderManager;
+use crate::strategy::instrument_refresh::DynamicInstrumentManager;
 use crate::strategy::strategy_constants::CLOSE_POSITION_LIMIT_PROFIT_RATIO;
 use crate::strategy::strategy_one::STRATEGY_ID;
 use trading_exchange::exchange::hyperliquid::utils::uuid_to_hex_string;
@@ -28,8 +29,8 @@ use trading_exchange::model::{
 };
 use trading_exchange::utils::future::interval;
 use trading_model::{
-    now, Asset, Exchange, InstrumentCode, SharedInstrumentManager, Side, Symbol, Time, TimeStampNs,
-    NANOSECONDS_PER_MILLISECOND, NANOSECONDS_PER_SECOND,
+    now, Asset, Exchange, InstrumentCode, Side, Symbol, Time, TimeStampNs, NANOSECONDS_PER_MILLISECOND,
+    NANOSECONDS_PER_SECOND,
 };
 
 pub struct StrategyOneOrderPlacement {
@@ -51,7 +52,7 @@ pub struct StrategyOneOrderPlacement {
     pub table_event: Table<SharedMemoryStorage, DbRowEventPriceChangeAndDiff>,
     /// balance request (do not edit the balance, just get balance and check the event status)
     pub balance_manager: BalanceManager,
-    pub instruments: SharedInstrumentManager,
+    pub instruments: Arc<DynamicInstrumentManager>,
 }
 
 impl StrategyOneOrderPlacement {
@@ -117,7 +118,11 @@ impl StrategyOneOrderPlacement {
             let order_value_usd = order_price * order_size;
             // check if we have enough fund with balance manager
 
-            let balance = match self.balance_manager.get_balance(Exchange::Hyperliquid).await {
+            let balance = match self
+                .balance_manager
+                .get_strategy_balance(STRATEGY_ID as _, Exchange::Hyperliquid)
+                .await
+            {
                 Err(e) => {
                     error!("balance request failure: {e}");
                     return Ok(None);
@@ -260,7 +265,12 @@ impl StrategyOneOrderPlacement {
                 warn!("failed cancelling order: {:?}", err);
             }
             if limit_to_market {
-                let ins = self.instruments.get(&(Exchange::Hyperliquid, order.symbol())).unwrap();
+                let ins = self
+                    .instruments
+                    .load()
+                    .get(&(Exchange::Hyperliquid, order.symbol()))
+                    .cloned()
+                    .unwrap();
                 let request = RequestPlaceOrder {
                     instrument: InstrumentCode::from_symbol(Exchange::Hyperliquid, order.symbol()),
                     order_lid: gen_local_id(),
@@ -385,7 +395,7 @@ pub struct StrategyOneResponseHandler {
     pub best_bid_ask: Arc<RwLock<HashMap<Asset, DbRowPriceVolume>>>,
     // update the order according to the update status, by getting event ID from the live order table row
     pub table_event: Table<SharedMemoryStorage, DbRowEventPriceChangeAndDiff>,
-    pub instruments: SharedInstrumentManager,
+    pub instruments: Arc<DynamicInstrumentManager>,
 }
 
 impl StrategyOneResponseHandler {
@@ -522,7 +532,7 @@ impl StrategyOneResponseHandler {
     ) -> eyre::Result<RequestPlaceOrder> {
         let exchange = Exchange::Hyperliquid;
         let symbol = opening_order_row_view.symbol();
-        let ins = self.instruments.get(&(exchange, symbol)).unwrap();
+        let ins = self.instruments.load().get(&(exchange, symbol)).cloned().unwrap();
 
         // we want to gain profit
         let profit_goal = CLOSE_POSITION_LIMIT_PROFIT_RATIO;
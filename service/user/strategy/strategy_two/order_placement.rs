@@ -15,12 +15,13 @@ use trading_exchange::exchange::gen_order_cid;
 use trading_exchange::model::{
     gen_local_id, ExecutionRequest, OrderStatus, OrderType, PositionEffect, RequestCancelOrder, RequestPlaceOrder,
 };
-use trading_model::{Exchange, InstrumentCode, SharedInstrumentManager, Side, Time};
+use trading_model::{Exchange, InstrumentCode, Side, Time};
 
 use crate::db::worktable::orders::OrderRowView;
 use crate::execution::PlaceBatchOrders;
 use crate::strategy::broadcast::AsyncBroadcaster;
 use crate::strategy::instrument::convert_asset_to_instrument;
+use crate::strategy::instrument_refresh::DynamicInstrumentManager;
 use crate::strategy::strategy_two::STRATEGY_ID;
 use crate::strategy::strategy_two_and_three::capture_event::CaptureCommon;
 use crate::strategy::strategy_two_and_three::constants::ORDERS_TYPE;
@@ -31,7 +32,7 @@ use crate::strategy::{StrategyStatus, StrategyStatusMap};
 pub struct Strategy2OrderPlacement {
     pub rx: AsyncReceiver<StrategyTwoAndThreeEvent>,
     pub capture_common: Arc<CaptureCommon>,
-    pub instruments: SharedInstrumentManager,
+    pub instruments: Arc<DynamicInstrumentManager>,
     pub table_ledger: Table<SharedSledStorage, DbRowLedger>,
     pub strategy_id: StrategyId,
     pub strategy_status: Arc<StrategyStatusMap>,
@@ -44,15 +45,15 @@ impl Strategy2OrderPlacement {
         event: &DbRowBestBidAskAcrossExchangesAndPosition,
     ) -> Result<Option<PlaceBatchOrders>> {
         let asset = event.asset();
-        let symbol1 =
-            convert_asset_to_instrument(&self.instruments, Exchange::BinanceFutures, &asset).with_context(|| {
+        let symbol1 = convert_asset_to_instrument(&self.instruments.load(), Exchange::BinanceFutures, &asset)
+            .with_context(|| {
                 CustomError::new(
                     EnumErrorCode::NotFound,
                     format!("symbol not found for {} {}", Exchange::BinanceFutures, asset),
                 )
             })?;
-        let symbol2 =
-            convert_asset_to_instrument(&self.instruments, Exchange::Hyperliquid, &asset).with_context(|| {
+        let symbol2 = convert_asset_to_instrument(&self.instruments.load(), Exchange::Hyperliquid, &asset)
+            .with_context(|| {
                 CustomError::new(
                     EnumErrorCode::NotFound,
                     format!("symbol not found for {} {}", Exchange::Hyperliquid, asset),
@@ -152,7 +153,7 @@ impl Strategy2OrderPlacement {
     ) -> Result<Vec<RequestPlaceOrder>> {
         let asset = row.asset();
         let exchange1 = Exchange::BinanceFutures;
-        let symbol1 = convert_asset_to_instrument(&self.instruments, exchange1, &asset).with_context(|| {
+        let symbol1 = convert_asset_to_instrument(&self.instruments.load(), exchange1, &asset).with_context(|| {
             CustomError::new(
                 EnumErrorCode::NotFound,
                 format!("symbol not found for {} {}", exchange1, asset),
@@ -185,12 +186,13 @@ impl Strategy2OrderPlacement {
         let leg2;
         if let Some(open_order_2) = open_order_2.clone() {
             let exchange2 = Exchange::Hyperliquid;
-            let symbol2 = convert_asset_to_instrument(&self.instruments, exchange2, &asset).with_context(|| {
-                CustomError::new(
-                    EnumErrorCode::NotFound,
-                    format!("symbol not found for {} {}", exchange2, asset),
-                )
-            })?;
+            let symbol2 =
+                convert_asset_to_instrument(&self.instruments.load(), exchange2, &asset).with_context(|| {
+                    CustomError::new(
+                        EnumErrorCode::NotFound,
+                        format!("symbol not found for {} {}", exchange2, asset),
+                    )
+                })?;
             leg2 = Some(RequestPlaceOrder {
                 instrument: symbol2.code_symbol.clone(),
                 order_lid: gen_local_id(),
@@ -234,7 +236,7 @@ impl Strategy2OrderPlacement {
     async fn handle_single_sided_event(&mut self, event: DbRowBestBidAskAcrossExchangesAndPosition) -> Result<()> {
         let exchange = event.close_exchange();
         let asset = event.asset();
-        let symbol = convert_asset_to_instrument(&self.instruments, exchange, &asset).with_context(|| {
+        let symbol = convert_asset_to_instrument(&self.instruments.load(), exchange, &asset).with_context(|| {
             CustomError::new(
                 EnumErrorCode::NotFound,
                 format!("symbol not found for {} {}", exchange, event.asset()),
@@ -16,16 +16,22 @@ use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 use tokio::time::Interval;
 use tracing::info;
-use trading_model::{Asset, Exchange, SharedInstrumentManager, Side, Symbol};
+use trading_exchange::model::AccountId;
+use trading_model::{Asset, Exchange, Side, Symbol};
 
 use crate::balance_manager::BalanceManager;
+use crate::db::gluesql::schema::common::StrategyId;
 use crate::db::gluesql::schema::DbRowSymbolFlag;
 use crate::db::worktable::position_manager::PositionManager;
-use crate::execution::PlaceBatchOrders;
+use crate::execution::{ExecutionAccounts, PlaceBatchOrders};
+use crate::metrics::strategy_signals;
+use crate::signals::executable_price::ExecutablePriceModel;
+use crate::signals::fair_price::FairPriceMap;
 use crate::signals::price_spread::{DbRowSignalBestBidAskAcrossExchanges, SpreadMeanTable};
 use crate::strategy::broadcast::AsyncBroadcaster;
 use crate::strategy::data_factory::LastPriceMap;
 use crate::strategy::instrument::convert_asset_to_instrument;
+use crate::strategy::instrument_refresh::DynamicInstrumentManager;
 use crate::strategy::strategy_two_and_three::capture_event::CaptureCommon;
 use crate::strategy::strategy_two_and_three::constants::*;
 use crate::strategy::strategy_two_and_three::spread::{PriceElements, SpreadQuoter, SpreadState};
@@ -160,15 +166,22 @@ pub struct BestBidAskAcrossExchangesAndPositionEventGenerator {
     pub rx: AsyncReceiver<DbRowSignalBestBidAskAcrossExchanges>,
     pub positions: Arc<RwLock<PositionManager>>,
     pub balance_manager: BalanceManager,
+    /// positions and balances are those of the accounts the strategy trades on
+    pub strategy_id: StrategyId,
+    pub accounts: Arc<ExecutionAccounts>,
     pub table: Table<SharedMemoryStorage, DbRowBestBidAskAcrossExchangesAndPosition>,
     pub tx: AsyncBroadcaster<StrategyTwoAndThreeEvent>,
     pub cooldown: CooldownMap,
     pub mean_spread: SpreadMeanTable,
     pub warn_manager: WarnManager,
     pub spread: Option<SpreadQuoter>,
-    pub instruments: SharedInstrumentManager,
+    pub instruments: Arc<DynamicInstrumentManager>,
     pub common: Arc<CaptureCommon>,
     pub price_map: Arc<LastPriceMap>,
+    /// prices net of depth and fees for `MAX_SIZE_NOTIONAL`, used for the spreads instead of touch-to-touch
+    pub executable: ExecutablePriceModel,
+    /// the hyper spreads are measured against the fair price, nothing is opened without a recent one
+    pub fair_price_map: Arc<FairPriceMap>,
 
     pub symbol_flags_interval: Interval,
     pub symbol_flags: Table<SharedSledStorage, DbRowSymbolFlag>,
@@ -185,8 +198,29 @@ impl BestBidAskAcrossExchangesAndPositionEventGenerator {
         self.symbol_flags_cache = flags.into_iter().map(|flag| (flag.asset(), flag.flag)).collect();
         Ok(())
     }
+    fn account(&self, exchange: Exchange) -> AccountId {
+        self.accounts.resolve(self.strategy_id, exchange)
+    }
     pub async fn get_positions(&self, asset: Asset) -> Result<(f64, f64)> {
-        get_positions(&self.positions, &self.instruments, &asset).await
+        get_positions(
+            &self.positions,
+            &self.instruments.load(),
+            &asset,
+            self.account(Exchange::Hyperliquid),
+            self.account(Exchange::BinanceFutures),
+        )
+        .await
+    }
+    /// sell the hyper bid above the fair price, buy the hyper ask below it. false without a recent fair price
+    fn fair_price_agrees(&self, signal: &DbRowSignalBestBidAskAcrossExchanges, hyper_side: Side) -> bool {
+        let Some(fair) = self.fair_price_map.fresh(&signal.asset, signal.datetime) else {
+            return false;
+        };
+        let spread = match hyper_side {
+            Side::Sell => signal.spread_sell_hyper_fair(fair),
+            _ => signal.spread_buy_hyper_fair(fair),
+        };
+        spread >= 0.0
     }
     pub async fn emit_limit_market_order(&mut self, signal: DbRowSignalBestBidAskAcrossExchanges) -> Result<()> {
         let asset = signal.asset.clone();
@@ -226,10 +260,17 @@ impl BestBidAskAcrossExchangesAndPositionEventGenerator {
         let Some(mean) = self.mean_spread.get_mean_spread(signal.asset.clone()) else {
             return Ok(());
         };
-        let spread_sell_hyper = signal.spread_sell_hyper();
+        let Some(executable) = self.executable.net_of_depth(&signal) else {
+            return Ok(());
+        };
+        // the hyper quotes against the fair price, on the basis of the mean spreads
+        let Some(fair) = self.fair_price_map.fresh(&asset, signal.datetime) else {
+            return Ok(());
+        };
+        let spread_sell_hyper = executable.spread_sell_hyper_fair(fair);
         let sell_hyper = spread_sell_hyper > mean.spread_sell_1 + SPREAD_THRESHOLD_OPEN_OFFSET;
 
-        let spread_buy_hyper = signal.spread_buy_hyper();
+        let spread_buy_hyper = executable.spread_buy_hyper_fair(fair);
         let buy_hyper = spread_buy_hyper > mean.spread_buy_1 + SPREAD_THRESHOLD_OPEN_OFFSET;
 
         let hp_side = if sell_hyper { Side::Sell } else { Side::Buy };
@@ -240,15 +281,15 @@ impl BestBidAskAcrossExchangesAndPositionEventGenerator {
         }
 
         // TODO: support reversal of two exchanges
-        let symbol1 =
-            convert_asset_to_instrument(&self.instruments, Exchange::BinanceFutures, &asset).with_context(|| {
+        let symbol1 = convert_asset_to_instrument(&self.instruments.load(), Exchange::BinanceFutures, &asset)
+            .with_context(|| {
                 CustomError::new(
                     EnumErrorCode::NotFound,
                     format!("symbol not found for {} {}", Exchange::BinanceFutures, asset),
                 )
             })?;
-        let symbol2 =
-            convert_asset_to_instrument(&self.instruments, Exchange::Hyperliquid, &asset).with_context(|| {
+        let symbol2 = convert_asset_to_instrument(&self.instruments.load(), Exchange::Hyperliquid, &asset)
+            .with_context(|| {
                 CustomError::new(
                     EnumErrorCode::NotFound,
                     format!("symbol not found for {} {}", Exchange::Hyperliquid, asset),
@@ -261,7 +302,7 @@ impl BestBidAskAcrossExchangesAndPositionEventGenerator {
         }
         let balance = self
             .balance_manager
-            .get_balance(Exchange::Hyperliquid)
+            .get_strategy_balance(self.strategy_id, Exchange::Hyperliquid)
             .await?
             .amount_usd;
         if opportunity_size * signal.binance_ask_price > balance {
@@ -297,6 +338,7 @@ impl BestBidAskAcrossExchangesAndPositionEventGenerator {
         if let Err(ok) = self.table.insert(event.clone()).await {
             self.warn_manager.warn(&format!("insert failed: {:?}", ok));
         }
+        strategy_signals(self.strategy_id as _).inc();
         if let Err(ok) = self.tx.broadcast(StrategyTwoAndThreeEvent::OpenHedged(event)) {
             self.warn_manager.warn(&format!("broadcast failed: {:?}", ok));
         }
@@ -307,19 +349,22 @@ impl BestBidAskAcrossExchangesAndPositionEventGenerator {
         let (hl_balance_coin, ba_balance_coin) = self.get_positions(asset.clone()).await?;
 
         let mut state = SpreadState::Idle;
+        let Some(executable) = self.executable.net_of_depth(&signal) else {
+            return Ok(());
+        };
 
         let (ba_position_target, hl_position_target) = self.spread.as_mut().unwrap().quote_spread(
             &asset,
             ba_balance_coin * signal.binance_ask_price,
             hl_balance_coin * signal.hyper_ask_price,
             &PriceElements {
-                best_bid: signal.binance_bid_price,
-                best_ask: signal.binance_ask_price,
+                best_bid: executable.binance_bid_price,
+                best_ask: executable.binance_ask_price,
                 mid_price: (signal.binance_bid_price + signal.binance_ask_price) / 2.0,
             },
             &PriceElements {
-                best_bid: signal.hyper_bid_price,
-                best_ask: signal.hyper_ask_price,
+                best_bid: executable.hyper_bid_price,
+                best_ask: executable.hyper_ask_price,
                 mid_price: (signal.hyper_bid_price + signal.hyper_ask_price) / 2.0,
             },
             &mut state,
@@ -340,15 +385,15 @@ impl BestBidAskAcrossExchangesAndPositionEventGenerator {
         }
 
         // TODO: support reversal of two exchanges
-        let symbol1 =
-            convert_asset_to_instrument(&self.instruments, Exchange::BinanceFutures, &asset).with_context(|| {
+        let symbol1 = convert_asset_to_instrument(&self.instruments.load(), Exchange::BinanceFutures, &asset)
+            .with_context(|| {
                 CustomError::new(
                     EnumErrorCode::NotFound,
                     format!("symbol not found for {} {}", Exchange::BinanceFutures, asset),
                 )
             })?;
-        let symbol2 =
-            convert_asset_to_instrument(&self.instruments, Exchange::Hyperliquid, &asset).with_context(|| {
+        let symbol2 = convert_asset_to_instrument(&self.instruments.load(), Exchange::Hyperliquid, &asset)
+            .with_context(|| {
                 CustomError::new(
                     EnumErrorCode::NotFound,
                     format!("symbol not found for {} {}", Exchange::Hyperliquid, asset),
@@ -393,7 +438,7 @@ impl BestBidAskAcrossExchangesAndPositionEventGenerator {
                 }
                 let balance = self
                     .balance_manager
-                    .get_balance(Exchange::Hyperliquid)
+                    .get_strategy_balance(self.strategy_id, Exchange::Hyperliquid)
                     .await?
                     .amount_usd;
                 if opportunity_size * signal.binance_ask_price > balance {
@@ -414,6 +459,9 @@ impl BestBidAskAcrossExchangesAndPositionEventGenerator {
                 } else {
                     Side::Sell
                 };
+                if !self.fair_price_agrees(&signal, order_hl_side) {
+                    return Ok(());
+                }
             }
             SpreadState::CloseLongX | SpreadState::CloseShortX => {
                 if (hl_balance_coin.abs() - opportunity_size) * signal.binance_ask_price < MIN_SIZE_NOTIONAL {
@@ -492,6 +540,7 @@ impl BestBidAskAcrossExchangesAndPositionEventGenerator {
         if let Err(ok) = self.table.insert(event.clone()).await {
             self.warn_manager.warn(&format!("insert failed: {:?}", ok));
         }
+        strategy_signals(self.strategy_id as _).inc();
         if let Err(ok) = self.tx.broadcast(StrategyTwoAndThreeEvent::OpenHedged(event)) {
             self.warn_manager.warn(&format!("broadcast failed: {:?}", ok));
         }
@@ -506,8 +555,11 @@ impl BestBidAskAcrossExchangesAndPositionEventGenerator {
 
         let (hl_balance_coin, ba_balance_coin) = self.get_positions(asset.clone()).await?;
         // First: market_spread >= SPREAD_THRESHOLD – SPREAD_TOLERANCE (e.g., 15bps – 5bps).
-        let spread_sell_hyper = signal.spread_sell_hyper();
-        let spread_buy_hyper = signal.spread_buy_hyper();
+        let Some(executable) = self.executable.net_of_depth(&signal) else {
+            return Ok(());
+        };
+        let spread_sell_hyper = executable.spread_sell_hyper();
+        let spread_buy_hyper = executable.spread_buy_hyper();
 
         if spread_sell_hyper > SPREAD_THRESHOLD_CLOSE || spread_buy_hyper > SPREAD_THRESHOLD_CLOSE {
             return Ok(());
@@ -552,6 +604,7 @@ impl BestBidAskAcrossExchangesAndPositionEventGenerator {
         if let Err(ok) = self.table.insert(event.clone()).await {
             self.warn_manager.warn(&format!("insert failed: {:?}", ok));
         }
+        strategy_signals(self.strategy_id as _).inc();
         if let Err(ok) = self.tx.broadcast(StrategyTwoAndThreeEvent::CloseHedged(event)) {
             self.warn_manager.warn(&format!("broadcast failed: {:?}", ok));
         }
@@ -603,6 +656,7 @@ impl BestBidAskAcrossExchangesAndPositionEventGenerator {
             if let Err(ok) = self.table.insert(event.clone()).await {
                 self.warn_manager.warn(&format!("insert failed: {:?}", ok));
             }
+            strategy_signals(self.strategy_id as _).inc();
             if let Err(ok) = self.tx.broadcast(StrategyTwoAndThreeEvent::CloseSingleSided(event)) {
                 self.warn_manager.warn(&format!("broadcast failed: {:?}", ok));
             }
@@ -613,11 +667,13 @@ impl BestBidAskAcrossExchangesAndPositionEventGenerator {
         let positions = self.positions.read().await;
         let hl_count = positions.count_positions_advanced(
             Exchange::Hyperliquid,
+            self.account(Exchange::Hyperliquid),
             &self.price_map,
             POSITION_COUNT_THRESHOLD_NOTIONAL_SIZE,
         );
         let ba_count = positions.count_positions_advanced(
             Exchange::BinanceFutures,
+            self.account(Exchange::BinanceFutures),
             &self.price_map,
             POSITION_COUNT_THRESHOLD_NOTIONAL_SIZE,
         );
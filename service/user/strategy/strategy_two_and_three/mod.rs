@@ -5,6 +5,7 @@ use eyre::ContextCompat;
 use eyre::Result;
 use std::collections::HashMap;
 use tokio::sync::RwLock;
+use trading_exchange::model::AccountId;
 use trading_model::{Asset, Exchange, InstrumentManager};
 
 pub mod capture_event;
@@ -37,24 +38,27 @@ pub fn try_cooldown(cooldown: &mut CooldownMap, asset: &Asset, now: i64) -> bool
     true
 }
 
+/// positions of the asset on the hyperliquid and binance accounts
 pub async fn get_positions(
     positions: &RwLock<PositionManager>,
     manager: &InstrumentManager,
     asset: &Asset,
+    hl_account: AccountId,
+    ba_account: AccountId,
 ) -> Result<(f64, f64)> {
     let positions = positions.read().await;
     let symbol = convert_asset_to_instrument(manager, Exchange::Hyperliquid, asset)
         .with_context(|| format!("failed to convert asset to plain symbol: {}", asset))?;
     let hl_balance_coin = positions
         .positions
-        .get_position_by_symbol(Exchange::Hyperliquid, &symbol.symbol)
+        .get_position_by_symbol(Exchange::Hyperliquid, hl_account, &symbol.symbol)
         .map(|x| x.size())
         .unwrap_or_default();
     let symbol = convert_asset_to_instrument(manager, Exchange::BinanceFutures, asset)
         .with_context(|| format!("failed to convert asset to plain symbol: {}", asset))?;
     let ba_balance_coin = positions
         .positions
-        .get_position_by_symbol(Exchange::BinanceFutures, &symbol.symbol)
+        .get_position_by_symbol(Exchange::BinanceFutures, ba_account, &symbol.symbol)
         .map(|x| x.size())
         .unwrap_or_default();
     Ok((hl_balance_coin, ba_balance_coin))
@@ -1,4 +1,5 @@
 use crate::db::gluesql::schema::DbRowSymbolFlag;
+use crate::metrics::strategy_signals;
 use crate::signals::price_difference::{
     DbRowSignalPriceDifference, HyperMarkCrossesBidSignalConverter, SignalCooldownFilter,
 };
@@ -106,6 +107,7 @@ impl<T: GStore + GStoreMut + Clone> HyperMarkCrossesBidEventFactory<T> {
                         warn!("failed to insert signal to database: {e}");
                     }
 
+                    strategy_signals(0).inc();
                     if let Err(e) = self.tx.try_send(signal)  {
                         warn!("failed to send signal to signal handler: {e}");
                      };
@@ -25,6 +25,7 @@ async fn test_manual_trading_place_order() -> Result<()> {
             price: 1.0, // play safe
             size: 0.0001,
             local_id: gen_local_id(),
+            account: None,
         })
         .await?;
     println!("resp: {:?}", resp);